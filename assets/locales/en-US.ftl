@@ -0,0 +1,440 @@
+# Strings of the overlay GUI and of the HTML controls of the web build, in Fluent syntax
+# (https://projectfluent.org/). Messages missing from other catalogues fall back to these.
+
+## Menu bar
+
+menu-file = File
+menu-open-model = Open model…
+menu-open-skybox = Open skybox…
+menu-open-terrain = Open terrain…
+menu-add-decal = Add decal…
+menu-save-scene = Save scene…
+menu-export-model = Export model…
+menu-screenshot = Screenshot
+menu-quit = Quit
+menu-mesh = Mesh
+menu-weld-vertices = Weld vertices
+menu-weld-distance = Largest distance of merged vertices
+menu-remove-degenerates = Remove degenerate triangles
+menu-fill-holes = Fill holes
+menu-max-hole-edges = Most edges around filled holes
+menu-unify-winding = Unify winding
+menu-view = View
+menu-properties = Properties
+menu-opengl-info = OpenGL info
+menu-shader-editor = Shader editor
+menu-model-shader-playground = Model shader playground
+menu-uv-layout = UV layout
+menu-profiler = Profiler
+menu-fullscreen = Fullscreen
+menu-exit-fullscreen = Exit fullscreen
+menu-help = Help
+menu-controls = Controls
+menu-about = About
+
+## Windows
+
+about-title = 3D renderer in Rust
+about-version = Version { $version }
+about-author = Written by { $author }
+about-description = Real-time 3D renderer with OpenGL 4 and WebGL2.
+about-source-code = Source code
+
+controls-movement = Movement: W, A, S, D
+controls-mouse-look = Mouse look: Right-click and drag
+controls-move-light = Move light: Left-click and drag light gizmo
+controls-ascend = Ascend: Spacebar (jump when walking)
+controls-descend = Descend: C
+controls-roll = Roll: Q, E
+controls-fullscreen = Fullscreen: F11
+controls-screenshot = Screenshot: F12
+controls-quit = Quit: Esc
+
+gl-info-vendor = Vendor
+gl-info-renderer = Renderer
+gl-info-version = Version
+gl-info-glsl-version = GLSL version
+gl-info-max-texture-size = Max texture size
+gl-info-max-samples = Max samples
+gl-info-copy = Copy to clipboard
+gl-info-extensions = Extensions ({ $count })
+
+shader-name = Name
+shader-open = Open…
+shader-compile = Compile
+shader-reset = Reset to template
+shader-compiled = Compiled '{ $name }', assign it to materials in the Material panel
+vertex-shader = Vertex shader
+fragment-shader = Fragment shader
+shader-apply = Apply
+shader-revert = Revert to built-in
+shader-applied = Applied to every material without a custom shader
+
+uv-layout-none = Model has no texture coordinates
+uv-layout-clean = { $triangles } triangles without overlap
+uv-layout-overlapping = { $overlapping } of { $triangles } triangles overlap
+
+prompt-open-model = Open model
+prompt-open-model-hint = Path of OBJ file
+prompt-open-skybox = Open skybox
+prompt-open-skybox-hint = Directory of right, left, top, bottom, front, back faces
+prompt-open-terrain = Open terrain
+prompt-open-terrain-hint = Path of PNG or EXR heightmap
+prompt-add-decal = Add decal
+prompt-add-decal-hint = Path of image, e.g. PNG with transparency
+prompt-save-scene = Save scene
+prompt-save-scene-hint = Path of RON file
+prompt-export-model = Export model
+prompt-export-model-hint = Path of OBJ or GLB file
+prompt-ok = OK
+prompt-cancel = Cancel
+
+error-title = Error
+error-continue = Continue without failed assets
+error-quit = Quit
+
+## Panels
+
+panel-scene = Scene
+panel-hierarchy = Hierarchy
+panel-material = Material
+panel-lighting = Lighting
+panel-renderer = Renderer
+panel-post-processing = Post-processing
+panel-time = Time
+panel-timeline = Timeline
+panel-stats = Stats
+
+camera = Camera
+camera-rotation = Yaw: { $yaw }° Pitch: { $pitch }° Roll: { $roll }°
+field-of-view = Field of view (FOV)
+camera-mode = Camera mode
+camera-mode-fly = Fly
+camera-mode-six-dof = Six degrees of freedom (spaceship)
+camera-mode-walk = Walk
+raw-mouse-input = Raw mouse input
+mouse-smoothing = Mouse smoothing
+mouse-acceleration = Mouse acceleration
+camera-bookmarks = Bookmarks
+camera-bookmark = Bookmark { $index }
+add-camera-bookmark = Bookmark current view
+remove-camera-bookmark = Remove
+go-to-camera-bookmark = Fly to this view
+camera-transition-duration = Transition duration
+camera-transition-easing = Transition easing
+easing-linear = Linear
+easing-smoothstep = Smoothstep
+easing-cubic = Cubic
+skybox = Skybox
+labels = 3D labels
+background-color = Background color
+
+ground-plane = Ground plane
+stencil-mirror = Stencil mirror
+stencil-mirror-hint = Vertical mirror behind the models, masked with the stencil buffer
+terrain = Terrain
+reflective-plane = Reflective ground plane
+ground-plane-height = Height
+reflectivity = Reflectivity
+reflection-blur = Blur
+
+model = Model
+select-model = Select model
+wireframe-mode = Wireframe mode
+overdraw-heatmap = Overdraw heatmap
+overdraw-heatmap-hint = Color pixels by how many fragments are shaded for them, from blue (one) to red (16 or more)
+bounding-box = Bounding box
+bounding-sphere = Bounding sphere
+explode-parts = Explode parts
+explode-parts-hint = Move parts of models assembled from several objects apart from the model center. Parts of the selected model: { $count }
+level-of-detail = Level of detail
+lod-selected-model = Selected model: LOD { $level }
+lod-entities = LOD { $level }: { $count } objects
+lod-triangles = Triangles drawn: { $count }
+occlusion-culling = Occlusion culling
+occlusion-culling-disabled = Enable occlusion culling in the Renderer panel
+multi-draw-indirect = Multi-draw indirect
+multi-draw-indirect-hint = Draw small meshes with one call per shader variant from a GPU command buffer
+multi-draw-indirect-unsupported = Requires OpenGL 4.3
+stereo-mode = Stereo output
+stereo-mode-off = Off
+stereo-mode-anaglyph = Anaglyph (red/cyan)
+stereo-mode-side-by-side = Side by side
+eye-separation = Eye separation
+comparison = A/B comparison
+comparison-enabled = Compare with snapshot
+comparison-enabled-hint = Draw a copy of the current settings and materials right of a draggable split line, while changes are shown left of it
+comparison-split = Split position
+comparison-swap = Swap sides
+comparison-swap-hint = Exchange settings and materials of the sides to edit the right side
+occlusion-queried = Tested objects: { $count }
+occlusion-occluded = Occluded objects: { $count }
+occlusion-culled-triangles = Triangles skipped: { $count }
+bvh = Bounding volume hierarchies
+bvh-model = { $name }: { $triangles } triangles, { $nodes } nodes, { $leaves } leaves (at most { $leaf_triangles } triangles), depth { $depth }, built in { $ms } ms
+bvh-none = Built on the first click into the viewport, which picks the object under cursor
+mesh-validation = Mesh validation
+mesh-validation-analyze = Analyze { $name }
+mesh-validation-non-manifold = Non-manifold edges
+mesh-validation-boundary = Open boundary edges
+mesh-validation-degenerate = Degenerate triangles
+mesh-validation-flipped = Flipped triangles
+mesh-validation-duplicate = Duplicate vertices
+mesh-validation-clean = No problems found
+mesh-validation-highlight = Highlight problems
+mesh-validation-highlight-hint = Draw problem edges in orange and outlines of degenerate and flipped triangles in magenta over the model
+terrain-tiles = Tiles drawn: { $drawn } / { $count }
+vertex-displacement = Vertex displacement (compute shader)
+vertex-displacement-unsupported = Requires OpenGL 4.3
+vertex-displacement-amplitude = Amplitude
+face-normals = Face normals (geometry shader)
+explode-distance = Exploded view
+explode-distance-hint = Push triangles apart along their normals with a geometry shader, flat shaded with the material color. Forward rendering only.
+geometry-shader-unsupported = Requires OpenGL 4.3
+back-face-culling = Back-face culling
+front-face-winding = Front face winding
+winding-counter-clockwise = Counter-clockwise
+winding-clockwise = Clockwise
+
+transform = Transform
+rotation-x = X rotation
+rotation-y = Y rotation
+rotation-z = Z rotation
+decals = Decals
+decal-volumes = Show projector frustums
+remove-decal = Remove
+decal-aspect-ratio = Aspect ratio
+decal-near = Near plane
+decal-far = Far plane
+decal-range-hint = Distance from the projector, surfaces outside of the range are not covered
+decal-opacity = Opacity
+decals-none = No decals, images are added from the File menu
+measurement = Measurement
+measure-mode = Measure mode
+measure-mode-hint = Click points on models instead of selecting them: two points measure a distance, three points an angle
+measure-unit-scale = Units per world unit
+measure-unit = Unit
+measure-no-points = Click a model to place the first point
+measure-distance = Distance: { $distance }
+measure-angle = Angle: { $angle }°
+measure-clear = Clear points
+inspector-mode = Inspector mode
+inspector-mode-hint = Highlight the triangle under the cursor with its normals, and list its vertices
+inspector-triangle = Triangle { $index }
+inspector-index = Index
+inspector-position = Position
+inspector-normal = Normal
+inspector-face-normal = Face normal: { $normal }
+inspector-flipped = Face winding disagrees with vertex normals
+
+hierarchy-empty = No objects in the scene
+hierarchy-visible = Show or hide
+hierarchy-duplicate = Duplicate
+hierarchy-delete = Delete
+hierarchy-add-instance = Place new instance
+hierarchy-rotation-from-selection = Rotation of the selected model is set in the Scene panel
+position = Position
+
+material-name = Name
+material-type = Material type
+material-type-phong = Phong
+material-type-reflective = Reflective
+material-type-refractive = Refractive
+refractive-index = Index of refraction
+diffuse = Diffuse
+specular = Specular
+vertex-colors = Vertex colors
+vertex-colors-hint = Use of per-vertex colors of meshes that have them, e.g. 3D scans loaded from OBJ or PLY files
+vertex-colors-ignore = Ignore
+vertex-colors-modulate = Modulate material color
+vertex-colors-replace = Replace material color
+material-shader = Shader
+material-shader-builtin = Built-in
+material-shader-hint = Custom shader from the shader editor replacing the material type. Deferred rendering always uses built-in shading.
+material-file = File
+material-save = Save
+material-load = Load
+material-copy-ron = Copy as RON
+parts = Parts ({ $count })
+part-material = Own material
+part-material-hint = Draw this part with its own material instead of the material of the object
+
+light-direction-x = Light direction X
+light-direction-y = Light direction Y
+light-direction-z = Light direction Z
+light-view = Light's-eye view
+light-gizmo = Light gizmo
+shadows = Shadows
+shadows-enabled = Enable shadows
+shadows-enabled-hint = Models cast shadows of the light onto each other from shadow maps. Forward rendering only.
+shadow-cascades = Cascades
+shadow-cascades-hint = Shadow maps the view is split into by distance. Nearer cascades cover less of the scene with more detail.
+shadow-resolution = Cascade { $cascade } resolution
+shadow-pcf-radius = Filter radius
+shadow-pcf-radius-hint = Shadow map texels averaged in each direction for soft shadow edges. 0 gives hard edges.
+shadow-distance = Shadow distance
+shadow-cascade-visualization = Visualize cascades
+shadow-cascade-visualization-hint = Tint surfaces by the cascade their shadow comes from: red, green, blue and yellow from near to far.
+point-lights = Point lights
+point-light = Point light { $index }
+add-point-light = Add point light
+remove-point-light = Remove
+point-lights-full = At most { $count } point lights are supported
+point-light-color = Color
+point-light-intensity = Intensity
+point-light-range = Range
+point-light-range-hint = Distance where the light fades out completely. Forward rendering only.
+point-light-shadows = Cast shadows
+point-light-shadows-hint = Models block the light in every direction using a cube shadow map rendered from the light.
+point-light-shadow-bias = Shadow bias
+point-light-shadow-bias-hint = Distance surfaces are moved towards the light when compared with the shadow map. Increase against shadow acne, decrease against light leaking behind casters.
+area-lights = Area lights
+area-light = Area light { $index }
+add-area-light = Add area light
+add-area-light-hint = Rectangle lighting the scene from its front side like a studio softbox. Forward rendering only.
+remove-area-light = Remove
+area-lights-full = At most { $count } area lights are supported
+area-light-width = Width
+area-light-height = Height
+area-light-color = Color
+area-light-intensity = Intensity
+area-light-intensity-hint = Brightness of the emitting surface. At 1, a light filling the whole view of a surface lights it as brightly as the directional light shining straight at it.
+baked-lighting = Baked lighting
+baked-lighting-enabled = Display baked lighting
+baked-lighting-enabled-hint = Draw models with their lightmaps instead of realtime ambient and diffuse lighting. Forward rendering only.
+baked-lighting-none = Bake lighting first
+lightmap-resolution = Lightmap resolution
+lightmap-samples = Indirect samples
+lightmap-samples-hint = Rays cast from each texel for light bounced off other models. More samples reduce noise.
+bake-lighting = Bake lighting
+bake-lighting-hint = Precompute direct and indirect lighting of the visible models into lightmaps in the background. Moving models or the light afterwards requires baking again.
+bake-lighting-running = Baking lighting, wait until it finishes
+lightmaps-baked = Lightmaps: { $count }, baked in { $ms } ms
+
+vsync = Vertical sync
+vsync-hint = Adaptive swaps late frames immediately instead of waiting for the next refresh. Falls back to On when unsupported.
+vsync-off = Off
+vsync-on = On
+vsync-adaptive = Adaptive
+vsync-half = Half refresh rate
+render-path = Render path
+render-path-forward = Forward
+render-path-deferred = Deferred
+anti-aliasing = Anti-aliasing
+anti-aliasing-off = Off
+anti-aliasing-multisample = Multisample (MSAA)
+anti-aliasing-temporal = Temporal (TAA)
+anti-aliasing-hint = MSAA requires forward rendering. TAA accumulates jittered frames and works with both render paths, but only in the window without stereo output.
+taa-sharpness = TAA sharpness
+taa-sharpness-hint = Detail of the current frame added back to the accumulated result. Higher values are sharper but can flicker.
+exposure = Exposure
+gamma = Gamma
+render-scale = Render scale
+auto-quality = Automatic quality
+auto-quality-hint = Lower render scale and skip reflections when frames are slower than the target frame rate. Targets above the refresh rate only lower quality with vertical sync.
+target-frame-rate = Target frame rate
+reduced-effects = Reflections skipped to keep the frame rate
+motion-blur = Motion blur
+motion-blur-hint = Blur pixels along their movement caused by the camera since the previous frame. Not applied to stereo output.
+shutter-angle = Shutter angle
+shutter-angle-hint = Part of the frame time the virtual shutter is open. 180° gives the usual look of films, larger angles blur more.
+post-effect-vignette = Vignette
+post-effect-film-grain = Film grain
+post-effect-chromatic-aberration = Chromatic aberration
+post-effect-earlier = Apply earlier
+post-effect-later = Apply later
+vignette-strength = Vignette strength
+film-grain-intensity = Grain intensity
+chromatic-aberration-strength = Aberration strength (px)
+post-effects-hint = Effects are applied from top to bottom, before exposure and gamma.
+interface = Interface
+gui-scale = GUI scale: { $percent }%
+theme = Theme
+theme-dark = Dark
+theme-light = Light
+language = Language
+
+pause = Pause
+time-scale = Time scale
+time-scale-hint = Speed of logic updates relative to real time, below 1 for slow motion
+step = Step
+step-hint = Run a single logic update while paused
+timeline-play = Play
+timeline-pause = Pause
+timeline-rewind = Rewind
+timeline-loop = Loop
+timeline-time = Time
+timeline-duration = Duration
+timeline-property = Property
+timeline-property-hint = Entity properties are listed once an entity is activated in the hierarchy
+keyframe-easing = Easing
+keyframe-easing-hint = Progress of the change from the previous keyframe towards the new one
+add-keyframe = Add keyframe
+add-keyframe-hint = Keyframe the current value of the property at the current time
+go-to-keyframe = Jump to this keyframe
+remove-keyframe = Remove
+animated-light-direction = Light direction
+animated-background-color = Background color
+animated-model-rotation = Model rotation
+animated-field-of-view = Field of view
+animated-exposure = Exposure
+animated-reflective-plane-height = Ground plane height
+animated-entity-position = { $name } position
+animated-entity-rotation = { $name } rotation
+export-directory = Export directory
+export-frame-rate = Frames per second
+export-animation = Export frames
+export-animation-hint = Render the timeline from its beginning into numbered PNG images of the directory
+modulation = Modulation
+modulation-no-sources = No sources, start with --audio to analyze audio input
+modulation-source = Source { $index }
+modulation-from = From
+modulation-to = To
+modulation-amount = Amount
+add-modulation = Add binding
+remove-modulation = Remove
+modulation-rotation-speed = Rotation speed (°/s)
+modulation-light-intensity = Light intensity
+modulation-exposure = Exposure (EV)
+modulation-part-explode = Part explosion
+
+frame-rate = { $fps } FPS, { $ms } ms/frame
+assets = Assets
+assets-meshes = Meshes: { $count } ({ $size })
+assets-skyboxes = Skyboxes: { $count } ({ $size })
+assets-evict-unused = Evict unused
+vram-usage = Estimated VRAM usage: { $size }
+resources = Resources
+resource-buffers = Buffers
+resource-textures = Textures
+resource-renderbuffers = Renderbuffers
+resource-vertex-arrays = Vertex arrays
+resource-framebuffers = Framebuffers
+resource-count = { $kind }: { $count } ({ $size })
+resource-lifetime = { $created } created, { $deleted } deleted
+resource-leak = ⚠ { $kind } keep growing, possible leak
+draws = Draw calls
+draw-calls = Draw calls per frame: { $count }
+draw-triangles = Triangles per frame: { $count }
+gl-state = GL state changes
+gl-state-changes = Changes per frame: { $count }
+gl-state-redundant = Redundant calls skipped: { $count }
+pipeline-statistics = Pipeline statistics
+pipeline-vertices = Vertices submitted: { $count }
+pipeline-fragments = Fragments shaded: { $count }
+pipeline-statistics-pending = Waiting for results
+pipeline-statistics-unsupported = Requires OpenGL 4.6 or ARB_pipeline_statistics_query
+
+## HTML controls of the web build
+
+html-help = Help
+html-properties = Properties
+html-overlay-gui = Enable overlay GUI
+html-view-in-xr = View in VR/AR
+html-exposure = Exposure (EV)
+html-ground-plane-height = Ground plane height
+html-reflection-blur = Reflection blur
+html-material-color = Material
+html-shadow-resolutions = Cascade resolutions
+html-post-effect-order = Post effect order
+html-shutter-angle = Shutter angle (°)
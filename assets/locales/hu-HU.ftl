@@ -0,0 +1,438 @@
+# Hungarian translation of en-US.ftl.
+
+## Menu bar
+
+menu-file = Fájl
+menu-open-model = Modell megnyitása…
+menu-open-skybox = Égbolt megnyitása…
+menu-open-terrain = Terep megnyitása…
+menu-add-decal = Matrica hozzáadása…
+menu-save-scene = Jelenet mentése…
+menu-export-model = Modell exportálása…
+menu-screenshot = Képernyőkép
+menu-quit = Kilépés
+menu-mesh = Háló
+menu-weld-vertices = Csúcspontok összevonása
+menu-weld-distance = Összevont csúcspontok legnagyobb távolsága
+menu-remove-degenerates = Elfajult háromszögek eltávolítása
+menu-fill-holes = Lyukak kitöltése
+menu-max-hole-edges = Kitöltött lyukak éleinek legnagyobb száma
+menu-unify-winding = Körüljárási irány egységesítése
+menu-view = Nézet
+menu-properties = Tulajdonságok
+menu-opengl-info = OpenGL-információk
+menu-shader-editor = Shaderszerkesztő
+menu-model-shader-playground = Modellshader-játszótér
+menu-uv-layout = UV-elrendezés
+menu-profiler = Profilozó
+menu-fullscreen = Teljes képernyő
+menu-exit-fullscreen = Kilépés a teljes képernyőből
+menu-help = Súgó
+menu-controls = Irányítás
+menu-about = Névjegy
+
+## Windows
+
+about-title = 3D renderelő Rustban
+about-version = Verzió: { $version }
+about-author = Készítette: { $author }
+about-description = Valós idejű 3D renderelő OpenGL 4 és WebGL2 alapokon.
+about-source-code = Forráskód
+
+controls-movement = Mozgás: W, A, S, D
+controls-mouse-look = Körbenézés: húzás jobb egérgombbal
+controls-move-light = Fény mozgatása: a fény jelölőjének húzása bal egérgombbal
+controls-ascend = Emelkedés: Szóköz (ugrás sétánál)
+controls-descend = Süllyedés: C
+controls-roll = Orsózás: Q, E
+controls-fullscreen = Teljes képernyő: F11
+controls-screenshot = Képernyőkép: F12
+controls-quit = Kilépés: Esc
+
+gl-info-vendor = Gyártó
+gl-info-renderer = Renderelő
+gl-info-version = Verzió
+gl-info-glsl-version = GLSL-verzió
+gl-info-max-texture-size = Legnagyobb textúraméret
+gl-info-max-samples = Legtöbb minta
+gl-info-copy = Másolás vágólapra
+gl-info-extensions = Kiterjesztések ({ $count })
+
+shader-name = Név
+shader-open = Megnyitás…
+shader-compile = Fordítás
+shader-reset = Visszaállítás a sablonra
+shader-compiled = '{ $name }' lefordítva, az Anyag panelen rendelhető anyagokhoz
+vertex-shader = Vertex shader
+fragment-shader = Fragment shader
+shader-apply = Alkalmazás
+shader-revert = Visszaállítás a beépítettre
+shader-applied = Alkalmazva minden egyéni shader nélküli anyagra
+
+uv-layout-none = Textúrakoordináták nélküli modell
+uv-layout-clean = { $triangles } háromszög, átfedés nélkül
+uv-layout-overlapping = { $triangles } háromszögből { $overlapping } átfedő
+
+prompt-open-model = Modell megnyitása
+prompt-open-model-hint = OBJ-fájl elérési útja
+prompt-open-skybox = Égbolt megnyitása
+prompt-open-skybox-hint = A right, left, top, bottom, front, back lapokat tartalmazó mappa
+prompt-open-terrain = Terep megnyitása
+prompt-open-terrain-hint = PNG- vagy EXR-magasságtérkép elérési útja
+prompt-add-decal = Matrica hozzáadása
+prompt-add-decal-hint = Kép elérési útja, pl. átlátszó PNG
+prompt-save-scene = Jelenet mentése
+prompt-save-scene-hint = RON-fájl elérési útja
+prompt-export-model = Modell exportálása
+prompt-export-model-hint = OBJ- vagy GLB-fájl elérési útja
+prompt-ok = OK
+prompt-cancel = Mégse
+
+error-title = Hiba
+error-continue = Folytatás a hibás fájlok nélkül
+error-quit = Kilépés
+
+## Panels
+
+panel-scene = Jelenet
+panel-hierarchy = Hierarchia
+panel-material = Anyag
+panel-lighting = Megvilágítás
+panel-renderer = Renderelő
+panel-post-processing = Utófeldolgozás
+panel-time = Idő
+panel-timeline = Idővonal
+panel-stats = Statisztika
+
+camera = Kamera
+camera-rotation = Elfordulás: { $yaw }° Dőlés: { $pitch }° Orsózás: { $roll }°
+field-of-view = Látószög (FOV)
+camera-mode = Kameramód
+camera-mode-fly = Repülés
+camera-mode-six-dof = Hat szabadsági fok (űrhajó)
+camera-mode-walk = Séta
+raw-mouse-input = Nyers egérbemenet
+mouse-smoothing = Egérsimítás
+mouse-acceleration = Egérgyorsítás
+camera-bookmarks = Könyvjelzők
+camera-bookmark = { $index }. könyvjelző
+add-camera-bookmark = Jelenlegi nézet mentése
+remove-camera-bookmark = Eltávolítás
+go-to-camera-bookmark = Repülés ehhez a nézethez
+camera-transition-duration = Átmenet időtartama
+camera-transition-easing = Átmenet görbéje
+easing-linear = Lineáris
+easing-smoothstep = Smoothstep
+easing-cubic = Köbös
+skybox = Égbolt
+labels = 3D feliratok
+background-color = Háttérszín
+
+ground-plane = Talajsík
+stencil-mirror = Stencilmaszkos tükör
+stencil-mirror-hint = Függőleges tükör a modellek mögött, stencilpufferrel maszkolva
+terrain = Terep
+reflective-plane = Tükröző talajsík
+ground-plane-height = Magasság
+reflectivity = Tükrözés mértéke
+reflection-blur = Elmosás
+
+model = Modell
+select-model = Modell kiválasztása
+wireframe-mode = Drótváz mód
+overdraw-heatmap = Túlrajzolási hőtérkép
+overdraw-heatmap-hint = Képpontok színezése az értük árnyalt fragmensek száma szerint, kéktől (egy) pirosig (16 vagy több)
+bounding-box = Befoglaló doboz
+bounding-sphere = Befoglaló gömb
+explode-parts = Részek szétszedése
+explode-parts-hint = Több objektumból összeállított modellek részeinek eltávolítása a modell középpontjától. A kiválasztott modell részei: { $count }
+level-of-detail = Részletességi szint
+lod-selected-model = Kiválasztott modell: LOD { $level }
+lod-entities = LOD { $level }: { $count } objektum
+lod-triangles = Kirajzolt háromszögek: { $count }
+occlusion-culling = Takarásos kivágás
+occlusion-culling-disabled = A takarásos kivágás a Renderelő panelen kapcsolható be
+multi-draw-indirect = Többszörös indirekt rajzolás
+multi-draw-indirect-hint = Kis hálók rajzolása árnyaló-változatonként egy hívással, GPU-n tárolt parancspufferből
+multi-draw-indirect-unsupported = OpenGL 4.3 szükséges
+stereo-mode = Sztereó kimenet
+stereo-mode-off = Ki
+stereo-mode-anaglyph = Anaglif (vörös/cián)
+stereo-mode-side-by-side = Egymás mellett
+eye-separation = Szemtávolság
+comparison-enabled = Összehasonlítás pillanatképpel
+comparison-enabled-hint = A jelenlegi beállítások és anyagok másolatának rajzolása egy húzható elválasztó vonaltól jobbra, a változtatások megjelenítése tőle balra
+comparison-split = Elválasztás helye
+comparison-swap = Oldalak cseréje
+comparison-swap-hint = Az oldalak beállításainak és anyagainak cseréje a jobb oldal szerkesztéséhez
+occlusion-queried = Vizsgált objektumok: { $count }
+occlusion-occluded = Takart objektumok: { $count }
+occlusion-culled-triangles = Kihagyott háromszögek: { $count }
+bvh = Befoglaló térfogat-hierarchiák
+bvh-model = { $name }: { $triangles } háromszög, { $nodes } csomópont, { $leaves } levél (legfeljebb { $leaf_triangles } háromszöggel), mélység { $depth }, felépítés { $ms } ms
+bvh-none = Az első kattintáskor épül fel a nézetben, ami kijelöli a kurzor alatti objektumot
+mesh-validation = Hálóellenőrzés
+mesh-validation-analyze = { $name } elemzése
+mesh-validation-non-manifold = Nem sokaság élek
+mesh-validation-boundary = Nyitott határélek
+mesh-validation-degenerate = Elfajult háromszögek
+mesh-validation-flipped = Megfordított háromszögek
+mesh-validation-duplicate = Ismétlődő csúcsok
+mesh-validation-clean = Nincs talált hiba
+mesh-validation-highlight = Hibák kiemelése
+mesh-validation-highlight-hint = Hibás élek narancssárga, elfajult és megfordított háromszögek körvonalának bíborszínű rajzolása a modellre
+terrain-tiles = Kirajzolt csempék: { $drawn } / { $count }
+vertex-displacement = Csúcspontok eltolása (compute shader)
+vertex-displacement-unsupported = OpenGL 4.3 szükséges
+vertex-displacement-amplitude = Amplitúdó
+face-normals = Lapnormálisok (geometry shader)
+explode-distance = Robbantott nézet
+explode-distance-hint = A háromszögek széttolása a normálisuk mentén geometry shaderrel, az anyag színével egyszínűen árnyalva. Csak forward renderelésnél.
+geometry-shader-unsupported = OpenGL 4.3 szükséges
+back-face-culling = Hátsó lapok elhagyása
+front-face-winding = Elülső lap körüljárása
+winding-counter-clockwise = Óramutatóval ellentétes
+winding-clockwise = Óramutató járásával megegyező
+
+transform = Transzformáció
+rotation-x = X forgatás
+rotation-y = Y forgatás
+rotation-z = Z forgatás
+decals = Matricák
+decal-volumes = Vetítési gúlák megjelenítése
+remove-decal = Eltávolítás
+decal-aspect-ratio = Képarány
+decal-near = Közeli sík
+decal-far = Távoli sík
+decal-range-hint = Távolság a vetítőtől, a tartományon kívüli felületek fedetlenek
+decal-opacity = Átlátszatlanság
+decals-none = Nincs matrica, képek hozzáadása a Fájl menüből
+measurement = Mérés
+measure-mode = Mérési mód
+measure-mode-hint = Pontok kijelölése kattintással a modelleken kiválasztás helyett: két pont távolság, három pont szög mérése
+measure-unit-scale = Mértékegység világegységenként
+measure-unit = Mértékegység
+measure-no-points = Az első pont elhelyezése kattintással egy modellen
+measure-distance = Távolság: { $distance }
+measure-angle = Szög: { $angle }°
+measure-clear = Pontok törlése
+inspector-mode = Vizsgáló mód
+inspector-mode-hint = A kurzor alatti háromszög kiemelése normálisaival, csúcsainak listázása
+inspector-triangle = { $index }. háromszög
+inspector-index = Index
+inspector-position = Pozíció
+inspector-normal = Normális
+inspector-face-normal = Lapnormális: { $normal }
+inspector-flipped = A lap körüljárási iránya ellentmond a csúcsnormálisoknak
+
+hierarchy-empty = Nincs objektum a jelenetben
+hierarchy-visible = Megjelenítés vagy elrejtés
+hierarchy-duplicate = Másolat készítése
+hierarchy-delete = Törlés
+hierarchy-add-instance = Új példány elhelyezése
+hierarchy-rotation-from-selection = A kiválasztott modell forgatása a Jelenet panelen állítható
+position = Pozíció
+
+material-name = Név
+material-type = Anyagtípus
+material-type-phong = Phong
+material-type-reflective = Tükröző
+material-type-refractive = Fénytörő
+refractive-index = Törésmutató
+diffuse = Diffúz
+specular = Spekuláris
+vertex-colors = Csúcspontszínek
+vertex-colors-hint = A csúcspontonkénti színek használata az ilyen színekkel rendelkező hálóknál, pl. OBJ vagy PLY fájlból betöltött 3D szkenneknél
+vertex-colors-ignore = Figyelmen kívül hagyás
+vertex-colors-modulate = Anyagszín módosítása
+vertex-colors-replace = Anyagszín lecserélése
+material-shader = Shader
+material-shader-builtin = Beépített
+material-shader-hint = A shaderszerkesztőben írt shader az anyagtípus helyett. Deferred renderelésnél mindig a beépített árnyalás működik.
+material-file = Fájl
+material-save = Mentés
+material-load = Betöltés
+material-copy-ron = Másolás RON-ként
+parts = Részek ({ $count })
+part-material = Saját anyag
+part-material-hint = A rész rajzolása saját anyaggal az objektum anyaga helyett
+
+light-direction-x = Fény iránya X
+light-direction-y = Fény iránya Y
+light-direction-z = Fény iránya Z
+light-view = Nézet a fény felől
+light-gizmo = Fény jelölője
+shadows = Árnyékok
+shadows-enabled = Árnyékok bekapcsolása
+shadows-enabled-hint = A modellek árnyéktérképek alapján vetnek árnyékot egymásra. Csak forward renderelésnél.
+shadow-cascades = Kaszkádok
+shadow-cascades-hint = Ennyi árnyéktérképre oszlik a nézet távolság szerint. A közelebbi kaszkádok kisebb területet fednek le részletesebben.
+shadow-resolution = { $cascade }. kaszkád felbontása
+shadow-pcf-radius = Szűrés sugara
+shadow-pcf-radius-hint = Ennyi árnyéktérkép-texel átlaga minden irányban a lágy árnyékszélekhez. 0 esetén éles a szél.
+shadow-distance = Árnyékok távolsága
+shadow-cascade-visualization = Kaszkádok megjelenítése
+shadow-cascade-visualization-hint = A felületek színezése aszerint, melyik kaszkádból származik az árnyékuk: közelről távolra piros, zöld, kék és sárga.
+point-lights = Pontfények
+point-light = { $index }. pontfény
+add-point-light = Pontfény hozzáadása
+remove-point-light = Eltávolítás
+point-lights-full = Legfeljebb { $count } pontfény támogatott
+point-light-color = Szín
+point-light-intensity = Erősség
+point-light-range = Hatótáv
+point-light-range-hint = Ekkora távolságban halványul el teljesen a fény. Csak forward renderelésnél.
+point-light-shadows = Árnyékvetés
+point-light-shadows-hint = A modellek minden irányban kitakarják a fényt a fény felől renderelt kocka árnyéktérkép alapján.
+point-light-shadow-bias = Árnyék eltolása
+point-light-shadow-bias-hint = Ennyivel kerülnek közelebb a felületek a fényhez az árnyéktérképpel való összehasonlításkor. Növeld az árnyékpattanások ellen, csökkentsd, ha a fény átszivárog az árnyékvetők mögé.
+area-lights = Területi fények
+area-light = { $index }. területi fény
+add-area-light = Területi fény hozzáadása
+add-area-light-hint = Az elülső oldala felől megvilágító téglalap, mint egy stúdió softbox. Csak forward renderelésnél.
+remove-area-light = Eltávolítás
+area-lights-full = Legfeljebb { $count } területi fény támogatott
+area-light-width = Szélesség
+area-light-height = Magasság
+area-light-color = Szín
+area-light-intensity = Erősség
+area-light-intensity-hint = A világító felület fényessége. 1 esetén a felület teljes rálátását kitöltő fény olyan erősen világítja meg, mint a merőlegesen rá eső irányított fény.
+baked-lighting = Előre számított megvilágítás
+baked-lighting-enabled = Előre számított megvilágítás megjelenítése
+baked-lighting-enabled-hint = A modellek a fénytérképükkel jelennek meg a valós idejű környezeti és szórt megvilágítás helyett. Csak Forward rendereléssel.
+baked-lighting-none = Előbb számítsd ki a megvilágítást
+lightmap-resolution = Fénytérkép felbontása
+lightmap-samples = Közvetett minták
+lightmap-samples-hint = Texelenként kilőtt sugarak a más modellekről visszaverődő fényhez. Több minta kevesebb zajt ad.
+bake-lighting = Megvilágítás kiszámítása
+bake-lighting-hint = A látható modellek közvetlen és közvetett megvilágítását a háttérben fénytérképekbe számítja ki előre. A modellek vagy a fény mozgatása után újra ki kell számítani.
+bake-lighting-running = A megvilágítás számítása folyamatban, várd meg a végét
+lightmaps-baked = Fénytérképek: { $count }, kiszámítva { $ms } ms alatt
+
+vsync = Függőleges szinkronizálás
+vsync-hint = Az adaptív mód a késő képkockákat azonnal megjeleníti a következő frissítés kivárása helyett. Ha nem támogatott, bekapcsolt módra vált.
+vsync-off = Ki
+vsync-on = Be
+vsync-adaptive = Adaptív
+vsync-half = Fél frissítési gyakoriság
+render-path = Renderelési mód
+render-path-forward = Forward
+render-path-deferred = Deferred
+anti-aliasing = Élsimítás
+anti-aliasing-off = Ki
+anti-aliasing-multisample = Többmintás (MSAA)
+anti-aliasing-temporal = Időbeli (TAA)
+anti-aliasing-hint = Az MSAA előre renderelést igényel. A TAA eltolt képkockákat halmoz fel és mindkét renderelési úttal működik, de csak az ablakban, sztereó kimenet nélkül.
+taa-sharpness = TAA élesség
+taa-sharpness-hint = Az aktuális képkocka részleteinek visszaadása a felhalmozott eredményhez. Nagyobb értékek élesebbek, de villoghatnak.
+exposure = Expozíció
+gamma = Gamma
+render-scale = Renderelési felbontás
+auto-quality = Automatikus minőség
+auto-quality-hint = Kisebb renderelési felbontás és tükröződések kihagyása, ha a képkockák lassabbak a cél képkockasebességnél. Függőleges szinkronnal a frissítési frekvenciánál magasabb cél csak csökkenti a minőséget.
+target-frame-rate = Cél képkockasebesség
+reduced-effects = Tükröződések kihagyva a képkockasebesség tartásáért
+motion-blur = Mozgási elmosódás
+motion-blur-hint = A pixelek elmosása a kamera által az előző képkocka óta okozott mozgásuk mentén. Sztereó kimenetre nem érvényes.
+shutter-angle = Záridő szöge
+shutter-angle-hint = A képkocka idejének az a része, amíg a virtuális zár nyitva van. 180° a filmek megszokott hatását adja, nagyobb szög jobban elmos.
+post-effect-vignette = Vignettálás
+post-effect-film-grain = Filmszemcse
+post-effect-chromatic-aberration = Kromatikus aberráció
+post-effect-earlier = Alkalmazás korábban
+post-effect-later = Alkalmazás később
+vignette-strength = Vignettálás erőssége
+film-grain-intensity = Szemcsék intenzitása
+chromatic-aberration-strength = Aberráció erőssége (px)
+post-effects-hint = Az effektek fentről lefelé, az expozíció és a gamma előtt érvényesülnek.
+interface = Felület
+gui-scale = Felület mérete: { $percent }%
+theme = Téma
+theme-dark = Sötét
+theme-light = Világos
+language = Nyelv
+
+pause = Szünet
+time-scale = Időskála
+time-scale-hint = A logikai frissítések sebessége a valós időhöz képest, 1 alatt lassítva
+step = Léptetés
+step-hint = Egyetlen logikai frissítés futtatása szüneteltetés közben
+timeline-play = Lejátszás
+timeline-pause = Szünet
+timeline-rewind = Visszatekerés
+timeline-loop = Ismétlés
+timeline-time = Idő
+timeline-duration = Időtartam
+timeline-property = Tulajdonság
+timeline-property-hint = Az entitások tulajdonságai akkor jelennek meg, ha egy entitás ki van jelölve a hierarchiában
+keyframe-easing = Görbe
+keyframe-easing-hint = A változás lefolyása az előző kulcskockától az újig
+add-keyframe = Kulcskocka hozzáadása
+add-keyframe-hint = A tulajdonság jelenlegi értékének rögzítése a jelenlegi időpontban
+go-to-keyframe = Ugrás ehhez a kulcskockához
+remove-keyframe = Eltávolítás
+animated-light-direction = Fény iránya
+animated-background-color = Háttérszín
+animated-model-rotation = Modell forgatása
+animated-field-of-view = Látószög
+animated-exposure = Expozíció
+animated-reflective-plane-height = Talajsík magassága
+animated-entity-position = { $name } pozíciója
+animated-entity-rotation = { $name } forgatása
+export-directory = Exportálási mappa
+export-frame-rate = Képkocka másodpercenként
+export-animation = Képkockák exportálása
+export-animation-hint = Az idővonal renderelése az elejétől számozott PNG képekbe a mappában
+modulation = Moduláció
+modulation-no-sources = Nincs forrás. Hangbemenet elemzése a --audio kapcsolóval indítva.
+modulation-source = { $index }. forrás
+modulation-from = Forrás
+modulation-to = Cél
+modulation-amount = Mérték
+add-modulation = Kötés hozzáadása
+remove-modulation = Eltávolítás
+modulation-rotation-speed = Forgási sebesség (°/s)
+modulation-light-intensity = Fényerősség
+modulation-exposure = Expozíció (EV)
+modulation-part-explode = Részek szétszedése
+
+frame-rate = { $fps } FPS, { $ms } ms/képkocka
+assets = Betöltött tartalom
+assets-meshes = Hálók: { $count } ({ $size })
+assets-skyboxes = Égboltok: { $count } ({ $size })
+assets-evict-unused = Nem használtak eltávolítása
+vram-usage = Becsült VRAM-használat: { $size }
+resources = Erőforrások
+resource-buffers = Pufferek
+resource-textures = Textúrák
+resource-renderbuffers = Renderpufferek
+resource-vertex-arrays = Csúcsponttömbök
+resource-framebuffers = Framebufferek
+resource-count = { $kind }: { $count } ({ $size })
+resource-lifetime = { $created } létrehozva, { $deleted } törölve
+resource-leak = ⚠ { $kind } száma folyamatosan nő, lehetséges szivárgás
+draws = Rajzolási hívások
+draw-calls = Rajzolási hívások képkockánként: { $count }
+draw-triangles = Háromszögek képkockánként: { $count }
+gl-state = GL állapotváltások
+gl-state-changes = Változások képkockánként: { $count }
+gl-state-redundant = Kihagyott felesleges hívások: { $count }
+pipeline-statistics = Csővezeték-statisztika
+pipeline-vertices = Beküldött csúcspontok: { $count }
+pipeline-fragments = Árnyalt fragmensek: { $count }
+pipeline-statistics-pending = Várakozás az eredményekre
+pipeline-statistics-unsupported = OpenGL 4.6 vagy ARB_pipeline_statistics_query szükséges
+
+## HTML controls of the web build
+
+html-help = Súgó
+html-properties = Tulajdonságok
+html-overlay-gui = Átfedő felület bekapcsolása
+html-view-in-xr = Megtekintés VR/AR-ben
+html-exposure = Expozíció (EV)
+html-ground-plane-height = Talajsík magassága
+html-reflection-blur = Tükröződés elmosása
+html-material-color = Anyag
+html-shadow-resolutions = Kaszkádok felbontása
+html-post-effect-order = Utóeffektek sorrendje
+html-shutter-angle = Záridő szöge (°)
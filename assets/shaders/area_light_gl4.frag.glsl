@@ -0,0 +1,12 @@
+#version 430 core
+
+// Emitting surface of an area light, drawn in the radiance of the light from both sides.
+
+uniform vec3 u_color;
+
+layout (location = 0) out vec4 o_FragColor;
+
+void main()
+{
+    o_FragColor = vec4(u_color, 1.0);
+}
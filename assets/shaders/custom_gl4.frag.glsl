@@ -0,0 +1,39 @@
+#version 430 core
+
+// Starting point of custom shaders registered at runtime, shading with a few flat bands of light.
+// Output is in linear color space, conversion to sRGB is done in post-processing.
+
+in vec3 v_fragPos;
+in vec3 v_normal;
+
+struct Light
+{
+    vec3 direction;
+};
+
+uniform vec3 u_color;
+uniform float u_refractiveIndex;
+uniform Light u_light;
+uniform vec3 u_viewPos;
+uniform samplerCube u_skyboxTexture;
+// Fragments behind this plane are discarded when rendering mirror reflections
+uniform vec4 u_clipPlane;
+
+layout (location = 0) out vec4 o_FragColor;
+
+const float BAND_COUNT = 4.0;
+
+void main()
+{
+    if (dot(vec4(v_fragPos, 1.0), u_clipPlane) < 0.0)
+    {
+        discard;
+    }
+
+    vec3 norm = normalize(v_normal);
+    vec3 lightDir = normalize(-u_light.direction);
+    float diff = max(dot(norm, lightDir), 0.0);
+    float band = ceil(diff * BAND_COUNT) / BAND_COUNT;
+
+    o_FragColor = vec4((0.2 + 0.8 * band) * u_color, 1.0);
+}
@@ -0,0 +1,22 @@
+#version 430 core
+
+// Starting point of custom shaders registered at runtime. Vertex attributes and uniforms match the
+// built-in model shader, so any of them can be used.
+
+layout (location = 0) in vec3 a_position;
+layout (location = 1) in vec3 a_normal;
+
+// Transformations are relative to the camera, the eye is at the origin
+uniform mat4 u_model;
+uniform mat4 u_mvp;
+uniform mat3 u_normalMatrix;
+
+out vec3 v_fragPos;
+out vec3 v_normal;
+
+void main()
+{
+    gl_Position = u_mvp * vec4(a_position, 1.0);
+    v_fragPos = vec3(u_model * vec4(a_position, 1.0));
+    v_normal = u_normalMatrix * a_normal;
+}
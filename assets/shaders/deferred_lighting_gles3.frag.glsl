@@ -0,0 +1,119 @@
+#version 300 es
+precision highp float;
+
+in vec2 v_texCoords;
+
+struct Light
+{
+    vec3 direction; // Light direction vector is determined from origin (0,0,0)
+};
+
+// Values of MaterialType
+const int MATERIAL_PHONG = 0;
+const int MATERIAL_REFLECTIVE = 1;
+const int MATERIAL_REFRACTIVE = 2;
+
+// Bit flags of lighting components stored in material texture
+const int DIFFUSE_ENABLED = 1;
+const int SPECULAR_ENABLED = 2;
+
+// Range of refractive index stored in normalized material texture
+const float MIN_REFRACTIVE_INDEX = 1.0;
+const float MAX_REFRACTIVE_INDEX = 3.0;
+
+// G-buffer
+uniform sampler2D u_albedoTexture;
+uniform sampler2D u_normalTexture;
+uniform sampler2D u_materialTexture;
+uniform sampler2D u_depthTexture;
+
+uniform samplerCube u_skyboxTexture;
+// Transforms normalized device coordinates back into world space
+uniform mat4 u_inverseProjectionView;
+uniform Light u_light;
+uniform vec3 u_viewPos;
+
+layout (location = 0) out vec4 o_FragColor;
+
+vec3 reconstructPosition(float depth)
+{
+    vec4 ndc = vec4(v_texCoords * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
+    vec4 worldPos = u_inverseProjectionView * ndc;
+    return worldPos.xyz / worldPos.w;
+}
+
+vec3 createPhongSurface(vec3 color, vec3 norm, vec3 lightDir, bool diffuseEnabled)
+{
+    // Ambient
+    float ambientStrength = 0.2;
+    vec3 ambient = ambientStrength * color;
+
+    // Diffuse
+    vec3 diffuse = vec3(0.0);
+    if (diffuseEnabled)
+    {
+        diffuse = max(dot(norm, lightDir), 0.0) * color;
+    }
+
+    return ambient + diffuse;
+}
+
+vec3 createRefractiveSurface(vec3 color, vec3 norm, vec3 incident, float refractiveIndex)
+{
+    vec3 reflection = texture(u_skyboxTexture, reflect(incident, norm)).rgb;
+    // Ray enters from air into the material
+    vec3 refraction = texture(u_skyboxTexture, refract(incident, norm, 1.0 / refractiveIndex)).rgb;
+
+    // Schlick's approximation of fresnel reflectance
+    float f0 = pow((refractiveIndex - 1.0) / (refractiveIndex + 1.0), 2.0);
+    float cosTheta = clamp(dot(-incident, norm), 0.0, 1.0);
+    float fresnel = f0 + (1.0 - f0) * pow(1.0 - cosTheta, 5.0);
+
+    return mix(refraction * color, reflection, fresnel);
+}
+
+void main()
+{
+    float depth = texture(u_depthTexture, v_texCoords).r;
+    // Nothing was drawn here, keep background
+    if (depth == 1.0)
+    {
+        discard;
+    }
+
+    vec3 color = texture(u_albedoTexture, v_texCoords).rgb;
+    vec3 norm = normalize(texture(u_normalTexture, v_texCoords).xyz * 2.0 - 1.0);
+    vec4 material = texture(u_materialTexture, v_texCoords);
+    int materialType = int(round(material.r * 255.0));
+    float refractiveIndex = mix(MIN_REFRACTIVE_INDEX, MAX_REFRACTIVE_INDEX, material.g);
+    int lightingFlags = int(round(material.b * 255.0));
+
+    vec3 fragPos = reconstructPosition(depth);
+    vec3 incident = normalize(fragPos - u_viewPos);
+    vec3 lightDir = normalize(-u_light.direction);
+
+    vec3 surface;
+    if (materialType == MATERIAL_REFLECTIVE)
+    {
+        surface = texture(u_skyboxTexture, reflect(incident, norm)).rgb * color;
+    }
+    else if (materialType == MATERIAL_REFRACTIVE)
+    {
+        surface = createRefractiveSurface(color, norm, incident, refractiveIndex);
+    }
+    else
+    {
+        surface = createPhongSurface(color, norm, lightDir, (lightingFlags & DIFFUSE_ENABLED) != 0);
+    }
+
+    // Specular
+    vec3 specular = vec3(0.0);
+    if ((lightingFlags & SPECULAR_ENABLED) != 0)
+    {
+        vec3 reflectDir = reflect(-lightDir, norm);
+        float spec = pow(max(dot(-incident, reflectDir), 0.0), 64.0);
+        specular = spec * color;
+    }
+
+    o_FragColor = vec4(surface + specular, 1.0);
+}
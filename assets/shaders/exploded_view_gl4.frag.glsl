@@ -0,0 +1,30 @@
+#version 430 core
+
+in vec3 v_fragPos;
+flat in vec3 v_normal;
+
+struct Light
+{
+    vec3 direction;
+};
+
+uniform vec3 u_color;
+uniform Light u_light;
+// Fragments behind this plane are discarded when rendering reflections
+uniform vec4 u_clipPlane;
+
+layout (location = 0) out vec4 o_FragColor;
+
+void main()
+{
+    if (dot(vec4(v_fragPos, 1.0), u_clipPlane) < 0.0)
+    {
+        discard;
+    }
+
+    // Flat shaded, so every separated triangle is distinguishable. Back faces of open meshes are
+    // lit from the other side.
+    vec3 norm = gl_FrontFacing ? v_normal : -v_normal;
+    float diff = max(dot(norm, normalize(-u_light.direction)), 0.0);
+    o_FragColor = vec4((0.2 + 0.8 * diff) * u_color, 1.0);
+}
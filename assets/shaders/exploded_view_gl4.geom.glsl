@@ -0,0 +1,30 @@
+#version 430 core
+
+// Triangles pushed apart along their face normals, revealing the structure of the mesh.
+
+layout (triangles) in;
+layout (triangle_strip, max_vertices = 3) out;
+
+uniform mat4 u_projectionView;
+uniform float u_distance;
+
+out vec3 v_fragPos;
+flat out vec3 v_normal;
+
+void main()
+{
+    vec3 a = gl_in[0].gl_Position.xyz;
+    vec3 b = gl_in[1].gl_Position.xyz;
+    vec3 c = gl_in[2].gl_Position.xyz;
+    vec3 normal = normalize(cross(b - a, c - a));
+
+    for (int i = 0; i < 3; ++i)
+    {
+        vec3 position = gl_in[i].gl_Position.xyz + u_distance * normal;
+        gl_Position = u_projectionView * vec4(position, 1.0);
+        v_fragPos = position;
+        v_normal = normal;
+        EmitVertex();
+    }
+    EndPrimitive();
+}
@@ -0,0 +1,10 @@
+#version 430 core
+
+uniform vec3 u_color;
+
+layout (location = 0) out vec4 o_FragColor;
+
+void main()
+{
+    o_FragColor = vec4(u_color, 1.0);
+}
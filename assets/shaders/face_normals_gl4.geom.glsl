@@ -0,0 +1,27 @@
+#version 430 core
+
+// Line from the center of each triangle along its face normal. Face normals are calculated from
+// vertex positions, so they show the actual winding order of triangles regardless of the vertex
+// normals of the mesh. Lines follow triangles pushed apart by exploded view.
+
+layout (triangles) in;
+layout (line_strip, max_vertices = 2) out;
+
+uniform mat4 u_projectionView;
+uniform float u_length;
+uniform float u_distance;
+
+void main()
+{
+    vec3 a = gl_in[0].gl_Position.xyz;
+    vec3 b = gl_in[1].gl_Position.xyz;
+    vec3 c = gl_in[2].gl_Position.xyz;
+    vec3 normal = normalize(cross(b - a, c - a));
+    vec3 center = (a + b + c) / 3.0 + u_distance * normal;
+
+    gl_Position = u_projectionView * vec4(center, 1.0);
+    EmitVertex();
+    gl_Position = u_projectionView * vec4(center + u_length * normal, 1.0);
+    EmitVertex();
+    EndPrimitive();
+}
@@ -0,0 +1,13 @@
+#version 430 core
+
+// Triangles are assembled in camera-relative world space, projection is applied by the geometry
+// shader after it moved or created vertices.
+
+layout (location = 0) in vec3 a_position;
+
+uniform mat4 u_model;
+
+void main()
+{
+    gl_Position = u_model * vec4(a_position, 1.0);
+}
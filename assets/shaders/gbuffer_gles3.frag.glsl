@@ -0,0 +1,111 @@
+#version 300 es
+precision mediump float;
+
+in vec3 v_fragPos;
+in vec3 v_normal;
+in vec3 v_vertexColor;
+
+uniform vec3 u_color;
+uniform int u_materialType;
+uniform float u_refractiveIndex;
+uniform int u_diffuseEnabled;
+uniform int u_specularEnabled;
+// 0 ignores vertex colors, 1 multiplies the material color with them, 2 replaces the material color.
+// Meshes without vertex colors are drawn with 0.
+uniform int u_vertexColorMode;
+// Fragments behind this world space plane are discarded.
+uniform vec4 u_clipPlane;
+
+// Images projected onto surfaces from perspective projectors. Matrices transform from the same
+// camera-relative space as v_fragPos into the clip space of each projector, positions are in the
+// same space. Depth seen by each projector is stored in a quadrant of the atlas, row by row from
+// the bottom left, hiding the image from occluded surfaces.
+const int MAX_DECALS = 4;
+uniform int u_decalCount;
+uniform mat4 u_decalMatrices[MAX_DECALS];
+uniform vec3 u_decalPositions[MAX_DECALS];
+uniform float u_decalOpacities[MAX_DECALS];
+uniform sampler2D u_decalTexture0;
+uniform sampler2D u_decalTexture1;
+uniform sampler2D u_decalTexture2;
+uniform sampler2D u_decalTexture3;
+// Shadow samplers have no default precision
+precision highp sampler2DShadow;
+uniform sampler2DShadow u_decalDepthAtlas;
+
+// Surface attributes read back by the deferred lighting pass
+layout (location = 0) out vec4 o_albedo;
+layout (location = 1) out vec4 o_normal;
+layout (location = 2) out vec4 o_material;
+
+// Range of refractive index stored in normalized material texture
+const float MIN_REFRACTIVE_INDEX = 1.0;
+const float MAX_REFRACTIVE_INDEX = 3.0;
+
+vec4 decalColor(int index, vec2 uv)
+{
+    if (index == 0)
+    {
+        return texture(u_decalTexture0, uv);
+    }
+    else if (index == 1)
+    {
+        return texture(u_decalTexture1, uv);
+    }
+    else if (index == 2)
+    {
+        return texture(u_decalTexture2, uv);
+    }
+    return texture(u_decalTexture3, uv);
+}
+
+// Color covered by decals blended by their alpha, later decals on top. Images are sampled for
+// fragments outside of frustums and occluded fragments too, keeping texture derivatives defined.
+vec3 applyDecals(vec3 color)
+{
+    vec3 norm = normalize(v_normal);
+    for (int i = 0; i < u_decalCount; ++i)
+    {
+        vec4 clipPos = u_decalMatrices[i] * vec4(v_fragPos, 1.0);
+        vec3 ndc = clipPos.xyz / max(clipPos.w, 1e-6);
+        float inside = clipPos.w > 0.0 && all(lessThanEqual(abs(ndc), vec3(1.0))) ? 1.0 : 0.0;
+        vec2 uv = ndc.xy * 0.5 + 0.5;
+        // Keep filtered depth comparisons within the quadrant of the projector
+        vec2 margin = 1.0 / vec2(textureSize(u_decalDepthAtlas, 0));
+        vec2 atlasUv = (clamp(uv, margin, 1.0 - margin) + vec2(float(i % 2), float(i / 2))) * 0.5;
+        float visible = texture(u_decalDepthAtlas, vec3(atlasUv, ndc.z * 0.5 + 0.5));
+        // Fade out on surfaces turning away, where the image would be stretched
+        float facing = smoothstep(0.0, 0.3, -dot(norm, normalize(v_fragPos - u_decalPositions[i])));
+        // Top of the image is the top of the clip space
+        vec4 decal = decalColor(i, vec2(uv.x, 1.0 - uv.y));
+        float coverage = decal.a * u_decalOpacities[i] * facing * inside * visible;
+        color = mix(color, decal.rgb, coverage);
+    }
+    return color;
+}
+
+void main()
+{
+    if (dot(vec4(v_fragPos, 1.0), u_clipPlane) < 0.0)
+    {
+        discard;
+    }
+
+    vec3 albedo = u_color;
+    if (u_vertexColorMode == 1)
+    {
+        albedo *= v_vertexColor;
+    }
+    else if (u_vertexColorMode == 2)
+    {
+        albedo = v_vertexColor;
+    }
+    o_albedo = vec4(applyDecals(albedo), 1.0);
+    // Normalized texture stores values in [0, 1] range
+    o_normal = vec4(normalize(v_normal) * 0.5 + 0.5, 1.0);
+    float refractiveIndex = (u_refractiveIndex - MIN_REFRACTIVE_INDEX)
+        / (MAX_REFRACTIVE_INDEX - MIN_REFRACTIVE_INDEX);
+    // Lighting components packed as bit flags
+    float lightingFlags = float(u_diffuseEnabled | (u_specularEnabled << 1)) / 255.0;
+    o_material = vec4(float(u_materialType) / 255.0, refractiveIndex, lightingFlags, 1.0);
+}
@@ -0,0 +1,19 @@
+#version 430 core
+
+in vec2 v_texCoords;
+in vec3 v_color;
+
+// Single channel glyph coverage
+uniform sampler2D u_fontAtlas;
+
+layout (location = 0) out vec4 o_FragColor;
+
+void main()
+{
+    float coverage = texture(u_fontAtlas, v_texCoords).r;
+    if (coverage < 0.01)
+    {
+        discard;
+    }
+    o_FragColor = vec4(v_color, coverage);
+}
@@ -0,0 +1,23 @@
+#version 430 core
+
+layout (location = 0) in vec3 a_anchor;
+layout (location = 1) in vec2 a_offset;
+layout (location = 2) in vec2 a_texCoords;
+layout (location = 3) in vec3 a_color;
+
+uniform mat4 u_projectionView;
+uniform vec2 u_viewportSize;
+
+out vec2 v_texCoords;
+out vec3 v_color;
+
+void main()
+{
+    vec4 position = u_projectionView * vec4(a_anchor, 1.0);
+    // Glyph offsets in pixels are applied after projection, keeping labels
+    // facing the camera with constant size on screen.
+    position.xy += a_offset * 2.0 / u_viewportSize * position.w;
+    gl_Position = position;
+    v_texCoords = a_texCoords;
+    v_color = a_color;
+}
@@ -0,0 +1,15 @@
+#version 300 es
+precision mediump float;
+
+layout (location = 0) in vec3 a_position;
+layout (location = 1) in vec3 a_color;
+
+uniform mat4 u_projectionView;
+
+out vec3 v_color;
+
+void main()
+{
+    gl_Position = u_projectionView * vec4(a_position, 1.0);
+    v_color = a_color;
+}
@@ -1,64 +1,474 @@
 #version 430 core
 
+// Lighting components and surface material are selected by defines of the shader variant:
+// DIFFUSE_ENABLED, SPECULAR_ENABLED and either REFLECTIVE_SURFACE or REFRACTIVE_SURFACE. Phong
+// surface is used when neither surface is defined. MULTI_DRAW_INDIRECT passes material of each
+// draw from the vertex shader instead of uniforms. VERTEX_COLOR multiplies the material color with
+// vertex colors, VERTEX_COLOR_REPLACE uses vertex colors instead. LIGHTMAP replaces ambient and
+// diffuse lighting of Phong surface with baked lighting. Shadows of the directional light are
+// enabled at runtime by a non-zero cascade count.
+// Point and area lights and decals are enabled by non-zero counts.
+
 in vec3 v_fragPos;
 in vec3 v_normal;
+#ifdef VERTEX_COLOR
+in vec3 v_vertexColor;
+#endif
+#ifdef LIGHTMAP
+in vec2 v_lightmapUv;
+
+// Irradiance of direct and indirect light including shadows, baked on CPU
+uniform sampler2D u_lightmap;
+#endif
 
 struct Light
 {
     vec3 direction; // Light direction vector is determined from origin (0,0,0)
 };
 
+#ifdef MULTI_DRAW_INDIRECT
+flat in vec3 v_color;
+flat in float v_refractiveIndex;
+#define u_color v_color
+#define u_refractiveIndex v_refractiveIndex
+#else
 uniform vec3 u_color;
+uniform float u_refractiveIndex;
+#endif
 uniform Light u_light;
 uniform vec3 u_viewPos;
+uniform samplerCube u_skyboxTexture;
+// Fragments behind this world space plane are discarded. Used for excluding
+// geometry below the mirror plane when rendering reflections.
+uniform vec4 u_clipPlane;
+
+// Cascaded shadow maps of the directional light, nearest first. Light space matrices transform
+// from the same camera-relative space as u_model. Cascades are selected by distance along the view
+// direction they were fitted to.
+const int MAX_SHADOW_CASCADES = 4;
+uniform int u_shadowCascadeCount;
+uniform mat4 u_lightSpaceMatrices[MAX_SHADOW_CASCADES];
+uniform float u_cascadeEnds[MAX_SHADOW_CASCADES];
+// World size of a shadow map texel of each cascade. Sampling position is offset along the surface
+// normal by it to avoid self-shadowing.
+uniform float u_shadowTexelSizes[MAX_SHADOW_CASCADES];
+uniform vec3 u_shadowViewOrigin;
+uniform vec3 u_shadowViewDirection;
+// Texels sampled in each direction by percentage-closer filtering
+uniform int u_pcfRadius;
+// Tint surfaces by the cascade their shadow is sampled from
+uniform bool u_cascadeVisualization;
+uniform sampler2DShadow u_shadowMap0;
+uniform sampler2DShadow u_shadowMap1;
+uniform sampler2DShadow u_shadowMap2;
+uniform sampler2DShadow u_shadowMap3;
+
+// Point lights with positions in the same camera-relative space as v_fragPos. Color is multiplied
+// by intensity. Shadowed lights sample a cube map of depth rendered from the light position.
+const int MAX_POINT_LIGHTS = 4;
+// Near plane of point light shadow map projections
+const float POINT_SHADOW_NEAR = 0.05;
+struct PointLight
+{
+    vec3 position;
+    vec3 color;
+    float range;
+    bool shadowed;
+    // Distance the fragment is moved towards the light when compared with the shadow map
+    float shadowBias;
+};
+uniform int u_pointLightCount;
+uniform PointLight u_pointLights[MAX_POINT_LIGHTS];
+uniform samplerCubeShadow u_pointShadowMap0;
+uniform samplerCubeShadow u_pointShadowMap1;
+uniform samplerCubeShadow u_pointShadowMap2;
+uniform samplerCubeShadow u_pointShadowMap3;
+
+// Rectangular area lights, each with four corners in the same camera-relative space as v_fragPos.
+// Corners are counter-clockwise seen from the emitting side. Color is multiplied by intensity.
+const int MAX_AREA_LIGHTS = 4;
+uniform int u_areaLightCount;
+uniform vec3 u_areaLightCorners[MAX_AREA_LIGHTS * 4];
+uniform vec3 u_areaLightColors[MAX_AREA_LIGHTS];
+
+// Images projected onto surfaces from perspective projectors. Matrices transform from the same
+// camera-relative space as v_fragPos into the clip space of each projector, positions are in the
+// same space. Depth seen by each projector is stored in a quadrant of the atlas, row by row from
+// the bottom left, hiding the image from occluded surfaces.
+const int MAX_DECALS = 4;
+uniform int u_decalCount;
+uniform mat4 u_decalMatrices[MAX_DECALS];
+uniform vec3 u_decalPositions[MAX_DECALS];
+uniform float u_decalOpacities[MAX_DECALS];
+uniform sampler2D u_decalTexture0;
+uniform sampler2D u_decalTexture1;
+uniform sampler2D u_decalTexture2;
+uniform sampler2D u_decalTexture3;
+uniform sampler2DShadow u_decalDepthAtlas;
+
+const vec3 CASCADE_COLORS[MAX_SHADOW_CASCADES] = vec3[](
+    vec3(1.0, 0.4, 0.4),
+    vec3(0.4, 1.0, 0.4),
+    vec3(0.4, 0.4, 1.0),
+    vec3(1.0, 1.0, 0.4)
+);
 
 layout (location = 0) out vec4 o_FragColor;
 
-subroutine vec3 CreateDiffuse(vec3 norm, vec3 lightDir);
-subroutine uniform CreateDiffuse createDiffuse;
+vec4 decalColor(int index, vec2 uv)
+{
+    if (index == 0)
+    {
+        return texture(u_decalTexture0, uv);
+    }
+    else if (index == 1)
+    {
+        return texture(u_decalTexture1, uv);
+    }
+    else if (index == 2)
+    {
+        return texture(u_decalTexture2, uv);
+    }
+    return texture(u_decalTexture3, uv);
+}
+
+// Color covered by decals blended by their alpha, later decals on top. Images are sampled for
+// fragments outside of frustums and occluded fragments too, keeping texture derivatives defined.
+vec3 applyDecals(vec3 color)
+{
+    vec3 norm = normalize(v_normal);
+    for (int i = 0; i < u_decalCount; ++i)
+    {
+        vec4 clipPos = u_decalMatrices[i] * vec4(v_fragPos, 1.0);
+        vec3 ndc = clipPos.xyz / max(clipPos.w, 1e-6);
+        float inside = clipPos.w > 0.0 && all(lessThanEqual(abs(ndc), vec3(1.0))) ? 1.0 : 0.0;
+        vec2 uv = ndc.xy * 0.5 + 0.5;
+        // Keep filtered depth comparisons within the quadrant of the projector
+        vec2 margin = 1.0 / vec2(textureSize(u_decalDepthAtlas, 0));
+        vec2 atlasUv = (clamp(uv, margin, 1.0 - margin) + vec2(float(i % 2), float(i / 2))) * 0.5;
+        float visible = texture(u_decalDepthAtlas, vec3(atlasUv, ndc.z * 0.5 + 0.5));
+        // Fade out on surfaces turning away, where the image would be stretched
+        float facing = smoothstep(0.0, 0.3, -dot(norm, normalize(v_fragPos - u_decalPositions[i])));
+        // Top of the image is the top of the clip space
+        vec4 decal = decalColor(i, vec2(uv.x, 1.0 - uv.y));
+        float coverage = decal.a * u_decalOpacities[i] * facing * inside * visible;
+        color = mix(color, decal.rgb, coverage);
+    }
+    return color;
+}
+
+vec3 baseColor()
+{
+#if defined(VERTEX_COLOR_REPLACE)
+    return applyDecals(v_vertexColor);
+#elif defined(VERTEX_COLOR)
+    return applyDecals(u_color * v_vertexColor);
+#else
+    return applyDecals(u_color);
+#endif
+}
+
+// Index of the cascade covering the fragment, -1 outside of every cascade
+int shadowCascade()
+{
+    float depth = dot(v_fragPos - u_shadowViewOrigin, u_shadowViewDirection);
+    for (int i = 0; i < u_shadowCascadeCount; ++i)
+    {
+        if (depth < u_cascadeEnds[i])
+        {
+            return i;
+        }
+    }
+    return -1;
+}
+
+// Average of depth comparisons around the shadow map position. Each comparison is already
+// filtered bilinearly by the sampler.
+float filterShadow(sampler2DShadow shadowMap, vec3 shadowCoord)
+{
+    vec2 texelSize = 1.0 / vec2(textureSize(shadowMap, 0));
+    float lit = 0.0;
+    for (int y = -u_pcfRadius; y <= u_pcfRadius; ++y)
+    {
+        for (int x = -u_pcfRadius; x <= u_pcfRadius; ++x)
+        {
+            vec2 offset = vec2(float(x), float(y)) * texelSize;
+            lit += texture(shadowMap, vec3(shadowCoord.xy + offset, shadowCoord.z));
+        }
+    }
+    float kernelWidth = float(2 * u_pcfRadius + 1);
+    return lit / (kernelWidth * kernelWidth);
+}
 
-subroutine vec3 CreateSpecular(vec3 norm, vec3 lightDir);
-subroutine uniform CreateSpecular createSpecular;
+// Fraction of direct light reaching the fragment
+float shadowVisibility(int cascade, vec3 norm)
+{
+    if (cascade < 0)
+    {
+        return 1.0;
+    }
+    vec3 position = v_fragPos + norm * u_shadowTexelSizes[cascade];
+    vec4 lightSpacePos = u_lightSpaceMatrices[cascade] * vec4(position, 1.0);
+    vec3 shadowCoord = lightSpacePos.xyz / lightSpacePos.w * 0.5 + 0.5;
+    // Beyond the far plane of the light view nothing casts shadow
+    if (1.0 < shadowCoord.z)
+    {
+        return 1.0;
+    }
+    // Sampler arrays can't be indexed dynamically in GLSL ES 3.00
+    if (cascade == 0)
+    {
+        return filterShadow(u_shadowMap0, shadowCoord);
+    }
+    else if (cascade == 1)
+    {
+        return filterShadow(u_shadowMap1, shadowCoord);
+    }
+    else if (cascade == 2)
+    {
+        return filterShadow(u_shadowMap2, shadowCoord);
+    }
+    return filterShadow(u_shadowMap3, shadowCoord);
+}
 
-subroutine(CreateDiffuse)
-vec3 DiffuseEnabled(vec3 norm, vec3 lightDir)
+vec3 createDiffuse(vec3 norm, vec3 lightDir, float visibility)
 {
-    float diff = max(dot(norm, lightDir), 0.0);
-    vec3 diffuse = diff * u_color;
+#ifdef DIFFUSE_ENABLED
+    float diff = max(dot(norm, lightDir), 0.0) * visibility;
+    vec3 diffuse = diff * baseColor();
     return diffuse;
+#else
+    return vec3(0.0);
+#endif
 }
 
-subroutine(CreateSpecular)
-vec3 SpecularEnabled(vec3 norm, vec3 lightDir)
+vec3 createSpecular(vec3 norm, vec3 lightDir)
 {
+#ifdef SPECULAR_ENABLED
     vec3 viewDir = normalize(u_viewPos - v_fragPos);
     vec3 reflectDir = reflect(-lightDir, norm);
     float spec = pow(max(dot(viewDir, reflectDir), 0.0), 64.0);
-    vec3 specular = 1.0 * spec * u_color;
+    vec3 specular = 1.0 * spec * baseColor();
     return specular;
+#else
+    return vec3(0.0);
+#endif
 }
 
-subroutine(CreateDiffuse, CreateSpecular)
-vec3 Disabled(vec3 norm, vec3 lightDir)
+// Fraction of light of a point light reaching the fragment. Each face of the cube map stores depth
+// of its perspective projection, which is calculated from the distance along the major axis.
+float pointShadowVisibility(int index, vec3 fromLight)
 {
-    return vec3(0.0);
+    vec3 axisDistances = abs(fromLight);
+    float major = max(axisDistances.x, max(axisDistances.y, axisDistances.z));
+    major -= u_pointLights[index].shadowBias;
+    float near = POINT_SHADOW_NEAR;
+    float far = max(u_pointLights[index].range, 2.0 * near);
+    float ndcDepth = (far + near) / (far - near) - 2.0 * far * near / ((far - near) * major);
+    vec4 shadowCoord = vec4(fromLight, ndcDepth * 0.5 + 0.5);
+    if (index == 0)
+    {
+        return texture(u_pointShadowMap0, shadowCoord);
+    }
+    else if (index == 1)
+    {
+        return texture(u_pointShadowMap1, shadowCoord);
+    }
+    else if (index == 2)
+    {
+        return texture(u_pointShadowMap2, shadowCoord);
+    }
+    return texture(u_pointShadowMap3, shadowCoord);
 }
 
-void main()
+// Diffuse and specular light of point lights, fading out smoothly until their range
+vec3 createPointLighting(vec3 norm)
 {
+    vec3 lighting = vec3(0.0);
+    for (int i = 0; i < u_pointLightCount; ++i)
+    {
+        vec3 fromLight = v_fragPos - u_pointLights[i].position;
+        float lightDistance = length(fromLight);
+        float falloff = clamp(1.0 - lightDistance / u_pointLights[i].range, 0.0, 1.0);
+        float attenuation = falloff * falloff;
+        if (attenuation <= 0.0)
+        {
+            continue;
+        }
+        if (u_pointLights[i].shadowed)
+        {
+            attenuation *= pointShadowVisibility(i, fromLight);
+        }
+        vec3 lightDir = -fromLight / max(lightDistance, 0.0001);
+#if defined(REFLECTIVE_SURFACE) || defined(REFRACTIVE_SURFACE)
+        vec3 diffuse = vec3(0.0);
+#else
+        vec3 diffuse = createDiffuse(norm, lightDir, 1.0);
+#endif
+        vec3 specular = createSpecular(norm, lightDir);
+        lighting += (diffuse + specular) * u_pointLights[i].color * attenuation;
+    }
+    return lighting;
+}
+
+// Cosine-weighted integral of the polygon edge from v1 to v2 projected onto the unit sphere,
+// as a vector whose Z component is the contribution. Rational fit of acos(x) / sin(acos(x)) from
+// "Real-Time Polygonal-Light Shading with Linearly Transformed Cosines" (Heitz et al. 2016).
+vec3 integrateEdge(vec3 v1, vec3 v2)
+{
+    float x = dot(v1, v2);
+    float y = abs(x);
+    float a = 0.8543985 + (0.4965155 + 0.0145206 * y) * y;
+    float b = 3.4175940 + (4.1616724 + y) * y;
+    float v = a / b;
+    float thetaSinTheta = 0.0 < x ? v : 0.5 * inversesqrt(max(1.0 - x * x, 1e-7)) - v;
+    return cross(v1, v2) * thetaSinTheta;
+}
+
+// Integral of a linearly transformed cosine distribution over an area light. The transform maps the
+// distribution to the clamped cosine around Z, so the integral is the form factor of the
+// transformed rectangle. Clipping by the horizon is approximated by a sphere with the same vector
+// form factor.
+float integrateAreaLight(int index, mat3 inverseTransform)
+{
+    vec3 corners[4];
+    for (int i = 0; i < 4; ++i)
+    {
+        corners[i] = normalize(inverseTransform * (u_areaLightCorners[index * 4 + i] - v_fragPos));
+    }
+    vec3 formFactor = integrateEdge(corners[0], corners[1]) + integrateEdge(corners[1], corners[2])
+        + integrateEdge(corners[2], corners[3]) + integrateEdge(corners[3], corners[0]);
+    // Points towards the rectangle regardless of the winding of its corners
+    if (dot(formFactor, corners[0] + corners[1] + corners[2] + corners[3]) < 0.0)
+    {
+        formFactor = -formFactor;
+    }
+    float len = length(formFactor);
+    return max((len * len + formFactor.z) / (len + 1.0), 0.0);
+}
+
+// Orthonormal basis with the Z axis pointing to the direction, as rows of the inverse rotation
+mat3 basisAround(vec3 direction, vec3 tangentHint)
+{
+    vec3 tangent = tangentHint - direction * dot(tangentHint, direction);
+    if (dot(tangent, tangent) < 1e-6)
+    {
+        tangent = abs(direction.x) < 0.9 ? vec3(1.0, 0.0, 0.0) : vec3(0.0, 1.0, 0.0);
+        tangent -= direction * dot(tangent, direction);
+    }
+    tangent = normalize(tangent);
+    return transpose(mat3(tangent, cross(direction, tangent), direction));
+}
+
+// Diffuse and specular light of area lights. Diffuse light of the rectangle is the exact integral
+// of the clamped cosine around the normal. The Phong lobe of specular light around the reflection
+// direction is fitted analytically by a clamped cosine narrowed to the same half-maximum angle,
+// instead of sampling fitted tables of the original method.
+vec3 createAreaLighting(vec3 norm)
+{
+    const float SHININESS = 64.0;
+    // Ratio of tangents of the angles where the Phong lobe and the clamped cosine fall to half
+    const float LOBE_SCALE = tan(acos(pow(0.5, 1.0 / SHININESS))) / sqrt(3.0);
+    // Integral of the Phong lobe over the hemisphere relative to the clamped cosine
+    const float LOBE_INTEGRAL = 2.0 * 3.14159265 / (SHININESS + 1.0);
+
+    vec3 viewDir = normalize(u_viewPos - v_fragPos);
+    mat3 diffuseTransform = basisAround(norm, viewDir);
+    vec3 reflectDir = reflect(-viewDir, norm);
+    mat3 specularTransform = mat3(
+        1.0 / LOBE_SCALE, 0.0, 0.0,
+        0.0, 1.0 / LOBE_SCALE, 0.0,
+        0.0, 0.0, 1.0
+    ) * basisAround(reflectDir, norm);
+
+    vec3 lighting = vec3(0.0);
+    for (int i = 0; i < u_areaLightCount; ++i)
+    {
+        vec3 corner = u_areaLightCorners[i * 4];
+        vec3 lightNormal = cross(u_areaLightCorners[i * 4 + 1] - corner,
+            u_areaLightCorners[i * 4 + 3] - corner);
+        // Emits from the front side only
+        if (dot(v_fragPos - corner, lightNormal) <= 0.0)
+        {
+            continue;
+        }
+        vec3 light = vec3(0.0);
+#if defined(DIFFUSE_ENABLED) && !defined(REFLECTIVE_SURFACE) && !defined(REFRACTIVE_SURFACE)
+        light += integrateAreaLight(i, diffuseTransform) * baseColor();
+#endif
+#ifdef SPECULAR_ENABLED
+        light += integrateAreaLight(i, specularTransform) * LOBE_INTEGRAL * baseColor();
+#endif
+        lighting += light * u_areaLightColors[i];
+    }
+    return lighting;
+}
+
+#if defined(REFLECTIVE_SURFACE)
+vec3 createSurface(vec3 norm, vec3 lightDir, float visibility)
+{
+    vec3 incident = normalize(v_fragPos - u_viewPos);
+    vec3 reflection = texture(u_skyboxTexture, reflect(incident, norm)).rgb;
+    return reflection * baseColor();
+}
+#elif defined(REFRACTIVE_SURFACE)
+vec3 createSurface(vec3 norm, vec3 lightDir, float visibility)
+{
+    vec3 incident = normalize(v_fragPos - u_viewPos);
+    vec3 reflection = texture(u_skyboxTexture, reflect(incident, norm)).rgb;
+    // Ray enters from air into the material
+    vec3 refraction = texture(u_skyboxTexture, refract(incident, norm, 1.0 / u_refractiveIndex)).rgb;
+
+    // Schlick's approximation of fresnel reflectance. Surfaces reflect more
+    // when viewed at grazing angles.
+    float f0 = pow((u_refractiveIndex - 1.0) / (u_refractiveIndex + 1.0), 2.0);
+    float cosTheta = clamp(dot(-incident, norm), 0.0, 1.0);
+    float fresnel = f0 + (1.0 - f0) * pow(1.0 - cosTheta, 5.0);
+
+    return mix(refraction * baseColor(), reflection, fresnel);
+}
+#else
+vec3 createSurface(vec3 norm, vec3 lightDir, float visibility)
+{
+#ifdef LIGHTMAP
+    return texture(u_lightmap, v_lightmapUv).rgb * baseColor();
+#else
     // Ambient
     float ambientStrength = 0.2;
-    vec3 ambient = ambientStrength * u_color;
+    vec3 ambient = ambientStrength * baseColor();
+
+    // Diffuse
+    vec3 diffuse = createDiffuse(norm, lightDir, visibility);
+
+    return ambient + diffuse;
+#endif
+}
+#endif
+
+void main()
+{
+    if (dot(vec4(v_fragPos, 1.0), u_clipPlane) < 0.0)
+    {
+        discard;
+    }
 
     vec3 norm = normalize(v_normal);
     vec3 lightDir = normalize(-u_light.direction);
 
-    // Diffuse
-    vec3 diffuse = createDiffuse(norm, lightDir);
+    int cascade = shadowCascade();
+    float visibility = shadowVisibility(cascade, norm);
+
+    vec3 surface = createSurface(norm, lightDir, visibility);
 
     // Specular
-    vec3 specular = createSpecular(norm, lightDir);
+    vec3 specular = createSpecular(norm, lightDir) * visibility;
 
-    vec3 result = ambient + diffuse + specular;
+    // Calculated in linear color space, conversion to sRGB is done in the final
+    // post-processing pass.
+    vec3 result = surface + specular + createPointLighting(norm) + createAreaLighting(norm);
+    if (u_cascadeVisualization && 0 <= cascade)
+    {
+        result *= CASCADE_COLORS[cascade];
+    }
     o_FragColor = vec4(result, 1.0);
 }
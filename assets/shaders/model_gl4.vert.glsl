@@ -2,7 +2,39 @@
 
 layout (location = 0) in vec3 a_position;
 layout (location = 1) in vec3 a_normal;
+#ifdef VERTEX_COLOR
+layout (location = 4) in vec4 a_color;
 
+out vec3 v_vertexColor;
+#endif
+#ifdef LIGHTMAP
+layout (location = 6) in vec2 a_lightmapUv;
+
+out vec2 v_lightmapUv;
+#endif
+
+#ifdef MULTI_DRAW_INDIRECT
+// Meshes drawn by a single multi-draw indirect call can't have their own uniforms. Their
+// transformations and material are read from a storage buffer instead, indexed by an instanced
+// attribute starting from the base instance of each draw command.
+struct Draw
+{
+    mat4 model;
+    mat4 mvp;
+    mat4 normalMatrix;
+    vec4 colorAndRefractiveIndex;
+};
+
+layout (std430, binding = 0) readonly buffer Draws
+{
+    Draw u_draws[];
+};
+
+layout (location = 5) in uint a_drawIndex;
+
+flat out vec3 v_color;
+flat out float v_refractiveIndex;
+#else
 uniform mat4 u_model;
 uniform mat4 u_mvp;
 // Normal matrix is used for correctly transform the input vertex normals to
@@ -12,13 +44,29 @@ uniform mat4 u_mvp;
 // normalMatrix = mat3(transpose(inverse(modelMatrix)))
 //
 uniform mat3 u_normalMatrix;
+#endif
 
 out vec3 v_fragPos;
 out vec3 v_normal;
 
 void main()
 {
+#ifdef MULTI_DRAW_INDIRECT
+    Draw draw = u_draws[a_drawIndex];
+    gl_Position = draw.mvp * vec4(a_position, 1.0);
+    v_fragPos = vec3(draw.model * vec4(a_position, 1.0));
+    v_normal = mat3(draw.normalMatrix) * a_normal;
+    v_color = draw.colorAndRefractiveIndex.rgb;
+    v_refractiveIndex = draw.colorAndRefractiveIndex.a;
+#else
     gl_Position = u_mvp * vec4(a_position, 1.0);
     v_fragPos = vec3(u_model * vec4(a_position, 1.0));
     v_normal = u_normalMatrix * a_normal;
+#endif
+#ifdef VERTEX_COLOR
+    v_vertexColor = a_color.rgb;
+#endif
+#ifdef LIGHTMAP
+    v_lightmapUv = a_lightmapUv;
+#endif
 }
@@ -3,6 +3,11 @@ precision mediump float;
 
 layout (location = 0) in vec3 a_position;
 layout (location = 1) in vec3 a_normal;
+#ifdef VERTEX_COLOR
+layout (location = 4) in vec4 a_color;
+
+out vec3 v_vertexColor;
+#endif
 
 uniform mat4 u_model;
 uniform mat4 u_mvp;
@@ -22,4 +27,7 @@ void main()
     gl_Position = u_mvp * vec4(a_position, 1.0);
     v_fragPos = vec3(u_model * vec4(a_position, 1.0));
     v_normal = u_normalMatrix * a_normal;
+#ifdef VERTEX_COLOR
+    v_vertexColor = a_color.rgb;
+#endif
 }
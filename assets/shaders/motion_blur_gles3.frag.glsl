@@ -0,0 +1,51 @@
+#version 300 es
+// Reprojection needs full precision, depth differences of distant points are tiny
+precision highp float;
+
+in vec2 v_texCoords;
+
+// Scene color in linear color space
+uniform sampler2D u_sceneTexture;
+// Scene depth, reconstructing the position seen by each pixel
+uniform sampler2D u_depthTexture;
+// Normalized device coordinates of this frame into clip space of the previous frame
+uniform mat4 u_reprojection;
+// Fraction of the frame time the shutter is open, shutter angle divided by 360°
+uniform float u_shutterFraction;
+
+layout (location = 0) out vec4 o_FragColor;
+
+const int SAMPLE_COUNT = 12;
+// Longest blur in pixels, keeps camera cuts from smearing the whole frame
+const float MAX_BLUR_PIXELS = 48.0;
+
+void main()
+{
+    float depth = texture(u_depthTexture, v_texCoords).r;
+    vec4 ndc = vec4(v_texCoords * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
+    vec4 previousClip = u_reprojection * ndc;
+    // Points behind the previous eye have no meaningful screen position
+    if (previousClip.w <= 0.0)
+    {
+        o_FragColor = vec4(texture(u_sceneTexture, v_texCoords).rgb, 1.0);
+        return;
+    }
+    vec2 previousTexCoords = previousClip.xy / previousClip.w * 0.5 + 0.5;
+
+    // Screen movement while the shutter is open, centered on the current position
+    vec2 texelSize = 1.0 / vec2(textureSize(u_sceneTexture, 0));
+    vec2 velocity = (v_texCoords - previousTexCoords) * u_shutterFraction;
+    float blurPixels = length(velocity / texelSize);
+    if (MAX_BLUR_PIXELS < blurPixels)
+    {
+        velocity *= MAX_BLUR_PIXELS / blurPixels;
+    }
+
+    vec3 color = vec3(0.0);
+    for (int i = 0; i < SAMPLE_COUNT; ++i)
+    {
+        float t = float(i) / float(SAMPLE_COUNT - 1) - 0.5;
+        color += texture(u_sceneTexture, v_texCoords + velocity * t).rgb;
+    }
+    o_FragColor = vec4(color / float(SAMPLE_COUNT), 1.0);
+}
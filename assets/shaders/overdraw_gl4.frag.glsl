@@ -0,0 +1,12 @@
+#version 430 core
+
+// Cost of a single shaded fragment, accumulated by additive blending. Post-processing maps the sum
+// to heatmap colors.
+const float FRAGMENT_COST = 1.0 / 16.0;
+
+layout (location = 0) out vec4 o_FragColor;
+
+void main()
+{
+    o_FragColor = vec4(vec3(FRAGMENT_COST), 1.0);
+}
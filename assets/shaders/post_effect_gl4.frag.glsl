@@ -0,0 +1,55 @@
+#version 430 core
+
+in vec2 v_texCoords;
+
+// Output of the previous pass in linear color space
+uniform sampler2D u_sourceTexture;
+// Effect drawn by this pass, discriminant of PostEffect
+uniform int u_effect;
+// Darkening of the corners from 0 to 1
+uniform float u_vignetteStrength;
+// Largest relative change of brightness by noise
+uniform float u_filmGrainIntensity;
+// Changes every frame to animate the grain
+uniform float u_filmGrainSeed;
+// Displacement of red and blue channels in the corners, in pixels
+uniform float u_chromaticAberrationStrength;
+
+layout (location = 0) out vec4 o_FragColor;
+
+const int VIGNETTE = 0;
+const int FILM_GRAIN = 1;
+const int CHROMATIC_ABERRATION = 2;
+
+// Pseudo-random value between 0 and 1
+float hash(vec2 p)
+{
+    return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+void main()
+{
+    vec3 color = texture(u_sourceTexture, v_texCoords).rgb;
+    // Offset from the center of the viewport, 1 at the edges
+    vec2 fromCenter = v_texCoords * 2.0 - 1.0;
+    if (u_effect == VIGNETTE)
+    {
+        float falloff = smoothstep(0.5, 1.5, length(fromCenter));
+        color *= 1.0 - u_vignetteStrength * falloff;
+    }
+    else if (u_effect == FILM_GRAIN)
+    {
+        float noise = hash(gl_FragCoord.xy + vec2(u_filmGrainSeed, 1.7 * u_filmGrainSeed));
+        color *= 1.0 + (noise * 2.0 - 1.0) * u_filmGrainIntensity;
+    }
+    else if (u_effect == CHROMATIC_ABERRATION)
+    {
+        // Grows linearly from the center, reaching the full displacement in the corners
+        vec2 texelSize = 1.0 / vec2(textureSize(u_sourceTexture, 0));
+        vec2 offset = fromCenter * sqrt(0.5) * u_chromaticAberrationStrength * texelSize;
+        // Channels appear displaced outwards when sampled from inwards
+        color.r = texture(u_sourceTexture, v_texCoords - offset).r;
+        color.b = texture(u_sourceTexture, v_texCoords + offset).b;
+    }
+    o_FragColor = vec4(color, 1.0);
+}
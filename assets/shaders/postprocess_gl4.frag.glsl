@@ -0,0 +1,54 @@
+#version 430 core
+
+in vec2 v_texCoords;
+
+// Scene color in linear color space
+uniform sampler2D u_sceneTexture;
+// Exposure compensation in stops (EV). Each stop doubles or halves brightness.
+uniform float u_exposure;
+uniform float u_gamma;
+// Scene contains the number of shaded fragments per pixel instead of lit colors
+uniform bool u_overdrawHeatmap;
+
+layout (location = 0) out vec4 o_FragColor;
+
+// Overdraw of 16 fragments per pixel saturates the scene texture
+const float MAX_OVERDRAW = 16.0;
+
+// Blue for a single fragment through green and yellow to red for the most expensive pixels.
+// Pixels without any fragment stay black.
+vec3 heatmap(float cost)
+{
+    if (cost <= 0.0)
+    {
+        return vec3(0.0);
+    }
+    float t = 3.0 * clamp((cost * MAX_OVERDRAW - 1.0) / (MAX_OVERDRAW - 1.0), 0.0, 1.0);
+    if (t < 1.0)
+    {
+        return mix(vec3(0.0, 0.0, 1.0), vec3(0.0, 1.0, 0.0), t);
+    }
+    if (t < 2.0)
+    {
+        return mix(vec3(0.0, 1.0, 0.0), vec3(1.0, 1.0, 0.0), t - 1.0);
+    }
+    return mix(vec3(1.0, 1.0, 0.0), vec3(1.0, 0.0, 0.0), t - 2.0);
+}
+
+void main()
+{
+    vec3 color = texture(u_sceneTexture, v_texCoords).rgb;
+    if (u_overdrawHeatmap)
+    {
+        // Heatmap colors are not affected by exposure and gamma
+        color = heatmap(color.r);
+    }
+    else
+    {
+        color *= exp2(u_exposure);
+        // Gamma is adjusted relative to the standard 2.2 display gamma, because
+        // sRGB-capable default framebuffer already encodes output.
+        color = pow(max(color, vec3(0.0)), vec3(2.2 / u_gamma));
+    }
+    o_FragColor = vec4(color, 1.0);
+}
@@ -0,0 +1,68 @@
+#version 300 es
+precision mediump float;
+
+in vec2 v_texCoords;
+
+// Scene color in linear color space
+uniform sampler2D u_sceneTexture;
+// Exposure compensation in stops (EV). Each stop doubles or halves brightness.
+uniform float u_exposure;
+uniform float u_gamma;
+// Scene contains the number of shaded fragments per pixel instead of lit colors
+uniform bool u_overdrawHeatmap;
+// Enabled when drawing into the default framebuffer. Writes into sRGB textures
+// are converted automatically.
+uniform bool u_encodeSrgb;
+
+layout (location = 0) out vec4 o_FragColor;
+
+// WebGL2 default framebuffer is not sRGB-capable, convert linear color to sRGB
+// manually before output.
+vec3 linearToSrgb(vec3 color)
+{
+    vec3 low = 12.92 * color;
+    vec3 high = 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055;
+    return mix(low, high, step(vec3(0.0031308), color));
+}
+
+// Overdraw of 16 fragments per pixel saturates the scene texture
+const float MAX_OVERDRAW = 16.0;
+
+// Blue for a single fragment through green and yellow to red for the most expensive pixels.
+// Pixels without any fragment stay black.
+vec3 heatmap(float cost)
+{
+    if (cost <= 0.0)
+    {
+        return vec3(0.0);
+    }
+    float t = 3.0 * clamp((cost * MAX_OVERDRAW - 1.0) / (MAX_OVERDRAW - 1.0), 0.0, 1.0);
+    if (t < 1.0)
+    {
+        return mix(vec3(0.0, 0.0, 1.0), vec3(0.0, 1.0, 0.0), t);
+    }
+    if (t < 2.0)
+    {
+        return mix(vec3(0.0, 1.0, 0.0), vec3(1.0, 1.0, 0.0), t - 1.0);
+    }
+    return mix(vec3(1.0, 1.0, 0.0), vec3(1.0, 0.0, 0.0), t - 2.0);
+}
+
+void main()
+{
+    vec3 color = texture(u_sceneTexture, v_texCoords).rgb;
+    if (u_overdrawHeatmap)
+    {
+        // Heatmap colors are not affected by exposure and gamma
+        color = heatmap(color.r);
+    }
+    else
+    {
+        color *= exp2(u_exposure);
+        // Gamma is adjusted relative to the standard 2.2 display gamma, because
+        // sRGB conversion takes care of standard encoding.
+        color = pow(max(color, vec3(0.0)), vec3(2.2 / u_gamma));
+    }
+    color = clamp(color, 0.0, 1.0);
+    o_FragColor = vec4(u_encodeSrgb ? linearToSrgb(color) : color, 1.0);
+}
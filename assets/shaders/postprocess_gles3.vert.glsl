@@ -0,0 +1,13 @@
+#version 300 es
+precision mediump float;
+
+out vec2 v_texCoords;
+
+// Fullscreen triangle generated from vertex IDs without vertex buffer. The
+// triangle covers the entire viewport, parts outside of it are clipped.
+void main()
+{
+    vec2 position = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    v_texCoords = position;
+    gl_Position = vec4(position * 2.0 - 1.0, 0.0, 1.0);
+}
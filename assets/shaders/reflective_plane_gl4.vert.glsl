@@ -0,0 +1,18 @@
+#version 430 core
+
+uniform mat4 u_model;
+uniform mat4 u_mvp;
+
+out vec3 v_fragPos;
+out vec2 v_localPos;
+
+// Quad on XZ plane generated from vertex IDs without vertex buffer, drawn as a
+// triangle strip.
+void main()
+{
+    vec2 corner = vec2(gl_VertexID & 1, (gl_VertexID >> 1) & 1) * 2.0 - 1.0;
+    vec4 position = vec4(corner.x, 0.0, corner.y, 1.0);
+    gl_Position = u_mvp * position;
+    v_fragPos = vec3(u_model * position);
+    v_localPos = corner;
+}
@@ -0,0 +1,47 @@
+#version 300 es
+precision mediump float;
+
+in vec3 v_fragPos;
+in vec2 v_localPos;
+
+// Scene rendered from camera mirrored by the plane, same size as the viewport
+uniform sampler2D u_reflectionTexture;
+uniform vec3 u_viewPos;
+// Reflectance when looking straight down at the plane
+uniform float u_reflectivity;
+// Distance of blur samples in texels
+uniform float u_blurRadius;
+
+layout (location = 0) out vec4 o_FragColor;
+
+const vec3 BASE_COLOR = vec3(0.02);
+
+void main()
+{
+    // Mirrored scene is projected onto the same pixels, no texture coordinates
+    // needed.
+    vec2 texelSize = 1.0 / vec2(textureSize(u_reflectionTexture, 0));
+    vec2 screenCoords = gl_FragCoord.xy * texelSize;
+
+    // Box blur imitating a rough, glossy surface
+    vec3 reflection = vec3(0.0);
+    for (int x = -1; x <= 1; ++x)
+    {
+        for (int y = -1; y <= 1; ++y)
+        {
+            vec2 offset = vec2(float(x), float(y)) * texelSize * u_blurRadius;
+            reflection += texture(u_reflectionTexture, screenCoords + offset).rgb;
+        }
+    }
+    reflection /= 9.0;
+
+    // Schlick's approximation of fresnel reflectance with plane normal pointing
+    // upwards. Plane reflects more when viewed at grazing angles.
+    vec3 viewDir = normalize(u_viewPos - v_fragPos);
+    float cosTheta = clamp(viewDir.y, 0.0, 1.0);
+    float fresnel = u_reflectivity + (1.0 - u_reflectivity) * pow(1.0 - cosTheta, 5.0);
+
+    // Fade out towards plane edges to avoid hard horizon line
+    float alpha = 1.0 - smoothstep(0.5, 1.0, length(v_localPos));
+    o_FragColor = vec4(mix(BASE_COLOR, reflection, fresnel), alpha);
+}
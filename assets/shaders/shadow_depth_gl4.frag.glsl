@@ -0,0 +1,7 @@
+#version 430 core
+
+// Shadow maps only store depth, written by fixed-function depth testing.
+
+void main()
+{
+}
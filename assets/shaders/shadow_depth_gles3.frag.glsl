@@ -0,0 +1,8 @@
+#version 300 es
+precision mediump float;
+
+// Shadow maps only store depth, written by fixed-function depth testing.
+
+void main()
+{
+}
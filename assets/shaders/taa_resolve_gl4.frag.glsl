@@ -0,0 +1,65 @@
+#version 430 core
+
+in vec2 v_texCoords;
+
+// Scene of this frame rendered with jittered projection, in linear color space
+uniform sampler2D u_sceneTexture;
+// Resolved output of the previous frame
+uniform sampler2D u_historyTexture;
+// Scene depth, reconstructing the position seen by each pixel
+uniform sampler2D u_depthTexture;
+// Normalized device coordinates of this frame into clip space of the previous frame
+uniform mat4 u_reprojection;
+// History is missing on the first frame and after resizing
+uniform bool u_historyValid;
+// Amount of detail of this frame added back to the blurry accumulated result, from 0 to 1
+uniform float u_sharpness;
+
+layout (location = 0) out vec4 o_FragColor;
+
+// Weight of this frame in the result, the rest comes from history
+const float CURRENT_WEIGHT = 0.1;
+
+void main()
+{
+    // Neighborhood of the pixel in this frame bounds plausible history colors
+    ivec2 pixel = ivec2(gl_FragCoord.xy);
+    ivec2 maxPixel = textureSize(u_sceneTexture, 0) - 1;
+    vec3 current = texelFetch(u_sceneTexture, pixel, 0).rgb;
+    vec3 neighborhoodMin = current;
+    vec3 neighborhoodMax = current;
+    vec3 neighborhoodSum = vec3(0.0);
+    for (int y = -1; y <= 1; ++y)
+    {
+        for (int x = -1; x <= 1; ++x)
+        {
+            vec3 neighbor =
+                texelFetch(u_sceneTexture, clamp(pixel + ivec2(x, y), ivec2(0), maxPixel), 0).rgb;
+            neighborhoodMin = min(neighborhoodMin, neighbor);
+            neighborhoodMax = max(neighborhoodMax, neighbor);
+            neighborhoodSum += neighbor;
+        }
+    }
+
+    vec3 result = current;
+    float depth = texture(u_depthTexture, v_texCoords).r;
+    vec4 previousClip = u_reprojection * vec4(v_texCoords * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
+    if (u_historyValid && 0.0 < previousClip.w)
+    {
+        vec2 previousTexCoords = previousClip.xy / previousClip.w * 0.5 + 0.5;
+        // Pixels that were off screen have no history
+        if (all(greaterThanEqual(previousTexCoords, vec2(0.0)))
+            && all(lessThanEqual(previousTexCoords, vec2(1.0))))
+        {
+            vec3 history = texture(u_historyTexture, previousTexCoords).rgb;
+            // Clamping rejects history of surfaces that became hidden or changed, avoiding ghosts
+            history = clamp(history, neighborhoodMin, neighborhoodMax);
+            result = mix(history, current, CURRENT_WEIGHT);
+        }
+    }
+
+    // Unsharp mask with details of this frame
+    vec3 neighborhoodAverage = neighborhoodSum / 9.0;
+    result += u_sharpness * (current - neighborhoodAverage);
+    o_FragColor = vec4(max(result, vec3(0.0)), 1.0);
+}
@@ -0,0 +1,51 @@
+#version 430 core
+
+in vec3 v_fragPos;
+in vec3 v_normal;
+in float v_height;
+
+struct Light
+{
+    vec3 direction; // Light direction vector is determined from origin (0,0,0)
+};
+
+uniform Light u_light;
+// Lowest and highest height of the terrain
+uniform vec2 u_heightRange;
+// Fragments behind this plane are discarded, same as for models
+uniform vec4 u_clipPlane;
+
+layout (location = 0) out vec4 o_FragColor;
+
+// Colors of height bands in linear color space, from lowest to highest
+const vec3 SAND_COLOR = vec3(0.45, 0.38, 0.22);
+const vec3 GRASS_COLOR = vec3(0.08, 0.22, 0.04);
+const vec3 ROCK_COLOR = vec3(0.16, 0.13, 0.11);
+const vec3 SNOW_COLOR = vec3(0.85, 0.87, 0.9);
+
+vec3 heightColor(float height)
+{
+    vec3 color = mix(SAND_COLOR, GRASS_COLOR, smoothstep(0.02, 0.1, height));
+    color = mix(color, ROCK_COLOR, smoothstep(0.5, 0.65, height));
+    return mix(color, SNOW_COLOR, smoothstep(0.8, 0.9, height));
+}
+
+void main()
+{
+    if (dot(vec4(v_fragPos, 1.0), u_clipPlane) < 0.0)
+    {
+        discard;
+    }
+
+    vec3 norm = normalize(v_normal);
+    float range = max(u_heightRange.y - u_heightRange.x, 0.0001);
+    vec3 color = heightColor((v_height - u_heightRange.x) / range);
+    // Steep slopes are bare rock at any height
+    float slope = 1.0 - norm.y;
+    color = mix(color, ROCK_COLOR, smoothstep(0.3, 0.5, slope));
+
+    // Ambient and diffuse lighting
+    vec3 lightDir = normalize(-u_light.direction);
+    float diffuse = max(dot(norm, lightDir), 0.0);
+    o_FragColor = vec4((0.2 + diffuse) * color, 1.0);
+}
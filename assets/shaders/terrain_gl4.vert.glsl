@@ -0,0 +1,21 @@
+#version 430 core
+
+layout (location = 0) in vec3 a_position;
+layout (location = 1) in vec3 a_normal;
+
+uniform mat4 u_model;
+uniform mat4 u_mvp;
+
+out vec3 v_fragPos;
+out vec3 v_normal;
+// World height before translation, colors are chosen by it
+out float v_height;
+
+void main()
+{
+    gl_Position = u_mvp * vec4(a_position, 1.0);
+    v_fragPos = vec3(u_model * vec4(a_position, 1.0));
+    // Terrain is only translated, normals don't need a normal matrix
+    v_normal = a_normal;
+    v_height = a_position.y;
+}
@@ -0,0 +1,58 @@
+#version 430 core
+
+layout (local_size_x = 64) in;
+
+// Vertices are tightly packed floats matching the vertex buffer layout of
+// models, starting with position XYZ and normal XYZ. Arrays of vec3 would be
+// padded to 16 bytes.
+
+// Undisplaced vertices
+layout (std430, binding = 0) readonly buffer SourceVertices
+{
+    float sourceVertices[];
+};
+
+// Vertex buffer of model used by draw calls
+layout (std430, binding = 1) writeonly buffer DisplacedVertices
+{
+    float displacedVertices[];
+};
+
+uniform uint u_vertexCount;
+// Stride of vertices, including optional attributes after the normal
+uniform uint u_floatsPerVertex;
+uniform float u_time;
+uniform float u_amplitude;
+
+const float WAVE_FREQUENCY = 8.0;
+const float WAVE_SPEED = 3.0;
+
+void main()
+{
+    uint vertexIndex = gl_GlobalInvocationID.x;
+    // Work group count is rounded up, last group can have excess invocations
+    if (vertexIndex >= u_vertexCount)
+    {
+        return;
+    }
+
+    uint offset = vertexIndex * u_floatsPerVertex;
+    vec3 position = vec3(sourceVertices[offset],
+                         sourceVertices[offset + 1],
+                         sourceVertices[offset + 2]);
+    vec3 normal = vec3(sourceVertices[offset + 3],
+                       sourceVertices[offset + 4],
+                       sourceVertices[offset + 5]);
+
+    // Waves travelling upwards along the surface
+    float wave = sin(position.y * WAVE_FREQUENCY - u_time * WAVE_SPEED);
+    position += normal * wave * u_amplitude;
+
+    displacedVertices[offset] = position.x;
+    displacedVertices[offset + 1] = position.y;
+    displacedVertices[offset + 2] = position.z;
+    // Normals are left as is, lighting approximates the undisplaced surface
+    displacedVertices[offset + 3] = normal.x;
+    displacedVertices[offset + 4] = normal.y;
+    displacedVertices[offset + 5] = normal.z;
+}
@@ -0,0 +1,200 @@
+//! Whole frame rendered offscreen with `Renderer::render_to_texture()`, including waiting for the
+//! GPU to finish, so both draw submission and GPU time are measured. Scenes of many small objects
+//! compare multi-draw indirect with drawing models one by one.
+//!
+//! Runs in a headless EGL context without a window, so an EGL device is required, e.g. a GPU driver
+//! or the Mesa software rasterizer. Skipped if none is available.
+
+#[cfg(not(target_os = "macos"))]
+mod headless {
+    use std::sync::Arc;
+
+    use cgmath::{vec3, Point3, Vector2};
+    use criterion::{BenchmarkId, Criterion};
+    use glow::HasContext;
+    use glutin::{
+        api::egl::{context::PossiblyCurrentContext, device::Device, display::Display},
+        config::{ConfigSurfaceTypes, ConfigTemplateBuilder},
+        context::{ContextApi, ContextAttributesBuilder, GlProfile, Version},
+        display::GetGlDisplay,
+        prelude::*,
+    };
+    use renderer_rust::{
+        primitives, Camera, DrawProperties, Material, Model, RenderPath, Renderable, Renderer,
+        Scene, Skybox, Transform, Visible,
+    };
+
+    const MESH_PATH: &str = "assets/meshes/bunny.obj";
+    const WIDTH: u32 = 1280;
+    const HEIGHT: u32 = 720;
+    /// Objects along each side of the grid of the multi-draw indirect benchmark.
+    const GRID_SIZE: usize = 32;
+
+    /// OpenGL 4.3 context made current without any surface. Context must be kept alive while the
+    /// returned glow context is used.
+    fn create_context() -> Result<(PossiblyCurrentContext, Arc<glow::Context>), String> {
+        let device = Device::query_devices()
+            .map_err(|e| format!("cannot query EGL devices: {e}"))?
+            .next()
+            .ok_or("no EGL device available")?;
+        let display = unsafe { Display::with_device(&device, None) }
+            .map_err(|e| format!("cannot create EGL display: {e}"))?;
+        // Offscreen rendering doesn't need any surface support
+        let template = ConfigTemplateBuilder::new()
+            .with_surface_type(ConfigSurfaceTypes::empty())
+            .build();
+        let config = unsafe { display.find_configs(template) }
+            .map_err(|e| format!("cannot query EGL configs: {e}"))?
+            .next()
+            .ok_or("no EGL config available")?;
+        let context_attributes = ContextAttributesBuilder::new()
+            .with_profile(GlProfile::Core)
+            .with_context_api(ContextApi::OpenGl(Some(Version::new(4, 3))))
+            .build(None);
+        let context = unsafe { display.create_context(&config, &context_attributes) }
+            .map_err(|e| format!("cannot create OpenGL 4.3 context: {e}"))?
+            .make_current_surfaceless()
+            .map_err(|e| format!("cannot make context current: {e}"))?;
+
+        let gl = unsafe {
+            glow::Context::from_loader_function_cstr(|symbol| display.get_proc_address(symbol))
+        };
+        Ok((context, Arc::new(gl)))
+    }
+
+    pub fn draw_submission(c: &mut Criterion) {
+        let (_context, gl) = match create_context() {
+            Ok(context) => context,
+            Err(e) => {
+                eprintln!("skipping draw submission benchmark: {e}");
+                return;
+            }
+        };
+
+        let mut renderer = Renderer::new(gl.clone()).unwrap();
+        let skybox = Skybox::placeholder(gl.clone()).unwrap();
+        let mut scene = Scene::new(Camera::new(
+            Point3::new(0.0, 0.0, 4.0),
+            Vector2::new(270.0, 0.0),
+        ));
+        scene
+            .models
+            .push(Model::create_from_file(gl.clone(), MESH_PATH).unwrap());
+        scene.spawn_selectable(0);
+        let mut draw_props = DrawProperties {
+            selected_model_index: 0,
+            ..Default::default()
+        };
+        scene.apply_draw_properties(&draw_props);
+
+        let mut group = c.benchmark_group("draw_submission");
+        for render_path in RenderPath::ALL {
+            draw_props.render_path = render_path;
+            group.bench_function(BenchmarkId::from_parameter(render_path.label()), |b| {
+                b.iter(|| {
+                    renderer
+                        .render_to_texture(
+                            &scene.camera,
+                            &draw_props,
+                            &scene,
+                            &skybox,
+                            WIDTH,
+                            HEIGHT,
+                        )
+                        .unwrap();
+                    unsafe { gl.finish() };
+                })
+            });
+        }
+        group.finish();
+    }
+
+    pub fn multi_draw_indirect(c: &mut Criterion) {
+        let (context, gl) = match create_context() {
+            Ok(context) => context,
+            Err(e) => {
+                eprintln!("skipping multi-draw indirect benchmark: {e}");
+                return;
+            }
+        };
+
+        let mut renderer = Renderer::new(gl.clone()).unwrap();
+        if let Err(e) =
+            renderer.load_multi_draw_indirect(|symbol| context.display().get_proc_address(symbol))
+        {
+            eprintln!("skipping multi-draw indirect benchmark: {e}");
+            return;
+        }
+        let skybox = Skybox::placeholder(gl.clone()).unwrap();
+        // Grid seen from above, every object is in view
+        let mut scene = Scene::new(Camera::new(
+            Point3::new(0.0, GRID_SIZE as f32, 0.0),
+            Vector2::new(270.0, -89.0),
+        ));
+        scene.models.push(Model::from_mesh_data(
+            gl.clone(),
+            &primitives::sphere(0.4, 16, 8),
+        ));
+        scene.models.push(Model::from_mesh_data(
+            gl.clone(),
+            &primitives::cuboid(0.6, 0.6, 0.6),
+        ));
+        for i in 0..GRID_SIZE * GRID_SIZE {
+            let (row, column) = (i / GRID_SIZE, i % GRID_SIZE);
+            let offset = GRID_SIZE as f32 / 2.0;
+            scene.world.spawn((
+                Renderable { model_index: i % 2 },
+                Transform {
+                    position: vec3(column as f32 - offset, 0.0, row as f32 - offset),
+                    rotation: [0.0; 3],
+                },
+                Material {
+                    color: [
+                        row as f32 / GRID_SIZE as f32,
+                        column as f32 / GRID_SIZE as f32,
+                        0.5,
+                    ],
+                    ..Default::default()
+                },
+                Visible,
+            ));
+        }
+        let mut draw_props = DrawProperties::default();
+
+        let mut group = c.benchmark_group("multi_draw_indirect");
+        for enabled in [false, true] {
+            draw_props.multi_draw_indirect_enabled = enabled;
+            let label = if enabled { "indirect" } else { "one_by_one" };
+            group.bench_function(BenchmarkId::from_parameter(label), |b| {
+                b.iter(|| {
+                    renderer
+                        .render_to_texture(
+                            &scene.camera,
+                            &draw_props,
+                            &scene,
+                            &skybox,
+                            WIDTH,
+                            HEIGHT,
+                        )
+                        .unwrap();
+                    unsafe { gl.finish() };
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+criterion::criterion_group!(
+    benches,
+    headless::draw_submission,
+    headless::multi_draw_indirect
+);
+#[cfg(not(target_os = "macos"))]
+criterion::criterion_main!(benches);
+
+#[cfg(target_os = "macos")]
+fn main() {
+    eprintln!("skipping draw submission benchmark: EGL is not available on macOS");
+}
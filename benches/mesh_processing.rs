@@ -0,0 +1,42 @@
+//! CPU side of mesh loading: parsing bundled OBJ files, converting them into vertex and index
+//! arrays, and detecting face culling from geometry. Uploading to GPU is not measured.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use renderer_rust::bench::{detect_face_culling, process_obj};
+
+const MESHES: [(&str, &str); 2] = [
+    ("teapot", "assets/meshes/teapot.obj"),
+    ("bunny", "assets/meshes/bunny.obj"),
+];
+
+fn mesh_processing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mesh_processing");
+    // Parsing the high poly bunny takes long, keep the total time reasonable
+    group.sample_size(20);
+
+    for (name, path) in MESHES {
+        group.bench_function(format!("parse_obj/{name}"), |b| {
+            b.iter(|| tobj::load_obj(black_box(path), &tobj::GPU_LOAD_OPTIONS).unwrap())
+        });
+
+        let (models, _) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS).unwrap();
+        group.bench_function(format!("process_obj/{name}"), |b| {
+            b.iter(|| process_obj(black_box(&models)))
+        });
+
+        let mesh_data = process_obj(&models);
+        group.bench_function(format!("detect_face_culling/{name}"), |b| {
+            b.iter(|| {
+                detect_face_culling(
+                    black_box(&mesh_data.vertices),
+                    black_box(&mesh_data.indices),
+                )
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, mesh_processing);
+criterion_main!(benches);
@@ -0,0 +1,79 @@
+//! Per-frame CPU work preceding draw calls: model and normal matrix calculation of every visible
+//! entity, and iterating the ECS world for them.
+
+use cgmath::{Point3, Vector2, Vector3};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use renderer_rust::{
+    bench::{calculate_model_matrix, calculate_normal_matrix},
+    Camera, Material, Renderable, Scene, Transform, Visible,
+};
+
+const ENTITY_COUNTS: [usize; 3] = [1, 100, 10_000];
+
+fn transform(i: usize) -> Transform {
+    let i = i as f32;
+    Transform {
+        position: Vector3::new(i % 100.0, 0.0, (i / 100.0).floor()),
+        rotation: [i % 360.0, (i * 7.0) % 360.0, (i * 13.0) % 360.0],
+    }
+}
+
+/// Scene with entities spawned directly into the world, models are not needed without drawing.
+fn scene(entity_count: usize) -> Scene {
+    let mut scene = Scene::new(Camera::new(
+        Point3::new(0.0, 0.0, 4.0),
+        Vector2::new(270.0, 0.0),
+    ));
+    for i in 0..entity_count {
+        scene.world.spawn((
+            Renderable { model_index: 0 },
+            transform(i),
+            Material::default(),
+            Visible,
+        ));
+    }
+    scene
+}
+
+fn matrices(c: &mut Criterion) {
+    let transform = transform(42);
+    c.bench_function("render_prep/model_matrix", |b| {
+        b.iter(|| calculate_model_matrix(black_box(&transform)))
+    });
+
+    let model_matrix = calculate_model_matrix(&transform);
+    c.bench_function("render_prep/normal_matrix", |b| {
+        b.iter(|| calculate_normal_matrix(black_box(&model_matrix)))
+    });
+}
+
+fn visible_entities(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render_prep/visible_entities");
+    for entity_count in ENTITY_COUNTS {
+        let scene = scene(entity_count);
+        let view = scene.camera.calculate_view_matrix();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(entity_count),
+            &scene,
+            |b, scene| {
+                // Same uniform values as calculated by forward and deferred model draws
+                b.iter(|| {
+                    let mut query = scene
+                        .world
+                        .query::<(&Renderable, &Transform, &Material)>()
+                        .with::<&Visible>();
+                    for (_, (_, transform, material)) in query.iter() {
+                        let model_matrix = calculate_model_matrix(transform);
+                        black_box(view * model_matrix);
+                        black_box(calculate_normal_matrix(&model_matrix));
+                        black_box(material.color);
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, matrices, visible_entities);
+criterion_main!(benches);
@@ -0,0 +1,186 @@
+use std::{fs, path::Path};
+
+// Structural checks of GLSL sources embedded into the binary: naming and desktop or web
+// counterparts of each file, the version directive, default float precision of GLSL ES fragment
+// shaders, and balance of brackets and conditional directives. These are text heuristics without
+// parsing GLSL, so type errors, undeclared identifiers and other compile errors pass the build.
+//
+// Sources are compiled with every shader variant by glslangValidator in the shader_sources_compile
+// test of src/shader_variant.rs, run by `cargo test`. Only the test requires glslang, the build
+// doesn't.
+
+const SHADER_DIR: &str = "assets/shaders";
+
+/// Language dialect of a shader, determined from file name suffix.
+#[derive(Clone, Copy, PartialEq)]
+enum Profile {
+    Gl4,
+    Gles3,
+}
+
+impl Profile {
+    fn version_directive(self) -> &'static str {
+        match self {
+            Profile::Gl4 => "#version 430 core",
+            Profile::Gles3 => "#version 300 es",
+        }
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={SHADER_DIR}");
+
+    let mut file_names: Vec<String> = fs::read_dir(SHADER_DIR)
+        .unwrap_or_else(|e| panic!("cannot read {SHADER_DIR}: {e}"))
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    file_names.sort();
+
+    let mut errors = Vec::new();
+    for file_name in &file_names {
+        let path = Path::new(SHADER_DIR).join(file_name);
+        println!("cargo:rerun-if-changed={}", path.display());
+        if let Err(e) = check_shader(file_name, &path, &file_names) {
+            errors.push(format!("{}: {e}", path.display()));
+        }
+    }
+
+    if !errors.is_empty() {
+        panic!("invalid shader sources:\n{}", errors.join("\n"));
+    }
+}
+
+fn check_shader(file_name: &str, path: &Path, file_names: &[String]) -> Result<(), String> {
+    // Expected naming is <name>_<profile>.<stage>.glsl
+    let (base_name, stage) = file_name
+        .strip_suffix(".glsl")
+        .and_then(|name| name.rsplit_once('.'))
+        .ok_or("file name must end with .<stage>.glsl")?;
+    let (name, profile) = if let Some(name) = base_name.strip_suffix("_gl4") {
+        (name, Profile::Gl4)
+    } else if let Some(name) = base_name.strip_suffix("_gles3") {
+        (name, Profile::Gles3)
+    } else {
+        return Err("file name must have _gl4 or _gles3 suffix".to_string());
+    };
+
+    match (stage, profile) {
+        // Stages of programs with a geometry shader, which is unavailable in GLSL ES 3.00
+        ("vert" | "frag", Profile::Gl4)
+            if file_names.contains(&format!("{name}_gl4.geom.glsl")) => {}
+        ("vert" | "frag", _) => {
+            // Every other graphics shader is needed for both desktop OpenGL 4.3 and WebGL2
+            let (other_suffix, other_profile) = match profile {
+                Profile::Gl4 => ("gles3", "GLSL ES 3.00"),
+                Profile::Gles3 => ("gl4", "GLSL 4.30"),
+            };
+            let counterpart = format!("{name}_{other_suffix}.{stage}.glsl");
+            if !file_names.contains(&counterpart) {
+                return Err(format!("missing {other_profile} counterpart {counterpart}"));
+            }
+        }
+        ("comp" | "geom", Profile::Gl4) => (),
+        ("comp", Profile::Gles3) => {
+            return Err("compute shaders are not available in GLSL ES 3.00".to_string())
+        }
+        ("geom", Profile::Gles3) => {
+            return Err("geometry shaders are not available in GLSL ES 3.00".to_string())
+        }
+        _ => return Err(format!("unknown shader stage '{stage}'")),
+    }
+
+    let source = fs::read_to_string(path).map_err(|e| format!("cannot read shader source: {e}"))?;
+
+    let first_line = source.lines().next().unwrap_or_default().trim();
+    if first_line != profile.version_directive() {
+        return Err(format!(
+            "first line must be '{}', found '{first_line}'",
+            profile.version_directive()
+        ));
+    }
+
+    // Fragment shaders of GLSL ES have no default float precision
+    if profile == Profile::Gles3
+        && stage == "frag"
+        && !source.lines().any(|line| {
+            let line = line.trim();
+            line.starts_with("precision ") && line.ends_with(" float;")
+        })
+    {
+        return Err("fragment shader must declare default float precision".to_string());
+    }
+
+    check_balance(&strip_comments(&source))
+}
+
+fn strip_comments(source: &str) -> String {
+    let mut stripped = String::with_capacity(source.len());
+    let mut rest = source;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("//") {
+            rest = after.find('\n').map_or("", |end| &after[end..]);
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            let end = after.find("*/").map_or(after.len(), |end| end + 2);
+            // Keep line breaks for reporting correct line numbers
+            stripped.extend(after[..end].chars().filter(|&c| c == '\n'));
+            stripped.push(' ');
+            rest = &after[end..];
+        } else {
+            let c = rest.chars().next().unwrap();
+            stripped.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    stripped
+}
+
+/// Brackets and conditional preprocessor directives must be closed in the right order.
+fn check_balance(source: &str) -> Result<(), String> {
+    let mut brackets = Vec::new();
+    let mut conditional_depth = 0;
+    for (line_index, line) in source.lines().enumerate() {
+        let line_number = line_index + 1;
+        let directive = line.trim_start();
+        if directive.starts_with("#if") {
+            conditional_depth += 1;
+        } else if directive.starts_with("#endif") {
+            if conditional_depth == 0 {
+                return Err(format!("line {line_number}: #endif without #if"));
+            }
+            conditional_depth -= 1;
+        }
+
+        for c in line.chars() {
+            match c {
+                '(' | '[' | '{' => brackets.push((c, line_number)),
+                ')' | ']' | '}' => {
+                    let expected = match c {
+                        ')' => '(',
+                        ']' => '[',
+                        _ => '{',
+                    };
+                    match brackets.pop() {
+                        Some((open, _)) if open == expected => (),
+                        Some((open, open_line)) => {
+                            return Err(format!(
+                                "line {line_number}: '{c}' closes '{open}' opened on line \
+                                 {open_line}"
+                            ))
+                        }
+                        None => return Err(format!("line {line_number}: unmatched '{c}'")),
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    if let Some((open, open_line)) = brackets.pop() {
+        return Err(format!("line {open_line}: '{open}' is never closed"));
+    }
+    if conditional_depth > 0 {
+        return Err("#if without #endif".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,270 @@
+//! egui_glow application embedding the renderer as a widget next to its own panels, e.g. `cargo
+//! run --example egui_viewport -- assets/meshes/bunny.obj`.
+//!
+//! Renderer and GUI painter share the OpenGL context of the window. The renderer forgets the state
+//! it knows on every call, so state left behind by the painter doesn't leak into the scene.
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> Result<(), String> {
+    let model_path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "assets/meshes/teapot.obj".to_owned());
+    let event_loop = winit::event_loop::EventLoop::new().map_err(|e| e.to_string())?;
+    let mut host = native::Host::new(model_path);
+    event_loop
+        .run_app(&mut host)
+        .map_err(|e| format!("event loop failed: {e}"))?;
+    host.error.map_or(Ok(()), Err)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::{num::NonZeroU32, sync::Arc};
+
+    use egui_glow::EguiGlow;
+    use glow::HasContext;
+    use glutin::{
+        config::{ConfigTemplateBuilder, GlConfig},
+        context::{
+            ContextApi, ContextAttributesBuilder, GlProfile, PossiblyCurrentContext, Version,
+        },
+        display::GetGlDisplay,
+        prelude::*,
+        surface::{Surface, SurfaceAttributesBuilder, WindowSurface},
+    };
+    use glutin_winit::{DisplayBuilder, GlWindow};
+    use raw_window_handle::HasWindowHandle;
+    use renderer_rust::{Camera, DrawProperties, Model, Renderer, Scene, Skybox, ViewportWidget};
+    use winit::{
+        application::ApplicationHandler,
+        dpi::LogicalSize,
+        event::WindowEvent,
+        event_loop::ActiveEventLoop,
+        window::{Window, WindowAttributes, WindowId},
+    };
+
+    /// Everything created once the window is available.
+    struct Running {
+        window: Window,
+        surface: Surface<WindowSurface>,
+        context: PossiblyCurrentContext,
+        gl: Arc<glow::Context>,
+        egui_glow: EguiGlow,
+        renderer: Renderer,
+        skybox: Skybox,
+        scene: Scene,
+        draw_props: DrawProperties,
+        viewport: ViewportWidget,
+    }
+
+    pub struct Host {
+        model_path: String,
+        running: Option<Running>,
+        /// Failure ending the event loop, reported by `main()`.
+        pub error: Option<String>,
+    }
+
+    impl Host {
+        pub fn new(model_path: String) -> Self {
+            Self {
+                model_path,
+                running: None,
+                error: None,
+            }
+        }
+    }
+
+    impl ApplicationHandler for Host {
+        fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+            if self.running.is_some() {
+                return;
+            }
+            match create(event_loop, &self.model_path) {
+                Ok(running) => self.running = Some(running),
+                Err(e) => {
+                    self.error = Some(e);
+                    event_loop.exit();
+                }
+            }
+        }
+
+        fn window_event(
+            &mut self,
+            event_loop: &ActiveEventLoop,
+            _window_id: WindowId,
+            event: WindowEvent,
+        ) {
+            let Some(running) = self.running.as_mut() else {
+                return;
+            };
+            if running
+                .egui_glow
+                .on_window_event(&running.window, &event)
+                .repaint
+            {
+                running.window.request_redraw();
+            }
+            match event {
+                WindowEvent::CloseRequested => {
+                    running.egui_glow.destroy();
+                    self.running = None;
+                    event_loop.exit();
+                }
+                WindowEvent::Resized(size) => {
+                    if let (Some(width), Some(height)) =
+                        (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
+                    {
+                        running.surface.resize(&running.context, width, height);
+                    }
+                }
+                WindowEvent::RedrawRequested => running.redraw(),
+                _ => (),
+            }
+        }
+    }
+
+    impl Running {
+        fn redraw(&mut self) {
+            let Running {
+                window,
+                egui_glow,
+                renderer,
+                skybox,
+                scene,
+                draw_props,
+                viewport,
+                ..
+            } = self;
+            // Frame is run by hand instead of `EguiGlow::run()`, because the widget registers its
+            // texture with the painter while the UI is built
+            let raw_input = egui_glow.egui_winit.take_egui_input(window);
+            let painter = &mut egui_glow.painter;
+            let full_output = egui_glow.egui_ctx.run(raw_input, |egui_ctx| {
+                egui::SidePanel::left("settings").show(egui_ctx, |ui| {
+                    ui.heading("Host panel");
+                    ui.checkbox(&mut draw_props.wireframe_mode_enabled, "Wireframe");
+                    ui.add(
+                        egui::Slider::new(&mut draw_props.field_of_view, 30.0..=120.0)
+                            .text("Field of view"),
+                    );
+                    ui.label(format!("Camera: {:.1?}", viewport.camera().position()));
+                });
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::none())
+                    .show(egui_ctx, |ui| {
+                        viewport.show(ui, renderer, draw_props, scene, skybox, |texture| {
+                            painter.register_native_texture(texture)
+                        });
+                    });
+            });
+            egui_glow
+                .egui_winit
+                .handle_platform_output(window, full_output.platform_output);
+
+            unsafe {
+                self.gl.clear_color(0.1, 0.1, 0.1, 1.0);
+                self.gl.clear(glow::COLOR_BUFFER_BIT);
+            }
+            for (id, image_delta) in &full_output.textures_delta.set {
+                painter.set_texture(*id, image_delta);
+            }
+            let clipped_primitives = egui_glow
+                .egui_ctx
+                .tessellate(full_output.shapes, full_output.pixels_per_point);
+            painter.paint_primitives(
+                window.inner_size().into(),
+                full_output.pixels_per_point,
+                &clipped_primitives,
+            );
+            for id in &full_output.textures_delta.free {
+                painter.free_texture(*id);
+            }
+            let _ = self.surface.swap_buffers(&self.context);
+            // Scene is redrawn continuously, like in a game engine editor
+            window.request_redraw();
+        }
+    }
+
+    fn create(event_loop: &ActiveEventLoop, model_path: &str) -> Result<Running, String> {
+        let window_attributes = WindowAttributes::default()
+            .with_title("egui_glow host with renderer viewport")
+            .with_inner_size(LogicalSize::new(1024, 640));
+        let (window, gl_config) = DisplayBuilder::new()
+            .with_window_attributes(Some(window_attributes))
+            .build(event_loop, ConfigTemplateBuilder::default(), |configs| {
+                configs
+                    .reduce(|accum, config| match config.srgb_capable() {
+                        true if !accum.srgb_capable() => config,
+                        _ => accum,
+                    })
+                    .unwrap()
+            })
+            .map_err(|e| format!("cannot create window: {e}"))?;
+        let window = window.ok_or("cannot create window")?;
+
+        let raw_window_handle = window
+            .window_handle()
+            .map_err(|e| format!("cannot get window handle: {e}"))?
+            .as_raw();
+        let context_attributes = ContextAttributesBuilder::new()
+            .with_profile(GlProfile::Core)
+            .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
+            .build(Some(raw_window_handle));
+        let display = gl_config.display();
+        let not_current_context =
+            unsafe { display.create_context(&gl_config, &context_attributes) }
+                .map_err(|e| format!("cannot create OpenGL context: {e}"))?;
+        let surface_attributes = window
+            .build_surface_attributes(
+                SurfaceAttributesBuilder::default().with_srgb(Some(gl_config.srgb_capable())),
+            )
+            .map_err(|e| format!("cannot build surface attributes: {e}"))?;
+        let surface = unsafe { display.create_window_surface(&gl_config, &surface_attributes) }
+            .map_err(|e| format!("cannot create window surface: {e}"))?;
+        let context = not_current_context
+            .make_current(&surface)
+            .map_err(|e| format!("cannot make context current: {e}"))?;
+        let gl = Arc::new(unsafe {
+            glow::Context::from_loader_function_cstr(|symbol| display.get_proc_address(symbol))
+        });
+
+        let egui_glow = EguiGlow::new(
+            event_loop,
+            gl.clone(),
+            None,
+            Some(window.scale_factor() as f32),
+            true,
+        );
+        let renderer = Renderer::new(gl.clone())?;
+        let skybox = Skybox::placeholder(gl.clone()).map_err(|e| e.to_string())?;
+        let mut scene = Scene::new(Camera::new(
+            cgmath::Point3::new(0.0, 0.0, 4.0),
+            cgmath::Vector2::new(270.0, 0.0),
+        ));
+        scene
+            .models
+            .push(Model::create_from_file(gl.clone(), model_path)?.with_name("model"));
+        scene.spawn_selectable(0);
+        let draw_props = DrawProperties {
+            skybox_enabled: false,
+            light_gizmo_enabled: false,
+            ..Default::default()
+        };
+
+        Ok(Running {
+            window,
+            surface,
+            context,
+            gl,
+            egui_glow,
+            renderer,
+            skybox,
+            scene,
+            draw_props,
+            viewport: ViewportWidget::default(),
+        })
+    }
+}
@@ -0,0 +1,62 @@
+/*
+ * C API of the renderer library built with the "ffi" feature, for embedding the
+ * renderer into a window of another application, e.g. as a viewport widget.
+ *
+ * Functions returning int return 0 on success and -1 on failure, with the
+ * message of the failure available from renderer_last_error(). A renderer must
+ * only be used on the thread that created it.
+ */
+#ifndef RENDERER_RUST_H
+#define RENDERER_RUST_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Windowing systems of the native handles passed to renderer_create(). */
+#define RENDERER_PLATFORM_XLIB 0    /* window: Window id cast to a pointer, display: Display* */
+#define RENDERER_PLATFORM_WAYLAND 1 /* window: wl_surface*, display: wl_display* */
+#define RENDERER_PLATFORM_WIN32 2   /* window: HWND, display: HINSTANCE or NULL */
+#define RENDERER_PLATFORM_APPKIT 3  /* window: NSView*, display: ignored */
+
+typedef struct RendererContext RendererContext;
+
+/* Create a renderer drawing into the window, or return NULL on failure.
+ * Handles must stay valid until the renderer is destroyed. */
+RendererContext *renderer_create(uint32_t platform, void *window, void *display,
+                                 uint32_t width, uint32_t height);
+
+/* Destroy the renderer and its OpenGL context. NULL is ignored. */
+void renderer_destroy(RendererContext *context);
+
+/* Message of the last failed call on the calling thread, empty if none failed
+ * yet. Valid until the next failing call on the same thread. */
+const char *renderer_last_error(void);
+
+/* Load an OBJ or PLY file and select it as the displayed model. Returns the
+ * index of the model, or -1 on failure. */
+int32_t renderer_load_model(RendererContext *context, const char *path);
+
+/* Set a draw property by its field name, e.g. "exposure" from 1 value or
+ * "background_color" from 3 values. */
+int32_t renderer_set_property(RendererContext *context, const char *name,
+                              const float *values, size_t count);
+
+/* Place the camera at the position, turned to yaw, pitch and roll in degrees. */
+void renderer_set_camera(RendererContext *context, const float position[3],
+                         const float rotation[3]);
+
+/* Resize the drawn area after the window was resized, in pixels. */
+void renderer_resize(RendererContext *context, uint32_t width, uint32_t height);
+
+/* Draw a frame into the window and present it. */
+int32_t renderer_render_frame(RendererContext *context);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
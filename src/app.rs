@@ -1,7 +1,7 @@
 use std::{cell::RefCell, sync::Arc};
 
 use cfg_if::cfg_if;
-use cgmath::{Point3, Vector2};
+use cgmath::{Matrix4, Point3, Vector2};
 use winit::{
     application::ApplicationHandler,
     event::{DeviceEvent, ElementState, KeyEvent, MouseButton, WindowEvent},
@@ -10,17 +10,28 @@ use winit::{
     window::{CursorGrabMode, Window, WindowAttributes},
 };
 
-use crate::{assets, Camera, DrawProperties, Gui, Model, Renderer, Skybox};
+use crate::{
+    assets, gizmo, gl_resources, gl_state,
+    input::{Input, InputEvent, InputState},
+    profiler::{self, profile_scope},
+    quality_scaler::QualityScaler,
+    AssetCache, Camera, DrawProperties, GlInfo, Gui, Mesh, MeshData, Model, RenderHook, Renderer,
+    Scene, Skybox,
+};
 
 cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
     use std::{
         num::NonZeroU32,
+        path::Path,
         time::Duration,
     };
 
     use glutin::{
         config::{Config, ConfigTemplateBuilder},
-        context::{ContextApi, ContextAttributesBuilder, PossiblyCurrentContext, Version},
+        context::{
+            ContextApi, ContextAttributes, ContextAttributesBuilder, GlProfile,
+            NotCurrentContext, PossiblyCurrentContext, Version,
+        },
         display::GetGlDisplay,
         prelude::*,
         surface::{Surface, SurfaceAttributesBuilder, SwapInterval, WindowSurface},
@@ -32,20 +43,51 @@ cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
         platform::pump_events::{EventLoopExtPumpEvents, PumpStatus}
     };
 
-    use crate::FrameRateInfo;
-    use crate::SkyboxFileBuilder;
+    use serde::Serialize;
+
+    use crate::{
+        Decal, FrameRateInfo, GuiAction, LightmapBake, Material, MeshRepair, Ray, Renderable,
+        Selectable, Transform, Winding, MAX_DECALS,
+    };
+    use crate::{
+        asset_watcher::AssetWatcher, frame_sequence::FrameSequenceRecorder,
+        input::{InputPlayback, InputRecorder}, mesh_export, osc::OscListener,
+        remote::RemoteCommand, renderer::calculate_model_matrix, swap_control, CameraTransition,
+        SkyboxFileBuilder, Terrain, TerrainSettings, VsyncMode,
+    };
+    #[cfg(feature = "xr")]
+    use crate::XrSession;
+    #[cfg(feature = "audio")]
+    use crate::AudioAnalyzer;
+    use std::net::SocketAddr;
+    #[cfg(feature = "remote")]
+    use crate::remote_server::{RemoteRequest, RemoteServer};
+    #[cfg(feature = "http")]
+    use crate::snapshot_server::SnapshotServer;
 } else {
+    use std::rc::{Rc, Weak};
+
     use wasm_bindgen::prelude::*;
-    use web_sys::{HtmlCanvasElement, WebGl2RenderingContext};
-    use winit::platform::web::WindowAttributesExtWebSys;
+    use web_sys::{HtmlCanvasElement, WebGl2RenderingContext, XrFrame, XrSessionMode};
+    use winit::platform::web::{WindowAttributesExtWebSys, WindowExtWebSys};
 
-    use crate::HtmlUI;
+    use crate::{web_xr, HtmlUI, WebXrSession};
     use crate::SkyboxBufferBuilder;
 }}
 
 cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
     const WINDOW_WIDTH: u32 = 1024;
     const WINDOW_HEIGHT: u32 = 768;
+
+    /// OpenGL context versions requested in order of preference. OpenGL 4.3 enables every feature.
+    /// Older drivers and macOS (limited to OpenGL 4.1) fall back to the shader variants of the web
+    /// build, so do systems with only OpenGL ES available, e.g. through ANGLE.
+    const GL_CONTEXT_CANDIDATES: [(&str, ContextApi); 4] = [
+        ("OpenGL 4.3 core", ContextApi::OpenGl(Some(Version::new(4, 3)))),
+        ("OpenGL 4.1 core", ContextApi::OpenGl(Some(Version::new(4, 1)))),
+        ("OpenGL 3.3 core", ContextApi::OpenGl(Some(Version::new(3, 3)))),
+        ("OpenGL ES 3.0", ContextApi::Gles(Some(Version::new(3, 0)))),
+    ];
 }}
 const WINDOW_TITLE: &str = "3D Renderer in Rust by Bálint Kiss";
 
@@ -61,49 +103,17 @@ const WINDOW_TITLE: &str = "3D Renderer in Rust by Bálint Kiss";
 const MAX_LOGIC_UPDATE_PER_SECOND: f32 = 60.0;
 const FIXED_UPDATE_TIMESTEP: f32 = 1.0 / MAX_LOGIC_UPDATE_PER_SECOND;
 
-enum InputEvent {
-    MoveForward,
-    MoveBackward,
-    StrafeLeft,
-    StrafeRight,
-    Ascend,
-    Descend,
-}
-
-// Using array instead of HashSet results in a single jump table which is more friendlier to cache,
-// avoids heap allocation and hash function calls for HashSet, has better branch prediction and has
-// fewer CPU instructions.
-//
-// (Even though gains are negligable, because bottleneck is usually not the input handling)
-type InputState = [bool; 6];
-
-impl std::ops::Index<InputEvent> for InputState {
-    type Output = bool;
-
-    fn index(&self, e: InputEvent) -> &Self::Output {
-        match e {
-            InputEvent::MoveForward => &self[0],
-            InputEvent::MoveBackward => &self[1],
-            InputEvent::StrafeLeft => &self[2],
-            InputEvent::StrafeRight => &self[3],
-            InputEvent::Ascend => &self[4],
-            InputEvent::Descend => &self[5],
-        }
-    }
+/// Parts of the scene that host applications can change procedurally on each logic update.
+pub struct SceneState<'a> {
+    /// Entities, models and camera. Transform of the model selected in GUI is overwritten by
+    /// `draw_props`, other components can be changed freely.
+    pub scene: &'a mut Scene,
+    /// Model transformation, lighting and every other setting adjustable from GUI.
+    pub draw_props: &'a mut DrawProperties,
 }
 
-impl std::ops::IndexMut<InputEvent> for InputState {
-    fn index_mut(&mut self, e: InputEvent) -> &mut Self::Output {
-        match e {
-            InputEvent::MoveForward => &mut self[0],
-            InputEvent::MoveBackward => &mut self[1],
-            InputEvent::StrafeLeft => &mut self[2],
-            InputEvent::StrafeRight => &mut self[3],
-            InputEvent::Ascend => &mut self[4],
-            InputEvent::Descend => &mut self[5],
-        }
-    }
-}
+/// Called on each fixed timestep logic update with the length of the timestep in seconds.
+type UpdateCallback = Box<dyn FnMut(&mut SceneState, f32)>;
 
 /// Encapsulation of renderer application lifecycle and logic update to avoid
 /// polluting main().
@@ -112,22 +122,86 @@ pub struct App {
     #[cfg(not(target_arch = "wasm32"))]
     glutin_window_context: Option<GlutinWindowContext>,
     #[cfg(not(target_arch = "wasm32"))]
-    vsync_enabled: bool,
+    vsync_mode: VsyncMode,
     #[cfg(not(target_arch = "wasm32"))]
     frame_rate_info: FrameRateInfo,
+    // Save next rendered frame before GUI is drawn on top of it
+    #[cfg(not(target_arch = "wasm32"))]
+    screenshot_requested: bool,
+    // File name of the requested screenshot, named after the current time if not given
+    #[cfg(not(target_arch = "wasm32"))]
+    screenshot_name: Option<String>,
+    // Saved into the working directory if not given
+    #[cfg(not(target_arch = "wasm32"))]
+    screenshot_directory: Option<String>,
+    // Timeline export in progress, saving every rendered frame
+    #[cfg(not(target_arch = "wasm32"))]
+    frame_sequence_recorder: Option<FrameSequenceRecorder>,
+    // Lighting being traced on a worker thread, uploaded when done
+    #[cfg(not(target_arch = "wasm32"))]
+    lightmap_bake: Option<LightmapBake>,
+    #[cfg(not(target_arch = "wasm32"))]
+    asset_watcher: Option<AssetWatcher>,
+    #[cfg(not(target_arch = "wasm32"))]
+    osc_listener: Option<OscListener>,
+    #[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+    remote_server: Option<RemoteServer>,
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    snapshot_server: Option<SnapshotServer>,
+    // Logic updates run since start, input is recorded and played back by these indices
+    #[cfg(not(target_arch = "wasm32"))]
+    update_count: u64,
+    #[cfg(not(target_arch = "wasm32"))]
+    input_recorder: Option<InputRecorder>,
+    #[cfg(not(target_arch = "wasm32"))]
+    input_playback: Option<InputPlayback>,
+    #[cfg(not(target_arch = "wasm32"))]
+    exit_after_playback: bool,
+    #[cfg(all(feature = "xr", not(target_arch = "wasm32")))]
+    xr_enabled: bool,
+    #[cfg(all(feature = "xr", not(target_arch = "wasm32")))]
+    xr_session: Option<XrSession>,
+    #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+    audio_enabled: bool,
+    // Levels of its frequency bands drive the modulation of the scene
+    #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+    audio_analyzer: Option<AudioAnalyzer>,
+    /// Window surface is released and logic clock is paused between `suspended()` and `resumed()`.
+    suspended: bool,
     renderer: Option<Renderer>,
     // Pushing pressed keys from event loop into this collection and processing in update() makes
     // movement continous. Naively checking for key press during event consumption leads to choppy
     // movement.
     input_state: InputState,
     right_mouse_pressed: bool,
+    // Physical pixel position of cursor inside window
+    cursor_position: (f32, f32),
+    light_dragging: bool,
     draw_props: Arc<RefCell<DrawProperties>>,
-    camera: Camera,
-    skybox: Option<Skybox>,
-    models: Vec<Model>,
+    scene: Scene,
+    scene_description: SceneDescription,
+    // Handed over to renderer once it's created
+    render_hooks: Vec<Box<dyn RenderHook>>,
+    stencil_bits: u8,
+    ui_scale: Option<f32>,
+    update_callback: Option<UpdateCallback>,
+    quality_scaler: QualityScaler,
+    asset_cache: Option<AssetCache>,
+    skybox: Option<Arc<Skybox>>,
     gui: Option<Gui>,
     #[cfg(target_arch = "wasm32")]
     html_ui: Option<HtmlUI>,
+    #[cfg(target_arch = "wasm32")]
+    web_xr_session: Option<WebXrSession>,
+    /// Web: logic updates owed to slowed down or sped up time, run once a whole one accumulates.
+    #[cfg(target_arch = "wasm32")]
+    update_credit: f32,
+    /// Web: timestamp of the previous animation frame in milliseconds.
+    #[cfg(target_arch = "wasm32")]
+    previous_frame_time: Option<f64>,
+    /// Web: handle of the app shared with browser callbacks, e.g. with WebXR frames.
+    #[cfg(target_arch = "wasm32")]
+    shared: Weak<RefCell<App>>,
 }
 
 impl ApplicationHandler for App {
@@ -137,109 +211,166 @@ impl ApplicationHandler for App {
     //
     // Web: WindowEvent::Resumed is emitted in response to `pageshow` event.
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
-            let (window, glutin_window_context, gl) = match initialize_native_window(&event_loop) {
-                Ok(v) => v,
-                Err(e) => {
-                    eprintln!("unable to initialize native window: {:?}", e);
+        // Resumed after suspension, GL context and resources are still alive. Only the window
+        // surface has to be recreated.
+        if self.window.is_some() {
+            #[cfg(not(target_arch = "wasm32"))]
+            if let (Some(window), Some(glutin_window_context)) =
+                (self.window.as_ref(), self.glutin_window_context.as_mut())
+            {
+                if let Err(e) = glutin_window_context.resume(window) {
+                    // Nothing to display errors with
+                    eprintln!("unable to resume native window: {e}");
+                    event_loop.exit();
                     return;
                 }
-            };
-            self.vsync_enabled = self.draw_props.borrow().vsync_enabled;
-            glutin_window_context.set_vsync_enabled(self.vsync_enabled);
-            let gl = Arc::new(gl);
+                glutin_window_context.set_vsync_mode(self.vsync_mode);
+            }
+            self.suspended = false;
+            return;
+        }
 
-            let skybox = match SkyboxFileBuilder::new()
-                .with_right(assets::skybox::RIGHT_FACE_PATH)
-                .with_left(assets::skybox::LEFT_FACE_PATH)
-                .with_top(assets::skybox::TOP_FACE_PATH)
-                .with_bottom(assets::skybox::BOTTOM_FACE_PATH)
-                .with_front(assets::skybox::FRONT_FACE_PATH)
-                .with_back(assets::skybox::BACK_FACE_PATH)
-                .build(gl.clone()) {
-                    Ok(s) => s,
+        cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
+            let (window, glutin_window_context, gl) =
+                match initialize_native_window(&event_loop, self.stencil_bits) {
+                    Ok(v) => v,
                     Err(e) => {
-                        eprintln!("unable to create skybox for application: {e}");
+                        // Nothing to display errors with
+                        eprintln!("unable to initialize native window: {:?}", e);
+                        event_loop.exit();
                         return;
                     }
                 };
-
-            let model_paths = [
-                assets::model::CUBE_PATH,
-                assets::model::TEAPOT_PATH,
-                assets::model::BUNNY_PATH,
-            ];
-            let mut models: Vec<Model> = Vec::with_capacity(model_paths.len());
-            for model_path in &model_paths {
-                match Model::create_from_file(gl.clone(), model_path) {
-                    Ok(m) => models.push(m),
-                    Err(e) => {
-                        eprintln!("unable to create model from path {model_path}: {e}");
-                        return;
-                    }
-                }
-            }
+            self.vsync_mode = self.draw_props.borrow().vsync_mode;
+            glutin_window_context.set_vsync_mode(self.vsync_mode);
+            let gl = Arc::new(gl);
         } else {
-            let (window, gl) = match initialize_web_window(&event_loop) {
+            let (window, gl) = match initialize_web_window(&event_loop, self.stencil_bits) {
                 Ok(v) => v,
                 Err(e) => {
+                    // Nothing to display errors with
                     eprintln!("unable to initialize web window: {:?}", e);
                     return;
                 }
             };
             let gl = Arc::new(gl);
+        }}
 
-            let skybox = match SkyboxBufferBuilder::new()
-                .with_right(assets::skybox::RIGHT_FACE_BYTES)
-                .with_left(assets::skybox::LEFT_FACE_BYTES)
-                .with_top(assets::skybox::TOP_FACE_BYTES)
-                .with_bottom(assets::skybox::BOTTOM_FACE_BYTES)
-                .with_front(assets::skybox::FRONT_FACE_BYTES)
-                .with_back(assets::skybox::BACK_FACE_BYTES)
-                .build(gl.clone()) {
-                    Ok(s) => s,
-                    Err(e) => {
-                        eprintln!("unable to create skybox for application: {e}");
-                        return;
-                    }
-                };
+        let mut asset_cache = AssetCache::new(gl.clone());
+        let mut errors = Vec::new();
 
-            let model_binaries: &[&'static [u8]] = &[
-                assets::model::CUBE_BYTES,
-                assets::model::TEAPOT_BYTES,
-                assets::model::BUNNY_BYTES,
-            ];
-            let mut models: Vec<Model> = Vec::with_capacity(model_binaries.len());
-            for model_data in model_binaries {
-                match Model::create_from_buffer(gl.clone(), model_data) {
-                    Ok(m) => models.push(m),
-                    Err(e) => {
-                        eprintln!("unable to create model: {e}");
-                        return;
-                    }
+        // Black background is displayed instead of missing skybox
+        let skybox = match self.scene_description.skybox.clone() {
+            Some(skybox_builder) => match asset_cache.load_skybox(skybox_builder) {
+                Ok(s) => Some(s),
+                Err(e) => {
+                    errors.push(format!("unable to create skybox for application: {e}"));
+                    Skybox::placeholder(gl.clone()).ok().map(Arc::new)
+                }
+            },
+            None => Skybox::placeholder(gl.clone()).ok().map(Arc::new),
+        };
+
+        self.scene.clear_models();
+        for model_description in &self.scene_description.models {
+            let mesh = match model_description.load_mesh(&mut asset_cache) {
+                Ok(mesh) => mesh,
+                Err(e) => {
+                    errors.push(format!(
+                        "unable to create model {}: {e}",
+                        model_description.name
+                    ));
+                    Arc::new(Mesh::empty(gl.clone()))
                 }
+            };
+            self.scene
+                .models
+                .push(Model::from_mesh(mesh).with_name(&model_description.name));
+            self.scene.spawn_selectable(self.scene.models.len() - 1);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(terrain_description) = &self.scene_description.terrain {
+            match Terrain::create_from_file(
+                gl.clone(),
+                &terrain_description.path,
+                &terrain_description.settings,
+            ) {
+                Ok(terrain) => self.scene.terrain = Some(terrain),
+                Err(e) => errors.push(format!("unable to create terrain: {e}")),
             }
-        }}
+        }
 
         let renderer = match Renderer::new(gl.clone()) {
-            Ok(r) => r,
+            Ok(mut r) => {
+                for render_hook in self.render_hooks.drain(..) {
+                    r.add_render_hook(render_hook);
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Err(e) = r.load_multi_draw_indirect(|symbol| {
+                    glutin_window_context
+                        .glutin_context
+                        .display()
+                        .get_proc_address(symbol)
+                }) {
+                    println!("{e}, models are drawn one by one");
+                }
+                Some(r)
+            }
             Err(e) => {
-                eprintln!("unable to create renderer: {e}");
-                return;
+                errors.push(format!("unable to create renderer: {e}"));
+                None
             }
         };
-        let gui = Gui::new(&event_loop, gl.clone());
+        // Window keeps displaying the scene when headset is not available
+        #[cfg(all(feature = "xr", not(target_arch = "wasm32")))]
+        if self.xr_enabled {
+            match XrSession::new(
+                gl.clone(),
+                &glutin_window_context.glutin_context,
+                glutin_window_context.glutin_surface.as_ref().unwrap(),
+            ) {
+                Ok(xr_session) => self.xr_session = Some(xr_session),
+                Err(e) => errors.push(format!("unable to start VR session: {e}")),
+            }
+        }
+        #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+        if self.audio_enabled && self.audio_analyzer.is_none() {
+            match AudioAnalyzer::new() {
+                Ok(audio_analyzer) => self.audio_analyzer = Some(audio_analyzer),
+                Err(e) => errors.push(format!("unable to start audio input: {e}")),
+            }
+        }
+        let gl_info = renderer
+            .as_ref()
+            .map_or_else(|| GlInfo::query(&gl), |r| r.gl_info().clone());
+        let mut gui = Gui::new(&event_loop, &window, gl.clone(), gl_info);
+        if let Some(ui_scale) = self.ui_scale {
+            gui.override_ui_scale(ui_scale);
+        }
+        for error in &errors {
+            eprintln!("{error}");
+        }
+        gui.report_errors(errors);
 
         self.window = Some(window);
-        self.renderer = Some(renderer);
-        self.skybox = Some(skybox);
-        self.models = models;
+        // Scene can't be rendered without renderer or skybox, only errors are displayed then
+        self.renderer = renderer.filter(|_| skybox.is_some());
+        if let Some(renderer) = self.renderer.as_mut() {
+            renderer.set_scale_factor(self.window.as_ref().unwrap().scale_factor() as f32);
+        }
+        self.asset_cache = Some(asset_cache);
+        self.skybox = skybox;
         self.gui = Some(gui);
 
         cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
             self.glutin_window_context = Some(glutin_window_context);
         } else {
-            let html_ui = HtmlUI::new(self.draw_props.clone());
+            let model_names: Vec<&str> =
+                self.scene.models.iter().map(|m| m.name.as_str()).collect();
+            let shared = self.shared.clone();
+            let html_ui = HtmlUI::new(self.draw_props.clone(), &model_names, move |mode| {
+                App::request_web_xr_session(&shared, mode)
+            });
             self.html_ui = Some(html_ui);
         }}
     }
@@ -275,12 +406,18 @@ impl ApplicationHandler for App {
                     .unwrap()
                     .resize(physical_size.width, physical_size.height);
 
-                let field_of_view = self.draw_props.borrow().field_of_view;
-                self.renderer.as_mut().unwrap().resize(
-                    physical_size.width,
-                    physical_size.height,
-                    field_of_view,
-                );
+                if let Some(renderer) = self.renderer.as_mut() {
+                    let field_of_view = self.draw_props.borrow().field_of_view;
+                    renderer.resize(physical_size.width, physical_size.height, field_of_view);
+                }
+            }
+            // Window moved to a display with different scale factor, or display settings changed.
+            // Physical size of the window follows with a Resized event. GUI handles the event
+            // itself.
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                if let Some(renderer) = self.renderer.as_mut() {
+                    renderer.set_scale_factor(scale_factor as f32);
+                }
             }
             WindowEvent::KeyboardInput {
                 event:
@@ -300,15 +437,21 @@ impl ApplicationHandler for App {
                     KeyCode::KeyD => InputEvent::StrafeRight,
                     KeyCode::Space => InputEvent::Ascend,
                     KeyCode::KeyC => InputEvent::Descend,
+                    KeyCode::KeyQ => InputEvent::RollLeft,
+                    KeyCode::KeyE => InputEvent::RollRight,
                     _ => return,
                 };
-                self.input_state[input_event] = state == ElementState::Pressed;
+                self.handle_input(Input::Key {
+                    event: input_event,
+                    pressed: state == ElementState::Pressed,
+                });
             }
             WindowEvent::MouseInput {
                 button: MouseButton::Right,
                 state,
                 ..
             } => {
+                let raw_mouse_input = self.raw_mouse_input();
                 let window = &mut self.window.as_mut().unwrap();
                 self.right_mouse_pressed = state == ElementState::Pressed;
                 match state {
@@ -316,9 +459,14 @@ impl ApplicationHandler for App {
                     // Web: Doesn't support CursorGrabMode::Confined
                     ElementState::Pressed => {
                         window.set_cursor_visible(false);
+                        // Locked cursor doesn't report cursor movement
+                        let grab_modes = match raw_mouse_input {
+                            true => [CursorGrabMode::Locked, CursorGrabMode::Confined],
+                            false => [CursorGrabMode::Confined, CursorGrabMode::Locked],
+                        };
                         window
-                            .set_cursor_grab(CursorGrabMode::Locked)
-                            .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
+                            .set_cursor_grab(grab_modes[0])
+                            .or_else(|_| window.set_cursor_grab(grab_modes[1]))
                             .unwrap();
                     }
                     ElementState::Released => {
@@ -327,8 +475,11 @@ impl ApplicationHandler for App {
                         // Web: Doesn't support changing cursor position
                         #[cfg(not(target_arch = "wasm32"))]
                         {
+                            // Physical size differs from the logical window size on scaled
+                            // displays
+                            let size = window.inner_size();
                             let window_center_pos =
-                                PhysicalPosition::new(WINDOW_WIDTH / 2, WINDOW_HEIGHT / 2);
+                                PhysicalPosition::new(size.width / 2, size.height / 2);
                             let _ = window.set_cursor_position(window_center_pos);
                         }
                         window.set_cursor_grab(CursorGrabMode::None).unwrap();
@@ -336,6 +487,65 @@ impl ApplicationHandler for App {
                     }
                 }
             }
+            WindowEvent::CursorMoved { position, .. } => {
+                let previous_position = self.cursor_position;
+                self.cursor_position = (position.x as f32, position.y as f32);
+                #[cfg(not(target_arch = "wasm32"))]
+                if self.right_mouse_pressed && !self.raw_mouse_input() {
+                    self.handle_input(Input::Look {
+                        x_offset: self.cursor_position.0 - previous_position.0,
+                        y_offset: self.cursor_position.1 - previous_position.1,
+                    });
+                    // Keep cursor away from window edges. Next event reports the cursor at the
+                    // center, so moving it there is not counted as movement.
+                    //
+                    // Wayland: Doesn't support changing cursor position, movement stops at the
+                    // edges of the window.
+                    let window = self.window.as_ref().unwrap();
+                    let size = window.inner_size();
+                    let window_center_pos = PhysicalPosition::new(size.width / 2, size.height / 2);
+                    if window.set_cursor_position(window_center_pos).is_ok() {
+                        self.cursor_position =
+                            (window_center_pos.x as f32, window_center_pos.y as f32);
+                    }
+                }
+                if self.light_dragging {
+                    self.drag_light();
+                }
+            }
+            WindowEvent::MouseInput {
+                button: MouseButton::Left,
+                state,
+                ..
+            } => match state {
+                ElementState::Pressed => {
+                    self.light_dragging =
+                        !self.gui_wants_pointer_input() && self.cursor_over_light();
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if !self.light_dragging
+                        && !self.gui_wants_pointer_input()
+                        && self.renderer.is_some()
+                    {
+                        self.pick_entity();
+                    }
+                }
+                ElementState::Released => self.light_dragging = false,
+            },
+            // Nothing to draw into without window surface
+            WindowEvent::RedrawRequested if self.suspended => (),
+            WindowEvent::RedrawRequested if self.renderer.is_none() => {
+                self.gui
+                    .as_mut()
+                    .unwrap()
+                    .draw_error_frame(self.window.as_ref().unwrap());
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    self.glutin_window_context.as_ref().unwrap().swap_buffers();
+                    if self.gui.as_ref().unwrap().quit_requested() {
+                        event_loop.exit();
+                    }
+                }
+            }
             WindowEvent::RedrawRequested => {
                 // TODO: Code littered with cfg directives. Consider platform-specific
                 // begin_frame() and end_frame() operations.
@@ -343,7 +553,17 @@ impl ApplicationHandler for App {
                 // Web: corresponds to HTML canvas requestAnimationFrame() call, hence calling
                 // update() here and using the custom loop on native.
                 #[cfg(target_arch = "wasm32")]
-                self.update();
+                {
+                    profiler::new_frame();
+                    // Logic is updated by headset frames during WebXR sessions
+                    if self.web_xr_session.is_none() {
+                        self.measure_frame_time();
+                        self.update_frame();
+                    }
+                }
+                profile_scope!("redraw");
+                #[cfg(not(target_arch = "wasm32"))]
+                self.inspect_triangle();
 
                 let draw_props = &mut self.draw_props.borrow_mut();
                 cfg_if! {
@@ -351,43 +571,128 @@ impl ApplicationHandler for App {
                         self.gui.as_mut().unwrap().prepare_frame(
                             &self.window.as_mut().unwrap(),
                             &self.frame_rate_info,
-                            &self.camera,
                             draw_props,
+                            &mut self.scene,
+                            self.asset_cache.as_mut().unwrap(),
+                            self.renderer.as_mut().unwrap(),
                         );
                     } else {
                         self.gui.as_mut().unwrap().prepare_frame(
                             &self.window.as_mut().unwrap(),
-                            &self.camera,
                             draw_props,
+                            &mut self.scene,
+                            self.asset_cache.as_mut().unwrap(),
+                            self.renderer.as_mut().unwrap(),
                         );
                     }
                 }
 
+                // Exported frames show the timeline at their own time instead of the playback time
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(recorder) = &self.frame_sequence_recorder {
+                    self.scene.store_previous_state();
+                    self.scene.seek_timeline(recorder.time(), draw_props);
+                }
+                self.scene.apply_draw_properties(draw_props);
                 let skybox = &self.skybox.as_ref().unwrap();
                 self.renderer.as_mut().unwrap().draw(
                     &self.window.as_ref().unwrap(),
-                    &self.camera,
                     &draw_props,
-                    &self.models,
+                    &self.scene,
                     &skybox,
                 );
+                if let Err(e) = draw_comparison(
+                    self.renderer.as_mut().unwrap(),
+                    draw_props,
+                    &mut self.scene,
+                    skybox,
+                ) {
+                    eprintln!("{e}");
+                    self.gui.as_mut().unwrap().report_errors(vec![e]);
+                }
 
                 cfg_if! {
                     if #[cfg(not(target_arch = "wasm32"))] {
+                        if self.screenshot_requested {
+                            self.screenshot_requested = false;
+                            if let Err(e) = save_screenshot(
+                                self.renderer.as_ref().unwrap(),
+                                self.screenshot_directory.as_deref(),
+                                self.screenshot_name.take(),
+                            ) {
+                                eprintln!("{e}");
+                                self.gui.as_mut().unwrap().report_errors(vec![e]);
+                            }
+                        }
+                        #[cfg(feature = "http")]
+                        if let Some(snapshot_server) =
+                            self.snapshot_server.as_mut().filter(|server| server.is_waiting())
+                        {
+                            let png = self.renderer.as_ref().unwrap().screenshot_png();
+                            snapshot_server.send_frame(png);
+                        }
+                        if let Err(e) = record_frame_sequence(
+                            &mut self.frame_sequence_recorder,
+                            self.renderer.as_ref().unwrap(),
+                        ) {
+                            eprintln!("{e}");
+                            self.gui.as_mut().unwrap().report_errors(vec![e]);
+                        }
                         self.gui
                             .as_mut()
                             .unwrap()
                             .draw(&self.window.as_mut().unwrap());
-                        self.glutin_window_context.as_ref().unwrap().swap_buffers();
+                        {
+                            // Waits for vertical sync and for GPU work queued by the frame
+                            profile_scope!("swap_buffers");
+                            self.glutin_window_context.as_ref().unwrap().swap_buffers();
+                        }
+                        // Headset frame is rendered from the same state after the window frame,
+                        // waiting for the runtime paces the loop to the headset refresh rate.
+                        #[cfg(feature = "xr")]
+                        if let Some(xr_session) = self.xr_session.as_mut() {
+                            let result = xr_session.poll_events().and_then(|running| {
+                                if running {
+                                    xr_session
+                                        .render(
+                                            self.renderer.as_mut().unwrap(),
+                                            &self.scene.camera,
+                                            draw_props,
+                                            &self.scene,
+                                            skybox,
+                                        )
+                                        .map(|_| true)
+                                } else {
+                                    Ok(false)
+                                }
+                            });
+                            match result {
+                                Ok(true) => (),
+                                Ok(false) => {
+                                    println!("VR session ended");
+                                    self.xr_session = None;
+                                }
+                                Err(e) => {
+                                    eprintln!("{e}");
+                                    self.gui.as_mut().unwrap().report_errors(vec![e]);
+                                    self.xr_session = None;
+                                }
+                            }
+                        }
+                        if self.gui.as_ref().unwrap().quit_requested() {
+                            event_loop.exit();
+                        }
                     } else {
-                        if draw_props.overlay_gui_enabled {
-                            self.gui
-                                .as_mut()
-                                .unwrap()
-                                .draw(&self.window.as_mut().unwrap());
+                        // Errors are displayed even if overlay is disabled
+                        let gui = self.gui.as_mut().unwrap();
+                        if draw_props.overlay_gui_enabled || gui.has_errors() {
+                            gui.draw(&self.window.as_mut().unwrap());
                         }
                     }
                 }
+
+                gl_resources::end_frame();
+                gl_state::end_frame();
             }
             _ => (),
         }
@@ -398,6 +703,22 @@ impl ApplicationHandler for App {
             .handle_events(&self.window.as_mut().unwrap(), &event);
     }
 
+    // Android: native window is destroyed on suspension.
+    // Web: emitted in response to `pagehide` event.
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        self.suspended = true;
+        cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
+            // Runtime renders with the drawable of the released surface
+            #[cfg(feature = "xr")]
+            if self.xr_session.take().is_some() {
+                println!("VR session ended");
+            }
+            if let Some(glutin_window_context) = self.glutin_window_context.as_mut() {
+                glutin_window_context.suspend();
+            }
+        }}
+    }
+
     fn device_event(
         &mut self,
         _event_loop: &ActiveEventLoop,
@@ -410,8 +731,11 @@ impl ApplicationHandler for App {
             DeviceEvent::MouseMotion {
                 delta: (offset_x, offset_y),
             } => {
-                if self.right_mouse_pressed {
-                    self.camera.look(offset_x as f32, offset_y as f32);
+                if self.right_mouse_pressed && self.raw_mouse_input() {
+                    self.handle_input(Input::Look {
+                        x_offset: offset_x as f32,
+                        y_offset: offset_y as f32,
+                    });
                 }
             }
             _ => (),
@@ -420,6 +744,9 @@ impl ApplicationHandler for App {
 
     #[cfg(target_arch = "wasm32")]
     fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        if self.suspended {
+            return;
+        }
         if let Some(w) = self.window.as_ref() {
             w.request_redraw();
         }
@@ -427,30 +754,9 @@ impl ApplicationHandler for App {
 }
 
 impl App {
+    /// Application displaying the bundled demo scene. Use `AppBuilder` for custom scenes.
     pub fn new() -> Result<Self, String> {
-        Ok(Self {
-            window: None,
-            #[cfg(not(target_arch = "wasm32"))]
-            glutin_window_context: None,
-            #[cfg(not(target_arch = "wasm32"))]
-            vsync_enabled: false,
-            #[cfg(not(target_arch = "wasm32"))]
-            frame_rate_info: FrameRateInfo::default(),
-            renderer: None,
-            input_state: InputState::default(),
-            right_mouse_pressed: false,
-            // Positioning and rotation accidentally imitates a right-handed 3D
-            // coordinate system with positive Z going farther from model, but this
-            // setting is done because of initial orientation of the loaded Stanford
-            // Bunny mesh.
-            camera: Camera::new(Point3::new(1.7, 1.3, 4.0), Vector2::new(240.0, -15.0)),
-            draw_props: Arc::new(RefCell::new(DrawProperties::default())),
-            skybox: None,
-            models: Vec::new(),
-            gui: None,
-            #[cfg(target_arch = "wasm32")]
-            html_ui: None,
-        })
+        AppBuilder::demo().build()
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -471,28 +777,61 @@ impl App {
         // "accumulator"
         let mut lag: f32 = 0.0;
         loop {
+            profiler::new_frame();
+
             let current_time = std::time::Instant::now();
             let elapsed_time = (current_time - previous_time).as_secs_f32();
             previous_time = current_time;
-            lag += elapsed_time;
+            lag += self.scaled_time(elapsed_time);
 
             // Increase framerate counter
             elapsed_frame_time += elapsed_time;
             frame_count += 1;
 
-            let timeout = Some(Duration::ZERO);
-            let status = event_loop.pump_app_events(timeout, self);
+            // Wait for events while suspended instead of spinning the loop
+            let was_suspended = self.suspended;
+            let timeout = if was_suspended {
+                None
+            } else {
+                Some(Duration::ZERO)
+            };
+            let status = {
+                // Includes redraw requested in the previous iteration
+                profile_scope!("pump_app_events");
+                event_loop.pump_app_events(timeout, self)
+            };
             if let PumpStatus::Exit(_exit_code) = status {
                 break;
             }
+            // Pause the clock while suspended. Otherwise time spent suspended, e.g. in laptop
+            // sleep, is caught up on resume with a burst of logic updates.
+            if was_suspended || self.suspended {
+                previous_time = std::time::Instant::now();
+                lag = 0.0;
+                continue;
+            }
+            self.handle_gui_actions();
+            self.finish_lightmap_bake();
+            self.handle_remote_commands();
+            self.reload_watched_models();
+            self.quality_scaler
+                .frame(elapsed_time, &mut self.draw_props.borrow_mut());
 
             while lag >= FIXED_UPDATE_TIMESTEP {
                 self.update();
                 lag -= FIXED_UPDATE_TIMESTEP;
             }
+            self.step_if_requested();
+            // Leftover time is drawn as a blend of the last two logic updates
+            self.set_interpolation(lag / FIXED_UPDATE_TIMESTEP);
+            if self.finish_input_playback() && self.exit_after_playback {
+                break;
+            }
 
-            let window = &self.window.as_ref().unwrap();
-            window.request_redraw();
+            // Window is missing if initialization failed, exit is requested on next pump
+            if let Some(window) = &self.window {
+                window.request_redraw();
+            }
 
             // Measure framerate when 1 second is exceeded
             if 1.0 <= elapsed_frame_time {
@@ -504,154 +843,1645 @@ impl App {
                 frame_count = 0;
             }
         }
+
+        if let Some(input_recorder) = self.input_recorder.take() {
+            match input_recorder.save(self.update_count) {
+                Ok(()) => println!("Input recording saved"),
+                Err(e) => eprintln!("{e}"),
+            }
+        }
     }
 
     #[cfg(target_arch = "wasm32")]
-    pub fn run(&mut self) -> Result<(), String> {
+    pub fn run(mut self) -> Result<(), String> {
         let event_loop = EventLoop::new().unwrap();
+        let app = Rc::new_cyclic(|shared| {
+            self.shared = shared.clone();
+            RefCell::new(self)
+        });
         let _ = event_loop
-            .run_app(self)
+            .run_app(&mut SharedApp(app))
             .map_err(|e| format!("error during app runtime: {:?}", e))?;
         Ok(())
     }
 
-    fn update(&mut self) {
-        // Keyboard input
-        if self.input_state[InputEvent::MoveForward] {
-            self.camera.move_forward(FIXED_UPDATE_TIMESTEP);
-        }
-        if self.input_state[InputEvent::MoveBackward] {
-            self.camera.move_backward(FIXED_UPDATE_TIMESTEP);
-        }
-        if self.input_state[InputEvent::StrafeLeft] {
-            self.camera.strafe_left(FIXED_UPDATE_TIMESTEP);
-        }
-        if self.input_state[InputEvent::StrafeRight] {
-            self.camera.strafe_right(FIXED_UPDATE_TIMESTEP);
-        }
-        if self.input_state[InputEvent::Ascend] {
-            self.camera.ascend(FIXED_UPDATE_TIMESTEP);
-        }
-        if self.input_state[InputEvent::Descend] {
-            self.camera.descend(FIXED_UPDATE_TIMESTEP);
-        }
+    /// Enter an immersive WebXR session on click of the HTML button. Canvas stops being redrawn by
+    /// the browser while the session is running.
+    #[cfg(target_arch = "wasm32")]
+    fn request_web_xr_session(shared: &Weak<RefCell<App>>, mode: XrSessionMode) {
+        let Some(app) = shared.upgrade() else {
+            return;
+        };
+        let Some(canvas) = app.borrow().window.as_ref().and_then(|w| w.canvas()) else {
+            return;
+        };
+        let shared = shared.clone();
+        web_xr::request_session(mode, &canvas, move |result| {
+            let Some(app) = shared.upgrade() else {
+                return;
+            };
+            let mut app = app.borrow_mut();
+            match result {
+                Ok(session) => {
+                    // Frame callback re-requests itself, so it has to be reachable from itself
+                    let on_frame: Rc<RefCell<Option<Closure<dyn FnMut(f64, XrFrame)>>>> =
+                        Rc::new(RefCell::new(None));
+                    let on_frame_clone = on_frame.clone();
+                    let frame_shared = shared.clone();
+                    *on_frame.borrow_mut() =
+                        Some(Closure::new(move |_time: f64, frame: XrFrame| {
+                            let Some(app) = frame_shared.upgrade() else {
+                                return;
+                            };
+                            let mut app = app.borrow_mut();
+                            app.render_web_xr_frame(&frame);
+                            if let Some(session) = app.web_xr_session.as_ref() {
+                                session.request_animation_frame(
+                                    on_frame_clone.borrow().as_ref().unwrap(),
+                                );
+                            }
+                        }));
+                    session.request_animation_frame(on_frame.borrow().as_ref().unwrap());
 
-        cfg_if! {
-            if #[cfg(not(target_arch = "wasm32"))] {
-                if self.vsync_enabled != self.draw_props.borrow().vsync_enabled {
-                    self.vsync_enabled = self.draw_props.borrow().vsync_enabled;
-                    self.glutin_window_context
-                        .as_mut()
-                        .unwrap()
-                        .set_vsync_enabled(self.vsync_enabled);
+                    let end_shared = shared.clone();
+                    session.on_end(move || {
+                        // Drops the frame callback
+                        on_frame.borrow_mut().take();
+                        if let Some(app) = end_shared.upgrade() {
+                            app.borrow_mut().web_xr_session = None;
+                        }
+                    });
+                    app.web_xr_session = Some(session);
                 }
-            } else {
-                // TODO: Calling this every frame is slow.
-                self.html_ui.as_mut().unwrap().sync_widgets(&self.draw_props.borrow());
+                Err(e) => {
+                    eprintln!("{e}");
+                    app.gui.as_mut().unwrap().report_errors(vec![e]);
+                }
+            }
+        });
+    }
+
+    /// Headset frames replace animation frames of the canvas, logic is updated at their rate.
+    #[cfg(target_arch = "wasm32")]
+    fn render_web_xr_frame(&mut self, frame: &XrFrame) {
+        if self.renderer.is_none() || self.web_xr_session.is_none() {
+            return;
+        }
+        profiler::new_frame();
+        self.update_frame();
+
+        let draw_props = &mut self.draw_props.borrow_mut();
+        self.scene.apply_draw_properties(draw_props);
+        let result = self.web_xr_session.as_ref().unwrap().render(
+            frame,
+            self.renderer.as_mut().unwrap(),
+            &self.scene.camera,
+            draw_props,
+            &self.scene,
+            self.skybox.as_ref().unwrap(),
+        );
+        if let Err(e) = result {
+            eprintln!("{e}");
+            self.gui.as_mut().unwrap().report_errors(vec![e]);
+            if let Some(session) = self.web_xr_session.take() {
+                session.end();
             }
         }
+        gl_resources::end_frame();
+        gl_state::end_frame();
     }
-}
 
-/// Context Object pattern
-/// (https://accu.org/journals/overload/12/63/kelly_246/) to avoid blowing up App with large number
-/// of Option<> fields.
-#[cfg(not(target_arch = "wasm32"))]
-struct GlutinWindowContext {
-    glutin_context: PossiblyCurrentContext,
-    glutin_surface: Surface<WindowSurface>,
-}
+    /// Animate transforms, lights and camera procedurally. Callback is invoked after keyboard
+    /// camera movement on every fixed timestep logic update with the timestep in seconds.
+    pub fn set_update_callback(
+        &mut self,
+        update_callback: impl FnMut(&mut SceneState, f32) + 'static,
+    ) {
+        self.update_callback = Some(Box::new(update_callback));
+    }
 
-#[cfg(not(target_arch = "wasm32"))]
-impl GlutinWindowContext {
-    fn new(glutin_context: PossiblyCurrentContext, glutin_surface: Surface<WindowSurface>) -> Self {
-        Self {
-            glutin_context,
-            glutin_surface,
+    /// Height the walking camera stands on: the terrain below the camera or the ground plane if
+    /// displayed, otherwise the floor of the bounding box of visible models.
+    fn ground_height(&self) -> f32 {
+        let draw_props = self.draw_props.borrow();
+        let position = self.scene.camera.position();
+        if let Some(height) = self
+            .scene
+            .terrain
+            .as_ref()
+            .filter(|_| draw_props.terrain_enabled)
+            .and_then(|terrain| terrain.height_at(position.x, position.z))
+        {
+            return height;
         }
+        if draw_props.reflective_plane_enabled {
+            return draw_props.reflective_plane_height;
+        }
+        self.scene
+            .visible_bounds()
+            .map_or(draw_props.reflective_plane_height, |bounds| bounds.min.y)
     }
 
-    fn set_vsync_enabled(&self, vsync_enabled: bool) {
-        let swap_interval = match vsync_enabled {
-            true => SwapInterval::Wait(NonZeroU32::new(1).unwrap()),
-            false => SwapInterval::DontWait,
-        };
+    /// Mouse look follows raw motion of the mouse device instead of cursor movement.
+    fn raw_mouse_input(&self) -> bool {
+        cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
+            self.draw_props.borrow().raw_mouse_input
+        } else {
+            // Web: Cursor doesn't move while pointer is locked
+            true
+        }}
+    }
 
-        self.glutin_surface
-            .set_swap_interval(&self.glutin_context, swap_interval)
-            .unwrap();
+    /// GUI windows are drawn above the scene, so they take priority over scene interaction.
+    fn gui_wants_pointer_input(&self) -> bool {
+        // Web: overlay GUI is not displayed when disabled, but its last state is kept
+        let gui = self.gui.as_ref().unwrap();
+        #[cfg(target_arch = "wasm32")]
+        if !self.draw_props.borrow().overlay_gui_enabled && !gui.has_errors() {
+            return false;
+        }
+        gui.wants_pointer_input()
     }
 
-    fn resize(&self, width: u32, height: u32) {
-        self.glutin_surface.resize(
-            &self.glutin_context,
-            NonZeroU32::new(width).unwrap(),
-            NonZeroU32::new(height).unwrap(),
-        );
+    fn projection_view(&self) -> Matrix4<f32> {
+        self.renderer.as_ref().unwrap().projection() * self.scene.camera.calculate_view_matrix()
     }
 
-    fn swap_buffers(&self) {
-        let _ = self.glutin_surface.swap_buffers(&self.glutin_context);
+    fn viewport_size(&self) -> (f32, f32) {
+        let size = self.window.as_ref().unwrap().inner_size();
+        (size.width as f32, size.height as f32)
     }
-}
 
-#[cfg(not(target_arch = "wasm32"))]
-fn initialize_native_window(
-    event_loop: &ActiveEventLoop,
-) -> Result<(Window, GlutinWindowContext, glow::Context), String> {
-    let window_attributes = WindowAttributes::default()
-        .with_title(WINDOW_TITLE)
-        .with_resizable(false)
-        .with_inner_size(LogicalSize::new(WINDOW_WIDTH, WINDOW_HEIGHT));
-    let display_builder =
-        DisplayBuilder::new().with_window_attributes(Some(window_attributes.clone()));
-    let (mut window, gl_config) = display_builder
-        .build(
-            event_loop,
-            ConfigTemplateBuilder::default(),
-            gl_config_picker,
-        )
-        .map_err(|e| format!("failed to create gl_config: {:?}", e))?;
-    let raw_window_handle = window
-        .as_ref()
-        .and_then(|w| w.window_handle().ok())
-        .map(|handle| handle.as_raw());
-    match raw_window_handle {
-        Some(RawWindowHandle::Win32(_)) => println!("Display backend is Win32"),
-        Some(RawWindowHandle::Xlib(_)) => println!("Display backend is X11"),
-        Some(RawWindowHandle::Wayland(_)) => println!("Display backend is Wayland"),
-        _ => (),
+    fn cursor_over_light(&self) -> bool {
+        let draw_props = self.draw_props.borrow();
+        self.renderer.is_some()
+            && draw_props.light_gizmo_enabled
+            && gizmo::hit_test_light_gizmo(
+                draw_props.light_direction,
+                &self.projection_view(),
+                self.viewport_size(),
+                self.cursor_position,
+                self.window.as_ref().unwrap().scale_factor() as f32,
+            )
     }
 
-    let gl_display = gl_config.display();
-    let gl_version = Version::new(4, 3);
-    let context_attributes = ContextAttributesBuilder::new()
-        .with_context_api(ContextApi::OpenGl(Some(gl_version)))
-        .build(raw_window_handle);
+    /// Move light gizmo under cursor by changing light direction.
+    fn drag_light(&mut self) {
+        if let Some(light_direction) = gizmo::light_direction_at_cursor(
+            &self.projection_view(),
+            self.viewport_size(),
+            self.cursor_position,
+        ) {
+            self.draw_props.borrow_mut().light_direction = light_direction;
+        }
+    }
 
-    let not_current_gl_context = unsafe {
-        gl_display
-            .create_context(&gl_config, &context_attributes)
-            .map_err(|e| format!("failed to create a temporary context: {:?}", e))?
-    };
+    /// Activate the entity under cursor in the GUI, or clear the active entity when clicking
+    /// into empty space. In measure mode the point under cursor is measured instead.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_entity(&mut self) {
+        let Some(ray) = Ray::through_cursor(
+            &self.projection_view(),
+            self.viewport_size(),
+            self.cursor_position,
+        ) else {
+            return;
+        };
+        match self.scene.pick(&ray) {
+            Ok(hit) if self.scene.measurement.enabled => {
+                if let Some((_, hit)) = hit {
+                    self.scene
+                        .measurement
+                        .add_point(ray.origin + ray.direction * hit.distance);
+                }
+            }
+            Ok(hit) => self
+                .gui
+                .as_mut()
+                .unwrap()
+                .set_active_entity(hit.map(|(entity, _)| entity)),
+            Err(e) => eprintln!("picking failed: {e}"),
+        }
+    }
 
-    // Apply glutin gl_config options to winit window (removing incompatible options in the
-    // process)
-    let window = match window.take() {
-        Some(w) => w,
-        None => glutin_winit::finalize_window(event_loop, window_attributes, &gl_config)
-            .map_err(|e| format!("failed to apply GL options to window: {:?}", e))?,
-    };
+    /// Update the triangle under cursor in inspector mode, also when the camera or the entities
+    /// moved since the cursor did.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn inspect_triangle(&mut self) {
+        if !self.scene.inspector.enabled || self.renderer.is_none() {
+            return;
+        }
+        let ray = Ray::through_cursor(
+            &self.projection_view(),
+            self.viewport_size(),
+            self.cursor_position,
+        )
+        .filter(|_| !self.gui_wants_pointer_input());
+        let result = match ray {
+            Some(ray) => self.scene.pick(&ray),
+            None => Ok(None),
+        }
+        .and_then(|hit| self.scene.inspect(hit));
+        if let Err(e) = result {
+            eprintln!("inspection failed: {e}");
+        }
+    }
 
-    let surface_attributes = window
-        .build_surface_attributes(SurfaceAttributesBuilder::default())
-        .map_err(|e| format!("failed to build window surface attributes: {:?}", e))?;
-    let glutin_surface = unsafe {
-        gl_config
-            .display()
-            .create_window_surface(&gl_config, &surface_attributes)
-            .map_err(|e| format!("failed to create window surface: {:?}", e))?
+    /// Carry out requests from the menu bar. Failures are reported in the GUI error window.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn handle_gui_actions(&mut self) {
+        let Some(gui) = self.gui.as_mut() else {
+            return;
+        };
+        for action in gui.take_actions() {
+            let result = match action {
+                GuiAction::OpenModel(path) => self.open_model(&path),
+                GuiAction::OpenSkybox(directory) => self.open_skybox(&directory),
+                GuiAction::OpenTerrain(path) => self.open_terrain(&path),
+                GuiAction::AddDecal(path) => self.add_decal(&path),
+                GuiAction::SaveScene(path) => self.save_scene(&path),
+                GuiAction::ExportModel(path, lod_level) => self.export_model(&path, lod_level),
+                GuiAction::Screenshot => {
+                    self.screenshot_requested = true;
+                    Ok(())
+                }
+                GuiAction::BakeLighting => self.bake_lighting(),
+                GuiAction::ExportAnimation(directory, frame_rate) => {
+                    self.export_animation(&directory, frame_rate)
+                }
+                GuiAction::RepairMesh(repair) => self.repair_mesh(repair),
+            };
+            if let Err(e) = result {
+                eprintln!("{e}");
+                self.gui.as_mut().unwrap().report_errors(vec![e]);
+            }
+        }
+    }
+
+    /// Carry out commands received from remote controllers. Failures are only printed, so a
+    /// misconfigured controller doesn't flood the GUI error window. Clients of the remote control
+    /// server are also answered with them.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn handle_remote_commands(&mut self) {
+        if let Some(osc_listener) = self.osc_listener.as_mut() {
+            let (commands, errors) = osc_listener.poll();
+            for e in errors {
+                eprintln!("{e}");
+            }
+            for command in commands {
+                if let Err(e) = self.apply_remote_command(command) {
+                    eprintln!("{e}");
+                }
+            }
+        }
+
+        #[cfg(feature = "remote")]
+        if let Some(remote_server) = self.remote_server.as_mut() {
+            let (requests, mut errors) = remote_server.poll();
+            for (client_id, RemoteRequest { id, command }) in requests {
+                let result = self.apply_remote_command(command);
+                if let Err(e) = &result {
+                    eprintln!("{e}");
+                }
+                self.remote_server
+                    .as_mut()
+                    .unwrap()
+                    .reply(client_id, id, result, &mut errors);
+            }
+            for e in errors {
+                eprintln!("{e}");
+            }
+        }
+
+        #[cfg(feature = "http")]
+        if let Some(snapshot_server) = self.snapshot_server.as_mut() {
+            for mut request in snapshot_server.poll() {
+                let result = std::mem::take(&mut request.commands)
+                    .into_iter()
+                    .try_for_each(|command| self.apply_remote_command(command));
+                self.snapshot_server
+                    .as_mut()
+                    .unwrap()
+                    .wait_for_frame(request, result);
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn apply_remote_command(&mut self, command: RemoteCommand) -> Result<(), String> {
+        match command {
+            RemoteCommand::SetProperty { name, values } => {
+                let mut draw_props = self.draw_props.borrow_mut();
+                draw_props.set_by_name(&name, &values)?;
+                // Scene may have cleared its models
+                let last_model_index = self.scene.models.len().saturating_sub(1);
+                draw_props.selected_model_index =
+                    draw_props.selected_model_index.min(last_model_index);
+            }
+            RemoteCommand::MoveCamera { position, rotation } => {
+                let camera = &mut self.scene.camera;
+                let position = position.map_or(*camera.position(), Point3::from);
+                let rotation = rotation.map_or(*camera.rotation(), cgmath::Vector3::from);
+                camera.set_placement(position, rotation);
+                // Remote placement wins over a flight in progress
+                self.scene.camera_transition = None;
+            }
+            RemoteCommand::GoToCameraBookmark { index } => {
+                let bookmark = self
+                    .scene
+                    .camera_bookmarks
+                    .get(index)
+                    .ok_or(format!("no camera bookmark at index {index}"))?;
+                let draw_props = self.draw_props.borrow();
+                self.scene.camera_transition = Some(CameraTransition::new(
+                    &self.scene.camera,
+                    draw_props.field_of_view,
+                    bookmark,
+                    draw_props.camera_transition_duration,
+                    draw_props.camera_transition_easing,
+                ));
+            }
+            RemoteCommand::SetModulationLevel { index, level } => {
+                self.scene.modulation.set_level(index, level)?;
+            }
+            RemoteCommand::LoadModel { path } => self.open_model(&path)?,
+            RemoteCommand::Screenshot { name } => {
+                if let Some(name) = &name {
+                    if Path::new(name).file_name() != Some(name.as_ref()) {
+                        return Err(format!("screenshot name {name} is not a file name"));
+                    }
+                }
+                self.screenshot_requested = true;
+                self.screenshot_name = name;
+            }
+        }
+        Ok(())
+    }
+
+    /// Start baking lighting of the visible entities on a worker thread, see
+    /// `finish_lightmap_bake()`.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn bake_lighting(&mut self) -> Result<(), String> {
+        if self.lightmap_bake.is_some() {
+            return Err("lighting is already being baked".to_owned());
+        }
+        let settings = self.draw_props.borrow().lightmap_settings;
+        self.lightmap_bake = Some(LightmapBake::start(&self.scene, &settings)?);
+        Ok(())
+    }
+
+    /// Show progress of the running bake in the GUI. Once done, upload the lightmaps and switch to
+    /// displaying them. Failures are reported in the GUI error window.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn finish_lightmap_bake(&mut self) {
+        if let Some(gui) = self.gui.as_mut() {
+            gui.set_bake_progress(self.lightmap_bake.as_ref().map(LightmapBake::progress));
+        }
+        if !self
+            .lightmap_bake
+            .as_ref()
+            .is_some_and(LightmapBake::is_finished)
+        {
+            return;
+        }
+        let Some(asset_cache) = self.asset_cache.as_ref() else {
+            return;
+        };
+        let result = self.lightmap_bake.take().unwrap().finish(asset_cache.gl());
+        match result {
+            Ok(lightmaps) => {
+                self.scene.set_lightmaps(lightmaps);
+                self.draw_props.borrow_mut().baked_lighting_enabled = true;
+            }
+            Err(e) => {
+                eprintln!("{e}");
+                if let Some(gui) = self.gui.as_mut() {
+                    gui.report_errors(vec![e]);
+                }
+            }
+        }
+    }
+
+    /// Add model to the scene as a new entry of the model selection and select it.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_model(&mut self, path: &str) -> Result<(), String> {
+        let asset_cache = self
+            .asset_cache
+            .as_mut()
+            .ok_or("no graphics context to load model with")?;
+        let mesh = asset_cache
+            .load_mesh_from_file(path)
+            .map_err(|e| format!("unable to open model {path}: {e}"))?;
+        let name = std::path::Path::new(path)
+            .file_stem()
+            .map_or(path.to_string(), |stem| stem.to_string_lossy().into_owned());
+
+        self.scene
+            .models
+            .push(Model::from_mesh(mesh).with_name(&name));
+        let model_index = self.scene.models.len() - 1;
+        self.scene.spawn_selectable(model_index);
+        // Loaded again on next resume
+        self.scene_description.models.push(ModelDescription {
+            name,
+            source: ModelSource::File(path.to_string()),
+        });
+        self.draw_props.borrow_mut().selected_model_index = model_index;
+        Ok(())
+    }
+
+    /// Reload models whose files changed in the watched directory, and open new ones. Failures are
+    /// reported in the GUI error window, e.g. when a file was saved with invalid contents.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reload_watched_models(&mut self) {
+        if self.gui.is_none() {
+            return;
+        }
+        let Some(asset_watcher) = self.asset_watcher.as_mut() else {
+            return;
+        };
+        let changed_paths = match asset_watcher.poll() {
+            Ok(paths) => paths,
+            Err(e) => {
+                eprintln!("{e}");
+                self.gui.as_mut().unwrap().report_errors(vec![e]);
+                // Watching stops instead of reporting the same error on every poll
+                self.asset_watcher = None;
+                return;
+            }
+        };
+        for path in changed_paths {
+            let path = path.to_string_lossy();
+            if let Err(e) = self.reload_model(&path) {
+                eprintln!("{e}");
+                self.gui.as_mut().unwrap().report_errors(vec![e]);
+            }
+        }
+    }
+
+    /// Replace mesh of every model loaded from the file and select the first one. Files not loaded
+    /// yet are opened as new models.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reload_model(&mut self, path: &str) -> Result<(), String> {
+        let canonical_path = |path: &str| std::fs::canonicalize(path).ok();
+        let reloaded_path = canonical_path(path);
+        let model_indices: Vec<usize> = self
+            .scene_description
+            .models
+            .iter()
+            .enumerate()
+            .filter(|(_, model)| {
+                model
+                    .path()
+                    .is_some_and(|path| canonical_path(path) == reloaded_path)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        let Some(&first_index) = model_indices.first() else {
+            return self.open_model(path);
+        };
+
+        let asset_cache = self
+            .asset_cache
+            .as_mut()
+            .ok_or("no graphics context to load model with")?;
+        let mesh = asset_cache
+            .reload_mesh_from_file(path)
+            .map_err(|e| format!("unable to reload model {path}: {e}"))?;
+        for index in model_indices {
+            let name = std::mem::take(&mut self.scene.models[index].name);
+            self.scene.models[index] = Model::from_mesh(mesh.clone()).with_name(&name);
+        }
+        self.draw_props.borrow_mut().selected_model_index = first_index;
+        println!("reloaded model {path}");
+        Ok(())
+    }
+
+    /// Replace skybox with cube faces found in the directory. Faces are looked up by file name
+    /// without extension, e.g. `right.jpg` or `right.exr`.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_skybox(&mut self, directory: &str) -> Result<(), String> {
+        let entries = std::fs::read_dir(directory)
+            .map_err(|e| format!("unable to open skybox directory {directory}: {e}"))?;
+        let face_paths: Vec<std::path::PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect();
+        let find_face = |face: &str| {
+            face_paths
+                .iter()
+                .find(|path| path.file_stem().is_some_and(|stem| stem == face))
+                .map(|path| path.to_string_lossy().into_owned())
+                .ok_or(format!("skybox directory {directory} has no {face} face"))
+        };
+        let skybox_builder = SkyboxFileBuilder::new()
+            .with_right(&find_face("right")?)
+            .with_left(&find_face("left")?)
+            .with_top(&find_face("top")?)
+            .with_bottom(&find_face("bottom")?)
+            .with_front(&find_face("front")?)
+            .with_back(&find_face("back")?);
+
+        let asset_cache = self
+            .asset_cache
+            .as_mut()
+            .ok_or("no graphics context to load skybox with")?;
+        let skybox = asset_cache
+            .load_skybox(skybox_builder.clone())
+            .map_err(|e| format!("unable to open skybox {directory}: {e}"))?;
+        self.skybox = Some(skybox);
+        self.scene_description.skybox = Some(skybox_builder);
+        Ok(())
+    }
+
+    /// Replace terrain with one generated from a heightmap image with default settings.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_terrain(&mut self, path: &str) -> Result<(), String> {
+        let gl = self
+            .asset_cache
+            .as_ref()
+            .ok_or("no graphics context to load terrain with")?
+            .gl()
+            .clone();
+        let settings = TerrainSettings::default();
+        let terrain = Terrain::create_from_file(gl, path, &settings)
+            .map_err(|e| format!("unable to open terrain {path}: {e}"))?;
+        self.scene.terrain = Some(terrain);
+        self.scene_description.terrain = Some(TerrainDescription {
+            path: path.to_string(),
+            settings,
+        });
+        self.draw_props.borrow_mut().terrain_enabled = true;
+        Ok(())
+    }
+
+    /// Project an image downwards onto the visible models from above, to be placed from the GUI.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn add_decal(&mut self, path: &str) -> Result<(), String> {
+        if self.scene.decals.len() >= MAX_DECALS {
+            return Err(format!(
+                "unable to add decal {path}: at most {MAX_DECALS} decals are drawn"
+            ));
+        }
+        let gl = self
+            .asset_cache
+            .as_ref()
+            .ok_or("no graphics context to load decal with")?
+            .gl()
+            .clone();
+        let mut decal = Decal::load(gl, path)?;
+        if let Some(bounds) = self.scene.visible_bounds() {
+            let center = bounds.center();
+            let radius = bounds.radius();
+            decal.position = cgmath::Vector3::new(center.x, bounds.max.y + radius, center.z);
+            decal.near = 0.1 * radius;
+            decal.far = 3.0 * radius;
+        }
+        self.scene.decals.push(decal);
+        Ok(())
+    }
+
+    /// Write mesh of the selected model as currently displayed, with the transform of its entity
+    /// and modifications made on GPU applied, into an OBJ or binary glTF file.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_model(&self, path: &str, lod_level: usize) -> Result<(), String> {
+        let draw_props = self.draw_props.borrow();
+        let model = &self.scene.models[draw_props.selected_model_index];
+        let MeshData {
+            mut vertices,
+            mut indices,
+            ..
+        } = model
+            .lod(lod_level)
+            .read_back()
+            .map_err(|e| format!("unable to export model {}: {e}", model.name))?;
+        if indices.is_empty() {
+            return Err(format!(
+                "unable to export model {}: mesh is empty",
+                model.name
+            ));
+        }
+
+        let transform = self
+            .scene
+            .selected_entity(draw_props.selected_model_index)
+            .and_then(|entity| self.scene.world.get::<&Transform>(entity).ok())
+            .map(|transform| *transform)
+            .unwrap_or_default();
+        mesh_export::transform_vertices(&mut vertices, &calculate_model_matrix(&transform));
+        if model.face_culling.front_face == Winding::Clockwise {
+            mesh_export::flip_winding(&mut indices);
+        }
+        mesh_export::export_mesh(path, &vertices, &indices)
+    }
+
+    /// Replace the selected model with one of its full detail mesh fixed by the repair operation.
+    /// Other models sharing the mesh are left unchanged.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn repair_mesh(&mut self, repair: MeshRepair) -> Result<(), String> {
+        let asset_cache = self
+            .asset_cache
+            .as_ref()
+            .ok_or("no graphics context to upload repaired mesh with")?;
+        let selected_model_index = self.draw_props.borrow().selected_model_index;
+        let model = &mut self.scene.models[selected_model_index];
+        let mut mesh_data = model
+            .lod(0)
+            .read_back()
+            .map_err(|e| format!("unable to repair model {}: {e}", model.name))?;
+        // Parts are not stored on GPU
+        mesh_data.parts = model.parts().to_vec();
+        let count = repair.apply(&mut mesh_data);
+
+        let face_culling = model.face_culling;
+        let name = std::mem::take(&mut model.name);
+        *model = Model::from_mesh_data(asset_cache.gl().clone(), &mesh_data).with_name(&name);
+        model.face_culling = face_culling;
+        println!("{}: {}", model.name, repair.report(count));
+        Ok(())
+    }
+
+    /// Start saving the timeline from its beginning as a sequence of frames, one per rendered
+    /// frame. Playback is stopped meanwhile.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_animation(&mut self, directory: &str, frame_rate: f32) -> Result<(), String> {
+        let recorder =
+            FrameSequenceRecorder::new(directory, frame_rate, self.scene.timeline.duration)?;
+        self.scene.timeline.playing = false;
+        self.frame_sequence_recorder = Some(recorder);
+        Ok(())
+    }
+
+    /// Write models, renderable entities, camera and light of the scene into a RON file.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_scene(&self, path: &str) -> Result<(), String> {
+        let draw_props = self.draw_props.borrow();
+        let models = self
+            .scene_description
+            .models
+            .iter()
+            .map(|model| SceneFileModel {
+                name: model.name.clone(),
+                // Generated models have no file to refer to
+                path: model.path().unwrap_or_default().to_owned(),
+            })
+            .collect();
+        let entities = self
+            .scene
+            .world
+            .query::<(&Renderable, &Transform, &Material)>()
+            .iter()
+            .map(
+                |(entity, (renderable, transform, material))| SceneFileEntity {
+                    name: self.scene.entity_name(entity),
+                    model_index: renderable.model_index,
+                    position: transform.position.into(),
+                    rotation: transform.rotation,
+                    material: material.clone(),
+                    selectable: self
+                        .scene
+                        .world
+                        .satisfies::<&Selectable>(entity)
+                        .unwrap_or(false),
+                },
+            )
+            .collect();
+        let scene_file = SceneFile {
+            models,
+            entities,
+            selected_model_index: draw_props.selected_model_index,
+            camera_position: (*self.scene.camera.position()).into(),
+            camera_rotation: (*self.scene.camera.rotation()).into(),
+            light_direction: draw_props.light_direction,
+        };
+
+        let text = ron::ser::to_string_pretty(&scene_file, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("cannot serialize scene: {e}"))?;
+        std::fs::write(path, text).map_err(|e| format!("cannot write scene file {path}: {e}"))
+    }
+
+    /// Apply camera input received from the window. Native: recorded while recording, ignored
+    /// during playback so it doesn't diverge from the recording.
+    fn handle_input(&mut self, input: Input) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if self.input_playback.is_some() {
+                return;
+            }
+            if let Some(input_recorder) = self.input_recorder.as_mut() {
+                input_recorder.record(self.update_count, input);
+            }
+        }
+        self.apply_input(input);
+    }
+
+    fn apply_input(&mut self, input: Input) {
+        match input {
+            Input::Key { event, pressed } => self.input_state[event] = pressed,
+            Input::Look { x_offset, y_offset } => self.scene.camera.look(x_offset, y_offset),
+        }
+    }
+
+    /// Stop playback once every recorded logic update was replayed, handing control back to the
+    /// user. Returns whether playback finished just now.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn finish_input_playback(&mut self) -> bool {
+        let finished = self
+            .input_playback
+            .as_ref()
+            .is_some_and(|input_playback| input_playback.finished(self.update_count));
+        if finished {
+            self.input_playback = None;
+            // Keys held at the end of the recording are not released by the user
+            self.input_state = InputState::default();
+            println!(
+                "Input playback finished after {} updates",
+                self.update_count
+            );
+        }
+        finished
+    }
+
+    /// Logic time passed during `elapsed_time` of real time, slowed down or sped up by the time
+    /// scale. None passes while paused.
+    fn scaled_time(&self, elapsed_time: f32) -> f32 {
+        let draw_props = self.draw_props.borrow();
+        if draw_props.paused {
+            0.0
+        } else {
+            elapsed_time * draw_props.time_scale
+        }
+    }
+
+    /// Run the single logic update requested by stepping, if any.
+    fn step_if_requested(&mut self) {
+        let step_requested = std::mem::take(&mut self.draw_props.borrow_mut().step_requested);
+        if step_requested {
+            self.update();
+        }
+    }
+
+    /// Blend the last two logic updates by `alpha` when drawing. Paused scene is drawn as left by
+    /// the last update, so single steps are seen in whole.
+    fn set_interpolation(&mut self, alpha: f32) {
+        let alpha = if self.draw_props.borrow().paused {
+            1.0
+        } else {
+            alpha
+        };
+        self.scene.set_interpolation(alpha);
+    }
+
+    /// Web: feed time elapsed since the previous animation frame into quality scaling.
+    #[cfg(target_arch = "wasm32")]
+    fn measure_frame_time(&mut self) {
+        let now = web_sys::window().unwrap().performance().unwrap().now();
+        let frame_time = self
+            .previous_frame_time
+            .map_or(0.0, |previous| (now - previous) / 1000.0);
+        self.previous_frame_time = Some(now);
+        self.quality_scaler
+            .frame(frame_time as f32, &mut self.draw_props.borrow_mut());
+    }
+
+    /// Web: logic is updated once per animation frame at normal speed. Slow motion skips updates
+    /// of some frames, while fast forward runs several in one.
+    #[cfg(target_arch = "wasm32")]
+    fn update_frame(&mut self) {
+        self.update_credit += self.scaled_time(1.0);
+        while self.update_credit >= 1.0 {
+            self.update();
+            self.update_credit -= 1.0;
+        }
+        self.step_if_requested();
+        self.set_interpolation(self.update_credit);
+    }
+
+    fn update(&mut self) {
+        profile_scope!("update");
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(input_playback) = self.input_playback.as_mut() {
+                for input in input_playback.inputs_before(self.update_count) {
+                    self.apply_input(input);
+                }
+            }
+            self.update_count += 1;
+        }
+        self.scene.store_previous_state();
+
+        // Keyboard input
+        if self.input_state[InputEvent::MoveForward] {
+            self.scene.camera.move_forward(FIXED_UPDATE_TIMESTEP);
+        }
+        if self.input_state[InputEvent::MoveBackward] {
+            self.scene.camera.move_backward(FIXED_UPDATE_TIMESTEP);
+        }
+        if self.input_state[InputEvent::StrafeLeft] {
+            self.scene.camera.strafe_left(FIXED_UPDATE_TIMESTEP);
+        }
+        if self.input_state[InputEvent::StrafeRight] {
+            self.scene.camera.strafe_right(FIXED_UPDATE_TIMESTEP);
+        }
+        if self.input_state[InputEvent::Ascend] {
+            self.scene.camera.ascend(FIXED_UPDATE_TIMESTEP);
+        }
+        if self.input_state[InputEvent::Descend] {
+            self.scene.camera.descend(FIXED_UPDATE_TIMESTEP);
+        }
+        if self.input_state[InputEvent::RollLeft] {
+            self.scene.camera.roll_left(FIXED_UPDATE_TIMESTEP);
+        }
+        if self.input_state[InputEvent::RollRight] {
+            self.scene.camera.roll_right(FIXED_UPDATE_TIMESTEP);
+        }
+
+        // Mouse look
+        {
+            let draw_props = self.draw_props.borrow();
+            self.scene.camera.set_mode(draw_props.camera_mode);
+            self.scene
+                .camera
+                .set_look_smoothing(draw_props.mouse_smoothing);
+            self.scene
+                .camera
+                .set_look_acceleration(draw_props.mouse_acceleration);
+        }
+        self.scene.camera.update_look();
+        let ground_height = self.ground_height();
+        self.scene
+            .camera
+            .update_walk(FIXED_UPDATE_TIMESTEP, ground_height);
+        // Transitions override movement by input until they finish
+        if let Some(field_of_view) = self.scene.update_camera_transition(FIXED_UPDATE_TIMESTEP) {
+            self.draw_props.borrow_mut().field_of_view = field_of_view;
+        }
+        self.scene
+            .update_timeline(FIXED_UPDATE_TIMESTEP, &mut self.draw_props.borrow_mut());
+        #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+        if let Some(audio_analyzer) = self.audio_analyzer.as_mut() {
+            self.scene.modulation.set_levels(&audio_analyzer.levels());
+        }
+        self.scene
+            .update_modulation(FIXED_UPDATE_TIMESTEP, &mut self.draw_props.borrow_mut());
+
+        if let Some(update_callback) = self.update_callback.as_mut() {
+            let mut scene_state = SceneState {
+                scene: &mut self.scene,
+                draw_props: &mut self.draw_props.borrow_mut(),
+            };
+            update_callback(&mut scene_state, FIXED_UPDATE_TIMESTEP);
+        }
+
+        cfg_if! {
+            if #[cfg(not(target_arch = "wasm32"))] {
+                if self.vsync_mode != self.draw_props.borrow().vsync_mode {
+                    self.vsync_mode = self.draw_props.borrow().vsync_mode;
+                    self.glutin_window_context
+                        .as_mut()
+                        .unwrap()
+                        .set_vsync_mode(self.vsync_mode);
+                }
+            } else {
+                // TODO: Calling this every frame is slow.
+                self.html_ui
+                    .as_mut()
+                    .unwrap()
+                    .sync_widgets(&self.draw_props.borrow(), &mut self.scene);
+            }
+        }
+    }
+}
+
+/// Web: app shared with browser callbacks, forwarding events of the window to it.
+#[cfg(target_arch = "wasm32")]
+struct SharedApp(Rc<RefCell<App>>);
+
+#[cfg(target_arch = "wasm32")]
+impl ApplicationHandler for SharedApp {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        self.0.borrow_mut().resumed(event_loop);
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: winit::window::WindowId,
+        event: WindowEvent,
+    ) {
+        self.0
+            .borrow_mut()
+            .window_event(event_loop, window_id, event);
+    }
+
+    fn device_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        device_id: winit::event::DeviceId,
+        event: DeviceEvent,
+    ) {
+        self.0
+            .borrow_mut()
+            .device_event(event_loop, device_id, event);
+    }
+
+    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+        self.0.borrow_mut().suspended(event_loop);
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.0.borrow_mut().about_to_wait(event_loop);
+    }
+}
+
+/// Model of the scene loaded on startup.
+#[derive(Clone)]
+struct ModelDescription {
+    name: String,
+    source: ModelSource,
+}
+
+#[derive(Clone)]
+enum ModelSource {
+    #[cfg(not(target_arch = "wasm32"))]
+    File(String),
+    #[cfg(target_arch = "wasm32")]
+    Buffer(&'static [u8]),
+    /// Mesh generated in code, e.g. by `primitives`.
+    Generated(Arc<MeshData>),
+}
+
+impl ModelDescription {
+    fn load_mesh(&self, asset_cache: &mut AssetCache) -> Result<Arc<Mesh>, String> {
+        match &self.source {
+            #[cfg(not(target_arch = "wasm32"))]
+            ModelSource::File(path) => asset_cache.load_mesh_from_file(path),
+            #[cfg(target_arch = "wasm32")]
+            ModelSource::Buffer(data) => asset_cache.load_mesh_from_buffer(data),
+            ModelSource::Generated(mesh_data) => Ok(Arc::new(Mesh::from_mesh_data(
+                asset_cache.gl().clone(),
+                mesh_data,
+            ))),
+        }
+    }
+
+    /// Path of the file the model was loaded from, `None` for generated models.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn path(&self) -> Option<&str> {
+        match &self.source {
+            ModelSource::File(path) => Some(path),
+            ModelSource::Generated(_) => None,
+        }
+    }
+}
+
+/// Terrain of the scene loaded on startup from a heightmap.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone)]
+struct TerrainDescription {
+    path: String,
+    settings: TerrainSettings,
+}
+
+/// Draw side B of the comparison enabled from the GUI over the frame drawn with the draw properties
+/// of side A.
+fn draw_comparison(
+    renderer: &mut Renderer,
+    draw_props: &DrawProperties,
+    scene: &mut Scene,
+    skybox: &Skybox,
+) -> Result<(), String> {
+    let Some(mut comparison) = scene.comparison.take() else {
+        return Ok(());
+    };
+    comparison.share_settings(draw_props);
+    comparison.exchange_materials(&mut scene.world);
+    scene.apply_draw_properties(&comparison.draw_props);
+    let result = renderer.draw_comparison(&comparison.draw_props, scene, skybox, comparison.split);
+    comparison.exchange_materials(&mut scene.world);
+    scene.apply_draw_properties(draw_props);
+    scene.comparison = Some(comparison);
+    result
+}
+
+/// Save the rendered frame into the file of the directory, or of the working directory if not
+/// given. File is named after the current time if not given.
+#[cfg(not(target_arch = "wasm32"))]
+fn save_screenshot(
+    renderer: &Renderer,
+    directory: Option<&str>,
+    name: Option<String>,
+) -> Result<(), String> {
+    let name = name.unwrap_or_else(|| {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        format!("screenshot_{timestamp}.png")
+    });
+    let path = match directory {
+        Some(directory) => Path::new(directory)
+            .join(name)
+            .to_string_lossy()
+            .into_owned(),
+        None => name,
     };
+    renderer
+        .save_screenshot(&path)
+        .map_err(|e| format!("unable to save screenshot: {e}"))?;
+    println!("saved screenshot to {path}");
+    Ok(())
+}
+
+/// Save the rendered frame of the timeline export in progress. The export ends after its last
+/// frame or on failure.
+#[cfg(not(target_arch = "wasm32"))]
+fn record_frame_sequence(
+    recorder: &mut Option<FrameSequenceRecorder>,
+    renderer: &Renderer,
+) -> Result<(), String> {
+    let Some(active) = recorder.as_mut() else {
+        return Ok(());
+    };
+    match active.save_frame(renderer) {
+        Ok(false) => Ok(()),
+        Ok(true) => {
+            println!(
+                "saved {} frames to {}",
+                active.frame_count(),
+                active.directory()
+            );
+            *recorder = None;
+            Ok(())
+        }
+        Err(e) => {
+            *recorder = None;
+            Err(e)
+        }
+    }
+}
+
+/// Scene saved from the File menu. Models are referred by the file they were loaded from.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Serialize)]
+struct SceneFile {
+    models: Vec<SceneFileModel>,
+    entities: Vec<SceneFileEntity>,
+    selected_model_index: usize,
+    camera_position: [f32; 3],
+    /// Yaw, pitch and roll in degrees.
+    camera_rotation: [f32; 3],
+    light_direction: [f32; 3],
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Serialize)]
+struct SceneFileModel {
+    name: String,
+    path: String,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Serialize)]
+struct SceneFileEntity {
+    name: String,
+    model_index: usize,
+    position: [f32; 3],
+    rotation: [f32; 3],
+    material: Material,
+    selectable: bool,
+}
+
+/// Assets of the scene, loaded only after the graphics context is created.
+struct SceneDescription {
+    models: Vec<ModelDescription>,
+    #[cfg(not(target_arch = "wasm32"))]
+    skybox: Option<SkyboxFileBuilder>,
+    #[cfg(target_arch = "wasm32")]
+    skybox: Option<SkyboxBufferBuilder>,
+    #[cfg(not(target_arch = "wasm32"))]
+    terrain: Option<TerrainDescription>,
+}
+
+/// Construct `App` displaying custom models and skybox instead of the bundled demo scene.
+///
+/// Models are selectable by their name in the GUI in the order they were added. Scene without
+/// skybox has black background when skybox display is enabled.
+pub struct AppBuilder {
+    scene: SceneDescription,
+    camera: Camera,
+    draw_props: DrawProperties,
+    render_hooks: Vec<Box<dyn RenderHook>>,
+    stencil_bits: u8,
+    ui_scale: Option<f32>,
+    #[cfg(not(target_arch = "wasm32"))]
+    watch_directory: Option<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    osc_address: Option<SocketAddr>,
+    #[cfg(not(target_arch = "wasm32"))]
+    screenshot_directory: Option<String>,
+    #[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+    remote_server_address: Option<SocketAddr>,
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    snapshot_server_address: Option<SocketAddr>,
+    #[cfg(not(target_arch = "wasm32"))]
+    input_recording_path: Option<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    input_playback_path: Option<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    exit_after_playback: bool,
+    #[cfg(all(feature = "xr", not(target_arch = "wasm32")))]
+    xr_enabled: bool,
+    #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+    audio_enabled: bool,
+}
+
+impl Default for AppBuilder {
+    fn default() -> Self {
+        Self {
+            scene: SceneDescription {
+                models: Vec::new(),
+                skybox: None,
+                #[cfg(not(target_arch = "wasm32"))]
+                terrain: None,
+            },
+            camera: Camera::new(Point3::new(0.0, 0.0, 4.0), Vector2::new(270.0, 0.0)),
+            draw_props: DrawProperties::default(),
+            render_hooks: Vec::new(),
+            stencil_bits: 8,
+            ui_scale: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            watch_directory: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            osc_address: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            screenshot_directory: None,
+            #[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+            remote_server_address: None,
+            #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+            snapshot_server_address: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            input_recording_path: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            input_playback_path: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            exit_after_playback: false,
+            #[cfg(all(feature = "xr", not(target_arch = "wasm32")))]
+            xr_enabled: false,
+            #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+            audio_enabled: false,
+        }
+    }
+}
+
+impl AppBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scene of the demo application with bundled models and skybox.
+    pub fn demo() -> Self {
+        cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
+            let builder = Self::new()
+                .with_model(assets::MODEL_NAMES[0], assets::model::CUBE_PATH)
+                .with_model(assets::MODEL_NAMES[1], assets::model::TEAPOT_PATH)
+                .with_model(assets::MODEL_NAMES[2], assets::model::BUNNY_PATH)
+                .with_skybox(
+                    SkyboxFileBuilder::new()
+                        .with_right(assets::skybox::RIGHT_FACE_PATH)
+                        .with_left(assets::skybox::LEFT_FACE_PATH)
+                        .with_top(assets::skybox::TOP_FACE_PATH)
+                        .with_bottom(assets::skybox::BOTTOM_FACE_PATH)
+                        .with_front(assets::skybox::FRONT_FACE_PATH)
+                        .with_back(assets::skybox::BACK_FACE_PATH),
+                );
+        } else {
+            let builder = Self::new()
+                .with_model(assets::MODEL_NAMES[0], assets::model::CUBE_BYTES)
+                .with_model(assets::MODEL_NAMES[1], assets::model::TEAPOT_BYTES)
+                .with_model(assets::MODEL_NAMES[2], assets::model::BUNNY_BYTES)
+                .with_skybox(
+                    SkyboxBufferBuilder::new()
+                        .with_right(assets::skybox::RIGHT_FACE_BYTES)
+                        .with_left(assets::skybox::LEFT_FACE_BYTES)
+                        .with_top(assets::skybox::TOP_FACE_BYTES)
+                        .with_bottom(assets::skybox::BOTTOM_FACE_BYTES)
+                        .with_front(assets::skybox::FRONT_FACE_BYTES)
+                        .with_back(assets::skybox::BACK_FACE_BYTES),
+                );
+        }}
+
+        // Positioning and rotation accidentally imitates a right-handed 3D
+        // coordinate system with positive Z going farther from model, but this
+        // setting is done because of initial orientation of the loaded Stanford
+        // Bunny mesh.
+        builder.with_camera(Camera::new(
+            Point3::new(1.7, 1.3, 4.0),
+            Vector2::new(240.0, -15.0),
+        ))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_model(mut self, name: &str, path: &str) -> Self {
+        self.scene.models.push(ModelDescription {
+            name: name.to_owned(),
+            source: ModelSource::File(path.to_owned()),
+        });
+        self
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn with_model(mut self, name: &str, data: &'static [u8]) -> Self {
+        self.scene.models.push(ModelDescription {
+            name: name.to_owned(),
+            source: ModelSource::Buffer(data),
+        });
+        self
+    }
+
+    /// Model from a mesh generated in code, e.g. a ground plane from `primitives::plane()`.
+    pub fn with_mesh_data(mut self, name: &str, mesh_data: MeshData) -> Self {
+        self.scene.models.push(ModelDescription {
+            name: name.to_owned(),
+            source: ModelSource::Generated(Arc::new(mesh_data)),
+        });
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_skybox(mut self, skybox_builder: SkyboxFileBuilder) -> Self {
+        self.scene.skybox = Some(skybox_builder);
+        self
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn with_skybox(mut self, skybox_builder: SkyboxBufferBuilder) -> Self {
+        self.scene.skybox = Some(skybox_builder);
+        self
+    }
+
+    /// Terrain generated from a grayscale PNG or EXR heightmap, see `Terrain`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_terrain(mut self, path: &str, settings: TerrainSettings) -> Self {
+        self.scene.terrain = Some(TerrainDescription {
+            path: path.to_owned(),
+            settings,
+        });
+        self
+    }
+
+    /// Initial camera pose.
+    pub fn with_camera(mut self, camera: Camera) -> Self {
+        self.camera = camera;
+        self
+    }
+
+    /// Initial rendering settings, also adjustable from GUI later.
+    pub fn with_draw_properties(mut self, draw_props: DrawProperties) -> Self {
+        self.draw_props = draw_props;
+        self
+    }
+
+    /// Custom draw code called on every rendered frame, see `RenderHook`.
+    pub fn with_render_hook(mut self, render_hook: impl RenderHook + 'static) -> Self {
+        self.render_hooks.push(Box::new(render_hook));
+        self
+    }
+
+    /// Number of stencil bits requested for the window framebuffer. Offscreen framebuffers of the
+    /// renderer always have 8 bits of stencil. Web canvas only distinguishes between no stencil
+    /// buffer (0) and 8 bits.
+    pub fn with_stencil_bits(mut self, stencil_bits: u8) -> Self {
+        self.stencil_bits = stencil_bits;
+        self
+    }
+
+    /// GUI scale on top of the scale factor of the display, overriding the scale remembered in
+    /// `config.ron`, e.g. `1.5` for 150%.
+    pub fn with_ui_scale(mut self, ui_scale: f32) -> Self {
+        self.ui_scale = Some(ui_scale);
+        self
+    }
+
+    /// Reload models when their files change in the directory, and open model files saved into it,
+    /// e.g. when exporting from a modeling application.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_watch_directory(mut self, directory: &str) -> Self {
+        self.watch_directory = Some(directory.to_owned());
+        self
+    }
+
+    /// Listen for Open Sound Control messages on the UDP address, e.g. from TouchOSC or lighting
+    /// desks, setting draw properties, the camera and modulation levels. See the README for the
+    /// addresses.
+    ///
+    /// Senders are not authenticated, so addresses of other network interfaces than loopback
+    /// should only be used on trusted networks.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_osc_listener(mut self, address: SocketAddr) -> Self {
+        self.osc_address = Some(address);
+        self
+    }
+
+    /// Save screenshots into the directory instead of the working directory, including the ones
+    /// requested by remote clients.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_screenshot_directory(mut self, directory: &str) -> Self {
+        self.screenshot_directory = Some(directory.to_owned());
+        self
+    }
+
+    /// Accept JSON remote control requests over WebSocket on the TCP address, e.g. from render
+    /// farms or test automation. See the README for the protocol.
+    ///
+    /// Clients are not authenticated, so addresses of other network interfaces than loopback
+    /// should only be used on trusted networks.
+    #[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+    pub fn with_remote_server(mut self, address: SocketAddr) -> Self {
+        self.remote_server_address = Some(address);
+        self
+    }
+
+    /// Answer HTTP requests on the TCP address with PNG images of rendered frames, e.g. for
+    /// integrating into web services. See the README for the endpoints.
+    ///
+    /// Clients are not authenticated, so addresses of other network interfaces than loopback
+    /// should only be used on trusted networks.
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    pub fn with_snapshot_server(mut self, address: SocketAddr) -> Self {
+        self.snapshot_server_address = Some(address);
+        self
+    }
+
+    /// Record keyboard and mouse camera input into a file when the application exits, to be
+    /// replayed with `with_input_playback()`, e.g. to reproduce a bug.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_input_recording(mut self, path: &str) -> Self {
+        self.input_recording_path = Some(path.to_owned());
+        self
+    }
+
+    /// Replay camera input recorded by `with_input_recording()` instead of taking it from the
+    /// user. With `exit_when_finished`, the application exits after the last recorded update, e.g.
+    /// for automated smoke tests of the whole application.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_input_playback(mut self, path: &str, exit_when_finished: bool) -> Self {
+        self.input_playback_path = Some(path.to_owned());
+        self.exit_after_playback = exit_when_finished;
+        self
+    }
+
+    /// Render the scene in stereo into a VR headset through the OpenXR runtime of the system, in
+    /// addition to the window. Camera movement moves the play space of the headset. Errors are
+    /// reported in the GUI when no headset is available.
+    #[cfg(all(feature = "xr", not(target_arch = "wasm32")))]
+    pub fn with_xr(mut self) -> Self {
+        self.xr_enabled = true;
+        self
+    }
+
+    /// Analyze the default audio input device into frequency bands, bass first, which are the
+    /// sources of `Scene::modulation`. Errors are reported in the GUI when there is no input
+    /// device.
+    #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+    pub fn with_audio_input(mut self) -> Self {
+        self.audio_enabled = true;
+        self
+    }
+
+    /// Assets are not loaded until the application is started with `App::run()`.
+    pub fn build(mut self) -> Result<App, String> {
+        if self.scene.models.is_empty() {
+            return Err("scene must contain at least one model".to_owned());
+        }
+        let last_model_index = self.scene.models.len() - 1;
+        self.draw_props.selected_model_index =
+            self.draw_props.selected_model_index.min(last_model_index);
+        #[cfg(not(target_arch = "wasm32"))]
+        let asset_watcher = self
+            .watch_directory
+            .as_deref()
+            .map(AssetWatcher::new)
+            .transpose()?;
+        #[cfg(not(target_arch = "wasm32"))]
+        let osc_listener = self.osc_address.map(OscListener::new).transpose()?;
+        #[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+        let remote_server = self
+            .remote_server_address
+            .map(RemoteServer::new)
+            .transpose()?;
+        #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+        let snapshot_server = self
+            .snapshot_server_address
+            .map(SnapshotServer::new)
+            .transpose()?;
+        #[cfg(not(target_arch = "wasm32"))]
+        let input_playback = self
+            .input_playback_path
+            .as_deref()
+            .map(InputPlayback::load)
+            .transpose()?;
+
+        Ok(App {
+            window: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            glutin_window_context: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            vsync_mode: VsyncMode::Off,
+            #[cfg(not(target_arch = "wasm32"))]
+            frame_rate_info: FrameRateInfo::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            screenshot_requested: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            screenshot_name: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            screenshot_directory: self.screenshot_directory,
+            #[cfg(not(target_arch = "wasm32"))]
+            frame_sequence_recorder: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            lightmap_bake: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            asset_watcher,
+            #[cfg(not(target_arch = "wasm32"))]
+            osc_listener,
+            #[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+            remote_server,
+            #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+            snapshot_server,
+            #[cfg(not(target_arch = "wasm32"))]
+            update_count: 0,
+            #[cfg(not(target_arch = "wasm32"))]
+            input_recorder: self.input_recording_path.as_deref().map(InputRecorder::new),
+            #[cfg(not(target_arch = "wasm32"))]
+            input_playback,
+            #[cfg(not(target_arch = "wasm32"))]
+            exit_after_playback: self.exit_after_playback,
+            #[cfg(all(feature = "xr", not(target_arch = "wasm32")))]
+            xr_enabled: self.xr_enabled,
+            #[cfg(all(feature = "xr", not(target_arch = "wasm32")))]
+            xr_session: None,
+            #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+            audio_enabled: self.audio_enabled,
+            #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+            audio_analyzer: None,
+            suspended: false,
+            renderer: None,
+            input_state: InputState::default(),
+            right_mouse_pressed: false,
+            cursor_position: (0.0, 0.0),
+            light_dragging: false,
+            draw_props: Arc::new(RefCell::new(self.draw_props)),
+            scene: Scene::new(self.camera),
+            scene_description: self.scene,
+            render_hooks: self.render_hooks,
+            stencil_bits: self.stencil_bits,
+            ui_scale: self.ui_scale,
+            update_callback: None,
+            quality_scaler: QualityScaler::default(),
+            asset_cache: None,
+            skybox: None,
+            gui: None,
+            #[cfg(target_arch = "wasm32")]
+            html_ui: None,
+            #[cfg(target_arch = "wasm32")]
+            web_xr_session: None,
+            #[cfg(target_arch = "wasm32")]
+            update_credit: 0.0,
+            #[cfg(target_arch = "wasm32")]
+            previous_frame_time: None,
+            #[cfg(target_arch = "wasm32")]
+            shared: Weak::new(),
+        })
+    }
+}
+
+/// Context Object pattern
+/// (https://accu.org/journals/overload/12/63/kelly_246/) to avoid blowing up App with large number
+/// of Option<> fields.
+#[cfg(not(target_arch = "wasm32"))]
+struct GlutinWindowContext {
+    gl_config: Config,
+    glutin_context: PossiblyCurrentContext,
+    /// Destroyed while the application is suspended, GL resources of the context are kept.
+    glutin_surface: Option<Surface<WindowSurface>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl GlutinWindowContext {
+    fn new(
+        gl_config: Config,
+        glutin_context: PossiblyCurrentContext,
+        glutin_surface: Surface<WindowSurface>,
+    ) -> Self {
+        Self {
+            gl_config,
+            glutin_context,
+            glutin_surface: Some(glutin_surface),
+        }
+    }
+
+    /// Modes unsupported by the platform or the driver fall back to waiting for every vertical
+    /// blank.
+    fn set_vsync_mode(&self, vsync_mode: VsyncMode) {
+        let Some(glutin_surface) = &self.glutin_surface else {
+            return;
+        };
+        let set_swap_interval = |swap_interval| {
+            glutin_surface
+                .set_swap_interval(&self.glutin_context, swap_interval)
+                .map_err(|e| e.to_string())
+        };
+        let result = match vsync_mode {
+            VsyncMode::Off => set_swap_interval(SwapInterval::DontWait),
+            VsyncMode::On => set_swap_interval(SwapInterval::Wait(NonZeroU32::new(1).unwrap())),
+            VsyncMode::Adaptive => {
+                swap_control::set_adaptive_vsync(&self.glutin_context.display(), glutin_surface)
+            }
+            VsyncMode::Half => set_swap_interval(SwapInterval::Wait(NonZeroU32::new(2).unwrap())),
+        };
+        if let Err(e) = result {
+            eprintln!("unable to set {} vertical sync: {e}", vsync_mode.label());
+            if vsync_mode != VsyncMode::On {
+                if let Err(e) = set_swap_interval(SwapInterval::Wait(NonZeroU32::new(1).unwrap())) {
+                    eprintln!("unable to enable vertical sync: {e}");
+                }
+            }
+        }
+    }
+
+    fn resize(&self, width: u32, height: u32) {
+        if let Some(glutin_surface) = &self.glutin_surface {
+            glutin_surface.resize(
+                &self.glutin_context,
+                NonZeroU32::new(width).unwrap(),
+                NonZeroU32::new(height).unwrap(),
+            );
+        }
+    }
+
+    fn swap_buffers(&self) {
+        if let Some(glutin_surface) = &self.glutin_surface {
+            let _ = glutin_surface.swap_buffers(&self.glutin_context);
+        }
+    }
+
+    /// Release the window surface, e.g. when the native window of Android is destroyed on
+    /// suspension. Context stays alive without a current surface.
+    fn suspend(&mut self) {
+        let _ = self.glutin_context.make_not_current_in_place();
+        self.glutin_surface = None;
+    }
+
+    /// Recreate the window surface released by `suspend()` and make the context current with it.
+    fn resume(&mut self, window: &Window) -> Result<(), String> {
+        let glutin_surface = create_window_surface(window, &self.gl_config)?;
+        self.glutin_context
+            .make_current(&glutin_surface)
+            .map_err(|e| format!("failed to context make current: {:?}", e))?;
+        self.glutin_surface = Some(glutin_surface);
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn initialize_native_window(
+    event_loop: &ActiveEventLoop,
+    stencil_bits: u8,
+) -> Result<(Window, GlutinWindowContext, glow::Context), String> {
+    let window_attributes = WindowAttributes::default()
+        .with_title(WINDOW_TITLE)
+        .with_resizable(false)
+        .with_inner_size(LogicalSize::new(WINDOW_WIDTH, WINDOW_HEIGHT));
+    let display_builder =
+        DisplayBuilder::new().with_window_attributes(Some(window_attributes.clone()));
+    let (mut window, gl_config) = display_builder
+        .build(
+            event_loop,
+            ConfigTemplateBuilder::default().with_stencil_size(stencil_bits),
+            gl_config_picker,
+        )
+        .map_err(|e| format!("failed to create gl_config: {:?}", e))?;
+    let raw_window_handle = window
+        .as_ref()
+        .and_then(|w| w.window_handle().ok())
+        .map(|handle| handle.as_raw());
+    match raw_window_handle {
+        Some(RawWindowHandle::Win32(_)) => println!("Display backend is Win32"),
+        Some(RawWindowHandle::Xlib(_)) => println!("Display backend is X11"),
+        Some(RawWindowHandle::Wayland(_)) => println!("Display backend is Wayland"),
+        _ => (),
+    }
+
+    let gl_display = gl_config.display();
+    let not_current_gl_context = create_gl_context(&gl_config, raw_window_handle)?;
+
+    // Apply glutin gl_config options to winit window (removing incompatible options in the
+    // process)
+    let window = match window.take() {
+        Some(w) => w,
+        None => glutin_winit::finalize_window(event_loop, window_attributes, &gl_config)
+            .map_err(|e| format!("failed to apply GL options to window: {:?}", e))?,
+    };
+
+    let glutin_surface = create_window_surface(&window, &gl_config)?;
     let glutin_context = not_current_gl_context
         .make_current(&glutin_surface)
         .map_err(|e| format!("failed to context make current: {:?}", e))?;
@@ -662,15 +2492,75 @@ fn initialize_native_window(
 
     Ok((
         window,
-        GlutinWindowContext::new(glutin_context, glutin_surface),
+        GlutinWindowContext::new(gl_config, glutin_context, glutin_surface),
         gl,
     ))
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-fn gl_config_picker(configs: Box<dyn Iterator<Item = Config> + '_>) -> Config {
+fn create_window_surface(
+    window: &Window,
+    gl_config: &Config,
+) -> Result<Surface<WindowSurface>, String> {
+    // Let the driver convert linear color shader outputs to sRGB when writing into the default
+    // framebuffer.
+    let surface_attributes = window
+        .build_surface_attributes(
+            SurfaceAttributesBuilder::default().with_srgb(Some(gl_config.srgb_capable())),
+        )
+        .map_err(|e| format!("failed to build window surface attributes: {:?}", e))?;
+    unsafe {
+        gl_config
+            .display()
+            .create_window_surface(gl_config, &surface_attributes)
+            .map_err(|e| format!("failed to create window surface: {:?}", e))
+    }
+}
+
+/// Create the first supported context of `GL_CONTEXT_CANDIDATES`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn create_gl_context(
+    gl_config: &Config,
+    raw_window_handle: Option<RawWindowHandle>,
+) -> Result<NotCurrentContext, String> {
+    let mut errors = Vec::new();
+    for (name, context_api) in GL_CONTEXT_CANDIDATES {
+        let context_attributes: ContextAttributes = match context_api {
+            ContextApi::OpenGl(_) => ContextAttributesBuilder::new().with_profile(GlProfile::Core),
+            ContextApi::Gles(_) => ContextAttributesBuilder::new(),
+        }
+        .with_context_api(context_api)
+        .build(raw_window_handle);
+
+        match unsafe {
+            gl_config
+                .display()
+                .create_context(gl_config, &context_attributes)
+        } {
+            Ok(context) => {
+                println!("Created {name} context");
+                return Ok(context);
+            }
+            Err(e) => errors.push(format!("{name}: {e}")),
+        }
+    }
+
+    Err(format!(
+        "failed to create any supported OpenGL context: {}",
+        errors.join("; ")
+    ))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn gl_config_picker(configs: Box<dyn Iterator<Item = Config> + '_>) -> Config {
     configs
         .reduce(|accum, config| {
+            // Prefer sRGB-capable configs above everything else, because color output would be
+            // too dark without them.
+            if config.srgb_capable() != accum.srgb_capable() {
+                return if config.srgb_capable() { config } else { accum };
+            }
+
             let transparency_check = config.supports_transparency().unwrap_or(false)
                 & !accum.supports_transparency().unwrap_or(false);
 
@@ -684,7 +2574,10 @@ fn gl_config_picker(configs: Box<dyn Iterator<Item = Config> + '_>) -> Config {
 }
 
 #[cfg(target_arch = "wasm32")]
-fn initialize_web_window(event_loop: &ActiveEventLoop) -> Result<(Window, glow::Context), String> {
+fn initialize_web_window(
+    event_loop: &ActiveEventLoop,
+    stencil_bits: u8,
+) -> Result<(Window, glow::Context), String> {
     let window = web_sys::window().ok_or_else(|| "could not get browser window".to_string())?;
     let document = window
         .document()
@@ -703,8 +2596,19 @@ fn initialize_web_window(event_loop: &ActiveEventLoop) -> Result<(Window, glow::
         .create_window(window_attributes)
         .map_err(|e| format!("failed to create window: {:?}", e))?;
 
+    // WebGL context attributes are passed as plain JavaScript object
+    let context_options = js_sys::Object::new();
+    js_sys::Reflect::set(
+        &context_options,
+        &"stencil".into(),
+        &(stencil_bits > 0).into(),
+    )
+    .map_err(|e| format!("failed to set WebGL2 context options: {:?}", e))?;
+    // Context must be compatible with the headset to render WebXR sessions with it
+    js_sys::Reflect::set(&context_options, &"xrCompatible".into(), &true.into())
+        .map_err(|e| format!("failed to set WebGL2 context options: {:?}", e))?;
     let webgl2_context: WebGl2RenderingContext = canvas
-        .get_context("webgl2")
+        .get_context_with_context_options("webgl2", &context_options)
         .map_err(|e| format!("failed to get WebGL2 context: {:?}", e))?
         .ok_or_else(|| "'webgl2' context is not available".to_string())?
         .dyn_into()
@@ -0,0 +1,166 @@
+use std::{collections::HashMap, sync::Arc};
+
+use cfg_if::cfg_if;
+
+use crate::{model::Mesh, RendererError, Skybox};
+
+cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
+    use std::path::PathBuf;
+
+    use crate::SkyboxFileBuilder;
+} else {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    use crate::SkyboxBufferBuilder;
+}}
+
+/// Identity of a loaded asset. Files are identified by their canonical path, embedded data by the
+/// hash of its contents.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum AssetKey {
+    #[cfg(not(target_arch = "wasm32"))]
+    Paths(Vec<PathBuf>),
+    #[cfg(target_arch = "wasm32")]
+    ContentHash(u64),
+}
+
+/// Number and estimated GPU memory size of cached assets.
+#[derive(Default, Clone, Copy)]
+pub struct AssetMemoryUsage {
+    pub mesh_count: usize,
+    pub mesh_bytes: usize,
+    pub skybox_count: usize,
+    pub skybox_bytes: usize,
+}
+
+impl AssetMemoryUsage {
+    pub fn total_bytes(&self) -> usize {
+        self.mesh_bytes + self.skybox_bytes
+    }
+}
+
+/// Shared storage of loaded meshes and skyboxes to avoid decoding and uploading the same asset to
+/// the GPU multiple times.
+///
+/// Assets are kept alive by the cache even if no model or skybox uses them anymore, until
+/// explicitly evicted.
+pub struct AssetCache {
+    gl: Arc<glow::Context>,
+    meshes: HashMap<AssetKey, Arc<Mesh>>,
+    skyboxes: HashMap<AssetKey, Arc<Skybox>>,
+}
+
+impl AssetCache {
+    pub fn new(gl: Arc<glow::Context>) -> Self {
+        Self {
+            gl,
+            meshes: HashMap::new(),
+            skyboxes: HashMap::new(),
+        }
+    }
+
+    /// Graphics context the assets are uploaded into, e.g. for creating uncached assets.
+    pub fn gl(&self) -> &Arc<glow::Context> {
+        &self.gl
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_mesh_from_file(&mut self, path: &str) -> Result<Arc<Mesh>, String> {
+        let key = AssetKey::Paths(vec![canonical_path(path)]);
+        if let Some(mesh) = self.meshes.get(&key) {
+            return Ok(mesh.clone());
+        }
+
+        let mesh = Arc::new(Mesh::create_from_file(self.gl.clone(), path)?);
+        self.meshes.insert(key, mesh.clone());
+        Ok(mesh)
+    }
+
+    /// Load mesh from file again, e.g. after it was changed on disk. Models keep using the previous
+    /// mesh until it's replaced with the returned one.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reload_mesh_from_file(&mut self, path: &str) -> Result<Arc<Mesh>, String> {
+        let key = AssetKey::Paths(vec![canonical_path(path)]);
+        let mesh = Arc::new(Mesh::create_from_file(self.gl.clone(), path)?);
+        self.meshes.insert(key, mesh.clone());
+        Ok(mesh)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn load_mesh_from_buffer(&mut self, data: &'static [u8]) -> Result<Arc<Mesh>, String> {
+        let key = AssetKey::ContentHash(content_hash(&[data]));
+        if let Some(mesh) = self.meshes.get(&key) {
+            return Ok(mesh.clone());
+        }
+
+        let mesh = Arc::new(Mesh::create_from_buffer(self.gl.clone(), data)?);
+        self.meshes.insert(key, mesh.clone());
+        Ok(mesh)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_skybox(
+        &mut self,
+        builder: SkyboxFileBuilder,
+    ) -> Result<Arc<Skybox>, RendererError> {
+        let key = AssetKey::Paths(builder.face_paths().map(canonical_path).to_vec());
+        let gl = self.gl.clone();
+        self.load_skybox_with_key(key, || builder.build(gl))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn load_skybox(
+        &mut self,
+        builder: SkyboxBufferBuilder,
+    ) -> Result<Arc<Skybox>, RendererError> {
+        let key = AssetKey::ContentHash(content_hash(&builder.face_data()));
+        let gl = self.gl.clone();
+        self.load_skybox_with_key(key, || builder.build(gl))
+    }
+
+    fn load_skybox_with_key(
+        &mut self,
+        key: AssetKey,
+        build: impl FnOnce() -> Result<Skybox, RendererError>,
+    ) -> Result<Arc<Skybox>, RendererError> {
+        if let Some(skybox) = self.skyboxes.get(&key) {
+            return Ok(skybox.clone());
+        }
+
+        let skybox = Arc::new(build()?);
+        self.skyboxes.insert(key, skybox.clone());
+        Ok(skybox)
+    }
+
+    /// Release assets that are not used outside of the cache. Returns number of evicted assets.
+    pub fn evict_unused(&mut self) -> usize {
+        let count = self.meshes.len() + self.skyboxes.len();
+        self.meshes.retain(|_, mesh| Arc::strong_count(mesh) > 1);
+        self.skyboxes
+            .retain(|_, skybox| Arc::strong_count(skybox) > 1);
+        count - self.meshes.len() - self.skyboxes.len()
+    }
+
+    pub fn memory_usage(&self) -> AssetMemoryUsage {
+        AssetMemoryUsage {
+            mesh_count: self.meshes.len(),
+            mesh_bytes: self.meshes.values().map(|m| m.gpu_memory_size()).sum(),
+            skybox_count: self.skyboxes.len(),
+            skybox_bytes: self.skyboxes.values().map(|s| s.gpu_memory_size()).sum(),
+        }
+    }
+}
+
+/// Same file referred by different relative paths is loaded only once. Missing files keep their
+/// path to report the error when loading.
+#[cfg(not(target_arch = "wasm32"))]
+fn canonical_path(path: &str) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn content_hash(data: &[&[u8]]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
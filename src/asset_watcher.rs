@@ -0,0 +1,92 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Directory is scanned at most this often, listing files is not free.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Model file formats picked up from the watched directory.
+const MODEL_EXTENSIONS: [&str; 2] = ["obj", "ply"];
+
+/// Modification time and size of a file, changing while the file is being written.
+type FileStamp = (SystemTime, u64);
+
+/// Reports model files created or changed in a directory, e.g. exported from a modeling
+/// application.
+///
+/// Directory is polled instead of subscribing to file system events, which would need a
+/// platform-specific API for each desktop operating system. Files are only reported once their
+/// stamp stays the same between two polls, so half-written files are not loaded.
+pub struct AssetWatcher {
+    directory: PathBuf,
+    // Stamps seen during the last poll
+    observed: HashMap<PathBuf, FileStamp>,
+    // Stamps of files when they were last reported, or when watching started
+    reported: HashMap<PathBuf, FileStamp>,
+    last_poll: Instant,
+}
+
+impl AssetWatcher {
+    /// Files already in the directory are not reported until they change.
+    pub fn new(directory: &str) -> Result<Self, String> {
+        let directory = PathBuf::from(directory);
+        let observed = scan(&directory)?;
+        Ok(Self {
+            directory,
+            reported: observed.clone(),
+            observed,
+            last_poll: Instant::now(),
+        })
+    }
+
+    /// Paths of model files that were created or modified and finished writing since the last
+    /// report.
+    pub fn poll(&mut self) -> Result<Vec<PathBuf>, String> {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return Ok(Vec::new());
+        }
+        self.last_poll = Instant::now();
+
+        let current = scan(&self.directory)?;
+        let mut changed = Vec::new();
+        for (path, stamp) in &current {
+            let settled = self.observed.get(path) == Some(stamp);
+            if settled && self.reported.get(path) != Some(stamp) {
+                self.reported.insert(path.clone(), *stamp);
+                changed.push(path.clone());
+            }
+        }
+        self.reported.retain(|path, _| current.contains_key(path));
+        self.observed = current;
+        changed.sort();
+        Ok(changed)
+    }
+}
+
+fn scan(directory: &Path) -> Result<HashMap<PathBuf, FileStamp>, String> {
+    let entries = std::fs::read_dir(directory).map_err(|e| {
+        format!(
+            "unable to watch directory {}: {e}",
+            directory.to_string_lossy()
+        )
+    })?;
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| is_model_file(path))
+        .filter_map(|path| {
+            // File can disappear between listing and reading its metadata
+            let metadata = std::fs::metadata(&path).ok()?;
+            Some((path, (metadata.modified().ok()?, metadata.len())))
+        })
+        .collect())
+}
+
+fn is_model_file(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        MODEL_EXTENSIONS
+            .iter()
+            .any(|model_extension| extension.eq_ignore_ascii_case(model_extension))
+    })
+}
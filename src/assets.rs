@@ -7,17 +7,140 @@ use cfg_if::cfg_if;
 //
 // TODO: Switch to Fetch API on web target instead of embedding assets into binary.
 
+/// Display names of bundled models in the order they are loaded.
+pub const MODEL_NAMES: [&str; 3] = ["Blender Cube", "Utah Teapot", "Stanford Bunny"];
+
+/// Shader sources are embedded into the binary on both native and web builds.
+///
+/// Slight increase in startup time because lack of file system read calls for shader code. No
+/// significant size increase in binary.
+pub mod shader {
+    /// Sources of every graphics shader program written in the same GLSL dialect.
+    pub struct ShaderSources {
+        pub model_vertex: &'static str,
+        pub model_fragment: &'static str,
+        pub skybox_vertex: &'static str,
+        pub skybox_fragment: &'static str,
+        pub postprocess_vertex: &'static str,
+        pub postprocess_fragment: &'static str,
+        pub reflective_plane_vertex: &'static str,
+        pub reflective_plane_fragment: &'static str,
+        pub gbuffer_fragment: &'static str,
+        pub deferred_lighting_fragment: &'static str,
+        pub label_vertex: &'static str,
+        pub label_fragment: &'static str,
+        pub line_vertex: &'static str,
+        pub line_fragment: &'static str,
+        pub terrain_vertex: &'static str,
+        pub terrain_fragment: &'static str,
+        pub overdraw_fragment: &'static str,
+        pub shadow_depth_fragment: &'static str,
+        pub area_light_fragment: &'static str,
+        pub post_effect_fragment: &'static str,
+        pub motion_blur_fragment: &'static str,
+        pub taa_resolve_fragment: &'static str,
+        /// Template of custom shaders registered at runtime.
+        pub custom_vertex: &'static str,
+        pub custom_fragment: &'static str,
+    }
+
+    /// GLSL 4.30 variants. Requires OpenGL 4.3.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub const GL4: ShaderSources = ShaderSources {
+        model_vertex: include_str!("../assets/shaders/model_gl4.vert.glsl"),
+        model_fragment: include_str!("../assets/shaders/model_gl4.frag.glsl"),
+        skybox_vertex: include_str!("../assets/shaders/skybox_gl4.vert.glsl"),
+        skybox_fragment: include_str!("../assets/shaders/skybox_gl4.frag.glsl"),
+        postprocess_vertex: include_str!("../assets/shaders/postprocess_gl4.vert.glsl"),
+        postprocess_fragment: include_str!("../assets/shaders/postprocess_gl4.frag.glsl"),
+        reflective_plane_vertex: include_str!("../assets/shaders/reflective_plane_gl4.vert.glsl"),
+        reflective_plane_fragment: include_str!("../assets/shaders/reflective_plane_gl4.frag.glsl"),
+        gbuffer_fragment: include_str!("../assets/shaders/gbuffer_gl4.frag.glsl"),
+        deferred_lighting_fragment: include_str!(
+            "../assets/shaders/deferred_lighting_gl4.frag.glsl"
+        ),
+        label_vertex: include_str!("../assets/shaders/label_gl4.vert.glsl"),
+        label_fragment: include_str!("../assets/shaders/label_gl4.frag.glsl"),
+        line_vertex: include_str!("../assets/shaders/line_gl4.vert.glsl"),
+        line_fragment: include_str!("../assets/shaders/line_gl4.frag.glsl"),
+        terrain_vertex: include_str!("../assets/shaders/terrain_gl4.vert.glsl"),
+        terrain_fragment: include_str!("../assets/shaders/terrain_gl4.frag.glsl"),
+        overdraw_fragment: include_str!("../assets/shaders/overdraw_gl4.frag.glsl"),
+        shadow_depth_fragment: include_str!("../assets/shaders/shadow_depth_gl4.frag.glsl"),
+        area_light_fragment: include_str!("../assets/shaders/area_light_gl4.frag.glsl"),
+        post_effect_fragment: include_str!("../assets/shaders/post_effect_gl4.frag.glsl"),
+        motion_blur_fragment: include_str!("../assets/shaders/motion_blur_gl4.frag.glsl"),
+        taa_resolve_fragment: include_str!("../assets/shaders/taa_resolve_gl4.frag.glsl"),
+        custom_vertex: include_str!("../assets/shaders/custom_gl4.vert.glsl"),
+        custom_fragment: include_str!("../assets/shaders/custom_gl4.frag.glsl"),
+    };
+
+    /// GLSL ES 3.00 variants used by WebGL2 and by native OpenGL contexts older than 4.3.
+    pub const GLES3: ShaderSources = ShaderSources {
+        model_vertex: include_str!("../assets/shaders/model_gles3.vert.glsl"),
+        model_fragment: include_str!("../assets/shaders/model_gles3.frag.glsl"),
+        skybox_vertex: include_str!("../assets/shaders/skybox_gles3.vert.glsl"),
+        skybox_fragment: include_str!("../assets/shaders/skybox_gles3.frag.glsl"),
+        postprocess_vertex: include_str!("../assets/shaders/postprocess_gles3.vert.glsl"),
+        postprocess_fragment: include_str!("../assets/shaders/postprocess_gles3.frag.glsl"),
+        reflective_plane_vertex: include_str!("../assets/shaders/reflective_plane_gles3.vert.glsl"),
+        reflective_plane_fragment: include_str!(
+            "../assets/shaders/reflective_plane_gles3.frag.glsl"
+        ),
+        gbuffer_fragment: include_str!("../assets/shaders/gbuffer_gles3.frag.glsl"),
+        deferred_lighting_fragment: include_str!(
+            "../assets/shaders/deferred_lighting_gles3.frag.glsl"
+        ),
+        label_vertex: include_str!("../assets/shaders/label_gles3.vert.glsl"),
+        label_fragment: include_str!("../assets/shaders/label_gles3.frag.glsl"),
+        line_vertex: include_str!("../assets/shaders/line_gles3.vert.glsl"),
+        line_fragment: include_str!("../assets/shaders/line_gles3.frag.glsl"),
+        terrain_vertex: include_str!("../assets/shaders/terrain_gles3.vert.glsl"),
+        terrain_fragment: include_str!("../assets/shaders/terrain_gles3.frag.glsl"),
+        overdraw_fragment: include_str!("../assets/shaders/overdraw_gles3.frag.glsl"),
+        shadow_depth_fragment: include_str!("../assets/shaders/shadow_depth_gles3.frag.glsl"),
+        area_light_fragment: include_str!("../assets/shaders/area_light_gles3.frag.glsl"),
+        post_effect_fragment: include_str!("../assets/shaders/post_effect_gles3.frag.glsl"),
+        motion_blur_fragment: include_str!("../assets/shaders/motion_blur_gles3.frag.glsl"),
+        taa_resolve_fragment: include_str!("../assets/shaders/taa_resolve_gles3.frag.glsl"),
+        custom_vertex: include_str!("../assets/shaders/custom_gles3.vert.glsl"),
+        custom_fragment: include_str!("../assets/shaders/custom_gles3.frag.glsl"),
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub const VERTEX_DISPLACEMENT_COMPUTE_SRC: &str =
+        include_str!("../assets/shaders/vertex_displacement_gl4.comp.glsl");
+
+    /// Debug passes built on geometry shaders, which are unavailable in GLSL ES 3.00.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub const FACE_NORMALS_VERTEX_SRC: &str =
+        include_str!("../assets/shaders/face_normals_gl4.vert.glsl");
+    #[cfg(not(target_arch = "wasm32"))]
+    pub const FACE_NORMALS_GEOMETRY_SRC: &str =
+        include_str!("../assets/shaders/face_normals_gl4.geom.glsl");
+    #[cfg(not(target_arch = "wasm32"))]
+    pub const FACE_NORMALS_FRAGMENT_SRC: &str =
+        include_str!("../assets/shaders/face_normals_gl4.frag.glsl");
+    #[cfg(not(target_arch = "wasm32"))]
+    pub const EXPLODED_VIEW_VERTEX_SRC: &str =
+        include_str!("../assets/shaders/exploded_view_gl4.vert.glsl");
+    #[cfg(not(target_arch = "wasm32"))]
+    pub const EXPLODED_VIEW_GEOMETRY_SRC: &str =
+        include_str!("../assets/shaders/exploded_view_gl4.geom.glsl");
+    #[cfg(not(target_arch = "wasm32"))]
+    pub const EXPLODED_VIEW_FRAGMENT_SRC: &str =
+        include_str!("../assets/shaders/exploded_view_gl4.frag.glsl");
+}
+
+/// Fluent catalogues of GUI strings, embedded like shaders so that switching language needs no
+/// file access.
+pub mod locale {
+    pub const EN_US: &str = include_str!("../assets/locales/en-US.ftl");
+    pub const HU_HU: &str = include_str!("../assets/locales/hu-HU.ftl");
+}
+
 cfg_if! {
     if #[cfg(not(target_arch = "wasm32"))] {
-        // Slight increase in startup time because lack of file system read calls for shader code.
-        // No significant size increase in binary.
-        pub mod shader {
-            pub const MODEL_VERTEX_SRC: &str = include_str!("../assets/shaders/model_gl4.vert.glsl");
-            pub const MODEL_FRAGMENT_SRC: &str = include_str!("../assets/shaders/model_gl4.frag.glsl");
-            pub const SKYBOX_VERTEX_SRC: &str = include_str!("../assets/shaders/skybox_gl4.vert.glsl");
-            pub const SKYBOX_FRAGMENT_SRC: &str = include_str!("../assets/shaders/skybox_gl4.frag.glsl");
-        }
-
         pub mod skybox {
             pub const RIGHT_FACE_PATH: &str = "assets/skybox/right.jpg";
             pub const LEFT_FACE_PATH: &str = "assets/skybox/left.jpg";
@@ -34,13 +157,6 @@ cfg_if! {
         }
     }
     else {
-        pub mod shader {
-            pub const MODEL_VERTEX_SRC: &str = include_str!("../assets/shaders/model_gles3.vert.glsl");
-            pub const MODEL_FRAGMENT_SRC: &str = include_str!("../assets/shaders/model_gles3.frag.glsl");
-            pub const SKYBOX_VERTEX_SRC: &str = include_str!("../assets/shaders/skybox_gles3.vert.glsl");
-            pub const SKYBOX_FRAGMENT_SRC: &str = include_str!("../assets/shaders/skybox_gles3.frag.glsl");
-        }
-
         pub mod skybox {
             pub const RIGHT_FACE_BYTES: &'static [u8] = include_bytes!("../assets/skybox/right.jpg");
             pub const LEFT_FACE_BYTES: &'static [u8] = include_bytes!("../assets/skybox/left.jpg");
@@ -0,0 +1,146 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    FromSample, SizedSample,
+};
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+
+/// Samples transformed at once, about 20 ms at common sample rates.
+const FFT_SIZE: usize = 1024;
+/// Number of frequency bands the input is analyzed into, from bass to treble.
+pub const AUDIO_BAND_COUNT: usize = 4;
+/// Upper frequency of each band in Hz, the first band starts at `LOWEST_FREQUENCY`.
+const BAND_EDGES: [f32; AUDIO_BAND_COUNT] = [150.0, 600.0, 2500.0, 8000.0];
+const LOWEST_FREQUENCY: f32 = 20.0;
+/// Fraction of the previous level kept by falling levels on each analysis, so beats fade out
+/// instead of flickering.
+const RELEASE: f32 = 0.85;
+/// Fraction of the loudest recent magnitude kept on each analysis. Levels are relative to it, so
+/// quiet and loud input both use the full range.
+const PEAK_DECAY: f32 = 0.998;
+/// Magnitude below which input counts as silence instead of being amplified to full level.
+const NOISE_FLOOR: f32 = 1e-3;
+
+/// Levels of frequency bands of the default audio input device, e.g. a microphone or a loopback
+/// of the playing music, for driving scene parameters with `Modulation`.
+///
+/// Samples are captured on the audio thread, the latest ones are analyzed with FFT whenever levels
+/// are requested.
+pub struct AudioAnalyzer {
+    // Capture stops when dropped
+    _stream: cpal::Stream,
+    samples: Arc<Mutex<VecDeque<f32>>>,
+    sample_rate: f32,
+    fft: Arc<dyn Fft<f32>>,
+    buffer: Vec<Complex<f32>>,
+    levels: [f32; AUDIO_BAND_COUNT],
+    peaks: [f32; AUDIO_BAND_COUNT],
+}
+
+impl AudioAnalyzer {
+    /// Start capturing from the default input device of the system.
+    pub fn new() -> Result<Self, String> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or("no audio input device available")?;
+        let config = device
+            .default_input_config()
+            .map_err(|e| format!("cannot query audio input configuration: {e}"))?;
+        let samples = Arc::new(Mutex::new(VecDeque::with_capacity(2 * FFT_SIZE)));
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config, samples.clone()),
+            cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config, samples.clone()),
+            cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config, samples.clone()),
+            cpal::SampleFormat::I32 => build_stream::<i32>(&device, &config, samples.clone()),
+            format => Err(format!("unsupported audio sample format {format}")),
+        }?;
+        stream
+            .play()
+            .map_err(|e| format!("cannot start audio input: {e}"))?;
+
+        Ok(Self {
+            _stream: stream,
+            samples,
+            sample_rate: config.sample_rate().0 as f32,
+            fft: FftPlanner::new().plan_fft_forward(FFT_SIZE),
+            buffer: vec![Complex::default(); FFT_SIZE],
+            levels: [0.0; AUDIO_BAND_COUNT],
+            peaks: [NOISE_FLOOR; AUDIO_BAND_COUNT],
+        })
+    }
+
+    /// Analyze the latest samples into levels from 0 to 1 of each band, bass first. Levels stay
+    /// the same until enough samples were captured.
+    pub fn levels(&mut self) -> [f32; AUDIO_BAND_COUNT] {
+        {
+            let samples = self.samples.lock().unwrap();
+            if samples.len() < FFT_SIZE {
+                return self.levels;
+            }
+            // Hann window against leakage between bands from cutting the signal at the ends
+            for (index, (bin, sample)) in self.buffer.iter_mut().zip(samples.iter()).enumerate() {
+                let phase = std::f32::consts::TAU * index as f32 / (FFT_SIZE - 1) as f32;
+                let window = 0.5 - 0.5 * phase.cos();
+                *bin = Complex::new(sample * window, 0.0);
+            }
+        }
+        self.fft.process(&mut self.buffer);
+
+        let mut sums = [0.0; AUDIO_BAND_COUNT];
+        let mut counts = [0; AUDIO_BAND_COUNT];
+        let bin_width = self.sample_rate / FFT_SIZE as f32;
+        // Upper half mirrors the lower one for real input
+        for (index, bin) in self.buffer[..FFT_SIZE / 2].iter().enumerate() {
+            let frequency = index as f32 * bin_width;
+            if frequency < LOWEST_FREQUENCY {
+                continue;
+            }
+            if let Some(band) = BAND_EDGES.iter().position(|&edge| frequency < edge) {
+                sums[band] += bin.norm();
+                counts[band] += 1;
+            }
+        }
+        for band in 0..AUDIO_BAND_COUNT {
+            let magnitude = sums[band] / counts[band].max(1) as f32;
+            self.peaks[band] = (self.peaks[band] * PEAK_DECAY)
+                .max(magnitude)
+                .max(NOISE_FLOOR);
+            let level = magnitude / self.peaks[band];
+            self.levels[band] = level.max(self.levels[band] * RELEASE);
+        }
+        self.levels
+    }
+}
+
+/// Capture input stream mixing channels into mono samples, keeping the latest `FFT_SIZE` ones.
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::SupportedStreamConfig,
+    samples: Arc<Mutex<VecDeque<f32>>>,
+) -> Result<cpal::Stream, String>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels().max(1) as usize;
+    device
+        .build_input_stream(
+            &config.config(),
+            move |data: &[T], _| {
+                let mut samples = samples.lock().unwrap();
+                for frame in data.chunks(channels) {
+                    let sum: f32 = frame.iter().map(|sample| sample.to_sample::<f32>()).sum();
+                    samples.push_back(sum / frame.len() as f32);
+                }
+                let excess = samples.len().saturating_sub(FFT_SIZE);
+                samples.drain(..excess);
+            },
+            |e| eprintln!("audio input error: {e}"),
+            None,
+        )
+        .map_err(|e| format!("cannot open audio input stream: {e}"))
+}
@@ -0,0 +1,543 @@
+use std::time::Duration;
+
+use cgmath::{
+    EuclideanSpace, InnerSpace, Matrix4, Point3, SquareMatrix, Transform, Vector3, Vector4,
+};
+
+use crate::model::{Aabb, Vertex};
+
+/// Triangles are not split further below this count.
+const MAX_LEAF_TRIANGLES: usize = 4;
+/// Candidate split planes evaluated along each axis.
+const BIN_COUNT: usize = 16;
+/// Cost of visiting a node relative to testing a triangle, used by the surface area heuristic.
+const TRAVERSAL_COST: f32 = 1.0;
+/// Guards against stack overflow on degenerate input, e.g. many triangles sharing a centroid.
+const MAX_DEPTH: usize = 64;
+
+/// Half-line in 3D space, e.g. cast from the camera through the cursor.
+///
+/// Direction doesn't have to be normalized. Hit distances are measured in multiples of its length,
+/// so rays transformed into model space report the same distances as in world space.
+#[derive(Clone, Copy, Debug)]
+pub struct Ray {
+    pub origin: Point3<f32>,
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    /// Ray cast from the camera through a point of the viewport given in pixels from the top left
+    /// corner. Normalized direction points away from the camera.
+    pub fn through_cursor(
+        projection_view: &Matrix4<f32>,
+        viewport_size: (f32, f32),
+        cursor: (f32, f32),
+    ) -> Option<Self> {
+        let inverse_projection_view = projection_view.invert()?;
+        let (width, height) = viewport_size;
+        let ndc_x = cursor.0 / width * 2.0 - 1.0;
+        let ndc_y = 1.0 - cursor.1 / height * 2.0;
+        let unproject = |ndc_z: f32| {
+            let p = inverse_projection_view * Vector4::new(ndc_x, ndc_y, ndc_z, 1.0);
+            Point3::new(p.x / p.w, p.y / p.w, p.z / p.w)
+        };
+        let origin = unproject(-1.0);
+        Some(Self {
+            origin,
+            direction: (unproject(1.0) - origin).normalize(),
+        })
+    }
+
+    /// Ray in the coordinate space the matrix transforms into, e.g. model space with the inverse
+    /// of the model matrix.
+    pub fn transformed(&self, matrix: &Matrix4<f32>) -> Self {
+        Self {
+            origin: matrix.transform_point(self.origin),
+            direction: matrix.transform_vector(self.direction),
+        }
+    }
+
+    pub fn at(&self, distance: f32) -> Point3<f32> {
+        self.origin + self.direction * distance
+    }
+
+    /// Distance where the ray enters the box, 0 when starting inside.
+    pub fn intersect_aabb(&self, bounds: &Aabb) -> Option<f32> {
+        intersect_bounds(bounds, self, &inverse(self.direction))
+    }
+}
+
+/// Closest intersection of a ray with the triangles of a BVH.
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit {
+    /// Distance along the ray in multiples of the ray direction length.
+    pub distance: f32,
+    /// Index of the triangle in the index buffer the BVH was built from, divided by 3.
+    pub triangle: usize,
+    /// Weights of the second and third vertex of the triangle at the hit point, the first one is
+    /// `1 - u - v`.
+    pub barycentric: (f32, f32),
+}
+
+/// Structure of a built BVH, for judging the build quality and cost.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BvhStats {
+    pub triangle_count: usize,
+    pub node_count: usize,
+    pub leaf_count: usize,
+    pub max_depth: usize,
+    pub max_leaf_triangles: usize,
+    /// Zero on web, where the standard library has no clock.
+    pub build_time: Duration,
+}
+
+/// Bounding volume hierarchy over the triangles of a mesh, answering ray queries on CPU in
+/// logarithmic instead of linear time of the triangle count. Shared by picking and other CPU ray
+/// casting, e.g. occlusion tests of baked lighting.
+///
+/// Nodes are split with the surface area heuristic evaluated at binned split planes. Triangle
+/// positions are copied in leaf order, so leaves read consecutive memory.
+pub struct Bvh {
+    // Root first, children of inner nodes next to each other
+    nodes: Vec<BvhNode>,
+    triangles: Vec<[Point3<f32>; 3]>,
+    // Index of each triangle of `triangles` in the source index buffer, divided by 3
+    triangle_ids: Vec<u32>,
+    stats: BvhStats,
+}
+
+struct BvhNode {
+    bounds: Aabb,
+    // Leaf: first triangle. Inner node: first child, the second one follows it.
+    first: u32,
+    // Number of triangles of a leaf, 0 for inner nodes
+    count: u32,
+}
+
+/// Triangle being sorted into nodes during the build.
+struct BuildTriangle {
+    bounds: Aabb,
+    centroid: Point3<f32>,
+    id: u32,
+}
+
+impl Bvh {
+    pub fn build(vertices: &[Vertex], indices: &[u32]) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let start_time = std::time::Instant::now();
+        let position = |index: u32| Point3::from_vec(vertices[index as usize].position);
+        let mut build_triangles: Vec<BuildTriangle> = indices
+            .chunks_exact(3)
+            .enumerate()
+            .map(|(id, triangle)| {
+                let bounds = Aabb::from_points(triangle.iter().map(|&index| position(index)));
+                BuildTriangle {
+                    bounds,
+                    centroid: bounds.center(),
+                    id: id as u32,
+                }
+            })
+            .collect();
+
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(2 * build_triangles.len() / MAX_LEAF_TRIANGLES + 1),
+            triangles: Vec::new(),
+            triangle_ids: Vec::with_capacity(build_triangles.len()),
+            stats: BvhStats {
+                triangle_count: build_triangles.len(),
+                ..Default::default()
+            },
+        };
+        bvh.nodes.push(BvhNode {
+            bounds: union_bounds(&build_triangles),
+            first: 0,
+            count: 0,
+        });
+        bvh.build_node(0, &mut build_triangles, 0);
+        bvh.triangles = bvh
+            .triangle_ids
+            .iter()
+            .map(|&id| {
+                let i = id as usize * 3;
+                [
+                    position(indices[i]),
+                    position(indices[i + 1]),
+                    position(indices[i + 2]),
+                ]
+            })
+            .collect();
+
+        bvh.stats.node_count = bvh.nodes.len();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            bvh.stats.build_time = start_time.elapsed();
+        }
+        bvh
+    }
+
+    pub fn stats(&self) -> &BvhStats {
+        &self.stats
+    }
+
+    /// Closest triangle hit by the ray within the distance, from either side.
+    pub fn intersect(&self, ray: &Ray, max_distance: f32) -> Option<RayHit> {
+        let mut closest: Option<RayHit> = None;
+        self.traverse(ray, max_distance, |triangle_index, max_distance| {
+            let hit = intersect_triangle(ray, &self.triangles[triangle_index])
+                .filter(|hit| hit.distance < max_distance)?;
+            let hit = RayHit {
+                triangle: self.triangle_ids[triangle_index] as usize,
+                ..hit
+            };
+            closest = Some(hit);
+            Some(hit.distance)
+        });
+        closest
+    }
+
+    /// Whether any triangle is hit by the ray within the distance. Cheaper than `intersect()`,
+    /// because traversal stops at the first hit, e.g. for shadow and occlusion rays.
+    pub fn occluded(&self, ray: &Ray, max_distance: f32) -> bool {
+        let mut occluded = false;
+        self.traverse(ray, max_distance, |triangle_index, max_distance| {
+            occluded = intersect_triangle(ray, &self.triangles[triangle_index])
+                .is_some_and(|hit| hit.distance < max_distance);
+            // Zero distance stops the traversal
+            occluded.then_some(0.0)
+        });
+        occluded
+    }
+
+    /// Visit triangles of leaves whose bounds are hit by the ray, nearer nodes first. The visitor
+    /// returns a shorter distance to cull farther nodes with.
+    fn traverse(
+        &self,
+        ray: &Ray,
+        mut max_distance: f32,
+        mut visit: impl FnMut(usize, f32) -> Option<f32>,
+    ) {
+        if self.stats.triangle_count == 0 {
+            return;
+        }
+        let inverse_direction = inverse(ray.direction);
+        let mut stack = Vec::with_capacity(MAX_DEPTH);
+        stack.push(0);
+        while let Some(node_index) = stack.pop() {
+            let node: &BvhNode = &self.nodes[node_index];
+            let Some(entry) = intersect_bounds(&node.bounds, ray, &inverse_direction) else {
+                continue;
+            };
+            if max_distance <= entry {
+                continue;
+            }
+            if node.count > 0 {
+                let first = node.first as usize;
+                for triangle_index in first..first + node.count as usize {
+                    if let Some(distance) = visit(triangle_index, max_distance) {
+                        max_distance = distance;
+                        if max_distance <= 0.0 {
+                            return;
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let left = node.first as usize;
+            let right = left + 1;
+            let left_entry = intersect_bounds(&self.nodes[left].bounds, ray, &inverse_direction);
+            let right_entry = intersect_bounds(&self.nodes[right].bounds, ray, &inverse_direction);
+            // Nearer child is popped first
+            match (left_entry, right_entry) {
+                (Some(left_entry), Some(right_entry)) if right_entry < left_entry => {
+                    stack.extend([left, right]);
+                }
+                (Some(_), Some(_)) => stack.extend([right, left]),
+                (Some(_), None) => stack.push(left),
+                (None, Some(_)) => stack.push(right),
+                (None, None) => (),
+            }
+        }
+    }
+
+    /// Split the node into children or turn it into a leaf of the triangles.
+    fn build_node(&mut self, node_index: usize, triangles: &mut [BuildTriangle], depth: usize) {
+        let split = if triangles.len() <= MAX_LEAF_TRIANGLES || MAX_DEPTH <= depth {
+            None
+        } else {
+            find_split(&self.nodes[node_index].bounds, triangles)
+        };
+
+        let Some(left_count) = split else {
+            // Leaves are completed from left to right, so their triangles follow each other
+            self.nodes[node_index].first = self.triangle_ids.len() as u32;
+            self.nodes[node_index].count = triangles.len() as u32;
+            self.triangle_ids
+                .extend(triangles.iter().map(|triangle| triangle.id));
+            self.stats.leaf_count += 1;
+            self.stats.max_depth = self.stats.max_depth.max(depth);
+            self.stats.max_leaf_triangles = self.stats.max_leaf_triangles.max(triangles.len());
+            return;
+        };
+
+        let (left, right) = triangles.split_at_mut(left_count);
+        let left_index = self.nodes.len();
+        self.nodes[node_index].first = left_index as u32;
+        for child in [&*left, &*right] {
+            self.nodes.push(BvhNode {
+                bounds: union_bounds(child),
+                first: 0,
+                count: 0,
+            });
+        }
+        self.build_node(left_index, left, depth + 1);
+        self.build_node(left_index + 1, right, depth + 1);
+    }
+}
+
+/// Partition triangles at the binned split plane with the lowest cost according to the surface
+/// area heuristic. Returns the number of triangles moved to the left side, `None` when keeping the
+/// triangles in a leaf is cheaper.
+fn find_split(bounds: &Aabb, triangles: &mut [BuildTriangle]) -> Option<usize> {
+    let centroid_bounds = Aabb::from_points(triangles.iter().map(|triangle| triangle.centroid));
+    let extent = centroid_bounds.max - centroid_bounds.min;
+    let bin_of = |triangle: &BuildTriangle, axis: usize| {
+        let offset = triangle.centroid[axis] - centroid_bounds.min[axis];
+        ((offset / extent[axis] * BIN_COUNT as f32) as usize).min(BIN_COUNT - 1)
+    };
+
+    // Cost, axis and the last bin on the left side of the best split so far
+    let mut best: Option<(f32, usize, usize)> = None;
+    for axis in 0..3 {
+        // Centroids can't be separated along a flat axis
+        if extent[axis] <= f32::EPSILON {
+            continue;
+        }
+        let mut bins: [(usize, Option<Aabb>); BIN_COUNT] = [(0, None); BIN_COUNT];
+        for triangle in triangles.iter() {
+            let bin = &mut bins[bin_of(triangle, axis)];
+            bin.0 += 1;
+            bin.1 = Some(
+                bin.1
+                    .map_or(triangle.bounds, |bounds| union(&bounds, &triangle.bounds)),
+            );
+        }
+        let merge = |(count, bounds): (usize, Option<Aabb>), bin: &(usize, Option<Aabb>)| {
+            let merged = match (bounds, bin.1) {
+                (Some(bounds), Some(bin_bounds)) => Some(union(&bounds, &bin_bounds)),
+                (bounds, bin_bounds) => bounds.or(bin_bounds),
+            };
+            (count + bin.0, merged)
+        };
+        let cost = |(count, bounds): (usize, Option<Aabb>)| {
+            count as f32 * bounds.map_or(0.0, |bounds| surface_area(&bounds))
+        };
+
+        // Cost of the right side of the plane after each bin
+        let mut right_costs = [0.0; BIN_COUNT];
+        let mut right = (0, None);
+        for bin in (1..BIN_COUNT).rev() {
+            right = merge(right, &bins[bin]);
+            right_costs[bin - 1] = cost(right);
+        }
+        let mut left = (0, None);
+        for bin in 0..BIN_COUNT - 1 {
+            left = merge(left, &bins[bin]);
+            if left.0 == 0 || left.0 == triangles.len() {
+                continue;
+            }
+            let split_cost = cost(left) + right_costs[bin];
+            if best.is_none_or(|(best_cost, _, _)| split_cost < best_cost) {
+                best = Some((split_cost, axis, bin));
+            }
+        }
+    }
+
+    let (split_cost, axis, last_left_bin) = best?;
+    // Cost of testing every triangle of the node, relative to visiting the children and testing
+    // the triangles of the child hit with the probability of its area
+    if triangles.len() as f32 <= TRAVERSAL_COST + split_cost / surface_area(bounds) {
+        return None;
+    }
+    let mut left_count = 0;
+    for i in 0..triangles.len() {
+        if bin_of(&triangles[i], axis) <= last_left_bin {
+            triangles.swap(i, left_count);
+            left_count += 1;
+        }
+    }
+    Some(left_count)
+}
+
+fn union(a: &Aabb, b: &Aabb) -> Aabb {
+    Aabb::from_points([a.min, a.max, b.min, b.max])
+}
+
+fn union_bounds(triangles: &[BuildTriangle]) -> Aabb {
+    Aabb::from_points(
+        triangles
+            .iter()
+            .flat_map(|triangle| [triangle.bounds.min, triangle.bounds.max]),
+    )
+}
+
+fn inverse(direction: Vector3<f32>) -> Vector3<f32> {
+    Vector3::new(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z)
+}
+
+fn surface_area(bounds: &Aabb) -> f32 {
+    let size = bounds.max - bounds.min;
+    2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+}
+
+/// Distance along the ray where it enters the box, 0 when starting inside. Slab test, infinite
+/// inverse direction components of axis-parallel rays are handled by IEEE arithmetic.
+fn intersect_bounds(bounds: &Aabb, ray: &Ray, inverse_direction: &Vector3<f32>) -> Option<f32> {
+    let mut entry: f32 = 0.0;
+    let mut exit = f32::INFINITY;
+    for axis in 0..3 {
+        let near = (bounds.min[axis] - ray.origin[axis]) * inverse_direction[axis];
+        let far = (bounds.max[axis] - ray.origin[axis]) * inverse_direction[axis];
+        entry = entry.max(near.min(far));
+        exit = exit.min(near.max(far));
+    }
+    (entry <= exit).then_some(entry)
+}
+
+/// Möller–Trumbore ray-triangle intersection, hitting both sides of the triangle. Triangle index
+/// of the hit is left for the caller.
+fn intersect_triangle(ray: &Ray, [a, b, c]: &[Point3<f32>; 3]) -> Option<RayHit> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = ray.direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    // Ray is parallel to the triangle plane
+    if determinant == 0.0 {
+        return None;
+    }
+    let inverse_determinant = 1.0 / determinant;
+    let s = ray.origin - a;
+    let u = s.dot(p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(edge1);
+    let v = ray.direction.dot(q) * inverse_determinant;
+    if v < 0.0 || 1.0 < u + v {
+        return None;
+    }
+    let distance = edge2.dot(q) * inverse_determinant;
+    (0.0 <= distance).then_some(RayHit {
+        distance,
+        triangle: 0,
+        barycentric: (u, v),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::vec3;
+
+    use super::*;
+    use crate::{primitives, MeshData};
+
+    /// Closest hit by testing every triangle, for comparison with the traversal.
+    fn brute_force(mesh_data: &MeshData, ray: &Ray, max_distance: f32) -> Option<RayHit> {
+        let position = |index: u32| Point3::from_vec(mesh_data.vertices[index as usize].position);
+        mesh_data
+            .indices
+            .chunks_exact(3)
+            .enumerate()
+            .filter_map(|(triangle_index, triangle)| {
+                let corners = [0, 1, 2].map(|i| position(triangle[i]));
+                let hit = intersect_triangle(ray, &corners)?;
+                Some(RayHit {
+                    triangle: triangle_index,
+                    ..hit
+                })
+            })
+            .filter(|hit| hit.distance < max_distance)
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    fn test_rays() -> impl Iterator<Item = Ray> {
+        (0..64).map(|i| {
+            let angle = i as f32 * 0.7;
+            let origin = Point3::new(angle.cos() * 3.0, (i as f32 * 0.37).sin() * 2.0, 3.0);
+            Ray {
+                origin,
+                direction: Point3::new((i % 5) as f32 * 0.2 - 0.4, 0.1, 0.0) - origin,
+            }
+        })
+    }
+
+    #[test]
+    fn build_splits_mesh_into_leaves() {
+        let mesh_data = primitives::sphere(1.0, 32, 16);
+        let bvh = Bvh::build(&mesh_data.vertices, &mesh_data.indices);
+        let stats = bvh.stats();
+        assert_eq!(stats.triangle_count, mesh_data.indices.len() / 3);
+        assert!(stats.leaf_count > 1);
+        assert_eq!(stats.node_count, 2 * stats.leaf_count - 1);
+        assert!(stats.max_depth <= MAX_DEPTH);
+    }
+
+    #[test]
+    fn intersect_matches_brute_force() {
+        let mesh_data = primitives::sphere(1.0, 32, 16);
+        let bvh = Bvh::build(&mesh_data.vertices, &mesh_data.indices);
+        for ray in test_rays() {
+            let expected = brute_force(&mesh_data, &ray, f32::INFINITY);
+            let hit = bvh.intersect(&ray, f32::INFINITY);
+            assert_eq!(hit.is_some(), expected.is_some(), "{ray:?}");
+            if let (Some(hit), Some(expected)) = (hit, expected) {
+                assert_eq!(hit.distance, expected.distance);
+                assert_eq!(hit.triangle, expected.triangle);
+            }
+        }
+    }
+
+    #[test]
+    fn intersect_reports_nearest_surface() {
+        let mesh_data = primitives::cuboid(2.0, 2.0, 2.0);
+        let bvh = Bvh::build(&mesh_data.vertices, &mesh_data.indices);
+        let ray = Ray {
+            origin: Point3::new(0.2, 0.3, 5.0),
+            direction: vec3(0.0, 0.0, -2.0),
+        };
+        let hit = bvh.intersect(&ray, f32::INFINITY).unwrap();
+        // Distance is measured in multiples of the direction length
+        assert!((hit.distance - 2.0).abs() < 1e-6);
+        assert!((ray.at(hit.distance).z - 1.0).abs() < 1e-6);
+        assert!(bvh.intersect(&ray, 1.5).is_none());
+    }
+
+    #[test]
+    fn occluded_respects_max_distance() {
+        let mesh_data = primitives::cuboid(2.0, 2.0, 2.0);
+        let bvh = Bvh::build(&mesh_data.vertices, &mesh_data.indices);
+        let ray = Ray {
+            origin: Point3::new(0.0, 0.0, 5.0),
+            direction: vec3(0.0, 0.0, -1.0),
+        };
+        assert!(bvh.occluded(&ray, 10.0));
+        assert!(!bvh.occluded(&ray, 3.0));
+        let missing = Ray {
+            origin: Point3::new(3.0, 0.0, 5.0),
+            direction: vec3(0.0, 0.0, -1.0),
+        };
+        assert!(!bvh.occluded(&missing, f32::INFINITY));
+        assert!(bvh.intersect(&missing, f32::INFINITY).is_none());
+    }
+
+    #[test]
+    fn empty_mesh_is_never_hit() {
+        let bvh = Bvh::build(&[], &[]);
+        let ray = Ray {
+            origin: Point3::new(0.0, 0.0, 0.0),
+            direction: vec3(1.0, 0.0, 0.0),
+        };
+        assert!(bvh.intersect(&ray, f32::INFINITY).is_none());
+        assert!(!bvh.occluded(&ray, f32::INFINITY));
+    }
+}
@@ -1,8 +1,26 @@
-use cgmath::{InnerSpace, Matrix4, Point3, Vector2, Vector3};
+use cgmath::{
+    Deg, EuclideanSpace, InnerSpace, Matrix4, Point3, Quaternion, Rad, Rotation, Rotation3,
+    Vector2, Vector3, Zero,
+};
+
+use crate::{
+    tween::{Easing, Tween},
+    CameraMode,
+};
 
 // TODO: Make them configurable
 const MOVEMENT_SPEED: f32 = 2.5;
 const LOOK_SENSITIVITY: f32 = 0.1;
+/// Degrees per second of rolling around the view direction.
+const ROLL_SPEED: f32 = 90.0;
+/// Height of the eyes above the ground when walking, with 1 unit as 1 meter.
+const EYE_HEIGHT: f32 = 1.7;
+/// Downwards acceleration when walking in units per second squared.
+const GRAVITY: f32 = 9.81;
+/// Initial upwards speed of jumps when walking in units per second.
+const JUMP_SPEED: f32 = 4.0;
+/// Mouse movement left to apply by smoothing is dropped below this distance.
+const LOOK_SMOOTHING_THRESHOLD: f32 = 0.01;
 
 /// Normalized mapping of positive Y axis in world coordinate space, always
 /// pointing upwards in the viewport (x:0, y:1, z:0). Required to determine
@@ -13,68 +31,275 @@ const UP_VECTOR: Vector3<f32> = Vector3 {
     y: 1.0,
     z: 0.0,
 };
+/// Direction the camera looks at in its own coordinate space, same as in OpenGL view space.
+const FORWARD_VECTOR: Vector3<f32> = Vector3 {
+    x: 0.0,
+    y: 0.0,
+    z: -1.0,
+};
 
 /// Decoupling of camera view position and rotation manipulation.
 ///
 /// Application-side logic accepts user input and updates viewing properties
 /// through movement and look operations while renderer accesses the resulting
 /// view matrix to use for applying Model-View-Projection transformation.
+///
+/// By default the camera flies like in FPS games: looking around turns it around the world up
+/// axis, pitch is limited and movement up and down is vertical. With six degrees of freedom
+/// (spaceship camera) every rotation and movement is relative to the camera itself. Walking camera
+/// rotates like the flying one, but stays on the ground.
+#[derive(Clone)]
 pub struct Camera {
     /// Camera location in world coordinate space. Also known as "eye
     /// position".
     position: Point3<f32>,
-    /// Rotation elements are stored as Euler angles. Looking along X axis
+    /// Rotation from camera space to world space.
+    orientation: Quaternion<f32>,
+    /// Rotation elements are stored as Euler angles in degrees. Looking along X axis
     /// (left/right, snapped around Y axis) is known as "yaw". Looking along Y
-    /// axis (up/down, snapped around X axis) us known as "pitch".
+    /// axis (up/down, snapped around X axis) us known as "pitch". Rolling around the view
+    /// direction (like an aeroplane or spaceship) is known as "roll", positive to the right.
     ///
-    /// Rolling around Z axis (like an aeroplane or spaceship) is omitted.
-    rotation: Vector2<f32>,
+    /// Orientation is built from them when flying or walking. With six degrees of freedom they are
+    /// derived from the orientation for display only.
+    rotation: Vector3<f32>,
+    mode: CameraMode,
+    // Speed of falling or jumping when walking, positive upwards
+    vertical_velocity: f32,
+    // Walking camera is on the ground, able to jump
+    grounded: bool,
     // Direction vector storing the rotations computed from mouse movements.
     // Determines where the camera should point at.
     direction: Vector3<f32>,
+    // Fraction of mouse movement carried over to the next logic update, 0 disables smoothing
+    look_smoothing: f32,
+    // Extra rotation per mouse movement distance in a logic update, 0 keeps rotation linear
+    look_acceleration: f32,
+    // Mouse movement not applied yet when smoothing or acceleration is enabled
+    pending_look: Vector2<f32>,
+    // Placement before the last logic update, frames drawn between updates blend it with the
+    // current one
+    previous_position: Point3<f32>,
+    previous_orientation: Quaternion<f32>,
 }
 
 impl Camera {
+    /// Camera without roll, rotation is given as yaw and pitch in degrees.
     pub fn new(position: Point3<f32>, rotation: Vector2<f32>) -> Self {
         let mut camera = Self {
             position,
-            rotation,
+            orientation: Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            rotation: Vector3::new(rotation.x, rotation.y, 0.0),
+            mode: CameraMode::Fly,
+            vertical_velocity: 0.0,
+            grounded: false,
             direction: Vector3::new(0.0, 0.0, 0.0),
+            look_smoothing: 0.0,
+            look_acceleration: 0.0,
+            pending_look: Vector2::zero(),
+            previous_position: position,
+            previous_orientation: Quaternion::new(1.0, 0.0, 0.0, 0.0),
         };
         // Avoid camera jump on first mouselook.
-        camera.update_direction();
+        camera.update_orientation();
+        camera.store_previous_placement();
         camera
     }
 
     pub fn move_forward(&mut self, delta_time: f32) {
-        self.position += MOVEMENT_SPEED * self.direction * delta_time;
+        self.position += MOVEMENT_SPEED * self.forward() * delta_time;
     }
 
     pub fn move_backward(&mut self, delta_time: f32) {
-        self.position -= MOVEMENT_SPEED * self.direction * delta_time;
+        self.position -= MOVEMENT_SPEED * self.forward() * delta_time;
     }
 
     pub fn strafe_left(&mut self, delta_time: f32) {
-        // If you don't normalize, you move fast or slow depending on camera
-        // direction.
-        self.position -= self.direction.cross(UP_VECTOR).normalize() * MOVEMENT_SPEED * delta_time;
+        self.position -= self.right() * MOVEMENT_SPEED * delta_time;
     }
 
     pub fn strafe_right(&mut self, delta_time: f32) {
-        self.position += self.direction.cross(UP_VECTOR).normalize() * MOVEMENT_SPEED * delta_time;
+        self.position += self.right() * MOVEMENT_SPEED * delta_time;
     }
 
+    /// Walking camera jumps instead, when it is on the ground.
     pub fn ascend(&mut self, delta_time: f32) {
-        self.position += MOVEMENT_SPEED * UP_VECTOR * delta_time;
+        if self.mode == CameraMode::Walk {
+            if self.grounded {
+                self.vertical_velocity = JUMP_SPEED;
+                self.grounded = false;
+            }
+            return;
+        }
+        self.position += MOVEMENT_SPEED * self.up() * delta_time;
     }
 
+    /// Walking camera can't descend below the ground.
     pub fn descend(&mut self, delta_time: f32) {
-        self.position -= MOVEMENT_SPEED * UP_VECTOR * delta_time;
+        if self.mode == CameraMode::Walk {
+            return;
+        }
+        self.position -= MOVEMENT_SPEED * self.up() * delta_time;
+    }
+
+    /// Apply gravity to the walking camera and keep its eyes above the ground at the given height.
+    /// Nothing happens in other modes.
+    pub fn update_walk(&mut self, delta_time: f32, ground_height: f32) {
+        if self.mode != CameraMode::Walk {
+            return;
+        }
+        self.vertical_velocity -= GRAVITY * delta_time;
+        self.position.y += self.vertical_velocity * delta_time;
+
+        let eye_level = ground_height + EYE_HEIGHT;
+        self.grounded = self.position.y <= eye_level;
+        if self.grounded {
+            self.position.y = eye_level;
+            self.vertical_velocity = 0.0;
+        }
+    }
+
+    pub fn roll_left(&mut self, delta_time: f32) {
+        self.roll(-ROLL_SPEED * delta_time);
+    }
+
+    pub fn roll_right(&mut self, delta_time: f32) {
+        self.roll(ROLL_SPEED * delta_time);
     }
 
     /// Apply mouse input changes to change camera direction. Offsets are mouse
     /// cursor distances from the center of the view.
+    ///
+    /// Applied immediately, unless smoothing or acceleration is enabled. Movement is collected
+    /// until the next `update_look()` call then, so that acceleration doesn't depend on the
+    /// polling rate of the mouse.
     pub fn look(&mut self, x_offset: f32, y_offset: f32) {
+        if self.look_smoothing == 0.0 && self.look_acceleration == 0.0 {
+            self.rotate(x_offset, y_offset);
+        } else {
+            self.pending_look += Vector2::new(x_offset, y_offset);
+        }
+    }
+
+    /// Apply mouse movement collected by `look()` on each fixed timestep logic update. Smoothing
+    /// spreads movement over the following updates, acceleration turns the camera further on fast
+    /// movements.
+    pub fn update_look(&mut self) {
+        let offset = self.pending_look * (1.0 - self.look_smoothing);
+        self.pending_look -= offset;
+        if self.pending_look.magnitude() < LOOK_SMOOTHING_THRESHOLD {
+            self.pending_look = Vector2::zero();
+        }
+        if offset.is_zero() {
+            return;
+        }
+
+        let gain = 1.0 + self.look_acceleration * offset.magnitude() * LOOK_SENSITIVITY;
+        self.rotate(offset.x * gain, offset.y * gain);
+    }
+
+    /// Fraction of mouse movement carried over to the next logic update, from 0 (off) to 0.95.
+    pub fn set_look_smoothing(&mut self, smoothing: f32) {
+        self.look_smoothing = smoothing.clamp(0.0, 0.95);
+    }
+
+    /// Strength of mouse acceleration, 0 turns the camera proportionally to mouse movement.
+    pub fn set_look_acceleration(&mut self, acceleration: f32) {
+        self.look_acceleration = acceleration.max(0.0);
+    }
+
+    /// Current view is kept, except pitch is limited again when leaving six degrees of freedom.
+    /// Walking camera starts falling to the ground from its current position.
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if self.mode == mode {
+            return;
+        }
+        let previous_mode = self.mode;
+        self.mode = mode;
+        self.vertical_velocity = 0.0;
+        self.grounded = false;
+        if previous_mode == CameraMode::SixDegreesOfFreedom {
+            self.rotation.x = wrap_yaw(self.rotation.x);
+            self.rotation.y = self.rotation.y.clamp(-89.0, 89.0);
+            self.update_orientation();
+        }
+    }
+
+    /// Remember the current placement before a logic update moves or turns the camera.
+    pub fn store_previous_placement(&mut self) {
+        self.previous_position = self.position;
+        self.previous_orientation = self.orientation;
+    }
+
+    /// Copy of the camera placed between its placement before the last logic update (`alpha` 0)
+    /// and the current one (`alpha` 1).
+    pub fn interpolated(&self, alpha: f32) -> Camera {
+        let mut camera = self.clone();
+        camera.position = self.previous_position + (self.position - self.previous_position) * alpha;
+        camera.orientation = self.previous_orientation.slerp(self.orientation, alpha);
+        camera.update_direction();
+        camera
+    }
+
+    pub fn mode(&self) -> CameraMode {
+        self.mode
+    }
+
+    /// Move and turn the camera to yaw, pitch and roll in degrees at once, e.g. by animated
+    /// transitions. Pitch is limited unless the camera has six degrees of freedom.
+    pub fn set_placement(&mut self, position: Point3<f32>, rotation: Vector3<f32>) {
+        self.position = position;
+        let pitch = if self.mode == CameraMode::SixDegreesOfFreedom {
+            rotation.y
+        } else {
+            rotation.y.clamp(-89.0, 89.0)
+        };
+        self.rotation = Vector3::new(rotation.x.rem_euclid(360.0), pitch, wrap_roll(rotation.z));
+        self.vertical_velocity = 0.0;
+        self.update_orientation();
+    }
+
+    pub fn calculate_view_matrix(&self) -> Matrix4<f32> {
+        // Inverse of camera placement in world space: move eye into origin first, then rotate
+        // view direction onto negative Z axis. OpenGL uses right-handed coordinate system.
+        Matrix4::from(self.orientation.invert())
+            * Matrix4::from_translation(-self.position.to_vec())
+    }
+
+    pub fn position(&self) -> &Point3<f32> {
+        &self.position
+    }
+
+    /// Yaw, pitch and roll in degrees.
+    pub fn rotation(&self) -> &Vector3<f32> {
+        &self.rotation
+    }
+
+    /// Normalized direction the camera is looking at.
+    pub fn direction(&self) -> &Vector3<f32> {
+        &self.direction
+    }
+
+    /// Transformation from the play space of a VR headset to world space, placing the play space
+    /// at the camera position and turning it with the camera yaw. Pitch is left out, tilting the
+    /// horizon in a headset causes motion sickness.
+    pub fn play_space_transform(&self) -> Matrix4<f32> {
+        // Yaw of 270 degrees looks along negative Z, the forward direction of VR runtimes
+        let yaw = Deg(-(self.rotation.x + 90.0));
+        Matrix4::from_translation(self.position.to_vec()) * Matrix4::from_angle_y(yaw)
+    }
+
+    fn rotate(&mut self, x_offset: f32, y_offset: f32) {
+        if self.mode == CameraMode::SixDegreesOfFreedom {
+            // Turn around the axes of the camera, y_offset signedness is different on winit than
+            // on GLFW
+            self.orientation = self.orientation
+                * Quaternion::from_angle_y(Deg(-x_offset * LOOK_SENSITIVITY))
+                * Quaternion::from_angle_x(Deg(-y_offset * LOOK_SENSITIVITY));
+            self.update_rotation();
+            return;
+        }
+
         self.rotation.x += x_offset * LOOK_SENSITIVITY;
         // Wrap to keep rotation degrees displayed between 0 and 360 on debug UI
         self.rotation.x = wrap_yaw(self.rotation.x);
@@ -83,31 +308,157 @@ impl Camera {
         self.rotation.y -= y_offset * LOOK_SENSITIVITY;
         // Avoid user to do a backflip
         self.rotation.y = self.rotation.y.clamp(-89.0, 89.0);
-        self.update_direction();
+        self.update_orientation();
     }
 
-    pub fn calculate_view_matrix(&self) -> Matrix4<f32> {
-        let eye = self.position;
-        let target = self.position + self.direction;
-        // OpenGL uses right-handed coordinate system.
-        Matrix4::look_at_rh(eye, target, UP_VECTOR)
+    fn roll(&mut self, degrees: f32) {
+        if self.mode == CameraMode::SixDegreesOfFreedom {
+            self.orientation = self.orientation * Quaternion::from_angle_z(Deg(-degrees));
+            self.update_rotation();
+        } else {
+            self.rotation.z = wrap_roll(self.rotation.z + degrees);
+            self.update_orientation();
+        }
     }
 
-    pub fn position(&self) -> &Point3<f32> {
-        &self.position
+    /// Movement forwards. Walking camera moves horizontally regardless of pitch.
+    fn forward(&self) -> Vector3<f32> {
+        if self.mode != CameraMode::Walk {
+            return self.direction;
+        }
+        let horizontal = Vector3::new(self.direction.x, 0.0, self.direction.z);
+        // Looking straight up or down
+        if horizontal.magnitude2() <= f32::EPSILON {
+            return Vector3::zero();
+        }
+        horizontal.normalize()
     }
 
-    pub fn rotation(&self) -> &Vector2<f32> {
-        &self.rotation
+    /// Movement to the right. FPS camera strafes horizontally regardless of pitch and roll.
+    fn right(&self) -> Vector3<f32> {
+        if self.mode == CameraMode::SixDegreesOfFreedom {
+            self.orientation.rotate_vector(Vector3::unit_x())
+        } else {
+            // If you don't normalize, you move fast or slow depending on camera
+            // direction.
+            self.direction.cross(UP_VECTOR).normalize()
+        }
+    }
+
+    /// Movement upwards. FPS camera ascends vertically.
+    fn up(&self) -> Vector3<f32> {
+        if self.mode == CameraMode::SixDegreesOfFreedom {
+            self.orientation.rotate_vector(UP_VECTOR)
+        } else {
+            UP_VECTOR
+        }
+    }
+
+    /// Build orientation from Euler angles of the flying or walking camera.
+    fn update_orientation(&mut self) {
+        // Yaw of 270 degrees looks along negative Z axis, the forward direction of camera space
+        self.orientation = Quaternion::from_angle_y(Deg(-(self.rotation.x + 90.0)))
+            * Quaternion::from_angle_x(Deg(self.rotation.y))
+            * Quaternion::from_angle_z(Deg(-self.rotation.z));
+        self.update_direction();
+    }
+
+    /// Derive Euler angles from orientation turned freely with six degrees of freedom.
+    fn update_rotation(&mut self) {
+        // Accumulated floating point errors would skew the rotation
+        self.orientation = self.orientation.normalize();
+        self.update_direction();
+
+        let direction = self.direction;
+        let yaw = Rad(direction.z.atan2(direction.x));
+        let pitch = Rad(direction.y.clamp(-1.0, 1.0).asin());
+        // Roll is the angle between the up vector of the camera and the one it would have without
+        // roll. Undefined when looking straight up or down.
+        let right_without_roll = direction.cross(UP_VECTOR);
+        let roll = if right_without_roll.magnitude2() > f32::EPSILON {
+            let right_without_roll = right_without_roll.normalize();
+            let up_without_roll = right_without_roll.cross(direction);
+            let up = self.orientation.rotate_vector(UP_VECTOR);
+            Rad(up.dot(right_without_roll).atan2(up.dot(up_without_roll)))
+        } else {
+            Rad(0.0)
+        };
+        self.rotation = Vector3::new(
+            Deg::from(yaw).0.rem_euclid(360.0),
+            Deg::from(pitch).0,
+            Deg::from(roll).0,
+        );
     }
 
     fn update_direction(&mut self) {
-        let rotation_x_radians = self.rotation.x.to_radians();
-        let rotation_y_radians = self.rotation.y.to_radians();
-        self.direction.x = rotation_x_radians.cos() * rotation_y_radians.cos();
-        self.direction.y = rotation_y_radians.sin();
-        self.direction.z = rotation_x_radians.sin() * rotation_y_radians.cos();
-        self.direction = self.direction.normalize();
+        self.direction = self.orientation.rotate_vector(FORWARD_VECTOR).normalize();
+    }
+}
+
+/// Camera placement and field of view saved by the user to return to later.
+#[derive(Clone)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub position: Point3<f32>,
+    /// Yaw, pitch and roll in degrees.
+    pub rotation: Vector3<f32>,
+    pub field_of_view: f32,
+}
+
+impl CameraBookmark {
+    pub fn new(name: &str, camera: &Camera, field_of_view: f32) -> Self {
+        Self {
+            name: name.to_owned(),
+            position: camera.position,
+            rotation: camera.rotation,
+            field_of_view,
+        }
+    }
+}
+
+/// Animated flight of the camera from its current placement and field of view to a bookmark.
+pub struct CameraTransition {
+    position: Tween<Point3<f32>>,
+    rotation: Tween<Vector3<f32>>,
+    field_of_view: Tween<f32>,
+}
+
+impl CameraTransition {
+    /// Transition over `duration` seconds. Yaw and roll turn the shorter way around.
+    pub fn new(
+        camera: &Camera,
+        field_of_view: f32,
+        target: &CameraBookmark,
+        duration: f32,
+        easing: Easing,
+    ) -> Self {
+        let from = camera.rotation;
+        let shortest_turn =
+            |from: f32, to: f32| from + (to - from + 180.0).rem_euclid(360.0) - 180.0;
+        let to = Vector3::new(
+            shortest_turn(from.x, target.rotation.x),
+            target.rotation.y,
+            shortest_turn(from.z, target.rotation.z),
+        );
+        Self {
+            position: Tween::new(camera.position, target.position, duration, easing),
+            rotation: Tween::new(from, to, duration, easing),
+            field_of_view: Tween::new(field_of_view, target.field_of_view, duration, easing),
+        }
+    }
+
+    /// Advance by `delta_time` seconds and place the camera accordingly. Returns the field of view
+    /// at the new time.
+    pub fn update(&mut self, camera: &mut Camera, delta_time: f32) -> f32 {
+        camera.set_placement(
+            self.position.advance(delta_time),
+            self.rotation.advance(delta_time),
+        );
+        self.field_of_view.advance(delta_time)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position.is_finished()
     }
 }
 
@@ -122,3 +473,170 @@ fn wrap_yaw(yaw: f32) -> f32 {
         yaw
     }
 }
+
+/// Keep roll between -180 and 180 degrees, rolling over upside down continues on the other side.
+fn wrap_roll(roll: f32) -> f32 {
+    (roll + 180.0).rem_euclid(360.0) - 180.0
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{
+        abs_diff_eq, assert_abs_diff_eq, EuclideanSpace, Matrix, SquareMatrix, Transform, Vector4,
+    };
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn test_camera(yaw: f32, pitch: f32) -> Camera {
+        Camera::new(Point3::new(1.0, 2.0, 3.0), Vector2::new(yaw, pitch))
+    }
+
+    #[test]
+    fn wrap_yaw_keeps_values_in_range() {
+        assert_eq!(wrap_yaw(0.0), 0.0);
+        assert_eq!(wrap_yaw(180.0), 180.0);
+        assert_eq!(wrap_yaw(359.0), 359.0);
+    }
+
+    #[test]
+    fn wrap_yaw_wraps_around_full_turn() {
+        assert_eq!(wrap_yaw(359.5), 0.0);
+        assert_eq!(wrap_yaw(-0.5), 359.0);
+    }
+
+    #[test]
+    fn look_wraps_yaw_past_full_turn() {
+        let mut camera = test_camera(358.0, 0.0);
+        camera.look(20.0, 0.0);
+        assert_eq!(camera.rotation().x, 0.0);
+
+        let mut camera = test_camera(1.0, 0.0);
+        camera.look(-20.0, 0.0);
+        assert_eq!(camera.rotation().x, 359.0);
+    }
+
+    #[test]
+    fn look_clamps_pitch() {
+        let mut camera = test_camera(0.0, 0.0);
+        camera.look(0.0, -10000.0);
+        assert_eq!(camera.rotation().y, 89.0);
+        camera.look(0.0, 10000.0);
+        assert_eq!(camera.rotation().y, -89.0);
+    }
+
+    #[test]
+    fn direction_follows_yaw_and_pitch() {
+        // Yaw 270° looks along negative Z axis, which is forward in OpenGL
+        assert_abs_diff_eq!(
+            test_camera(270.0, 0.0).direction,
+            Vector3::new(0.0, 0.0, -1.0),
+            epsilon = EPSILON
+        );
+        assert_abs_diff_eq!(
+            test_camera(0.0, 0.0).direction,
+            Vector3::new(1.0, 0.0, 0.0),
+            epsilon = EPSILON
+        );
+        assert_abs_diff_eq!(
+            test_camera(0.0, 90.0).direction,
+            Vector3::new(0.0, 1.0, 0.0),
+            epsilon = EPSILON
+        );
+    }
+
+    #[test]
+    fn view_matrix_moves_eye_to_origin() {
+        let camera = test_camera(270.0, 0.0);
+        let view = camera.calculate_view_matrix();
+        assert_abs_diff_eq!(
+            view.transform_point(*camera.position()),
+            Point3::new(0.0, 0.0, 0.0),
+            epsilon = EPSILON
+        );
+        // Without rotation, view matrix is only a translation
+        assert_abs_diff_eq!(
+            view,
+            Matrix4::from_translation(Vector3::new(-1.0, -2.0, -3.0)),
+            epsilon = EPSILON
+        );
+    }
+
+    #[test]
+    fn movement_is_relative_to_direction() {
+        let mut camera = test_camera(270.0, 0.0);
+        camera.move_forward(1.0);
+        assert_abs_diff_eq!(
+            *camera.position(),
+            Point3::new(1.0, 2.0, 3.0 - MOVEMENT_SPEED),
+            epsilon = EPSILON
+        );
+        camera.strafe_right(1.0);
+        assert_abs_diff_eq!(
+            *camera.position(),
+            Point3::new(1.0 + MOVEMENT_SPEED, 2.0, 3.0 - MOVEMENT_SPEED),
+            epsilon = EPSILON
+        );
+        camera.ascend(1.0);
+        camera.descend(1.0);
+        camera.strafe_left(1.0);
+        camera.move_backward(1.0);
+        assert_abs_diff_eq!(
+            *camera.position(),
+            Point3::new(1.0, 2.0, 3.0),
+            epsilon = EPSILON
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn look_keeps_rotation_in_range(
+            yaw in 0.0f32..=359.0,
+            pitch in -89.0f32..=89.0,
+            offsets in prop::collection::vec((-1000.0f32..1000.0, -1000.0f32..1000.0), 1..20),
+        ) {
+            let mut camera = test_camera(yaw, pitch);
+            for (x_offset, y_offset) in offsets {
+                camera.look(x_offset, y_offset);
+                let rotation = camera.rotation();
+                prop_assert!((0.0..=359.0).contains(&rotation.x));
+                prop_assert!((-89.0..=89.0).contains(&rotation.y));
+                prop_assert!((camera.direction.magnitude() - 1.0).abs() < EPSILON);
+            }
+        }
+
+        #[test]
+        fn view_matrix_is_rigid_transformation(
+            x in -100.0f32..100.0,
+            y in -100.0f32..100.0,
+            z in -100.0f32..100.0,
+            yaw in 0.0f32..=359.0,
+            pitch in -89.0f32..=89.0,
+        ) {
+            let camera = Camera::new(Point3::new(x, y, z), Vector2::new(yaw, pitch));
+            let view = camera.calculate_view_matrix();
+
+            // Rotation part preserves lengths and angles
+            let rotation = Matrix4::from_cols(
+                view.x,
+                view.y,
+                view.z,
+                Vector4::new(0.0, 0.0, 0.0, 1.0),
+            );
+            prop_assert!((rotation.determinant() - 1.0).abs() < 1e-4);
+            prop_assert!(abs_diff_eq!(
+                rotation * rotation.transpose(),
+                Matrix4::identity(),
+                epsilon = 1e-4
+            ));
+
+            // Eye is at the origin of view space, looking along negative Z axis
+            let eye = view.transform_point(*camera.position());
+            prop_assert!(eye.to_vec().magnitude() < 1e-2);
+            let forward = view.transform_vector(camera.direction);
+            prop_assert!((forward - Vector3::new(0.0, 0.0, -1.0)).magnitude() < 1e-4);
+        }
+    }
+}
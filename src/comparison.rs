@@ -0,0 +1,79 @@
+use hecs::{Entity, World};
+
+use crate::{DrawProperties, Material};
+
+/// Second set of draw properties and entity materials drawn right of a split line over the
+/// window, e.g. for comparing shading modes, level of detail or the scene before and after
+/// editing. The scene seen from the same camera is drawn twice, the current settings are side A
+/// left of the line.
+///
+/// Settings unrelated to the look of the scene, e.g. field of view, camera controls, time and
+/// language, are shared with side A.
+pub struct Comparison {
+    /// Settings of side B.
+    pub draw_props: DrawProperties,
+    /// Horizontal position of the split line relative to the window width.
+    pub split: f32,
+    // Materials of side B, entities spawned since capture look the same on both sides
+    materials: Vec<(Entity, Material)>,
+}
+
+impl Comparison {
+    /// Start comparing with a copy of the current settings as side B, which stay unchanged while
+    /// side A is edited.
+    pub fn capture(draw_props: &DrawProperties, world: &World) -> Self {
+        Self {
+            draw_props: draw_props.clone(),
+            split: 0.5,
+            materials: world
+                .query::<&Material>()
+                .iter()
+                .map(|(entity, material)| (entity, material.clone()))
+                .collect(),
+        }
+    }
+
+    /// Exchange the settings of the sides, making side B editable.
+    pub fn swap(&mut self, draw_props: &mut DrawProperties, world: &mut World) {
+        std::mem::swap(draw_props, &mut self.draw_props);
+        self.exchange_materials(world);
+        copy_shared_settings(draw_props, &self.draw_props);
+    }
+
+    /// Exchange the materials of the entities with the ones of the other side, e.g. for drawing
+    /// side B and back.
+    pub fn exchange_materials(&mut self, world: &mut World) {
+        for (entity, material) in &mut self.materials {
+            if let Ok(mut entity_material) = world.get::<&mut Material>(*entity) {
+                std::mem::swap(&mut *entity_material, material);
+            }
+        }
+    }
+
+    /// Take over settings of side A shared by both sides.
+    pub fn share_settings(&mut self, draw_props: &DrawProperties) {
+        copy_shared_settings(&mut self.draw_props, draw_props);
+    }
+}
+
+fn copy_shared_settings(to: &mut DrawProperties, from: &DrawProperties) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        to.overlay_gui_enabled = from.overlay_gui_enabled;
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        to.vsync_mode = from.vsync_mode;
+        to.raw_mouse_input = from.raw_mouse_input;
+    }
+    to.field_of_view = from.field_of_view;
+    to.camera_mode = from.camera_mode;
+    to.camera_transition_duration = from.camera_transition_duration;
+    to.camera_transition_easing = from.camera_transition_easing;
+    to.mouse_smoothing = from.mouse_smoothing;
+    to.mouse_acceleration = from.mouse_acceleration;
+    to.paused = from.paused;
+    to.time_scale = from.time_scale;
+    to.step_requested = from.step_requested;
+    to.language = from.language;
+}
@@ -0,0 +1,67 @@
+use cfg_if::cfg_if;
+
+cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
+    use std::{
+        backtrace::Backtrace,
+        path::PathBuf,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+}}
+
+/// Report panics to the user instead of silently terminating.
+///
+/// Native: Panic message and backtrace are written into a crash log file in the temporary
+/// directory, because release builds on Windows have no console to print into.
+///
+/// Web: Panic message and JavaScript stack trace are logged into the browser console and displayed
+/// in an error banner above the canvas, which otherwise stops updating without explanation.
+pub fn install_panic_hook() {
+    cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let report = format!("{info}\n\nBacktrace:\n{}", Backtrace::force_capture());
+            match write_crash_log(&report) {
+                Ok(path) => eprintln!("crash report written to {}", path.display()),
+                Err(e) => eprintln!("unable to write crash report: {e}"),
+            }
+            default_hook(info);
+        }));
+    } else {
+        std::panic::set_hook(Box::new(|info| {
+            let message = info.to_string();
+            // Stack trace of JavaScript error includes the WebAssembly functions of the panic
+            let stack = js_sys::Reflect::get(&js_sys::Error::new(&message), &"stack".into())
+                .ok()
+                .and_then(|stack| stack.as_string())
+                .unwrap_or_default();
+            web_sys::console::error_1(&format!("{message}\n\nStack:\n{stack}").into());
+            show_crash_banner(&message);
+        }));
+    }}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_crash_log(report: &str) -> std::io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let path = std::env::temp_dir().join(format!("renderer-rust-crash-{timestamp}.log"));
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+/// Banner element is part of the page and hidden while empty.
+#[cfg(target_arch = "wasm32")]
+fn show_crash_banner(message: &str) {
+    let Some(banner) = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id("crash-banner"))
+    else {
+        return;
+    };
+    banner.set_text_content(Some(&format!(
+        "The renderer has crashed: {message}\n\
+         See the browser console for details and reload the page to restart."
+    )));
+}
@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use cgmath::{Deg, EuclideanSpace, Matrix4, Point3, SquareMatrix, Transform as _, Vector3};
+use glow::{HasContext, Texture};
+use image::{DynamicImage, EncodableLayout};
+
+use crate::{
+    gl_resources, gl_state, line::LineVertex, renderer::calculate_model_matrix, Transform,
+};
+
+/// Most decals taken into account by shaders.
+pub const MAX_DECALS: usize = 4;
+/// Width and height of the depth map of each projector in texels. Depth maps are quadrants of a
+/// 2×2 atlas.
+pub(crate) const DECAL_DEPTH_RESOLUTION: u32 = 1024;
+const VOLUME_COLOR: [f32; 3] = [0.9, 0.5, 1.0];
+
+/// Image projected onto model surfaces from a projector placed in the scene, e.g. a logo or an
+/// annotation placed without editing texture coordinates of the model. Projection is perspective
+/// along the -Y axis of the projector like a slide projector, covering surfaces facing it between
+/// the near and far planes with the image blended by its alpha over the material color. The top of
+/// the image points towards -Z of the projector.
+///
+/// Surfaces hidden from the projector by other geometry are not covered. They are found by
+/// comparing with the depth seen by the projector, drawn every frame like a shadow map.
+pub struct Decal {
+    /// File name of the image, displayed in the GUI.
+    pub name: String,
+    /// Position of the projector.
+    pub position: Vector3<f32>,
+    /// Euler angles in degrees around X, Y and Z axes. Without rotation the image is projected
+    /// downwards.
+    pub rotation: [f32; 3],
+    /// Vertical field of view of the projector in degrees.
+    pub field_of_view: f32,
+    /// Width divided by height of the projected image, the aspect ratio of the image by default.
+    pub aspect_ratio: f32,
+    /// Distance of the near clip plane from the projector.
+    pub near: f32,
+    /// Distance of the far clip plane from the projector, surfaces beyond it are not covered.
+    pub far: f32,
+    pub opacity: f32,
+    gl: Arc<glow::Context>,
+    texture: Texture,
+}
+
+impl Decal {
+    /// Upload the image as sRGB color with alpha. The projector is placed 1 world unit above the
+    /// origin, where the image is 1 world unit tall.
+    pub fn from_image(
+        gl: Arc<glow::Context>,
+        name: &str,
+        image: &DynamicImage,
+    ) -> Result<Self, String> {
+        let pixels = image.to_rgba8();
+        let (width, height) = pixels.dimensions();
+        let texture = unsafe {
+            let texture = gl_resources::create_texture(&gl)
+                .map_err(|e| format!("cannot create decal texture: {e}"))?;
+            gl_state::bind_texture(&gl, glow::TEXTURE_2D, Some(texture));
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::SRGB8_ALPHA8 as i32,
+                width as i32,
+                height as i32,
+                0,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                Some(pixels.as_bytes()),
+            );
+            gl.generate_mipmap(glow::TEXTURE_2D);
+            // Mip levels add a third of the base level
+            gl_resources::set_texture_size(texture, pixels.as_bytes().len() * 4 / 3);
+            for (parameter, value) in [
+                (glow::TEXTURE_MIN_FILTER, glow::LINEAR_MIPMAP_LINEAR),
+                (glow::TEXTURE_MAG_FILTER, glow::LINEAR),
+                (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+                (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+            ] {
+                gl.tex_parameter_i32(glow::TEXTURE_2D, parameter, value as i32);
+            }
+            gl_state::bind_texture(&gl, glow::TEXTURE_2D, None);
+            texture
+        };
+
+        Ok(Self {
+            name: name.to_owned(),
+            position: Vector3::new(0.0, 1.0, 0.0),
+            rotation: [0.0, 0.0, 0.0],
+            // Half of the image height at unit distance is tan(field of view / 2)
+            field_of_view: 2.0 * 0.5f32.atan().to_degrees(),
+            aspect_ratio: width as f32 / height.max(1) as f32,
+            near: 0.1,
+            far: 2.0,
+            opacity: 1.0,
+            gl,
+            texture,
+        })
+    }
+
+    /// Load image file decoded by `image` crate, e.g. a PNG with transparency.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(gl: Arc<glow::Context>, path: &str) -> Result<Self, String> {
+        let image = image::open(path).map_err(|e| format!("unable to load decal {path}: {e}"))?;
+        let name = std::path::Path::new(path)
+            .file_name()
+            .map_or(path.into(), |name| name.to_string_lossy());
+        Self::from_image(gl, &name, &image)
+    }
+
+    pub fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    /// View from the projector looking along the projection direction, with the top of the image
+    /// upwards.
+    pub fn view_matrix(&self) -> Matrix4<f32> {
+        let model_matrix = calculate_model_matrix(&Transform {
+            position: self.position,
+            rotation: self.rotation,
+        });
+        Matrix4::look_to_rh(
+            Point3::from_vec(self.position),
+            self.direction(),
+            model_matrix.transform_vector(-Vector3::unit_z()),
+        )
+    }
+
+    /// Perspective projection of the projector, with parameters clamped to valid ranges.
+    pub fn projection_matrix(&self) -> Matrix4<f32> {
+        let near = self.near.max(0.001);
+        cgmath::perspective(
+            Deg(self.field_of_view.clamp(1.0, 179.0)),
+            self.aspect_ratio.max(0.01),
+            near,
+            self.far.max(2.0 * near),
+        )
+    }
+
+    /// Normalized direction of the projection in world space.
+    pub fn direction(&self) -> Vector3<f32> {
+        let transform = Transform {
+            position: self.position,
+            rotation: self.rotation,
+        };
+        calculate_model_matrix(&transform).transform_vector(-Vector3::unit_y())
+    }
+
+    /// Edges of the projector frustum with the projection direction from the projector to the
+    /// center of the far plane.
+    pub(crate) fn volume_lines(&self) -> Vec<LineVertex> {
+        let inverse_projection_view = (self.projection_matrix() * self.view_matrix())
+            .invert()
+            .unwrap_or(Matrix4::from_scale(0.0));
+        let unproject = |x, y, z| inverse_projection_view.transform_point(Point3::new(x, y, z));
+        let corner = |index: usize| {
+            // Corner indices are bit masks of the maximum X, Y and Z coordinates
+            let coordinate = |bit| if index & bit == 0 { -1.0 } else { 1.0 };
+            unproject(coordinate(1), coordinate(2), coordinate(4))
+        };
+        let edges = (0..8).flat_map(|start: usize| {
+            [1, 2, 4]
+                .into_iter()
+                .filter(move |bit| start & bit == 0)
+                .map(move |bit| (start, start | bit))
+        });
+        let mut points: Vec<_> = edges
+            .flat_map(|(start, end)| [corner(start), corner(end)])
+            .collect();
+        points.extend([Point3::from_vec(self.position), unproject(0.0, 0.0, 1.0)]);
+        points
+            .into_iter()
+            .map(|position| LineVertex {
+                position: position.into(),
+                color: VOLUME_COLOR,
+            })
+            .collect()
+    }
+}
+
+impl Drop for Decal {
+    fn drop(&mut self) {
+        unsafe {
+            gl_resources::delete_texture(&self.gl, self.texture);
+        }
+    }
+}
@@ -1,22 +1,134 @@
+use std::ops::RangeInclusive;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::LightmapSettings;
+use crate::{renderer::MIN_RENDER_SCALE, Easing, Language, PostEffectSettings, ShadowSettings};
+
 /// Parameter object for user to customize selected model, model transformations
 /// and rendering properties from UI.
 ///
 /// Recommended to use RefCell instead of Cell, because coyping this data is costly.
+#[derive(Clone)]
 pub struct DrawProperties {
     #[cfg(target_arch = "wasm32")]
     pub overlay_gui_enabled: bool,
     #[cfg(not(target_arch = "wasm32"))]
-    pub vsync_enabled: bool,
+    pub vsync_mode: VsyncMode,
     pub background_color: [f32; 3],
     pub model_rotation: [f32; 3],
-    pub model_color: [f32; 3],
     pub light_direction: [f32; 3],
+    /// Display inset of the scene seen from the light's perspective.
+    pub light_view_enabled: bool,
+    /// Display draggable sun marker showing where the light comes from.
+    pub light_gizmo_enabled: bool,
+    /// Draw entities with their lightmaps instead of realtime ambient and diffuse lighting, if
+    /// lighting was baked. Forward rendering only.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub baked_lighting_enabled: bool,
+    /// Quality of the next light baking.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub lightmap_settings: LightmapSettings,
+    /// Shadows of the directional light. Forward rendering only.
+    pub shadow_settings: ShadowSettings,
     pub field_of_view: f32,
+    pub camera_mode: CameraMode,
+    /// Seconds the camera flies to a bookmark.
+    pub camera_transition_duration: f32,
+    pub camera_transition_easing: Easing,
+    /// Mouse look from raw motion of the mouse device, otherwise from cursor movement within the
+    /// window. Web: always raw motion of the locked pointer.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub raw_mouse_input: bool,
+    /// Fraction of mouse look movement carried over to the next logic update, 0 disables
+    /// smoothing.
+    pub mouse_smoothing: f32,
+    /// Extra camera rotation on fast mouse movements, 0 disables acceleration.
+    pub mouse_acceleration: f32,
+    pub render_path: RenderPath,
+    pub anti_aliasing: AntiAliasing,
+    /// Detail of the current frame added back to the result of temporal anti-aliasing, from 0 to
+    /// 1.
+    pub taa_sharpness: f32,
+    /// Exposure compensation in stops (EV) applied in post-processing.
+    pub exposure: f32,
+    /// Display gamma used for output encoding. 2.2 matches standard sRGB displays.
+    pub gamma: f32,
+    /// Stylistic effects applied before exposure and gamma adjustments.
+    pub post_effect_settings: PostEffectSettings,
     pub selected_model_index: usize,
     pub skybox_enabled: bool,
+    /// Display names of axes, light and model in the 3D scene.
+    pub labels_enabled: bool,
+    pub reflective_plane_enabled: bool,
+    /// Height of the horizontal reflective ground plane in world space.
+    pub reflective_plane_height: f32,
+    /// Display terrain of the scene, if it has one.
+    pub terrain_enabled: bool,
+    /// Display vertical mirror behind the models, drawn with stencil buffer masking.
+    pub mirror_enabled: bool,
+    /// Reflectance of the ground plane when looking straight down at it.
+    pub reflectivity: f32,
+    /// Blurriness of ground plane reflection in pixels.
+    pub reflection_blur: f32,
     pub wireframe_mode_enabled: bool,
-    pub diffuse_enabled: bool,
-    pub specular_enabled: bool,
+    /// Fraction of the window resolution the scene is rendered at, upscaled when displayed.
+    pub render_scale: f32,
+    /// Adjust render scale and skip expensive passes to keep up with the target frame rate.
+    pub auto_quality_enabled: bool,
+    /// Frames per second kept by automatic quality scaling.
+    pub target_frame_rate: f32,
+    /// Skip reflections of the ground plane and the stencil mirror while keeping their settings.
+    /// Set by automatic quality scaling.
+    pub reduced_effects: bool,
+    /// Display how many fragments are shaded for each pixel instead of the lit scene.
+    pub overdraw_heatmap_enabled: bool,
+    /// Display axis-aligned bounding box of the selected model.
+    pub bounding_box_enabled: bool,
+    /// Display bounding sphere of the selected model.
+    pub bounding_sphere_enabled: bool,
+    /// Display frustums of projectors decals are projected from.
+    pub decal_volumes_enabled: bool,
+    /// Highlight problem edges and triangles of the selected model found by mesh validation.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub mesh_problems_enabled: bool,
+    /// Distance of parts of models assembled from several objects from their assembled place, as
+    /// a multiple of the vector from the model center to the part center. 0 keeps them assembled.
+    pub part_explode_factor: f32,
+    /// Draw dense meshes with less detail when they cover a small part of the screen.
+    pub lod_enabled: bool,
+    /// Skip drawing models hidden behind others, tested with hardware occlusion queries.
+    pub occlusion_culling_enabled: bool,
+    /// Draw models merged into shared buffers with a single `glMultiDrawElementsIndirect()` call
+    /// per shader variant instead of one draw call each. Requires OpenGL 4.3.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub multi_draw_indirect_enabled: bool,
+    /// Render the scene for two eyes and combine them for 3D glasses or stereo displays.
+    pub stereo_mode: StereoMode,
+    /// Distance between the eyes in world units when stereo output is enabled.
+    pub eye_separation: f32,
+    /// Animate waves on model surface with compute shader.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub vertex_displacement_enabled: bool,
+    /// Maximum distance of vertices moved along their normals.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub vertex_displacement_amplitude: f32,
+    /// Display a line along the face normal of every triangle of visible models, drawn with a
+    /// geometry shader. Requires OpenGL 4.3.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub face_normals_enabled: bool,
+    /// Distance triangles of models are pushed apart along their face normals by a geometry
+    /// shader, 0 for regular display. Models are flat shaded with their material color instead of
+    /// their shading model. Forward rendering only, requires OpenGL 4.3.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub explode_distance: f32,
+    /// Stop logic updates, freezing the scene and the camera in place.
+    pub paused: bool,
+    /// Speed of logic time relative to real time, below 1 for slow motion.
+    pub time_scale: f32,
+    /// Run a single logic update on the next frame, even while paused. Reset once done.
+    pub step_requested: bool,
+    /// Language of the overlay GUI and of the HTML controls.
+    pub language: Language,
 }
 
 impl Default for DrawProperties {
@@ -25,17 +137,336 @@ impl Default for DrawProperties {
             #[cfg(target_arch = "wasm32")]
             overlay_gui_enabled: false,
             #[cfg(not(target_arch = "wasm32"))]
-            vsync_enabled: false,
+            vsync_mode: VsyncMode::Off,
             background_color: [0.5, 0.5, 0.5],
             model_rotation: [0.0, 0.0, 0.0],
-            model_color: [0.0, 0.8, 1.0],
             light_direction: [-0.5, -1.0, 0.0],
+            light_view_enabled: false,
+            light_gizmo_enabled: true,
+            #[cfg(not(target_arch = "wasm32"))]
+            baked_lighting_enabled: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            lightmap_settings: LightmapSettings::default(),
+            shadow_settings: ShadowSettings::default(),
             field_of_view: 60.0,
+            camera_mode: CameraMode::Fly,
+            camera_transition_duration: 1.0,
+            camera_transition_easing: Easing::Smoothstep,
+            #[cfg(not(target_arch = "wasm32"))]
+            raw_mouse_input: true,
+            mouse_smoothing: 0.0,
+            mouse_acceleration: 0.0,
+            render_path: RenderPath::Forward,
+            anti_aliasing: AntiAliasing::Multisample,
+            taa_sharpness: 0.25,
+            exposure: 0.0,
+            gamma: 2.2,
+            post_effect_settings: PostEffectSettings::default(),
             selected_model_index: 2,
             skybox_enabled: true,
+            labels_enabled: false,
+            reflective_plane_enabled: false,
+            reflective_plane_height: -1.0,
+            terrain_enabled: true,
+            mirror_enabled: false,
+            reflectivity: 0.3,
+            reflection_blur: 1.0,
             wireframe_mode_enabled: false,
-            diffuse_enabled: true,
-            specular_enabled: true,
+            render_scale: 1.0,
+            auto_quality_enabled: false,
+            target_frame_rate: 60.0,
+            reduced_effects: false,
+            overdraw_heatmap_enabled: false,
+            bounding_box_enabled: false,
+            bounding_sphere_enabled: false,
+            decal_volumes_enabled: true,
+            #[cfg(not(target_arch = "wasm32"))]
+            mesh_problems_enabled: false,
+            part_explode_factor: 0.0,
+            lod_enabled: true,
+            occlusion_culling_enabled: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            multi_draw_indirect_enabled: true,
+            stereo_mode: StereoMode::Off,
+            // Average human interpupillary distance with 1 unit as 1 meter
+            eye_separation: 0.065,
+            #[cfg(not(target_arch = "wasm32"))]
+            vertex_displacement_enabled: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            vertex_displacement_amplitude: 0.05,
+            #[cfg(not(target_arch = "wasm32"))]
+            face_normals_enabled: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            explode_distance: 0.0,
+            paused: false,
+            time_scale: 1.0,
+            step_requested: false,
+            language: Language::English,
+        }
+    }
+}
+
+impl DrawProperties {
+    /// Set a property by its field name from numbers, for remote control. Vectors take 3 values,
+    /// flags are enabled by non-zero values and modes are selected by their index in `ALL`.
+    /// Non-finite values and scalars outside of the range of their GUI slider are rejected.
+    pub fn set_by_name(&mut self, name: &str, values: &[f32]) -> Result<(), String> {
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err(format!("{name} requires finite values"));
+        }
+        let vector = || -> Result<[f32; 3], String> {
+            <[f32; 3]>::try_from(values).map_err(|_| format!("{name} requires 3 values"))
+        };
+        let scalar = || -> Result<f32, String> {
+            match values {
+                [value] => Ok(*value),
+                _ => Err(format!("{name} requires 1 value")),
+            }
+        };
+        let ranged = |range: RangeInclusive<f32>| -> Result<f32, String> {
+            let value = scalar()?;
+            if !range.contains(&value) {
+                return Err(format!(
+                    "{name} requires a value between {} and {}",
+                    range.start(),
+                    range.end()
+                ));
+            }
+            Ok(value)
+        };
+        let flag = || scalar().map(|value| value != 0.0);
+        let mode = |count: usize| -> Result<usize, String> {
+            let index = scalar()?;
+            if index < 0.0 || count as f32 <= index {
+                return Err(format!("{name} requires an index below {count}"));
+            }
+            Ok(index as usize)
+        };
+        match name {
+            "background_color" => self.background_color = vector()?,
+            "model_rotation" => self.model_rotation = vector()?,
+            "light_direction" => self.light_direction = vector()?,
+            "light_gizmo_enabled" => self.light_gizmo_enabled = flag()?,
+            "field_of_view" => self.field_of_view = ranged(45.0..=120.0)?,
+            "camera_mode" => self.camera_mode = CameraMode::ALL[mode(CameraMode::ALL.len())?],
+            "render_path" => self.render_path = RenderPath::ALL[mode(RenderPath::ALL.len())?],
+            "anti_aliasing" => {
+                self.anti_aliasing = AntiAliasing::ALL[mode(AntiAliasing::ALL.len())?]
+            }
+            "exposure" => self.exposure = ranged(-5.0..=5.0)?,
+            "gamma" => self.gamma = ranged(1.0..=3.0)?,
+            // Range is checked by the application, which knows the number of models
+            "selected_model_index" => self.selected_model_index = mode(usize::MAX)?,
+            "skybox_enabled" => self.skybox_enabled = flag()?,
+            "labels_enabled" => self.labels_enabled = flag()?,
+            "reflective_plane_enabled" => self.reflective_plane_enabled = flag()?,
+            "reflective_plane_height" => self.reflective_plane_height = ranged(-3.0..=0.0)?,
+            "mirror_enabled" => self.mirror_enabled = flag()?,
+            "reflectivity" => self.reflectivity = ranged(0.0..=1.0)?,
+            "wireframe_mode_enabled" => self.wireframe_mode_enabled = flag()?,
+            "render_scale" => self.render_scale = ranged(MIN_RENDER_SCALE..=1.0)?,
+            "bounding_box_enabled" => self.bounding_box_enabled = flag()?,
+            "part_explode_factor" => self.part_explode_factor = ranged(0.0..=2.0)?,
+            "stereo_mode" => self.stereo_mode = StereoMode::ALL[mode(StereoMode::ALL.len())?],
+            "eye_separation" => self.eye_separation = ranged(0.0..=0.3)?,
+            "paused" => self.paused = flag()?,
+            "time_scale" => self.time_scale = ranged(0.1..=4.0)?,
+            _ => return Err(format!("unknown property {name}")),
+        }
+        Ok(())
+    }
+}
+
+/// Rendering pipeline used for drawing the scene.
+///
+/// Discriminant values match option indices of HTML select element.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RenderPath {
+    /// Lighting is calculated while drawing each model. Supports multisample anti-aliasing.
+    Forward = 0,
+    /// Surface attributes are written into a G-buffer first, then lighting is calculated once per
+    /// screen pixel in a separate pass. No multisample anti-aliasing.
+    Deferred = 1,
+}
+
+impl RenderPath {
+    pub const ALL: [RenderPath; 2] = [RenderPath::Forward, RenderPath::Deferred];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RenderPath::Forward => "Forward",
+            RenderPath::Deferred => "Deferred",
+        }
+    }
+
+    /// Message identifier of the translated label.
+    pub(crate) fn l10n_id(self) -> &'static str {
+        match self {
+            RenderPath::Forward => "render-path-forward",
+            RenderPath::Deferred => "render-path-deferred",
+        }
+    }
+}
+
+/// Smoothing of jagged edges in the scene.
+///
+/// Discriminant values match option indices of HTML select element.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AntiAliasing {
+    Off = 0,
+    /// Several coverage samples per pixel with the sample count of the window. Forward rendering
+    /// only.
+    Multisample = 1,
+    /// Projection jittered by a subpixel offset every frame, accumulated over frames with
+    /// reprojection into the previous frame. Smooths shading and transparency as well, with either
+    /// render path. Window output without stereo only, other outputs are not anti-aliased.
+    Temporal = 2,
+}
+
+impl AntiAliasing {
+    pub const ALL: [AntiAliasing; 3] = [
+        AntiAliasing::Off,
+        AntiAliasing::Multisample,
+        AntiAliasing::Temporal,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AntiAliasing::Off => "Off",
+            AntiAliasing::Multisample => "MSAA",
+            AntiAliasing::Temporal => "TAA",
+        }
+    }
+
+    /// Message identifier of the translated label.
+    pub(crate) fn l10n_id(self) -> &'static str {
+        match self {
+            AntiAliasing::Off => "anti-aliasing-off",
+            AntiAliasing::Multisample => "anti-aliasing-multisample",
+            AntiAliasing::Temporal => "anti-aliasing-temporal",
+        }
+    }
+}
+
+/// Movement and rotation rules of the camera.
+///
+/// Discriminant values match option indices of HTML select element.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Flying like in FPS games, turning around the world up axis with limited pitch.
+    Fly = 0,
+    /// Spaceship camera rotating and moving relative to itself.
+    SixDegreesOfFreedom = 1,
+    /// Walking at eye level above the ground with gravity, flying is not possible.
+    Walk = 2,
+}
+
+impl CameraMode {
+    pub const ALL: [CameraMode; 3] = [
+        CameraMode::Fly,
+        CameraMode::SixDegreesOfFreedom,
+        CameraMode::Walk,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CameraMode::Fly => "Fly",
+            CameraMode::SixDegreesOfFreedom => "Six degrees of freedom",
+            CameraMode::Walk => "Walk",
+        }
+    }
+
+    /// Message identifier of the translated label.
+    pub(crate) fn l10n_id(self) -> &'static str {
+        match self {
+            CameraMode::Fly => "camera-mode-fly",
+            CameraMode::SixDegreesOfFreedom => "camera-mode-six-dof",
+            CameraMode::Walk => "camera-mode-walk",
+        }
+    }
+}
+
+/// Combination of the scene rendered from two eyes in the window.
+///
+/// Discriminant values match option indices of HTML select element.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StereoMode {
+    /// Single view from the camera.
+    Off = 0,
+    /// Left eye in the red channel and right eye in the green and blue channels, viewed with
+    /// red/cyan glasses.
+    Anaglyph = 1,
+    /// Left eye in the left half of the window and right eye in the right half, for stereo displays
+    /// and parallel free-viewing.
+    SideBySide = 2,
+}
+
+impl StereoMode {
+    pub const ALL: [StereoMode; 3] = [
+        StereoMode::Off,
+        StereoMode::Anaglyph,
+        StereoMode::SideBySide,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StereoMode::Off => "Off",
+            StereoMode::Anaglyph => "Anaglyph",
+            StereoMode::SideBySide => "Side by side",
+        }
+    }
+
+    /// Message identifier of the translated label.
+    pub(crate) fn l10n_id(self) -> &'static str {
+        match self {
+            StereoMode::Off => "stereo-mode-off",
+            StereoMode::Anaglyph => "stereo-mode-anaglyph",
+            StereoMode::SideBySide => "stereo-mode-side-by-side",
+        }
+    }
+}
+
+/// Synchronization of buffer swaps with the vertical blank of the display.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum VsyncMode {
+    /// Swap immediately, tearing is visible but latency is the lowest.
+    Off,
+    /// Wait for every vertical blank.
+    On,
+    /// Wait for vertical blank, but swap immediately when the frame is late instead of waiting
+    /// for the next one. Requires the `EXT_swap_control_tear` extension of GLX or WGL, falls back
+    /// to `On` elsewhere.
+    Adaptive,
+    /// Wait for every second vertical blank, halving the refresh rate.
+    Half,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl VsyncMode {
+    pub const ALL: [VsyncMode; 4] = [
+        VsyncMode::Off,
+        VsyncMode::On,
+        VsyncMode::Adaptive,
+        VsyncMode::Half,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            VsyncMode::Off => "Off",
+            VsyncMode::On => "On",
+            VsyncMode::Adaptive => "Adaptive",
+            VsyncMode::Half => "Half",
+        }
+    }
+
+    /// Message identifier of the translated label.
+    pub(crate) fn l10n_id(self) -> &'static str {
+        match self {
+            VsyncMode::Off => "vsync-off",
+            VsyncMode::On => "vsync-on",
+            VsyncMode::Adaptive => "vsync-adaptive",
+            VsyncMode::Half => "vsync-half",
         }
     }
 }
@@ -50,3 +481,40 @@ pub struct FrameRateInfo {
     /// useful metric for performance measurement than simple FPS.
     pub ms_per_frame: f32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_by_name_rejects_values_outside_of_slider_range() {
+        let mut draw_props = DrawProperties::default();
+        for (name, value) in [
+            ("field_of_view", 0.0),
+            ("field_of_view", 180.0),
+            ("field_of_view", f32::NAN),
+            ("time_scale", 1e30),
+            ("time_scale", -1.0),
+            ("render_scale", 0.0),
+            ("exposure", f32::INFINITY),
+        ] {
+            assert!(
+                draw_props.set_by_name(name, &[value]).is_err(),
+                "{name} {value}"
+            );
+        }
+        assert_eq!(
+            draw_props.field_of_view,
+            DrawProperties::default().field_of_view
+        );
+        assert_eq!(draw_props.time_scale, 1.0);
+        assert!(draw_props
+            .set_by_name("background_color", &[0.0, f32::NAN, 0.0])
+            .is_err());
+
+        draw_props.set_by_name("field_of_view", &[90.0]).unwrap();
+        draw_props.set_by_name("time_scale", &[4.0]).unwrap();
+        assert_eq!(draw_props.field_of_view, 90.0);
+        assert_eq!(draw_props.time_scale, 4.0);
+    }
+}
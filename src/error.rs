@@ -0,0 +1,69 @@
+use std::fmt;
+
+use crate::CubemapFace;
+
+/// Errors of renderer resource creation that callers may want to distinguish or report in detail.
+#[derive(Debug)]
+pub enum RendererError {
+    /// Image of a cube-map face could not be read or decoded.
+    CubemapFaceLoad { face: CubemapFace, reason: String },
+    /// Cube-map faces must have equal width and height.
+    CubemapFaceNotSquare {
+        face: CubemapFace,
+        width: u32,
+        height: u32,
+    },
+    /// Every cube-map face must have the same size as the right face.
+    CubemapFaceSizeMismatch {
+        face: CubemapFace,
+        size: u32,
+        expected_size: u32,
+    },
+    /// Every cube-map face must have the same pixel format as the right face.
+    CubemapFaceFormatMismatch {
+        face: CubemapFace,
+        format: String,
+        expected_format: String,
+    },
+    /// OpenGL object could not be allocated.
+    ResourceCreation(String),
+}
+
+impl fmt::Display for RendererError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RendererError::CubemapFaceLoad { face, reason } => {
+                write!(f, "unable to load {face} cube-map face: {reason}")
+            }
+            RendererError::CubemapFaceNotSquare {
+                face,
+                width,
+                height,
+            } => write!(
+                f,
+                "{face} cube-map face is not square: {width}x{height} pixels"
+            ),
+            RendererError::CubemapFaceSizeMismatch {
+                face,
+                size,
+                expected_size,
+            } => write!(
+                f,
+                "{face} cube-map face is {size} pixels wide, expected {expected_size}"
+            ),
+            RendererError::CubemapFaceFormatMismatch {
+                face,
+                format,
+                expected_format,
+            } => write!(
+                f,
+                "{face} cube-map face has {format} pixel format, expected {expected_format}"
+            ),
+            RendererError::ResourceCreation(reason) => {
+                write!(f, "unable to create OpenGL resource: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RendererError {}
@@ -0,0 +1,363 @@
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_void, CStr, CString},
+    num::{NonZeroIsize, NonZeroU32},
+    ptr::NonNull,
+    sync::Arc,
+};
+
+use cgmath::{Point3, Vector2, Vector3};
+use glutin::{
+    config::ConfigTemplateBuilder,
+    context::PossiblyCurrentContext,
+    display::{Display, DisplayApiPreference},
+    prelude::*,
+    surface::{Surface, SurfaceAttributesBuilder, WindowSurface},
+};
+use raw_window_handle::{
+    AppKitDisplayHandle, AppKitWindowHandle, RawDisplayHandle, RawWindowHandle,
+    WaylandDisplayHandle, WaylandWindowHandle, Win32WindowHandle, WindowsDisplayHandle,
+    XlibDisplayHandle, XlibWindowHandle,
+};
+
+use crate::{
+    app::{create_gl_context, gl_config_picker},
+    Camera, DrawProperties, Model, Renderer, Scene, Skybox,
+};
+
+/// Windowing systems of the native handles passed to `renderer_create()`.
+pub const RENDERER_PLATFORM_XLIB: u32 = 0;
+pub const RENDERER_PLATFORM_WAYLAND: u32 = 1;
+pub const RENDERER_PLATFORM_WIN32: u32 = 2;
+pub const RENDERER_PLATFORM_APPKIT: u32 = 3;
+
+thread_local! {
+    // Message of the last failed call on this thread, returned by `renderer_last_error()`
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// Renderer drawing into a window of the embedding application, opaque to C code.
+pub struct RendererContext {
+    // OpenGL objects are dropped before the context they belong to
+    renderer: Renderer,
+    skybox: Skybox,
+    scene: Scene,
+    draw_props: DrawProperties,
+    gl: Arc<glow::Context>,
+    width: u32,
+    height: u32,
+    surface: Surface<WindowSurface>,
+    context: PossiblyCurrentContext,
+}
+
+impl RendererContext {
+    fn new(
+        raw_window_handle: RawWindowHandle,
+        raw_display_handle: RawDisplayHandle,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, String> {
+        cfg_if::cfg_if! {
+            if #[cfg(windows)] {
+                let preference = DisplayApiPreference::Wgl(Some(raw_window_handle));
+            } else if #[cfg(target_os = "macos")] {
+                let preference = DisplayApiPreference::Cgl;
+            } else {
+                let preference = DisplayApiPreference::Egl;
+            }
+        }
+        let display = unsafe { Display::new(raw_display_handle, preference) }
+            .map_err(|e| format!("cannot create display: {e}"))?;
+        let template = ConfigTemplateBuilder::new()
+            .compatible_with_native_window(raw_window_handle)
+            .with_stencil_size(8)
+            .build();
+        let mut configs = unsafe { display.find_configs(template) }
+            .map_err(|e| format!("cannot query configs: {e}"))?
+            .peekable();
+        if configs.peek().is_none() {
+            return Err("no config compatible with the window".to_string());
+        }
+        let config = gl_config_picker(Box::new(configs));
+
+        let surface_size = |size: u32| NonZeroU32::new(size.max(1)).unwrap();
+        let surface_attributes = SurfaceAttributesBuilder::<WindowSurface>::new()
+            .with_srgb(Some(config.srgb_capable()))
+            .build(raw_window_handle, surface_size(width), surface_size(height));
+        let surface = unsafe { display.create_window_surface(&config, &surface_attributes) }
+            .map_err(|e| format!("cannot create window surface: {e}"))?;
+        let context = create_gl_context(&config, Some(raw_window_handle))?
+            .make_current(&surface)
+            .map_err(|e| format!("cannot make context current: {e}"))?;
+        let gl = Arc::new(unsafe {
+            glow::Context::from_loader_function_cstr(|symbol| display.get_proc_address(symbol))
+        });
+
+        Ok(Self {
+            renderer: Renderer::new(gl.clone())?,
+            skybox: Skybox::placeholder(gl.clone()).map_err(|e| e.to_string())?,
+            scene: Scene::new(Camera::new(
+                Point3::new(0.0, 0.0, 4.0),
+                Vector2::new(270.0, 0.0),
+            )),
+            draw_props: DrawProperties {
+                skybox_enabled: false,
+                ..Default::default()
+            },
+            gl,
+            width,
+            height,
+            surface,
+            context,
+        })
+    }
+
+    fn load_model(&mut self, path: &str) -> Result<usize, String> {
+        self.make_current()?;
+        let name = std::path::Path::new(path)
+            .file_stem()
+            .map_or(path.to_string(), |stem| stem.to_string_lossy().into_owned());
+        let model = Model::create_from_file(self.gl.clone(), path)?.with_name(&name);
+        self.scene.models.push(model);
+        let model_index = self.scene.models.len() - 1;
+        self.scene.spawn_selectable(model_index);
+        self.draw_props.selected_model_index = model_index;
+        Ok(model_index)
+    }
+
+    fn set_property(&mut self, name: &str, values: &[f32]) -> Result<(), String> {
+        self.draw_props.set_by_name(name, values)?;
+        let last_model_index = self.scene.models.len().saturating_sub(1);
+        self.draw_props.selected_model_index =
+            self.draw_props.selected_model_index.min(last_model_index);
+        Ok(())
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.surface.resize(
+            &self.context,
+            NonZeroU32::new(width.max(1)).unwrap(),
+            NonZeroU32::new(height.max(1)).unwrap(),
+        );
+    }
+
+    fn render_frame(&mut self) -> Result<(), String> {
+        self.make_current()?;
+        self.scene.apply_draw_properties(&self.draw_props);
+        self.renderer.draw_to_size(
+            self.width,
+            self.height,
+            &self.draw_props,
+            &self.scene,
+            &self.skybox,
+        );
+        self.surface
+            .swap_buffers(&self.context)
+            .map_err(|e| format!("cannot swap buffers: {e}"))
+    }
+
+    /// The embedding application may use contexts of its own on the same thread.
+    fn make_current(&self) -> Result<(), String> {
+        self.context
+            .make_current(&self.surface)
+            .map_err(|e| format!("cannot make context current: {e}"))
+    }
+}
+
+/// Create a renderer drawing into the native window, or return null on failure.
+///
+/// `window` and `display` by platform:
+/// - `RENDERER_PLATFORM_XLIB`: X11 `Window` id cast to a pointer and `Display*`
+/// - `RENDERER_PLATFORM_WAYLAND`: `wl_surface*` and `wl_display*`
+/// - `RENDERER_PLATFORM_WIN32`: `HWND` and `HINSTANCE`, which may be null
+/// - `RENDERER_PLATFORM_APPKIT`: `NSView*`, display is ignored
+///
+/// # Safety
+///
+/// Handles must stay valid until the renderer is destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn renderer_create(
+    platform: u32,
+    window: *mut c_void,
+    display: *mut c_void,
+    width: u32,
+    height: u32,
+) -> *mut RendererContext {
+    let handles = match platform {
+        RENDERER_PLATFORM_XLIB => (!window.is_null()).then(|| {
+            (
+                RawWindowHandle::Xlib(XlibWindowHandle::new(window as _)),
+                RawDisplayHandle::Xlib(XlibDisplayHandle::new(NonNull::new(display), 0)),
+            )
+        }),
+        RENDERER_PLATFORM_WAYLAND => {
+            NonNull::new(window)
+                .zip(NonNull::new(display))
+                .map(|(surface, display)| {
+                    (
+                        RawWindowHandle::Wayland(WaylandWindowHandle::new(surface)),
+                        RawDisplayHandle::Wayland(WaylandDisplayHandle::new(display)),
+                    )
+                })
+        }
+        RENDERER_PLATFORM_WIN32 => NonZeroIsize::new(window as isize).map(|hwnd| {
+            let mut window_handle = Win32WindowHandle::new(hwnd);
+            window_handle.hinstance = NonZeroIsize::new(display as isize);
+            (
+                RawWindowHandle::Win32(window_handle),
+                RawDisplayHandle::Windows(WindowsDisplayHandle::new()),
+            )
+        }),
+        RENDERER_PLATFORM_APPKIT => NonNull::new(window).map(|ns_view| {
+            (
+                RawWindowHandle::AppKit(AppKitWindowHandle::new(ns_view)),
+                RawDisplayHandle::AppKit(AppKitDisplayHandle::new()),
+            )
+        }),
+        _ => {
+            set_last_error(format!("unknown platform {platform}"));
+            return std::ptr::null_mut();
+        }
+    };
+    let Some((raw_window_handle, raw_display_handle)) = handles else {
+        set_last_error("window handle must not be null".to_string());
+        return std::ptr::null_mut();
+    };
+    match RendererContext::new(raw_window_handle, raw_display_handle, width, height) {
+        Ok(context) => Box::into_raw(Box::new(context)),
+        Err(e) => {
+            set_last_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Destroy the renderer and its OpenGL context. Null is ignored.
+///
+/// # Safety
+///
+/// `context` must be returned by `renderer_create()` and not destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn renderer_destroy(context: *mut RendererContext) {
+    if !context.is_null() {
+        let context = Box::from_raw(context);
+        // OpenGL objects are deleted with the context current
+        let _ = context.make_current();
+        drop(context);
+    }
+}
+
+/// Message of the last failed call on the calling thread, empty if none failed yet. Valid until
+/// the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn renderer_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| last_error.borrow().as_ptr())
+}
+
+/// Load an OBJ or PLY file and select it as the displayed model. Returns the index of the model,
+/// or -1 on failure.
+///
+/// # Safety
+///
+/// `context` must be a live renderer, `path` a null-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn renderer_load_model(
+    context: *mut RendererContext,
+    path: *const c_char,
+) -> i32 {
+    let result = read_string(path).and_then(|path| (*context).load_model(path));
+    match result {
+        Ok(model_index) => model_index as i32,
+        Err(e) => {
+            set_last_error(e);
+            -1
+        }
+    }
+}
+
+/// Set a draw property by its field name from `count` values, see the README for the names.
+/// Returns 0, or -1 on failure.
+///
+/// # Safety
+///
+/// `context` must be a live renderer, `name` a null-terminated UTF-8 string and `values` point to
+/// `count` floats.
+#[no_mangle]
+pub unsafe extern "C" fn renderer_set_property(
+    context: *mut RendererContext,
+    name: *const c_char,
+    values: *const f32,
+    count: usize,
+) -> i32 {
+    let values = if count == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(values, count)
+    };
+    let result = read_string(name).and_then(|name| (*context).set_property(name, values));
+    report(result)
+}
+
+/// Place the camera at the position, turned to yaw, pitch and roll in degrees.
+///
+/// # Safety
+///
+/// `context` must be a live renderer, `position` and `rotation` point to 3 floats each.
+#[no_mangle]
+pub unsafe extern "C" fn renderer_set_camera(
+    context: *mut RendererContext,
+    position: *const [f32; 3],
+    rotation: *const [f32; 3],
+) {
+    (*context)
+        .scene
+        .camera
+        .set_placement(Point3::from(*position), Vector3::from(*rotation));
+}
+
+/// Resize the drawn area after the window was resized, in pixels.
+///
+/// # Safety
+///
+/// `context` must be a live renderer.
+#[no_mangle]
+pub unsafe extern "C" fn renderer_resize(context: *mut RendererContext, width: u32, height: u32) {
+    (*context).resize(width, height);
+}
+
+/// Draw a frame into the window and present it. Returns 0, or -1 on failure.
+///
+/// # Safety
+///
+/// `context` must be a live renderer, used on the thread that created it.
+#[no_mangle]
+pub unsafe extern "C" fn renderer_render_frame(context: *mut RendererContext) -> i32 {
+    report((*context).render_frame())
+}
+
+unsafe fn read_string<'a>(string: *const c_char) -> Result<&'a str, String> {
+    if string.is_null() {
+        return Err("string must not be null".to_string());
+    }
+    CStr::from_ptr(string)
+        .to_str()
+        .map_err(|e| format!("string is not valid UTF-8: {e}"))
+}
+
+fn report(result: Result<(), String>) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            set_last_error(e);
+            -1
+        }
+    }
+}
+
+fn set_last_error(message: String) {
+    // Interior null bytes would end the message early anyway
+    let message = CString::new(message.replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = message);
+}
@@ -0,0 +1,53 @@
+use crate::Renderer;
+
+/// Saves rendered frames into numbered PNG images of a directory at a fixed frame rate, e.g. to
+/// encode the playback of the timeline into a video with external tools.
+///
+/// Frames are rendered as fast as possible, each one at the time of the animation it shows, so
+/// the sequence plays smoothly regardless of how long rendering took.
+pub(crate) struct FrameSequenceRecorder {
+    directory: String,
+    frame_rate: f32,
+    frame: u32,
+    frame_count: u32,
+}
+
+impl FrameSequenceRecorder {
+    /// Create the directory for enough frames to cover `duration` seconds.
+    pub fn new(directory: &str, frame_rate: f32, duration: f32) -> Result<Self, String> {
+        if frame_rate <= 0.0 {
+            return Err(format!("invalid frame rate {frame_rate}"));
+        }
+        std::fs::create_dir_all(directory)
+            .map_err(|e| format!("cannot create directory {directory}: {e}"))?;
+        Ok(Self {
+            directory: directory.to_string(),
+            frame_rate,
+            frame: 0,
+            frame_count: (duration * frame_rate).round().max(1.0) as u32,
+        })
+    }
+
+    /// Seconds of the animation the next frame shows.
+    pub fn time(&self) -> f32 {
+        self.frame as f32 / self.frame_rate
+    }
+
+    /// Save the frame rendered into the window. Returns true once every frame was saved.
+    pub fn save_frame(&mut self, renderer: &Renderer) -> Result<bool, String> {
+        let path = format!("{}/frame_{:05}.png", self.directory, self.frame);
+        renderer
+            .save_screenshot(&path)
+            .map_err(|e| format!("unable to save frame {path}: {e}"))?;
+        self.frame += 1;
+        Ok(self.frame_count <= self.frame)
+    }
+
+    pub fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    pub fn directory(&self) -> &str {
+        &self.directory
+    }
+}
@@ -0,0 +1,918 @@
+use std::sync::Arc;
+
+use glow::{HasContext, Renderbuffer, Texture};
+
+use crate::{gl_resources, gl_state};
+
+/// Depth and stencil attachments are stored as DEPTH24_STENCIL8.
+const DEPTH_STENCIL_BYTES_PER_PIXEL: usize = 4;
+
+/// Storage format of color attachments.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ColorFormat {
+    /// Color values are converted from linear to sRGB on write and back to linear on sampling.
+    /// Color-renderable on WebGL2 without extensions.
+    Srgb8Alpha8,
+    /// Floating point storage keeping values above 1.0 for high dynamic range rendering.
+    Rgba16F,
+}
+
+impl ColorFormat {
+    /// Pick the format for intermediate scene rendering. Values are kept in linear color space in
+    /// both cases.
+    ///
+    /// OpenGL ES and WebGL2: Rendering into floating point textures requires the
+    /// EXT_color_buffer_float extension, fall back to sRGB storage which still has enough
+    /// precision for dark tones.
+    pub fn scene(gl: &glow::Context) -> Self {
+        if gl.version().is_embedded {
+            ColorFormat::Srgb8Alpha8
+        } else {
+            ColorFormat::Rgba16F
+        }
+    }
+
+    fn internal_format(self) -> u32 {
+        match self {
+            ColorFormat::Srgb8Alpha8 => glow::SRGB8_ALPHA8,
+            ColorFormat::Rgba16F => glow::RGBA16F,
+        }
+    }
+
+    fn bytes_per_pixel(self) -> usize {
+        match self {
+            ColorFormat::Srgb8Alpha8 => 4,
+            ColorFormat::Rgba16F => 8,
+        }
+    }
+
+    fn pixel_type(self) -> u32 {
+        match self {
+            ColorFormat::Srgb8Alpha8 => glow::UNSIGNED_BYTE,
+            ColorFormat::Rgba16F => glow::FLOAT,
+        }
+    }
+}
+
+/// Offscreen render target with color and depth textures that can be sampled by later passes.
+///
+/// Contents are stored in GPU memory.
+pub struct Framebuffer {
+    gl: Arc<glow::Context>,
+    framebuffer: glow::Framebuffer,
+    color_texture: Texture,
+    depth_texture: Texture,
+    color_format: ColorFormat,
+    width: u32,
+    height: u32,
+}
+
+impl Framebuffer {
+    pub fn new(
+        gl: Arc<glow::Context>,
+        width: u32,
+        height: u32,
+        color_format: ColorFormat,
+    ) -> Result<Self, String> {
+        unsafe {
+            let framebuffer = gl_resources::create_framebuffer(&gl)
+                .map_err(|e| format!("cannot create framebuffer: {e}"))?;
+            let color_texture = create_attachment_texture(&gl, glow::LINEAR)
+                .map_err(|e| format!("cannot create framebuffer color texture: {e}"))?;
+            // Depth values cannot be filtered
+            let depth_texture = create_attachment_texture(&gl, glow::NEAREST)
+                .map_err(|e| format!("cannot create framebuffer depth texture: {e}"))?;
+
+            let mut framebuffer = Self {
+                gl,
+                framebuffer,
+                color_texture,
+                depth_texture,
+                color_format,
+                width: 0,
+                height: 0,
+            };
+            framebuffer.allocate_storage(width, height)?;
+            Ok(framebuffer)
+        }
+    }
+
+    /// Redirect draw calls into this framebuffer and set viewport to cover all of it.
+    pub fn bind(&self) {
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+            self.gl
+                .viewport(0, 0, self.width as i32, self.height as i32);
+        }
+    }
+
+    /// Make this framebuffer the source of pixel reads, e.g. `glReadPixels()`.
+    pub fn bind_read(&self) {
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.framebuffer));
+        }
+    }
+
+    /// Copy color contents into a region of another framebuffer starting at the given offset.
+    pub fn blit_into(&self, target: glow::Framebuffer, x: i32, y: i32) {
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.framebuffer));
+            self.gl
+                .bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(target));
+            self.gl.blit_framebuffer(
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                x,
+                y,
+                x + self.width as i32,
+                y + self.height as i32,
+                glow::COLOR_BUFFER_BIT,
+                glow::NEAREST,
+            );
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+    }
+
+    /// Copy color contents right of the horizontal position into the same region of the default
+    /// framebuffer.
+    pub fn blit_right_of_into_default(&self, x: i32) {
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.framebuffer));
+            self.gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
+            self.gl.blit_framebuffer(
+                x,
+                0,
+                self.width as i32,
+                self.height as i32,
+                x,
+                0,
+                self.width as i32,
+                self.height as i32,
+                glow::COLOR_BUFFER_BIT,
+                glow::NEAREST,
+            );
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+    }
+
+    /// Reallocate attachments if size differs. Previous contents are discarded.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        // Zero-sized attachments make framebuffer incomplete (e.g. minimized window)
+        let (width, height) = (width.max(1), height.max(1));
+        if self.width == width && self.height == height {
+            return Ok(());
+        }
+        self.allocate_storage(width, height)
+    }
+
+    pub fn color_texture(&self) -> Texture {
+        self.color_texture
+    }
+
+    /// Depth and stencil values, depth is sampled from the red channel.
+    pub fn depth_texture(&self) -> Texture {
+        self.depth_texture
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn allocate_storage(&mut self, width: u32, height: u32) -> Result<(), String> {
+        let (width, height) = (width.max(1), height.max(1));
+        unsafe {
+            let gl = &self.gl;
+            gl_state::bind_texture(gl, glow::TEXTURE_2D, Some(self.color_texture));
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                self.color_format.internal_format() as i32,
+                width as i32,
+                height as i32,
+                0,
+                glow::RGBA,
+                self.color_format.pixel_type(),
+                None,
+            );
+            gl_state::bind_texture(gl, glow::TEXTURE_2D, Some(self.depth_texture));
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::DEPTH24_STENCIL8 as i32,
+                width as i32,
+                height as i32,
+                0,
+                glow::DEPTH_STENCIL,
+                glow::UNSIGNED_INT_24_8,
+                None,
+            );
+            gl_state::bind_texture(gl, glow::TEXTURE_2D, None);
+            let pixel_count = width as usize * height as usize;
+            gl_resources::set_texture_size(
+                self.color_texture,
+                pixel_count * self.color_format.bytes_per_pixel(),
+            );
+            gl_resources::set_texture_size(
+                self.depth_texture,
+                pixel_count * DEPTH_STENCIL_BYTES_PER_PIXEL,
+            );
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(self.color_texture),
+                0,
+            );
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::DEPTH_STENCIL_ATTACHMENT,
+                glow::TEXTURE_2D,
+                Some(self.depth_texture),
+                0,
+            );
+            let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            // Record size even on failure to avoid retrying allocation on every frame
+            self.width = width;
+            self.height = height;
+            if status != glow::FRAMEBUFFER_COMPLETE {
+                return Err(format!(
+                    "framebuffer of size {width}x{height} is incomplete: status {status:#x}"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl_resources::delete_framebuffer(&self.gl, self.framebuffer);
+            gl_resources::delete_texture(&self.gl, self.color_texture);
+            gl_resources::delete_texture(&self.gl, self.depth_texture);
+        }
+    }
+}
+
+/// Pair of framebuffers for chains of fullscreen passes, e.g. post effects. Each pass draws into
+/// one of them while sampling the output of the previous pass from the other.
+pub struct PingPongFramebuffers {
+    framebuffers: [Framebuffer; 2],
+}
+
+impl PingPongFramebuffers {
+    pub fn new(
+        gl: Arc<glow::Context>,
+        width: u32,
+        height: u32,
+        color_format: ColorFormat,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            framebuffers: [
+                Framebuffer::new(gl.clone(), width, height, color_format)?,
+                Framebuffer::new(gl, width, height, color_format)?,
+            ],
+        })
+    }
+
+    /// Reallocate both framebuffers if size differs. Previous contents are discarded.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        for framebuffer in &mut self.framebuffers {
+            framebuffer.resize(width, height)?;
+        }
+        Ok(())
+    }
+
+    /// Framebuffer the pass at the index of the chain draws into. The first pass reads its input
+    /// from elsewhere, later passes read the target of the pass before them.
+    pub fn target(&self, pass: usize) -> &Framebuffer {
+        &self.framebuffers[pass % 2]
+    }
+}
+
+/// Offscreen render target backed by multisampled renderbuffers for anti-aliasing.
+///
+/// Multisampled contents cannot be sampled by shaders directly, they have to be resolved into a
+/// regular [`Framebuffer`] first.
+pub struct MultisampleFramebuffer {
+    gl: Arc<glow::Context>,
+    framebuffer: glow::Framebuffer,
+    color_renderbuffer: Renderbuffer,
+    depth_renderbuffer: Renderbuffer,
+    color_format: ColorFormat,
+    samples: i32,
+    width: u32,
+    height: u32,
+}
+
+impl MultisampleFramebuffer {
+    pub fn new(
+        gl: Arc<glow::Context>,
+        width: u32,
+        height: u32,
+        color_format: ColorFormat,
+        samples: i32,
+    ) -> Result<Self, String> {
+        unsafe {
+            let framebuffer = gl_resources::create_framebuffer(&gl)
+                .map_err(|e| format!("cannot create multisample framebuffer: {e}"))?;
+            let color_renderbuffer = gl_resources::create_renderbuffer(&gl)
+                .map_err(|e| format!("cannot create multisample color renderbuffer: {e}"))?;
+            let depth_renderbuffer = gl_resources::create_renderbuffer(&gl)
+                .map_err(|e| format!("cannot create multisample depth renderbuffer: {e}"))?;
+
+            let mut framebuffer = Self {
+                gl,
+                framebuffer,
+                color_renderbuffer,
+                depth_renderbuffer,
+                color_format,
+                samples,
+                width: 0,
+                height: 0,
+            };
+            framebuffer.allocate_storage(width, height)?;
+            Ok(framebuffer)
+        }
+    }
+
+    /// Redirect draw calls into this framebuffer and set viewport to cover all of it.
+    pub fn bind(&self) {
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+            self.gl
+                .viewport(0, 0, self.width as i32, self.height as i32);
+        }
+    }
+
+    /// Reallocate attachments if size differs. Previous contents are discarded.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        // Zero-sized attachments make framebuffer incomplete (e.g. minimized window)
+        let (width, height) = (width.max(1), height.max(1));
+        if self.width == width && self.height == height {
+            return Ok(());
+        }
+        self.allocate_storage(width, height)
+    }
+
+    /// Average samples of each pixel into the color texture of target framebuffer and copy depth
+    /// of one sample into its depth texture. Both framebuffers must have the same size and color
+    /// format.
+    pub fn resolve(&self, target: &Framebuffer) {
+        debug_assert!(self.color_format == target.color_format);
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.framebuffer));
+            self.gl
+                .bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(target.framebuffer));
+            self.gl.blit_framebuffer(
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                0,
+                0,
+                target.width as i32,
+                target.height as i32,
+                glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT,
+                glow::NEAREST,
+            );
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+    }
+
+    fn allocate_storage(&mut self, width: u32, height: u32) -> Result<(), String> {
+        let (width, height) = (width.max(1), height.max(1));
+        unsafe {
+            let gl = &self.gl;
+            gl.bind_renderbuffer(glow::RENDERBUFFER, Some(self.color_renderbuffer));
+            gl.renderbuffer_storage_multisample(
+                glow::RENDERBUFFER,
+                self.samples,
+                self.color_format.internal_format(),
+                width as i32,
+                height as i32,
+            );
+            gl.bind_renderbuffer(glow::RENDERBUFFER, Some(self.depth_renderbuffer));
+            gl.renderbuffer_storage_multisample(
+                glow::RENDERBUFFER,
+                self.samples,
+                glow::DEPTH24_STENCIL8,
+                width as i32,
+                height as i32,
+            );
+            gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+            let sample_count = width as usize * height as usize * self.samples.max(1) as usize;
+            gl_resources::set_renderbuffer_size(
+                self.color_renderbuffer,
+                sample_count * self.color_format.bytes_per_pixel(),
+            );
+            gl_resources::set_renderbuffer_size(
+                self.depth_renderbuffer,
+                sample_count * DEPTH_STENCIL_BYTES_PER_PIXEL,
+            );
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+            gl.framebuffer_renderbuffer(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::RENDERBUFFER,
+                Some(self.color_renderbuffer),
+            );
+            gl.framebuffer_renderbuffer(
+                glow::FRAMEBUFFER,
+                glow::DEPTH_STENCIL_ATTACHMENT,
+                glow::RENDERBUFFER,
+                Some(self.depth_renderbuffer),
+            );
+            let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            // Record size even on failure to avoid retrying allocation on every frame
+            self.width = width;
+            self.height = height;
+            if status != glow::FRAMEBUFFER_COMPLETE {
+                return Err(format!(
+                    "multisample framebuffer of size {width}x{height} with {} samples is \
+                     incomplete: status {status:#x}",
+                    self.samples
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for MultisampleFramebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl_resources::delete_framebuffer(&self.gl, self.framebuffer);
+            gl_resources::delete_renderbuffer(&self.gl, self.depth_renderbuffer);
+            gl_resources::delete_renderbuffer(&self.gl, self.color_renderbuffer);
+        }
+    }
+}
+
+/// Geometry buffer of deferred rendering. Surface attributes of visible fragments are written into
+/// multiple textures at once, lighting is calculated later in a single fullscreen pass.
+///
+/// Attachments:
+/// - Albedo: Linear surface color stored with sRGB encoding.
+/// - Normal: World space normal vector remapped into [0, 1] range.
+/// - Material: Material type and parameters in normalized form.
+/// - Depth: Used for reconstructing world space position of fragments.
+pub struct GBuffer {
+    gl: Arc<glow::Context>,
+    framebuffer: glow::Framebuffer,
+    albedo_texture: Texture,
+    normal_texture: Texture,
+    material_texture: Texture,
+    depth_texture: Texture,
+    width: u32,
+    height: u32,
+}
+
+impl GBuffer {
+    pub fn new(gl: Arc<glow::Context>, width: u32, height: u32) -> Result<Self, String> {
+        unsafe {
+            let framebuffer = gl_resources::create_framebuffer(&gl)
+                .map_err(|e| format!("cannot create G-buffer: {e}"))?;
+            // Attributes must not be interpolated between neighbouring fragments
+            let albedo_texture = create_attachment_texture(&gl, glow::NEAREST)
+                .map_err(|e| format!("cannot create G-buffer albedo texture: {e}"))?;
+            let normal_texture = create_attachment_texture(&gl, glow::NEAREST)
+                .map_err(|e| format!("cannot create G-buffer normal texture: {e}"))?;
+            let material_texture = create_attachment_texture(&gl, glow::NEAREST)
+                .map_err(|e| format!("cannot create G-buffer material texture: {e}"))?;
+            let depth_texture = create_attachment_texture(&gl, glow::NEAREST)
+                .map_err(|e| format!("cannot create G-buffer depth texture: {e}"))?;
+
+            let mut gbuffer = Self {
+                gl,
+                framebuffer,
+                albedo_texture,
+                normal_texture,
+                material_texture,
+                depth_texture,
+                width: 0,
+                height: 0,
+            };
+            gbuffer.allocate_storage(width, height)?;
+            Ok(gbuffer)
+        }
+    }
+
+    /// Redirect draw calls into this framebuffer and set viewport to cover all of it.
+    pub fn bind(&self) {
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+            self.gl
+                .viewport(0, 0, self.width as i32, self.height as i32);
+        }
+    }
+
+    /// Reallocate attachments if size differs. Previous contents are discarded.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        // Zero-sized attachments make framebuffer incomplete (e.g. minimized window)
+        let (width, height) = (width.max(1), height.max(1));
+        if self.width == width && self.height == height {
+            return Ok(());
+        }
+        self.allocate_storage(width, height)
+    }
+
+    /// Copy depth values into target framebuffer, so forward rendered entities drawn after the
+    /// lighting pass are occluded by deferred geometry. Both framebuffers must have the same size.
+    pub fn blit_depth(&self, target: &Framebuffer) {
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.framebuffer));
+            self.gl
+                .bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(target.framebuffer));
+            self.gl.blit_framebuffer(
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                0,
+                0,
+                target.width as i32,
+                target.height as i32,
+                glow::DEPTH_BUFFER_BIT,
+                glow::NEAREST,
+            );
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+    }
+
+    pub fn albedo_texture(&self) -> Texture {
+        self.albedo_texture
+    }
+
+    pub fn normal_texture(&self) -> Texture {
+        self.normal_texture
+    }
+
+    pub fn material_texture(&self) -> Texture {
+        self.material_texture
+    }
+
+    pub fn depth_texture(&self) -> Texture {
+        self.depth_texture
+    }
+
+    fn allocate_storage(&mut self, width: u32, height: u32) -> Result<(), String> {
+        let (width, height) = (width.max(1), height.max(1));
+        unsafe {
+            let gl = &self.gl;
+            // All formats are color-renderable on WebGL2 without extensions
+            let attachments = [
+                (
+                    self.albedo_texture,
+                    glow::SRGB8_ALPHA8,
+                    glow::RGBA,
+                    glow::UNSIGNED_BYTE,
+                ),
+                (
+                    self.normal_texture,
+                    glow::RGB10_A2,
+                    glow::RGBA,
+                    glow::UNSIGNED_INT_2_10_10_10_REV,
+                ),
+                (
+                    self.material_texture,
+                    glow::RGBA8,
+                    glow::RGBA,
+                    glow::UNSIGNED_BYTE,
+                ),
+                (
+                    self.depth_texture,
+                    glow::DEPTH24_STENCIL8,
+                    glow::DEPTH_STENCIL,
+                    glow::UNSIGNED_INT_24_8,
+                ),
+            ];
+            for (texture, internal_format, format, pixel_type) in attachments {
+                gl_state::bind_texture(gl, glow::TEXTURE_2D, Some(texture));
+                gl.tex_image_2d(
+                    glow::TEXTURE_2D,
+                    0,
+                    internal_format as i32,
+                    width as i32,
+                    height as i32,
+                    0,
+                    format,
+                    pixel_type,
+                    None,
+                );
+                // Every attachment format is 32 bits per pixel
+                gl_resources::set_texture_size(texture, width as usize * height as usize * 4);
+            }
+            gl_state::bind_texture(gl, glow::TEXTURE_2D, None);
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+            let texture_attachments = [
+                (glow::COLOR_ATTACHMENT0, self.albedo_texture),
+                (glow::COLOR_ATTACHMENT1, self.normal_texture),
+                (glow::COLOR_ATTACHMENT2, self.material_texture),
+                (glow::DEPTH_STENCIL_ATTACHMENT, self.depth_texture),
+            ];
+            for (attachment, texture) in texture_attachments {
+                gl.framebuffer_texture_2d(
+                    glow::FRAMEBUFFER,
+                    attachment,
+                    glow::TEXTURE_2D,
+                    Some(texture),
+                    0,
+                );
+            }
+            // Map fragment shader outputs to color attachments
+            gl.draw_buffers(&[
+                glow::COLOR_ATTACHMENT0,
+                glow::COLOR_ATTACHMENT1,
+                glow::COLOR_ATTACHMENT2,
+            ]);
+            let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            // Record size even on failure to avoid retrying allocation on every frame
+            self.width = width;
+            self.height = height;
+            if status != glow::FRAMEBUFFER_COMPLETE {
+                return Err(format!(
+                    "G-buffer of size {width}x{height} is incomplete: status {status:#x}"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for GBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl_resources::delete_framebuffer(&self.gl, self.framebuffer);
+            gl_resources::delete_texture(&self.gl, self.albedo_texture);
+            gl_resources::delete_texture(&self.gl, self.normal_texture);
+            gl_resources::delete_texture(&self.gl, self.material_texture);
+            gl_resources::delete_texture(&self.gl, self.depth_texture);
+        }
+    }
+}
+
+/// Offscreen render target with only a square depth texture, e.g. a shadow map. Shaders sample it
+/// through `sampler2DShadow`, comparing a reference depth with stored values. Comparison results
+/// of neighbouring texels are filtered linearly for smoother edges.
+pub struct DepthFramebuffer {
+    gl: Arc<glow::Context>,
+    framebuffer: glow::Framebuffer,
+    depth_texture: Texture,
+    size: u32,
+}
+
+impl DepthFramebuffer {
+    pub fn new(gl: Arc<glow::Context>, size: u32) -> Result<Self, String> {
+        unsafe {
+            let framebuffer = gl_resources::create_framebuffer(&gl)
+                .map_err(|e| format!("cannot create depth framebuffer: {e}"))?;
+            let depth_texture = create_attachment_texture(&gl, glow::LINEAR)
+                .map_err(|e| format!("cannot create depth framebuffer texture: {e}"))?;
+            gl_state::bind_texture(&gl, glow::TEXTURE_2D, Some(depth_texture));
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_COMPARE_MODE,
+                glow::COMPARE_REF_TO_TEXTURE as i32,
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_COMPARE_FUNC,
+                glow::LEQUAL as i32,
+            );
+            gl_state::bind_texture(&gl, glow::TEXTURE_2D, None);
+
+            let mut depth_framebuffer = Self {
+                gl,
+                framebuffer,
+                depth_texture,
+                size: 0,
+            };
+            depth_framebuffer.allocate_storage(size)?;
+            Ok(depth_framebuffer)
+        }
+    }
+
+    /// Redirect draw calls into this framebuffer and set viewport to cover all of it.
+    pub fn bind(&self) {
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+            self.gl.viewport(0, 0, self.size as i32, self.size as i32);
+        }
+    }
+
+    /// Reallocate depth texture if size differs. Previous contents are discarded.
+    pub fn resize(&mut self, size: u32) -> Result<(), String> {
+        let size = size.max(1);
+        if self.size == size {
+            return Ok(());
+        }
+        self.allocate_storage(size)
+    }
+
+    pub fn depth_texture(&self) -> Texture {
+        self.depth_texture
+    }
+
+    fn allocate_storage(&mut self, size: u32) -> Result<(), String> {
+        let size = size.max(1);
+        unsafe {
+            let gl = &self.gl;
+            gl_state::bind_texture(gl, glow::TEXTURE_2D, Some(self.depth_texture));
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::DEPTH_COMPONENT24 as i32,
+                size as i32,
+                size as i32,
+                0,
+                glow::DEPTH_COMPONENT,
+                glow::UNSIGNED_INT,
+                None,
+            );
+            gl_state::bind_texture(gl, glow::TEXTURE_2D, None);
+            // 24-bit depth is padded to 32 bits
+            gl_resources::set_texture_size(self.depth_texture, size as usize * size as usize * 4);
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::DEPTH_ATTACHMENT,
+                glow::TEXTURE_2D,
+                Some(self.depth_texture),
+                0,
+            );
+            // No color is written
+            gl.draw_buffers(&[glow::NONE]);
+            gl.read_buffer(glow::NONE);
+            let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            // Record size even on failure to avoid retrying allocation on every frame
+            self.size = size;
+            if status != glow::FRAMEBUFFER_COMPLETE {
+                return Err(format!(
+                    "depth framebuffer of size {size}x{size} is incomplete: status {status:#x}"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for DepthFramebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl_resources::delete_framebuffer(&self.gl, self.framebuffer);
+            gl_resources::delete_texture(&self.gl, self.depth_texture);
+        }
+    }
+}
+
+/// Offscreen render target with only a depth cube map, e.g. an omnidirectional shadow map. Faces
+/// are drawn one by one and sampled through `samplerCubeShadow` like [`DepthFramebuffer`].
+pub struct DepthCubeFramebuffer {
+    gl: Arc<glow::Context>,
+    framebuffer: glow::Framebuffer,
+    depth_texture: Texture,
+    size: u32,
+}
+
+impl DepthCubeFramebuffer {
+    pub fn new(gl: Arc<glow::Context>, size: u32) -> Result<Self, String> {
+        let size = size.max(1);
+        unsafe {
+            let framebuffer = gl_resources::create_framebuffer(&gl)
+                .map_err(|e| format!("cannot create depth cube framebuffer: {e}"))?;
+            let depth_texture = gl_resources::create_texture(&gl)
+                .map_err(|e| format!("cannot create depth cube framebuffer texture: {e}"))?;
+            gl_state::bind_texture(&gl, glow::TEXTURE_CUBE_MAP, Some(depth_texture));
+            for face in 0..6 {
+                gl.tex_image_2d(
+                    glow::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                    0,
+                    glow::DEPTH_COMPONENT24 as i32,
+                    size as i32,
+                    size as i32,
+                    0,
+                    glow::DEPTH_COMPONENT,
+                    glow::UNSIGNED_INT,
+                    None,
+                );
+            }
+            let parameters = [
+                (glow::TEXTURE_MIN_FILTER, glow::LINEAR),
+                (glow::TEXTURE_MAG_FILTER, glow::LINEAR),
+                (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+                (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+                (glow::TEXTURE_WRAP_R, glow::CLAMP_TO_EDGE),
+                (glow::TEXTURE_COMPARE_MODE, glow::COMPARE_REF_TO_TEXTURE),
+                (glow::TEXTURE_COMPARE_FUNC, glow::LEQUAL),
+            ];
+            for (parameter, value) in parameters {
+                gl.tex_parameter_i32(glow::TEXTURE_CUBE_MAP, parameter, value as i32);
+            }
+            gl_state::bind_texture(&gl, glow::TEXTURE_CUBE_MAP, None);
+            // 24-bit depth is padded to 32 bits
+            gl_resources::set_texture_size(depth_texture, 6 * size as usize * size as usize * 4);
+
+            let depth_cube_framebuffer = Self {
+                gl,
+                framebuffer,
+                depth_texture,
+                size,
+            };
+            depth_cube_framebuffer.bind_face(0);
+            let status = depth_cube_framebuffer
+                .gl
+                .check_framebuffer_status(glow::FRAMEBUFFER);
+            depth_cube_framebuffer
+                .gl
+                .bind_framebuffer(glow::FRAMEBUFFER, None);
+            if status != glow::FRAMEBUFFER_COMPLETE {
+                return Err(format!(
+                    "depth cube framebuffer of size {size}x{size} is incomplete: status {status:#x}"
+                ));
+            }
+            Ok(depth_cube_framebuffer)
+        }
+    }
+
+    /// Redirect draw calls into a face of the cube map, in the order of
+    /// `TEXTURE_CUBE_MAP_POSITIVE_X` and following targets, and set viewport to cover all of it.
+    pub fn bind_face(&self, face: u32) {
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+            self.gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::DEPTH_ATTACHMENT,
+                glow::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                Some(self.depth_texture),
+                0,
+            );
+            // No color is written
+            self.gl.draw_buffers(&[glow::NONE]);
+            self.gl.read_buffer(glow::NONE);
+            self.gl.viewport(0, 0, self.size as i32, self.size as i32);
+        }
+    }
+
+    pub fn depth_texture(&self) -> Texture {
+        self.depth_texture
+    }
+}
+
+impl Drop for DepthCubeFramebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl_resources::delete_framebuffer(&self.gl, self.framebuffer);
+            gl_resources::delete_texture(&self.gl, self.depth_texture);
+        }
+    }
+}
+
+/// Create texture suitable for framebuffer attachment without allocating storage.
+unsafe fn create_attachment_texture(gl: &glow::Context, filter: u32) -> Result<Texture, String> {
+    let texture = gl_resources::create_texture(gl)?;
+    gl_state::bind_texture(gl, glow::TEXTURE_2D, Some(texture));
+    gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, filter as i32);
+    gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, filter as i32);
+    gl.tex_parameter_i32(
+        glow::TEXTURE_2D,
+        glow::TEXTURE_WRAP_S,
+        glow::CLAMP_TO_EDGE as i32,
+    );
+    gl.tex_parameter_i32(
+        glow::TEXTURE_2D,
+        glow::TEXTURE_WRAP_T,
+        glow::CLAMP_TO_EDGE as i32,
+    );
+    gl_state::bind_texture(gl, glow::TEXTURE_2D, None);
+    Ok(texture)
+}
@@ -0,0 +1,43 @@
+use cgmath::{InnerSpace, Matrix, Matrix4, Vector4};
+
+use crate::Aabb;
+
+/// Planes bounding the volume seen through a projection, pointing inwards.
+pub struct Frustum {
+    // Left, right, bottom, top, near and far planes as (normal, distance)
+    planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    /// Extract planes in the space the projection-view matrix transforms from, e.g. world space
+    /// (Gribb-Hartmann method).
+    pub fn from_matrix(projection_view: &Matrix4<f32>) -> Self {
+        let row = |i| projection_view.row(i);
+        let planes = [
+            row(3) + row(0),
+            row(3) - row(0),
+            row(3) + row(1),
+            row(3) - row(1),
+            row(3) + row(2),
+            row(3) - row(2),
+        ]
+        .map(|plane| plane / plane.truncate().magnitude());
+        Self { planes }
+    }
+
+    /// Box is considered visible unless it is completely outside one of the planes. Boxes near
+    /// frustum corners can be accepted without being visible.
+    pub fn intersects(&self, aabb: &Aabb) -> bool {
+        // Coordinate of the corner farthest along the plane normal
+        let farthest = |normal: f32, min: f32, max: f32| if normal >= 0.0 { max } else { min };
+        self.planes.iter().all(|plane| {
+            let corner = Vector4::new(
+                farthest(plane.x, aabb.min.x, aabb.max.x),
+                farthest(plane.y, aabb.min.y, aabb.max.y),
+                farthest(plane.z, aabb.min.z, aabb.max.z),
+                1.0,
+            );
+            plane.dot(corner) >= 0.0
+        })
+    }
+}
@@ -0,0 +1,136 @@
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, Vector3};
+
+use crate::{
+    line::{self, LineVertex},
+    PointLight, Ray,
+};
+
+/// Distance of light gizmo from world origin. Dragging the gizmo moves it on the surface of a
+/// sphere with this radius.
+pub const LIGHT_GIZMO_DISTANCE: f32 = 2.0;
+/// Distance in logical pixels from the gizmo on screen within which mouse press grabs it.
+const GRAB_RADIUS: f32 = 16.0;
+const SUN_RADIUS: f32 = 0.15;
+const ARROW_LENGTH: f32 = 0.6;
+const SUN_COLOR: [f32; 3] = [1.0, 0.85, 0.2];
+const POINT_LIGHT_RADIUS: f32 = 0.1;
+
+/// Lines of a sun with an arrow pointing towards light direction. Returns no lines for
+/// zero-length direction.
+pub fn light_gizmo_lines(light_direction: [f32; 3]) -> Vec<LineVertex> {
+    let Some(sun_position) = light_gizmo_position(light_direction) else {
+        return Vec::new();
+    };
+    let direction = Vector3::from(light_direction).normalize();
+    build_light_gizmo(sun_position, direction)
+}
+
+/// Lines of a small sphere at the position of a point light in its color.
+pub fn point_light_gizmo_lines(point_light: &PointLight) -> Vec<LineVertex> {
+    line::sphere_lines(
+        Point3::from_vec(point_light.position),
+        POINT_LIGHT_RADIUS,
+        point_light.color,
+    )
+}
+
+/// World space position of light gizmo, the light shines from the opposite of its direction.
+/// Returns `None` for zero-length direction.
+pub fn light_gizmo_position(light_direction: [f32; 3]) -> Option<Point3<f32>> {
+    let direction = Vector3::from(light_direction);
+    if direction.magnitude2() < f32::EPSILON {
+        return None;
+    }
+    Some(Point3::origin() - direction.normalize() * LIGHT_GIZMO_DISTANCE)
+}
+
+/// Check if cursor position in physical pixels is close enough to the light gizmo on screen to
+/// start dragging it. Grab radius is scaled by the scale factor of the display.
+pub fn hit_test_light_gizmo(
+    light_direction: [f32; 3],
+    projection_view: &Matrix4<f32>,
+    viewport_size: (f32, f32),
+    cursor: (f32, f32),
+    scale_factor: f32,
+) -> bool {
+    let Some(sun_position) = light_gizmo_position(light_direction) else {
+        return false;
+    };
+    let clip = projection_view * sun_position.to_homogeneous();
+    // Behind the camera
+    if clip.w <= 0.0 {
+        return false;
+    }
+    let (width, height) = viewport_size;
+    let screen_x = (clip.x / clip.w * 0.5 + 0.5) * width;
+    let screen_y = (0.5 - clip.y / clip.w * 0.5) * height;
+    let (dx, dy) = (cursor.0 - screen_x, cursor.1 - screen_y);
+    let grab_radius = GRAB_RADIUS * scale_factor;
+    dx * dx + dy * dy <= grab_radius * grab_radius
+}
+
+/// Calculate light direction placing the light gizmo under cursor, by intersecting the ray cast
+/// from camera through the cursor with the sphere the gizmo moves on.
+pub fn light_direction_at_cursor(
+    projection_view: &Matrix4<f32>,
+    viewport_size: (f32, f32),
+    cursor: (f32, f32),
+) -> Option<[f32; 3]> {
+    let ray = Ray::through_cursor(projection_view, viewport_size, cursor)?;
+
+    // Solve |origin + t * direction| = radius
+    let origin = ray.origin.to_vec();
+    let b = origin.dot(ray.direction);
+    let c = origin.magnitude2() - LIGHT_GIZMO_DISTANCE * LIGHT_GIZMO_DISTANCE;
+    let discriminant = b * b - c;
+    let point_on_sphere = if discriminant < 0.0 {
+        // Ray misses the sphere, use the closest point of the ray projected onto the sphere
+        origin - ray.direction * b
+    } else {
+        // Prefer the near side, far side when camera is inside the sphere
+        let near_t = -b - discriminant.sqrt();
+        let t = if 0.0 <= near_t {
+            near_t
+        } else {
+            -b + discriminant.sqrt()
+        };
+        origin + ray.direction * t
+    };
+    if point_on_sphere.magnitude2() < f32::EPSILON {
+        return None;
+    }
+
+    let light_direction = -point_on_sphere.normalize();
+    Some([light_direction.x, light_direction.y, light_direction.z])
+}
+
+/// Lines of a wireframe sphere and an arrow pointing from it towards the light direction.
+fn build_light_gizmo(sun_position: Point3<f32>, direction: Vector3<f32>) -> Vec<LineVertex> {
+    let vertex = |position: Point3<f32>| LineVertex {
+        position: position.into(),
+        color: SUN_COLOR,
+    };
+    let mut vertices = line::sphere_lines(sun_position, SUN_RADIUS, SUN_COLOR);
+
+    // Arrowhead wings need a vector perpendicular to the arrow
+    let helper = if direction.y.abs() < 0.99 {
+        Vector3::unit_y()
+    } else {
+        Vector3::unit_x()
+    };
+    let side = direction.cross(helper).normalize();
+    let arrow_start = sun_position + direction * SUN_RADIUS;
+    let arrow_tip = arrow_start + direction * ARROW_LENGTH;
+    let wing_base = arrow_tip - direction * (ARROW_LENGTH * 0.25);
+    let wing_size = ARROW_LENGTH * 0.12;
+    vertices.extend([
+        vertex(arrow_start),
+        vertex(arrow_tip),
+        vertex(arrow_tip),
+        vertex(wing_base + side * wing_size),
+        vertex(arrow_tip),
+        vertex(wing_base - side * wing_size),
+    ]);
+
+    vertices
+}
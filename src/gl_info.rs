@@ -0,0 +1,81 @@
+use glow::HasContext;
+
+/// Capabilities of the OpenGL implementation, queried once on renderer creation. Useful for bug
+/// reports.
+#[derive(Clone)]
+pub struct GlInfo {
+    pub vendor: String,
+    pub renderer: String,
+    pub version: String,
+    /// Major and minor version of the created context.
+    pub version_number: (u32, u32),
+    /// OpenGL ES context (including WebGL2) instead of desktop OpenGL.
+    pub embedded: bool,
+    pub shading_language_version: String,
+    pub max_texture_size: i32,
+    pub max_samples: i32,
+    /// Supported extensions in alphabetical order.
+    pub extensions: Vec<String>,
+}
+
+impl GlInfo {
+    pub fn query(gl: &glow::Context) -> Self {
+        unsafe {
+            let mut extensions: Vec<String> = gl.supported_extensions().iter().cloned().collect();
+            extensions.sort();
+            let version = gl.version();
+
+            Self {
+                vendor: gl.get_parameter_string(glow::VENDOR),
+                renderer: gl.get_parameter_string(glow::RENDERER),
+                version: gl.get_parameter_string(glow::VERSION),
+                version_number: (version.major, version.minor),
+                embedded: version.is_embedded,
+                shading_language_version: gl.get_parameter_string(glow::SHADING_LANGUAGE_VERSION),
+                max_texture_size: gl.get_parameter_i32(glow::MAX_TEXTURE_SIZE),
+                max_samples: gl.get_parameter_i32(glow::MAX_SAMPLES),
+                extensions,
+            }
+        }
+    }
+
+    /// Desktop OpenGL 4.3 features are available, like compute shaders.
+    pub fn supports_gl43(&self) -> bool {
+        !self.embedded && self.version_number >= (4, 3)
+    }
+
+    /// Vertex and fragment counts can be queried, core since OpenGL 4.6.
+    pub fn supports_pipeline_statistics(&self) -> bool {
+        !self.embedded
+            && (self.version_number >= (4, 6)
+                || self
+                    .extensions
+                    .iter()
+                    .any(|extension| extension == "GL_ARB_pipeline_statistics_query"))
+    }
+
+    /// Plain text listing of every queried property, one per line.
+    pub fn report(&self) -> String {
+        let mut report = format!(
+            "Vendor: {}\n\
+             Renderer: {}\n\
+             Version: {}\n\
+             GLSL version: {}\n\
+             Max texture size: {}\n\
+             Max samples: {}\n\
+             Extensions ({}):\n",
+            self.vendor,
+            self.renderer,
+            self.version,
+            self.shading_language_version,
+            self.max_texture_size,
+            self.max_samples,
+            self.extensions.len()
+        );
+        for extension in &self.extensions {
+            report.push_str(extension);
+            report.push('\n');
+        }
+        report
+    }
+}
@@ -0,0 +1,281 @@
+use std::{cell::RefCell, collections::HashMap, hash::Hash};
+
+use glow::{Buffer, Framebuffer, HasContext, Renderbuffer, Texture, VertexArray};
+
+use crate::gl_state;
+
+/// Number of frames between samples of live object counts for leak detection.
+const LEAK_SAMPLE_INTERVAL_FRAMES: u32 = 60;
+/// Number of consecutive samples with growing object count after which a leak is suspected.
+/// One-off allocations (e.g. lazily created render targets) only grow the count once.
+const LEAK_SAMPLE_STREAK: u32 = 5;
+
+/// Category of tracked OpenGL objects.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Buffer = 0,
+    Texture = 1,
+    Renderbuffer = 2,
+    VertexArray = 3,
+    Framebuffer = 4,
+}
+
+impl ResourceKind {
+    pub const ALL: [ResourceKind; 5] = [
+        ResourceKind::Buffer,
+        ResourceKind::Texture,
+        ResourceKind::Renderbuffer,
+        ResourceKind::VertexArray,
+        ResourceKind::Framebuffer,
+    ];
+
+    /// Message identifier of the translated label.
+    pub fn l10n_id(self) -> &'static str {
+        match self {
+            ResourceKind::Buffer => "resource-buffers",
+            ResourceKind::Texture => "resource-textures",
+            ResourceKind::Renderbuffer => "resource-renderbuffers",
+            ResourceKind::VertexArray => "resource-vertex-arrays",
+            ResourceKind::Framebuffer => "resource-framebuffers",
+        }
+    }
+}
+
+/// Usage statistics of a resource category.
+#[derive(Default, Clone, Copy)]
+pub struct ResourceStats {
+    /// Number of objects currently alive.
+    pub live_count: usize,
+    /// Estimated GPU memory size of storage allocated for live objects in bytes.
+    pub bytes: usize,
+    pub created_count: usize,
+    pub deleted_count: usize,
+    /// Live object count kept growing over the last few seconds.
+    pub leak_suspected: bool,
+}
+
+/// Live objects of a single category with their storage sizes.
+struct TrackedObjects<K> {
+    sizes: HashMap<K, usize>,
+    created_count: usize,
+    deleted_count: usize,
+}
+
+impl<K: Hash + Eq> TrackedObjects<K> {
+    fn new() -> Self {
+        Self {
+            sizes: HashMap::new(),
+            created_count: 0,
+            deleted_count: 0,
+        }
+    }
+
+    fn insert(&mut self, object: K) {
+        self.sizes.insert(object, 0);
+        self.created_count += 1;
+    }
+
+    fn remove(&mut self, object: &K) {
+        if self.sizes.remove(object).is_some() {
+            self.deleted_count += 1;
+        }
+    }
+
+    fn set_size(&mut self, object: K, bytes: usize) {
+        if let Some(size) = self.sizes.get_mut(&object) {
+            *size = bytes;
+        }
+    }
+
+    fn stats(&self) -> ResourceStats {
+        ResourceStats {
+            live_count: self.sizes.len(),
+            bytes: self.sizes.values().sum(),
+            created_count: self.created_count,
+            deleted_count: self.deleted_count,
+            leak_suspected: false,
+        }
+    }
+}
+
+struct Registry {
+    buffers: TrackedObjects<Buffer>,
+    textures: TrackedObjects<Texture>,
+    renderbuffers: TrackedObjects<Renderbuffer>,
+    vertex_arrays: TrackedObjects<VertexArray>,
+    framebuffers: TrackedObjects<Framebuffer>,
+    frame_count: u32,
+    // Indexed by ResourceKind
+    sampled_live_counts: [usize; 5],
+    growth_streaks: [u32; 5],
+}
+
+impl Registry {
+    fn new() -> Self {
+        Self {
+            buffers: TrackedObjects::new(),
+            textures: TrackedObjects::new(),
+            renderbuffers: TrackedObjects::new(),
+            vertex_arrays: TrackedObjects::new(),
+            framebuffers: TrackedObjects::new(),
+            frame_count: 0,
+            sampled_live_counts: [0; 5],
+            growth_streaks: [0; 5],
+        }
+    }
+
+    fn stats(&self, kind: ResourceKind) -> ResourceStats {
+        let mut stats = match kind {
+            ResourceKind::Buffer => self.buffers.stats(),
+            ResourceKind::Texture => self.textures.stats(),
+            ResourceKind::Renderbuffer => self.renderbuffers.stats(),
+            ResourceKind::VertexArray => self.vertex_arrays.stats(),
+            ResourceKind::Framebuffer => self.framebuffers.stats(),
+        };
+        stats.leak_suspected = LEAK_SAMPLE_STREAK <= self.growth_streaks[kind as usize];
+        stats
+    }
+}
+
+// OpenGL contexts are bound to a single thread, so is the registry of their objects
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::new());
+}
+
+fn with_registry<R>(f: impl FnOnce(&mut Registry) -> R) -> R {
+    REGISTRY.with(|registry| f(&mut registry.borrow_mut()))
+}
+
+/// Statistics of each resource category in the order of `ResourceKind::ALL`.
+///
+/// Only objects created through this module are tracked, e.g. objects of the GUI library are not.
+pub fn stats() -> [(ResourceKind, ResourceStats); 5] {
+    with_registry(|registry| ResourceKind::ALL.map(|kind| (kind, registry.stats(kind))))
+}
+
+/// Advance frame counter of leak detection. Call once per rendered frame.
+pub fn end_frame() {
+    with_registry(|registry| {
+        registry.frame_count += 1;
+        if registry.frame_count < LEAK_SAMPLE_INTERVAL_FRAMES {
+            return;
+        }
+        registry.frame_count = 0;
+
+        for kind in ResourceKind::ALL {
+            let live_count = registry.stats(kind).live_count;
+            let i = kind as usize;
+            if registry.sampled_live_counts[i] < live_count {
+                registry.growth_streaks[i] += 1;
+            } else {
+                registry.growth_streaks[i] = 0;
+            }
+            registry.sampled_live_counts[i] = live_count;
+        }
+    });
+}
+
+pub unsafe fn create_buffer(gl: &glow::Context) -> Result<Buffer, String> {
+    let buffer = gl.create_buffer()?;
+    with_registry(|registry| registry.buffers.insert(buffer));
+    Ok(buffer)
+}
+
+pub unsafe fn delete_buffer(gl: &glow::Context, buffer: Buffer) {
+    gl.delete_buffer(buffer);
+    with_registry(|registry| registry.buffers.remove(&buffer));
+}
+
+/// Upload data into buffer bound to target and record its size.
+pub unsafe fn buffer_data_u8_slice(
+    gl: &glow::Context,
+    buffer: Buffer,
+    target: u32,
+    data: &[u8],
+    usage: u32,
+) {
+    gl.buffer_data_u8_slice(target, data, usage);
+    with_registry(|registry| registry.buffers.set_size(buffer, data.len()));
+}
+
+/// Allocate uninitialized storage for buffer bound to target and record its size.
+pub unsafe fn buffer_data_size(
+    gl: &glow::Context,
+    buffer: Buffer,
+    target: u32,
+    size: i32,
+    usage: u32,
+) {
+    gl.buffer_data_size(target, size, usage);
+    with_registry(|registry| registry.buffers.set_size(buffer, size as usize));
+}
+
+/// Allocate immutable storage for buffer bound to target and record its size.
+#[cfg(not(target_arch = "wasm32"))]
+pub unsafe fn buffer_storage(
+    gl: &glow::Context,
+    buffer: Buffer,
+    target: u32,
+    size: i32,
+    flags: u32,
+) {
+    gl.buffer_storage(target, size, None, flags);
+    with_registry(|registry| registry.buffers.set_size(buffer, size as usize));
+}
+
+pub unsafe fn create_texture(gl: &glow::Context) -> Result<Texture, String> {
+    let texture = gl.create_texture()?;
+    with_registry(|registry| registry.textures.insert(texture));
+    Ok(texture)
+}
+
+pub unsafe fn delete_texture(gl: &glow::Context, texture: Texture) {
+    gl.delete_texture(texture);
+    gl_state::forget_texture(texture);
+    with_registry(|registry| registry.textures.remove(&texture));
+}
+
+/// Record estimated size of texture storage after allocating it, including every mip level and
+/// cube-map face.
+pub fn set_texture_size(texture: Texture, bytes: usize) {
+    with_registry(|registry| registry.textures.set_size(texture, bytes));
+}
+
+pub unsafe fn create_renderbuffer(gl: &glow::Context) -> Result<Renderbuffer, String> {
+    let renderbuffer = gl.create_renderbuffer()?;
+    with_registry(|registry| registry.renderbuffers.insert(renderbuffer));
+    Ok(renderbuffer)
+}
+
+pub unsafe fn delete_renderbuffer(gl: &glow::Context, renderbuffer: Renderbuffer) {
+    gl.delete_renderbuffer(renderbuffer);
+    with_registry(|registry| registry.renderbuffers.remove(&renderbuffer));
+}
+
+/// Record estimated size of renderbuffer storage after allocating it, including every sample.
+pub fn set_renderbuffer_size(renderbuffer: Renderbuffer, bytes: usize) {
+    with_registry(|registry| registry.renderbuffers.set_size(renderbuffer, bytes));
+}
+
+pub unsafe fn create_vertex_array(gl: &glow::Context) -> Result<VertexArray, String> {
+    let vertex_array = gl.create_vertex_array()?;
+    with_registry(|registry| registry.vertex_arrays.insert(vertex_array));
+    Ok(vertex_array)
+}
+
+pub unsafe fn delete_vertex_array(gl: &glow::Context, vertex_array: VertexArray) {
+    gl.delete_vertex_array(vertex_array);
+    gl_state::forget_vertex_array(vertex_array);
+    with_registry(|registry| registry.vertex_arrays.remove(&vertex_array));
+}
+
+pub unsafe fn create_framebuffer(gl: &glow::Context) -> Result<Framebuffer, String> {
+    let framebuffer = gl.create_framebuffer()?;
+    with_registry(|registry| registry.framebuffers.insert(framebuffer));
+    Ok(framebuffer)
+}
+
+pub unsafe fn delete_framebuffer(gl: &glow::Context, framebuffer: Framebuffer) {
+    gl.delete_framebuffer(framebuffer);
+    with_registry(|registry| registry.framebuffers.remove(&framebuffer));
+}
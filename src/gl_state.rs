@@ -0,0 +1,174 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use glow::{HasContext, Program, Texture, VertexArray};
+
+/// Number of state changes issued and skipped during the last frame.
+#[derive(Clone, Copy, Default)]
+pub struct StateChangeStats {
+    /// Calls passed to OpenGL.
+    pub change_count: usize,
+    /// Calls skipped, because they would set the state already in effect.
+    pub redundant_count: usize,
+}
+
+/// Last known values of frequently changed OpenGL state. `None` is unknown, e.g. after foreign
+/// code like the GUI library or render hooks had the context.
+#[derive(Default)]
+struct StateCache {
+    program: Option<Option<Program>>,
+    vertex_array: Option<Option<VertexArray>>,
+    active_texture: Option<u32>,
+    // Keyed by texture unit and target
+    textures: HashMap<(u32, u32), Option<Texture>>,
+    // Keyed by capability, e.g. GL_DEPTH_TEST
+    capabilities: HashMap<u32, bool>,
+    frame_stats: StateChangeStats,
+    last_frame_stats: StateChangeStats,
+}
+
+impl StateCache {
+    /// Record the new value and tell if it differs from the known one.
+    fn update<T: PartialEq>(
+        stats: &mut StateChangeStats,
+        current: &mut Option<T>,
+        value: T,
+    ) -> bool {
+        if current.as_ref() == Some(&value) {
+            stats.redundant_count += 1;
+            false
+        } else {
+            *current = Some(value);
+            stats.change_count += 1;
+            true
+        }
+    }
+}
+
+// Like the objects, state belongs to the context bound to the current thread
+thread_local! {
+    static CACHE: RefCell<StateCache> = RefCell::new(StateCache::default());
+}
+
+fn with_cache<R>(f: impl FnOnce(&mut StateCache) -> R) -> R {
+    CACHE.with(|cache| f(&mut cache.borrow_mut()))
+}
+
+pub unsafe fn use_program(gl: &glow::Context, program: Option<Program>) {
+    let changed =
+        with_cache(|cache| StateCache::update(&mut cache.frame_stats, &mut cache.program, program));
+    if changed {
+        gl.use_program(program);
+    }
+}
+
+pub unsafe fn bind_vertex_array(gl: &glow::Context, vertex_array: Option<VertexArray>) {
+    let changed = with_cache(|cache| {
+        StateCache::update(
+            &mut cache.frame_stats,
+            &mut cache.vertex_array,
+            vertex_array,
+        )
+    });
+    if changed {
+        gl.bind_vertex_array(vertex_array);
+    }
+}
+
+pub unsafe fn active_texture(gl: &glow::Context, unit: u32) {
+    let changed = with_cache(|cache| {
+        StateCache::update(&mut cache.frame_stats, &mut cache.active_texture, unit)
+    });
+    if changed {
+        gl.active_texture(unit);
+    }
+}
+
+/// Bind texture to the target of the active texture unit.
+pub unsafe fn bind_texture(gl: &glow::Context, target: u32, texture: Option<Texture>) {
+    let changed = with_cache(|cache| {
+        // Texture bindings are only known while the active unit is
+        let Some(unit) = cache.active_texture else {
+            cache.frame_stats.change_count += 1;
+            return true;
+        };
+        let mut current = cache.textures.get(&(unit, target)).copied();
+        let changed = StateCache::update(&mut cache.frame_stats, &mut current, texture);
+        cache.textures.insert((unit, target), texture);
+        changed
+    });
+    if changed {
+        gl.bind_texture(target, texture);
+    }
+}
+
+pub unsafe fn enable(gl: &glow::Context, capability: u32) {
+    if set_capability(capability, true) {
+        gl.enable(capability);
+    }
+}
+
+pub unsafe fn disable(gl: &glow::Context, capability: u32) {
+    if set_capability(capability, false) {
+        gl.disable(capability);
+    }
+}
+
+fn set_capability(capability: u32, enabled: bool) -> bool {
+    with_cache(|cache| {
+        let mut current = cache.capabilities.get(&capability).copied();
+        let changed = StateCache::update(&mut cache.frame_stats, &mut current, enabled);
+        cache.capabilities.insert(capability, enabled);
+        changed
+    })
+}
+
+/// Forget every known value, e.g. after code not using this module changed the state. The next
+/// call of each kind is passed to OpenGL.
+pub fn invalidate() {
+    with_cache(|cache| {
+        cache.program = None;
+        cache.vertex_array = None;
+        cache.active_texture = None;
+        cache.textures.clear();
+        cache.capabilities.clear();
+    });
+}
+
+/// Deleted objects are unbound by OpenGL, and their names can be reused by new objects.
+pub fn forget_program(program: Program) {
+    with_cache(|cache| {
+        if cache.program == Some(Some(program)) {
+            cache.program = None;
+        }
+    });
+}
+
+pub fn forget_vertex_array(vertex_array: VertexArray) {
+    with_cache(|cache| {
+        if cache.vertex_array == Some(Some(vertex_array)) {
+            cache.vertex_array = None;
+        }
+    });
+}
+
+pub fn forget_texture(texture: Texture) {
+    with_cache(|cache| {
+        cache
+            .textures
+            .retain(|_, bound_texture| *bound_texture != Some(texture))
+    });
+}
+
+/// State changes of the last finished frame.
+pub fn stats() -> StateChangeStats {
+    with_cache(|cache| cache.last_frame_stats)
+}
+
+/// Publish counters of the frame and invalidate the cache, because the GUI is drawn with its own
+/// state handling. Call once per rendered frame.
+pub fn end_frame() {
+    with_cache(|cache| {
+        cache.last_frame_stats = std::mem::take(&mut cache.frame_stats);
+    });
+    invalidate();
+}
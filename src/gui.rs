@@ -1,28 +1,217 @@
 use std::sync::Arc;
 
+use cfg_if::cfg_if;
 use egui::Shadow;
+use egui_dock::{DockArea, DockState, NodeIndex, Style, TabViewer};
 use egui_glow::EguiGlow;
-use winit::{event::WindowEvent, event_loop::ActiveEventLoop};
+use fluent::fluent_args;
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+use winit::{
+    event::WindowEvent,
+    event_loop::ActiveEventLoop,
+    window::{Fullscreen, Window},
+};
 
-use crate::{Camera, DrawProperties};
+#[cfg(feature = "profiling")]
+use crate::profiler::ProfilerWindow;
+use crate::{
+    gl_resources, gl_state, localization::Localization, profiler::profile_scope, AnimatedProperty,
+    AntiAliasing, AreaLight, AssetCache, CameraBookmark, CameraMode, CameraTransition, Comparison,
+    DrawProperties, Easing, GlInfo, Language, Material, MaterialType, ModulationBinding,
+    ModulationTarget, Name, PartOverrides, PointLight, PostEffect, RenderPath, RenderStats,
+    Renderable, Renderer, Scene, StereoMode, Transform, VertexColorMode, Visible, Winding,
+    MAX_AREA_LIGHTS, MAX_POINT_LIGHTS, MAX_SHADOW_CASCADES,
+};
 #[cfg(not(target_arch = "wasm32"))]
-use crate::FrameRateInfo;
+use crate::{FrameRateInfo, InspectedTriangle, MeshRepair, UvLayout, VertexAttribute, VsyncMode};
+
+/// GUI settings are stored next to the executable's working directory, like material files.
+#[cfg(not(target_arch = "wasm32"))]
+const CONFIG_PATH: &str = "config.ron";
+
+/// Request from the menu bar that has to be carried out by the application, because it changes
+/// assets of the scene or needs the rendered frame.
+#[cfg(not(target_arch = "wasm32"))]
+pub enum GuiAction {
+    /// Add OBJ model from file path to the scene and select it.
+    OpenModel(String),
+    /// Replace skybox with the faces found in a directory, named after the cube faces (e.g.
+    /// `right.jpg`, `left.jpg`).
+    OpenSkybox(String),
+    /// Replace terrain with one generated from a grayscale PNG or EXR heightmap.
+    OpenTerrain(String),
+    /// Project an image file onto the visible models from above.
+    AddDecal(String),
+    /// Save models, entities, camera and light of the scene into a RON file.
+    SaveScene(String),
+    /// Write mesh of the selected model at the level of detail into an OBJ or binary glTF file.
+    ExportModel(String, usize),
+    /// Save the next rendered frame without GUI into an image file.
+    Screenshot,
+    /// Bake lighting of visible entities into lightmaps with the settings of `DrawProperties`.
+    BakeLighting,
+    /// Save playback of the timeline into numbered PNG images of a directory, at the frame rate.
+    ExportAnimation(String, f32),
+    /// Fix the mesh of the selected model and upload it again.
+    RepairMesh(MeshRepair),
+}
 
 /// Immediate GUI displayed as an overlay on top of rendered 3D scene. Available for both native and
 /// web builds.
+///
+/// Properties are grouped into panels of a dock on the left side, which can be rearranged by
+/// dragging their tabs. Native builds keep the arrangement in the config file between runs. Common
+/// actions and windows are reachable from the menu bar on top. Strings are translated to the
+/// language selected in `DrawProperties`.
 pub struct Gui {
     egui_glow: EguiGlow,
+    gl_info: GlInfo,
+    config: GuiConfig,
+    localization: Localization,
+    // Target of transform and material editing, selected in the scene hierarchy
+    active_entity: Option<Entity>,
+    timeline_editor: TimelineEditor,
+    open_windows: OpenWindows,
+    #[cfg(feature = "profiling")]
+    profiler_window: ProfilerWindow,
+    // Created with the template sources of the renderer when first opened
+    shader_editor: Option<ShaderEditor>,
+    // Created with the model shader source of the renderer when first opened
+    model_shader_playground: Option<ModelShaderPlayground>,
+    // Displayed in a modal window until dismissed
+    errors: Vec<String>,
+    // GUI scale given by the application is not saved into config
+    ui_scale_overridden: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    quit_requested: bool,
+    // File path of material editor save and load
+    #[cfg(not(target_arch = "wasm32"))]
+    material_path: String,
+    // File operation waiting for path input
+    #[cfg(not(target_arch = "wasm32"))]
+    path_prompt: Option<PathPrompt>,
+    // Parameters of repair operations in the Mesh menu
+    #[cfg(not(target_arch = "wasm32"))]
+    weld_distance: f32,
+    #[cfg(not(target_arch = "wasm32"))]
+    max_hole_edges: usize,
+    // Fraction of the lighting bake running in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    bake_progress: Option<f32>,
+    #[cfg(not(target_arch = "wasm32"))]
+    actions: Vec<GuiAction>,
 }
 
 impl Gui {
-    pub fn new(event_loop: &ActiveEventLoop, gl: Arc<glow::Context>) -> Self {
-        let egui_glow = EguiGlow::new(&event_loop, gl.clone(), None, None, true);
+    /// GUI follows the scale factor of the display the window is on, multiplied by the GUI scale
+    /// adjustable in the interface settings.
+    pub fn new(
+        event_loop: &ActiveEventLoop,
+        window: &Window,
+        gl: Arc<glow::Context>,
+        gl_info: GlInfo,
+    ) -> Self {
+        let native_pixels_per_point = window.scale_factor() as f32;
+        let egui_glow = EguiGlow::new(
+            &event_loop,
+            gl.clone(),
+            None,
+            Some(native_pixels_per_point),
+            true,
+        );
 
-        egui_glow.egui_ctx.style_mut(|style| {
-            style.visuals.window_shadow = Shadow::NONE;
-        });
+        let mut errors = Vec::new();
+        cfg_if! {
+            if #[cfg(not(target_arch = "wasm32"))] {
+                let config = GuiConfig::load(CONFIG_PATH).unwrap_or_else(|e| {
+                    errors.push(e);
+                    GuiConfig::default()
+                });
+            } else {
+                // Web: There's no config file, panels are arranged by default on every page load
+                let config = GuiConfig::default();
+            }
+        }
+        egui_glow.egui_ctx.set_zoom_factor(config.zoom_factor);
+        egui_glow.egui_ctx.set_visuals(config.theme.visuals());
+
+        Self {
+            egui_glow,
+            gl_info,
+            config,
+            localization: Localization::new(Language::English),
+            active_entity: None,
+            timeline_editor: TimelineEditor::default(),
+            open_windows: OpenWindows::default(),
+            #[cfg(feature = "profiling")]
+            profiler_window: ProfilerWindow::new(),
+            shader_editor: None,
+            model_shader_playground: None,
+            errors,
+            ui_scale_overridden: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            quit_requested: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            material_path: "material.ron".to_string(),
+            #[cfg(not(target_arch = "wasm32"))]
+            path_prompt: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            weld_distance: 0.0001,
+            #[cfg(not(target_arch = "wasm32"))]
+            max_hole_edges: 32,
+            #[cfg(not(target_arch = "wasm32"))]
+            bake_progress: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            actions: Vec::new(),
+        }
+    }
+
+    /// Use a fixed GUI scale instead of the one remembered in the config file, e.g. when the
+    /// display reports a wrong scale factor. Changes of the scale are not remembered either.
+    pub fn override_ui_scale(&mut self, ui_scale: f32) {
+        self.egui_glow.egui_ctx.set_zoom_factor(ui_scale);
+        self.ui_scale_overridden = true;
+    }
 
-        Self { egui_glow }
+    /// Entity edited in the material panel and highlighted in the hierarchy, e.g. picked in the
+    /// viewport. `None` falls back to the selected model.
+    pub fn set_active_entity(&mut self, entity: Option<Entity>) {
+        self.active_entity = entity;
+    }
+
+    /// Progress of baking lighting from 0 to 1 shown in the baked lighting panel, `None` when not
+    /// baking.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_bake_progress(&mut self, progress: Option<f32>) {
+        self.bake_progress = progress;
+    }
+
+    /// Show errors in a modal window until dismissed.
+    pub fn report_errors(&mut self, errors: Vec<String>) {
+        self.errors.extend(errors);
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// User chose to quit the application from the menu or the error window.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn quit_requested(&self) -> bool {
+        self.quit_requested
+    }
+
+    /// Menu actions requested since the last call, in the order they were chosen.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn take_actions(&mut self) -> Vec<GuiAction> {
+        std::mem::take(&mut self.actions)
+    }
+
+    /// Check if pointer is over a GUI window or a widget is being dragged, in which case the
+    /// pointer input is not meant for the scene.
+    pub fn wants_pointer_input(&self) -> bool {
+        self.egui_glow.egui_ctx.wants_pointer_input()
     }
 
     pub fn handle_events(&mut self, window: &winit::window::Window, event: &WindowEvent) {
@@ -31,173 +220,3077 @@ impl Gui {
 
     pub fn prepare_frame(
         &mut self,
-        window: &winit::window::Window,
+        window: &Window,
         #[cfg(not(target_arch = "wasm32"))] frame_rate_info: &FrameRateInfo,
-        camera: &Camera,
         draw_props: &mut DrawProperties,
+        scene: &mut Scene,
+        asset_cache: &mut AssetCache,
+        renderer: &mut Renderer,
     ) {
+        profile_scope!("Gui::prepare_frame");
+        // Language is also selected from HTML controls on web
+        if self.localization.language() != draw_props.language {
+            self.localization = Localization::new(draw_props.language);
+        }
+
+        let gl_info = &self.gl_info;
+        let l10n = &self.localization;
+        let layout = &mut self.config.layout;
+        let theme = &mut self.config.theme;
+        let open_windows = &mut self.open_windows;
+        let active_entity = &mut self.active_entity;
+        let timeline_editor = &mut self.timeline_editor;
+        #[cfg(feature = "profiling")]
+        let profiler_window = &mut self.profiler_window;
+        let shader_editor = &mut self.shader_editor;
+        let model_shader_playground = &mut self.model_shader_playground;
+        let errors = &mut self.errors;
+        #[cfg(not(target_arch = "wasm32"))]
+        let quit_requested = &mut self.quit_requested;
+        #[cfg(not(target_arch = "wasm32"))]
+        let material_path = &mut self.material_path;
+        #[cfg(not(target_arch = "wasm32"))]
+        let path_prompt = &mut self.path_prompt;
+        #[cfg(not(target_arch = "wasm32"))]
+        let weld_distance = &mut self.weld_distance;
+        #[cfg(not(target_arch = "wasm32"))]
+        let max_hole_edges = &mut self.max_hole_edges;
+        #[cfg(not(target_arch = "wasm32"))]
+        let bake_progress = self.bake_progress;
+        #[cfg(not(target_arch = "wasm32"))]
+        let actions = &mut self.actions;
         self.egui_glow.run(&window, |egui_ctx| {
-            egui::Window::new("Properties")
-                .default_pos([20.0, 20.0])
-                .default_size([280.0, 600.])
-                .default_open(true)
-                .show(egui_ctx, |ui| {
-                    // Help
-                    egui::CollapsingHeader::new("Help")
-                        .default_open(true)
-                        .show(ui, |ui| {
-                            ui.label("• Movement: W, A, S, D");
-                            ui.label("• Mouse look: Right-click and drag");
-                            ui.label("• Ascend: Spacebar");
-                            ui.label("• Descend: C");
-                            #[cfg(not(target_arch = "wasm32"))]
-                            {
-                                ui.label("• Quit: Esc");
+            show_errors(
+                egui_ctx,
+                l10n,
+                errors,
+                #[cfg(not(target_arch = "wasm32"))]
+                quit_requested,
+                true,
+            );
+
+            // Keyboard shortcuts listed in menus
+            if egui_ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F11)) {
+                toggle_fullscreen(window);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if egui_ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F12)) {
+                actions.push(GuiAction::Screenshot);
+            }
+
+            egui::TopBottomPanel::top("menu_bar").show(egui_ctx, |ui| {
+                egui::menu::bar(ui, |ui| {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    ui.menu_button(l10n.tr("menu-file"), |ui| {
+                        for kind in PathPromptKind::ALL {
+                            if ui.button(l10n.tr(kind.menu_l10n_id())).clicked() {
+                                *path_prompt = Some(PathPrompt::new(kind));
+                                ui.close_menu();
                             }
-                        });
+                        }
+                        ui.separator();
+                        if ui
+                            .add(egui::Button::new(l10n.tr("menu-screenshot")).shortcut_text("F12"))
+                            .clicked()
+                        {
+                            actions.push(GuiAction::Screenshot);
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui
+                            .add(egui::Button::new(l10n.tr("menu-quit")).shortcut_text("Esc"))
+                            .clicked()
+                        {
+                            *quit_requested = true;
+                        }
+                    });
 
                     #[cfg(not(target_arch = "wasm32"))]
-                    egui::CollapsingHeader::new("Renderer")
-                        .default_open(true)
-                        .show(ui, |ui| {
-                            ui.label(format!(
-                                "{:.2} FPS, {:.6} ms/frame",
-                                frame_rate_info.frames_per_second, frame_rate_info.ms_per_frame
-                            ));
-                            ui.checkbox(&mut draw_props.vsync_enabled, "Vertical sync");
+                    ui.menu_button(l10n.tr("menu-mesh"), |ui| {
+                        let mut repair = None;
+                        ui.horizontal(|ui| {
+                            if ui.button(l10n.tr("menu-weld-vertices")).clicked() {
+                                repair = Some(MeshRepair::WeldVertices(*weld_distance));
+                            }
+                            ui.add(
+                                egui::DragValue::new(weld_distance)
+                                    .speed(0.0001)
+                                    .range(0.0..=1.0)
+                                    .max_decimals(4),
+                            )
+                            .on_hover_text(l10n.tr("menu-weld-distance"));
+                        });
+                        if ui.button(l10n.tr("menu-remove-degenerates")).clicked() {
+                            repair = Some(MeshRepair::RemoveDegenerateTriangles);
+                        }
+                        ui.horizontal(|ui| {
+                            if ui.button(l10n.tr("menu-fill-holes")).clicked() {
+                                repair = Some(MeshRepair::FillHoles(*max_hole_edges));
+                            }
+                            ui.add(egui::DragValue::new(max_hole_edges).range(3..=1000))
+                                .on_hover_text(l10n.tr("menu-max-hole-edges"));
                         });
+                        if ui.button(l10n.tr("menu-unify-winding")).clicked() {
+                            repair = Some(MeshRepair::UnifyWinding);
+                        }
+                        if let Some(repair) = repair {
+                            actions.push(GuiAction::RepairMesh(repair));
+                            ui.close_menu();
+                        }
+                    });
 
-                    // Camera
-                    egui::CollapsingHeader::new("Camera")
-                        .default_open(true)
-                        .show(ui, |ui| {
-                            let camera_position = camera.position();
-                            ui.label(format!(
-                                "X: {:.3} Y: {:.3} Z: {:.3}",
-                                camera_position.x, camera_position.y, camera_position.z
-                            ));
+                    ui.menu_button(l10n.tr("menu-view"), |ui| {
+                        ui.checkbox(&mut open_windows.properties, l10n.tr("menu-properties"));
+                        ui.add_enabled_ui(open_windows.properties, |ui| {
+                            for panel in Panel::ALL {
+                                let tab = layout.find_tab(&panel);
+                                let mut panel_open = tab.is_some();
+                                if ui
+                                    .checkbox(&mut panel_open, l10n.tr(panel.l10n_id()))
+                                    .changed()
+                                {
+                                    match tab {
+                                        Some(tab) => {
+                                            layout.remove_tab(tab);
+                                        }
+                                        None => layout.push_to_focused_leaf(panel),
+                                    }
+                                }
+                            }
+                        });
+                        ui.separator();
+                        ui.checkbox(&mut open_windows.gl_info, l10n.tr("menu-opengl-info"));
+                        ui.checkbox(
+                            &mut open_windows.shader_editor,
+                            l10n.tr("menu-shader-editor"),
+                        );
+                        ui.checkbox(
+                            &mut open_windows.model_shader_playground,
+                            l10n.tr("menu-model-shader-playground"),
+                        );
+                        #[cfg(not(target_arch = "wasm32"))]
+                        ui.checkbox(&mut open_windows.uv_layout, l10n.tr("menu-uv-layout"));
+                        #[cfg(feature = "profiling")]
+                        ui.checkbox(&mut open_windows.profiler, l10n.tr("menu-profiler"));
+                        ui.separator();
+                        let fullscreen_label = if window.fullscreen().is_some() {
+                            l10n.tr("menu-exit-fullscreen")
+                        } else {
+                            l10n.tr("menu-fullscreen")
+                        };
+                        if ui
+                            .add(egui::Button::new(fullscreen_label).shortcut_text("F11"))
+                            .clicked()
+                        {
+                            toggle_fullscreen(window);
+                            ui.close_menu();
+                        }
+                    });
 
-                            let camera_rotation = camera.rotation();
-                            ui.label(format!(
-                                "Yaw: {:.1}° Pitch: {:.1}°",
-                                camera_rotation.x, camera_rotation.y
-                            ));
+                    ui.menu_button(l10n.tr("menu-help"), |ui| {
+                        if ui.button(l10n.tr("menu-controls")).clicked() {
+                            open_windows.controls = true;
+                            ui.close_menu();
+                        }
+                        if ui.button(l10n.tr("menu-about")).clicked() {
+                            open_windows.about = true;
+                            ui.close_menu();
+                        }
+                    });
+                });
+            });
 
-                            ui.add(
-                                egui::Slider::new(&mut draw_props.field_of_view, 45.0..=120.0)
-                                    .text("Field of view (FOV)")
-                                    .suffix("°"),
-                            );
+            egui::SidePanel::left("properties")
+                .resizable(true)
+                .default_width(300.0)
+                .show_animated(egui_ctx, open_windows.properties, |ui| {
+                    let mut panel_viewer = PanelViewer {
+                        gl_info,
+                        draw_props,
+                        scene,
+                        asset_cache,
+                        render_stats: renderer.stats(),
+                        custom_shader_names: renderer.custom_shader_names().collect(),
+                        errors,
+                        theme,
+                        l10n,
+                        active_entity,
+                        timeline_editor,
+                        #[cfg(not(target_arch = "wasm32"))]
+                        frame_rate_info,
+                        #[cfg(not(target_arch = "wasm32"))]
+                        material_path,
+                        #[cfg(not(target_arch = "wasm32"))]
+                        bake_progress,
+                        #[cfg(not(target_arch = "wasm32"))]
+                        actions,
+                    };
+                    DockArea::new(layout)
+                        .id(egui::Id::new("properties_dock"))
+                        .style(Style::from_egui(ui.style().as_ref()))
+                        .show_inside(ui, &mut panel_viewer);
+                });
 
-                            ui.checkbox(&mut draw_props.skybox_enabled, "Skybox");
-                            if !draw_props.skybox_enabled {
-                                ui.horizontal(|ui| {
-                                    ui.color_edit_button_rgb(&mut draw_props.background_color);
-                                    ui.label("Background color");
-                                });
-                            }
-                        });
+            if let Some(comparison) = &mut scene.comparison {
+                show_comparison_split(egui_ctx, comparison);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(inspected) = scene.inspector.hovered() {
+                show_inspected_triangle(egui_ctx, l10n, inspected);
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(prompt) = path_prompt {
+                let lod_count = scene.models[draw_props.selected_model_index].lod_count();
+                match prompt.show(egui_ctx, l10n, lod_count) {
+                    Some(Some(action)) => {
+                        actions.push(action);
+                        *path_prompt = None;
+                    }
+                    Some(None) => *path_prompt = None,
+                    None => (),
+                }
+            }
+
+            // Windows are identified explicitly, otherwise their state would be lost on language
+            // change
+            egui::Window::new(l10n.tr("menu-controls"))
+                .id(egui::Id::new("controls_window"))
+                .open(&mut open_windows.controls)
+                .default_pos([340.0, 40.0])
+                .resizable(false)
+                .show(egui_ctx, |ui| show_controls(ui, l10n));
+
+            egui::Window::new(l10n.tr("menu-about"))
+                .id(egui::Id::new("about_window"))
+                .open(&mut open_windows.about)
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(egui_ctx, |ui| {
+                    ui.heading(l10n.tr("about-title"));
+                    ui.label(l10n.tr_args(
+                        "about-version",
+                        &fluent_args!["version" => env!("CARGO_PKG_VERSION")],
+                    ));
+                    ui.label(l10n.tr_args(
+                        "about-author",
+                        &fluent_args!["author" => env!("CARGO_PKG_AUTHORS")],
+                    ));
+                    ui.label(l10n.tr("about-description"));
+                    ui.hyperlink_to(
+                        l10n.tr("about-source-code"),
+                        "https://github.com/balintkissdev/3d-renderer-rust",
+                    );
+                });
 
-                    // Model
-                    egui::CollapsingHeader::new("Model")
-                        .default_open(true)
+            egui::Window::new(l10n.tr("menu-opengl-info"))
+                .id(egui::Id::new("gl_info_window"))
+                .open(&mut open_windows.gl_info)
+                .default_pos([340.0, 40.0])
+                .default_size([360.0, 400.0])
+                .show(egui_ctx, |ui| {
+                    egui::Grid::new("gl_info_grid")
+                        .num_columns(2)
                         .show(ui, |ui| {
-                            let model_items = ["Blender Cube", "Utah Teapot", "Stanford Bunny"];
-                            let selected_model_index = draw_props.selected_model_index;
-                            egui::ComboBox::from_label("Select Model")
-                                .selected_text(model_items[selected_model_index])
-                                .show_ui(ui, |ui| {
-                                    for (index, model) in model_items.iter().enumerate() {
-                                        ui.selectable_value(
-                                            &mut draw_props.selected_model_index,
-                                            index,
-                                            *model,
-                                        );
-                                    }
-                                });
+                            ui.label(l10n.tr("gl-info-vendor"));
+                            ui.label(&gl_info.vendor);
+                            ui.end_row();
+                            ui.label(l10n.tr("gl-info-renderer"));
+                            ui.label(&gl_info.renderer);
+                            ui.end_row();
+                            ui.label(l10n.tr("gl-info-version"));
+                            ui.label(&gl_info.version);
+                            ui.end_row();
+                            ui.label(l10n.tr("gl-info-glsl-version"));
+                            ui.label(&gl_info.shading_language_version);
+                            ui.end_row();
+                            ui.label(l10n.tr("gl-info-max-texture-size"));
+                            ui.label(gl_info.max_texture_size.to_string());
+                            ui.end_row();
+                            ui.label(l10n.tr("gl-info-max-samples"));
+                            ui.label(gl_info.max_samples.to_string());
+                            ui.end_row();
+                        });
 
-                            #[cfg(not(target_arch = "wasm32"))]
-                            {
-                                ui.checkbox(
-                                    &mut draw_props.wireframe_mode_enabled,
-                                    "Wireframe mode",
-                                );
+                    if ui.button(l10n.tr("gl-info-copy")).clicked() {
+                        copy_to_clipboard(ui.ctx(), gl_info.report());
+                    }
+
+                    egui::CollapsingHeader::new(l10n.tr_args(
+                        "gl-info-extensions",
+                        &fluent_args!["count" => gl_info.extensions.len()],
+                    ))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            for extension in &gl_info.extensions {
+                                ui.label(extension);
                             }
                         });
+                    });
+                });
 
-                    // Transform
-                    egui::CollapsingHeader::new("Transform")
-                        .default_open(true)
-                        .show(ui, |ui| {
-                            let model_rotation_range = 0.0..=360.0;
-                            ui.add(
-                                egui::Slider::new(
-                                    &mut draw_props.model_rotation[0],
-                                    model_rotation_range.clone(),
-                                )
-                                .text("X rotation")
-                                .suffix("°"),
+            #[cfg(not(target_arch = "wasm32"))]
+            egui::Window::new(l10n.tr("menu-uv-layout"))
+                .id(egui::Id::new("uv_layout_window"))
+                .open(&mut open_windows.uv_layout)
+                .default_pos([340.0, 40.0])
+                .default_size([400.0, 440.0])
+                .show(egui_ctx, |ui| {
+                    let model = &scene.models[draw_props.selected_model_index];
+                    ui.label(model.name.as_str());
+                    let mesh = model.lod(0);
+                    if !mesh.vertex_layout().contains(VertexAttribute::Uv) {
+                        ui.label(l10n.tr("uv-layout-none"));
+                        return;
+                    }
+                    match mesh.uv_layout() {
+                        Ok(uv_layout) => show_uv_layout(ui, l10n, uv_layout),
+                        Err(e) => {
+                            ui.colored_label(ui.visuals().error_fg_color, e);
+                        }
+                    }
+                });
+
+            if open_windows.shader_editor {
+                shader_editor
+                    .get_or_insert_with(|| ShaderEditor::new(renderer.custom_shader_template()))
+                    .show(egui_ctx, l10n, &mut open_windows.shader_editor, renderer);
+            }
+            if open_windows.model_shader_playground {
+                model_shader_playground
+                    .get_or_insert_with(|| {
+                        ModelShaderPlayground::new(renderer.model_fragment_source())
+                    })
+                    .show(
+                        egui_ctx,
+                        l10n,
+                        &mut open_windows.model_shader_playground,
+                        renderer,
+                    );
+            }
+
+            #[cfg(feature = "profiling")]
+            profiler_window.show(egui_ctx, &mut open_windows.profiler);
+        });
+
+        // Zoom is also changed by Ctrl + Plus/Minus keyboard shortcuts of egui
+        if !self.ui_scale_overridden {
+            self.config.zoom_factor = self.egui_glow.egui_ctx.zoom_factor();
+        }
+    }
+
+    pub fn draw(&mut self, window: &winit::window::Window) {
+        profile_scope!("Gui::draw");
+        self.egui_glow.paint(&window);
+    }
+
+    /// Draw only the error window onto a blank screen, when the scene cannot be rendered at all.
+    pub fn draw_error_frame(&mut self, window: &winit::window::Window) {
+        let l10n = &self.localization;
+        let errors = &mut self.errors;
+        #[cfg(not(target_arch = "wasm32"))]
+        let quit_requested = &mut self.quit_requested;
+        self.egui_glow.run(window, |egui_ctx| {
+            show_errors(
+                egui_ctx,
+                l10n,
+                errors,
+                #[cfg(not(target_arch = "wasm32"))]
+                quit_requested,
+                false,
+            );
+        });
+
+        let size = window.inner_size();
+        self.egui_glow
+            .painter
+            .clear([size.width, size.height], [0.0, 0.0, 0.0, 1.0]);
+        self.egui_glow.paint(window);
+    }
+}
+
+/// Native: Panel arrangement is saved when the application exits, no matter how it was closed.
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for Gui {
+    fn drop(&mut self) {
+        if let Err(e) = self.config.save(CONFIG_PATH) {
+            eprintln!("{e}");
+        }
+    }
+}
+
+/// Visibility of GUI windows and of the properties dock, toggled from the menu bar.
+struct OpenWindows {
+    properties: bool,
+    controls: bool,
+    about: bool,
+    gl_info: bool,
+    shader_editor: bool,
+    model_shader_playground: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    uv_layout: bool,
+    #[cfg(feature = "profiling")]
+    profiler: bool,
+}
+
+impl Default for OpenWindows {
+    fn default() -> Self {
+        Self {
+            properties: true,
+            // Shown on startup for first-time users
+            controls: true,
+            about: false,
+            gl_info: false,
+            shader_editor: false,
+            model_shader_playground: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            uv_layout: false,
+            #[cfg(feature = "profiling")]
+            profiler: false,
+        }
+    }
+}
+
+/// Tab of the properties dock.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+enum Panel {
+    Scene,
+    Hierarchy,
+    Material,
+    Lighting,
+    Renderer,
+    PostProcessing,
+    Time,
+    Timeline,
+    Stats,
+}
+
+impl Panel {
+    const ALL: [Panel; 9] = [
+        Panel::Scene,
+        Panel::Hierarchy,
+        Panel::Material,
+        Panel::Lighting,
+        Panel::Renderer,
+        Panel::PostProcessing,
+        Panel::Time,
+        Panel::Timeline,
+        Panel::Stats,
+    ];
+
+    fn l10n_id(self) -> &'static str {
+        match self {
+            Panel::Scene => "panel-scene",
+            Panel::Hierarchy => "panel-hierarchy",
+            Panel::Material => "panel-material",
+            Panel::Lighting => "panel-lighting",
+            Panel::Renderer => "panel-renderer",
+            Panel::PostProcessing => "panel-post-processing",
+            Panel::Time => "panel-time",
+            Panel::Timeline => "panel-timeline",
+            Panel::Stats => "panel-stats",
+        }
+    }
+}
+
+/// Choices of the timeline panel kept between frames.
+struct TimelineEditor {
+    // Keyframed by the add keyframe button
+    property: AnimatedProperty,
+    easing: Easing,
+    #[cfg(not(target_arch = "wasm32"))]
+    export_directory: String,
+    #[cfg(not(target_arch = "wasm32"))]
+    export_frame_rate: f32,
+}
+
+impl Default for TimelineEditor {
+    fn default() -> Self {
+        Self {
+            property: AnimatedProperty::LightDirection,
+            easing: Easing::Smoothstep,
+            #[cfg(not(target_arch = "wasm32"))]
+            export_directory: "frames".to_string(),
+            #[cfg(not(target_arch = "wasm32"))]
+            export_frame_rate: 30.0,
+        }
+    }
+}
+
+/// Color scheme of GUI widgets.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Theme {
+    Dark,
+    Light,
+}
+
+impl Theme {
+    fn visuals(self) -> egui::Visuals {
+        let mut visuals = match self {
+            Theme::Dark => egui::Visuals::dark(),
+            Theme::Light => egui::Visuals::light(),
+        };
+        visuals.window_shadow = Shadow::NONE;
+        visuals
+    }
+}
+
+/// GUI settings kept between runs in RON format.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct GuiConfig {
+    layout: DockState<Panel>,
+    /// Scale applied on top of the display scale factor, for GUI that is too small on
+    /// high-resolution displays.
+    zoom_factor: f32,
+    theme: Theme,
+}
+
+impl Default for GuiConfig {
+    fn default() -> Self {
+        let mut layout = DockState::new(vec![
+            Panel::Scene,
+            Panel::Hierarchy,
+            Panel::Material,
+            Panel::Lighting,
+        ]);
+        layout.main_surface_mut().split_below(
+            NodeIndex::root(),
+            0.7,
+            vec![
+                Panel::Renderer,
+                Panel::PostProcessing,
+                Panel::Time,
+                Panel::Timeline,
+                Panel::Stats,
+            ],
+        );
+        Self {
+            layout,
+            zoom_factor: 1.0,
+            theme: Theme::Dark,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl GuiConfig {
+    /// Default settings are used if the config file doesn't exist yet.
+    fn load(path: &str) -> Result<Self, String> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("cannot read config file {path}: {e}")),
+        };
+        ron::from_str(&text).map_err(|e| format!("cannot parse config file {path}: {e}"))
+    }
+
+    fn save(&self, path: &str) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("cannot serialize config: {e}"))?;
+        std::fs::write(path, text).map_err(|e| format!("cannot write config file {path}: {e}"))
+    }
+}
+
+/// Contents of dock panels, editing the state passed to `Gui::prepare_frame()`.
+struct PanelViewer<'a> {
+    gl_info: &'a GlInfo,
+    draw_props: &'a mut DrawProperties,
+    scene: &'a mut Scene,
+    asset_cache: &'a mut AssetCache,
+    render_stats: &'a RenderStats,
+    // Registered with the renderer, assignable to materials
+    custom_shader_names: Vec<&'a str>,
+    errors: &'a mut Vec<String>,
+    theme: &'a mut Theme,
+    l10n: &'a Localization,
+    // Entity clicked in the hierarchy
+    active_entity: &'a mut Option<Entity>,
+    timeline_editor: &'a mut TimelineEditor,
+    #[cfg(not(target_arch = "wasm32"))]
+    frame_rate_info: &'a FrameRateInfo,
+    #[cfg(not(target_arch = "wasm32"))]
+    material_path: &'a mut String,
+    #[cfg(not(target_arch = "wasm32"))]
+    bake_progress: Option<f32>,
+    #[cfg(not(target_arch = "wasm32"))]
+    actions: &'a mut Vec<GuiAction>,
+}
+
+impl TabViewer for PanelViewer<'_> {
+    type Tab = Panel;
+
+    fn title(&mut self, panel: &mut Panel) -> egui::WidgetText {
+        self.l10n.tr(panel.l10n_id()).into()
+    }
+
+    // Title changes with language
+    fn id(&mut self, panel: &mut Panel) -> egui::Id {
+        egui::Id::new(*panel)
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, panel: &mut Panel) {
+        match panel {
+            Panel::Scene => self.scene_panel(ui),
+            Panel::Hierarchy => self.hierarchy_panel(ui),
+            Panel::Material => self.material_panel(ui),
+            Panel::Lighting => self.lighting_panel(ui),
+            Panel::Renderer => self.renderer_panel(ui),
+            Panel::PostProcessing => self.post_processing_panel(ui),
+            Panel::Time => self.time_panel(ui),
+            Panel::Timeline => self.timeline_panel(ui),
+            Panel::Stats => self.stats_panel(ui),
+        }
+    }
+
+    // Closed panels are brought back from the View menu
+    fn closeable(&mut self, _panel: &mut Panel) -> bool {
+        true
+    }
+}
+
+impl PanelViewer<'_> {
+    fn scene_panel(&mut self, ui: &mut egui::Ui) {
+        let l10n = self.l10n;
+        let draw_props = &mut *self.draw_props;
+        let scene = &mut *self.scene;
+        let active_entity = &mut *self.active_entity;
+
+        // Camera
+        egui::CollapsingHeader::new(l10n.tr("camera"))
+            .default_open(true)
+            .show(ui, |ui| {
+                let camera_position = scene.camera.position();
+                ui.label(format!(
+                    "X: {:.3} Y: {:.3} Z: {:.3}",
+                    camera_position.x, camera_position.y, camera_position.z
+                ));
+
+                let camera_rotation = scene.camera.rotation();
+                ui.label(l10n.tr_args(
+                    "camera-rotation",
+                    &fluent_args![
+                        "yaw" => format!("{:.1}", camera_rotation.x),
+                        "pitch" => format!("{:.1}", camera_rotation.y),
+                        "roll" => format!("{:.1}", camera_rotation.z),
+                    ],
+                ));
+
+                ui.add(
+                    egui::Slider::new(&mut draw_props.field_of_view, 45.0..=120.0)
+                        .text(l10n.tr("field-of-view"))
+                        .suffix("°"),
+                );
+                egui::ComboBox::from_label(l10n.tr("camera-mode"))
+                    .selected_text(l10n.tr(draw_props.camera_mode.l10n_id()))
+                    .show_ui(ui, |ui| {
+                        for camera_mode in CameraMode::ALL {
+                            ui.selectable_value(
+                                &mut draw_props.camera_mode,
+                                camera_mode,
+                                l10n.tr(camera_mode.l10n_id()),
                             );
-                            ui.add(
-                                egui::Slider::new(
-                                    &mut draw_props.model_rotation[1],
-                                    model_rotation_range.clone(),
-                                )
-                                .text("Y rotation")
-                                .suffix("°"),
+                        }
+                    });
+                // Raw motion feels wrong on some trackpads
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.checkbox(&mut draw_props.raw_mouse_input, l10n.tr("raw-mouse-input"));
+                ui.add(
+                    egui::Slider::new(&mut draw_props.mouse_smoothing, 0.0..=0.95)
+                        .text(l10n.tr("mouse-smoothing")),
+                );
+                ui.add(
+                    egui::Slider::new(&mut draw_props.mouse_acceleration, 0.0..=1.0)
+                        .text(l10n.tr("mouse-acceleration")),
+                );
+
+                ui.separator();
+                ui.label(l10n.tr("camera-bookmarks"));
+                let mut removed = None;
+                for (index, bookmark) in scene.camera_bookmarks.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button(&bookmark.name)
+                            .on_hover_text(l10n.tr("go-to-camera-bookmark"))
+                            .clicked()
+                        {
+                            scene.camera_transition = Some(CameraTransition::new(
+                                &scene.camera,
+                                draw_props.field_of_view,
+                                bookmark,
+                                draw_props.camera_transition_duration,
+                                draw_props.camera_transition_easing,
+                            ));
+                        }
+                        if ui.button(l10n.tr("remove-camera-bookmark")).clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = removed {
+                    scene.camera_bookmarks.remove(index);
+                }
+                if ui.button(l10n.tr("add-camera-bookmark")).clicked() {
+                    let name = l10n.tr_args(
+                        "camera-bookmark",
+                        &fluent_args!["index" => scene.camera_bookmarks.len() + 1],
+                    );
+                    scene.camera_bookmarks.push(CameraBookmark::new(
+                        &name,
+                        &scene.camera,
+                        draw_props.field_of_view,
+                    ));
+                }
+                ui.add(
+                    egui::Slider::new(&mut draw_props.camera_transition_duration, 0.0..=5.0)
+                        .text(l10n.tr("camera-transition-duration"))
+                        .suffix(" s"),
+                );
+                egui::ComboBox::from_label(l10n.tr("camera-transition-easing"))
+                    .selected_text(l10n.tr(draw_props.camera_transition_easing.l10n_id()))
+                    .show_ui(ui, |ui| {
+                        for easing in Easing::ALL {
+                            ui.selectable_value(
+                                &mut draw_props.camera_transition_easing,
+                                easing,
+                                l10n.tr(easing.l10n_id()),
                             );
-                            ui.add(
-                                egui::Slider::new(
-                                    &mut draw_props.model_rotation[2],
-                                    model_rotation_range.clone(),
+                        }
+                    });
+                ui.separator();
+
+                ui.checkbox(&mut draw_props.skybox_enabled, l10n.tr("skybox"));
+                ui.checkbox(&mut draw_props.labels_enabled, l10n.tr("labels"));
+                if !draw_props.skybox_enabled {
+                    ui.horizontal(|ui| {
+                        ui.color_edit_button_rgb(&mut draw_props.background_color);
+                        ui.label(l10n.tr("background-color"));
+                    });
+                }
+            });
+
+        // Ground plane
+        egui::CollapsingHeader::new(l10n.tr("ground-plane"))
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.checkbox(
+                    &mut draw_props.reflective_plane_enabled,
+                    l10n.tr("reflective-plane"),
+                );
+                if draw_props.reflective_plane_enabled {
+                    ui.add(
+                        egui::Slider::new(&mut draw_props.reflective_plane_height, -3.0..=0.0)
+                            .text(l10n.tr("ground-plane-height")),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut draw_props.reflectivity, 0.0..=1.0)
+                            .text(l10n.tr("reflectivity")),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut draw_props.reflection_blur, 0.0..=5.0)
+                            .text(l10n.tr("reflection-blur")),
+                    );
+                }
+                ui.checkbox(&mut draw_props.mirror_enabled, l10n.tr("stencil-mirror"))
+                    .on_hover_text(l10n.tr("stencil-mirror-hint"));
+                if scene.terrain.is_some() {
+                    ui.checkbox(&mut draw_props.terrain_enabled, l10n.tr("terrain"));
+                }
+            });
+
+        // Model
+        egui::CollapsingHeader::new(l10n.tr("model"))
+            .default_open(true)
+            .show(ui, |ui| {
+                let selected_model_index = draw_props.selected_model_index;
+                egui::ComboBox::from_label(l10n.tr("select-model"))
+                    .selected_text(&scene.models[selected_model_index].name)
+                    .show_ui(ui, |ui| {
+                        for (index, model) in scene.models.iter().enumerate() {
+                            if ui
+                                .selectable_value(
+                                    &mut draw_props.selected_model_index,
+                                    index,
+                                    &model.name,
                                 )
-                                .text("Z rotation")
-                                .suffix("°"),
+                                .changed()
+                            {
+                                // Material panel follows the newly selected model
+                                *active_entity = None;
+                            }
+                        }
+                    });
+
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    // Polygon mode is not available in OpenGL ES
+                    ui.add_enabled(
+                        !self.gl_info.embedded,
+                        egui::Checkbox::new(
+                            &mut draw_props.wireframe_mode_enabled,
+                            l10n.tr("wireframe-mode"),
+                        ),
+                    );
+                    ui.add_enabled(
+                        self.gl_info.supports_gl43(),
+                        egui::Checkbox::new(
+                            &mut draw_props.vertex_displacement_enabled,
+                            l10n.tr("vertex-displacement"),
+                        ),
+                    )
+                    .on_disabled_hover_text(l10n.tr("vertex-displacement-unsupported"));
+                    if draw_props.vertex_displacement_enabled && self.gl_info.supports_gl43() {
+                        ui.add(
+                            egui::Slider::new(
+                                &mut draw_props.vertex_displacement_amplitude,
+                                0.0..=0.2,
+                            )
+                            .text(l10n.tr("vertex-displacement-amplitude")),
+                        );
+                    }
+                    ui.add_enabled(
+                        self.gl_info.supports_gl43(),
+                        egui::Checkbox::new(
+                            &mut draw_props.face_normals_enabled,
+                            l10n.tr("face-normals"),
+                        ),
+                    )
+                    .on_disabled_hover_text(l10n.tr("geometry-shader-unsupported"));
+                    ui.add_enabled(
+                        self.gl_info.supports_gl43(),
+                        egui::Slider::new(&mut draw_props.explode_distance, 0.0..=0.5)
+                            .text(l10n.tr("explode-distance")),
+                    )
+                    .on_hover_text(l10n.tr("explode-distance-hint"))
+                    .on_disabled_hover_text(l10n.tr("geometry-shader-unsupported"));
+                }
+
+                ui.checkbox(
+                    &mut draw_props.overdraw_heatmap_enabled,
+                    l10n.tr("overdraw-heatmap"),
+                )
+                .on_hover_text(l10n.tr("overdraw-heatmap-hint"));
+                ui.checkbox(
+                    &mut draw_props.bounding_box_enabled,
+                    l10n.tr("bounding-box"),
+                );
+                ui.checkbox(
+                    &mut draw_props.bounding_sphere_enabled,
+                    l10n.tr("bounding-sphere"),
+                );
+                let part_count = scene.models[draw_props.selected_model_index].parts().len();
+                ui.add(
+                    egui::Slider::new(&mut draw_props.part_explode_factor, 0.0..=2.0)
+                        .text(l10n.tr("explode-parts")),
+                )
+                .on_hover_text(
+                    l10n.tr_args("explode-parts-hint", &fluent_args!["count" => part_count]),
+                );
+
+                if let Some(model) = scene.models.get_mut(draw_props.selected_model_index) {
+                    let face_culling = &mut model.face_culling;
+                    ui.checkbox(&mut face_culling.enabled, l10n.tr("back-face-culling"));
+                    ui.add_enabled_ui(face_culling.enabled, |ui| {
+                        egui::ComboBox::from_label(l10n.tr("front-face-winding"))
+                            .selected_text(match face_culling.front_face {
+                                Winding::CounterClockwise => l10n.tr("winding-counter-clockwise"),
+                                Winding::Clockwise => l10n.tr("winding-clockwise"),
+                            })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(
+                                    &mut face_culling.front_face,
+                                    Winding::CounterClockwise,
+                                    l10n.tr("winding-counter-clockwise"),
+                                );
+                                ui.selectable_value(
+                                    &mut face_culling.front_face,
+                                    Winding::Clockwise,
+                                    l10n.tr("winding-clockwise"),
+                                );
+                            });
+                    });
+                }
+            });
+
+        // Transform
+        egui::CollapsingHeader::new(l10n.tr("transform"))
+            .default_open(true)
+            .show(ui, |ui| {
+                let model_rotation_range = 0.0..=360.0;
+                ui.add(
+                    egui::Slider::new(
+                        &mut draw_props.model_rotation[0],
+                        model_rotation_range.clone(),
+                    )
+                    .text(l10n.tr("rotation-x"))
+                    .suffix("°"),
+                );
+                ui.add(
+                    egui::Slider::new(
+                        &mut draw_props.model_rotation[1],
+                        model_rotation_range.clone(),
+                    )
+                    .text(l10n.tr("rotation-y"))
+                    .suffix("°"),
+                );
+                ui.add(
+                    egui::Slider::new(
+                        &mut draw_props.model_rotation[2],
+                        model_rotation_range.clone(),
+                    )
+                    .text(l10n.tr("rotation-z"))
+                    .suffix("°"),
+                );
+            });
+
+        // Decals
+        #[cfg(not(target_arch = "wasm32"))]
+        egui::CollapsingHeader::new(l10n.tr("decals"))
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.checkbox(
+                    &mut draw_props.decal_volumes_enabled,
+                    l10n.tr("decal-volumes"),
+                );
+                let mut removed = None;
+                for (index, decal) in scene.decals.iter_mut().enumerate() {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.strong(decal.name.as_str());
+                        if ui.button(l10n.tr("remove-decal")).clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(l10n.tr("position"));
+                        for (axis, prefix) in ["X: ", "Y: ", "Z: "].into_iter().enumerate() {
+                            ui.add(
+                                egui::DragValue::new(&mut decal.position[axis])
+                                    .speed(0.01)
+                                    .prefix(prefix),
                             );
+                        }
+                    });
+                    for (axis, message_id) in ["rotation-x", "rotation-y", "rotation-z"]
+                        .into_iter()
+                        .enumerate()
+                    {
+                        ui.add(
+                            egui::Slider::new(&mut decal.rotation[axis], 0.0..=360.0)
+                                .text(l10n.tr(message_id))
+                                .suffix("°"),
+                        );
+                    }
+                    ui.add(
+                        egui::Slider::new(&mut decal.field_of_view, 1.0..=120.0)
+                            .text(l10n.tr("field-of-view"))
+                            .suffix("°"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut decal.aspect_ratio, 0.1..=10.0)
+                            .logarithmic(true)
+                            .text(l10n.tr("decal-aspect-ratio")),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut decal.near, 0.01..=1000.0)
+                            .logarithmic(true)
+                            .text(l10n.tr("decal-near")),
+                    )
+                    .on_hover_text(l10n.tr("decal-range-hint"));
+                    ui.add(
+                        egui::Slider::new(&mut decal.far, 0.01..=1000.0)
+                            .logarithmic(true)
+                            .text(l10n.tr("decal-far")),
+                    )
+                    .on_hover_text(l10n.tr("decal-range-hint"));
+                    ui.add(
+                        egui::Slider::new(&mut decal.opacity, 0.0..=1.0)
+                            .text(l10n.tr("decal-opacity")),
+                    );
+                }
+                if let Some(index) = removed {
+                    scene.decals.remove(index);
+                }
+                if scene.decals.is_empty() {
+                    ui.label(l10n.tr("decals-none"));
+                }
+            });
+
+        // Measurement
+        #[cfg(not(target_arch = "wasm32"))]
+        egui::CollapsingHeader::new(l10n.tr("measurement"))
+            .default_open(false)
+            .show(ui, |ui| {
+                let measurement = &mut scene.measurement;
+                ui.checkbox(&mut measurement.enabled, l10n.tr("measure-mode"))
+                    .on_hover_text(l10n.tr("measure-mode-hint"));
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut measurement.unit_scale)
+                            .speed(0.01)
+                            .range(0.0001..=f32::MAX),
+                    );
+                    ui.label(l10n.tr("measure-unit-scale"));
+                    ui.add(egui::TextEdit::singleline(&mut measurement.unit).desired_width(40.0));
+                    ui.label(l10n.tr("measure-unit"));
+                });
+                if measurement.points().is_empty() {
+                    ui.label(l10n.tr("measure-no-points"));
+                }
+                for distance in measurement.distances() {
+                    ui.label(l10n.tr_args(
+                        "measure-distance",
+                        &fluent_args!["distance" => measurement.format_distance(distance)],
+                    ));
+                }
+                if let Some(angle) = measurement.angle() {
+                    ui.label(l10n.tr_args(
+                        "measure-angle",
+                        &fluent_args!["angle" => format!("{angle:.1}")],
+                    ));
+                }
+                if ui.button(l10n.tr("measure-clear")).clicked() {
+                    measurement.clear();
+                }
+            });
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.checkbox(&mut scene.inspector.enabled, l10n.tr("inspector-mode"))
+            .on_hover_text(l10n.tr("inspector-mode-hint"));
+    }
+
+    /// Renderable entities grouped by their model, with visibility toggle, rename, duplicate and
+    /// delete actions. Clicked entity becomes the target of transform and material editing.
+    fn hierarchy_panel(&mut self, ui: &mut egui::Ui) {
+        let l10n = self.l10n;
+        let scene = &mut *self.scene;
+        if scene.models.is_empty() {
+            ui.label(l10n.tr("hierarchy-empty"));
+            return;
+        }
+
+        let mut nodes: Vec<(Entity, usize)> = scene
+            .world
+            .query::<&Renderable>()
+            .iter()
+            .map(|(entity, renderable)| (entity, renderable.model_index))
+            .collect();
+        // Query order changes with structural changes, keep rows in place
+        nodes.sort_by_key(|(entity, model_index)| (*model_index, entity.id()));
+
+        // World can't be changed structurally while components are borrowed by widgets
+        let mut node_action = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (model_index, model) in scene.models.iter().enumerate() {
+                // Models can have the same name when opened from the same file twice
+                let id = ui.make_persistent_id(("hierarchy_model", model_index));
+                egui::collapsing_header::CollapsingState::load_with_default_open(
+                    ui.ctx(),
+                    id,
+                    true,
+                )
+                .show_header(ui, |ui| {
+                    ui.label(&model.name);
+                    if ui
+                        .small_button("➕")
+                        .on_hover_text(l10n.tr("hierarchy-add-instance"))
+                        .clicked()
+                    {
+                        node_action = Some(NodeAction::AddInstance(model_index));
+                    }
+                })
+                .body(|ui| {
+                    for (entity, _) in nodes.iter().filter(|(_, index)| *index == model_index) {
+                        let active = *self.active_entity == Some(*entity);
+                        if let Some(action) = hierarchy_node(ui, l10n, scene, *entity, active) {
+                            node_action = Some(action);
+                        }
+                    }
+                });
+            }
+        });
+
+        match node_action {
+            Some(NodeAction::SetVisible(entity, true)) => {
+                let _ = scene.world.insert_one(entity, Visible);
+            }
+            Some(NodeAction::SetVisible(entity, false)) => {
+                let _ = scene.world.remove_one::<Visible>(entity);
+            }
+            Some(NodeAction::Activate(entity)) => *self.active_entity = Some(entity),
+            Some(NodeAction::AddInstance(model_index)) => {
+                *self.active_entity = Some(scene.spawn_instance(model_index));
+            }
+            Some(NodeAction::Duplicate(entity)) => {
+                *self.active_entity = scene.duplicate(entity);
+            }
+            Some(NodeAction::Delete(entity)) => {
+                let _ = scene.world.despawn(entity);
+            }
+            None => (),
+        }
+
+        // Transform of the clicked entity
+        let Some(entity) = self
+            .active_entity
+            .filter(|entity| scene.world.contains(*entity))
+        else {
+            return;
+        };
+        ui.separator();
+        ui.strong(scene.entity_name(entity));
+        // Overwritten by the Transform settings of the Scene panel on every frame
+        let rotation_from_selection = scene
+            .selected_entity(self.draw_props.selected_model_index)
+            .is_some_and(|selected| selected == entity);
+        let Ok(mut transform) = scene.world.get::<&mut Transform>(entity) else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label(l10n.tr("position"));
+            for (axis, prefix) in ["X: ", "Y: ", "Z: "].into_iter().enumerate() {
+                ui.add(
+                    egui::DragValue::new(&mut transform.position[axis])
+                        .speed(0.05)
+                        .prefix(prefix),
+                );
+            }
+        });
+        if rotation_from_selection {
+            ui.label(l10n.tr("hierarchy-rotation-from-selection"));
+        } else {
+            for (axis, message_id) in ["rotation-x", "rotation-y", "rotation-z"]
+                .into_iter()
+                .enumerate()
+            {
+                ui.add(
+                    egui::Slider::new(&mut transform.rotation[axis], 0.0..=360.0)
+                        .text(l10n.tr(message_id))
+                        .suffix("°"),
+                );
+            }
+        }
+    }
+
+    /// Material of the entity clicked in the hierarchy, or of the selected model by default.
+    fn material_target(&self) -> Option<Entity> {
+        self.active_entity
+            .filter(|entity| {
+                self.scene
+                    .world
+                    .satisfies::<&Material>(*entity)
+                    .unwrap_or(false)
+            })
+            .or_else(|| {
+                self.scene
+                    .selected_entity(self.draw_props.selected_model_index)
+            })
+    }
+
+    fn material_panel(&mut self, ui: &mut egui::Ui) {
+        let l10n = self.l10n;
+        let Some(entity) = self.material_target() else {
+            return;
+        };
+        ui.strong(self.scene.entity_name(entity));
+        let Ok(mut material) = self.scene.world.get::<&mut Material>(entity) else {
+            return;
+        };
+
+        material_editor(ui, l10n, &mut material, &self.custom_shader_names);
+
+        ui.separator();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            ui.horizontal(|ui| {
+                ui.label(l10n.tr("material-file"));
+                ui.text_edit_singleline(self.material_path);
+            });
+            ui.horizontal(|ui| {
+                if ui.button(l10n.tr("material-save")).clicked() {
+                    if let Err(e) = material.save(self.material_path) {
+                        self.errors.push(e);
+                    }
+                }
+                if ui.button(l10n.tr("material-load")).clicked() {
+                    match Material::load(self.material_path) {
+                        Ok(loaded) => *material = loaded,
+                        Err(e) => self.errors.push(e),
+                    }
+                }
+            });
+        }
+        if ui.button(l10n.tr("material-copy-ron")).clicked() {
+            match material.to_ron() {
+                Ok(text) => copy_to_clipboard(ui.ctx(), text),
+                Err(e) => self.errors.push(e),
+            }
+        }
+        drop(material);
+
+        self.parts_section(ui, entity);
+    }
+
+    /// Visibility and material slot of each part of a model consisting of multiple parts.
+    fn parts_section(&mut self, ui: &mut egui::Ui, entity: Entity) {
+        let l10n = self.l10n;
+        let scene = &mut *self.scene;
+        let Some(model) = scene
+            .world
+            .get::<&Renderable>(entity)
+            .ok()
+            .and_then(|renderable| scene.models.get(renderable.model_index))
+        else {
+            return;
+        };
+        let parts = model.parts();
+        if parts.len() < 2 {
+            return;
+        }
+        if !scene
+            .world
+            .satisfies::<&PartOverrides>(entity)
+            .unwrap_or(false)
+        {
+            scene
+                .world
+                .insert_one(entity, PartOverrides::new(parts.len()))
+                .unwrap();
+        }
+        let Ok(mut part_overrides) = scene.world.get::<&mut PartOverrides>(entity) else {
+            return;
+        };
+        let entity_material = scene.world.get::<&Material>(entity).unwrap().clone();
+
+        ui.separator();
+        ui.strong(l10n.tr_args("parts", &fluent_args!["count" => parts.len()]));
+        for (part_index, (part, part_override)) in
+            parts.iter().zip(part_overrides.0.iter_mut()).enumerate()
+        {
+            ui.push_id(part_index, |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut part_override.visible, &part.name);
+                    let mut own_material = part_override.material.is_some();
+                    if ui
+                        .checkbox(&mut own_material, l10n.tr("part-material"))
+                        .on_hover_text(l10n.tr("part-material-hint"))
+                        .changed()
+                    {
+                        part_override.material = own_material.then(|| Material {
+                            name: part.name.clone(),
+                            ..entity_material.clone()
                         });
+                    }
+                });
+                if let Some(material) = &mut part_override.material {
+                    ui.indent("material", |ui| {
+                        material_editor(ui, l10n, material, &self.custom_shader_names);
+                    });
+                }
+            });
+        }
+    }
 
-                    // Material
-                    egui::CollapsingHeader::new("Material")
-                        .default_open(true)
-                        .show(ui, |ui| {
-                            ui.color_edit_button_rgb(&mut draw_props.model_color);
+    fn lighting_panel(&mut self, ui: &mut egui::Ui) {
+        let l10n = self.l10n;
+        let draw_props = &mut *self.draw_props;
+        let light_direction_range = -1.0..=1.0;
+        ui.add(
+            egui::Slider::new(
+                &mut draw_props.light_direction[0],
+                light_direction_range.clone(),
+            )
+            .text(l10n.tr("light-direction-x")),
+        );
+        ui.add(
+            egui::Slider::new(
+                &mut draw_props.light_direction[1],
+                light_direction_range.clone(),
+            )
+            .text(l10n.tr("light-direction-y")),
+        );
+        ui.add(
+            egui::Slider::new(
+                &mut draw_props.light_direction[2],
+                light_direction_range.clone(),
+            )
+            .text(l10n.tr("light-direction-z")),
+        );
+
+        ui.checkbox(&mut draw_props.light_view_enabled, l10n.tr("light-view"));
+        ui.checkbox(&mut draw_props.light_gizmo_enabled, l10n.tr("light-gizmo"));
+
+        egui::CollapsingHeader::new(l10n.tr("shadows"))
+            .default_open(false)
+            .show(ui, |ui| {
+                let settings = &mut draw_props.shadow_settings;
+                ui.checkbox(&mut settings.enabled, l10n.tr("shadows-enabled"))
+                    .on_hover_text(l10n.tr("shadows-enabled-hint"));
+                ui.add_enabled_ui(settings.enabled, |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut settings.cascade_count, 1..=MAX_SHADOW_CASCADES)
+                            .text(l10n.tr("shadow-cascades")),
+                    )
+                    .on_hover_text(l10n.tr("shadow-cascades-hint"));
+                    let resolution_text = |resolution| format!("{resolution}×{resolution}");
+                    for index in 0..settings.cascade_count {
+                        let resolution = &mut settings.resolutions[index];
+                        egui::ComboBox::new(
+                            ("shadow-resolution", index),
+                            l10n.tr_args(
+                                "shadow-resolution",
+                                &fluent_args!["cascade" => index + 1],
+                            ),
+                        )
+                        .selected_text(resolution_text(*resolution))
+                        .show_ui(ui, |ui| {
+                            for option in [256, 512, 1024, 2048, 4096] {
+                                ui.selectable_value(resolution, option, resolution_text(option));
+                            }
                         });
+                    }
+                    ui.add(
+                        egui::Slider::new(&mut settings.pcf_radius, 0..=3)
+                            .text(l10n.tr("shadow-pcf-radius")),
+                    )
+                    .on_hover_text(l10n.tr("shadow-pcf-radius-hint"));
+                    ui.add(
+                        egui::Slider::new(&mut settings.distance, 5.0..=100.0)
+                            .text(l10n.tr("shadow-distance")),
+                    );
+                    ui.checkbox(
+                        &mut settings.cascade_visualization,
+                        l10n.tr("shadow-cascade-visualization"),
+                    )
+                    .on_hover_text(l10n.tr("shadow-cascade-visualization-hint"));
+                });
+            });
 
-                    // Lighting
-                    egui::CollapsingHeader::new("Lighting")
-                        .default_open(true)
-                        .show(ui, |ui| {
-                            let light_direction_range = -1.0..=1.0;
+        egui::CollapsingHeader::new(l10n.tr("point-lights"))
+            .default_open(false)
+            .show(ui, |ui| {
+                let scene = &mut *self.scene;
+                let point_lights = scene.point_lights();
+                let mut removed = None;
+                for (index, &(entity, _)) in point_lights.iter().enumerate() {
+                    let Ok(mut point_light) = scene.world.get::<&mut PointLight>(entity) else {
+                        continue;
+                    };
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.strong(l10n.tr_args("point-light", &fluent_args!["index" => index + 1]));
+                        if ui.button(l10n.tr("remove-point-light")).clicked() {
+                            removed = Some(entity);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(l10n.tr("position"));
+                        for (axis, prefix) in ["X: ", "Y: ", "Z: "].into_iter().enumerate() {
                             ui.add(
-                                egui::Slider::new(
-                                    &mut draw_props.light_direction[0],
-                                    light_direction_range.clone(),
-                                )
-                                .text("Light direction X"),
+                                egui::DragValue::new(&mut point_light.position[axis])
+                                    .speed(0.05)
+                                    .prefix(prefix),
                             );
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.color_edit_button_rgb(&mut point_light.color);
+                        ui.label(l10n.tr("point-light-color"));
+                    });
+                    ui.add(
+                        egui::Slider::new(&mut point_light.intensity, 0.0..=10.0)
+                            .text(l10n.tr("point-light-intensity")),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut point_light.range, 0.5..=50.0)
+                            .logarithmic(true)
+                            .text(l10n.tr("point-light-range")),
+                    )
+                    .on_hover_text(l10n.tr("point-light-range-hint"));
+                    ui.checkbox(
+                        &mut point_light.shadows_enabled,
+                        l10n.tr("point-light-shadows"),
+                    )
+                    .on_hover_text(l10n.tr("point-light-shadows-hint"));
+                    ui.add_enabled(
+                        point_light.shadows_enabled,
+                        egui::Slider::new(&mut point_light.shadow_bias, 0.0..=0.5)
+                            .text(l10n.tr("point-light-shadow-bias")),
+                    )
+                    .on_hover_text(l10n.tr("point-light-shadow-bias-hint"));
+                }
+                if let Some(entity) = removed {
+                    let _ = scene.world.despawn(entity);
+                }
+                ui.separator();
+                if ui
+                    .add_enabled(
+                        point_lights.len() < MAX_POINT_LIGHTS,
+                        egui::Button::new(l10n.tr("add-point-light")),
+                    )
+                    .on_disabled_hover_text(l10n.tr_args(
+                        "point-lights-full",
+                        &fluent_args!["count" => MAX_POINT_LIGHTS],
+                    ))
+                    .clicked()
+                {
+                    scene.spawn_point_light(PointLight::default());
+                }
+            });
+
+        egui::CollapsingHeader::new(l10n.tr("area-lights"))
+            .default_open(false)
+            .show(ui, |ui| {
+                let scene = &mut *self.scene;
+                let area_lights = scene.area_lights();
+                let mut removed = None;
+                for (index, &(entity, _)) in area_lights.iter().enumerate() {
+                    let Ok(mut area_light) = scene.world.get::<&mut AreaLight>(entity) else {
+                        continue;
+                    };
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.strong(l10n.tr_args("area-light", &fluent_args!["index" => index + 1]));
+                        if ui.button(l10n.tr("remove-area-light")).clicked() {
+                            removed = Some(entity);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(l10n.tr("position"));
+                        for (axis, prefix) in ["X: ", "Y: ", "Z: "].into_iter().enumerate() {
                             ui.add(
-                                egui::Slider::new(
-                                    &mut draw_props.light_direction[1],
-                                    light_direction_range.clone(),
-                                )
-                                .text("Light direction Y"),
+                                egui::DragValue::new(&mut area_light.position[axis])
+                                    .speed(0.05)
+                                    .prefix(prefix),
                             );
-                            ui.add(
-                                egui::Slider::new(
-                                    &mut draw_props.light_direction[2],
-                                    light_direction_range.clone(),
-                                )
-                                .text("Light direction Z"),
+                        }
+                    });
+                    for (axis, message_id) in ["rotation-x", "rotation-y", "rotation-z"]
+                        .into_iter()
+                        .enumerate()
+                    {
+                        ui.add(
+                            egui::Slider::new(&mut area_light.rotation[axis], 0.0..=360.0)
+                                .text(l10n.tr(message_id))
+                                .suffix("°"),
+                        );
+                    }
+                    ui.add(
+                        egui::Slider::new(&mut area_light.width, 0.1..=10.0)
+                            .text(l10n.tr("area-light-width")),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut area_light.height, 0.1..=10.0)
+                            .text(l10n.tr("area-light-height")),
+                    );
+                    ui.horizontal(|ui| {
+                        ui.color_edit_button_rgb(&mut area_light.color);
+                        ui.label(l10n.tr("area-light-color"));
+                    });
+                    ui.add(
+                        egui::Slider::new(&mut area_light.intensity, 0.0..=10.0)
+                            .text(l10n.tr("area-light-intensity")),
+                    )
+                    .on_hover_text(l10n.tr("area-light-intensity-hint"));
+                }
+                if let Some(entity) = removed {
+                    let _ = scene.world.despawn(entity);
+                }
+                ui.separator();
+                if ui
+                    .add_enabled(
+                        area_lights.len() < MAX_AREA_LIGHTS,
+                        egui::Button::new(l10n.tr("add-area-light")),
+                    )
+                    .on_hover_text(l10n.tr("add-area-light-hint"))
+                    .on_disabled_hover_text(l10n.tr_args(
+                        "area-lights-full",
+                        &fluent_args!["count" => MAX_AREA_LIGHTS],
+                    ))
+                    .clicked()
+                {
+                    scene.spawn_area_light(AreaLight::default());
+                }
+            });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        egui::CollapsingHeader::new(l10n.tr("baked-lighting"))
+            .default_open(false)
+            .show(ui, |ui| {
+                let lightmap_count = self.scene.lightmaps().count();
+                ui.add_enabled(
+                    0 < lightmap_count,
+                    egui::Checkbox::new(
+                        &mut draw_props.baked_lighting_enabled,
+                        l10n.tr("baked-lighting-enabled"),
+                    ),
+                )
+                .on_hover_text(l10n.tr("baked-lighting-enabled-hint"))
+                .on_disabled_hover_text(l10n.tr("baked-lighting-none"));
+
+                let settings = &mut draw_props.lightmap_settings;
+                let resolution_text = |resolution| format!("{resolution}×{resolution}");
+                egui::ComboBox::from_label(l10n.tr("lightmap-resolution"))
+                    .selected_text(resolution_text(settings.resolution))
+                    .show_ui(ui, |ui| {
+                        for resolution in [256, 512, 1024, 2048, 4096] {
+                            ui.selectable_value(
+                                &mut settings.resolution,
+                                resolution,
+                                resolution_text(resolution),
                             );
+                        }
+                    });
+                ui.add(
+                    egui::Slider::new(&mut settings.samples, 1..=256)
+                        .logarithmic(true)
+                        .text(l10n.tr("lightmap-samples")),
+                )
+                .on_hover_text(l10n.tr("lightmap-samples-hint"));
+                if ui
+                    .add_enabled(
+                        self.bake_progress.is_none(),
+                        egui::Button::new(l10n.tr("bake-lighting")),
+                    )
+                    .on_hover_text(l10n.tr("bake-lighting-hint"))
+                    .on_disabled_hover_text(l10n.tr("bake-lighting-running"))
+                    .clicked()
+                {
+                    self.actions.push(GuiAction::BakeLighting);
+                }
 
-                            ui.checkbox(&mut draw_props.diffuse_enabled, "Diffuse");
-                            ui.checkbox(&mut draw_props.specular_enabled, "Specular");
+                if let Some(progress) = self.bake_progress {
+                    ui.add(egui::ProgressBar::new(progress).show_percentage());
+                } else if 0 < lightmap_count {
+                    let bake_time: std::time::Duration = self
+                        .scene
+                        .lightmaps()
+                        .map(|(_, lightmap)| lightmap.bake_time())
+                        .sum();
+                    ui.label(l10n.tr_args(
+                        "lightmaps-baked",
+                        &fluent_args![
+                            "count" => lightmap_count,
+                            "ms" => format!("{:.0}", bake_time.as_secs_f64() * 1000.0)
+                        ],
+                    ));
+                }
+            });
+    }
+
+    fn renderer_panel(&mut self, ui: &mut egui::Ui) {
+        let l10n = self.l10n;
+        let draw_props = &mut *self.draw_props;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        egui::ComboBox::from_label(l10n.tr("vsync"))
+            .selected_text(l10n.tr(draw_props.vsync_mode.l10n_id()))
+            .show_ui(ui, |ui| {
+                for vsync_mode in VsyncMode::ALL {
+                    ui.selectable_value(
+                        &mut draw_props.vsync_mode,
+                        vsync_mode,
+                        l10n.tr(vsync_mode.l10n_id()),
+                    );
+                }
+            })
+            .response
+            .on_hover_text(l10n.tr("vsync-hint"));
+        egui::ComboBox::from_label(l10n.tr("render-path"))
+            .selected_text(l10n.tr(draw_props.render_path.l10n_id()))
+            .show_ui(ui, |ui| {
+                for render_path in RenderPath::ALL {
+                    ui.selectable_value(
+                        &mut draw_props.render_path,
+                        render_path,
+                        l10n.tr(render_path.l10n_id()),
+                    );
+                }
+            });
+        egui::ComboBox::from_label(l10n.tr("anti-aliasing"))
+            .selected_text(l10n.tr(draw_props.anti_aliasing.l10n_id()))
+            .show_ui(ui, |ui| {
+                for anti_aliasing in AntiAliasing::ALL {
+                    ui.selectable_value(
+                        &mut draw_props.anti_aliasing,
+                        anti_aliasing,
+                        l10n.tr(anti_aliasing.l10n_id()),
+                    );
+                }
+            })
+            .response
+            .on_hover_text(l10n.tr("anti-aliasing-hint"));
+        ui.add_enabled(
+            draw_props.anti_aliasing == AntiAliasing::Temporal,
+            egui::Slider::new(&mut draw_props.taa_sharpness, 0.0..=1.0)
+                .text(l10n.tr("taa-sharpness")),
+        )
+        .on_hover_text(l10n.tr("taa-sharpness-hint"));
+        ui.add(
+            egui::Slider::new(&mut draw_props.exposure, -5.0..=5.0)
+                .text(l10n.tr("exposure"))
+                .suffix(" EV"),
+        );
+        ui.add(egui::Slider::new(&mut draw_props.gamma, 1.0..=3.0).text(l10n.tr("gamma")));
+        ui.add_enabled(
+            !draw_props.auto_quality_enabled,
+            egui::Slider::new(&mut draw_props.render_scale, 0.25..=1.0)
+                .text(l10n.tr("render-scale"))
+                .custom_formatter(|scale, _| format!("{:.0}%", scale * 100.0)),
+        );
+        ui.checkbox(
+            &mut draw_props.auto_quality_enabled,
+            l10n.tr("auto-quality"),
+        )
+        .on_hover_text(l10n.tr("auto-quality-hint"));
+        ui.add_enabled(
+            draw_props.auto_quality_enabled,
+            egui::Slider::new(&mut draw_props.target_frame_rate, 15.0..=144.0)
+                .text(l10n.tr("target-frame-rate"))
+                .suffix(" FPS"),
+        );
+        if draw_props.reduced_effects {
+            ui.label(l10n.tr("reduced-effects"));
+        }
+        ui.checkbox(&mut draw_props.lod_enabled, l10n.tr("level-of-detail"));
+        ui.checkbox(
+            &mut draw_props.occlusion_culling_enabled,
+            l10n.tr("occlusion-culling"),
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.add_enabled(
+            self.gl_info.supports_gl43(),
+            egui::Checkbox::new(
+                &mut draw_props.multi_draw_indirect_enabled,
+                l10n.tr("multi-draw-indirect"),
+            ),
+        )
+        .on_hover_text(l10n.tr("multi-draw-indirect-hint"))
+        .on_disabled_hover_text(l10n.tr("multi-draw-indirect-unsupported"));
+        egui::ComboBox::from_label(l10n.tr("stereo-mode"))
+            .selected_text(l10n.tr(draw_props.stereo_mode.l10n_id()))
+            .show_ui(ui, |ui| {
+                for stereo_mode in StereoMode::ALL {
+                    ui.selectable_value(
+                        &mut draw_props.stereo_mode,
+                        stereo_mode,
+                        l10n.tr(stereo_mode.l10n_id()),
+                    );
+                }
+            });
+        ui.add_enabled(
+            draw_props.stereo_mode != StereoMode::Off,
+            egui::Slider::new(&mut draw_props.eye_separation, 0.0..=0.3)
+                .text(l10n.tr("eye-separation")),
+        );
+
+        // A/B comparison
+        egui::CollapsingHeader::new(l10n.tr("comparison"))
+            .default_open(false)
+            .show(ui, |ui| {
+                let mut comparison_enabled = self.scene.comparison.is_some();
+                if ui
+                    .checkbox(&mut comparison_enabled, l10n.tr("comparison-enabled"))
+                    .on_hover_text(l10n.tr("comparison-enabled-hint"))
+                    .changed()
+                {
+                    self.scene.comparison = comparison_enabled
+                        .then(|| Comparison::capture(draw_props, &self.scene.world));
+                }
+                if let Some(comparison) = &mut self.scene.comparison {
+                    ui.add(
+                        egui::Slider::new(&mut comparison.split, 0.0..=1.0)
+                            .text(l10n.tr("comparison-split"))
+                            .custom_formatter(|split, _| format!("{:.0}%", split * 100.0)),
+                    );
+                    if ui
+                        .button(l10n.tr("comparison-swap"))
+                        .on_hover_text(l10n.tr("comparison-swap-hint"))
+                        .clicked()
+                    {
+                        comparison.swap(draw_props, &mut self.scene.world);
+                    }
+                }
+            });
+
+        // Interface
+        egui::CollapsingHeader::new(l10n.tr("interface"))
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(l10n.tr_args(
+                    "gui-scale",
+                    &fluent_args!["percent" => format!("{:.0}", ui.ctx().zoom_factor() * 100.0)],
+                ));
+                // Buttons instead of a slider, which would move away from the pointer when rescaled
+                egui::gui_zoom::zoom_menu_buttons(ui);
+
+                ui.horizontal(|ui| {
+                    ui.label(l10n.tr("theme"));
+                    let previous_theme = *self.theme;
+                    ui.selectable_value(self.theme, Theme::Dark, l10n.tr("theme-dark"));
+                    ui.selectable_value(self.theme, Theme::Light, l10n.tr("theme-light"));
+                    if *self.theme != previous_theme {
+                        ui.ctx().set_visuals(self.theme.visuals());
+                    }
+                });
+
+                egui::ComboBox::from_label(l10n.tr("language"))
+                    .selected_text(draw_props.language.native_name())
+                    .show_ui(ui, |ui| {
+                        for language in Language::ALL {
+                            ui.selectable_value(
+                                &mut draw_props.language,
+                                language,
+                                language.native_name(),
+                            );
+                        }
+                    });
+            });
+    }
+
+    /// Motion blur followed by post effects listed in the order they are applied, with buttons
+    /// moving them in the order.
+    fn post_processing_panel(&mut self, ui: &mut egui::Ui) {
+        let l10n = self.l10n;
+        let settings = &mut self.draw_props.post_effect_settings;
+        ui.checkbox(&mut settings.motion_blur_enabled, l10n.tr("motion-blur"))
+            .on_hover_text(l10n.tr("motion-blur-hint"));
+        ui.add_enabled(
+            settings.motion_blur_enabled,
+            egui::Slider::new(&mut settings.shutter_angle, 0.0..=360.0)
+                .suffix("°")
+                .text(l10n.tr("shutter-angle")),
+        )
+        .on_hover_text(l10n.tr("shutter-angle-hint"));
+        ui.separator();
+
+        let mut moved = None;
+        for (position, effect) in settings.order.into_iter().enumerate() {
+            if 0 < position {
+                ui.separator();
+            }
+            ui.horizontal(|ui| {
+                let enabled = match effect {
+                    PostEffect::Vignette => &mut settings.vignette_enabled,
+                    PostEffect::FilmGrain => &mut settings.film_grain_enabled,
+                    PostEffect::ChromaticAberration => &mut settings.chromatic_aberration_enabled,
+                };
+                ui.checkbox(enabled, l10n.tr(effect.l10n_id()));
+                if ui
+                    .add_enabled(0 < position, egui::Button::new("⬆").small())
+                    .on_hover_text(l10n.tr("post-effect-earlier"))
+                    .clicked()
+                {
+                    moved = Some((position, true));
+                }
+                if ui
+                    .add_enabled(
+                        position + 1 < settings.order.len(),
+                        egui::Button::new("⬇").small(),
+                    )
+                    .on_hover_text(l10n.tr("post-effect-later"))
+                    .clicked()
+                {
+                    moved = Some((position, false));
+                }
+            });
+            let enabled = settings.enabled(effect);
+            let slider = match effect {
+                PostEffect::Vignette => {
+                    egui::Slider::new(&mut settings.vignette_strength, 0.0..=1.0)
+                        .text(l10n.tr("vignette-strength"))
+                }
+                PostEffect::FilmGrain => {
+                    egui::Slider::new(&mut settings.film_grain_intensity, 0.0..=0.5)
+                        .text(l10n.tr("film-grain-intensity"))
+                }
+                PostEffect::ChromaticAberration => {
+                    egui::Slider::new(&mut settings.chromatic_aberration_strength, 0.0..=20.0)
+                        .text(l10n.tr("chromatic-aberration-strength"))
+                }
+            };
+            ui.add_enabled(enabled, slider);
+        }
+        if let Some((position, earlier)) = moved {
+            settings.move_effect(position, earlier);
+        }
+        ui.separator();
+        ui.label(l10n.tr("post-effects-hint"));
+    }
+
+    fn time_panel(&mut self, ui: &mut egui::Ui) {
+        let l10n = self.l10n;
+        let draw_props = &mut *self.draw_props;
+
+        ui.checkbox(&mut draw_props.paused, l10n.tr("pause"));
+        ui.add(
+            egui::Slider::new(&mut draw_props.time_scale, 0.1..=4.0)
+                .logarithmic(true)
+                .text(l10n.tr("time-scale")),
+        )
+        .on_hover_text(l10n.tr("time-scale-hint"));
+        if ui
+            .add_enabled(draw_props.paused, egui::Button::new(l10n.tr("step")))
+            .on_hover_text(l10n.tr("step-hint"))
+            .clicked()
+        {
+            draw_props.step_requested = true;
+        }
+    }
+
+    fn timeline_panel(&mut self, ui: &mut egui::Ui) {
+        let l10n = self.l10n;
+        let draw_props = &mut *self.draw_props;
+        let scene = &mut *self.scene;
+        let editor = &mut *self.timeline_editor;
+
+        ui.horizontal(|ui| {
+            let play_l10n_id = if scene.timeline.playing {
+                "timeline-pause"
+            } else {
+                "timeline-play"
+            };
+            if ui.button(l10n.tr(play_l10n_id)).clicked() {
+                // Start over once the end was reached
+                if !scene.timeline.playing && scene.timeline.duration <= scene.timeline.time() {
+                    scene.seek_timeline(0.0, draw_props);
+                }
+                scene.timeline.playing = !scene.timeline.playing;
+            }
+            if ui.button(l10n.tr("timeline-rewind")).clicked() {
+                scene.seek_timeline(0.0, draw_props);
+            }
+            ui.checkbox(&mut scene.timeline.looping, l10n.tr("timeline-loop"));
+        });
+        let mut time = scene.timeline.time();
+        if ui
+            .add(
+                egui::Slider::new(&mut time, 0.0..=scene.timeline.duration)
+                    .text(l10n.tr("timeline-time"))
+                    .suffix(" s"),
+            )
+            .changed()
+        {
+            scene.seek_timeline(time, draw_props);
+        }
+        ui.add(
+            egui::Slider::new(&mut scene.timeline.duration, 0.5..=60.0)
+                .text(l10n.tr("timeline-duration"))
+                .suffix(" s"),
+        );
+
+        // Entities are keyframed once activated in the hierarchy
+        let mut properties = AnimatedProperty::DRAW_PROPERTIES.to_vec();
+        if let Some(entity) = *self.active_entity {
+            if scene.world.satisfies::<&Transform>(entity).unwrap_or(false) {
+                properties.push(AnimatedProperty::EntityPosition(entity));
+                properties.push(AnimatedProperty::EntityRotation(entity));
+            }
+        }
+        if !properties.contains(&editor.property) {
+            editor.property = properties[0];
+        }
+        ui.separator();
+        egui::ComboBox::from_label(l10n.tr("timeline-property"))
+            .selected_text(animated_property_label(l10n, scene, editor.property))
+            .show_ui(ui, |ui| {
+                for property in properties {
+                    ui.selectable_value(
+                        &mut editor.property,
+                        property,
+                        animated_property_label(l10n, scene, property),
+                    );
+                }
+            })
+            .response
+            .on_hover_text(l10n.tr("timeline-property-hint"));
+        egui::ComboBox::from_label(l10n.tr("keyframe-easing"))
+            .selected_text(l10n.tr(editor.easing.l10n_id()))
+            .show_ui(ui, |ui| {
+                for easing in Easing::ALL {
+                    ui.selectable_value(&mut editor.easing, easing, l10n.tr(easing.l10n_id()));
+                }
+            })
+            .response
+            .on_hover_text(l10n.tr("keyframe-easing-hint"));
+        if ui
+            .button(l10n.tr("add-keyframe"))
+            .on_hover_text(l10n.tr("add-keyframe-hint"))
+            .clicked()
+        {
+            scene
+                .timeline
+                .add_keyframe(editor.property, editor.easing, draw_props, &scene.world);
+        }
+
+        let mut removed = None;
+        let mut seek = None;
+        for (track_index, track) in scene.timeline.tracks.iter().enumerate() {
+            ui.separator();
+            ui.strong(animated_property_label(l10n, scene, track.property));
+            for (index, keyframe) in track.keyframes().iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(format!("{:.2} s", keyframe.time))
+                        .on_hover_text(l10n.tr("go-to-keyframe"))
+                        .clicked()
+                    {
+                        seek = Some(keyframe.time);
+                    }
+                    ui.label(l10n.tr(keyframe.easing.l10n_id()));
+                    if ui.button(l10n.tr("remove-keyframe")).clicked() {
+                        removed = Some((track_index, index));
+                    }
+                });
+            }
+        }
+        if let Some((track_index, index)) = removed {
+            let track = &mut scene.timeline.tracks[track_index];
+            track.remove_keyframe(index);
+            if track.keyframes().is_empty() {
+                scene.timeline.tracks.remove(track_index);
+            }
+        }
+        if let Some(time) = seek {
+            scene.seek_timeline(time, draw_props);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut editor.export_directory).desired_width(160.0),
+                );
+                ui.label(l10n.tr("export-directory"));
+            });
+            ui.add(
+                egui::Slider::new(&mut editor.export_frame_rate, 1.0..=120.0)
+                    .text(l10n.tr("export-frame-rate")),
+            );
+            let directory = editor.export_directory.trim();
+            if ui
+                .add_enabled(
+                    !directory.is_empty(),
+                    egui::Button::new(l10n.tr("export-animation")),
+                )
+                .on_hover_text(l10n.tr("export-animation-hint"))
+                .clicked()
+            {
+                self.actions.push(GuiAction::ExportAnimation(
+                    directory.to_string(),
+                    editor.export_frame_rate,
+                ));
+            }
+        }
+
+        ui.separator();
+        egui::CollapsingHeader::new(l10n.tr("modulation"))
+            .default_open(false)
+            .show(ui, |ui| {
+                let source_count = scene.modulation.levels().len();
+                if source_count == 0 {
+                    ui.label(l10n.tr("modulation-no-sources"));
+                }
+                for (index, &level) in scene.modulation.levels().iter().enumerate() {
+                    ui.add(egui::ProgressBar::new(level).text(
+                        l10n.tr_args("modulation-source", &fluent_args!["index" => index + 1]),
+                    ));
+                }
+
+                let mut removed = None;
+                for (index, binding) in scene.modulation.bindings.iter_mut().enumerate() {
+                    ui.separator();
+                    let mut edited = *binding;
+                    egui::ComboBox::new(("modulation_source", index), l10n.tr("modulation-from"))
+                        .selected_text(l10n.tr_args(
+                            "modulation-source",
+                            &fluent_args!["index" => edited.source + 1],
+                        ))
+                        .show_ui(ui, |ui| {
+                            for source in 0..source_count.max(edited.source + 1) {
+                                ui.selectable_value(
+                                    &mut edited.source,
+                                    source,
+                                    l10n.tr_args(
+                                        "modulation-source",
+                                        &fluent_args!["index" => source + 1],
+                                    ),
+                                );
+                            }
                         });
+                    egui::ComboBox::new(("modulation_target", index), l10n.tr("modulation-to"))
+                        .selected_text(l10n.tr(edited.target.l10n_id()))
+                        .show_ui(ui, |ui| {
+                            for target in ModulationTarget::ALL {
+                                ui.selectable_value(
+                                    &mut edited.target,
+                                    target,
+                                    l10n.tr(target.l10n_id()),
+                                );
+                            }
+                        });
+                    // Offset of the previous target is taken back before driving the new one
+                    if edited.source != binding.source || edited.target != binding.target {
+                        binding.revert(draw_props, &mut scene.world);
+                        binding.source = edited.source;
+                        binding.target = edited.target;
+                        let range = binding.target.amount_range();
+                        binding.amount = binding.amount.clamp(*range.start(), *range.end());
+                    }
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::Slider::new(&mut binding.amount, binding.target.amount_range())
+                                .text(l10n.tr("modulation-amount")),
+                        );
+                        if ui.button(l10n.tr("remove-modulation")).clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = removed {
+                    scene
+                        .modulation
+                        .remove_binding(index, draw_props, &mut scene.world);
+                }
+                if ui.button(l10n.tr("add-modulation")).clicked() {
+                    scene.modulation.bindings.push(ModulationBinding::new(
+                        0,
+                        ModulationTarget::RotationSpeed,
+                        90.0,
+                    ));
+                }
+            });
+    }
+
+    fn stats_panel(&mut self, ui: &mut egui::Ui) {
+        let l10n = self.l10n;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.label(l10n.tr_args(
+            "frame-rate",
+            &fluent_args![
+                "fps" => format!("{:.2}", self.frame_rate_info.frames_per_second),
+                "ms" => format!("{:.6}", self.frame_rate_info.ms_per_frame),
+            ],
+        ));
+
+        // Level of detail
+        egui::CollapsingHeader::new(l10n.tr("level-of-detail"))
+            .default_open(true)
+            .show(ui, |ui| {
+                let lod_stats = &self.render_stats.lod;
+                if let Some(level) = lod_stats.selected_model_level {
+                    ui.label(l10n.tr_args("lod-selected-model", &fluent_args!["level" => level]));
+                }
+                for (level, count) in lod_stats.entities_per_level.iter().enumerate() {
+                    ui.label(l10n.tr_args(
+                        "lod-entities",
+                        &fluent_args!["level" => level, "count" => *count],
+                    ));
+                }
+                ui.label(l10n.tr_args(
+                    "lod-triangles",
+                    &fluent_args!["count" => lod_stats.triangle_count],
+                ));
+            });
+
+        // Occlusion culling
+        egui::CollapsingHeader::new(l10n.tr("occlusion-culling"))
+            .default_open(true)
+            .show(ui, |ui| {
+                if !self.draw_props.occlusion_culling_enabled {
+                    ui.label(l10n.tr("occlusion-culling-disabled"));
+                    return;
+                }
+                let occlusion_stats = &self.render_stats.occlusion;
+                ui.label(l10n.tr_args(
+                    "occlusion-queried",
+                    &fluent_args!["count" => occlusion_stats.queried_count],
+                ));
+                ui.label(l10n.tr_args(
+                    "occlusion-occluded",
+                    &fluent_args!["count" => occlusion_stats.occluded_count],
+                ));
+                ui.label(l10n.tr_args(
+                    "occlusion-culled-triangles",
+                    &fluent_args!["count" => occlusion_stats.culled_triangle_count],
+                ));
+            });
+
+        // Bounding volume hierarchies
+        #[cfg(not(target_arch = "wasm32"))]
+        egui::CollapsingHeader::new(l10n.tr("bvh"))
+            .default_open(false)
+            .show(ui, |ui| {
+                let mut built = false;
+                for model in &self.scene.models {
+                    let Some(stats) = model.lod(0).bvh_stats() else {
+                        continue;
+                    };
+                    built = true;
+                    ui.label(l10n.tr_args(
+                        "bvh-model",
+                        &fluent_args![
+                            "name" => model.name.as_str(),
+                            "triangles" => stats.triangle_count,
+                            "nodes" => stats.node_count,
+                            "leaves" => stats.leaf_count,
+                            "depth" => stats.max_depth,
+                            "leaf_triangles" => stats.max_leaf_triangles,
+                            "ms" => format!("{:.2}", stats.build_time.as_secs_f64() * 1000.0),
+                        ],
+                    ));
+                }
+                if !built {
+                    ui.label(l10n.tr("bvh-none"));
+                }
+            });
+
+        // Mesh validation
+        #[cfg(not(target_arch = "wasm32"))]
+        egui::CollapsingHeader::new(l10n.tr("mesh-validation"))
+            .default_open(false)
+            .show(ui, |ui| {
+                let model = &self.scene.models[self.draw_props.selected_model_index];
+                let mesh = model.lod(0);
+                let Some(validation) = mesh.validation() else {
+                    if ui
+                        .button(l10n.tr_args(
+                            "mesh-validation-analyze",
+                            &fluent_args!["name" => model.name.as_str()],
+                        ))
+                        .clicked()
+                    {
+                        if let Err(e) = mesh.validate() {
+                            self.errors.push(e);
+                        }
+                    }
+                    return;
+                };
+                ui.label(model.name.as_str());
+                egui::Grid::new("mesh_validation_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for (l10n_id, count) in [
+                            (
+                                "mesh-validation-non-manifold",
+                                validation.non_manifold_edges,
+                            ),
+                            ("mesh-validation-boundary", validation.boundary_edges),
+                            (
+                                "mesh-validation-degenerate",
+                                validation.degenerate_triangles,
+                            ),
+                            ("mesh-validation-flipped", validation.flipped_triangles),
+                            ("mesh-validation-duplicate", validation.duplicate_vertices),
+                        ] {
+                            ui.label(l10n.tr(l10n_id));
+                            ui.label(count.to_string());
+                            ui.end_row();
+                        }
+                    });
+                if !validation.has_problems() {
+                    ui.label(l10n.tr("mesh-validation-clean"));
+                }
+                ui.checkbox(
+                    &mut self.draw_props.mesh_problems_enabled,
+                    l10n.tr("mesh-validation-highlight"),
+                )
+                .on_hover_text(l10n.tr("mesh-validation-highlight-hint"));
+            });
+
+        // Terrain
+        if self.scene.terrain.is_some() {
+            egui::CollapsingHeader::new(l10n.tr("terrain"))
+                .default_open(true)
+                .show(ui, |ui| {
+                    let terrain_stats = &self.render_stats.terrain;
+                    ui.label(l10n.tr_args(
+                        "terrain-tiles",
+                        &fluent_args![
+                            "drawn" => terrain_stats.drawn_tile_count,
+                            "count" => terrain_stats.tile_count,
+                        ],
+                    ));
                 });
+        }
+
+        // Assets
+        egui::CollapsingHeader::new(l10n.tr("assets"))
+            .default_open(true)
+            .show(ui, |ui| {
+                let usage = self.asset_cache.memory_usage();
+                ui.label(l10n.tr_args(
+                    "assets-meshes",
+                    &fluent_args![
+                        "count" => usage.mesh_count,
+                        "size" => format_bytes(usage.mesh_bytes),
+                    ],
+                ));
+                ui.label(l10n.tr_args(
+                    "assets-skyboxes",
+                    &fluent_args![
+                        "count" => usage.skybox_count,
+                        "size" => format_bytes(usage.skybox_bytes),
+                    ],
+                ));
+                ui.label(l10n.tr_args(
+                    "vram-usage",
+                    &fluent_args!["size" => format_bytes(usage.total_bytes())],
+                ));
+                if ui.button(l10n.tr("assets-evict-unused")).clicked() {
+                    self.asset_cache.evict_unused();
+                }
+            });
+
+        // Resources
+        egui::CollapsingHeader::new(l10n.tr("resources"))
+            .default_open(true)
+            .show(ui, |ui| {
+                let resource_stats = gl_resources::stats();
+                for (kind, stats) in &resource_stats {
+                    let kind_label = l10n.tr(kind.l10n_id());
+                    ui.label(l10n.tr_args(
+                        "resource-count",
+                        &fluent_args![
+                            "kind" => kind_label.as_str(),
+                            "count" => stats.live_count,
+                            "size" => format_bytes(stats.bytes),
+                        ],
+                    ))
+                    .on_hover_text(l10n.tr_args(
+                        "resource-lifetime",
+                        &fluent_args![
+                            "created" => stats.created_count,
+                            "deleted" => stats.deleted_count,
+                        ],
+                    ));
+                    if stats.leak_suspected {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            l10n.tr_args(
+                                "resource-leak",
+                                &fluent_args!["kind" => kind_label.as_str()],
+                            ),
+                        );
+                    }
+                }
+                let total_bytes: usize = resource_stats.iter().map(|(_, stats)| stats.bytes).sum();
+                ui.label(l10n.tr_args(
+                    "vram-usage",
+                    &fluent_args!["size" => format_bytes(total_bytes)],
+                ));
+            });
+
+        // Draw calls
+        egui::CollapsingHeader::new(l10n.tr("draws"))
+            .default_open(true)
+            .show(ui, |ui| {
+                let draw_stats = &self.render_stats.draws;
+                ui.label(l10n.tr_args(
+                    "draw-calls",
+                    &fluent_args!["count" => draw_stats.draw_call_count],
+                ));
+                ui.label(l10n.tr_args(
+                    "draw-triangles",
+                    &fluent_args!["count" => draw_stats.triangle_count],
+                ));
+            });
+
+        // Vertices and fragments counted by the GPU
+        #[cfg(not(target_arch = "wasm32"))]
+        egui::CollapsingHeader::new(l10n.tr("pipeline-statistics"))
+            .default_open(true)
+            .show(ui, |ui| match &self.render_stats.pipeline {
+                Some(pipeline_stats) => {
+                    ui.label(l10n.tr_args(
+                        "pipeline-vertices",
+                        &fluent_args!["count" => pipeline_stats.vertices_submitted],
+                    ));
+                    ui.label(l10n.tr_args(
+                        "pipeline-fragments",
+                        &fluent_args!["count" => pipeline_stats.fragments_shaded],
+                    ));
+                }
+                None if self.gl_info.supports_pipeline_statistics() => {
+                    ui.label(l10n.tr("pipeline-statistics-pending"));
+                }
+                None => {
+                    ui.label(l10n.tr("pipeline-statistics-unsupported"));
+                }
+            });
+
+        // State changes
+        egui::CollapsingHeader::new(l10n.tr("gl-state"))
+            .default_open(true)
+            .show(ui, |ui| {
+                let state_stats = gl_state::stats();
+                ui.label(l10n.tr_args(
+                    "gl-state-changes",
+                    &fluent_args!["count" => state_stats.change_count],
+                ));
+                ui.label(l10n.tr_args(
+                    "gl-state-redundant",
+                    &fluent_args!["count" => state_stats.redundant_count],
+                ));
+            });
+    }
+}
+
+/// Change of the scene hierarchy, applied after the hierarchy is displayed.
+enum NodeAction {
+    SetVisible(Entity, bool),
+    Activate(Entity),
+    AddInstance(usize),
+    Duplicate(Entity),
+    Delete(Entity),
+}
+
+/// Row of an entity in the scene hierarchy. Name of the active entity can be edited.
+fn hierarchy_node(
+    ui: &mut egui::Ui,
+    l10n: &Localization,
+    scene: &Scene,
+    entity: Entity,
+    active: bool,
+) -> Option<NodeAction> {
+    let mut action = None;
+    ui.push_id(entity.id(), |ui| {
+        ui.horizontal(|ui| {
+            let mut visible = scene.world.satisfies::<&Visible>(entity).unwrap_or(false);
+            if ui
+                .toggle_value(&mut visible, "👁")
+                .on_hover_text(l10n.tr("hierarchy-visible"))
+                .changed()
+            {
+                action = Some(NodeAction::SetVisible(entity, visible));
+            }
+            match scene.world.get::<&mut Name>(entity) {
+                Ok(mut name) if active => {
+                    ui.add(egui::TextEdit::singleline(&mut name.0).desired_width(140.0));
+                }
+                _ => {
+                    if ui
+                        .selectable_label(active, scene.entity_name(entity))
+                        .clicked()
+                    {
+                        action = Some(NodeAction::Activate(entity));
+                    }
+                }
+            }
+            if ui
+                .small_button("📋")
+                .on_hover_text(l10n.tr("hierarchy-duplicate"))
+                .clicked()
+            {
+                action = Some(NodeAction::Duplicate(entity));
+            }
+            if ui
+                .small_button("🗑")
+                .on_hover_text(l10n.tr("hierarchy-delete"))
+                .clicked()
+            {
+                action = Some(NodeAction::Delete(entity));
+            }
         });
+    });
+    action
+}
+
+/// Contents of the Controls window, listing keyboard and mouse bindings.
+fn material_editor(
+    ui: &mut egui::Ui,
+    l10n: &Localization,
+    material: &mut Material,
+    custom_shader_names: &[&str],
+) {
+    ui.horizontal(|ui| {
+        ui.label(l10n.tr("material-name"));
+        ui.text_edit_singleline(&mut material.name);
+    });
+    ui.color_edit_button_rgb(&mut material.color);
+
+    egui::ComboBox::from_label(l10n.tr("material-type"))
+        .selected_text(l10n.tr(material.material_type.l10n_id()))
+        .show_ui(ui, |ui| {
+            for material_type in MaterialType::ALL {
+                ui.selectable_value(
+                    &mut material.material_type,
+                    material_type,
+                    l10n.tr(material_type.l10n_id()),
+                );
+            }
+        });
+    if material.material_type == MaterialType::Refractive {
+        ui.add(
+            egui::Slider::new(&mut material.refractive_index, 1.0..=2.5)
+                .text(l10n.tr("refractive-index")),
+        );
     }
+    ui.checkbox(&mut material.diffuse_enabled, l10n.tr("diffuse"));
+    ui.checkbox(&mut material.specular_enabled, l10n.tr("specular"));
+    egui::ComboBox::from_label(l10n.tr("vertex-colors"))
+        .selected_text(l10n.tr(material.vertex_colors.l10n_id()))
+        .show_ui(ui, |ui| {
+            for vertex_color_mode in VertexColorMode::ALL {
+                ui.selectable_value(
+                    &mut material.vertex_colors,
+                    vertex_color_mode,
+                    l10n.tr(vertex_color_mode.l10n_id()),
+                );
+            }
+        })
+        .response
+        .on_hover_text(l10n.tr("vertex-colors-hint"));
 
-    pub fn draw(&mut self, window: &winit::window::Window) {
-        self.egui_glow.paint(&window);
+    let builtin_shader = l10n.tr("material-shader-builtin");
+    egui::ComboBox::from_label(l10n.tr("material-shader"))
+        .selected_text(material.shader.clone().unwrap_or(builtin_shader.clone()))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut material.shader, None, builtin_shader);
+            for name in custom_shader_names {
+                ui.selectable_value(&mut material.shader, Some(name.to_string()), *name);
+            }
+        })
+        .response
+        .on_hover_text(l10n.tr("material-shader-hint"));
+}
+
+/// Translated name of the property, with the name of the entity it belongs to.
+fn animated_property_label(
+    l10n: &Localization,
+    scene: &Scene,
+    property: AnimatedProperty,
+) -> String {
+    match property.entity() {
+        Some(entity) => l10n.tr_args(
+            property.l10n_id(),
+            &fluent_args!["name" => scene.entity_name(entity)],
+        ),
+        None => l10n.tr(property.l10n_id()),
+    }
+}
+
+fn show_controls(ui: &mut egui::Ui, l10n: &Localization) {
+    let mut message_ids = vec![
+        "controls-movement",
+        "controls-mouse-look",
+        "controls-move-light",
+        "controls-ascend",
+        "controls-descend",
+        "controls-roll",
+        "controls-fullscreen",
+    ];
+    #[cfg(not(target_arch = "wasm32"))]
+    message_ids.extend(["controls-screenshot", "controls-quit"]);
+    for message_id in message_ids {
+        ui.label(format!("• {}", l10n.tr(message_id)));
+    }
+}
+
+fn toggle_fullscreen(window: &Window) {
+    if window.fullscreen().is_some() {
+        window.set_fullscreen(None);
+    } else {
+        window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+    }
+}
+
+/// Line dragged over the scene between the sides of the comparison, which stays where the window
+/// is covered by panels.
+fn show_comparison_split(egui_ctx: &egui::Context, comparison: &mut Comparison) {
+    const HANDLE_WIDTH: f32 = 8.0;
+    // Split is relative to the whole window the scene is drawn into
+    let screen_rect = egui_ctx.screen_rect();
+    let scene_rect = egui_ctx.available_rect();
+    let x = screen_rect.left() + comparison.split * screen_rect.width();
+    if !scene_rect.x_range().contains(x) {
+        return;
+    }
+    egui::Area::new(egui::Id::new("comparison_split"))
+        .order(egui::Order::Background)
+        .fixed_pos([x - HANDLE_WIDTH / 2.0, scene_rect.top()])
+        .show(egui_ctx, |ui| {
+            let (rect, response) = ui.allocate_exact_size(
+                egui::vec2(HANDLE_WIDTH, scene_rect.height()),
+                egui::Sense::drag(),
+            );
+            let response = response.on_hover_cursor(egui::CursorIcon::ResizeHorizontal);
+            if let Some(pointer) = response
+                .interact_pointer_pos()
+                .filter(|_| response.dragged())
+            {
+                comparison.split =
+                    ((pointer.x - screen_rect.left()) / screen_rect.width()).clamp(0.0, 1.0);
+            }
+
+            let visuals = ui.style().interact(&response);
+            let painter = ui.painter();
+            painter.vline(rect.center().x, rect.y_range(), visuals.fg_stroke);
+            let label_y = scene_rect.top() + 16.0;
+            for (label, align, offset) in [
+                ("A", egui::Align2::RIGHT_CENTER, -HANDLE_WIDTH),
+                ("B", egui::Align2::LEFT_CENTER, HANDLE_WIDTH),
+            ] {
+                painter.text(
+                    egui::pos2(rect.center().x + offset, label_y),
+                    align,
+                    label,
+                    egui::FontId::proportional(16.0),
+                    visuals.fg_stroke.color,
+                );
+            }
+        });
+}
+
+/// Tooltip next to the cursor listing vertices of the triangle under it.
+#[cfg(not(target_arch = "wasm32"))]
+fn show_inspected_triangle(
+    egui_ctx: &egui::Context,
+    l10n: &Localization,
+    inspected: &InspectedTriangle,
+) {
+    let Some(pointer) = egui_ctx.pointer_hover_pos() else {
+        return;
+    };
+    let format_vector = |v: cgmath::Vector3<f32>| format!("{:.4}, {:.4}, {:.4}", v.x, v.y, v.z);
+    egui::Area::new(egui::Id::new("inspected_triangle"))
+        .order(egui::Order::Tooltip)
+        .fixed_pos(pointer + egui::vec2(16.0, 16.0))
+        .interactable(false)
+        .show(egui_ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let triangle = &inspected.triangle;
+                ui.strong(l10n.tr_args(
+                    "inspector-triangle",
+                    &fluent_args!["index" => triangle.index],
+                ));
+                egui::Grid::new("inspected_vertices")
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        ui.label(l10n.tr("inspector-index"));
+                        ui.label(l10n.tr("inspector-position"));
+                        ui.label(l10n.tr("inspector-normal"));
+                        ui.end_row();
+                        for (index, vertex) in
+                            triangle.vertex_indices.iter().zip(&triangle.vertices)
+                        {
+                            ui.monospace(index.to_string());
+                            ui.monospace(format_vector(vertex.position));
+                            ui.monospace(format_vector(vertex.normal));
+                            ui.end_row();
+                        }
+                    });
+                ui.label(l10n.tr_args(
+                    "inspector-face-normal",
+                    &fluent_args!["normal" => format_vector(inspected.face_normal())],
+                ));
+                if inspected.is_flipped() {
+                    ui.colored_label(ui.visuals().warn_fg_color, l10n.tr("inspector-flipped"));
+                }
+            });
+        });
+}
+
+/// Triangle edges in texture space over the 0-1 texture range, with overlapping triangles filled.
+/// Texture coordinate v points up, as in OpenGL.
+#[cfg(not(target_arch = "wasm32"))]
+fn show_uv_layout(ui: &mut egui::Ui, l10n: &Localization, uv_layout: &UvLayout) {
+    let overlap_color = ui.visuals().error_fg_color;
+    if uv_layout.overlapping_triangles > 0 {
+        ui.colored_label(
+            overlap_color,
+            l10n.tr_args(
+                "uv-layout-overlapping",
+                &fluent_args![
+                    "overlapping" => uv_layout.overlapping_triangles,
+                    "triangles" => uv_layout.triangle_count,
+                ],
+            ),
+        );
+    } else {
+        ui.label(l10n.tr_args(
+            "uv-layout-clean",
+            &fluent_args!["triangles" => uv_layout.triangle_count],
+        ));
+    }
+
+    let side = ui.available_width().min(ui.available_height()).max(64.0);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(side, side), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+    // Bounds are scaled uniformly into the square, keeping texel proportions
+    let (min, max) = uv_layout.bounds();
+    let scale = rect.width() / (max.x - min.x).max(max.y - min.y);
+    let to_screen = |uv: cgmath::Vector2<f32>| {
+        egui::pos2(
+            rect.left() + (uv.x - min.x) * scale,
+            rect.bottom() - (uv.y - min.y) * scale,
+        )
+    };
+
+    let mut overlap_mesh = egui::Mesh::default();
+    for triangle in uv_layout.overlapping() {
+        let first = overlap_mesh.vertices.len() as u32;
+        for uv in triangle {
+            overlap_mesh.colored_vertex(to_screen(*uv), overlap_color.gamma_multiply(0.5));
+        }
+        overlap_mesh.add_triangle(first, first + 1, first + 2);
     }
+    painter.add(overlap_mesh);
+    let edge_stroke = egui::Stroke::new(1.0, ui.visuals().text_color().gamma_multiply(0.6));
+    painter.extend(uv_layout.edges().iter().map(|[start, end]| {
+        egui::Shape::line_segment([to_screen(*start), to_screen(*end)], edge_stroke)
+    }));
+    let texture_range = egui::Rect::from_two_pos(
+        to_screen(cgmath::Vector2::new(0.0, 0.0)),
+        to_screen(cgmath::Vector2::new(1.0, 1.0)),
+    );
+    painter.rect_stroke(
+        texture_range,
+        0.0,
+        egui::Stroke::new(1.5, ui.visuals().selection.stroke.color),
+    );
+}
+
+/// File operation of the menu bar waiting for the path to be entered.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Copy)]
+enum PathPromptKind {
+    OpenModel,
+    OpenSkybox,
+    OpenTerrain,
+    AddDecal,
+    SaveScene,
+    ExportModel,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PathPromptKind {
+    const ALL: [PathPromptKind; 6] = [
+        PathPromptKind::OpenModel,
+        PathPromptKind::OpenSkybox,
+        PathPromptKind::OpenTerrain,
+        PathPromptKind::AddDecal,
+        PathPromptKind::SaveScene,
+        PathPromptKind::ExportModel,
+    ];
+
+    fn menu_l10n_id(self) -> &'static str {
+        match self {
+            PathPromptKind::OpenModel => "menu-open-model",
+            PathPromptKind::OpenSkybox => "menu-open-skybox",
+            PathPromptKind::OpenTerrain => "menu-open-terrain",
+            PathPromptKind::AddDecal => "menu-add-decal",
+            PathPromptKind::SaveScene => "menu-save-scene",
+            PathPromptKind::ExportModel => "menu-export-model",
+        }
+    }
+
+    fn title_l10n_id(self) -> &'static str {
+        match self {
+            PathPromptKind::OpenModel => "prompt-open-model",
+            PathPromptKind::OpenSkybox => "prompt-open-skybox",
+            PathPromptKind::OpenTerrain => "prompt-open-terrain",
+            PathPromptKind::AddDecal => "prompt-add-decal",
+            PathPromptKind::SaveScene => "prompt-save-scene",
+            PathPromptKind::ExportModel => "prompt-export-model",
+        }
+    }
+
+    fn hint_l10n_id(self) -> &'static str {
+        match self {
+            PathPromptKind::OpenModel => "prompt-open-model-hint",
+            PathPromptKind::OpenSkybox => "prompt-open-skybox-hint",
+            PathPromptKind::OpenTerrain => "prompt-open-terrain-hint",
+            PathPromptKind::AddDecal => "prompt-add-decal-hint",
+            PathPromptKind::SaveScene => "prompt-save-scene-hint",
+            PathPromptKind::ExportModel => "prompt-export-model-hint",
+        }
+    }
+}
+
+/// Window asking for the path of a file operation. There is no native file dialog, as it would
+/// depend on platform libraries.
+#[cfg(not(target_arch = "wasm32"))]
+struct PathPrompt {
+    kind: PathPromptKind,
+    path: String,
+    // Only used by model export, 0 is the full detail mesh
+    lod_level: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PathPrompt {
+    fn new(kind: PathPromptKind) -> Self {
+        let path = match kind {
+            PathPromptKind::SaveScene => "scene.ron".to_string(),
+            PathPromptKind::ExportModel => "model.obj".to_string(),
+            PathPromptKind::OpenModel
+            | PathPromptKind::OpenSkybox
+            | PathPromptKind::OpenTerrain
+            | PathPromptKind::AddDecal => String::new(),
+        };
+        Self {
+            kind,
+            path,
+            lod_level: 0,
+        }
+    }
+
+    /// Returns the chosen action once confirmed, or `Some(None)` when cancelled. Level of detail
+    /// of model export is chosen from the levels of the selected model.
+    fn show(
+        &mut self,
+        egui_ctx: &egui::Context,
+        l10n: &Localization,
+        lod_count: usize,
+    ) -> Option<Option<GuiAction>> {
+        let mut result = None;
+        egui::Window::new(l10n.tr(self.kind.title_l10n_id()))
+            .id(egui::Id::new("path_prompt_window"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(egui_ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.path)
+                        .hint_text(l10n.tr(self.kind.hint_l10n_id()))
+                        .desired_width(320.0),
+                );
+                let entered =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if let PathPromptKind::ExportModel = self.kind {
+                    self.lod_level = self.lod_level.min(lod_count - 1);
+                    ui.add_enabled(
+                        1 < lod_count,
+                        egui::Slider::new(&mut self.lod_level, 0..=lod_count - 1)
+                            .text(l10n.tr("level-of-detail")),
+                    );
+                }
+                ui.horizontal(|ui| {
+                    let path_valid = !self.path.trim().is_empty();
+                    let ok_clicked = ui
+                        .add_enabled(path_valid, egui::Button::new(l10n.tr("prompt-ok")))
+                        .clicked();
+                    if path_valid && (ok_clicked || entered) {
+                        let path = self.path.trim().to_string();
+                        result = Some(Some(match self.kind {
+                            PathPromptKind::OpenModel => GuiAction::OpenModel(path),
+                            PathPromptKind::OpenSkybox => GuiAction::OpenSkybox(path),
+                            PathPromptKind::OpenTerrain => GuiAction::OpenTerrain(path),
+                            PathPromptKind::AddDecal => GuiAction::AddDecal(path),
+                            PathPromptKind::SaveScene => GuiAction::SaveScene(path),
+                            PathPromptKind::ExportModel => {
+                                GuiAction::ExportModel(path, self.lod_level)
+                            }
+                        }));
+                    }
+                    if ui.button(l10n.tr("prompt-cancel")).clicked() {
+                        result = Some(None);
+                    }
+                });
+            });
+        result
+    }
+}
+
+/// Window editing GLSL sources of a custom shader, registering it with the renderer whenever the
+/// sources change. Compilation errors are shown in the window instead of the error window, as they
+/// are expected while typing.
+struct ShaderEditor {
+    name: String,
+    vertex_source: String,
+    fragment_source: String,
+    // Result of the last compilation, `None` before the first one
+    status: Option<Result<(), String>>,
+}
+
+impl ShaderEditor {
+    fn new((vertex_source, fragment_source): (&str, &str)) -> Self {
+        Self {
+            name: "custom".to_string(),
+            vertex_source: vertex_source.to_owned(),
+            fragment_source: fragment_source.to_owned(),
+            status: None,
+        }
+    }
+
+    fn show(
+        &mut self,
+        egui_ctx: &egui::Context,
+        l10n: &Localization,
+        open: &mut bool,
+        renderer: &mut Renderer,
+    ) {
+        egui::Window::new(l10n.tr("menu-shader-editor"))
+            .id(egui::Id::new("shader_editor_window"))
+            .open(open)
+            .default_pos([340.0, 40.0])
+            .default_size([480.0, 560.0])
+            .show(egui_ctx, |ui| {
+                let mut compile_requested = false;
+                ui.horizontal(|ui| {
+                    ui.label(l10n.tr("shader-name"));
+                    ui.text_edit_singleline(&mut self.name);
+                    let mut opened = None;
+                    egui::ComboBox::from_id_salt("shader_editor_open")
+                        .selected_text(l10n.tr("shader-open"))
+                        .show_ui(ui, |ui| {
+                            for name in renderer.custom_shader_names() {
+                                if ui.selectable_label(false, name).clicked() {
+                                    opened = Some(name.to_owned());
+                                }
+                            }
+                        });
+                    if let Some(name) = opened {
+                        let (vertex_source, fragment_source) =
+                            renderer.custom_shader_sources(&name).unwrap();
+                        self.vertex_source = vertex_source.to_owned();
+                        self.fragment_source = fragment_source.to_owned();
+                        self.name = name;
+                        self.status = None;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    compile_requested |= ui.button(l10n.tr("shader-compile")).clicked();
+                    if ui.button(l10n.tr("shader-reset")).clicked() {
+                        let (vertex_source, fragment_source) = renderer.custom_shader_template();
+                        self.vertex_source = vertex_source.to_owned();
+                        self.fragment_source = fragment_source.to_owned();
+                        compile_requested = true;
+                    }
+                });
+
+                match &self.status {
+                    Some(Ok(())) => {
+                        ui.colored_label(
+                            egui::Color32::LIGHT_GREEN,
+                            l10n.tr_args("shader-compiled", &fluent_args!["name" => &self.name]),
+                        );
+                    }
+                    Some(Err(e)) => {
+                        ui.colored_label(egui::Color32::LIGHT_RED, e);
+                    }
+                    None => (),
+                }
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (l10n_id, source) in [
+                        ("vertex-shader", &mut self.vertex_source),
+                        ("fragment-shader", &mut self.fragment_source),
+                    ] {
+                        egui::CollapsingHeader::new(l10n.tr(l10n_id))
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                compile_requested |= ui
+                                    .add(
+                                        egui::TextEdit::multiline(source)
+                                            .code_editor()
+                                            .desired_rows(12)
+                                            .desired_width(f32::INFINITY),
+                                    )
+                                    .changed();
+                            });
+                    }
+                });
+
+                if compile_requested {
+                    self.status = Some(renderer.register_shader(
+                        &self.name,
+                        &self.vertex_source,
+                        &self.fragment_source,
+                    ));
+                }
+            });
+    }
+}
+
+/// Window editing the fragment shader of built-in model shading, applied to every material without
+/// a custom shader. Lines referred to by compiler messages are highlighted.
+struct ModelShaderPlayground {
+    source: String,
+    // Result of the last apply, `None` before the first one
+    status: Option<Result<(), String>>,
+}
+
+impl ModelShaderPlayground {
+    fn new(source: &str) -> Self {
+        Self {
+            source: source.to_owned(),
+            status: None,
+        }
+    }
+
+    fn show(
+        &mut self,
+        egui_ctx: &egui::Context,
+        l10n: &Localization,
+        open: &mut bool,
+        renderer: &mut Renderer,
+    ) {
+        egui::Window::new(l10n.tr("menu-model-shader-playground"))
+            .id(egui::Id::new("model_shader_playground_window"))
+            .open(open)
+            .default_pos([340.0, 40.0])
+            .default_size([480.0, 560.0])
+            .show(egui_ctx, |ui| {
+                let mut apply_requested = false;
+                ui.horizontal(|ui| {
+                    let modified = self.source != renderer.model_fragment_source();
+                    apply_requested |= ui
+                        .add_enabled(modified, egui::Button::new(l10n.tr("shader-apply")))
+                        .clicked();
+                    if ui.button(l10n.tr("shader-revert")).clicked() {
+                        self.source = renderer.builtin_model_fragment_source().to_owned();
+                        apply_requested = true;
+                    }
+                });
+                if apply_requested {
+                    self.status = Some(renderer.set_model_fragment_source(&self.source));
+                }
+
+                let annotations = match &self.status {
+                    Some(Ok(())) => {
+                        ui.colored_label(egui::Color32::LIGHT_GREEN, l10n.tr("shader-applied"));
+                        Vec::new()
+                    }
+                    Some(Err(e)) => compiler_annotations(e),
+                    None => Vec::new(),
+                };
+                if let Some(Err(e)) = &self.status {
+                    if annotations.is_empty() {
+                        ui.colored_label(egui::Color32::LIGHT_RED, e);
+                    }
+                }
+                for (line, message) in &annotations {
+                    ui.colored_label(egui::Color32::LIGHT_RED, *message);
+                    let source_line = self.source.lines().nth(line - 1).unwrap_or_default();
+                    ui.monospace(format!("{line:>4} | {}", source_line.trim()));
+                }
+
+                let error_lines: Vec<usize> = annotations.iter().map(|(line, _)| *line).collect();
+                let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+                    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+                    let mut job = egui::text::LayoutJob::default();
+                    for (index, line) in text.split_inclusive('\n').enumerate() {
+                        let background = if error_lines.contains(&(index + 1)) {
+                            egui::Color32::from_rgba_unmultiplied(255, 0, 0, 48)
+                        } else {
+                            egui::Color32::TRANSPARENT
+                        };
+                        job.append(
+                            line,
+                            0.0,
+                            egui::TextFormat {
+                                font_id: font_id.clone(),
+                                color: ui.visuals().text_color(),
+                                background,
+                                ..Default::default()
+                            },
+                        );
+                    }
+                    job.wrap.max_width = wrap_width;
+                    ui.fonts(|fonts| fonts.layout_job(job))
+                };
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.source)
+                            .code_editor()
+                            .desired_rows(24)
+                            .desired_width(f32::INFINITY)
+                            .layouter(&mut layouter),
+                    );
+                });
+            });
+    }
+}
+
+/// Messages of a shader info log referring to a line of the source, with the line number.
+///
+/// Drivers format messages differently, e.g. `0:12(5): error: ...` (Mesa), `0(12) : error ...`
+/// (NVIDIA) and `ERROR: 0:12: ...` (ANGLE), so the first word made of a source string index
+/// followed by a line number is taken.
+fn compiler_annotations(info_log: &str) -> Vec<(usize, &str)> {
+    info_log
+        .lines()
+        .filter_map(|message| {
+            let line = message.split_whitespace().find_map(|word| {
+                let (source_string, rest) = word.split_once([':', '('])?;
+                if source_string.is_empty() || !source_string.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                let digit_count = rest.bytes().take_while(u8::is_ascii_digit).count();
+                rest[..digit_count].parse::<usize>().ok()
+            })?;
+            // Source lines are numbered from 1
+            (0 < line).then_some((line, message.trim()))
+        })
+        .collect()
+}
+
+/// Modal window listing errors. Recoverable errors can be dismissed to continue without the
+/// failed assets.
+fn show_errors(
+    egui_ctx: &egui::Context,
+    l10n: &Localization,
+    errors: &mut Vec<String>,
+    #[cfg(not(target_arch = "wasm32"))] quit_requested: &mut bool,
+    recoverable: bool,
+) {
+    if errors.is_empty() {
+        return;
+    }
+
+    egui::Window::new(l10n.tr("error-title"))
+        .id(egui::Id::new("error_window"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(egui_ctx, |ui| {
+            for error in errors.iter() {
+                ui.colored_label(egui::Color32::LIGHT_RED, error);
+            }
+            ui.horizontal(|ui| {
+                if recoverable && ui.button(l10n.tr("error-continue")).clicked() {
+                    errors.clear();
+                }
+                // Web: browser tab can't be closed by the page itself
+                #[cfg(not(target_arch = "wasm32"))]
+                if ui.button(l10n.tr("error-quit")).clicked() {
+                    *quit_requested = true;
+                }
+            });
+        });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn copy_to_clipboard(egui_ctx: &egui::Context, text: String) {
+    egui_ctx.copy_text(text);
+}
+
+/// Clipboard of egui on the web is only kept in memory, so write into the clipboard of the browser
+/// directly with `navigator.clipboard.writeText()`.
+#[cfg(target_arch = "wasm32")]
+fn copy_to_clipboard(_egui_ctx: &egui::Context, text: String) {
+    use js_sys::{Function, Reflect};
+    use wasm_bindgen::{JsCast, JsValue};
+
+    let write_text = || -> Result<(), JsValue> {
+        let navigator = Reflect::get(&js_sys::global(), &"navigator".into())?;
+        let clipboard = Reflect::get(&navigator, &"clipboard".into())?;
+        let write_text: Function = Reflect::get(&clipboard, &"writeText".into())?.dyn_into()?;
+        write_text.call1(&clipboard, &text.into())?;
+        Ok(())
+    };
+    if write_text().is_err() {
+        eprintln!("clipboard is not available in this browser");
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const MIB: f32 = 1024.0 * 1024.0;
+    format!("{:.2} MiB", bytes as f32 / MIB)
 }
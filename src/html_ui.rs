@@ -4,36 +4,127 @@ cfg_if! { if #[cfg(target_arch = "wasm32")] {
 use std::{cell::RefCell, sync::Arc};
 
 use egui::{Color32, Rgba};
+use hecs::Entity;
 use wasm_bindgen::prelude::*;
 use web_sys::{
-    Document, HtmlInputElement, HtmlSelectElement,
+    Document, Element, HtmlElement, HtmlInputElement, HtmlSelectElement, XrSessionMode,
 };
 
-use crate::DrawProperties;
+use crate::{
+    localization::Localization, web_xr, AntiAliasing, CameraMode, DrawProperties, Language,
+    Material, MaterialType, PostEffect, RenderPath, Scene, StereoMode, VertexColorMode,
+    MAX_SHADOW_CASCADES,
+};
+
+/// Options of shadow map resolution selects in the order of the page.
+const SHADOW_RESOLUTIONS: [u32; 5] = [256, 512, 1024, 2048, 4096];
+
+/// Material of the selected entity edited by HTML widgets between frames.
+#[derive(Default)]
+struct MaterialEdit {
+    entity: Option<Entity>,
+    material: Material,
+    // Widgets were changed since last synchronization, material is written back into the scene
+    changed: bool,
+}
 
 /// HTML equivalent of widgets available in overlay immediate GUI.
 ///
 /// Values of HTML and immediate GUI widgets are synchronized with eachother in the application.
+/// Texts of the page marked with `data-l10n-id` attribute are translated to the selected language.
 pub struct HtmlUI {
+    document: Document,
+    localization: Localization,
+    language_select: HtmlSelectElement,
     skybox_checkbox: HtmlInputElement,
+    labels_checkbox: HtmlInputElement,
     background_color_picker: HtmlInputElement,
     fov_slider: HtmlInputElement,
+    camera_mode_select: HtmlSelectElement,
+    mouse_smoothing_slider: HtmlInputElement,
+    mouse_acceleration_slider: HtmlInputElement,
+    exposure_slider: HtmlInputElement,
+    gamma_slider: HtmlInputElement,
+    motion_blur_checkbox: HtmlInputElement,
+    shutter_angle_slider: HtmlInputElement,
+    vignette_checkbox: HtmlInputElement,
+    vignette_strength_slider: HtmlInputElement,
+    film_grain_checkbox: HtmlInputElement,
+    film_grain_intensity_slider: HtmlInputElement,
+    chromatic_aberration_checkbox: HtmlInputElement,
+    chromatic_aberration_strength_slider: HtmlInputElement,
+    post_effect_order_selects: [HtmlSelectElement; PostEffect::ALL.len()],
+    render_scale_slider: HtmlInputElement,
+    auto_quality_checkbox: HtmlInputElement,
+    target_frame_rate_slider: HtmlInputElement,
+    render_path_select: HtmlSelectElement,
+    anti_aliasing_select: HtmlSelectElement,
+    taa_sharpness_slider: HtmlInputElement,
+    lod_checkbox: HtmlInputElement,
+    occlusion_culling_checkbox: HtmlInputElement,
+    stereo_mode_select: HtmlSelectElement,
+    eye_separation_slider: HtmlInputElement,
+    reflective_plane_checkbox: HtmlInputElement,
+    reflective_plane_height_slider: HtmlInputElement,
+    reflectivity_slider: HtmlInputElement,
+    reflection_blur_slider: HtmlInputElement,
+    mirror_checkbox: HtmlInputElement,
     model_select: HtmlSelectElement,
+    overdraw_heatmap_checkbox: HtmlInputElement,
+    bounding_box_checkbox: HtmlInputElement,
+    bounding_sphere_checkbox: HtmlInputElement,
+    explode_parts_slider: HtmlInputElement,
     transform_rotation_x_slider: HtmlInputElement,
     transform_rotation_y_slider: HtmlInputElement,
     transform_rotation_z_slider: HtmlInputElement,
+    material_edit: Arc<RefCell<MaterialEdit>>,
     material_color_picker: HtmlInputElement,
+    material_type_select: HtmlSelectElement,
+    refractive_index_slider: HtmlInputElement,
+    diffuse_checkbox: HtmlInputElement,
+    specular_checkbox: HtmlInputElement,
+    vertex_colors_select: HtmlSelectElement,
     light_direction_x_slider: HtmlInputElement,
     light_direction_y_slider: HtmlInputElement,
     light_direction_z_slider: HtmlInputElement,
-    diffuse_checkbox: HtmlInputElement,
-    specular_checkbox: HtmlInputElement,
+    light_view_checkbox: HtmlInputElement,
+    light_gizmo_checkbox: HtmlInputElement,
+    shadows_checkbox: HtmlInputElement,
+    shadow_cascades_slider: HtmlInputElement,
+    shadow_resolution_selects: [HtmlSelectElement; MAX_SHADOW_CASCADES],
+    shadow_pcf_radius_slider: HtmlInputElement,
+    shadow_distance_slider: HtmlInputElement,
+    shadow_cascade_visualization_checkbox: HtmlInputElement,
+    pause_checkbox: HtmlInputElement,
+    time_scale_slider: HtmlInputElement,
 }
 
 impl HtmlUI {
-    pub fn new(draw_props: Arc<RefCell<DrawProperties>>) -> Self {
+    /// `on_enter_xr` is called on click of the WebXR button with the immersive session mode
+    /// supported by the browser.
+    pub fn new(
+        draw_props: Arc<RefCell<DrawProperties>>,
+        model_names: &[&str],
+        on_enter_xr: impl Fn(XrSessionMode) + 'static,
+    ) -> Self {
         let document = web_sys::window().unwrap().document().unwrap();
 
+        // Language
+        let localization = Localization::new(draw_props.borrow().language);
+        translate_document(&document, &localization);
+        let draw_props_clone = draw_props.clone();
+        let language_names: Vec<&str> =
+            Language::ALL.iter().map(|language| language.native_name()).collect();
+        fill_select_options(&document, "language-select", &language_names);
+        let language_select = setup_select(
+            &document,
+            "language-select",
+            language_index(draw_props.borrow().language),
+            move |v| {
+                draw_props_clone.borrow_mut().language = Language::ALL[v];
+            },
+        );
+
         // Display immediate mode overlay GUI
         let draw_props_clone = draw_props.clone();
         // This HTML element is not required to sync with overlay GUI, therefore it is
@@ -47,6 +138,17 @@ impl HtmlUI {
             },
         );
 
+        // WebXR, button stays hidden without a supported immersive session mode. This HTML element
+        // is not required to sync with overlay GUI either.
+        let xr_button: HtmlElement =
+            document.get_element_by_id("xr-button").unwrap().dyn_into().unwrap();
+        web_xr::query_session_mode(move |mode| {
+            let f = Closure::<dyn FnMut()>::new(move || on_enter_xr(mode));
+            xr_button.set_onclick(Some(f.as_ref().unchecked_ref()));
+            f.forget();
+            xr_button.set_hidden(false);
+        });
+
         // Skybox
         let draw_props_clone = draw_props.clone();
         let skybox_checkbox = setup_checkbox(
@@ -58,6 +160,17 @@ impl HtmlUI {
             },
         );
 
+        // Labels
+        let draw_props_clone = draw_props.clone();
+        let labels_checkbox = setup_checkbox(
+            &document,
+            "labels-checkbox",
+            draw_props.borrow().labels_enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().labels_enabled = v;
+            },
+        );
+
         // Background
         let draw_props_clone = draw_props.clone();
         let background_color_picker = setup_color_picker(
@@ -79,9 +192,297 @@ impl HtmlUI {
                 draw_props_clone.borrow_mut().field_of_view = v;
             },
         );
+        let draw_props_clone = draw_props.clone();
+        let camera_mode_select = setup_select(
+            &document,
+            "camera-mode-select",
+            draw_props.borrow().camera_mode as usize,
+            move |v| {
+                draw_props_clone.borrow_mut().camera_mode = CameraMode::ALL[v];
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let mouse_smoothing_slider = setup_slider(
+            &document,
+            "mouse-smoothing-slider",
+            draw_props.borrow().mouse_smoothing,
+            move |v| {
+                draw_props_clone.borrow_mut().mouse_smoothing = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let mouse_acceleration_slider = setup_slider(
+            &document,
+            "mouse-acceleration-slider",
+            draw_props.borrow().mouse_acceleration,
+            move |v| {
+                draw_props_clone.borrow_mut().mouse_acceleration = v;
+            },
+        );
+
+        // Display
+        let draw_props_clone = draw_props.clone();
+        let exposure_slider = setup_slider(
+            &document,
+            "exposure-slider",
+            draw_props.borrow().exposure,
+            move |v| {
+                draw_props_clone.borrow_mut().exposure = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let gamma_slider = setup_slider(
+            &document,
+            "gamma-slider",
+            draw_props.borrow().gamma,
+            move |v| {
+                draw_props_clone.borrow_mut().gamma = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let motion_blur_checkbox = setup_checkbox(
+            &document,
+            "motion-blur-checkbox",
+            draw_props.borrow().post_effect_settings.motion_blur_enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().post_effect_settings.motion_blur_enabled = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let shutter_angle_slider = setup_slider(
+            &document,
+            "shutter-angle-slider",
+            draw_props.borrow().post_effect_settings.shutter_angle,
+            move |v| {
+                draw_props_clone.borrow_mut().post_effect_settings.shutter_angle = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let vignette_checkbox = setup_checkbox(
+            &document,
+            "vignette-checkbox",
+            draw_props.borrow().post_effect_settings.vignette_enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().post_effect_settings.vignette_enabled = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let vignette_strength_slider = setup_slider(
+            &document,
+            "vignette-strength-slider",
+            draw_props.borrow().post_effect_settings.vignette_strength,
+            move |v| {
+                draw_props_clone.borrow_mut().post_effect_settings.vignette_strength = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let film_grain_checkbox = setup_checkbox(
+            &document,
+            "film-grain-checkbox",
+            draw_props.borrow().post_effect_settings.film_grain_enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().post_effect_settings.film_grain_enabled = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let film_grain_intensity_slider = setup_slider(
+            &document,
+            "film-grain-intensity-slider",
+            draw_props.borrow().post_effect_settings.film_grain_intensity,
+            move |v| {
+                draw_props_clone.borrow_mut().post_effect_settings.film_grain_intensity = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let chromatic_aberration_checkbox = setup_checkbox(
+            &document,
+            "chromatic-aberration-checkbox",
+            draw_props.borrow().post_effect_settings.chromatic_aberration_enabled,
+            move |v| {
+                draw_props_clone
+                    .borrow_mut()
+                    .post_effect_settings
+                    .chromatic_aberration_enabled = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let chromatic_aberration_strength_slider = setup_slider(
+            &document,
+            "chromatic-aberration-strength-slider",
+            draw_props.borrow().post_effect_settings.chromatic_aberration_strength,
+            move |v| {
+                draw_props_clone
+                    .borrow_mut()
+                    .post_effect_settings
+                    .chromatic_aberration_strength = v;
+            },
+        );
+        // Options are valued by effect, choosing one swaps it with the effect at that position
+        let post_effect_order_selects = std::array::from_fn(|position| {
+            let draw_props_clone = draw_props.clone();
+            let effect = draw_props.borrow().post_effect_settings.order[position];
+            setup_select(
+                &document,
+                &format!("post-effect-order-{position}-select"),
+                effect as usize,
+                move |v| {
+                    draw_props_clone
+                        .borrow_mut()
+                        .post_effect_settings
+                        .place_effect(position, PostEffect::ALL[v]);
+                },
+            )
+        });
+        let draw_props_clone = draw_props.clone();
+        let render_scale_slider = setup_slider(
+            &document,
+            "render-scale-slider",
+            draw_props.borrow().render_scale,
+            move |v| {
+                draw_props_clone.borrow_mut().render_scale = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let auto_quality_checkbox = setup_checkbox(
+            &document,
+            "auto-quality-checkbox",
+            draw_props.borrow().auto_quality_enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().auto_quality_enabled = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let target_frame_rate_slider = setup_slider(
+            &document,
+            "target-frame-rate-slider",
+            draw_props.borrow().target_frame_rate,
+            move |v| {
+                draw_props_clone.borrow_mut().target_frame_rate = v;
+            },
+        );
+
+        let draw_props_clone = draw_props.clone();
+        let render_path_select = setup_select(
+            &document,
+            "render-path-select",
+            draw_props.borrow().render_path as usize,
+            move |v| {
+                draw_props_clone.borrow_mut().render_path = RenderPath::ALL[v];
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let anti_aliasing_select = setup_select(
+            &document,
+            "anti-aliasing-select",
+            draw_props.borrow().anti_aliasing as usize,
+            move |v| {
+                draw_props_clone.borrow_mut().anti_aliasing = AntiAliasing::ALL[v];
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let taa_sharpness_slider = setup_slider(
+            &document,
+            "taa-sharpness-slider",
+            draw_props.borrow().taa_sharpness,
+            move |v| {
+                draw_props_clone.borrow_mut().taa_sharpness = v;
+            },
+        );
+
+        // Level of detail
+        let draw_props_clone = draw_props.clone();
+        let lod_checkbox = setup_checkbox(
+            &document,
+            "lod-checkbox",
+            draw_props.borrow().lod_enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().lod_enabled = v;
+            },
+        );
+
+        // Occlusion culling
+        let draw_props_clone = draw_props.clone();
+        let occlusion_culling_checkbox = setup_checkbox(
+            &document,
+            "occlusion-culling-checkbox",
+            draw_props.borrow().occlusion_culling_enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().occlusion_culling_enabled = v;
+            },
+        );
+
+        // Stereo output
+        let draw_props_clone = draw_props.clone();
+        let stereo_mode_select = setup_select(
+            &document,
+            "stereo-mode-select",
+            draw_props.borrow().stereo_mode as usize,
+            move |v| {
+                draw_props_clone.borrow_mut().stereo_mode = StereoMode::ALL[v];
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let eye_separation_slider = setup_slider(
+            &document,
+            "eye-separation-slider",
+            draw_props.borrow().eye_separation,
+            move |v| {
+                draw_props_clone.borrow_mut().eye_separation = v;
+            },
+        );
+
+        // Ground plane
+        let draw_props_clone = draw_props.clone();
+        let reflective_plane_checkbox = setup_checkbox(
+            &document,
+            "reflective-plane-checkbox",
+            draw_props.borrow().reflective_plane_enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().reflective_plane_enabled = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let reflective_plane_height_slider = setup_slider(
+            &document,
+            "reflective-plane-height-slider",
+            draw_props.borrow().reflective_plane_height,
+            move |v| {
+                draw_props_clone.borrow_mut().reflective_plane_height = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let reflectivity_slider = setup_slider(
+            &document,
+            "reflectivity-slider",
+            draw_props.borrow().reflectivity,
+            move |v| {
+                draw_props_clone.borrow_mut().reflectivity = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let reflection_blur_slider = setup_slider(
+            &document,
+            "reflection-blur-slider",
+            draw_props.borrow().reflection_blur,
+            move |v| {
+                draw_props_clone.borrow_mut().reflection_blur = v;
+            },
+        );
+
+        // Mirror
+        let draw_props_clone = draw_props.clone();
+        let mirror_checkbox = setup_checkbox(
+            &document,
+            "mirror-checkbox",
+            draw_props.borrow().mirror_enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().mirror_enabled = v;
+            },
+        );
 
         // Model
         let draw_props_clone = draw_props.clone();
+        fill_select_options(&document, "model-select", model_names);
         let model_select = setup_select(
             &document,
             "model-select",
@@ -90,6 +491,42 @@ impl HtmlUI {
                 draw_props_clone.borrow_mut().selected_model_index = v;
             },
         );
+        let draw_props_clone = draw_props.clone();
+        let overdraw_heatmap_checkbox = setup_checkbox(
+            &document,
+            "overdraw-heatmap-checkbox",
+            draw_props.borrow().overdraw_heatmap_enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().overdraw_heatmap_enabled = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let bounding_box_checkbox = setup_checkbox(
+            &document,
+            "bounding-box-checkbox",
+            draw_props.borrow().bounding_box_enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().bounding_box_enabled = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let bounding_sphere_checkbox = setup_checkbox(
+            &document,
+            "bounding-sphere-checkbox",
+            draw_props.borrow().bounding_sphere_enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().bounding_sphere_enabled = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let explode_parts_slider = setup_slider(
+            &document,
+            "explode-parts-slider",
+            draw_props.borrow().part_explode_factor,
+            move |v| {
+                draw_props_clone.borrow_mut().part_explode_factor = v;
+            },
+        );
 
         // Transform
         let draw_props_clone = draw_props.clone();
@@ -120,14 +557,65 @@ impl HtmlUI {
             },
         );
 
-        // Material
-        let draw_props_clone = draw_props.clone();
+        // Material of selected entity, filled on first synchronization
+        let material_edit = Arc::new(RefCell::new(MaterialEdit::default()));
+        let initial_material = Material::default();
+        let material_edit_clone = material_edit.clone();
         let material_color_picker = setup_color_picker(
             &document,
             "material-color-picker",
-            draw_props.borrow().model_color,
+            initial_material.color,
+            move |v| {
+                edit_material(&material_edit_clone, |material| material.color = v);
+            },
+        );
+        let material_edit_clone = material_edit.clone();
+        let material_type_select = setup_select(
+            &document,
+            "material-type-select",
+            initial_material.material_type as usize,
+            move |v| {
+                edit_material(&material_edit_clone, |material| {
+                    material.material_type = MaterialType::ALL[v];
+                });
+            },
+        );
+        let material_edit_clone = material_edit.clone();
+        let refractive_index_slider = setup_slider(
+            &document,
+            "refractive-index-slider",
+            initial_material.refractive_index,
+            move |v| {
+                edit_material(&material_edit_clone, |material| material.refractive_index = v);
+            },
+        );
+        let material_edit_clone = material_edit.clone();
+        let diffuse_checkbox = setup_checkbox(
+            &document,
+            "diffuse-checkbox",
+            initial_material.diffuse_enabled,
+            move |v| {
+                edit_material(&material_edit_clone, |material| material.diffuse_enabled = v);
+            },
+        );
+        let material_edit_clone = material_edit.clone();
+        let specular_checkbox = setup_checkbox(
+            &document,
+            "specular-checkbox",
+            initial_material.specular_enabled,
+            move |v| {
+                edit_material(&material_edit_clone, |material| material.specular_enabled = v);
+            },
+        );
+        let material_edit_clone = material_edit.clone();
+        let vertex_colors_select = setup_select(
+            &document,
+            "vertex-colors-select",
+            initial_material.vertex_colors as usize,
             move |v| {
-                draw_props_clone.borrow_mut().model_color = v;
+                edit_material(&material_edit_clone, |material| {
+                    material.vertex_colors = VertexColorMode::ALL[v];
+                });
             },
         );
 
@@ -161,52 +649,283 @@ impl HtmlUI {
         );
 
         let draw_props_clone = draw_props.clone();
-        let diffuse_checkbox = setup_checkbox(
+        let light_view_checkbox = setup_checkbox(
             &document,
-            "diffuse-checkbox",
-            draw_props.borrow().diffuse_enabled,
+            "light-view-checkbox",
+            draw_props.borrow().light_view_enabled,
             move |v| {
-                draw_props_clone.borrow_mut().diffuse_enabled = v;
+                draw_props_clone.borrow_mut().light_view_enabled = v;
             },
         );
         let draw_props_clone = draw_props.clone();
-        let specular_checkbox = setup_checkbox(
+        let light_gizmo_checkbox = setup_checkbox(
             &document,
-            "specular-checkbox",
-            draw_props.borrow().specular_enabled,
+            "light-gizmo-checkbox",
+            draw_props.borrow().light_gizmo_enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().light_gizmo_enabled = v;
+            },
+        );
+
+        // Shadows
+        let draw_props_clone = draw_props.clone();
+        let shadows_checkbox = setup_checkbox(
+            &document,
+            "shadows-checkbox",
+            draw_props.borrow().shadow_settings.enabled,
+            move |v| {
+                draw_props_clone.borrow_mut().shadow_settings.enabled = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let shadow_cascades_slider = setup_slider(
+            &document,
+            "shadow-cascades-slider",
+            draw_props.borrow().shadow_settings.cascade_count as f32,
+            move |v| {
+                draw_props_clone.borrow_mut().shadow_settings.cascade_count = v as usize;
+            },
+        );
+        let shadow_resolution_selects = std::array::from_fn(|index| {
+            let draw_props_clone = draw_props.clone();
+            let resolution = draw_props.borrow().shadow_settings.resolutions[index];
+            setup_select(
+                &document,
+                &format!("shadow-resolution-{index}-select"),
+                shadow_resolution_index(resolution),
+                move |v| {
+                    draw_props_clone.borrow_mut().shadow_settings.resolutions[index] =
+                        SHADOW_RESOLUTIONS[v];
+                },
+            )
+        });
+        let draw_props_clone = draw_props.clone();
+        let shadow_pcf_radius_slider = setup_slider(
+            &document,
+            "shadow-pcf-radius-slider",
+            draw_props.borrow().shadow_settings.pcf_radius as f32,
+            move |v| {
+                draw_props_clone.borrow_mut().shadow_settings.pcf_radius = v as u32;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let shadow_distance_slider = setup_slider(
+            &document,
+            "shadow-distance-slider",
+            draw_props.borrow().shadow_settings.distance,
+            move |v| {
+                draw_props_clone.borrow_mut().shadow_settings.distance = v;
+            },
+        );
+        let draw_props_clone = draw_props.clone();
+        let shadow_cascade_visualization_checkbox = setup_checkbox(
+            &document,
+            "shadow-cascade-visualization-checkbox",
+            draw_props.borrow().shadow_settings.cascade_visualization,
+            move |v| {
+                draw_props_clone
+                    .borrow_mut()
+                    .shadow_settings
+                    .cascade_visualization = v;
+            },
+        );
+
+        // Time controls
+        let draw_props_clone = draw_props.clone();
+        let pause_checkbox = setup_checkbox(
+            &document,
+            "pause-checkbox",
+            draw_props.borrow().paused,
             move |v| {
-                draw_props_clone.borrow_mut().specular_enabled = v;
+                draw_props_clone.borrow_mut().paused = v;
             },
         );
+        let draw_props_clone = draw_props.clone();
+        let time_scale_slider = setup_slider(
+            &document,
+            "time-scale-slider",
+            draw_props.borrow().time_scale,
+            move |v| {
+                draw_props_clone.borrow_mut().time_scale = v;
+            },
+        );
+        // Button has no state to sync with overlay GUI, not saved as struct field
+        let step_button: HtmlElement = document
+            .get_element_by_id("step-button")
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+        let draw_props_clone = draw_props.clone();
+        let f = Closure::<dyn FnMut()>::new(move || {
+            draw_props_clone.borrow_mut().step_requested = true;
+        });
+        step_button.set_onclick(Some(f.as_ref().unchecked_ref()));
+        f.forget();
 
         Self {
+            document,
+            localization,
+            language_select,
             skybox_checkbox,
+            labels_checkbox,
             background_color_picker,
             fov_slider,
+            camera_mode_select,
+            mouse_smoothing_slider,
+            mouse_acceleration_slider,
+            exposure_slider,
+            gamma_slider,
+            motion_blur_checkbox,
+            shutter_angle_slider,
+            vignette_checkbox,
+            vignette_strength_slider,
+            film_grain_checkbox,
+            film_grain_intensity_slider,
+            chromatic_aberration_checkbox,
+            chromatic_aberration_strength_slider,
+            post_effect_order_selects,
+            render_scale_slider,
+            auto_quality_checkbox,
+            target_frame_rate_slider,
+            render_path_select,
+            anti_aliasing_select,
+            taa_sharpness_slider,
+            lod_checkbox,
+            occlusion_culling_checkbox,
+            stereo_mode_select,
+            eye_separation_slider,
+            reflective_plane_checkbox,
+            reflective_plane_height_slider,
+            reflectivity_slider,
+            reflection_blur_slider,
+            mirror_checkbox,
             model_select,
+            overdraw_heatmap_checkbox,
+            bounding_box_checkbox,
+            bounding_sphere_checkbox,
+            explode_parts_slider,
             transform_rotation_x_slider,
             transform_rotation_y_slider,
             transform_rotation_z_slider,
+            material_edit,
             material_color_picker,
+            material_type_select,
+            refractive_index_slider,
+            diffuse_checkbox,
+            specular_checkbox,
+            vertex_colors_select,
             light_direction_x_slider,
             light_direction_y_slider,
             light_direction_z_slider,
-            diffuse_checkbox,
-            specular_checkbox,
+            light_view_checkbox,
+            light_gizmo_checkbox,
+            shadows_checkbox,
+            shadow_cascades_slider,
+            shadow_resolution_selects,
+            shadow_pcf_radius_slider,
+            shadow_distance_slider,
+            shadow_cascade_visualization_checkbox,
+            pause_checkbox,
+            time_scale_slider,
         }
     }
 
-    pub fn sync_widgets(&mut self, draw_props: &DrawProperties) {
+    /// Material edited by HTML widgets is written back into the scene before widgets are updated
+    /// with the material of the currently selected entity.
+    pub fn sync_widgets(&mut self, draw_props: &DrawProperties, scene: &mut Scene) {
+        if self.localization.language() != draw_props.language {
+            self.localization = Localization::new(draw_props.language);
+            translate_document(&self.document, &self.localization);
+        }
+        self.language_select
+            .set_selected_index(language_index(draw_props.language) as i32);
         self.skybox_checkbox
             .set_checked(draw_props.skybox_enabled);
+        self.labels_checkbox
+            .set_checked(draw_props.labels_enabled);
         let background_color_hex =
             normalized_rgb_to_hex_color(&draw_props.background_color);
         self.background_color_picker
             .set_value(&background_color_hex.as_str());
         self.fov_slider
             .set_value(&draw_props.field_of_view.to_string().to_string());
+        self.camera_mode_select
+            .set_selected_index(draw_props.camera_mode as i32);
+        self.mouse_smoothing_slider
+            .set_value(&draw_props.mouse_smoothing.to_string());
+        self.mouse_acceleration_slider
+            .set_value(&draw_props.mouse_acceleration.to_string());
+        self.exposure_slider
+            .set_value(&draw_props.exposure.to_string());
+        self.gamma_slider
+            .set_value(&draw_props.gamma.to_string());
+        let post_effect_settings = &draw_props.post_effect_settings;
+        self.motion_blur_checkbox
+            .set_checked(post_effect_settings.motion_blur_enabled);
+        self.shutter_angle_slider
+            .set_value(&post_effect_settings.shutter_angle.to_string());
+        self.vignette_checkbox
+            .set_checked(post_effect_settings.vignette_enabled);
+        self.vignette_strength_slider
+            .set_value(&post_effect_settings.vignette_strength.to_string());
+        self.film_grain_checkbox
+            .set_checked(post_effect_settings.film_grain_enabled);
+        self.film_grain_intensity_slider
+            .set_value(&post_effect_settings.film_grain_intensity.to_string());
+        self.chromatic_aberration_checkbox
+            .set_checked(post_effect_settings.chromatic_aberration_enabled);
+        self.chromatic_aberration_strength_slider
+            .set_value(&post_effect_settings.chromatic_aberration_strength.to_string());
+        // Other selects change when an effect is swapped into place
+        for (select, &effect) in self
+            .post_effect_order_selects
+            .iter()
+            .zip(&post_effect_settings.order)
+        {
+            select.set_selected_index(effect as i32);
+        }
+        // Changed by automatic quality scaling
+        self.render_scale_slider
+            .set_value(&draw_props.render_scale.to_string());
+        self.render_scale_slider
+            .set_disabled(draw_props.auto_quality_enabled);
+        self.auto_quality_checkbox
+            .set_checked(draw_props.auto_quality_enabled);
+        self.target_frame_rate_slider
+            .set_value(&draw_props.target_frame_rate.to_string());
+        self.render_path_select
+            .set_selected_index(draw_props.render_path as i32);
+        self.anti_aliasing_select
+            .set_selected_index(draw_props.anti_aliasing as i32);
+        self.taa_sharpness_slider
+            .set_value(&draw_props.taa_sharpness.to_string());
+        self.lod_checkbox.set_checked(draw_props.lod_enabled);
+        self.occlusion_culling_checkbox
+            .set_checked(draw_props.occlusion_culling_enabled);
+        self.stereo_mode_select
+            .set_selected_index(draw_props.stereo_mode as i32);
+        self.eye_separation_slider
+            .set_value(&draw_props.eye_separation.to_string());
+        self.reflective_plane_checkbox
+            .set_checked(draw_props.reflective_plane_enabled);
+        self.reflective_plane_height_slider
+            .set_value(&draw_props.reflective_plane_height.to_string());
+        self.reflectivity_slider
+            .set_value(&draw_props.reflectivity.to_string());
+        self.reflection_blur_slider
+            .set_value(&draw_props.reflection_blur.to_string());
+        self.mirror_checkbox
+            .set_checked(draw_props.mirror_enabled);
         self.model_select
             .set_selected_index(draw_props.selected_model_index as i32);
+        self.overdraw_heatmap_checkbox
+            .set_checked(draw_props.overdraw_heatmap_enabled);
+        self.bounding_box_checkbox
+            .set_checked(draw_props.bounding_box_enabled);
+        self.bounding_sphere_checkbox
+            .set_checked(draw_props.bounding_sphere_enabled);
+        self.explode_parts_slider
+            .set_value(&draw_props.part_explode_factor.to_string());
         self.transform_rotation_x_slider.set_value(
             &draw_props.model_rotation[0]
                 .to_string()
@@ -222,9 +941,37 @@ impl HtmlUI {
                 .to_string()
                 .to_string(),
         );
-        let material_color_hex = normalized_rgb_to_hex_color(&draw_props.model_color);
+        let mut material_edit = self.material_edit.borrow_mut();
+        if material_edit.changed {
+            if let Some(entity) = material_edit.entity {
+                if let Ok(mut material) = scene.world.get::<&mut Material>(entity) {
+                    *material = material_edit.material.clone();
+                }
+            }
+        }
+        let entity = scene.selected_entity(draw_props.selected_model_index);
+        let material = entity
+            .and_then(|entity| scene.world.get::<&Material>(entity).ok())
+            .map(|material| Material::clone(&material))
+            .unwrap_or_default();
+        let material_color_hex = normalized_rgb_to_hex_color(&material.color);
         self.material_color_picker
             .set_value(&material_color_hex.as_str());
+        self.material_type_select
+            .set_selected_index(material.material_type as i32);
+        self.refractive_index_slider
+            .set_value(&material.refractive_index.to_string());
+        self.diffuse_checkbox
+            .set_checked(material.diffuse_enabled);
+        self.specular_checkbox
+            .set_checked(material.specular_enabled);
+        self.vertex_colors_select
+            .set_selected_index(material.vertex_colors as i32);
+        *material_edit = MaterialEdit {
+            entity,
+            material,
+            changed: false,
+        };
         self.light_direction_x_slider.set_value(
             &draw_props.light_direction[0]
                 .to_string()
@@ -240,13 +987,39 @@ impl HtmlUI {
                 .to_string()
                 .to_string(),
         );
-        self.diffuse_checkbox
-            .set_checked(draw_props.diffuse_enabled);
-        self.specular_checkbox
-            .set_checked(draw_props.specular_enabled);
+        self.light_view_checkbox
+            .set_checked(draw_props.light_view_enabled);
+        self.light_gizmo_checkbox
+            .set_checked(draw_props.light_gizmo_enabled);
+        let shadow_settings = &draw_props.shadow_settings;
+        self.shadows_checkbox.set_checked(shadow_settings.enabled);
+        self.shadow_cascades_slider
+            .set_value(&shadow_settings.cascade_count.to_string());
+        for (select, &resolution) in self
+            .shadow_resolution_selects
+            .iter()
+            .zip(&shadow_settings.resolutions)
+        {
+            select.set_selected_index(shadow_resolution_index(resolution) as i32);
+        }
+        self.shadow_pcf_radius_slider
+            .set_value(&shadow_settings.pcf_radius.to_string());
+        self.shadow_distance_slider
+            .set_value(&shadow_settings.distance.to_string());
+        self.shadow_cascade_visualization_checkbox
+            .set_checked(shadow_settings.cascade_visualization);
+        self.pause_checkbox.set_checked(draw_props.paused);
+        self.time_scale_slider
+            .set_value(&draw_props.time_scale.to_string());
     }
 }
 
+fn edit_material(material_edit: &RefCell<MaterialEdit>, edit: impl FnOnce(&mut Material)) {
+    let mut material_edit = material_edit.borrow_mut();
+    edit(&mut material_edit.material);
+    material_edit.changed = true;
+}
+
 fn setup_checkbox<F>(
     document: &Document,
     id: &str,
@@ -313,6 +1086,52 @@ where
     select
 }
 
+/// Index of the option of a shadow map resolution select, the nearest one if not listed.
+fn shadow_resolution_index(resolution: u32) -> usize {
+    SHADOW_RESOLUTIONS
+        .iter()
+        .position(|&option| resolution <= option)
+        .unwrap_or(SHADOW_RESOLUTIONS.len() - 1)
+}
+
+fn language_index(language: Language) -> usize {
+    Language::ALL
+        .iter()
+        .position(|l| *l == language)
+        .unwrap_or(0)
+}
+
+/// Replace text of elements marked with `data-l10n-id` attribute by the message of that identifier.
+fn translate_document(document: &Document, localization: &Localization) {
+    if let Some(root) = document.document_element() {
+        let _ = root.set_attribute("lang", localization.language().tag());
+    }
+    let elements = document.query_selector_all("[data-l10n-id]").unwrap();
+    for index in 0..elements.length() {
+        let Some(element) = elements
+            .item(index)
+            .and_then(|node| node.dyn_into::<Element>().ok())
+        else {
+            continue;
+        };
+        if let Some(message_id) = element.get_attribute("data-l10n-id") {
+            element.set_text_content(Some(&localization.tr(&message_id)));
+        }
+    }
+}
+
+/// Replace options of select element with labels, valued by their index.
+fn fill_select_options(document: &Document, id: &str, labels: &[&str]) {
+    let select = document.get_element_by_id(id).unwrap();
+    select.set_inner_html("");
+    for (index, label) in labels.iter().enumerate() {
+        let option = document.create_element("option").unwrap();
+        option.set_attribute("value", &index.to_string()).unwrap();
+        option.set_text_content(Some(label));
+        select.append_child(&option).unwrap();
+    }
+}
+
 fn setup_color_picker<F>(
     document: &Document,
     id: &str,
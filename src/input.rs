@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+
+/// Camera movement bound to a keyboard key, continuing while the key is held down.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum InputEvent {
+    MoveForward,
+    MoveBackward,
+    StrafeLeft,
+    StrafeRight,
+    Ascend,
+    Descend,
+    RollLeft,
+    RollRight,
+}
+
+// Using array instead of HashSet results in a single jump table which is more friendlier to cache,
+// avoids heap allocation and hash function calls for HashSet, has better branch prediction and has
+// fewer CPU instructions.
+//
+// (Even though gains are negligable, because bottleneck is usually not the input handling)
+pub type InputState = [bool; 8];
+
+impl std::ops::Index<InputEvent> for InputState {
+    type Output = bool;
+
+    fn index(&self, e: InputEvent) -> &Self::Output {
+        match e {
+            InputEvent::MoveForward => &self[0],
+            InputEvent::MoveBackward => &self[1],
+            InputEvent::StrafeLeft => &self[2],
+            InputEvent::StrafeRight => &self[3],
+            InputEvent::Ascend => &self[4],
+            InputEvent::Descend => &self[5],
+            InputEvent::RollLeft => &self[6],
+            InputEvent::RollRight => &self[7],
+        }
+    }
+}
+
+impl std::ops::IndexMut<InputEvent> for InputState {
+    fn index_mut(&mut self, e: InputEvent) -> &mut Self::Output {
+        match e {
+            InputEvent::MoveForward => &mut self[0],
+            InputEvent::MoveBackward => &mut self[1],
+            InputEvent::StrafeLeft => &mut self[2],
+            InputEvent::StrafeRight => &mut self[3],
+            InputEvent::Ascend => &mut self[4],
+            InputEvent::Descend => &mut self[5],
+            InputEvent::RollLeft => &mut self[6],
+            InputEvent::RollRight => &mut self[7],
+        }
+    }
+}
+
+/// Keyboard and mouse input steering the camera, applied the same way whether received from the
+/// window or played back from a recording.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum Input {
+    Key {
+        event: InputEvent,
+        pressed: bool,
+    },
+    /// Mouse look by cursor or raw mouse movement, in pixels.
+    Look {
+        x_offset: f32,
+        y_offset: f32,
+    },
+}
+
+/// Input received before the logic update of the index, counted from application start.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Serialize, Deserialize)]
+struct RecordedInput {
+    update: u64,
+    input: Input,
+}
+
+/// Contents of an input recording file in RON format.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default, Serialize, Deserialize)]
+struct InputRecording {
+    inputs: Vec<RecordedInput>,
+    /// Logic updates run until recording stopped, playback lasts as long.
+    update_count: u64,
+}
+
+/// Captures camera input together with the index of the fixed timestep logic update it arrived
+/// before. Unlike timestamps, update indices replay the same way regardless of frame rate.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct InputRecorder {
+    path: String,
+    recording: InputRecording,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl InputRecorder {
+    /// Nothing is written until `save()`.
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+            recording: InputRecording::default(),
+        }
+    }
+
+    pub fn record(&mut self, update: u64, input: Input) {
+        self.recording.inputs.push(RecordedInput { update, input });
+    }
+
+    /// Write the recording after `update_count` logic updates were run in total.
+    pub fn save(mut self, update_count: u64) -> Result<(), String> {
+        self.recording.update_count = update_count;
+        let text = ron::ser::to_string_pretty(&self.recording, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("cannot serialize input recording: {e}"))?;
+        std::fs::write(&self.path, text)
+            .map_err(|e| format!("cannot write input recording {}: {e}", self.path))
+    }
+}
+
+/// Replays input captured by `InputRecorder`. Results match the recorded session only when
+/// started from the same scene and settings, changes made in the GUI are not recorded.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct InputPlayback {
+    recording: InputRecording,
+    // Index of the first input not yet played back
+    next_input: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl InputPlayback {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read input recording {path}: {e}"))?;
+        let recording = ron::from_str(&text)
+            .map_err(|e| format!("cannot parse input recording {path}: {e}"))?;
+        Ok(Self {
+            recording,
+            next_input: 0,
+        })
+    }
+
+    /// Inputs to apply before the logic update of the index, in the order they were recorded.
+    pub fn inputs_before(&mut self, update: u64) -> Vec<Input> {
+        let inputs: Vec<Input> = self.recording.inputs[self.next_input..]
+            .iter()
+            .take_while(|recorded| recorded.update <= update)
+            .map(|recorded| recorded.input)
+            .collect();
+        self.next_input += inputs.len();
+        inputs
+    }
+
+    /// As many logic updates were run as during recording.
+    pub fn finished(&self, update_count: u64) -> bool {
+        update_count >= self.recording.update_count
+    }
+}
@@ -0,0 +1,97 @@
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, MetricSpace, Point3, Transform as _, Vector3};
+use hecs::Entity;
+
+use crate::{line::LineVertex, mesh_validation, MeshTriangle, Vertex};
+
+const EDGE_COLOR: [f32; 3] = [1.0, 0.2, 0.8];
+const VERTEX_NORMAL_COLOR: [f32; 3] = [0.2, 0.6, 1.0];
+const FACE_NORMAL_COLOR: [f32; 3] = [1.0, 1.0, 0.2];
+/// Length of normal lines relative to the longest edge of the triangle.
+const NORMAL_LENGTH: f32 = 0.5;
+
+/// Triangle of an entity's model under the cursor.
+pub struct InspectedTriangle {
+    pub entity: Entity,
+    /// Vertices in model space, as stored in the mesh.
+    pub triangle: MeshTriangle,
+    /// Placement of the entity when the triangle was inspected.
+    pub model_matrix: Matrix4<f32>,
+}
+
+impl InspectedTriangle {
+    /// Normal of the face by the winding order of its vertices in model space, counter-clockwise
+    /// triangles face the viewer. Zero for degenerate triangles.
+    pub fn face_normal(&self) -> Vector3<f32> {
+        let normal = mesh_validation::face_normal(&self.triangle.vertices);
+        if normal.magnitude2() == 0.0 {
+            return normal;
+        }
+        normal.normalize()
+    }
+
+    /// Whether the face points away from its vertex normals, e.g. because of a flipped winding
+    /// order or inverted normals.
+    pub fn is_flipped(&self) -> bool {
+        mesh_validation::is_flipped(&self.triangle.vertices)
+    }
+
+    /// Edges, vertex normals and face normal in world space.
+    pub(crate) fn lines(&self) -> Vec<LineVertex> {
+        let world_position = |vertex: &Vertex| {
+            self.model_matrix
+                .transform_point(Point3::from_vec(vertex.position))
+        };
+        let corners = self.triangle.vertices.each_ref().map(world_position);
+        let longest_edge = (0..3)
+            .map(|i| corners[i].distance(corners[(i + 1) % 3]))
+            .fold(0.0, f32::max);
+        let normal_length = longest_edge * NORMAL_LENGTH;
+        let line = |start: Point3<f32>, end: Point3<f32>, color| {
+            [start, end].map(|position| LineVertex {
+                position: position.into(),
+                color,
+            })
+        };
+
+        let mut vertices = Vec::with_capacity(16);
+        for i in 0..3 {
+            vertices.extend(line(corners[i], corners[(i + 1) % 3], EDGE_COLOR));
+        }
+        // Model matrix has no scaling, rotation keeps normals unit length
+        for (corner, vertex) in corners.iter().zip(&self.triangle.vertices) {
+            let normal = self.model_matrix.transform_vector(vertex.normal);
+            vertices.extend(line(
+                *corner,
+                corner + normal * normal_length,
+                VERTEX_NORMAL_COLOR,
+            ));
+        }
+        let center = Point3::centroid(&corners);
+        let face_normal = self.model_matrix.transform_vector(self.face_normal());
+        vertices.extend(line(
+            center,
+            center + face_normal * normal_length,
+            FACE_NORMAL_COLOR,
+        ));
+        vertices
+    }
+}
+
+/// Mode highlighting the triangle under the cursor and displaying its vertex indices, positions
+/// and normals, e.g. for debugging bad normals or flipped faces of models.
+#[derive(Default)]
+pub struct Inspector {
+    pub enabled: bool,
+    hovered: Option<InspectedTriangle>,
+}
+
+impl Inspector {
+    /// Triangle under the cursor while enabled.
+    pub fn hovered(&self) -> Option<&InspectedTriangle> {
+        self.hovered.as_ref().filter(|_| self.enabled)
+    }
+
+    pub(crate) fn set_hovered(&mut self, hovered: Option<InspectedTriangle>) {
+        self.hovered = hovered;
+    }
+}
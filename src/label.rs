@@ -0,0 +1,344 @@
+use std::{cell::RefCell, sync::Arc};
+
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont};
+use cgmath::{Matrix4, Point3};
+use glow::{HasContext, Texture, VertexArray};
+
+use crate::{
+    assets::shader::ShaderSources, gl_resources, gl_state, shader::Shader,
+    streaming_buffer::StreamingBuffer,
+};
+
+/// Pixel size glyphs are rasterized with into the font atlas.
+const ATLAS_FONT_SIZE: f32 = 32.0;
+const ATLAS_WIDTH: u32 = 512;
+/// Empty space around glyphs avoids bleeding of neighbours during linear filtering.
+const ATLAS_PADDING: u32 = 1;
+/// Height of labels on screen in logical pixels.
+const LABEL_FONT_SIZE: f32 = 18.0;
+/// Printable ASCII characters.
+const CHARACTERS: std::ops::RangeInclusive<char> = ' '..='~';
+
+/// Text displayed at a point in 3D space.
+///
+/// Labels always face the camera and keep the same size on screen regardless of distance, but are
+/// hidden behind geometry in front of them.
+pub struct Label {
+    pub text: String,
+    pub position: Point3<f32>,
+    /// Linear RGB color.
+    pub color: [f32; 3],
+}
+
+/// Placement of a single glyph within the font atlas.
+#[derive(Clone, Copy, Default)]
+struct Glyph {
+    /// Top left and bottom right texture coordinates.
+    uv_min: [f32; 2],
+    uv_max: [f32; 2],
+    /// Bitmap corners relative to pen position on baseline in pixels, Y axis pointing down.
+    bounds_min: [f32; 2],
+    bounds_max: [f32; 2],
+    /// Horizontal distance to the next glyph in pixels.
+    advance: f32,
+}
+
+/// Per-vertex data of glyph quads.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct LabelVertex {
+    anchor: [f32; 3],
+    offset: [f32; 2],
+    tex_coords: [f32; 2],
+    color: [f32; 3],
+}
+
+/// Draws world-space labels as camera-facing quads textured from a font atlas.
+///
+/// Font atlas is rasterized once on creation from the monospace font bundled with egui.
+pub struct LabelRenderer {
+    gl: Arc<glow::Context>,
+    shader: Shader,
+    font_atlas: Texture,
+    glyphs: Vec<Glyph>,
+    vertex_array: VertexArray,
+    // Rewritten on every draw, because labels follow changing scene properties
+    vertex_buffer: RefCell<StreamingBuffer>,
+    // Physical pixels per logical pixel of the display
+    scale_factor: f32,
+}
+
+impl LabelRenderer {
+    pub fn new(gl: Arc<glow::Context>, shaders: &ShaderSources) -> Result<Self, String> {
+        let shader = Shader::new(gl.clone(), shaders.label_vertex, shaders.label_fragment)
+            .map_err(|e| format!("label shader creation failed: {:?}", e))?;
+
+        let font_data = egui::FontDefinitions::default()
+            .font_data
+            .remove("Hack")
+            .ok_or("label font is not available")?;
+        let font = FontVec::try_from_vec(font_data.font.into_owned())
+            .map_err(|e| format!("unable to load label font: {e}"))?;
+        let (glyphs, atlas_height, pixels) = rasterize_atlas(&font);
+
+        unsafe {
+            let font_atlas = gl_resources::create_texture(&gl)
+                .map_err(|e| format!("cannot create font atlas texture: {e}"))?;
+            gl_state::bind_texture(&gl, glow::TEXTURE_2D, Some(font_atlas));
+            // Rows of single channel texture are not 4-byte aligned
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::R8 as i32,
+                ATLAS_WIDTH as i32,
+                atlas_height as i32,
+                0,
+                glow::RED,
+                glow::UNSIGNED_BYTE,
+                Some(&pixels),
+            );
+            gl_resources::set_texture_size(font_atlas, pixels.len());
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_MIN_FILTER,
+                glow::LINEAR as i32,
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_MAG_FILTER,
+                glow::LINEAR as i32,
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_S,
+                glow::CLAMP_TO_EDGE as i32,
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_T,
+                glow::CLAMP_TO_EDGE as i32,
+            );
+            gl_state::bind_texture(&gl, glow::TEXTURE_2D, None);
+
+            let vertex_array = gl_resources::create_vertex_array(&gl)
+                .map_err(|e| format!("cannot create label vertex array: {e}"))?;
+            let vertex_buffer =
+                StreamingBuffer::new(gl.clone(), glow::ARRAY_BUFFER, size_of::<LabelVertex>())
+                    .map_err(|e| format!("cannot create label vertex buffer: {e}"))?;
+
+            Ok(Self {
+                gl,
+                shader,
+                font_atlas,
+                glyphs,
+                vertex_array,
+                vertex_buffer: RefCell::new(vertex_buffer),
+                scale_factor: 1.0,
+            })
+        }
+    }
+
+    /// Keep the size of labels on high-DPI displays.
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+    }
+
+    /// Draw labels into currently bound framebuffer. Depth test is expected to be enabled.
+    pub fn draw(
+        &self,
+        labels: &[Label],
+        projection_view: &Matrix4<f32>,
+        viewport_width: u32,
+        viewport_height: u32,
+    ) {
+        let vertices = self.build_vertices(labels);
+        if vertices.is_empty() {
+            return;
+        }
+
+        unsafe {
+            self.shader.r#use();
+            gl_state::bind_vertex_array(&self.gl, Some(self.vertex_array));
+            let first = match self.vertex_buffer.borrow_mut().write(&vertices) {
+                Ok(first) => first,
+                Err(e) => {
+                    eprintln!("unable to upload labels: {e}");
+                    gl_state::bind_vertex_array(&self.gl, None);
+                    return;
+                }
+            };
+            // Buffer is replaced when it grows
+            let stride = size_of::<LabelVertex>() as i32;
+            let attributes = [
+                (3, std::mem::offset_of!(LabelVertex, anchor)),
+                (2, std::mem::offset_of!(LabelVertex, offset)),
+                (2, std::mem::offset_of!(LabelVertex, tex_coords)),
+                (3, std::mem::offset_of!(LabelVertex, color)),
+            ];
+            for (location, (size, offset)) in attributes.into_iter().enumerate() {
+                self.gl.enable_vertex_attrib_array(location as u32);
+                self.gl.vertex_attrib_pointer_f32(
+                    location as u32,
+                    size,
+                    glow::FLOAT,
+                    false,
+                    stride,
+                    offset as i32,
+                );
+            }
+
+            gl_state::active_texture(&self.gl, glow::TEXTURE0);
+            gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, Some(self.font_atlas));
+            let texture_unit = 0;
+            self.shader.set_uniform("u_fontAtlas", &texture_unit);
+            self.shader.set_uniform("u_projectionView", projection_view);
+            self.shader.set_uniform(
+                "u_viewportSize",
+                &[viewport_width as f32, viewport_height as f32],
+            );
+
+            // Overlapping labels must not hide each other
+            self.gl.depth_mask(false);
+            gl_state::enable(&self.gl, glow::BLEND);
+            self.gl
+                .blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+
+            self.gl
+                .draw_arrays(glow::TRIANGLES, first as i32, vertices.len() as i32);
+
+            // Reset state
+            self.gl.depth_mask(true);
+            gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, None);
+            self.gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            gl_state::bind_vertex_array(&self.gl, None);
+        }
+    }
+
+    /// Lay out text of labels horizontally centered on their position, two triangles per glyph.
+    fn build_vertices(&self, labels: &[Label]) -> Vec<LabelVertex> {
+        let scale = LABEL_FONT_SIZE * self.scale_factor / ATLAS_FONT_SIZE;
+        let mut vertices = Vec::new();
+        for label in labels {
+            let text_width: f32 = label
+                .text
+                .chars()
+                .filter_map(|c| self.glyph(c))
+                .map(|glyph| glyph.advance)
+                .sum();
+            let mut pen_x = -text_width / 2.0;
+            let anchor = [label.position.x, label.position.y, label.position.z];
+            for glyph in label.text.chars().filter_map(|c| self.glyph(c)) {
+                // Flip Y axis of bitmap space to point upwards on screen
+                let left = (pen_x + glyph.bounds_min[0]) * scale;
+                let right = (pen_x + glyph.bounds_max[0]) * scale;
+                let top = -glyph.bounds_min[1] * scale;
+                let bottom = -glyph.bounds_max[1] * scale;
+                let corner = |x: f32, y: f32, u: f32, v: f32| LabelVertex {
+                    anchor,
+                    offset: [x, y],
+                    tex_coords: [u, v],
+                    color: label.color,
+                };
+                let [u0, v0] = glyph.uv_min;
+                let [u1, v1] = glyph.uv_max;
+                vertices.extend([
+                    corner(left, top, u0, v0),
+                    corner(left, bottom, u0, v1),
+                    corner(right, bottom, u1, v1),
+                    corner(left, top, u0, v0),
+                    corner(right, bottom, u1, v1),
+                    corner(right, top, u1, v0),
+                ]);
+                pen_x += glyph.advance;
+            }
+        }
+        vertices
+    }
+
+    /// Glyph of character if available in atlas. Whitespace has no bitmap but advances the pen.
+    fn glyph(&self, c: char) -> Option<&Glyph> {
+        if CHARACTERS.contains(&c) {
+            self.glyphs
+                .get((c as u32 - *CHARACTERS.start() as u32) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for LabelRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            gl_resources::delete_vertex_array(&self.gl, self.vertex_array);
+            gl_resources::delete_texture(&self.gl, self.font_atlas);
+        }
+    }
+}
+
+/// Pack bitmaps of all characters into rows of a single channel texture.
+///
+/// Returns glyph placements, height of the atlas and its pixels.
+fn rasterize_atlas(font: &FontVec) -> (Vec<Glyph>, u32, Vec<u8>) {
+    let scale = PxScale::from(ATLAS_FONT_SIZE);
+    let scaled_font = font.as_scaled(scale);
+
+    // Place glyphs first to know the height of atlas
+    let mut placements = Vec::new();
+    let (mut x, mut y, mut row_height) = (ATLAS_PADDING, ATLAS_PADDING, 0);
+    for c in CHARACTERS {
+        let glyph_id = scaled_font.glyph_id(c);
+        let advance = scaled_font.h_advance(glyph_id);
+        let outline = font.outline_glyph(glyph_id.with_scale(scale));
+        let (width, height) = outline.as_ref().map_or((0, 0), |outline| {
+            let bounds = outline.px_bounds();
+            (bounds.width() as u32, bounds.height() as u32)
+        });
+        if ATLAS_WIDTH < x + width + ATLAS_PADDING {
+            x = ATLAS_PADDING;
+            y += row_height + ATLAS_PADDING;
+            row_height = 0;
+        }
+        placements.push((outline, advance, x, y, width, height));
+        x += width + ATLAS_PADDING;
+        row_height = row_height.max(height);
+    }
+    let atlas_height = y + row_height + ATLAS_PADDING;
+
+    let mut pixels = vec![0u8; (ATLAS_WIDTH * atlas_height) as usize];
+    let glyphs = placements
+        .into_iter()
+        .map(|(outline, advance, x, y, width, height)| {
+            let Some(outline) = outline else {
+                return Glyph {
+                    advance,
+                    ..Default::default()
+                };
+            };
+            outline.draw(|glyph_x, glyph_y, coverage| {
+                // Rounding of bounds can produce coverage outside of reserved space
+                if glyph_x < width && glyph_y < height {
+                    let index = (y + glyph_y) * ATLAS_WIDTH + x + glyph_x;
+                    pixels[index as usize] = (coverage.clamp(0.0, 1.0) * 255.0) as u8;
+                }
+            });
+            let bounds = outline.px_bounds();
+            Glyph {
+                uv_min: [
+                    x as f32 / ATLAS_WIDTH as f32,
+                    y as f32 / atlas_height as f32,
+                ],
+                uv_max: [
+                    (x + width) as f32 / ATLAS_WIDTH as f32,
+                    (y + height) as f32 / atlas_height as f32,
+                ],
+                bounds_min: [bounds.min.x, bounds.min.y],
+                bounds_max: [bounds.min.x + width as f32, bounds.min.y + height as f32],
+                advance,
+            }
+        })
+        .collect();
+
+    (glyphs, atlas_height, pixels)
+}
@@ -1,37 +1,153 @@
 use cfg_if::cfg_if;
 
 mod app;
+pub use app::{App, AppBuilder, SceneState};
+mod asset_cache;
+pub use asset_cache::{AssetCache, AssetMemoryUsage};
 mod assets;
-pub use app::App;
+mod bvh;
+pub use bvh::{Bvh, BvhStats, Ray, RayHit};
 mod camera;
-pub use camera::Camera;
+pub use camera::{Camera, CameraBookmark, CameraTransition};
+mod comparison;
+pub use comparison::Comparison;
+mod crash_report;
+pub use crash_report::install_panic_hook;
+mod decal;
+pub use decal::{Decal, MAX_DECALS};
 mod draw_properties;
-pub use draw_properties::DrawProperties;
+pub use draw_properties::{AntiAliasing, CameraMode, DrawProperties, RenderPath, StereoMode};
+mod error;
+pub use error::RendererError;
+mod framebuffer;
+mod frustum;
+mod gizmo;
+mod gl_info;
+pub use gl_info::GlInfo;
+mod gl_resources;
+mod gl_state;
 mod gui;
 pub use gui::Gui;
+mod input;
+mod label;
+mod line;
+mod localization;
+pub use localization::Language;
+mod material;
+pub use material::{Material, MaterialType, VertexColorMode};
+mod mesh_batch;
+mod mirror;
 mod model;
-pub use model::Model;
+pub use model::{Aabb, FaceCulling, Mesh, MeshData, MeshPart, Model, Vertex, Winding};
+mod modulation;
+pub use modulation::{Modulation, ModulationBinding, ModulationTarget, MAX_MODULATION_SOURCES};
+mod occlusion;
+pub use occlusion::OcclusionStats;
+mod post_effect;
+pub use post_effect::{PostEffect, PostEffectSettings};
+pub mod primitives;
+mod profiler;
+mod quality_scaler;
+mod render_hook;
+pub use render_hook::RenderHook;
 mod renderer;
-pub use renderer::Renderer;
+pub use renderer::{DrawStats, EyeView, LodStats, RenderStats, Renderer};
+mod scene;
+pub use scene::{
+    AreaLight, DirectionalLight, Name, PartOverride, PartOverrides, PointLight, Renderable, Scene,
+    Selectable, Transform, Visible, MAX_AREA_LIGHTS, MAX_POINT_LIGHTS,
+};
 mod shader;
+mod shader_variant;
+mod shadow;
+pub use shadow::{ShadowSettings, MAX_SHADOW_CASCADES};
 mod skybox;
-pub use skybox::Skybox;
+pub use skybox::{CubemapFace, Skybox};
+mod streaming_buffer;
+mod taa;
+mod terrain;
+pub use terrain::{Terrain, TerrainSettings, TerrainStats};
+mod texture;
+mod timeline;
+pub use timeline::{AnimatedProperty, Keyframe, Timeline, Track};
+mod tween;
+pub use tween::{Easing, Interpolate, Tween};
+mod vertex_layout;
+pub use vertex_layout::{VertexAttribute, VertexLayout};
+mod viewport_widget;
+pub use viewport_widget::ViewportWidget;
+
+/// Internals measured by benchmarks under `benches/`. Not part of the public API.
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::model::{detect_face_culling, process_obj, Vertex};
+    pub use crate::renderer::{calculate_model_matrix, calculate_normal_matrix};
+}
 
 cfg_if! { if #[cfg(target_arch = "wasm32")] {
     use wasm_bindgen::prelude::*;
 
     mod html_ui;
     pub use html_ui::HtmlUI;
+    mod web_xr;
+    pub use web_xr::WebXrSession;
     pub use skybox::SkyboxBufferBuilder;
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
     pub fn start() -> Result<(), JsValue> {
-        let mut app = App::new().map_err(|e| JsValue::from_str(&format!("failed to initialize app: {}", e)))?;
+        install_panic_hook();
+        let app = App::new().map_err(|e| JsValue::from_str(&format!("failed to initialize app: {}", e)))?;
         app.run().map_err(|e| JsValue::from_str(&e))?;
 
         Ok(())
     }
 } else {
-    pub use draw_properties::FrameRateInfo;
+    mod asset_watcher;
+    #[cfg(feature = "ffi")]
+    mod ffi;
+    #[cfg(feature = "audio")]
+    mod audio;
+    mod frame_sequence;
+    mod inspector;
+    mod lightmap;
+    mod measurement;
+    mod mesh_export;
+    mod mesh_repair;
+    mod mesh_validation;
+    mod multi_draw;
+    mod osc;
+    mod pipeline_statistics;
+    mod ply;
+    #[cfg(feature = "python")]
+    mod python;
+    mod remote;
+    #[cfg(feature = "remote")]
+    mod remote_server;
+    #[cfg(feature = "http")]
+    mod snapshot_server;
+    mod swap_control;
+    mod thumbnail;
+    mod uv_layout;
+    mod vertex_displacement;
+    #[cfg(feature = "xr")]
+    mod xr;
+
+    #[cfg(feature = "audio")]
+    pub use audio::{AudioAnalyzer, AUDIO_BAND_COUNT};
+    pub use draw_properties::{FrameRateInfo, VsyncMode};
+    pub use gui::GuiAction;
+    pub use inspector::{InspectedTriangle, Inspector};
+    pub use lightmap::{
+        bake_lightmaps, generate_lightmap_uvs, Lightmap, LightmapBake, LightmapSettings,
+    };
+    pub use measurement::Measurement;
+    pub use mesh_repair::MeshRepair;
+    pub use mesh_validation::MeshValidation;
+    pub use model::MeshTriangle;
+    pub use pipeline_statistics::PipelineStats;
     pub use skybox::SkyboxFileBuilder;
+    pub use thumbnail::{render_thumbnail, ThumbnailOptions};
+    pub use uv_layout::UvLayout;
+    #[cfg(feature = "xr")]
+    pub use xr::XrSession;
 }}
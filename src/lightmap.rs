@@ -0,0 +1,699 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use cgmath::{
+    vec2, vec3, Array, EuclideanSpace, InnerSpace, Matrix3, Matrix4, Point3, SquareMatrix,
+    Transform, Vector2, Vector3, Zero,
+};
+use glow::{HasContext, Texture};
+use hecs::Entity;
+
+use crate::{
+    gl_resources, gl_state,
+    renderer::{calculate_model_matrix, calculate_normal_matrix},
+    Aabb, Bvh, Material, Mesh, MeshData, Ray, RayHit, Renderable, Scene, VertexAttribute, Visible,
+};
+
+/// Empty texels kept between the triangles of a lightmap, so bilinear filtering doesn't blend
+/// lighting of unrelated triangles.
+const TEXEL_PADDING: f32 = 1.0;
+/// Radiance of the sky reaching surfaces from directions not blocked by the scene. Matches the
+/// ambient strength of realtime lighting, so unoccluded surfaces are equally bright in both modes.
+const SKY_RADIANCE: f32 = 0.2;
+/// Distance of ray origins from the surface relative to the scene size, avoiding hits of the
+/// surface the ray starts from.
+const RAY_OFFSET_SCALE: f32 = 1e-4;
+/// Passes filling texels outside of triangles from their neighbours, so texels sampled by bilinear
+/// filtering at triangle edges aren't black.
+const DILATION_PASSES: usize = 2;
+
+/// Quality of baked lighting.
+#[derive(Clone, Copy)]
+pub struct LightmapSettings {
+    /// Width and height of the lightmap texture of each entity in texels.
+    pub resolution: u32,
+    /// Rays cast from each texel for indirect lighting. More samples reduce noise.
+    pub samples: u32,
+}
+
+impl Default for LightmapSettings {
+    fn default() -> Self {
+        Self {
+            resolution: 1024,
+            samples: 16,
+        }
+    }
+}
+
+/// Lighting of an entity precomputed into a texture, drawn instead of realtime lighting for
+/// comparison.
+///
+/// Holds a copy of the entity mesh with lightmap texture coordinates, because generating them
+/// splits every vertex shared by triangles. Triangles keep their order, so parts of the model
+/// refer to the same indices. Baked for the placement of the entity at the time of baking, moving
+/// the entity or the light afterwards makes the lighting stale.
+pub struct Lightmap {
+    gl: Arc<glow::Context>,
+    mesh: Mesh,
+    texture: Texture,
+    resolution: u32,
+    bake_time: Duration,
+}
+
+impl Lightmap {
+    /// Mesh of the entity with `VertexAttribute::LightmapUv`.
+    pub fn mesh(&self) -> &Mesh {
+        &self.mesh
+    }
+
+    /// Linear RGB irradiance, multiplied by the material color when drawn.
+    pub fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    pub fn bake_time(&self) -> Duration {
+        self.bake_time
+    }
+}
+
+impl Drop for Lightmap {
+    fn drop(&mut self) {
+        unsafe {
+            gl_resources::delete_texture(&self.gl, self.texture);
+        }
+    }
+}
+
+/// Copy of the mesh with lightmap texture coordinates. Every triangle gets its own vertices and
+/// its own area of the lightmap, pairs of triangles sharing a square cell of a uniform grid. Cells
+/// have the same size regardless of triangle size, trading texel density of large triangles for a
+/// simple packing without overlaps.
+pub fn generate_lightmap_uvs(mesh_data: &MeshData, resolution: u32) -> Result<MeshData, String> {
+    let triangle_count = mesh_data.indices.len() / 3;
+    let cells_per_row = (triangle_count.div_ceil(2) as f32).sqrt().ceil().max(1.0);
+    let cell_size = resolution as f32 / cells_per_row;
+    // Each triangle needs at least a texel inside of the padding around and between them
+    if cell_size < 3.0 * TEXEL_PADDING + 2.0 {
+        return Err(format!(
+            "lightmap resolution {resolution} is too low for {triangle_count} triangles"
+        ));
+    }
+
+    let layout = mesh_data.vertex_layout();
+    let has = |attribute| layout.contains(attribute);
+    let mut unwelded = MeshData {
+        parts: mesh_data.parts.clone(),
+        ..Default::default()
+    };
+    // Legs of the right triangle mapped into each half of the cell, with padding on every side and
+    // between the diagonals
+    let leg = cell_size - 3.0 * TEXEL_PADDING;
+    for (triangle_index, triangle) in mesh_data.indices.chunks_exact(3).enumerate() {
+        let cell = triangle_index / 2;
+        let cell_origin = vec2(
+            (cell % cells_per_row as usize) as f32,
+            (cell / cells_per_row as usize) as f32,
+        ) * cell_size;
+        let corners = if triangle_index % 2 == 0 {
+            let origin = cell_origin + vec2(TEXEL_PADDING, TEXEL_PADDING);
+            [origin, origin + vec2(leg, 0.0), origin + vec2(0.0, leg)]
+        } else {
+            let origin = cell_origin + vec2(cell_size - TEXEL_PADDING, cell_size - TEXEL_PADDING);
+            [origin, origin - vec2(leg, 0.0), origin - vec2(0.0, leg)]
+        };
+
+        for (&index, corner) in triangle.iter().zip(corners) {
+            let index = index as usize;
+            unwelded.indices.push(unwelded.vertices.len() as u32);
+            unwelded.vertices.push(mesh_data.vertices[index]);
+            if has(VertexAttribute::Uv) {
+                unwelded.uvs.push(mesh_data.uvs[index]);
+            }
+            if has(VertexAttribute::Tangent) {
+                unwelded.tangents.push(mesh_data.tangents[index]);
+            }
+            if has(VertexAttribute::Color) {
+                unwelded.colors.push(mesh_data.colors[index]);
+            }
+            unwelded.lightmap_uvs.push(corner / resolution as f32);
+        }
+    }
+    Ok(unwelded)
+}
+
+/// Bake direct and indirect lighting of every visible entity into lightmaps, lit by the light of
+/// the scene and a uniform sky. Blocks until every lightmap is done, see `LightmapBake` for baking
+/// in the background.
+///
+/// Direct light is shadowed by the other entities. Indirect light gathers a single bounce of direct
+/// light and sky light off the surfaces hit by rays cast into the hemisphere above each texel,
+/// tinted by their material color. Terrain is not taken into account. Texels are processed in
+/// parallel on every available CPU core.
+pub fn bake_lightmaps(
+    gl: &Arc<glow::Context>,
+    scene: &Scene,
+    settings: &LightmapSettings,
+) -> Result<Vec<(Entity, Lightmap)>, String> {
+    LightmapBake::start(scene, settings)?.finish(gl)
+}
+
+/// Lighting of `bake_lightmaps()` traced on a worker thread, so the application stays responsive
+/// during baking. Poll `is_finished()` and upload the lightmaps with `finish()` on the thread of
+/// the graphics context.
+///
+/// Meshes and entity placements are read when starting, later changes of the scene don't affect
+/// the result.
+pub struct LightmapBake {
+    worker: thread::JoinHandle<Result<Vec<TracedLightmap>, String>>,
+    // Texels of every lightmap traced so far
+    traced_texels: Arc<AtomicUsize>,
+    texel_count: usize,
+    resolution: u32,
+}
+
+/// Irradiance of an entity waiting for upload.
+struct TracedLightmap {
+    entity: Entity,
+    unwelded: MeshData,
+    texels: Vec<Vector3<f32>>,
+    bake_time: Duration,
+}
+
+impl LightmapBake {
+    pub fn start(scene: &Scene, settings: &LightmapSettings) -> Result<Self, String> {
+        let occluders = collect_occluders(scene)?;
+        let scene_radius = scene.visible_bounds().map_or(0.0, |bounds| bounds.radius());
+        let [x, y, z] = scene.light_direction();
+        let to_light = -vec3(x, y, z).normalize();
+        let resolution = settings.resolution;
+        let samples = settings.samples.max(1);
+        let traced_texels = Arc::new(AtomicUsize::new(0));
+        let texel_count = occluders.len() * (resolution * resolution) as usize;
+
+        let progress = traced_texels.clone();
+        let worker = thread::Builder::new()
+            .name("lightmap bake".to_owned())
+            .spawn(move || {
+                let tracer = Tracer {
+                    occluders: &occluders,
+                    to_light,
+                    ray_offset: scene_radius.max(f32::EPSILON) * RAY_OFFSET_SCALE,
+                    samples,
+                    traced_texels: &progress,
+                };
+                occluders
+                    .iter()
+                    .map(|occluder| tracer.trace_lightmap(occluder, resolution))
+                    .collect()
+            })
+            .map_err(|e| format!("cannot start baking lighting: {e}"))?;
+        Ok(Self {
+            worker,
+            traced_texels,
+            texel_count,
+            resolution,
+        })
+    }
+
+    /// Fraction of texels traced from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        let traced_texels = self.traced_texels.load(Ordering::Relaxed);
+        (traced_texels as f32 / self.texel_count.max(1) as f32).min(1.0)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.worker.is_finished()
+    }
+
+    /// Upload the traced lighting, waiting for the worker thread if it's still running.
+    pub fn finish(self, gl: &Arc<glow::Context>) -> Result<Vec<(Entity, Lightmap)>, String> {
+        let traced_lightmaps = self
+            .worker
+            .join()
+            .map_err(|_| "baking lighting failed unexpectedly".to_owned())??;
+        traced_lightmaps
+            .into_iter()
+            .map(|traced| {
+                let texture = upload_texture(gl, &traced.texels, self.resolution)?;
+                Ok((
+                    traced.entity,
+                    Lightmap {
+                        gl: gl.clone(),
+                        // Simplified levels of detail would lose lightmap texture coordinates
+                        mesh: Mesh::upload(gl.clone(), &traced.unwelded),
+                        texture,
+                        resolution: self.resolution,
+                        bake_time: traced.bake_time,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Visible entity blocking and reflecting light, in model space of its mesh.
+struct Occluder {
+    entity: Entity,
+    name: String,
+    model_matrix: Matrix4<f32>,
+    inverse_model_matrix: Matrix4<f32>,
+    normal_matrix: Matrix3<f32>,
+    bounds: Aabb,
+    bvh: Arc<Bvh>,
+    // Vertex normals of triangles hit by rays
+    mesh_data: MeshData,
+    color: Vector3<f32>,
+}
+
+fn collect_occluders(scene: &Scene) -> Result<Vec<Occluder>, String> {
+    let mut occluders = Vec::new();
+    let mut query = scene
+        .world
+        .query::<(&Renderable, &crate::Transform, &Material)>()
+        .with::<&Visible>();
+    for (entity, (renderable, transform, material)) in query.iter() {
+        let model = &scene.models[renderable.model_index];
+        let model_matrix = calculate_model_matrix(transform);
+        let Some(inverse_model_matrix) = model_matrix.invert() else {
+            continue;
+        };
+        let mesh = model.lod(0);
+        let mesh_data = mesh
+            .read_back()
+            .map_err(|e| format!("unable to bake lightmap of {}: {e}", model.name))?;
+        if mesh_data.indices.is_empty() {
+            continue;
+        }
+        let [r, g, b] = material.color;
+        occluders.push(Occluder {
+            entity,
+            name: model.name.clone(),
+            model_matrix,
+            inverse_model_matrix,
+            normal_matrix: calculate_normal_matrix(&model_matrix),
+            bounds: model.bounds(),
+            bvh: mesh.bvh()?.clone(),
+            mesh_data,
+            color: vec3(r, g, b),
+        });
+    }
+    Ok(occluders)
+}
+
+/// World space point of a triangle seen by a lightmap texel.
+#[derive(Clone, Copy)]
+struct Surface {
+    position: Point3<f32>,
+    normal: Vector3<f32>,
+}
+
+/// Surface seen by each texel of the lightmap, `None` for texels outside of triangles.
+fn rasterize(unwelded: &MeshData, resolution: u32, occluder: &Occluder) -> Vec<Option<Surface>> {
+    let mut surfaces = vec![None; (resolution * resolution) as usize];
+    for triangle in unwelded.indices.chunks_exact(3) {
+        let [a, b, c] =
+            [0, 1, 2].map(|i| unwelded.lightmap_uvs[triangle[i] as usize] * resolution as f32);
+        let vertices = [0, 1, 2].map(|i| unwelded.vertices[triangle[i] as usize]);
+        let min = vec2(a.x.min(b.x).min(c.x), a.y.min(b.y).min(c.y));
+        let max = vec2(a.x.max(b.x).max(c.x), a.y.max(b.y).max(c.y));
+        for y in min.y.floor() as u32..(max.y.ceil() as u32).min(resolution) {
+            for x in min.x.floor() as u32..(max.x.ceil() as u32).min(resolution) {
+                let center = vec2(x as f32 + 0.5, y as f32 + 0.5);
+                // Texels overlapping the edges are included, their centers clamped onto the
+                // triangle
+                let Some(weights) = barycentric(center, [a, b, c], 0.5 / (max - min).magnitude())
+                else {
+                    continue;
+                };
+                let position = interpolate(weights, vertices.map(|vertex| vertex.position));
+                let normal = interpolate(weights, vertices.map(|vertex| vertex.normal));
+                if normal.magnitude2() < f32::EPSILON {
+                    continue;
+                }
+                surfaces[(y * resolution + x) as usize] = Some(Surface {
+                    position: occluder
+                        .model_matrix
+                        .transform_point(Point3::from_vec(position)),
+                    normal: (occluder.normal_matrix * normal).normalize(),
+                });
+            }
+        }
+    }
+    surfaces
+}
+
+/// Weights of the triangle corners at the point, clamped onto the triangle. `None` if the point is
+/// farther outside than the tolerance, given in fractions of the triangle size.
+fn barycentric(
+    point: Vector2<f32>,
+    [a, b, c]: [Vector2<f32>; 3],
+    tolerance: f32,
+) -> Option<[f32; 3]> {
+    let cross = |u: Vector2<f32>, v: Vector2<f32>| u.x * v.y - u.y * v.x;
+    let area = cross(b - a, c - a);
+    if area.abs() < f32::EPSILON {
+        return None;
+    }
+    let v = cross(point - a, c - a) / area;
+    let w = cross(b - a, point - a) / area;
+    let weights = [1.0 - v - w, v, w];
+    if weights.iter().any(|&weight| weight < -tolerance) {
+        return None;
+    }
+    let weights = weights.map(|weight| weight.max(0.0));
+    let sum: f32 = weights.iter().sum();
+    Some(weights.map(|weight| weight / sum))
+}
+
+fn interpolate([u, v, w]: [f32; 3], [a, b, c]: [Vector3<f32>; 3]) -> Vector3<f32> {
+    a * u + b * v + c * w
+}
+
+/// Ray queries against the occluders of the scene.
+struct Tracer<'a> {
+    occluders: &'a [Occluder],
+    // Normalized direction pointing towards the light
+    to_light: Vector3<f32>,
+    ray_offset: f32,
+    samples: u32,
+    // Progress reported to `LightmapBake`
+    traced_texels: &'a AtomicUsize,
+}
+
+impl Tracer<'_> {
+    fn trace_lightmap(
+        &self,
+        occluder: &Occluder,
+        resolution: u32,
+    ) -> Result<TracedLightmap, String> {
+        let start_time = Instant::now();
+        let unwelded = generate_lightmap_uvs(&occluder.mesh_data, resolution)
+            .map_err(|e| format!("unable to bake lightmap of {}: {e}", occluder.name))?;
+        let surfaces = rasterize(&unwelded, resolution, occluder);
+        let mut texels = self.irradiance(&surfaces, resolution);
+        dilate(&mut texels, &surfaces, resolution);
+        Ok(TracedLightmap {
+            entity: occluder.entity,
+            unwelded,
+            texels,
+            bake_time: start_time.elapsed(),
+        })
+    }
+
+    /// Irradiance of each texel as linear RGB, split into rows processed on separate threads.
+    fn irradiance(&self, surfaces: &[Option<Surface>], resolution: u32) -> Vec<Vector3<f32>> {
+        let mut texels = vec![Vector3::zero(); surfaces.len()];
+        let thread_count = thread::available_parallelism().map_or(1, |count| count.get());
+        let chunk_size = surfaces
+            .len()
+            .div_ceil(thread_count)
+            .max(resolution as usize);
+        thread::scope(|scope| {
+            for (chunk_index, (texels, surfaces)) in texels
+                .chunks_mut(chunk_size)
+                .zip(surfaces.chunks(chunk_size))
+                .enumerate()
+            {
+                scope.spawn(move || {
+                    for (i, (texel, surface)) in texels.iter_mut().zip(surfaces).enumerate() {
+                        if let Some(surface) = surface {
+                            let seed = (chunk_index * chunk_size + i) as u32;
+                            *texel = self.texel_irradiance(surface, seed);
+                        }
+                        // Reported once per row, so threads don't contend for the counter
+                        if (i + 1) % resolution as usize == 0 {
+                            self.traced_texels
+                                .fetch_add(resolution as usize, Ordering::Relaxed);
+                        }
+                    }
+                    self.traced_texels
+                        .fetch_add(texels.len() % resolution as usize, Ordering::Relaxed);
+                });
+            }
+        });
+        texels
+    }
+
+    fn texel_irradiance(&self, surface: &Surface, seed: u32) -> Vector3<f32> {
+        let direct = Vector3::from_value(self.direct(surface));
+        // Cosine weighted directions cancel the cosine term of the rendering equation, leaving
+        // the average of incoming radiance
+        let rotation = vec2(hash(seed), hash(seed ^ 0x9e37_79b9));
+        let mut indirect = Vector3::zero();
+        for sample in 0..self.samples {
+            let (u, v) = hammersley(sample, self.samples);
+            let direction = cosine_direction(
+                surface.normal,
+                (u + rotation.x).fract(),
+                (v + rotation.y).fract(),
+            );
+            let ray = Ray {
+                origin: surface.position + surface.normal * self.ray_offset,
+                direction,
+            };
+            indirect += match self.closest_hit(&ray) {
+                Some((occluder, hit)) => {
+                    let mut normal = occluder.hit_normal(&hit);
+                    if 0.0 < normal.dot(direction) {
+                        normal = -normal;
+                    }
+                    let hit_surface = Surface {
+                        position: ray.at(hit.distance),
+                        normal,
+                    };
+                    occluder.color * (self.direct(&hit_surface) + SKY_RADIANCE)
+                }
+                None => Vector3::from_value(SKY_RADIANCE),
+            };
+        }
+        direct + indirect / self.samples as f32
+    }
+
+    /// Irradiance of the light reaching the surface unless shadowed, relative to the light
+    /// intensity.
+    fn direct(&self, surface: &Surface) -> f32 {
+        let cosine = surface.normal.dot(self.to_light);
+        if cosine <= 0.0 {
+            return 0.0;
+        }
+        let shadow_ray = Ray {
+            origin: surface.position + surface.normal * self.ray_offset,
+            direction: self.to_light,
+        };
+        let shadowed = self.occluders.iter().any(|occluder| {
+            let model_ray = shadow_ray.transformed(&occluder.inverse_model_matrix);
+            model_ray.intersect_aabb(&occluder.bounds).is_some()
+                && occluder.bvh.occluded(&model_ray, f32::INFINITY)
+        });
+        if shadowed {
+            0.0
+        } else {
+            cosine
+        }
+    }
+
+    fn closest_hit(&self, ray: &Ray) -> Option<(&Occluder, RayHit)> {
+        let mut closest: Option<(&Occluder, RayHit)> = None;
+        for occluder in self.occluders {
+            let model_ray = ray.transformed(&occluder.inverse_model_matrix);
+            let max_distance = closest.map_or(f32::INFINITY, |(_, hit)| hit.distance);
+            if model_ray
+                .intersect_aabb(&occluder.bounds)
+                .is_none_or(|distance| max_distance <= distance)
+            {
+                continue;
+            }
+            if let Some(hit) = occluder.bvh.intersect(&model_ray, max_distance) {
+                closest = Some((occluder, hit));
+            }
+        }
+        closest
+    }
+}
+
+impl Occluder {
+    /// World space vertex normal interpolated at the hit point.
+    fn hit_normal(&self, hit: &RayHit) -> Vector3<f32> {
+        let triangle = &self.mesh_data.indices[hit.triangle * 3..hit.triangle * 3 + 3];
+        let (v, w) = hit.barycentric;
+        let vertices = [0, 1, 2].map(|i| self.mesh_data.vertices[triangle[i] as usize]);
+        let normal = interpolate([1.0 - v - w, v, w], vertices.map(|vertex| vertex.normal));
+        (self.normal_matrix * normal).normalize()
+    }
+}
+
+/// Point of a low-discrepancy 2D sequence, spreading samples more evenly than random numbers.
+fn hammersley(index: u32, count: u32) -> (f32, f32) {
+    (
+        (index as f32 + 0.5) / count as f32,
+        index.reverse_bits() as f32 / 2f32.powi(32),
+    )
+}
+
+/// Pseudo-random number from 0 to 1, decorrelating the sample sequence of neighbouring texels.
+fn hash(seed: u32) -> f32 {
+    let mut x = seed;
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    x as f32 / 2f32.powi(32)
+}
+
+/// Direction in the hemisphere around the normal, distributed proportionally to the cosine of its
+/// angle with the normal.
+fn cosine_direction(normal: Vector3<f32>, u: f32, v: f32) -> Vector3<f32> {
+    let radius = u.sqrt();
+    let angle = 2.0 * std::f32::consts::PI * v;
+    let helper = if normal.x.abs() < 0.9 {
+        Vector3::unit_x()
+    } else {
+        Vector3::unit_y()
+    };
+    let tangent = normal.cross(helper).normalize();
+    let bitangent = normal.cross(tangent);
+    tangent * (radius * angle.cos())
+        + bitangent * (radius * angle.sin())
+        + normal * (1.0 - u).max(0.0).sqrt()
+}
+
+/// Fill texels outside of triangles with the average of their covered neighbours.
+fn dilate(texels: &mut [Vector3<f32>], surfaces: &[Option<Surface>], resolution: u32) {
+    let resolution = resolution as i32;
+    let mut covered: Vec<bool> = surfaces.iter().map(Option::is_some).collect();
+    for _ in 0..DILATION_PASSES {
+        let mut filled = Vec::new();
+        for y in 0..resolution {
+            for x in 0..resolution {
+                let index = (y * resolution + x) as usize;
+                if covered[index] {
+                    continue;
+                }
+                let mut sum = Vector3::zero();
+                let mut count = 0;
+                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if (0..resolution).contains(&nx) && (0..resolution).contains(&ny) {
+                        let neighbour = (ny * resolution + nx) as usize;
+                        if covered[neighbour] {
+                            sum += texels[neighbour];
+                            count += 1;
+                        }
+                    }
+                }
+                if 0 < count {
+                    filled.push((index, sum / count as f32));
+                }
+            }
+        }
+        for (index, value) in filled {
+            texels[index] = value;
+            covered[index] = true;
+        }
+    }
+}
+
+fn upload_texture(
+    gl: &glow::Context,
+    texels: &[Vector3<f32>],
+    resolution: u32,
+) -> Result<Texture, String> {
+    let floats: Vec<f32> = texels
+        .iter()
+        .flat_map(|texel| [texel.x, texel.y, texel.z])
+        .collect();
+    unsafe {
+        let texture = gl_resources::create_texture(gl)
+            .map_err(|e| format!("cannot create lightmap texture: {e}"))?;
+        gl_state::bind_texture(gl, glow::TEXTURE_2D, Some(texture));
+        // Half precision keeps irradiance above 1.0 with half the memory of full precision
+        let (_, bytes, _) = floats.align_to::<u8>();
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::RGB16F as i32,
+            resolution as i32,
+            resolution as i32,
+            0,
+            glow::RGB,
+            glow::FLOAT,
+            Some(bytes),
+        );
+        gl_resources::set_texture_size(texture, texels.len() * 6);
+        for (parameter, value) in [
+            (glow::TEXTURE_MIN_FILTER, glow::LINEAR),
+            (glow::TEXTURE_MAG_FILTER, glow::LINEAR),
+            (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+            (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+        ] {
+            gl.tex_parameter_i32(glow::TEXTURE_2D, parameter, value as i32);
+        }
+        gl_state::bind_texture(gl, glow::TEXTURE_2D, None);
+        Ok(texture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{primitives, UvLayout};
+
+    #[test]
+    fn lightmap_uvs_stay_inside_texture() {
+        let mesh_data = primitives::sphere(1.0, 24, 12);
+        let unwelded = generate_lightmap_uvs(&mesh_data, 512).unwrap();
+        assert_eq!(unwelded.vertices.len(), mesh_data.indices.len());
+        assert_eq!(unwelded.lightmap_uvs.len(), unwelded.vertices.len());
+        assert_eq!(unwelded.uvs.len(), unwelded.vertices.len());
+        // Padding keeps texture coordinates off the texture edges
+        let padding = TEXEL_PADDING / 512.0;
+        for uv in &unwelded.lightmap_uvs {
+            assert!((padding..=1.0 - padding).contains(&uv.x), "{uv:?}");
+            assert!((padding..=1.0 - padding).contains(&uv.y), "{uv:?}");
+        }
+    }
+
+    #[test]
+    fn lightmap_cells_do_not_overlap() {
+        for mesh_data in [
+            primitives::cuboid(1.0, 1.0, 1.0),
+            primitives::sphere(1.0, 24, 12),
+            primitives::torus(1.0, 0.25, 32, 16),
+        ] {
+            let unwelded = generate_lightmap_uvs(&mesh_data, 1024).unwrap();
+            let layout = UvLayout::analyze(&unwelded.lightmap_uvs, &unwelded.indices);
+            assert_eq!(layout.triangle_count, mesh_data.indices.len() / 3);
+            assert_eq!(layout.overlapping_triangles, 0);
+        }
+    }
+
+    #[test]
+    fn lightmap_uvs_keep_triangle_order() {
+        let mesh_data = primitives::cuboid(1.0, 2.0, 3.0);
+        let unwelded = generate_lightmap_uvs(&mesh_data, 256).unwrap();
+        for (index, &original) in mesh_data.indices.iter().enumerate() {
+            assert_eq!(unwelded.indices[index] as usize, index);
+            assert_eq!(
+                unwelded.vertices[index].position,
+                mesh_data.vertices[original as usize].position
+            );
+        }
+    }
+
+    #[test]
+    fn too_low_resolution_is_rejected() {
+        let mesh_data = primitives::sphere(1.0, 64, 32);
+        assert!(generate_lightmap_uvs(&mesh_data, 16).is_err());
+    }
+}
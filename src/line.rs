@@ -0,0 +1,176 @@
+use std::{cell::RefCell, sync::Arc};
+
+use cgmath::{Matrix4, Point3, Vector3};
+use glow::{HasContext, VertexArray};
+
+use crate::{
+    assets::shader::ShaderSources, gl_resources, gl_state, model::Aabb, shader::Shader,
+    streaming_buffer::StreamingBuffer,
+};
+
+/// Number of line segments approximating a circle.
+const CIRCLE_SEGMENTS: usize = 32;
+
+/// Per-vertex data of lines.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct LineVertex {
+    pub position: [f32; 3],
+    /// Linear RGB color.
+    pub color: [f32; 3],
+}
+
+/// Draws unlit colored line segments on top of the scene, used by gizmos and debug overlays, and
+/// helper surfaces.
+pub struct LineRenderer {
+    gl: Arc<glow::Context>,
+    shader: Shader,
+    vertex_array: VertexArray,
+    // Rewritten on every draw, because lines follow changing scene properties
+    vertex_buffer: RefCell<StreamingBuffer>,
+}
+
+impl LineRenderer {
+    pub fn new(gl: Arc<glow::Context>, shaders: &ShaderSources) -> Result<Self, String> {
+        let shader = Shader::new(gl.clone(), shaders.line_vertex, shaders.line_fragment)
+            .map_err(|e| format!("line shader creation failed: {:?}", e))?;
+
+        let vertex_buffer =
+            StreamingBuffer::new(gl.clone(), glow::ARRAY_BUFFER, size_of::<LineVertex>())
+                .map_err(|e| format!("cannot create line vertex buffer: {e}"))?;
+        unsafe {
+            let vertex_array = gl_resources::create_vertex_array(&gl)
+                .map_err(|e| format!("cannot create line vertex array: {e}"))?;
+
+            Ok(Self {
+                gl,
+                shader,
+                vertex_array,
+                vertex_buffer: RefCell::new(vertex_buffer),
+            })
+        }
+    }
+
+    /// Draw line segments, each made of a pair of world space vertices, into currently bound
+    /// framebuffer. Lines are always visible, even behind geometry.
+    pub fn draw(&self, vertices: &[LineVertex], projection_view: &Matrix4<f32>) {
+        unsafe {
+            gl_state::disable(&self.gl, glow::DEPTH_TEST);
+            self.draw_primitives(glow::LINES, vertices, projection_view);
+            gl_state::enable(&self.gl, glow::DEPTH_TEST);
+        }
+    }
+
+    /// Draw triangles, each made of three world space vertices, into currently bound framebuffer.
+    /// Depth, stencil and blending state is left to the caller.
+    pub fn draw_triangles(&self, vertices: &[LineVertex], projection_view: &Matrix4<f32>) {
+        self.draw_primitives(glow::TRIANGLES, vertices, projection_view);
+    }
+
+    fn draw_primitives(&self, mode: u32, vertices: &[LineVertex], projection_view: &Matrix4<f32>) {
+        if vertices.is_empty() {
+            return;
+        }
+
+        unsafe {
+            self.shader.r#use();
+            gl_state::bind_vertex_array(&self.gl, Some(self.vertex_array));
+            let first = match self.vertex_buffer.borrow_mut().write(vertices) {
+                Ok(first) => first,
+                Err(e) => {
+                    eprintln!("unable to upload lines: {e}");
+                    gl_state::bind_vertex_array(&self.gl, None);
+                    return;
+                }
+            };
+            // Buffer is replaced when it grows
+            let stride = size_of::<LineVertex>() as i32;
+            self.gl.enable_vertex_attrib_array(0);
+            self.gl.vertex_attrib_pointer_f32(
+                0,
+                3,
+                glow::FLOAT,
+                false,
+                stride,
+                std::mem::offset_of!(LineVertex, position) as i32,
+            );
+            self.gl.enable_vertex_attrib_array(1);
+            self.gl.vertex_attrib_pointer_f32(
+                1,
+                3,
+                glow::FLOAT,
+                false,
+                stride,
+                std::mem::offset_of!(LineVertex, color) as i32,
+            );
+            self.shader.set_uniform("u_projectionView", projection_view);
+
+            self.gl
+                .draw_arrays(mode, first as i32, vertices.len() as i32);
+
+            // Reset state
+            self.gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            gl_state::bind_vertex_array(&self.gl, None);
+        }
+    }
+}
+
+impl Drop for LineRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            gl_resources::delete_vertex_array(&self.gl, self.vertex_array);
+        }
+    }
+}
+
+/// Segments of the 12 edges of a box.
+pub fn box_lines(aabb: &Aabb, color: [f32; 3]) -> Vec<LineVertex> {
+    let corners = aabb.corners();
+    // Corner indices are bit masks of the maximum X, Y and Z coordinates
+    const EDGES: [(usize, usize); 12] = [
+        (0, 1),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (0, 2),
+        (1, 3),
+        (4, 6),
+        (5, 7),
+        (0, 4),
+        (1, 5),
+        (2, 6),
+        (3, 7),
+    ];
+    EDGES
+        .iter()
+        .flat_map(|&(start, end)| [corners[start], corners[end]])
+        .map(|position| LineVertex {
+            position: position.into(),
+            color,
+        })
+        .collect()
+}
+
+/// Segments of a wireframe sphere made of three orthogonal circles.
+pub fn sphere_lines(center: Point3<f32>, radius: f32, color: [f32; 3]) -> Vec<LineVertex> {
+    let axes = [
+        (Vector3::unit_x(), Vector3::unit_y()),
+        (Vector3::unit_y(), Vector3::unit_z()),
+        (Vector3::unit_z(), Vector3::unit_x()),
+    ];
+    let mut vertices = Vec::with_capacity(axes.len() * CIRCLE_SEGMENTS * 2);
+    for (u, v) in axes {
+        let circle_point = |i: usize| {
+            let angle = i as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+            LineVertex {
+                position: (center + (u * angle.cos() + v * angle.sin()) * radius).into(),
+                color,
+            }
+        };
+        for i in 0..CIRCLE_SEGMENTS {
+            vertices.push(circle_point(i));
+            vertices.push(circle_point(i + 1));
+        }
+    }
+    vertices
+}
@@ -0,0 +1,117 @@
+use fluent::{FluentArgs, FluentBundle, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+use crate::assets;
+
+/// Language of the overlay GUI and of the HTML controls.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Hungarian,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Hungarian];
+
+    /// Name of the language written in the language itself, as displayed by language selectors.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Hungarian => "Magyar",
+        }
+    }
+
+    /// BCP 47 language tag, e.g. for the `lang` attribute of HTML documents.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Language::English => "en-US",
+            Language::Hungarian => "hu-HU",
+        }
+    }
+
+    fn catalogue(self) -> &'static str {
+        match self {
+            Language::English => assets::locale::EN_US,
+            Language::Hungarian => assets::locale::HU_HU,
+        }
+    }
+}
+
+/// Translated GUI strings of a language, looked up by message identifier.
+///
+/// Messages missing from the catalogue of the language fall back to English, then to the message
+/// identifier itself, so an incomplete translation never hides a widget.
+pub struct Localization {
+    language: Language,
+    bundle: FluentBundle<FluentResource>,
+    // Missing when the language is English already
+    fallback: Option<FluentBundle<FluentResource>>,
+}
+
+impl Localization {
+    pub fn new(language: Language) -> Self {
+        let fallback = (language != Language::English).then(|| create_bundle(Language::English));
+        Self {
+            language,
+            bundle: create_bundle(language),
+            fallback,
+        }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Message without arguments.
+    pub fn tr(&self, id: &str) -> String {
+        self.format(id, None)
+    }
+
+    /// Message with arguments substituted into its placeables, e.g. `{ $count }`.
+    pub fn tr_args(&self, id: &str, args: &FluentArgs) -> String {
+        self.format(id, Some(args))
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        std::iter::once(&self.bundle)
+            .chain(self.fallback.as_ref())
+            .find_map(|bundle| {
+                let pattern = bundle.get_message(id)?.value()?;
+                // Formatting errors are reported inline in the returned text
+                let mut errors = Vec::new();
+                Some(
+                    bundle
+                        .format_pattern(pattern, args, &mut errors)
+                        .into_owned(),
+                )
+            })
+            .unwrap_or_else(|| id.to_string())
+    }
+}
+
+fn create_bundle(language: Language) -> FluentBundle<FluentResource> {
+    // Tags are constants known to be valid
+    let language_id: LanguageIdentifier = language.tag().parse().unwrap();
+    // Invalid entries are skipped by the parser, the rest of the catalogue is still usable
+    let resource = FluentResource::try_new(language.catalogue().to_string()).unwrap_or_else(
+        |(resource, errors)| {
+            eprintln!(
+                "invalid entries in {} catalogue: {errors:?}",
+                language.tag()
+            );
+            resource
+        },
+    );
+
+    let mut bundle = FluentBundle::new(vec![language_id]);
+    // Unicode isolation marks around placeables are rendered as boxes by egui
+    bundle.set_use_isolating(false);
+    // Duplicate messages keep their first definition
+    if let Err(errors) = bundle.add_resource(resource) {
+        eprintln!(
+            "duplicate messages in {} catalogue: {errors:?}",
+            language.tag()
+        );
+    }
+    bundle
+}
@@ -10,8 +10,137 @@ use std::error::Error;
 fn main() -> Result<(), Box<dyn Error>> {
     #[cfg(not(target_arch = "wasm32"))]
     {
-        let mut app = renderer_rust::App::new()?;
+        // Headless mode rendering a model into an image file, e.g. `thumbnail bunny.obj --out
+        // bunny.png --size 256`
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if args.first().is_some_and(|command| command == "thumbnail") {
+            let options = renderer_rust::ThumbnailOptions::from_args(&args[1..])?;
+            renderer_rust::render_thumbnail(&options)?;
+            return Ok(());
+        }
+
+        // Models saved into the watched directory are reloaded, e.g. `--watch exports`
+        let app_builder = match args.iter().position(|arg| arg == "--watch") {
+            Some(index) => {
+                let directory = args.get(index + 1).ok_or("--watch requires a directory")?;
+                renderer_rust::AppBuilder::demo().with_watch_directory(directory)
+            }
+            None => renderer_rust::AppBuilder::demo(),
+        };
+        // GUI scale overriding the remembered one, e.g. `--ui-scale 1.5`
+        let app_builder = match args.iter().position(|arg| arg == "--ui-scale") {
+            Some(index) => {
+                let ui_scale = args
+                    .get(index + 1)
+                    .and_then(|scale| scale.parse::<f32>().ok())
+                    .filter(|scale| *scale > 0.0)
+                    .ok_or("--ui-scale requires a positive number")?;
+                app_builder.with_ui_scale(ui_scale)
+            }
+            None => app_builder,
+        };
+        // Terrain generated from a heightmap image, e.g. `--terrain heightmap.png`
+        let app_builder = match args.iter().position(|arg| arg == "--terrain") {
+            Some(index) => {
+                let path = args
+                    .get(index + 1)
+                    .ok_or("--terrain requires a heightmap path")?;
+                app_builder.with_terrain(path, renderer_rust::TerrainSettings::default())
+            }
+            None => app_builder,
+        };
+        // Camera input saved on exit, e.g. `--record input.ron`
+        let app_builder = match args.iter().position(|arg| arg == "--record") {
+            Some(index) => {
+                let path = args.get(index + 1).ok_or("--record requires a file path")?;
+                app_builder.with_input_recording(path)
+            }
+            None => app_builder,
+        };
+        // Recorded camera input replayed, e.g. `--replay input.ron`. Smoke tests exit once it
+        // ends with `--exit-after-replay`.
+        let app_builder = match args.iter().position(|arg| arg == "--replay") {
+            Some(index) => {
+                let path = args.get(index + 1).ok_or("--replay requires a file path")?;
+                let exit_when_finished = args.iter().any(|arg| arg == "--exit-after-replay");
+                app_builder.with_input_playback(path, exit_when_finished)
+            }
+            None => app_builder,
+        };
+        // Remote control by Open Sound Control messages on a loopback UDP port, e.g. `--osc 9000`,
+        // or on another interface, e.g. `--osc 0.0.0.0:9000`
+        let app_builder = match args.iter().position(|arg| arg == "--osc") {
+            Some(index) => {
+                let address = args
+                    .get(index + 1)
+                    .and_then(|address| parse_server_address(address))
+                    .ok_or("--osc requires a port number or an address with port")?;
+                app_builder.with_osc_listener(address)
+            }
+            None => app_builder,
+        };
+        // Stereo rendering into a VR headset, e.g. `--xr`
+        #[cfg(feature = "xr")]
+        let app_builder = match args.iter().any(|arg| arg == "--xr") {
+            true => app_builder.with_xr(),
+            false => app_builder,
+        };
+
+        // Scene parameters driven by audio input, e.g. `--audio`
+        #[cfg(feature = "audio")]
+        let app_builder = match args.iter().any(|arg| arg == "--audio") {
+            true => app_builder.with_audio_input(),
+            false => app_builder,
+        };
+        // Screenshots saved into a directory, e.g. `--screenshot-directory frames`
+        let app_builder = match args.iter().position(|arg| arg == "--screenshot-directory") {
+            Some(index) => {
+                let directory = args
+                    .get(index + 1)
+                    .ok_or("--screenshot-directory requires a directory")?;
+                app_builder.with_screenshot_directory(directory)
+            }
+            None => app_builder,
+        };
+        // JSON remote control requests over WebSocket on a loopback port, e.g. `--remote 9001`,
+        // or on another interface, e.g. `--remote 0.0.0.0:9001`
+        #[cfg(feature = "remote")]
+        let app_builder = match args.iter().position(|arg| arg == "--remote") {
+            Some(index) => {
+                let address = args
+                    .get(index + 1)
+                    .and_then(|address| parse_server_address(address))
+                    .ok_or("--remote requires a port number or an address with port")?;
+                app_builder.with_remote_server(address)
+            }
+            None => app_builder,
+        };
+        // PNG images of rendered frames over HTTP on a loopback port, e.g. `--http 8080`, or on
+        // another interface, e.g. `--http 0.0.0.0:8080`
+        #[cfg(feature = "http")]
+        let app_builder = match args.iter().position(|arg| arg == "--http") {
+            Some(index) => {
+                let address = args
+                    .get(index + 1)
+                    .and_then(|address| parse_server_address(address))
+                    .ok_or("--http requires a port number or an address with port")?;
+                app_builder.with_snapshot_server(address)
+            }
+            None => app_builder,
+        };
+
+        renderer_rust::install_panic_hook();
+        let mut app = app_builder.build()?;
         app.run();
     }
     Ok(())
 }
+
+/// Port of the loopback interface, or an address with port to listen on other interfaces.
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_server_address(argument: &str) -> Option<std::net::SocketAddr> {
+    match argument.parse::<u16>() {
+        Ok(port) => Some((std::net::Ipv4Addr::LOCALHOST, port).into()),
+        Err(_) => argument.parse().ok(),
+    }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+/// Shading model of a material, selecting the lighting calculation of model shaders.
+///
+/// Discriminant values are passed as shader uniform on web target.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MaterialType {
+    /// Ambient, diffuse and specular lighting.
+    Phong = 0,
+    /// Mirror reflection of the skybox environment tinted by material color.
+    Reflective = 1,
+    /// Glass-like refraction of the skybox environment blended with reflection based on the
+    /// viewing angle (fresnel effect).
+    Refractive = 2,
+}
+
+impl MaterialType {
+    pub const ALL: [MaterialType; 3] = [
+        MaterialType::Phong,
+        MaterialType::Reflective,
+        MaterialType::Refractive,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MaterialType::Phong => "Phong",
+            MaterialType::Reflective => "Reflective",
+            MaterialType::Refractive => "Refractive",
+        }
+    }
+
+    /// Message identifier of the translated label.
+    pub(crate) fn l10n_id(self) -> &'static str {
+        match self {
+            MaterialType::Phong => "material-type-phong",
+            MaterialType::Reflective => "material-type-reflective",
+            MaterialType::Refractive => "material-type-refractive",
+        }
+    }
+}
+
+/// Use of per-vertex colors of meshes that have them, e.g. 3D scans. Meshes without vertex colors
+/// are always drawn with the material color.
+///
+/// Discriminant values are the option values of the HTML select element on web target.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VertexColorMode {
+    /// Material color only.
+    Ignore = 0,
+    /// Material color multiplied by the vertex color.
+    Modulate = 1,
+    /// Vertex color instead of the material color.
+    Replace = 2,
+}
+
+impl VertexColorMode {
+    pub const ALL: [VertexColorMode; 3] = [
+        VertexColorMode::Ignore,
+        VertexColorMode::Modulate,
+        VertexColorMode::Replace,
+    ];
+
+    /// Message identifier of the translated label.
+    pub(crate) fn l10n_id(self) -> &'static str {
+        match self {
+            VertexColorMode::Ignore => "vertex-colors-ignore",
+            VertexColorMode::Modulate => "vertex-colors-modulate",
+            VertexColorMode::Replace => "vertex-colors-replace",
+        }
+    }
+}
+
+/// Surface appearance of a renderable entity, consisting of the shading model and its parameters.
+///
+/// Meshes have no texture coordinates, so the only texture sampled by materials is the skybox
+/// environment of reflective and refractive surfaces.
+///
+/// Materials can be saved and loaded in RON format for reusing them between scenes.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Material {
+    /// Display name in material editor.
+    pub name: String,
+    pub material_type: MaterialType,
+    pub color: [f32; 3],
+    pub diffuse_enabled: bool,
+    pub specular_enabled: bool,
+    /// Index of refraction used by refractive material. 1.0 is air, 1.33 is water, 1.5 is glass.
+    pub refractive_index: f32,
+    pub vertex_colors: VertexColorMode,
+    /// Name of a custom shader registered with `Renderer::register_shader()` replacing the shading
+    /// model. Built-in shading is used while no shader is registered with the name.
+    pub shader: Option<String>,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            name: "Default".to_string(),
+            material_type: MaterialType::Phong,
+            color: [0.0, 0.8, 1.0],
+            diffuse_enabled: true,
+            specular_enabled: true,
+            refractive_index: 1.5,
+            vertex_colors: VertexColorMode::Replace,
+            shader: None,
+        }
+    }
+}
+
+impl Material {
+    pub fn with_shader(mut self, name: &str) -> Self {
+        self.shader = Some(name.to_owned());
+        self
+    }
+
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("cannot serialize material '{}': {e}", self.name))
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        ron::from_str(text).map_err(|e| format!("cannot parse material: {e}"))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_ron()?)
+            .map_err(|e| format!("cannot write material file {path}: {e}"))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read material file {path}: {e}"))?;
+        Self::from_ron(&text).map_err(|e| format!("{path}: {e}"))
+    }
+}
@@ -0,0 +1,120 @@
+use cgmath::{EuclideanSpace, InnerSpace, MetricSpace, Point3};
+
+use crate::{
+    label::Label,
+    line::{self, LineVertex},
+};
+
+/// Most points of a measurement, the next click starts a new one.
+const MAX_POINTS: usize = 3;
+const MEASUREMENT_COLOR: [f32; 3] = [1.0, 0.8, 0.1];
+/// Radius of point markers relative to their distance from the camera, keeping their size on
+/// screen.
+const MARKER_SCALE: f32 = 0.01;
+
+/// Distance between points picked on models, and the angle at the middle one when three points
+/// are picked, e.g. for inspecting scanned objects.
+///
+/// Points are placed in world space and stay in place when entities move.
+pub struct Measurement {
+    /// Clicking a model places points instead of activating the entity.
+    pub enabled: bool,
+    /// Measured units per world unit, e.g. 100 for centimeters when 1 world unit is 1 meter.
+    pub unit_scale: f32,
+    /// Name of measured units appended to distances.
+    pub unit: String,
+    points: Vec<Point3<f32>>,
+}
+
+impl Default for Measurement {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            unit_scale: 1.0,
+            unit: "m".to_string(),
+            points: Vec::new(),
+        }
+    }
+}
+
+impl Measurement {
+    /// Place the next point, starting over after a complete angle measurement.
+    pub fn add_point(&mut self, point: Point3<f32>) {
+        if self.points.len() == MAX_POINTS {
+            self.points.clear();
+        }
+        self.points.push(point);
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    pub fn points(&self) -> &[Point3<f32>] {
+        &self.points
+    }
+
+    /// Lengths of the segments between consecutive points in measured units.
+    pub fn distances(&self) -> Vec<f32> {
+        self.points
+            .windows(2)
+            .map(|segment| segment[0].distance(segment[1]) * self.unit_scale)
+            .collect()
+    }
+
+    /// Angle between the segments at the middle point in degrees, once three points are placed.
+    pub fn angle(&self) -> Option<f32> {
+        let [first, middle, last] = <[Point3<f32>; 3]>::try_from(self.points.as_slice()).ok()?;
+        let (a, b) = (first - middle, last - middle);
+        if a.magnitude2() == 0.0 || b.magnitude2() == 0.0 {
+            return None;
+        }
+        Some(a.angle(b).0.to_degrees())
+    }
+
+    /// Distance in measured units with the unit name.
+    pub fn format_distance(&self, distance: f32) -> String {
+        format!("{distance:.3} {}", self.unit)
+    }
+
+    /// Markers of the points and segments between them, markers sized for the camera position.
+    pub(crate) fn lines(&self, camera_position: Point3<f32>) -> Vec<LineVertex> {
+        let mut vertices: Vec<_> = self
+            .points
+            .windows(2)
+            .flatten()
+            .map(|point| LineVertex {
+                position: (*point).into(),
+                color: MEASUREMENT_COLOR,
+            })
+            .collect();
+        for point in &self.points {
+            let radius = point.distance(camera_position) * MARKER_SCALE;
+            vertices.extend(line::sphere_lines(*point, radius, MEASUREMENT_COLOR));
+        }
+        vertices
+    }
+
+    /// Distances at the middle of segments and the angle at its point. Label font only has ASCII
+    /// characters.
+    pub(crate) fn labels(&self) -> Vec<Label> {
+        let mut labels: Vec<_> = self
+            .points
+            .windows(2)
+            .zip(self.distances())
+            .map(|(segment, distance)| Label {
+                text: self.format_distance(distance),
+                position: segment[0].midpoint(segment[1]),
+                color: MEASUREMENT_COLOR,
+            })
+            .collect();
+        if let Some(angle) = self.angle() {
+            labels.push(Label {
+                text: format!("{angle:.1} deg"),
+                position: self.points[1],
+                color: MEASUREMENT_COLOR,
+            });
+        }
+        labels
+    }
+}
@@ -0,0 +1,232 @@
+use std::{collections::HashMap, sync::Arc};
+
+use glow::{Buffer, HasContext, VertexArray};
+
+use crate::{
+    gl_resources, gl_state,
+    model::{self, Mesh},
+    Model, VertexLayout,
+};
+
+/// Meshes with at most this many vertices are merged. Larger meshes gain nothing from sharing
+/// buffers, because their draw calls are dominated by vertex processing.
+const MAX_BATCHED_VERTEX_COUNT: usize = 4096;
+
+/// Where a merged mesh is stored in the shared buffers of its batch.
+#[derive(Clone, Copy)]
+pub struct BatchedMesh {
+    /// Vertex array of the shared buffers.
+    pub vertex_array: VertexArray,
+    /// Offset of the first index in bytes, passed to `glDrawElements()`.
+    pub index_offset: i32,
+    /// Added to each index, so indices of the mesh keep referring to its own vertices.
+    pub base_vertex: i32,
+}
+
+/// Small static meshes of the scene merged into shared vertex and index buffers, one batch for
+/// each vertex layout. Consecutive draws of merged meshes don't rebind vertex arrays.
+///
+/// Merged meshes are drawn with `glDrawElementsBaseVertex()`, which requires OpenGL (ES) 3.2.
+/// Batching is disabled on older versions and WebGL.
+pub struct MeshBatches {
+    gl: Arc<glow::Context>,
+    supported: bool,
+    batches: Vec<Batch>,
+    // Merged meshes are kept alive, so handles of their vertex arrays can't be reused by others
+    meshes: Vec<Arc<Mesh>>,
+    // Keyed by the own vertex array of merged meshes
+    ranges: HashMap<VertexArray, BatchedMesh>,
+}
+
+struct Batch {
+    vertex_array: VertexArray,
+    vertex_buffer: Buffer,
+    index_buffer: Buffer,
+}
+
+impl MeshBatches {
+    pub fn new(gl: Arc<glow::Context>) -> Self {
+        let version = gl.version();
+        Self {
+            supported: (version.major, version.minor) >= (3, 2),
+            gl,
+            batches: Vec::new(),
+            meshes: Vec::new(),
+            ranges: HashMap::new(),
+        }
+    }
+
+    /// Merge small meshes of the models again if they changed since the last call. Meshes
+    /// modified on GPU (e.g. by vertex displacement) must be excluded, merged copies would not
+    /// follow the changes.
+    pub fn update(&mut self, models: &[Model], excluded: Option<&Arc<Mesh>>) {
+        if !self.supported {
+            return;
+        }
+        let mut meshes: Vec<Arc<Mesh>> = Vec::new();
+        for model in models {
+            let mesh = model.mesh();
+            let eligible = mesh.index_count() > 0
+                && mesh.vertex_count() <= MAX_BATCHED_VERTEX_COUNT
+                && !excluded.is_some_and(|excluded| Arc::ptr_eq(excluded, mesh))
+                && !meshes.iter().any(|merged| Arc::ptr_eq(merged, mesh));
+            if eligible {
+                meshes.push(mesh.clone());
+            }
+        }
+        let unchanged = meshes.len() == self.meshes.len()
+            && meshes
+                .iter()
+                .zip(&self.meshes)
+                .all(|(mesh, merged)| Arc::ptr_eq(mesh, merged));
+        if unchanged {
+            return;
+        }
+
+        self.clear();
+        let mut layouts: Vec<&VertexLayout> = Vec::new();
+        for mesh in &meshes {
+            if !layouts.contains(&mesh.vertex_layout()) {
+                layouts.push(mesh.vertex_layout());
+            }
+        }
+        for layout in layouts {
+            let batch_meshes: Vec<&Mesh> = meshes
+                .iter()
+                .map(|mesh| mesh.as_ref())
+                .filter(|mesh| mesh.vertex_layout() == layout)
+                .collect();
+            match unsafe { self.merge(layout, &batch_meshes) } {
+                Ok(batch) => self.batches.push(batch),
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+        self.meshes = meshes;
+    }
+
+    /// Location of the mesh in shared buffers, `None` if it is drawn from its own buffers.
+    pub fn get(&self, mesh: &Mesh) -> Option<BatchedMesh> {
+        self.ranges.get(&mesh.vertex_array()).copied()
+    }
+
+    /// Copy buffers of meshes with the same vertex layout into a new batch on GPU.
+    unsafe fn merge(&mut self, layout: &VertexLayout, meshes: &[&Mesh]) -> Result<Batch, String> {
+        let gl = &self.gl;
+        let stride = layout.stride();
+        let vertex_bytes: usize = meshes.iter().map(|mesh| mesh.vertex_count() * stride).sum();
+        // Ranges of 16-bit and 32-bit indices are mixed, every range starts 32-bit aligned
+        let index_bytes: usize = meshes
+            .iter()
+            .map(|mesh| index_range_size(mesh).next_multiple_of(size_of::<u32>()))
+            .sum();
+
+        let vertex_array = gl_resources::create_vertex_array(gl)
+            .map_err(|e| format!("cannot create mesh batch: {e}"))?;
+        let vertex_buffer = gl_resources::create_buffer(gl)
+            .map_err(|e| format!("cannot create mesh batch: {e}"))?;
+        let index_buffer = gl_resources::create_buffer(gl)
+            .map_err(|e| format!("cannot create mesh batch: {e}"))?;
+        let batch = Batch {
+            vertex_array,
+            vertex_buffer,
+            index_buffer,
+        };
+
+        // Copy targets don't change element buffer binding of the currently bound vertex array
+        gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(vertex_buffer));
+        gl_resources::buffer_data_size(
+            gl,
+            vertex_buffer,
+            glow::COPY_WRITE_BUFFER,
+            vertex_bytes as i32,
+            glow::STATIC_DRAW,
+        );
+        let mut vertex_offset = 0;
+        for mesh in meshes {
+            let size = mesh.vertex_count() * stride;
+            gl.bind_buffer(glow::COPY_READ_BUFFER, Some(mesh.vertex_buffer()));
+            gl.copy_buffer_sub_data(
+                glow::COPY_READ_BUFFER,
+                glow::COPY_WRITE_BUFFER,
+                0,
+                vertex_offset as i32,
+                size as i32,
+            );
+            self.ranges.insert(
+                mesh.vertex_array(),
+                BatchedMesh {
+                    vertex_array,
+                    index_offset: 0,
+                    base_vertex: (vertex_offset / stride) as i32,
+                },
+            );
+            vertex_offset += size;
+        }
+
+        gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(index_buffer));
+        gl_resources::buffer_data_size(
+            gl,
+            index_buffer,
+            glow::COPY_WRITE_BUFFER,
+            index_bytes as i32,
+            glow::STATIC_DRAW,
+        );
+        let mut index_offset = 0;
+        for mesh in meshes {
+            let size = index_range_size(mesh);
+            gl.bind_buffer(glow::COPY_READ_BUFFER, Some(mesh.index_buffer()));
+            gl.copy_buffer_sub_data(
+                glow::COPY_READ_BUFFER,
+                glow::COPY_WRITE_BUFFER,
+                0,
+                index_offset as i32,
+                size as i32,
+            );
+            self.ranges
+                .get_mut(&mesh.vertex_array())
+                .unwrap()
+                .index_offset = index_offset as i32;
+            index_offset += size.next_multiple_of(size_of::<u32>());
+        }
+        gl.bind_buffer(glow::COPY_READ_BUFFER, None);
+        gl.bind_buffer(glow::COPY_WRITE_BUFFER, None);
+
+        gl_state::bind_vertex_array(gl, Some(vertex_array));
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(vertex_buffer));
+        gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(index_buffer));
+        model::set_vertex_attributes(gl, layout);
+        gl_state::bind_vertex_array(gl, None);
+        gl.bind_buffer(glow::ARRAY_BUFFER, None);
+
+        Ok(batch)
+    }
+
+    fn clear(&mut self) {
+        for batch in self.batches.drain(..) {
+            batch.delete(&self.gl);
+        }
+        self.meshes.clear();
+        self.ranges.clear();
+    }
+}
+
+impl Drop for MeshBatches {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl Batch {
+    fn delete(self, gl: &glow::Context) {
+        unsafe {
+            gl_resources::delete_vertex_array(gl, self.vertex_array);
+            gl_resources::delete_buffer(gl, self.vertex_buffer);
+            gl_resources::delete_buffer(gl, self.index_buffer);
+        }
+    }
+}
+
+/// Size of the index buffer of the mesh in bytes.
+fn index_range_size(mesh: &Mesh) -> usize {
+    mesh.index_count() * mesh.index_size()
+}
@@ -0,0 +1,160 @@
+use std::{fmt::Write, path::Path};
+
+use cgmath::{EuclideanSpace, Matrix4, Point3, Transform};
+
+use crate::{
+    model::{Aabb, Vertex},
+    renderer::calculate_normal_matrix,
+};
+
+/// Written into file headers to identify the exporting application.
+const GENERATOR: &str = "3D Renderer in Rust";
+
+// Binary glTF container constants
+const GLB_MAGIC: u32 = 0x4654_6C67; // "glTF"
+const GLB_VERSION: u32 = 2;
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A; // "JSON"
+const GLB_CHUNK_BIN: u32 = 0x004E_4942; // "BIN\0"
+const GL_ARRAY_BUFFER: u32 = 34962;
+const GL_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const GL_FLOAT: u32 = 5126;
+const GL_UNSIGNED_INT: u32 = 5125;
+
+/// Write triangle mesh into an OBJ or binary glTF (`.glb`) file, chosen by the file extension.
+pub fn export_mesh(path: &str, vertices: &[Vertex], indices: &[u32]) -> Result<(), String> {
+    let extension = Path::new(path)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    let bytes = match extension.as_deref() {
+        Some("obj") => obj_bytes(vertices, indices),
+        Some("glb") => glb_bytes(vertices, indices),
+        _ => {
+            return Err(format!(
+                "cannot export mesh to {path}: expected .obj or .glb file"
+            ))
+        }
+    };
+    std::fs::write(path, bytes).map_err(|e| format!("cannot write mesh file {path}: {e}"))
+}
+
+/// Bake model transformation into vertex positions and normals.
+pub fn transform_vertices(vertices: &mut [Vertex], model_matrix: &Matrix4<f32>) {
+    let normal_matrix = calculate_normal_matrix(model_matrix);
+    for vertex in vertices {
+        vertex.position = model_matrix
+            .transform_point(Point3::from_vec(vertex.position))
+            .to_vec();
+        vertex.normal = normal_matrix * vertex.normal;
+    }
+}
+
+/// Reverse vertex order of triangles, turning clockwise front faces into counter-clockwise ones
+/// expected by both formats.
+pub fn flip_winding(indices: &mut [u32]) {
+    for triangle in indices.chunks_exact_mut(3) {
+        triangle.swap(1, 2);
+    }
+}
+
+fn obj_bytes(vertices: &[Vertex], indices: &[u32]) -> Vec<u8> {
+    let mut text = format!("# Exported by {GENERATOR}\n");
+    for vertex in vertices {
+        let p = vertex.position;
+        writeln!(text, "v {} {} {}", p.x, p.y, p.z).unwrap();
+    }
+    for vertex in vertices {
+        let n = vertex.normal;
+        writeln!(text, "vn {} {} {}", n.x, n.y, n.z).unwrap();
+    }
+    // OBJ indices start from 1, position and normal indices are the same
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0] + 1, triangle[1] + 1, triangle[2] + 1];
+        writeln!(text, "f {a}//{a} {b}//{b} {c}//{c}").unwrap();
+    }
+    text.into_bytes()
+}
+
+/// Single mesh with positions, normals and indices stored in the binary chunk, one after the
+/// other.
+fn glb_bytes(vertices: &[Vertex], indices: &[u32]) -> Vec<u8> {
+    let mut binary = Vec::with_capacity(vertices.len() * 24 + indices.len() * 4);
+    for vertex in vertices {
+        let p = vertex.position;
+        for coordinate in [p.x, p.y, p.z] {
+            binary.extend_from_slice(&coordinate.to_le_bytes());
+        }
+    }
+    for vertex in vertices {
+        let n = vertex.normal;
+        for coordinate in [n.x, n.y, n.z] {
+            binary.extend_from_slice(&coordinate.to_le_bytes());
+        }
+    }
+    for index in indices {
+        binary.extend_from_slice(&index.to_le_bytes());
+    }
+
+    let attribute_length = vertices.len() * 12;
+    let index_length = indices.len() * 4;
+    // Bounds of positions are mandatory in glTF
+    let bounds = Aabb::from_points(
+        vertices
+            .iter()
+            .map(|vertex| Point3::from_vec(vertex.position)),
+    );
+    let json = format!(
+        concat!(
+            r#"{{"asset":{{"version":"2.0","generator":"{generator}"}},"#,
+            r#""scene":0,"scenes":[{{"nodes":[0]}}],"nodes":[{{"mesh":0}}],"#,
+            r#""meshes":[{{"primitives":[{{"attributes":{{"POSITION":0,"NORMAL":1}},"#,
+            r#""indices":2,"mode":4}}]}}],"#,
+            r#""buffers":[{{"byteLength":{buffer_length}}}],"#,
+            r#""bufferViews":["#,
+            r#"{{"buffer":0,"byteOffset":0,"byteLength":{attribute_length},"#,
+            r#""target":{vertex_target}}},"#,
+            r#"{{"buffer":0,"byteOffset":{attribute_length},"byteLength":{attribute_length},"#,
+            r#""target":{vertex_target}}},"#,
+            r#"{{"buffer":0,"byteOffset":{index_offset},"byteLength":{index_length},"#,
+            r#""target":{index_target}}}],"#,
+            r#""accessors":["#,
+            r#"{{"bufferView":0,"componentType":{float},"count":{vertex_count},"type":"VEC3","#,
+            r#""min":[{min_x},{min_y},{min_z}],"max":[{max_x},{max_y},{max_z}]}},"#,
+            r#"{{"bufferView":1,"componentType":{float},"count":{vertex_count},"type":"VEC3"}},"#,
+            r#"{{"bufferView":2,"componentType":{uint},"count":{index_count},"type":"SCALAR"}}]}}"#,
+        ),
+        generator = GENERATOR,
+        buffer_length = binary.len(),
+        attribute_length = attribute_length,
+        index_offset = attribute_length * 2,
+        index_length = index_length,
+        vertex_target = GL_ARRAY_BUFFER,
+        index_target = GL_ELEMENT_ARRAY_BUFFER,
+        float = GL_FLOAT,
+        uint = GL_UNSIGNED_INT,
+        vertex_count = vertices.len(),
+        index_count = indices.len(),
+        min_x = bounds.min.x,
+        min_y = bounds.min.y,
+        min_z = bounds.min.z,
+        max_x = bounds.max.x,
+        max_y = bounds.max.y,
+        max_z = bounds.max.z,
+    );
+
+    // Chunks are aligned to 4 bytes, JSON is padded with spaces
+    let mut json = json.into_bytes();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    binary.resize(binary.len().next_multiple_of(4), 0);
+
+    let total_length = 12 + 8 + json.len() + 8 + binary.len();
+    let mut bytes = Vec::with_capacity(total_length);
+    for word in [GLB_MAGIC, GLB_VERSION, total_length as u32] {
+        bytes.extend_from_slice(&word.to_le_bytes());
+    }
+    for (chunk_type, chunk) in [(GLB_CHUNK_JSON, json), (GLB_CHUNK_BIN, binary)] {
+        bytes.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&chunk_type.to_le_bytes());
+        bytes.extend_from_slice(&chunk);
+    }
+    bytes
+}
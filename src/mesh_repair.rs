@@ -0,0 +1,429 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use cgmath::{InnerSpace, MetricSpace, Vector3, Zero};
+
+use crate::{mesh_validation, MeshData};
+
+/// Operation of the Mesh menu fixing common problems of meshes, e.g. of 3D scans.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MeshRepair {
+    /// Merge vertices closer to each other than the distance, see `MeshData::weld_vertices()`.
+    WeldVertices(f32),
+    RemoveDegenerateTriangles,
+    /// Close holes bordered by at most the number of edges, see `MeshData::fill_holes()`.
+    FillHoles(usize),
+    UnifyWinding,
+}
+
+impl MeshRepair {
+    /// Carry out the operation, returning the number of changed elements for reporting.
+    pub fn apply(self, mesh_data: &mut MeshData) -> usize {
+        match self {
+            Self::WeldVertices(distance) => mesh_data.weld_vertices(distance),
+            Self::RemoveDegenerateTriangles => mesh_data.remove_degenerate_triangles(),
+            Self::FillHoles(max_edges) => mesh_data.fill_holes(max_edges),
+            Self::UnifyWinding => mesh_data.unify_winding(),
+        }
+    }
+
+    /// Describe the result of `apply()`.
+    pub fn report(self, count: usize) -> String {
+        match self {
+            Self::WeldVertices(_) => format!("welded {count} vertices"),
+            Self::RemoveDegenerateTriangles => format!("removed {count} degenerate triangles"),
+            Self::FillHoles(_) => format!("filled {count} holes"),
+            Self::UnifyWinding => format!("flipped {count} triangles"),
+        }
+    }
+}
+
+impl MeshData {
+    /// Merge each vertex into the first vertex found within the distance, averaging their normals.
+    /// Other attributes of the first vertex are kept. Distance 0 merges vertices at the same
+    /// position only. Returns the number of removed vertices.
+    pub fn weld_vertices(&mut self, distance: f32) -> usize {
+        let cell_size = distance.max(f32::EPSILON);
+        let cell_of = |position: Vector3<f32>| (position / cell_size).map(|c| c.floor() as i32);
+        // Kept vertices of grid cells, merged vertices are searched in neighbouring cells
+        let mut cells: HashMap<[i32; 3], Vec<u32>> = HashMap::new();
+        let mut kept: Vec<usize> = Vec::new();
+        let mut normal_sums: Vec<Vector3<f32>> = Vec::new();
+        let remap: Vec<u32> = self
+            .vertices
+            .iter()
+            .enumerate()
+            .map(|(vertex_index, vertex)| {
+                let cell = cell_of(vertex.position);
+                let mut neighbours = (-1..=1)
+                    .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| [x, y, z])));
+                let merged = neighbours.find_map(|[x, y, z]| {
+                    cells
+                        .get(&[cell.x + x, cell.y + y, cell.z + z])?
+                        .iter()
+                        .copied()
+                        .find(|&index| {
+                            let other = self.vertices[kept[index as usize]].position;
+                            other.distance(vertex.position) <= distance
+                        })
+                });
+                let index = merged.unwrap_or_else(|| {
+                    let index = kept.len() as u32;
+                    kept.push(vertex_index);
+                    normal_sums.push(Vector3::zero());
+                    cells
+                        .entry([cell.x, cell.y, cell.z])
+                        .or_default()
+                        .push(index);
+                    index
+                });
+                normal_sums[index as usize] += vertex.normal;
+                index
+            })
+            .collect();
+        let removed_count = self.vertices.len() - kept.len();
+
+        self.keep_vertices(&kept, &remap);
+        for (vertex, normal_sum) in self.vertices.iter_mut().zip(normal_sums) {
+            // Opposite normals of merged vertices cancel out
+            if normal_sum.magnitude2() > f32::EPSILON {
+                vertex.normal = normal_sum.normalize();
+            }
+        }
+        removed_count
+    }
+
+    /// Remove triangles without area. Returns the number of removed triangles.
+    pub fn remove_degenerate_triangles(&mut self) -> usize {
+        let vertices = &self.vertices;
+        let is_degenerate = |triangle: &[u32]| {
+            mesh_validation::is_degenerate(&[0, 1, 2].map(|i| vertices[triangle[i] as usize]))
+        };
+        let keep: Vec<bool> = self
+            .indices
+            .chunks_exact(3)
+            .map(|triangle| !is_degenerate(triangle))
+            .collect();
+        self.retain_triangles(&keep)
+    }
+
+    /// Close holes bordered by at most the number of edges with fans of triangles from a corner of
+    /// the hole. Holes are found by vertex indices, so vertices split along seams have to be welded
+    /// first. Borders passing a vertex more than once are skipped. Returns the number of filled
+    /// holes.
+    pub fn fill_holes(&mut self, max_edges: usize) -> usize {
+        let mut edge_counts: HashMap<(u32, u32), usize> = HashMap::new();
+        for triangle in self.indices.chunks_exact(3) {
+            for i in 0..3 {
+                let (start, end) = (triangle[i], triangle[(i + 1) % 3]);
+                *edge_counts
+                    .entry((start.min(end), start.max(end)))
+                    .or_default() += 1;
+            }
+        }
+        // Boundary edges in the direction triangles filling the hole traverse them, opposite of
+        // the triangle they belong to
+        let mut next: HashMap<u32, Option<u32>> = HashMap::new();
+        for triangle in self.indices.chunks_exact(3) {
+            for i in 0..3 {
+                let (start, end) = (triangle[i], triangle[(i + 1) % 3]);
+                if edge_counts[&(start.min(end), start.max(end))] == 1 {
+                    next.entry(end)
+                        .and_modify(|target| *target = None)
+                        .or_insert(Some(start));
+                }
+            }
+        }
+
+        // Holes are walked from their smallest vertex index, so the same mesh is filled with the
+        // same triangles every time
+        let mut starts: Vec<u32> = next.keys().copied().collect();
+        starts.sort_unstable();
+        let mut filled_count = 0;
+        let mut fill_indices = Vec::new();
+        let mut visited = HashSet::new();
+        for start in starts {
+            if visited.contains(&start) {
+                continue;
+            }
+            let mut hole = vec![start];
+            let mut current = start;
+            let closed = loop {
+                visited.insert(current);
+                let Some(Some(following)) = next.get(&current) else {
+                    break false;
+                };
+                if *following == start {
+                    break true;
+                }
+                if visited.contains(following) || hole.len() >= max_edges {
+                    break false;
+                }
+                hole.push(*following);
+                current = *following;
+            };
+            if !closed || hole.len() < 3 {
+                continue;
+            }
+            for i in 1..hole.len() - 1 {
+                fill_indices.extend([hole[0], hole[i], hole[i + 1]]);
+            }
+            filled_count += 1;
+        }
+
+        // Filled triangles belong to the last part, so they are drawn with the parts
+        if let Some(part) = self.parts.last_mut() {
+            part.index_count += fill_indices.len();
+        }
+        self.indices.extend(fill_indices);
+        filled_count
+    }
+
+    /// Flip triangles so neighbours sharing an edge are wound in the same direction, then flip
+    /// every connected surface whose triangles mostly face away from their vertex normals.
+    /// Returns the number of flipped triangles.
+    pub fn unify_winding(&mut self) -> usize {
+        let triangle_count = self.indices.len() / 3;
+        let mut edge_triangles: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+        for (triangle_index, triangle) in self.indices.chunks_exact(3).enumerate() {
+            for i in 0..3 {
+                let (start, end) = (triangle[i], triangle[(i + 1) % 3]);
+                edge_triangles
+                    .entry((start.min(end), start.max(end)))
+                    .or_default()
+                    .push(triangle_index);
+            }
+        }
+
+        let mut flipped = vec![false; triangle_count];
+        let mut visited = vec![false; triangle_count];
+        for seed in 0..triangle_count {
+            if visited[seed] {
+                continue;
+            }
+            visited[seed] = true;
+            let mut surface = vec![seed];
+            let mut queue = VecDeque::from([seed]);
+            while let Some(triangle_index) = queue.pop_front() {
+                let triangle = self.triangle(triangle_index);
+                for i in 0..3 {
+                    let (start, end) = (triangle[i], triangle[(i + 1) % 3]);
+                    // Orientation is ambiguous across non-manifold edges
+                    let [first, second] = edge_triangles[&(start.min(end), start.max(end))][..]
+                    else {
+                        continue;
+                    };
+                    let neighbour = if first == triangle_index {
+                        second
+                    } else {
+                        first
+                    };
+                    if visited[neighbour] {
+                        continue;
+                    }
+                    visited[neighbour] = true;
+                    // Consistently wound neighbours traverse the shared edge in opposite direction
+                    let neighbour_triangle = self.triangle(neighbour);
+                    if (0..3).any(|j| {
+                        neighbour_triangle[j] == start && neighbour_triangle[(j + 1) % 3] == end
+                    }) {
+                        self.flip_triangle(neighbour);
+                        flipped[neighbour] = !flipped[neighbour];
+                    }
+                    surface.push(neighbour);
+                    queue.push_back(neighbour);
+                }
+            }
+
+            let facing_away = surface
+                .iter()
+                .filter(|&&triangle_index| {
+                    let triangle = self.triangle(triangle_index);
+                    mesh_validation::is_flipped(
+                        &triangle.map(|index| self.vertices[index as usize]),
+                    )
+                })
+                .count();
+            if facing_away * 2 > surface.len() {
+                for triangle_index in surface {
+                    self.flip_triangle(triangle_index);
+                    flipped[triangle_index] = !flipped[triangle_index];
+                }
+            }
+        }
+        flipped.into_iter().filter(|&flipped| flipped).count()
+    }
+
+    fn triangle(&self, triangle_index: usize) -> [u32; 3] {
+        let first = triangle_index * 3;
+        [
+            self.indices[first],
+            self.indices[first + 1],
+            self.indices[first + 2],
+        ]
+    }
+
+    fn flip_triangle(&mut self, triangle_index: usize) {
+        self.indices
+            .swap(triangle_index * 3 + 1, triangle_index * 3 + 2);
+    }
+
+    /// Keep triangles marked in index buffer order, shrinking the parts they belong to. Returns the
+    /// number of removed triangles.
+    fn retain_triangles(&mut self, keep: &[bool]) -> usize {
+        // Parts are consecutive, their new ranges follow from the triangles kept before them
+        let kept_before =
+            |first_index: usize| keep[..first_index / 3].iter().filter(|&&kept| kept).count() * 3;
+        for part in &mut self.parts {
+            let first_index = kept_before(part.first_index);
+            part.index_count = kept_before(part.first_index + part.index_count) - first_index;
+            part.first_index = first_index;
+        }
+        let indices = std::mem::take(&mut self.indices);
+        self.indices = indices
+            .chunks_exact(3)
+            .zip(keep)
+            .filter(|(_, &kept)| kept)
+            .flat_map(|(triangle, _)| triangle.iter().copied())
+            .collect();
+        keep.iter().filter(|&&kept| !kept).count()
+    }
+
+    /// Replace vertices with the ones at the indices, with triangles referring to the vertex of
+    /// `remap`. Optional attributes of other lengths are left as they are.
+    fn keep_vertices(&mut self, kept: &[usize], remap: &[u32]) {
+        let vertex_count = self.vertices.len();
+        fn select<T: Copy>(values: &mut Vec<T>, kept: &[usize], vertex_count: usize) {
+            if values.len() == vertex_count {
+                *values = kept.iter().map(|&index| values[index]).collect();
+            }
+        }
+        select(&mut self.uvs, kept, vertex_count);
+        select(&mut self.tangents, kept, vertex_count);
+        select(&mut self.colors, kept, vertex_count);
+        select(&mut self.lightmap_uvs, kept, vertex_count);
+        select(&mut self.vertices, kept, vertex_count);
+        for index in &mut self.indices {
+            *index = remap[*index as usize];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mesh_validation::MeshValidation, primitives};
+
+    /// Cube with a single vertex at each corner.
+    fn welded_cube() -> MeshData {
+        let mut mesh_data = primitives::cuboid(1.0, 1.0, 1.0);
+        mesh_data.weld_vertices(0.0);
+        mesh_data
+    }
+
+    #[test]
+    fn weld_vertices_merges_face_corners_of_cube() {
+        let mut mesh_data = primitives::cuboid(1.0, 1.0, 1.0);
+        assert_eq!(mesh_data.weld_vertices(0.0), 16);
+        assert_eq!(mesh_data.vertices.len(), 8);
+        assert_eq!(mesh_data.uvs.len(), 8);
+        assert_eq!(mesh_data.indices.len(), 36);
+        assert!(mesh_data.indices.iter().all(|&index| index < 8));
+        // Corner normals average the normals of the three faces meeting there
+        for vertex in &mesh_data.vertices {
+            let expected = vertex.position.normalize();
+            assert!(vertex.normal.dot(expected) > 0.999);
+        }
+    }
+
+    #[test]
+    fn weld_vertices_keeps_vertices_farther_than_distance() {
+        let mut mesh_data = primitives::cuboid(1.0, 1.0, 1.0);
+        mesh_data.weld_vertices(0.0);
+        assert_eq!(mesh_data.weld_vertices(0.5), 0);
+        assert_eq!(mesh_data.vertices.len(), 8);
+    }
+
+    #[test]
+    fn remove_degenerate_triangles_shrinks_parts() {
+        let mut mesh_data = welded_cube();
+        mesh_data.indices.extend([0, 0, 1]);
+        mesh_data.parts = vec![
+            crate::model::MeshPart {
+                name: "cube".to_owned(),
+                first_index: 0,
+                index_count: 36,
+                center: cgmath::Point3::new(0.0, 0.0, 0.0),
+            },
+            crate::model::MeshPart {
+                name: "degenerate".to_owned(),
+                first_index: 36,
+                index_count: 3,
+                center: cgmath::Point3::new(0.0, 0.0, 0.0),
+            },
+        ];
+        assert_eq!(mesh_data.remove_degenerate_triangles(), 1);
+        assert_eq!(mesh_data.indices.len(), 36);
+        assert_eq!(mesh_data.parts[0].index_count, 36);
+        assert_eq!(mesh_data.parts[1].first_index, 36);
+        assert_eq!(mesh_data.parts[1].index_count, 0);
+    }
+
+    #[test]
+    fn fill_holes_closes_missing_triangles() {
+        let mut mesh_data = welded_cube();
+        // Triangles of opposite faces, leaving two separate holes
+        mesh_data.indices.drain(6..9);
+        mesh_data.indices.drain(0..3);
+        assert_eq!(mesh_data.fill_holes(3), 2);
+        assert_eq!(mesh_data.indices.len(), 36);
+        let validation = MeshValidation::analyze(&mesh_data.vertices, &mesh_data.indices);
+        assert_eq!(validation.boundary_edges, 0);
+        assert_eq!(validation.flipped_triangles, 0);
+    }
+
+    #[test]
+    fn fill_holes_skips_holes_with_more_edges() {
+        let mut mesh_data = welded_cube();
+        // Both triangles of a face, leaving a hole of 4 edges
+        mesh_data.indices.drain(0..6);
+        assert_eq!(mesh_data.fill_holes(3), 0);
+        assert_eq!(mesh_data.fill_holes(4), 1);
+        assert_eq!(mesh_data.indices.len(), 36);
+    }
+
+    #[test]
+    fn fill_holes_is_deterministic() {
+        let mut mesh_data = welded_cube();
+        // Opposite faces, each hole can be filled from any of its corners
+        mesh_data.indices.drain(0..12);
+        let filled = (0..8)
+            .map(|_| {
+                let mut mesh_data = mesh_data.clone();
+                mesh_data.fill_holes(4);
+                mesh_data.indices
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(filled[0].len(), 36);
+        assert!(filled.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+
+    #[test]
+    fn unify_winding_restores_flipped_triangle() {
+        let original = welded_cube();
+        let mut mesh_data = original.clone();
+        mesh_data.flip_triangle(4);
+        assert_eq!(mesh_data.unify_winding(), 1);
+        assert_eq!(mesh_data.indices, original.indices);
+    }
+
+    #[test]
+    fn unify_winding_flips_inside_out_surface() {
+        let original = welded_cube();
+        let mut mesh_data = original.clone();
+        for triangle_index in 0..12 {
+            mesh_data.flip_triangle(triangle_index);
+        }
+        assert_eq!(mesh_data.unify_winding(), 12);
+        assert_eq!(mesh_data.indices, original.indices);
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet};
+
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, MetricSpace, Point3, Transform, Vector3};
+
+use crate::{line::LineVertex, Vertex};
+
+const PROBLEM_EDGE_COLOR: [f32; 3] = [1.0, 0.3, 0.1];
+const PROBLEM_FACE_COLOR: [f32; 3] = [1.0, 0.0, 0.6];
+/// Triangles with less area relative to their longest edge squared are degenerate.
+const DEGENERATE_AREA_RATIO: f32 = 1e-6;
+
+/// Problems of a triangle mesh causing shading artifacts or breaking further processing, e.g.
+/// lightmap baking or 3D printing.
+///
+/// Edges are connected by vertex positions, because vertices are split along hard edges and seams
+/// of other attributes.
+#[derive(Default)]
+pub struct MeshValidation {
+    /// Edges shared by more than two triangles.
+    pub non_manifold_edges: usize,
+    /// Edges of a single triangle, bordering holes of closed surfaces.
+    pub boundary_edges: usize,
+    /// Triangles without area, e.g. with repeated or collinear vertices.
+    pub degenerate_triangles: usize,
+    /// Triangles wound against their vertex normals, lit as if seen from behind.
+    pub flipped_triangles: usize,
+    /// Vertices with the same position and normal as another one, wasting memory.
+    pub duplicate_vertices: usize,
+    // Non-manifold and boundary edges in model space
+    problem_edges: Vec<[Point3<f32>; 2]>,
+    // Degenerate and flipped triangles in model space
+    problem_faces: Vec<[Point3<f32>; 3]>,
+}
+
+impl MeshValidation {
+    pub fn analyze(vertices: &[Vertex], indices: &[u32]) -> Self {
+        let mut validation = Self::default();
+        let bits = |v: Vector3<f32>| [v.x.to_bits(), v.y.to_bits(), v.z.to_bits()];
+
+        let mut position_ids = HashMap::new();
+        let mut unique_vertices = HashSet::new();
+        let vertex_position_ids: Vec<usize> = vertices
+            .iter()
+            .map(|vertex| {
+                if !unique_vertices.insert((bits(vertex.position), bits(vertex.normal))) {
+                    validation.duplicate_vertices += 1;
+                }
+                let next_id = position_ids.len();
+                *position_ids.entry(bits(vertex.position)).or_insert(next_id)
+            })
+            .collect();
+
+        // Number of triangles of each edge between position IDs, the smaller one first
+        let mut edges = HashMap::new();
+        for triangle in indices.chunks_exact(3) {
+            let triangle = [triangle[0], triangle[1], triangle[2]].map(|index| index as usize);
+            let triangle_vertices = triangle.map(|index| vertices[index]);
+            let corners = triangle_vertices.map(|vertex| Point3::from_vec(vertex.position));
+            if is_degenerate(&triangle_vertices) {
+                validation.degenerate_triangles += 1;
+                validation.problem_faces.push(corners);
+            } else if is_flipped(&triangle_vertices) {
+                validation.flipped_triangles += 1;
+                validation.problem_faces.push(corners);
+            }
+
+            for i in 0..3 {
+                let (start, end) = (i, (i + 1) % 3);
+                let (start_id, end_id) = (
+                    vertex_position_ids[triangle[start]],
+                    vertex_position_ids[triangle[end]],
+                );
+                // Collapsed edge of a degenerate triangle
+                if start_id == end_id {
+                    continue;
+                }
+                edges
+                    .entry((start_id.min(end_id), start_id.max(end_id)))
+                    .or_insert((0, [corners[start], corners[end]]))
+                    .0 += 1;
+            }
+        }
+        for (triangle_count, edge) in edges.into_values() {
+            match triangle_count {
+                1 => validation.boundary_edges += 1,
+                2 => continue,
+                _ => validation.non_manifold_edges += 1,
+            }
+            validation.problem_edges.push(edge);
+        }
+        validation
+    }
+
+    /// Whether any problem was found.
+    pub fn has_problems(&self) -> bool {
+        self.non_manifold_edges
+            + self.boundary_edges
+            + self.degenerate_triangles
+            + self.flipped_triangles
+            + self.duplicate_vertices
+            > 0
+    }
+
+    /// Problem edges and outlines of problem triangles in world space.
+    pub(crate) fn lines(&self, model_matrix: &Matrix4<f32>) -> Vec<LineVertex> {
+        let vertex = |position: Point3<f32>, color| LineVertex {
+            position: model_matrix.transform_point(position).into(),
+            color,
+        };
+        let edges = self.problem_edges.iter().copied();
+        let face_edges = self
+            .problem_faces
+            .iter()
+            .flat_map(|&[a, b, c]| [[a, b], [b, c], [c, a]]);
+        edges
+            .map(|edge| (edge, PROBLEM_EDGE_COLOR))
+            .chain(face_edges.map(|edge| (edge, PROBLEM_FACE_COLOR)))
+            .flat_map(|(edge, color)| edge.map(|position| vertex(position, color)))
+            .collect()
+    }
+}
+
+/// Normal of the triangle by the winding order of its vertices, not normalized. Counter-clockwise
+/// triangles face the viewer.
+pub(crate) fn face_normal(vertices: &[Vertex; 3]) -> Vector3<f32> {
+    let [a, b, c] = vertices.map(|vertex| vertex.position);
+    (b - a).cross(c - a)
+}
+
+/// Whether the face points away from its vertex normals, e.g. because of a flipped winding order
+/// or inverted normals.
+pub(crate) fn is_flipped(vertices: &[Vertex; 3]) -> bool {
+    let normal_sum: Vector3<f32> = vertices.iter().map(|vertex| vertex.normal).sum();
+    face_normal(vertices).dot(normal_sum) < 0.0
+}
+
+pub(crate) fn is_degenerate(vertices: &[Vertex; 3]) -> bool {
+    let [a, b, c] = vertices.map(|vertex| Point3::from_vec(vertex.position));
+    let longest_edge_squared = a.distance2(b).max(b.distance2(c)).max(c.distance2(a));
+    // Length of the cross product is twice the area of the triangle
+    face_normal(vertices).magnitude() <= longest_edge_squared * DEGENERATE_AREA_RATIO * 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::vec3;
+
+    use super::*;
+    use crate::primitives;
+
+    #[test]
+    fn closed_mesh_has_no_problems() {
+        let mesh_data = primitives::cuboid(1.0, 2.0, 3.0);
+        let validation = MeshValidation::analyze(&mesh_data.vertices, &mesh_data.indices);
+        assert!(!validation.has_problems());
+        assert!(validation.lines(&Matrix4::from_scale(1.0)).is_empty());
+    }
+
+    #[test]
+    fn missing_triangle_leaves_boundary_edges() {
+        let mut mesh_data = primitives::cuboid(1.0, 1.0, 1.0);
+        mesh_data.indices.truncate(33);
+        let validation = MeshValidation::analyze(&mesh_data.vertices, &mesh_data.indices);
+        assert_eq!(validation.boundary_edges, 3);
+        assert_eq!(validation.non_manifold_edges, 0);
+        // Each problem edge is drawn as a line
+        assert_eq!(validation.lines(&Matrix4::from_scale(1.0)).len(), 6);
+    }
+
+    #[test]
+    fn edge_shared_by_three_triangles_is_non_manifold() {
+        let vertex = |x, y, z| Vertex {
+            position: vec3(x, y, z),
+            normal: vec3(0.0, 0.0, 1.0),
+        };
+        let vertices = [
+            vertex(0.0, 0.0, 0.0),
+            vertex(1.0, 0.0, 0.0),
+            vertex(0.5, 1.0, 0.0),
+            vertex(0.5, -1.0, 0.0),
+            vertex(0.5, 0.0, 1.0),
+        ];
+        let validation = MeshValidation::analyze(&vertices, &[0, 1, 2, 1, 0, 3, 0, 1, 4]);
+        assert_eq!(validation.non_manifold_edges, 1);
+        assert_eq!(validation.boundary_edges, 6);
+    }
+
+    #[test]
+    fn degenerate_flipped_and_duplicate_are_counted() {
+        let mut mesh_data = primitives::cuboid(1.0, 1.0, 1.0);
+        // Repeated index
+        mesh_data.indices.extend([0, 0, 1]);
+        // Winding against the normals of the face
+        mesh_data.indices.extend([0, 2, 1]);
+        // Same position and normal as the first vertex
+        mesh_data.vertices.push(mesh_data.vertices[0]);
+        let validation = MeshValidation::analyze(&mesh_data.vertices, &mesh_data.indices);
+        assert_eq!(validation.degenerate_triangles, 1);
+        assert_eq!(validation.flipped_triangles, 1);
+        assert_eq!(validation.duplicate_vertices, 1);
+        assert!(validation.has_problems());
+    }
+
+    #[test]
+    fn collinear_triangle_is_degenerate() {
+        let vertex = |x| Vertex {
+            position: vec3(x, 0.0, 0.0),
+            normal: vec3(0.0, 0.0, 1.0),
+        };
+        assert!(is_degenerate(&[vertex(0.0), vertex(1.0), vertex(2.0)]));
+    }
+}
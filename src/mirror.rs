@@ -0,0 +1,50 @@
+use cgmath::{Matrix4, Point3, Vector3, Vector4};
+
+use crate::line::LineVertex;
+
+/// Mirror stands behind the models at the world origin, facing the default camera.
+const MIRROR_Z: f32 = -2.5;
+const MIRROR_CENTER: [f32; 2] = [0.0, 0.25];
+const MIRROR_HALF_WIDTH: f32 = 2.0;
+const MIRROR_HALF_HEIGHT: f32 = 1.5;
+/// Linear RGB color of the glass tint blended over the reflection.
+const TINT_COLOR: [f32; 3] = [0.6, 0.7, 0.8];
+/// Opacity of the glass tint.
+pub const TINT_ALPHA: f32 = 0.15;
+
+/// Fragments behind the mirror are discarded from the reflected scene, they would appear in front
+/// of the mirror surface.
+pub const CLIP_PLANE: Vector4<f32> = Vector4::new(0.0, 0.0, 1.0, -MIRROR_Z);
+
+/// Reflection is only visible from the front side of the mirror.
+pub fn is_facing(position: Point3<f32>) -> bool {
+    MIRROR_Z < position.z
+}
+
+/// Transformation mirroring the world by the plane of the mirror.
+pub fn reflection_matrix() -> Matrix4<f32> {
+    Matrix4::from_translation(Vector3::new(0.0, 0.0, MIRROR_Z))
+        * Matrix4::from_nonuniform_scale(1.0, 1.0, -1.0)
+        * Matrix4::from_translation(Vector3::new(0.0, 0.0, -MIRROR_Z))
+}
+
+/// Two triangles of the rectangular mirror surface.
+pub fn surface_vertices() -> Vec<LineVertex> {
+    let [center_x, center_y] = MIRROR_CENTER;
+    let corner = |x: f32, y: f32| LineVertex {
+        position: [
+            center_x + x * MIRROR_HALF_WIDTH,
+            center_y + y * MIRROR_HALF_HEIGHT,
+            MIRROR_Z,
+        ],
+        color: TINT_COLOR,
+    };
+    vec![
+        corner(-1.0, -1.0),
+        corner(1.0, -1.0),
+        corner(1.0, 1.0),
+        corner(-1.0, -1.0),
+        corner(1.0, 1.0),
+        corner(-1.0, 1.0),
+    ]
+}
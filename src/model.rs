@@ -1,44 +1,436 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use cgmath::{vec3, Vector3};
+use cgmath::{
+    vec2, vec3, vec4, EuclideanSpace, InnerSpace, Matrix4, Point3, Transform, Vector2, Vector3,
+    Vector4, VectorSpace, Zero,
+};
 use glow::{Buffer, HasContext, VertexArray};
 
+use crate::{gl_resources, gl_state, VertexAttribute, VertexLayout};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{Bvh, BvhStats, MeshValidation, UvLayout};
+
+/// Meshes with fewer vertices are drawn with full detail at any distance.
+const LOD_MIN_VERTEX_COUNT: usize = 10_000;
+/// Number of grid cells along the longest side of the bounding box for each generated level of
+/// detail.
+const LOD_GRID_RESOLUTIONS: [f32; 3] = [64.0, 32.0, 16.0];
+/// Screen coverage below which the next coarser level of detail is drawn. Coverage is the
+/// fraction of the viewport height taken by the bounding sphere of the model.
+const LOD_COVERAGE_THRESHOLDS: [f32; 3] = [0.5, 0.25, 0.1];
+
 /// Representation of 3D model (currently mesh only).
 ///
-/// Mesh face vertices reside in GPU memory.
-/// Vertices are referred by indices to avoid storing duplicated vertices.
+/// Mesh can be shared between multiple models, e.g. when loaded through `AssetCache`. Display
+/// settings like face culling are stored per model.
 pub struct Model {
+    mesh: Arc<Mesh>,
+    /// Display name in model selection and scene label.
+    pub name: String,
+    pub face_culling: FaceCulling,
+}
+
+/// Triangle mesh with face vertices residing in GPU memory.
+/// Vertices are referred by indices to avoid storing duplicated vertices.
+pub struct Mesh {
     gl: Arc<glow::Context>,
-    pub vertex_array: VertexArray,
-    pub indices: Vec<u32>,
+    vertex_array: VertexArray,
+    index_count: usize,
+    vertex_count: usize,
+    vertex_layout: VertexLayout,
     vertex_buffer: Buffer,
     index_buffer: Buffer,
+    // GL_UNSIGNED_SHORT or GL_UNSIGNED_INT
+    index_type: u32,
+    // Detected from mesh geometry, used as default of models
+    face_culling: FaceCulling,
+    bounds: Aabb,
+    // Coarser levels of detail, from the most to the least detailed
+    lods: Vec<Mesh>,
+    // Only the full detail mesh has parts, simplification merges them
+    parts: Vec<MeshPart>,
+    // Built on the first ray query
+    #[cfg(not(target_arch = "wasm32"))]
+    bvh: std::sync::OnceLock<Arc<Bvh>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    validation: std::sync::OnceLock<MeshValidation>,
+    #[cfg(not(target_arch = "wasm32"))]
+    uv_layout: std::sync::OnceLock<UvLayout>,
 }
 
-/// Per-vertex data containing vertex attributes for each vertex.
+/// Consecutive triangles of a mesh loaded from a separate object of a file, e.g. the lid of a
+/// teapot modeled apart from its body. Parts are drawn separately when moved apart in exploded
+/// view.
+#[derive(Clone, Debug)]
+pub struct MeshPart {
+    pub name: String,
+    /// Position of the first index of the part in the index buffer.
+    pub first_index: usize,
+    pub index_count: usize,
+    /// Center of the bounding box of part vertices in model space.
+    pub center: Point3<f32>,
+}
+
+/// Triangle of a mesh as stored in its buffers, e.g. for debugging normals and winding.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Copy)]
+pub struct MeshTriangle {
+    /// Position of the first index of the triangle in the index buffer, divided by 3.
+    pub index: usize,
+    pub vertex_indices: [u32; 3],
+    pub vertices: [Vertex; 3],
+}
+
+/// Axis-aligned bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+/// Vertex order of triangles that are considered front-facing.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Winding {
+    CounterClockwise,
+    Clockwise,
+}
+
+/// Per-model back-face culling settings.
+///
+/// Culling back faces skips rasterizing roughly half of the triangles of a closed mesh, but breaks
+/// display of open meshes like the Utah Teapot where the inside can be seen through the holes.
+#[derive(Clone, Copy)]
+pub struct FaceCulling {
+    pub enabled: bool,
+    pub front_face: Winding,
+}
+
+impl Default for FaceCulling {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            front_face: Winding::CounterClockwise,
+        }
+    }
+}
+
+/// Position and normal of a vertex, the attributes every mesh has.
 ///
-/// Texture UV coordinates are omitted because none of the bundled default
-/// models have textures.
+/// Optional attributes like texture coordinates are stored in separate arrays of `MeshData`, so
+/// meshes without them don't waste memory.
 #[repr(C)] // Avoid Rust compiler to reorder or use different alignments for vertex fields
-struct Vertex {
+#[derive(Clone, Copy)]
+pub struct Vertex {
     pub position: Vector3<f32>,
     pub normal: Vector3<f32>,
 }
 
-impl Model {
+/// Triangle mesh in CPU memory, independent of the graphics context.
+///
+/// Meshes can be loaded, generated by `primitives` or built vertex by vertex, then processed (e.g.
+/// normals recalculated or simplified) before uploading with `Mesh::from_mesh_data()` or
+/// `Model::from_mesh_data()`.
+///
+/// Optional attribute arrays are either empty or have one element for each vertex, arrays of other
+/// lengths are left out of the vertex buffer.
+#[derive(Clone, Default)]
+pub struct MeshData {
+    pub vertices: Vec<Vertex>,
+    /// Vertex indices of triangles, 3 for each triangle.
+    pub indices: Vec<u32>,
+    /// Texture coordinates.
+    pub uvs: Vec<Vector2<f32>>,
+    /// Tangent direction in XYZ and handedness of the bitangent in W, see `calculate_tangents()`.
+    pub tangents: Vec<Vector4<f32>>,
+    /// Linear RGBA colors.
+    pub colors: Vec<Vector4<f32>>,
+    /// Texture coordinates of baked lighting, see `generate_lightmap_uvs()`.
+    pub lightmap_uvs: Vec<Vector2<f32>>,
+    /// Parts of meshes assembled from several objects in index buffer order, empty for meshes of a
+    /// single object.
+    pub parts: Vec<MeshPart>,
+}
+
+impl MeshData {
+    /// Load OBJ or PLY file, chosen by the file extension.
     #[cfg(not(target_arch = "wasm32"))]
-    pub fn create_from_file(gl: Arc<glow::Context>, path: &str) -> Result<Model, String> {
-        let (vertices, indices) = load_obj_from_file(path)?;
-        let (vertex_array, vertex_buffer, index_buffer) =
-            setup_shader_plumbing(&gl, &vertices, &indices);
+    pub fn load_from_file(path: &str) -> Result<MeshData, String> {
+        let is_ply = std::path::Path::new(path)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("ply"));
+        if is_ply {
+            let data = std::fs::read(path)
+                .map_err(|e| format!("failed to load model from {path}: {e}"))?;
+            return crate::ply::parse_ply(&data)
+                .map_err(|e| format!("failed to load model from {path}: {e}"));
+        }
+        let obj = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)
+            .map_err(|e| format!("failed to load model from {path}: {:?}", e))?;
+        Ok(process_obj(&obj.0))
+    }
 
-        Ok(Self {
-            gl,
-            vertex_array,
-            indices,
-            vertex_buffer,
-            index_buffer,
+    /// Load OBJ file contents, e.g. embedded with `include_bytes!()`.
+    pub fn load_from_buffer(data: &[u8]) -> Result<MeshData, String> {
+        let obj = tobj::load_obj_buf(&mut &data[..], &tobj::GPU_LOAD_OPTIONS, |_mtl_path| {
+            Ok(Default::default())
         })
+        .map_err(|e| format!("failed to load model: {:?}", e))?;
+        Ok(process_obj(&obj.0))
+    }
+
+    /// Attributes present in the vertex buffer uploaded from this mesh.
+    pub fn vertex_layout(&self) -> VertexLayout {
+        let vertex_count = self.vertices.len();
+        let mut layout = VertexLayout::new();
+        for (attribute, len) in [
+            (VertexAttribute::Uv, self.uvs.len()),
+            (VertexAttribute::Tangent, self.tangents.len()),
+            (VertexAttribute::Color, self.colors.len()),
+            (VertexAttribute::LightmapUv, self.lightmap_uvs.len()),
+        ] {
+            if vertex_count > 0 && len == vertex_count {
+                layout = layout.with_attribute(attribute);
+            }
+        }
+        layout
+    }
+
+    /// Bounding box of vertices.
+    pub fn bounds(&self) -> Aabb {
+        Aabb::from_points(
+            self.vertices
+                .iter()
+                .map(|vertex| Point3::from_vec(vertex.position)),
+        )
+    }
+
+    /// Replace normals with the average of the normals of adjacent triangles, weighted by triangle
+    /// area. Only triangles sharing the same vertex index are averaged, so hard edges with
+    /// duplicated vertices are kept.
+    pub fn recalculate_normals(&mut self) {
+        let mut normal_sums = vec![Vector3::zero(); self.vertices.len()];
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| self.vertices[triangle[i] as usize].position);
+            // Length of the cross product is twice the triangle area
+            let face_normal = (b - a).cross(c - a);
+            for &index in triangle {
+                normal_sums[index as usize] += face_normal;
+            }
+        }
+        for (vertex, normal_sum) in self.vertices.iter_mut().zip(normal_sums) {
+            // Unreferenced vertices and degenerate triangles have no direction
+            if normal_sum.magnitude2() > f32::EPSILON {
+                vertex.normal = normal_sum.normalize();
+            }
+        }
+    }
+
+    /// Generate tangents from texture coordinates for normal mapping, orthogonalized to the
+    /// normals. Tangents are averaged from adjacent triangles like normals in
+    /// `recalculate_normals()`.
+    pub fn calculate_tangents(&mut self) -> Result<(), String> {
+        if self.uvs.len() != self.vertices.len() {
+            return Err(format!(
+                "mesh has {} texture coordinates for {} vertices",
+                self.uvs.len(),
+                self.vertices.len()
+            ));
+        }
+
+        let mut tangent_sums = vec![Vector3::zero(); self.vertices.len()];
+        let mut bitangent_sums = vec![Vector3::zero(); self.vertices.len()];
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+            let edge1 = self.vertices[b].position - self.vertices[a].position;
+            let edge2 = self.vertices[c].position - self.vertices[a].position;
+            let uv_edge1 = self.uvs[b] - self.uvs[a];
+            let uv_edge2 = self.uvs[c] - self.uvs[a];
+            let determinant = uv_edge1.x * uv_edge2.y - uv_edge2.x * uv_edge1.y;
+            // Texture coordinates collapsed into a line have no tangent space
+            if determinant.abs() < f32::EPSILON {
+                continue;
+            }
+            let tangent = (edge1 * uv_edge2.y - edge2 * uv_edge1.y) / determinant;
+            let bitangent = (edge2 * uv_edge1.x - edge1 * uv_edge2.x) / determinant;
+            for index in [a, b, c] {
+                tangent_sums[index] += tangent;
+                bitangent_sums[index] += bitangent;
+            }
+        }
+
+        self.tangents = self
+            .vertices
+            .iter()
+            .zip(tangent_sums.into_iter().zip(bitangent_sums))
+            .map(|(vertex, (tangent_sum, bitangent_sum))| {
+                // Gram-Schmidt orthogonalization
+                let tangent = tangent_sum - vertex.normal * vertex.normal.dot(tangent_sum);
+                if tangent.magnitude2() < f32::EPSILON {
+                    return Vector4::zero();
+                }
+                let handedness = if vertex.normal.cross(tangent).dot(bitangent_sum) < 0.0 {
+                    -1.0
+                } else {
+                    1.0
+                };
+                tangent.normalize().extend(handedness)
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Simplify mesh by vertex clustering. Vertices within the same cell of a uniform grid are
+    /// merged into their average, triangles collapsing into a line or a point are removed.
+    /// Optional attributes are averaged the same way.
+    pub fn simplified(&self, cell_size: f32) -> MeshData {
+        let bounds = self.bounds();
+        let mut cells: HashMap<[i32; 3], u32> = HashMap::new();
+        // Sum of positions and normals, and number of merged vertices per cell
+        let mut clusters: Vec<(Vector3<f32>, Vector3<f32>, f32)> = Vec::new();
+        let remap: Vec<u32> = self
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let cell = ((vertex.position - bounds.min.to_vec()) / cell_size)
+                    .map(|coordinate| coordinate.floor() as i32);
+                let index = *cells.entry([cell.x, cell.y, cell.z]).or_insert_with(|| {
+                    clusters.push((Vector3::zero(), Vector3::zero(), 0.0));
+                    (clusters.len() - 1) as u32
+                });
+                let cluster = &mut clusters[index as usize];
+                cluster.0 += vertex.position;
+                cluster.1 += vertex.normal;
+                cluster.2 += 1.0;
+                index
+            })
+            .collect();
+
+        let vertices = clusters
+            .into_iter()
+            .map(|(position_sum, normal_sum, count)| Vertex {
+                position: position_sum / count,
+                // Opposite normals can cancel out
+                normal: if normal_sum.magnitude2() < f32::EPSILON {
+                    normal_sum
+                } else {
+                    normal_sum.normalize()
+                },
+            })
+            .collect();
+        let indices = self
+            .indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                [
+                    remap[triangle[0] as usize],
+                    remap[triangle[1] as usize],
+                    remap[triangle[2] as usize],
+                ]
+            })
+            .filter(|[a, b, c]| a != b && b != c && a != c)
+            .flatten()
+            .collect();
+
+        let layout = self.vertex_layout();
+        let cluster_count = cells.len();
+        let average = |values, attribute| {
+            if layout.contains(attribute) {
+                average_clusters(values, &remap, cluster_count)
+            } else {
+                Vec::new()
+            }
+        };
+        let tangents = average(&self.tangents, VertexAttribute::Tangent)
+            .into_iter()
+            .map(|tangent| {
+                let direction = tangent.truncate();
+                if direction.magnitude2() < f32::EPSILON {
+                    tangent
+                } else {
+                    direction.normalize().extend(tangent.w.signum())
+                }
+            })
+            .collect();
+
+        Self {
+            vertices,
+            indices,
+            uvs: if layout.contains(VertexAttribute::Uv) {
+                average_clusters(&self.uvs, &remap, cluster_count)
+            } else {
+                Vec::new()
+            },
+            tangents,
+            colors: average(&self.colors, VertexAttribute::Color),
+            // Merged vertices would stretch triangles across lightmap charts
+            lightmap_uvs: Vec::new(),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Vertex attributes of the layout interleaved vertex by vertex, as uploaded to vertex buffers.
+    fn interleaved(&self, layout: &VertexLayout) -> Vec<f32> {
+        let mut floats = Vec::with_capacity(self.vertices.len() * layout.floats_per_vertex());
+        for (i, vertex) in self.vertices.iter().enumerate() {
+            for &attribute in layout.attributes() {
+                let values: &[f32] = match attribute {
+                    VertexAttribute::Position => AsRef::<[f32; 3]>::as_ref(&vertex.position),
+                    VertexAttribute::Normal => AsRef::<[f32; 3]>::as_ref(&vertex.normal),
+                    VertexAttribute::Uv => AsRef::<[f32; 2]>::as_ref(&self.uvs[i]),
+                    VertexAttribute::Tangent => AsRef::<[f32; 4]>::as_ref(&self.tangents[i]),
+                    VertexAttribute::Color => AsRef::<[f32; 4]>::as_ref(&self.colors[i]),
+                    VertexAttribute::LightmapUv => AsRef::<[f32; 2]>::as_ref(&self.lightmap_uvs[i]),
+                };
+                floats.extend_from_slice(values);
+            }
+        }
+        floats
+    }
+
+    /// Inverse of `interleaved()`.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn from_interleaved(floats: &[f32], layout: &VertexLayout, indices: Vec<u32>) -> Self {
+        let mut mesh_data = Self {
+            indices,
+            ..Default::default()
+        };
+        for vertex in floats.chunks_exact(layout.floats_per_vertex()) {
+            let attribute = move |attribute: VertexAttribute| {
+                layout
+                    .float_offset(attribute)
+                    .map(|offset| &vertex[offset..offset + attribute.component_count()])
+            };
+            let position = attribute(VertexAttribute::Position).unwrap();
+            let normal = attribute(VertexAttribute::Normal).unwrap();
+            mesh_data.vertices.push(Vertex {
+                position: vec3(position[0], position[1], position[2]),
+                normal: vec3(normal[0], normal[1], normal[2]),
+            });
+            if let Some(uv) = attribute(VertexAttribute::Uv) {
+                mesh_data.uvs.push(vec2(uv[0], uv[1]));
+            }
+            if let Some(tangent) = attribute(VertexAttribute::Tangent) {
+                mesh_data
+                    .tangents
+                    .push(vec4(tangent[0], tangent[1], tangent[2], tangent[3]));
+            }
+            if let Some(color) = attribute(VertexAttribute::Color) {
+                mesh_data
+                    .colors
+                    .push(vec4(color[0], color[1], color[2], color[3]));
+            }
+            if let Some(uv) = attribute(VertexAttribute::LightmapUv) {
+                mesh_data.lightmap_uvs.push(vec2(uv[0], uv[1]));
+            }
+        }
+        mesh_data
+    }
+}
+
+impl Model {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn create_from_file(gl: Arc<glow::Context>, path: &str) -> Result<Model, String> {
+        Ok(Self::from_mesh(Arc::new(Mesh::create_from_file(gl, path)?)))
     }
 
     #[cfg(target_arch = "wasm32")]
@@ -46,60 +438,493 @@ impl Model {
         gl: Arc<glow::Context>,
         data: &'static [u8],
     ) -> Result<Model, String> {
-        let (vertices, indices) =
-            load_obj_from_buffer(data).map_err(|e| format!("failed to load model: {:?}", e))?;
-        let (vertex_array, vertex_buffer, index_buffer) =
-            setup_shader_plumbing(&gl, &vertices, &indices);
+        Ok(Self::from_mesh(Arc::new(Mesh::create_from_buffer(
+            gl, data,
+        )?)))
+    }
+
+    /// Upload mesh from CPU memory, e.g. generated by `primitives`.
+    pub fn from_mesh_data(gl: Arc<glow::Context>, mesh_data: &MeshData) -> Self {
+        Self::from_mesh(Arc::new(Mesh::from_mesh_data(gl, mesh_data)))
+    }
+
+    pub fn from_mesh(mesh: Arc<Mesh>) -> Self {
+        Self {
+            face_culling: mesh.face_culling,
+            name: String::new(),
+            mesh,
+        }
+    }
 
-        Ok(Self {
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_owned();
+        self
+    }
+
+    pub fn mesh(&self) -> &Arc<Mesh> {
+        &self.mesh
+    }
+
+    pub fn vertex_array(&self) -> VertexArray {
+        self.mesh.vertex_array
+    }
+
+    pub fn index_count(&self) -> usize {
+        self.mesh.index_count
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.mesh.vertex_count
+    }
+
+    /// Bounding box of mesh vertices in model space.
+    pub fn bounds(&self) -> Aabb {
+        self.mesh.bounds
+    }
+
+    /// Number of levels of detail, including the full detail mesh.
+    pub fn lod_count(&self) -> usize {
+        1 + self.mesh.lods.len()
+    }
+
+    /// Level of detail to draw when the bounding sphere covers the given fraction of the viewport
+    /// height. Level 0 is the full detail mesh.
+    pub fn lod_for_coverage(&self, coverage: f32) -> usize {
+        LOD_COVERAGE_THRESHOLDS
+            .iter()
+            .take(self.mesh.lods.len())
+            .filter(|threshold| coverage < **threshold)
+            .count()
+    }
+
+    /// Mesh of the level of detail, falling back to the least detailed one.
+    pub fn lod(&self, level: usize) -> &Mesh {
+        match level.checked_sub(1) {
+            Some(index) => self.mesh.lods.get(index).or(self.mesh.lods.last()).unwrap(),
+            None => &self.mesh,
+        }
+    }
+
+    /// Buffer of interleaved vertex positions and normals, 6 floats per vertex. Can be bound as
+    /// shader storage buffer for modifying vertices on GPU.
+    ///
+    /// Modifying the buffer affects every model sharing the same mesh.
+    pub fn vertex_buffer(&self) -> Buffer {
+        self.mesh.vertex_buffer
+    }
+
+    /// Parts of the full detail mesh, empty if the mesh was loaded from a single object.
+    pub fn parts(&self) -> &[MeshPart] {
+        &self.mesh.parts
+    }
+}
+
+impl Mesh {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn create_from_file(gl: Arc<glow::Context>, path: &str) -> Result<Mesh, String> {
+        Ok(Self::from_mesh_data(gl, &MeshData::load_from_file(path)?))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn create_from_buffer(gl: Arc<glow::Context>, data: &'static [u8]) -> Result<Mesh, String> {
+        Ok(Self::from_mesh_data(gl, &MeshData::load_from_buffer(data)?))
+    }
+
+    /// Upload mesh from CPU memory and generate its levels of detail, the same way as for meshes
+    /// loaded from files.
+    pub fn from_mesh_data(gl: Arc<glow::Context>, mesh_data: &MeshData) -> Mesh {
+        let mut mesh = Self::upload(gl, mesh_data);
+        mesh.lods = mesh.generate_lods(mesh_data);
+        mesh
+    }
+
+    /// Mesh without any geometry standing in for a model that failed to load. Keeps the indices
+    /// of the remaining models intact.
+    pub fn empty(gl: Arc<glow::Context>) -> Mesh {
+        Self::from_mesh_data(gl, &MeshData::default())
+    }
+
+    /// Replace the levels of detail with coarser meshes provided by the user, ordered from the most
+    /// to the least detailed. At most 3 levels are used.
+    pub fn with_lods(mut self, lods: Vec<Mesh>) -> Self {
+        self.lods = lods;
+        self
+    }
+
+    pub fn vertex_array(&self) -> VertexArray {
+        self.vertex_array
+    }
+
+    pub fn index_count(&self) -> usize {
+        self.index_count
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    /// Buffer of interleaved vertex attributes described by `vertex_layout()`.
+    pub fn vertex_buffer(&self) -> Buffer {
+        self.vertex_buffer
+    }
+
+    /// Buffer of `index_count()` indices of `index_type()`.
+    pub fn index_buffer(&self) -> Buffer {
+        self.index_buffer
+    }
+
+    /// Bounding box of vertices in model space.
+    pub fn bounds(&self) -> Aabb {
+        self.bounds
+    }
+
+    /// Upload mesh from CPU memory without generating levels of detail.
+    pub(crate) fn upload(gl: Arc<glow::Context>, mesh_data: &MeshData) -> Self {
+        let face_culling = detect_face_culling(&mesh_data.vertices, &mesh_data.indices);
+        let bounds = mesh_data.bounds();
+        let vertex_layout = mesh_data.vertex_layout();
+        // 16-bit indices halve the size of the index buffer when they can address every vertex
+        let index_type = if mesh_data.vertices.len() <= u16::MAX as usize + 1 {
+            glow::UNSIGNED_SHORT
+        } else {
+            glow::UNSIGNED_INT
+        };
+        let (vertex_array, vertex_buffer, index_buffer) = setup_shader_plumbing(
+            &gl,
+            &mesh_data.interleaved(&vertex_layout),
+            &vertex_layout,
+            &mesh_data.indices,
+            index_type,
+        );
+
+        Self {
             gl,
             vertex_array,
-            indices,
+            index_count: mesh_data.indices.len(),
+            vertex_count: mesh_data.vertices.len(),
+            vertex_layout,
             vertex_buffer,
             index_buffer,
+            index_type,
+            face_culling,
+            bounds,
+            lods: Vec::new(),
+            parts: mesh_data.parts.clone(),
+            #[cfg(not(target_arch = "wasm32"))]
+            bvh: std::sync::OnceLock::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            validation: std::sync::OnceLock::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            uv_layout: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// Simplify dense meshes into coarser levels of detail. Levels not reducing the triangle
+    /// count considerably are skipped.
+    fn generate_lods(&self, mesh_data: &MeshData) -> Vec<Mesh> {
+        if mesh_data.vertices.len() < LOD_MIN_VERTEX_COUNT {
+            return Vec::new();
+        }
+
+        let extent = self.bounds.max - self.bounds.min;
+        let longest_side = extent.x.max(extent.y).max(extent.z);
+        let mut lods = Vec::new();
+        let mut previous_index_count = mesh_data.indices.len();
+        for resolution in LOD_GRID_RESOLUTIONS {
+            let lod = mesh_data.simplified(longest_side / resolution);
+            if lod.indices.is_empty() || previous_index_count * 3 < lod.indices.len() * 4 {
+                continue;
+            }
+            previous_index_count = lod.indices.len();
+            lods.push(Self::upload(self.gl.clone(), &lod));
+        }
+        lods
+    }
+
+    /// Copy vertices and indices back from GPU memory, including modifications made on GPU like
+    /// vertex displacement.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_back(&self) -> Result<MeshData, String> {
+        let vertex_floats: Vec<f32> = read_buffer(
+            &self.gl,
+            self.vertex_buffer,
+            0,
+            self.vertex_count * self.vertex_layout.floats_per_vertex(),
+        )?;
+        let indices = if self.index_type == glow::UNSIGNED_SHORT {
+            read_buffer::<u16>(&self.gl, self.index_buffer, 0, self.index_count)?
+                .into_iter()
+                .map(u32::from)
+                .collect()
+        } else {
+            read_buffer(&self.gl, self.index_buffer, 0, self.index_count)?
+        };
+        Ok(MeshData::from_interleaved(
+            &vertex_floats,
+            &self.vertex_layout,
+            indices,
+        ))
+    }
+
+    /// Vertex indices and vertices of a triangle of the index buffer read back from GPU memory,
+    /// e.g. for inspecting the triangle hit by a ray.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_triangle(&self, index: usize) -> Result<MeshTriangle, String> {
+        if self.index_count < (index + 1) * 3 {
+            return Err(format!("mesh has no triangle {index}"));
+        }
+        let vertex_indices: Vec<u32> = if self.index_type == glow::UNSIGNED_SHORT {
+            read_buffer::<u16>(&self.gl, self.index_buffer, index * 3, 3)?
+                .into_iter()
+                .map(u32::from)
+                .collect()
+        } else {
+            read_buffer(&self.gl, self.index_buffer, index * 3, 3)?
+        };
+        let vertex_indices = <[u32; 3]>::try_from(vertex_indices).unwrap();
+        // Position and normal come first in every vertex layout
+        let floats_per_vertex = self.vertex_layout.floats_per_vertex();
+        let mut vertices = [Vertex {
+            position: Vector3::zero(),
+            normal: Vector3::zero(),
+        }; 3];
+        for (vertex, vertex_index) in vertices.iter_mut().zip(vertex_indices) {
+            let floats: Vec<f32> = read_buffer(
+                &self.gl,
+                self.vertex_buffer,
+                vertex_index as usize * floats_per_vertex,
+                6,
+            )?;
+            vertex.position = Vector3::new(floats[0], floats[1], floats[2]);
+            vertex.normal = Vector3::new(floats[3], floats[4], floats[5]);
+        }
+        Ok(MeshTriangle {
+            index,
+            vertex_indices,
+            vertices,
+        })
+    }
+
+    /// Bounding volume hierarchy of the triangles for ray queries, built on first use from the
+    /// vertices read back from GPU memory. Later modifications on GPU are not reflected. Shared,
+    /// so lighting can be baked on another thread.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn bvh(&self) -> Result<&Arc<Bvh>, String> {
+        if let Some(bvh) = self.bvh.get() {
+            return Ok(bvh);
+        }
+        let mesh_data = self.read_back()?;
+        Ok(self
+            .bvh
+            .get_or_init(|| Arc::new(Bvh::build(&mesh_data.vertices, &mesh_data.indices))))
+    }
+
+    /// Statistics of the bounding volume hierarchy, `None` until the first ray query.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn bvh_stats(&self) -> Option<&BvhStats> {
+        self.bvh.get().map(|bvh| bvh.stats())
+    }
+
+    /// Problems of the mesh found by analyzing the vertices read back from GPU memory on the first
+    /// call. Later modifications on GPU are not reflected.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn validate(&self) -> Result<&MeshValidation, String> {
+        if let Some(validation) = self.validation.get() {
+            return Ok(validation);
+        }
+        let mesh_data = self.read_back()?;
+        Ok(self
+            .validation
+            .get_or_init(|| MeshValidation::analyze(&mesh_data.vertices, &mesh_data.indices)))
+    }
+
+    /// Result of `validate()`, `None` until the mesh is analyzed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn validation(&self) -> Option<&MeshValidation> {
+        self.validation.get()
+    }
+
+    /// Triangles in texture space with overlaps found from the texture coordinates read back from
+    /// GPU memory on the first call.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn uv_layout(&self) -> Result<&UvLayout, String> {
+        if let Some(uv_layout) = self.uv_layout.get() {
+            return Ok(uv_layout);
+        }
+        if !self.vertex_layout.contains(VertexAttribute::Uv) {
+            return Err("mesh has no texture coordinates".to_string());
+        }
+        let mesh_data = self.read_back()?;
+        Ok(self
+            .uv_layout
+            .get_or_init(|| UvLayout::analyze(&mesh_data.uvs, &mesh_data.indices)))
+    }
+
+    /// Attributes interleaved in the vertex buffer.
+    pub fn vertex_layout(&self) -> &VertexLayout {
+        &self.vertex_layout
+    }
+
+    /// Type of elements in the index buffer to pass to `glDrawElements()`, `GL_UNSIGNED_SHORT` for
+    /// meshes with at most 65536 vertices, otherwise `GL_UNSIGNED_INT`.
+    pub fn index_type(&self) -> u32 {
+        self.index_type
+    }
+
+    /// Size of an index in bytes.
+    pub fn index_size(&self) -> usize {
+        if self.index_type == glow::UNSIGNED_SHORT {
+            size_of::<u16>()
+        } else {
+            size_of::<u32>()
+        }
+    }
+
+    /// Estimated size of vertex and index buffers in GPU memory in bytes, including levels of
+    /// detail.
+    pub fn gpu_memory_size(&self) -> usize {
+        self.vertex_count * self.vertex_layout.stride()
+            + self.index_count * self.index_size()
+            + self.lods.iter().map(Mesh::gpu_memory_size).sum::<usize>()
+    }
+}
+
+impl Aabb {
+    /// Smallest box enclosing the points. Box of an empty point set is a single point at the
+    /// origin.
+    pub fn from_points(points: impl IntoIterator<Item = Point3<f32>>) -> Self {
+        let mut points = points.into_iter();
+        let Some(first) = points.next() else {
+            return Self {
+                min: Point3::origin(),
+                max: Point3::origin(),
+            };
+        };
+        points.fold(
+            Self {
+                min: first,
+                max: first,
+            },
+            |aabb, point| Self {
+                min: Point3::new(
+                    aabb.min.x.min(point.x),
+                    aabb.min.y.min(point.y),
+                    aabb.min.z.min(point.z),
+                ),
+                max: Point3::new(
+                    aabb.max.x.max(point.x),
+                    aabb.max.y.max(point.y),
+                    aabb.max.z.max(point.z),
+                ),
+            },
+        )
+    }
+
+    pub fn center(&self) -> Point3<f32> {
+        self.min.midpoint(self.max)
+    }
+
+    /// Radius of the bounding sphere around the center of the box.
+    pub fn radius(&self) -> f32 {
+        (self.max - self.min).magnitude() * 0.5
+    }
+
+    /// Corners indexed by bit masks, bit 0, 1 and 2 selecting the maximum X, Y and Z coordinate.
+    pub fn corners(&self) -> [Point3<f32>; 8] {
+        std::array::from_fn(|i| {
+            Point3::new(
+                if i & 1 == 0 { self.min.x } else { self.max.x },
+                if i & 2 == 0 { self.min.y } else { self.max.y },
+                if i & 4 == 0 { self.min.z } else { self.max.z },
+            )
         })
     }
+
+    /// Axis-aligned box enclosing this box transformed by the matrix.
+    pub fn transformed(&self, matrix: &Matrix4<f32>) -> Self {
+        Self::from_points(
+            self.corners()
+                .into_iter()
+                .map(|corner| matrix.transform_point(corner)),
+        )
+    }
 }
 
-impl Drop for Model {
+impl Drop for Mesh {
     fn drop(&mut self) {
         unsafe {
-            self.gl.delete_buffer(self.index_buffer);
-            self.gl.delete_buffer(self.vertex_buffer);
-            self.gl.delete_vertex_array(self.vertex_array);
+            gl_resources::delete_buffer(&self.gl, self.index_buffer);
+            gl_resources::delete_buffer(&self.gl, self.vertex_buffer);
+            gl_resources::delete_vertex_array(&self.gl, self.vertex_array);
         }
     }
 }
 
+/// Copy elements of a buffer starting at the element index into CPU memory. Mapping is used
+/// instead of `glGetBufferSubData()`, which is missing from OpenGL ES.
 #[cfg(not(target_arch = "wasm32"))]
-fn load_obj_from_file(path: &str) -> Result<(Vec<Vertex>, Vec<u32>), String> {
-    let obj = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)
-        .map_err(|e| format!("failed to load model from {path}: {:?}", e))?;
+fn read_buffer<T: Copy + Default>(
+    gl: &glow::Context,
+    buffer: Buffer,
+    first: usize,
+    len: usize,
+) -> Result<Vec<T>, String> {
+    let mut data = vec![T::default(); len];
+    if len == 0 {
+        return Ok(data);
+    }
 
-    Ok(process_obj(&obj.0))
+    let offset = first * size_of::<T>();
+    let size = len * size_of::<T>();
+    unsafe {
+        gl.bind_buffer(glow::COPY_READ_BUFFER, Some(buffer));
+        let mapped = gl.map_buffer_range(
+            glow::COPY_READ_BUFFER,
+            offset as i32,
+            size as i32,
+            glow::MAP_READ_BIT,
+        );
+        let result = if mapped.is_null() {
+            Err(format!(
+                "cannot map buffer for reading: error {}",
+                gl.get_error()
+            ))
+        } else {
+            std::ptr::copy_nonoverlapping(mapped, data.as_mut_ptr() as *mut u8, size);
+            gl.unmap_buffer(glow::COPY_READ_BUFFER);
+            Ok(data)
+        };
+        gl.bind_buffer(glow::COPY_READ_BUFFER, None);
+        result
+    }
 }
 
-#[cfg(target_arch = "wasm32")]
-fn load_obj_from_buffer(data: &'static [u8]) -> Result<(Vec<Vertex>, Vec<u32>), String> {
-    let obj = tobj::load_obj_buf(&mut &data[..], &tobj::GPU_LOAD_OPTIONS, |_mtl_path| {
-        Ok(Default::default())
-    })
-    .map_err(|e| format!("failed to load model: {:?}", e))?;
-
-    Ok(process_obj(&obj.0))
+/// Average of values merged into the same cluster, `remap` is the cluster index of each value.
+fn average_clusters<V: VectorSpace<Scalar = f32>>(
+    values: &[V],
+    remap: &[u32],
+    cluster_count: usize,
+) -> Vec<V> {
+    let mut sums = vec![(V::zero(), 0.0); cluster_count];
+    for (value, &index) in values.iter().zip(remap) {
+        sums[index as usize].0 = sums[index as usize].0 + *value;
+        sums[index as usize].1 += 1.0;
+    }
+    sums.into_iter().map(|(sum, count)| sum / count).collect()
 }
 
-fn process_obj(models: &Vec<tobj::Model>) -> (Vec<Vertex>, Vec<u32>) {
-    let mut vertices: Vec<Vertex> = Vec::new();
-    let mut indices: Vec<u32> = Vec::new();
+pub fn process_obj(models: &Vec<tobj::Model>) -> MeshData {
+    let mut mesh_data = MeshData::default();
     // Sometimes you get a mesh file with just a single mesh and no others.
     // The bundled default files are such meshes.
     for model in models {
         let mesh = &model.mesh;
         let vertices_count = mesh.positions.len() / 3;
-        vertices.reserve(vertices_count);
+        let first_vertex = mesh_data.vertices.len();
+        let first_index = mesh_data.indices.len();
+        mesh_data.vertices.reserve(vertices_count);
         for i in 0..vertices_count {
-            vertices.push(Vertex {
+            mesh_data.vertices.push(Vertex {
                 position: vec3(
                     mesh.positions[i * 3],
                     mesh.positions[i * 3 + 1],
@@ -112,54 +937,165 @@ fn process_obj(models: &Vec<tobj::Model>) -> (Vec<Vertex>, Vec<u32>) {
                 ),
             });
         }
+        mesh_data
+            .uvs
+            .extend(mesh.texcoords.chunks_exact(2).map(|uv| vec2(uv[0], uv[1])));
+        mesh_data
+            .colors
+            .extend(mesh.vertex_color.chunks_exact(3).map(|color| {
+                vec4(
+                    srgb_to_linear(color[0]),
+                    srgb_to_linear(color[1]),
+                    srgb_to_linear(color[2]),
+                    1.0,
+                )
+            }));
 
-        indices.extend_from_slice(&mesh.indices);
+        // Indices of each object start from its own first vertex
+        mesh_data
+            .indices
+            .extend(mesh.indices.iter().map(|index| index + first_vertex as u32));
+        if 1 < models.len() && !mesh.indices.is_empty() {
+            let part_bounds = Aabb::from_points(
+                mesh_data.vertices[first_vertex..]
+                    .iter()
+                    .map(|vertex| Point3::from_vec(vertex.position)),
+            );
+            mesh_data.parts.push(MeshPart {
+                name: model.name.clone(),
+                first_index,
+                index_count: mesh.indices.len(),
+                center: part_bounds.center(),
+            });
+        }
     }
 
-    (vertices, indices)
+    // Attributes missing from some of the meshes are dropped
+    if mesh_data.uvs.len() != mesh_data.vertices.len() {
+        mesh_data.uvs.clear();
+    }
+    if mesh_data.colors.len() != mesh_data.vertices.len() {
+        mesh_data.colors.clear();
+    }
+    mesh_data
+}
+
+/// Convert a color component stored in files from sRGB to linear color space.
+pub(crate) fn srgb_to_linear(component: f32) -> f32 {
+    if component <= 0.04045 {
+        component / 12.92
+    } else {
+        ((component + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Enable culling only for closed meshes and determine front face winding from the sign of the
+/// mesh volume.
+///
+/// A mesh is considered closed if every edge is shared by exactly two triangles. Vertices are
+/// compared by position, because vertices on hard edges are duplicated for each distinct normal.
+pub fn detect_face_culling(vertices: &[Vertex], indices: &[u32]) -> FaceCulling {
+    let position_key = |i: u32| {
+        let p = vertices[i as usize].position;
+        [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]
+    };
+
+    let mut edge_counts: HashMap<([u32; 3], [u32; 3]), u32> = HashMap::new();
+    // Divergence theorem: sum of signed tetrahedron volumes formed by origin and each triangle is
+    // positive if triangles are wound counter-clockwise when viewed from outside.
+    let mut signed_volume = 0.0;
+    for triangle in indices.chunks_exact(3) {
+        for (a, b) in [
+            (triangle[0], triangle[1]),
+            (triangle[1], triangle[2]),
+            (triangle[2], triangle[0]),
+        ] {
+            let (a, b) = (position_key(a), position_key(b));
+            let edge = if a < b { (a, b) } else { (b, a) };
+            *edge_counts.entry(edge).or_insert(0) += 1;
+        }
+
+        let v0 = vertices[triangle[0] as usize].position;
+        let v1 = vertices[triangle[1] as usize].position;
+        let v2 = vertices[triangle[2] as usize].position;
+        signed_volume += v0.dot(v1.cross(v2)) / 6.0;
+    }
+
+    let closed = !edge_counts.is_empty() && edge_counts.values().all(|&count| count == 2);
+    FaceCulling {
+        enabled: closed,
+        front_face: if signed_volume < 0.0 {
+            Winding::Clockwise
+        } else {
+            Winding::CounterClockwise
+        },
+    }
 }
 
 fn setup_shader_plumbing(
     gl: &glow::Context,
-    vertices: &Vec<Vertex>,
-    indices: &Vec<u32>,
+    vertex_floats: &[f32],
+    vertex_layout: &VertexLayout,
+    indices: &[u32],
+    index_type: u32,
 ) -> (VertexArray, Buffer, Buffer) {
     unsafe {
         // Create vertex array
-        let vertex_array = gl.create_vertex_array().unwrap();
-        gl.bind_vertex_array(Some(vertex_array));
+        let vertex_array = gl_resources::create_vertex_array(gl).unwrap();
+        gl_state::bind_vertex_array(gl, Some(vertex_array));
 
         // Create vertex buffer
-        let vertex_buffer = gl.create_buffer().unwrap();
+        let vertex_buffer = gl_resources::create_buffer(gl).unwrap();
         gl.bind_buffer(glow::ARRAY_BUFFER, Some(vertex_buffer));
-        let (_, vertices_bytes, _) = vertices.align_to::<u8>();
-        gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, vertices_bytes, glow::STATIC_DRAW);
+        let (_, vertices_bytes, _) = vertex_floats.align_to::<u8>();
+        gl_resources::buffer_data_u8_slice(
+            gl,
+            vertex_buffer,
+            glow::ARRAY_BUFFER,
+            vertices_bytes,
+            glow::STATIC_DRAW,
+        );
 
         // Create index buffer
-        let index_buffer = gl.create_buffer().unwrap();
+        let index_buffer = gl_resources::create_buffer(gl).unwrap();
         gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(index_buffer));
-        let (_, indices_bytes, _) = indices.align_to::<u8>();
-        gl.buffer_data_u8_slice(glow::ELEMENT_ARRAY_BUFFER, indices_bytes, glow::STATIC_DRAW);
+        let short_indices: Vec<u16>;
+        let indices_bytes = if index_type == glow::UNSIGNED_SHORT {
+            short_indices = indices.iter().map(|&index| index as u16).collect();
+            short_indices.align_to::<u8>().1
+        } else {
+            indices.align_to::<u8>().1
+        };
+        gl_resources::buffer_data_u8_slice(
+            gl,
+            index_buffer,
+            glow::ELEMENT_ARRAY_BUFFER,
+            indices_bytes,
+            glow::STATIC_DRAW,
+        );
+
+        set_vertex_attributes(gl, vertex_layout);
 
-        // Setup vertex array layout
-        let position_vertex_attribute = 0;
-        let stride = size_of::<Vertex>() as i32;
-        gl.enable_vertex_attrib_array(position_vertex_attribute);
-        gl.vertex_attrib_pointer_f32(position_vertex_attribute, 3, glow::FLOAT, false, stride, 0);
+        gl_state::bind_vertex_array(gl, None);
 
-        let normal_vertex_attribute = 1;
-        gl.enable_vertex_attrib_array(normal_vertex_attribute);
+        (vertex_array, vertex_buffer, index_buffer)
+    }
+}
+
+/// Point attributes of the bound vertex array at the interleaved vertices of the buffer bound to
+/// `GL_ARRAY_BUFFER`.
+pub(crate) unsafe fn set_vertex_attributes(gl: &glow::Context, vertex_layout: &VertexLayout) {
+    let stride = vertex_layout.stride() as i32;
+    for &attribute in vertex_layout.attributes() {
+        let offset = vertex_layout.float_offset(attribute).unwrap() * size_of::<f32>();
+        gl.enable_vertex_attrib_array(attribute.location());
         gl.vertex_attrib_pointer_f32(
-            1,
-            3,
+            attribute.location(),
+            attribute.component_count() as i32,
             glow::FLOAT,
             false,
             stride,
-            std::mem::offset_of!(Vertex, normal) as i32,
+            offset as i32,
         );
-
-        gl.bind_vertex_array(None);
-
-        (vertex_array, vertex_buffer, index_buffer)
     }
 }
@@ -0,0 +1,202 @@
+use hecs::World;
+
+use crate::{AreaLight, DrawProperties, PointLight};
+
+/// Most modulation sources with a level, e.g. frequency bands or controller channels.
+pub const MAX_MODULATION_SOURCES: usize = 64;
+
+/// Scene parameter driven by the level of a modulation source.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ModulationTarget {
+    /// Spin of the selected model around the vertical axis, amount in degrees per second at full
+    /// level.
+    RotationSpeed,
+    /// Intensity added to every point and area light.
+    LightIntensity,
+    /// Stops added to the exposure compensation.
+    Exposure,
+    /// Distance added to the parts of assembled models, see `DrawProperties::part_explode_factor`.
+    PartExplode,
+}
+
+impl ModulationTarget {
+    pub const ALL: [ModulationTarget; 4] = [
+        ModulationTarget::RotationSpeed,
+        ModulationTarget::LightIntensity,
+        ModulationTarget::Exposure,
+        ModulationTarget::PartExplode,
+    ];
+
+    /// Message identifier of the translated label.
+    pub(crate) fn l10n_id(self) -> &'static str {
+        match self {
+            ModulationTarget::RotationSpeed => "modulation-rotation-speed",
+            ModulationTarget::LightIntensity => "modulation-light-intensity",
+            ModulationTarget::Exposure => "modulation-exposure",
+            ModulationTarget::PartExplode => "modulation-part-explode",
+        }
+    }
+
+    /// Amounts selectable in the GUI.
+    pub(crate) fn amount_range(self) -> std::ops::RangeInclusive<f32> {
+        match self {
+            ModulationTarget::RotationSpeed => -360.0..=360.0,
+            ModulationTarget::LightIntensity => 0.0..=10.0,
+            ModulationTarget::Exposure => -4.0..=4.0,
+            ModulationTarget::PartExplode => 0.0..=2.0,
+        }
+    }
+
+    /// Change the parameter by `delta`, in the units of the target amount.
+    fn add(self, delta: f32, draw_props: &mut DrawProperties, world: &mut World) {
+        match self {
+            ModulationTarget::RotationSpeed => {
+                let rotation = &mut draw_props.model_rotation[1];
+                *rotation = (*rotation + delta).rem_euclid(360.0);
+            }
+            ModulationTarget::LightIntensity => {
+                for (_, point_light) in world.query_mut::<&mut PointLight>() {
+                    point_light.intensity += delta;
+                }
+                for (_, area_light) in world.query_mut::<&mut AreaLight>() {
+                    area_light.intensity += delta;
+                }
+            }
+            ModulationTarget::Exposure => draw_props.exposure += delta,
+            ModulationTarget::PartExplode => draw_props.part_explode_factor += delta,
+        }
+    }
+}
+
+/// Connection from a source level to a scene parameter.
+#[derive(Clone, Copy)]
+pub struct ModulationBinding {
+    /// Index into the levels of `Modulation`.
+    pub source: usize,
+    pub target: ModulationTarget,
+    /// Change of the target at full level.
+    pub amount: f32,
+    // Offset currently added to the target, taken back before the next one is added
+    applied: f32,
+}
+
+impl ModulationBinding {
+    pub fn new(source: usize, target: ModulationTarget, amount: f32) -> Self {
+        Self {
+            source,
+            target,
+            amount,
+            applied: 0.0,
+        }
+    }
+
+    /// Take back the offset added to the target, e.g. before changing the target or removing the
+    /// binding.
+    pub fn revert(&mut self, draw_props: &mut DrawProperties, world: &mut World) {
+        self.target.add(-self.applied, draw_props, world);
+        self.applied = 0.0;
+    }
+}
+
+/// Scene parameters driven by levels of external sources, e.g. frequency bands of audio input or
+/// values of a controller, set by the application or by host applications from the update
+/// callback.
+///
+/// Bindings add an offset to their target on top of the value set from the GUI, replacing the
+/// offset of the previous update, so the base value can still be adjusted while modulated. Rate
+/// targets, like rotation speed, accumulate instead.
+#[derive(Default)]
+pub struct Modulation {
+    pub bindings: Vec<ModulationBinding>,
+    // Latest level of each source from 0 to 1
+    levels: Vec<f32>,
+}
+
+impl Modulation {
+    pub fn levels(&self) -> &[f32] {
+        &self.levels
+    }
+
+    /// Replace the levels of every source, clamped between 0 and 1. Levels beyond
+    /// `MAX_MODULATION_SOURCES` are ignored.
+    pub fn set_levels(&mut self, levels: &[f32]) {
+        self.levels.clear();
+        self.levels.extend(
+            levels
+                .iter()
+                .take(MAX_MODULATION_SOURCES)
+                .map(|level| level.clamp(0.0, 1.0)),
+        );
+    }
+
+    /// Set the level of a single source, clamped between 0 and 1. Sources up to the index without
+    /// a level count as silent. Fails for indices from `MAX_MODULATION_SOURCES` and non-finite
+    /// levels.
+    pub fn set_level(&mut self, index: usize, level: f32) -> Result<(), String> {
+        if MAX_MODULATION_SOURCES <= index {
+            return Err(format!(
+                "modulation source {index} is out of range, at most {MAX_MODULATION_SOURCES} \
+                 sources are supported"
+            ));
+        }
+        if !level.is_finite() {
+            return Err(format!("level of modulation source {index} is not finite"));
+        }
+        if self.levels.len() <= index {
+            self.levels.resize(index + 1, 0.0);
+        }
+        self.levels[index] = level.clamp(0.0, 1.0);
+        Ok(())
+    }
+
+    /// Remove the binding at the index after taking back its offset.
+    pub fn remove_binding(
+        &mut self,
+        index: usize,
+        draw_props: &mut DrawProperties,
+        world: &mut World,
+    ) {
+        let mut binding = self.bindings.remove(index);
+        binding.revert(draw_props, world);
+    }
+
+    /// Drive targets of the bindings by the current levels for a logic update of `delta_time`
+    /// seconds. Sources without a level count as silent.
+    pub fn update(&mut self, delta_time: f32, draw_props: &mut DrawProperties, world: &mut World) {
+        for binding in &mut self.bindings {
+            let level = self.levels.get(binding.source).copied().unwrap_or(0.0);
+            let value = binding.amount * level;
+            match binding.target {
+                ModulationTarget::RotationSpeed => {
+                    binding.target.add(value * delta_time, draw_props, world);
+                }
+                _ => {
+                    binding
+                        .target
+                        .add(value - binding.applied, draw_props, world);
+                    binding.applied = value;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_level_rejects_sources_out_of_range() {
+        let mut modulation = Modulation::default();
+        assert!(modulation.set_level(usize::MAX, 1.0).is_err());
+        assert!(modulation.set_level(MAX_MODULATION_SOURCES, 1.0).is_err());
+        assert!(modulation.set_level(0, f32::NAN).is_err());
+        assert!(modulation.levels().is_empty());
+
+        modulation.set_level(2, 2.0).unwrap();
+        assert_eq!(modulation.levels(), [0.0, 0.0, 1.0]);
+
+        modulation.set_levels(&[0.5; MAX_MODULATION_SOURCES + 1]);
+        assert_eq!(modulation.levels().len(), MAX_MODULATION_SOURCES);
+    }
+}
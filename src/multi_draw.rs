@@ -0,0 +1,191 @@
+use std::{
+    ffi::{c_void, CStr},
+    sync::Arc,
+};
+
+use glow::{Buffer, HasContext, VertexArray};
+
+use crate::{gl_resources, gl_state};
+
+/// Location of the per-draw index attribute of the `MULTI_DRAW_INDIRECT` model shader variant.
+const DRAW_INDEX_LOCATION: u32 = 5;
+/// Binding point of the storage buffer holding `DrawData` of each draw.
+const DRAW_DATA_BINDING: u32 = 0;
+
+/// `glMultiDrawElementsIndirect()` is core since OpenGL 4.3, but not exposed by glow.
+type MultiDrawElementsIndirectFn = unsafe extern "system" fn(
+    mode: u32,
+    kind: u32,
+    indirect: *const c_void,
+    count: i32,
+    stride: i32,
+);
+
+/// Layout of `DrawElementsIndirectCommand` read by the GPU from the command buffer.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct DrawCommand {
+    count: u32,
+    instance_count: u32,
+    first_index: u32,
+    base_vertex: i32,
+    // Single instance of each draw, the instanced draw index attribute starts from this
+    base_instance: u32,
+}
+
+/// Transformations and material of a single draw, matching the `Draw` struct of the model vertex
+/// shader in std430 layout. Matrices are column-major.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct DrawData {
+    pub model: [[f32; 4]; 4],
+    pub mvp: [[f32; 4]; 4],
+    // Upper-left 3x3 is the normal matrix, mat3 columns would be padded to vec4 anyway
+    pub normal_matrix: [[f32; 4]; 4],
+    pub color_and_refractive_index: [f32; 4],
+}
+
+/// Draws of meshes sharing the same vertex and index buffers, issued by a single
+/// `glMultiDrawElementsIndirect()` call from a command buffer on GPU. Uniforms can't change
+/// between these draws, so the shader reads them from a storage buffer indexed by the draw.
+pub struct MultiDrawIndirect {
+    gl: Arc<glow::Context>,
+    multi_draw_elements_indirect: MultiDrawElementsIndirectFn,
+    command_buffer: Buffer,
+    draw_data_buffer: Buffer,
+    // Consecutive indices read as instanced attribute, so each draw finds its own data
+    draw_index_buffer: Buffer,
+    draw_index_count: usize,
+    commands: Vec<DrawCommand>,
+    draws: Vec<DrawData>,
+}
+
+impl MultiDrawIndirect {
+    /// Entry point is looked up with the loader function of the context, e.g.
+    /// `glutin::display::GlDisplay::get_proc_address()`. Requires OpenGL 4.3.
+    pub fn new(
+        gl: Arc<glow::Context>,
+        get_proc_address: impl Fn(&CStr) -> *const c_void,
+    ) -> Result<Self, String> {
+        let pointer = get_proc_address(c"glMultiDrawElementsIndirect");
+        if pointer.is_null() {
+            return Err("glMultiDrawElementsIndirect is not available".to_string());
+        }
+        let multi_draw_elements_indirect =
+            unsafe { std::mem::transmute::<*const c_void, MultiDrawElementsIndirectFn>(pointer) };
+
+        unsafe {
+            let command_buffer = gl_resources::create_buffer(&gl)
+                .map_err(|e| format!("cannot create draw command buffer: {e}"))?;
+            let draw_data_buffer = gl_resources::create_buffer(&gl)
+                .map_err(|e| format!("cannot create draw data buffer: {e}"))?;
+            let draw_index_buffer = gl_resources::create_buffer(&gl)
+                .map_err(|e| format!("cannot create draw index buffer: {e}"))?;
+
+            Ok(Self {
+                gl,
+                multi_draw_elements_indirect,
+                command_buffer,
+                draw_data_buffer,
+                draw_index_buffer,
+                draw_index_count: 0,
+                commands: Vec::new(),
+                draws: Vec::new(),
+            })
+        }
+    }
+
+    /// Queue draw of `index_count` indices starting from `first_index`, added to `base_vertex`.
+    pub fn push(
+        &mut self,
+        index_count: usize,
+        first_index: usize,
+        base_vertex: i32,
+        draw_data: DrawData,
+    ) {
+        self.commands.push(DrawCommand {
+            count: index_count as u32,
+            instance_count: 1,
+            first_index: first_index as u32,
+            base_vertex,
+            base_instance: self.draws.len() as u32,
+        });
+        self.draws.push(draw_data);
+    }
+
+    /// Issue queued draws from the shared buffers of the vertex array with a single call, then
+    /// clear the queue. Shader variant reading `DrawData` must be in use.
+    pub unsafe fn submit(&mut self, vertex_array: VertexArray, index_type: u32) {
+        if self.commands.is_empty() {
+            return;
+        }
+        let gl = &self.gl;
+
+        // Contents are replaced by every submission, orphaning avoids waiting for previous draws
+        gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, Some(self.command_buffer));
+        gl_resources::buffer_data_u8_slice(
+            gl,
+            self.command_buffer,
+            glow::DRAW_INDIRECT_BUFFER,
+            self.commands.align_to::<u8>().1,
+            glow::STREAM_DRAW,
+        );
+        gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, Some(self.draw_data_buffer));
+        gl_resources::buffer_data_u8_slice(
+            gl,
+            self.draw_data_buffer,
+            glow::SHADER_STORAGE_BUFFER,
+            self.draws.align_to::<u8>().1,
+            glow::STREAM_DRAW,
+        );
+        gl.bind_buffer_base(
+            glow::SHADER_STORAGE_BUFFER,
+            DRAW_DATA_BINDING,
+            Some(self.draw_data_buffer),
+        );
+
+        gl_state::bind_vertex_array(gl, Some(vertex_array));
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.draw_index_buffer));
+        if self.draw_index_count < self.commands.len() {
+            self.draw_index_count = self.commands.len().next_power_of_two();
+            let draw_indices: Vec<u32> = (0..self.draw_index_count as u32).collect();
+            gl_resources::buffer_data_u8_slice(
+                gl,
+                self.draw_index_buffer,
+                glow::ARRAY_BUFFER,
+                draw_indices.align_to::<u8>().1,
+                glow::STATIC_DRAW,
+            );
+        }
+        gl.enable_vertex_attrib_array(DRAW_INDEX_LOCATION);
+        gl.vertex_attrib_pointer_i32(DRAW_INDEX_LOCATION, 1, glow::UNSIGNED_INT, 0, 0);
+        gl.vertex_attrib_divisor(DRAW_INDEX_LOCATION, 1);
+        gl.bind_buffer(glow::ARRAY_BUFFER, None);
+
+        (self.multi_draw_elements_indirect)(
+            glow::TRIANGLES,
+            index_type,
+            std::ptr::null(),
+            self.commands.len() as i32,
+            0,
+        );
+
+        // Vertex array is shared with draws not reading the attribute
+        gl.disable_vertex_attrib_array(DRAW_INDEX_LOCATION);
+        gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, None);
+        gl.bind_buffer_base(glow::SHADER_STORAGE_BUFFER, DRAW_DATA_BINDING, None);
+        gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, None);
+        self.commands.clear();
+        self.draws.clear();
+    }
+}
+
+impl Drop for MultiDrawIndirect {
+    fn drop(&mut self) {
+        unsafe {
+            gl_resources::delete_buffer(&self.gl, self.command_buffer);
+            gl_resources::delete_buffer(&self.gl, self.draw_data_buffer);
+            gl_resources::delete_buffer(&self.gl, self.draw_index_buffer);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    cell::RefCell,
+    collections::{hash_map::Entry, HashMap, HashSet},
+    sync::Arc,
+};
+
+use cgmath::{Matrix4, Point3, Vector3};
+use glow::{HasContext, Query, VertexArray};
+use hecs::Entity;
+
+use crate::{
+    assets::shader::ShaderSources, gl_resources, gl_state, model::Aabb, shader::Shader,
+    streaming_buffer::StreamingBuffer,
+};
+
+/// Bounding boxes are enlarged by this distance when checking if the viewer is inside them. Near
+/// clipping plane would cut off the front faces of a box right in front of the viewer.
+const NEAR_MARGIN: f32 = 0.2;
+/// Two triangles for each of the 6 faces of a box.
+const BOX_VERTEX_COUNT: usize = 36;
+
+/// Occlusion query of a single entity.
+struct EntityQuery {
+    query: Query,
+    /// Result is not yet read back from the GPU.
+    pending: bool,
+    /// No sample of the bounding box passed the depth test in the last finished query.
+    occluded: bool,
+}
+
+/// Results of occlusion queries used for the last frame.
+#[derive(Clone, Copy, Default)]
+pub struct OcclusionStats {
+    /// Number of entities tested with occlusion queries.
+    pub queried_count: usize,
+    /// Number of entities skipped, because they are hidden behind others.
+    pub occluded_count: usize,
+    /// Number of triangles of the skipped entities.
+    pub culled_triangle_count: usize,
+}
+
+/// Skips drawing entities hidden behind others, based on hardware occlusion queries of their
+/// bounding boxes.
+///
+/// Bounding boxes are tested against the depth buffer of the drawn scene and the results are used
+/// when drawing the next frame, so the CPU never waits for the GPU. Entities coming into view can
+/// appear one frame late.
+pub struct OcclusionCulling {
+    gl: Arc<glow::Context>,
+    // Color output of the line shader is masked, proxy boxes only take part in depth testing
+    shader: Shader,
+    vertex_array: VertexArray,
+    // Rewritten on every frame, because boxes follow changing entity transforms
+    vertex_buffer: RefCell<StreamingBuffer>,
+    // Queries are issued while the renderer is borrowed for drawing the scene
+    queries: RefCell<HashMap<Entity, EntityQuery>>,
+}
+
+impl OcclusionCulling {
+    pub fn new(gl: Arc<glow::Context>, shaders: &ShaderSources) -> Result<Self, String> {
+        let shader = Shader::new(gl.clone(), shaders.line_vertex, shaders.line_fragment)
+            .map_err(|e| format!("occlusion proxy shader creation failed: {:?}", e))?;
+
+        let vertex_buffer =
+            StreamingBuffer::new(gl.clone(), glow::ARRAY_BUFFER, size_of::<[f32; 3]>())
+                .map_err(|e| format!("cannot create occlusion proxy vertex buffer: {e}"))?;
+        unsafe {
+            let vertex_array = gl_resources::create_vertex_array(&gl)
+                .map_err(|e| format!("cannot create occlusion proxy vertex array: {e}"))?;
+
+            Ok(Self {
+                gl,
+                shader,
+                vertex_array,
+                vertex_buffer: RefCell::new(vertex_buffer),
+                queries: RefCell::new(HashMap::new()),
+            })
+        }
+    }
+
+    /// Entity was hidden when its bounding box was last tested.
+    pub fn is_occluded(&self, entity: Entity) -> bool {
+        self.queries
+            .borrow()
+            .get(&entity)
+            .is_some_and(|entity_query| entity_query.occluded)
+    }
+
+    /// Number of entities with occlusion queries.
+    pub fn queried_count(&self) -> usize {
+        self.queries.borrow().len()
+    }
+
+    /// Read back results of finished queries without waiting for unfinished ones.
+    pub fn update_results(&self) {
+        for entity_query in self.queries.borrow_mut().values_mut() {
+            if !entity_query.pending {
+                continue;
+            }
+            unsafe {
+                let available = self
+                    .gl
+                    .get_query_parameter_u32(entity_query.query, glow::QUERY_RESULT_AVAILABLE);
+                if available != 0 {
+                    let samples_passed = self
+                        .gl
+                        .get_query_parameter_u32(entity_query.query, glow::QUERY_RESULT);
+                    entity_query.occluded = samples_passed == 0;
+                    entity_query.pending = false;
+                }
+            }
+        }
+    }
+
+    /// Test world space bounding boxes of entities against the depth buffer of the currently
+    /// bound framebuffer. Queries of entities missing from the list are deleted.
+    pub fn issue_queries(
+        &self,
+        bounding_boxes: &[(Entity, Aabb)],
+        projection_view: &Matrix4<f32>,
+        eye: Point3<f32>,
+    ) {
+        let mut queries = self.queries.borrow_mut();
+        let tested_entities: HashSet<Entity> =
+            bounding_boxes.iter().map(|(entity, _)| *entity).collect();
+        queries.retain(|entity, entity_query| {
+            let alive = tested_entities.contains(entity);
+            if !alive {
+                unsafe {
+                    self.gl.delete_query(entity_query.query);
+                }
+            }
+            alive
+        });
+
+        let vertices: Vec<[f32; 3]> = bounding_boxes
+            .iter()
+            .flat_map(|(_, aabb)| box_triangles(aabb))
+            .collect();
+        if vertices.is_empty() {
+            return;
+        }
+
+        unsafe {
+            self.shader.r#use();
+            self.shader.set_uniform("u_projectionView", projection_view);
+            gl_state::bind_vertex_array(&self.gl, Some(self.vertex_array));
+            let first = match self.vertex_buffer.borrow_mut().write(&vertices) {
+                Ok(first) => first,
+                Err(e) => {
+                    eprintln!("unable to upload occlusion proxies: {e}");
+                    gl_state::bind_vertex_array(&self.gl, None);
+                    return;
+                }
+            };
+            // Buffer is replaced when it grows
+            self.gl.enable_vertex_attrib_array(0);
+            self.gl
+                .vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, 0, 0);
+
+            // Proxies must not change the scene
+            self.gl.color_mask(false, false, false, false);
+            self.gl.depth_mask(false);
+            gl_state::enable(&self.gl, glow::DEPTH_TEST);
+
+            for (index, (entity, aabb)) in bounding_boxes.iter().enumerate() {
+                // Faces of the box around the viewer are behind it or clipped, nothing would pass
+                if contains_with_margin(aabb, eye) {
+                    if let Some(entity_query) = queries.get_mut(entity) {
+                        entity_query.occluded = false;
+                    }
+                    continue;
+                }
+
+                let entity_query = match queries.entry(*entity) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let Ok(query) = self.gl.create_query() else {
+                            continue;
+                        };
+                        entry.insert(EntityQuery {
+                            query,
+                            pending: false,
+                            occluded: false,
+                        })
+                    }
+                };
+                // Previous result is still on its way, query can't be reused until then
+                if entity_query.pending {
+                    continue;
+                }
+
+                self.gl
+                    .begin_query(glow::ANY_SAMPLES_PASSED, entity_query.query);
+                self.gl.draw_arrays(
+                    glow::TRIANGLES,
+                    (first + index * BOX_VERTEX_COUNT) as i32,
+                    BOX_VERTEX_COUNT as i32,
+                );
+                self.gl.end_query(glow::ANY_SAMPLES_PASSED);
+                entity_query.pending = true;
+            }
+
+            // Reset state
+            self.gl.color_mask(true, true, true, true);
+            self.gl.depth_mask(true);
+            self.gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            gl_state::bind_vertex_array(&self.gl, None);
+        }
+    }
+
+    /// Delete every query, e.g. when occlusion culling is disabled. Stale results would hide
+    /// entities when it is enabled again.
+    pub fn clear(&self) {
+        for (_, entity_query) in self.queries.borrow_mut().drain() {
+            unsafe {
+                self.gl.delete_query(entity_query.query);
+            }
+        }
+    }
+}
+
+impl Drop for OcclusionCulling {
+    fn drop(&mut self) {
+        self.clear();
+        unsafe {
+            gl_resources::delete_vertex_array(&self.gl, self.vertex_array);
+        }
+    }
+}
+
+fn contains_with_margin(aabb: &Aabb, point: Point3<f32>) -> bool {
+    let margin = Vector3::new(NEAR_MARGIN, NEAR_MARGIN, NEAR_MARGIN);
+    let (min, max) = (aabb.min - margin, aabb.max + margin);
+    (min.x..=max.x).contains(&point.x)
+        && (min.y..=max.y).contains(&point.y)
+        && (min.z..=max.z).contains(&point.z)
+}
+
+/// Triangles of the faces of a box. Winding doesn't matter, face culling is disabled.
+fn box_triangles(aabb: &Aabb) -> [[f32; 3]; BOX_VERTEX_COUNT] {
+    let corners = aabb.corners();
+    // Corner indices are bit masks of the maximum X, Y and Z coordinates
+    const FACES: [[usize; 4]; 6] = [
+        [0, 2, 6, 4],
+        [1, 3, 7, 5],
+        [0, 1, 5, 4],
+        [2, 3, 7, 6],
+        [0, 1, 3, 2],
+        [4, 5, 7, 6],
+    ];
+    let mut vertices = [[0.0; 3]; BOX_VERTEX_COUNT];
+    for (face_index, face) in FACES.iter().enumerate() {
+        for (i, corner) in [face[0], face[1], face[2], face[0], face[2], face[3]]
+            .into_iter()
+            .enumerate()
+        {
+            vertices[face_index * 6 + i] = corners[corner].into();
+        }
+    }
+    vertices
+}
@@ -0,0 +1,189 @@
+use std::net::{SocketAddr, UdpSocket};
+
+use crate::remote::RemoteCommand;
+
+/// Largest datagram read from the socket. Control surfaces send far smaller packets.
+const MAX_PACKET_SIZE: usize = 8192;
+
+/// Receives Open Sound Control (OSC) messages over UDP, e.g. from TouchOSC or lighting desks, and
+/// translates them into remote commands.
+///
+/// Addresses and their arguments:
+/// - `/property/<name> <values>`: field of `DrawProperties`, see `DrawProperties::set_by_name()`
+/// - `/camera/position <x> <y> <z>` and `/camera/rotation <yaw> <pitch> <roll>`
+/// - `/camera/bookmark/<index>`: fly to the bookmark, ignored with a zero argument so buttons
+///   trigger only when pressed
+/// - `/modulation/<index> <level>`: level of a modulation source from 0 to 1
+pub struct OscListener {
+    socket: UdpSocket,
+    buffer: Vec<u8>,
+}
+
+impl OscListener {
+    /// Listen on the UDP address. Senders are not authenticated, so addresses of other network
+    /// interfaces than loopback should only be used on trusted networks.
+    pub fn new(address: SocketAddr) -> Result<Self, String> {
+        let socket = UdpSocket::bind(address)
+            .map_err(|e| format!("cannot listen for OSC on {address}: {e}"))?;
+        socket
+            .set_nonblocking(true)
+            .map_err(|e| format!("cannot listen for OSC on {address}: {e}"))?;
+        Ok(Self {
+            socket,
+            buffer: vec![0; MAX_PACKET_SIZE],
+        })
+    }
+
+    /// Commands of every message received since the last poll. Malformed packets and unknown
+    /// addresses are reported in the error list instead of stopping the others.
+    pub fn poll(&mut self) -> (Vec<RemoteCommand>, Vec<String>) {
+        let mut messages = Vec::new();
+        let mut errors = Vec::new();
+        loop {
+            match self.socket.recv(&mut self.buffer) {
+                Ok(size) => {
+                    if let Err(e) = parse_packet(&self.buffer[..size], &mut messages) {
+                        errors.push(format!("invalid OSC packet: {e}"));
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    errors.push(format!("cannot receive OSC packet: {e}"));
+                    break;
+                }
+            }
+        }
+        let mut commands = Vec::with_capacity(messages.len());
+        for message in messages {
+            match message.command() {
+                Ok(Some(command)) => commands.push(command),
+                Ok(None) => (),
+                Err(e) => errors.push(e),
+            }
+        }
+        (commands, errors)
+    }
+}
+
+/// Single OSC message with the arguments this listener understands.
+struct OscMessage {
+    address: String,
+    arguments: Vec<f32>,
+}
+
+impl OscMessage {
+    /// Remote command requested by the message, `None` for messages that only release a button.
+    fn command(&self) -> Result<Option<RemoteCommand>, String> {
+        let address = &self.address;
+        let vector = || -> Result<[f32; 3], String> {
+            <[f32; 3]>::try_from(self.arguments.as_slice())
+                .map_err(|_| format!("OSC address {address} requires 3 arguments"))
+        };
+        let index = |text: &str| {
+            text.parse::<usize>()
+                .map_err(|_| format!("invalid index in OSC address {address}"))
+        };
+        let parts: Vec<&str> = address.trim_start_matches('/').split('/').collect();
+        let command = match parts.as_slice() {
+            ["property", name] => RemoteCommand::SetProperty {
+                name: name.to_string(),
+                values: self.arguments.clone(),
+            },
+            ["camera", "position"] => RemoteCommand::MoveCamera {
+                position: Some(vector()?),
+                rotation: None,
+            },
+            ["camera", "rotation"] => RemoteCommand::MoveCamera {
+                position: None,
+                rotation: Some(vector()?),
+            },
+            ["camera", "bookmark", bookmark] => {
+                if self.arguments.first().is_some_and(|&value| value == 0.0) {
+                    return Ok(None);
+                }
+                RemoteCommand::GoToCameraBookmark {
+                    index: index(bookmark)?,
+                }
+            }
+            ["modulation", source] => {
+                let &[level] = self.arguments.as_slice() else {
+                    return Err(format!("OSC address {address} requires 1 argument"));
+                };
+                RemoteCommand::SetModulationLevel {
+                    index: index(source)?,
+                    level,
+                }
+            }
+            _ => return Err(format!("unknown OSC address {address}")),
+        };
+        Ok(Some(command))
+    }
+}
+
+/// Collect messages of a packet, which is either a message or a bundle of packets.
+fn parse_packet(mut data: &[u8], messages: &mut Vec<OscMessage>) -> Result<(), String> {
+    if data.starts_with(b"#bundle\0") {
+        // Time tag is ignored, bundled messages are carried out immediately
+        data = data.get(16..).ok_or("truncated bundle")?;
+        while !data.is_empty() {
+            let size = i32::from_be_bytes(read_word(&mut data)?).max(0) as usize;
+            let element = data.get(..size).ok_or("truncated bundle element")?;
+            parse_packet(element, messages)?;
+            data = &data[size..];
+        }
+        return Ok(());
+    }
+
+    let address = read_string(&mut data)?;
+    if !address.starts_with('/') {
+        return Err(format!("invalid address {address}"));
+    }
+    // Type tags may be missing in messages of old implementations, meaning no arguments
+    let type_tags = if data.is_empty() {
+        String::new()
+    } else {
+        read_string(&mut data)?
+    };
+    let mut arguments = Vec::new();
+    for tag in type_tags.chars().skip_while(|&tag| tag == ',') {
+        let argument = match tag {
+            'f' => f32::from_be_bytes(read_word(&mut data)?),
+            'i' => i32::from_be_bytes(read_word(&mut data)?) as f32,
+            'd' => {
+                let high = read_word(&mut data)?;
+                let low = read_word(&mut data)?;
+                let mut bytes = [0; 8];
+                bytes[..4].copy_from_slice(&high);
+                bytes[4..].copy_from_slice(&low);
+                f64::from_be_bytes(bytes) as f32
+            }
+            'T' => 1.0,
+            'F' => 0.0,
+            _ => return Err(format!("unsupported argument type '{tag}' of {address}")),
+        };
+        arguments.push(argument);
+    }
+    messages.push(OscMessage { address, arguments });
+    Ok(())
+}
+
+/// Read a string terminated by zero and padded to a multiple of 4 bytes.
+fn read_string(data: &mut &[u8]) -> Result<String, String> {
+    let length = data
+        .iter()
+        .position(|&byte| byte == 0)
+        .ok_or("unterminated string")?;
+    let string = std::str::from_utf8(&data[..length])
+        .map_err(|e| format!("invalid string: {e}"))?
+        .to_string();
+    let padded_length = (length + 4) & !3;
+    *data = data.get(padded_length..).ok_or("truncated string")?;
+    Ok(string)
+}
+
+/// Read 4 bytes of a big-endian number.
+fn read_word(data: &mut &[u8]) -> Result<[u8; 4], String> {
+    let (word, rest) = data.split_first_chunk::<4>().ok_or("truncated argument")?;
+    *data = rest;
+    Ok(*word)
+}
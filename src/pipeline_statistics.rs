@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use glow::{HasContext, Query};
+
+/// Frames whose queries can be in flight at once. Results usually arrive a frame or two later.
+const FRAMES_IN_FLIGHT: usize = 3;
+
+/// Work done by the programmable pipeline stages for the last measured frame.
+#[derive(Clone, Copy, Default)]
+pub struct PipelineStats {
+    /// Vertices fetched by draw calls, counted again for every draw and index referring to them.
+    pub vertices_submitted: usize,
+    /// Fragment shader invocations, including fragments later hidden by others (overdraw).
+    pub fragments_shaded: usize,
+}
+
+/// Pair of queries counting a single frame.
+struct FrameQueries {
+    vertices_submitted: Query,
+    fragments_shaded: Query,
+    /// Result is not yet read back from the GPU.
+    pending: bool,
+}
+
+/// Counts vertices and fragments processed by the GPU while drawing frames, with queries of
+/// `ARB_pipeline_statistics_query` (core since OpenGL 4.6).
+///
+/// Like occlusion queries, results are read back frames later so the CPU never waits for the GPU.
+pub struct PipelineStatistics {
+    gl: Arc<glow::Context>,
+    frames: Vec<FrameQueries>,
+    // Index of the frame recorded next, also the oldest one in flight
+    next_frame: usize,
+    // Queries of the current frame are running
+    recording: bool,
+    latest: Option<PipelineStats>,
+}
+
+impl PipelineStatistics {
+    pub fn new(gl: Arc<glow::Context>) -> Result<Self, String> {
+        let mut frames = Vec::with_capacity(FRAMES_IN_FLIGHT);
+        unsafe {
+            for _ in 0..FRAMES_IN_FLIGHT {
+                let vertices_submitted = gl
+                    .create_query()
+                    .map_err(|e| format!("cannot create pipeline statistics query: {e}"))?;
+                let fragments_shaded = gl
+                    .create_query()
+                    .map_err(|e| format!("cannot create pipeline statistics query: {e}"))?;
+                frames.push(FrameQueries {
+                    vertices_submitted,
+                    fragments_shaded,
+                    pending: false,
+                });
+            }
+        }
+        Ok(Self {
+            gl,
+            frames,
+            next_frame: 0,
+            recording: false,
+            latest: None,
+        })
+    }
+
+    /// Statistics of the most recent frame with results available, `None` until the first one
+    /// arrives.
+    pub fn latest(&self) -> Option<PipelineStats> {
+        self.latest
+    }
+
+    /// Start counting the frame. Skipped if every query is still waiting for results.
+    pub fn begin_frame(&mut self) {
+        self.update_results();
+        let frame = &self.frames[self.next_frame];
+        if frame.pending {
+            return;
+        }
+        unsafe {
+            self.gl
+                .begin_query(glow::VERTICES_SUBMITTED, frame.vertices_submitted);
+            self.gl
+                .begin_query(glow::FRAGMENT_SHADER_INVOCATIONS, frame.fragments_shaded);
+        }
+        self.recording = true;
+    }
+
+    /// Stop counting the frame started by `begin_frame()`.
+    pub fn end_frame(&mut self) {
+        if !self.recording {
+            return;
+        }
+        unsafe {
+            self.gl.end_query(glow::VERTICES_SUBMITTED);
+            self.gl.end_query(glow::FRAGMENT_SHADER_INVOCATIONS);
+        }
+        self.frames[self.next_frame].pending = true;
+        self.next_frame = (self.next_frame + 1) % FRAMES_IN_FLIGHT;
+        self.recording = false;
+    }
+
+    /// Read back results of finished frames from the oldest to the newest, without waiting for
+    /// unfinished ones.
+    fn update_results(&mut self) {
+        for offset in 0..FRAMES_IN_FLIGHT {
+            let frame = &mut self.frames[(self.next_frame + offset) % FRAMES_IN_FLIGHT];
+            if !frame.pending {
+                continue;
+            }
+            unsafe {
+                let available = [frame.vertices_submitted, frame.fragments_shaded]
+                    .into_iter()
+                    .all(|query| {
+                        self.gl
+                            .get_query_parameter_u32(query, glow::QUERY_RESULT_AVAILABLE)
+                            != 0
+                    });
+                if !available {
+                    continue;
+                }
+                self.latest = Some(PipelineStats {
+                    vertices_submitted: self
+                        .gl
+                        .get_query_parameter_u32(frame.vertices_submitted, glow::QUERY_RESULT)
+                        as usize,
+                    fragments_shaded: self
+                        .gl
+                        .get_query_parameter_u32(frame.fragments_shaded, glow::QUERY_RESULT)
+                        as usize,
+                });
+            }
+            frame.pending = false;
+        }
+    }
+}
+
+impl Drop for PipelineStatistics {
+    fn drop(&mut self) {
+        for frame in &self.frames {
+            unsafe {
+                self.gl.delete_query(frame.vertices_submitted);
+                self.gl.delete_query(frame.fragments_shaded);
+            }
+        }
+    }
+}
@@ -0,0 +1,482 @@
+use cgmath::{vec3, vec4, Vector3, Zero};
+
+use crate::model::{srgb_to_linear, MeshData, Vertex};
+
+/// Encoding of the element data following the header.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "char" | "int8" => Ok(Self::Int8),
+            "uchar" | "uint8" => Ok(Self::UInt8),
+            "short" | "int16" => Ok(Self::Int16),
+            "ushort" | "uint16" => Ok(Self::UInt16),
+            "int" | "int32" => Ok(Self::Int32),
+            "uint" | "uint32" => Ok(Self::UInt32),
+            "float" | "float32" => Ok(Self::Float32),
+            "double" | "float64" => Ok(Self::Float64),
+            _ => Err(format!("unknown property type {name}")),
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Self::Int8 | Self::UInt8 => 1,
+            Self::Int16 | Self::UInt16 => 2,
+            Self::Int32 | Self::UInt32 | Self::Float32 => 4,
+            Self::Float64 => 8,
+        }
+    }
+
+    /// Largest value of unsigned integer types, to which color components are normalized.
+    fn color_scale(self) -> f64 {
+        match self {
+            Self::UInt8 => u8::MAX as f64,
+            Self::UInt16 => u16::MAX as f64,
+            Self::UInt32 => u32::MAX as f64,
+            _ => 1.0,
+        }
+    }
+}
+
+enum Property {
+    Scalar(String, ScalarType),
+    /// Name, type of the item count and type of the items.
+    List(String, ScalarType, ScalarType),
+}
+
+impl Property {
+    fn name(&self) -> &str {
+        match self {
+            Property::Scalar(name, _) | Property::List(name, _, _) => name,
+        }
+    }
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    /// Index and type of a scalar property.
+    fn scalar(&self, name: &str) -> Option<(usize, ScalarType)> {
+        self.properties
+            .iter()
+            .enumerate()
+            .find_map(|(index, property)| match property {
+                Property::Scalar(property_name, scalar_type) if property_name == name => {
+                    Some((index, *scalar_type))
+                }
+                _ => None,
+            })
+    }
+}
+
+/// Values of element data, read one by one in the order of the header.
+enum Body<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary { data: &'a [u8], big_endian: bool },
+}
+
+impl Body<'_> {
+    fn read(&mut self, scalar_type: ScalarType) -> Result<f64, String> {
+        match self {
+            Body::Ascii(tokens) => {
+                let token = tokens.next().ok_or("unexpected end of data")?;
+                token
+                    .parse()
+                    .map_err(|e| format!("invalid value {token}: {e}"))
+            }
+            Body::Binary { data, big_endian } => {
+                let size = scalar_type.size();
+                if data.len() < size {
+                    return Err("unexpected end of data".to_owned());
+                }
+                let (value, rest) = data.split_at(size);
+                *data = rest;
+                // Converted to little endian to decode all types the same way
+                let mut bytes = [0; 8];
+                bytes[..size].copy_from_slice(value);
+                if *big_endian {
+                    bytes[..size].reverse();
+                }
+                Ok(match scalar_type {
+                    ScalarType::Int8 => bytes[0] as i8 as f64,
+                    ScalarType::UInt8 => bytes[0] as f64,
+                    ScalarType::Int16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+                    ScalarType::UInt16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+                    ScalarType::Int32 => i32::from_le_bytes(bytes[..4].try_into().unwrap()) as f64,
+                    ScalarType::UInt32 => u32::from_le_bytes(bytes[..4].try_into().unwrap()) as f64,
+                    ScalarType::Float32 => {
+                        f32::from_le_bytes(bytes[..4].try_into().unwrap()) as f64
+                    }
+                    ScalarType::Float64 => f64::from_le_bytes(bytes),
+                })
+            }
+        }
+    }
+
+    /// Values of every property of an element. List properties are returned separately in the
+    /// order they appear.
+    fn read_element(&mut self, element: &Element) -> Result<(Vec<f64>, Vec<Vec<f64>>), String> {
+        let mut scalars = Vec::with_capacity(element.properties.len());
+        let mut lists = Vec::new();
+        for property in &element.properties {
+            match property {
+                Property::Scalar(_, scalar_type) => scalars.push(self.read(*scalar_type)?),
+                Property::List(_, count_type, item_type) => {
+                    let count = self.read(*count_type)? as usize;
+                    let items = (0..count)
+                        .map(|_| self.read(*item_type))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    // Keeps scalar indices of the element aligned with its properties
+                    scalars.push(0.0);
+                    lists.push(items);
+                }
+            }
+        }
+        Ok((scalars, lists))
+    }
+}
+
+/// Parse a triangle mesh from PLY (Polygon File Format) data, as written by many 3D scanners.
+///
+/// ASCII and binary encodings are supported. Vertex normals and colors are read when present,
+/// otherwise normals are calculated from the faces. Polygons are triangulated as fans.
+pub fn parse_ply(data: &[u8]) -> Result<MeshData, String> {
+    const END_HEADER: &[u8] = b"end_header";
+    let header_end = data
+        .windows(END_HEADER.len())
+        .position(|window| window == END_HEADER)
+        .ok_or("missing end_header")?;
+    // Element data starts after the line ending of end_header
+    let body_start = data[header_end..]
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .map_or(data.len(), |position| header_end + position + 1);
+    let header =
+        std::str::from_utf8(&data[..header_end]).map_err(|e| format!("invalid header: {e}"))?;
+
+    let mut lines = header.lines().map(str::trim);
+    if lines.next() != Some("ply") {
+        return Err("not a PLY file".to_owned());
+    }
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    for line in lines {
+        let words: Vec<&str> = line.split_ascii_whitespace().collect();
+        match words.as_slice() {
+            ["format", name, _version] => {
+                format = Some(match *name {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => return Err(format!("unknown format {name}")),
+                });
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|e| format!("invalid count of element {name}: {e}"))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count_type, item_type, name] => elements
+                .last_mut()
+                .ok_or("property outside of element")?
+                .properties
+                .push(Property::List(
+                    name.to_string(),
+                    ScalarType::parse(count_type)?,
+                    ScalarType::parse(item_type)?,
+                )),
+            ["property", scalar_type, name] => elements
+                .last_mut()
+                .ok_or("property outside of element")?
+                .properties
+                .push(Property::Scalar(
+                    name.to_string(),
+                    ScalarType::parse(scalar_type)?,
+                )),
+            // Comments, object info and empty lines
+            _ => (),
+        }
+    }
+
+    let mut body = match format.ok_or("missing format")? {
+        Format::Ascii => Body::Ascii(
+            std::str::from_utf8(&data[body_start..])
+                .map_err(|e| format!("invalid ASCII data: {e}"))?
+                .split_ascii_whitespace(),
+        ),
+        Format::BinaryLittleEndian => Body::Binary {
+            data: &data[body_start..],
+            big_endian: false,
+        },
+        Format::BinaryBigEndian => Body::Binary {
+            data: &data[body_start..],
+            big_endian: true,
+        },
+    };
+
+    let mut mesh_data = MeshData::default();
+    let mut has_normals = false;
+    for element in &elements {
+        match element.name.as_str() {
+            "vertex" => {
+                let position = ["x", "y", "z"].map(|name| element.scalar(name));
+                let [Some((x, _)), Some((y, _)), Some((z, _))] = position else {
+                    return Err("vertex position is missing".to_owned());
+                };
+                let normal = match ["nx", "ny", "nz"].map(|name| element.scalar(name)) {
+                    [Some((nx, _)), Some((ny, _)), Some((nz, _))] => Some([nx, ny, nz]),
+                    _ => None,
+                };
+                has_normals = normal.is_some();
+                let color = match ["red", "green", "blue"].map(|name| element.scalar(name)) {
+                    [Some(red), Some(green), Some(blue)] => Some([red, green, blue]),
+                    _ => None,
+                };
+                let alpha = element.scalar("alpha");
+
+                mesh_data.vertices.reserve(element.count);
+                for _ in 0..element.count {
+                    let (values, _) = body.read_element(element)?;
+                    mesh_data.vertices.push(Vertex {
+                        position: vec3(values[x] as f32, values[y] as f32, values[z] as f32),
+                        normal: normal.map_or(Vector3::zero(), |[nx, ny, nz]| {
+                            vec3(values[nx] as f32, values[ny] as f32, values[nz] as f32)
+                        }),
+                    });
+                    if let Some(color) = color {
+                        let [red, green, blue] = color.map(|(index, scalar_type)| {
+                            srgb_to_linear((values[index] / scalar_type.color_scale()) as f32)
+                        });
+                        let alpha = alpha.map_or(1.0, |(index, scalar_type)| {
+                            (values[index] / scalar_type.color_scale()) as f32
+                        });
+                        mesh_data.colors.push(vec4(red, green, blue, alpha));
+                    }
+                }
+            }
+            "face" => {
+                let index_list = element
+                    .properties
+                    .iter()
+                    .filter(|property| matches!(property, Property::List(..)))
+                    .position(|property| {
+                        matches!(property.name(), "vertex_indices" | "vertex_index")
+                    })
+                    .ok_or("face vertex indices are missing")?;
+                for _ in 0..element.count {
+                    let (_, lists) = body.read_element(element)?;
+                    let polygon = &lists[index_list];
+                    for i in 1..polygon.len().saturating_sub(1) {
+                        for index in [polygon[0], polygon[i], polygon[i + 1]] {
+                            if index < 0.0 || mesh_data.vertices.len() as f64 <= index {
+                                return Err(format!("face refers to missing vertex {index}"));
+                            }
+                            mesh_data.indices.push(index as u32);
+                        }
+                    }
+                }
+            }
+            // Skipped, e.g. edges or materials
+            _ => {
+                for _ in 0..element.count {
+                    body.read_element(element)?;
+                }
+            }
+        }
+    }
+
+    if mesh_data.indices.is_empty() {
+        return Err("mesh has no faces".to_owned());
+    }
+    if !has_normals {
+        mesh_data.recalculate_normals();
+    }
+    Ok(mesh_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::assert_abs_diff_eq;
+
+    use super::*;
+
+    const ASCII_QUAD: &str = "ply
+format ascii 1.0
+comment unit square facing +Z
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+1 1 0 0 0 255
+0 1 0 255 255 255
+4 0 1 2 3
+";
+
+    /// Binary big endian triangle with normals and an edge element, which is skipped.
+    fn binary_triangle() -> Vec<u8> {
+        let mut data = b"ply
+format binary_big_endian 1.0
+element vertex 3
+property float x
+property float y
+property float z
+property float nx
+property float ny
+property float nz
+element edge 1
+property int vertex1
+property int vertex2
+element face 1
+property list uchar uint vertex_index
+end_header
+"
+        .to_vec();
+        for position in [[0.0f32, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 0.0, -2.0]] {
+            for value in position.into_iter().chain([0.0, 1.0, 0.0]) {
+                data.extend(value.to_be_bytes());
+            }
+        }
+        data.extend(0i32.to_be_bytes());
+        data.extend(1i32.to_be_bytes());
+        data.push(3);
+        for index in [0u32, 1, 2] {
+            data.extend(index.to_be_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn ascii_polygons_are_triangulated_as_fans() {
+        let mesh_data = parse_ply(ASCII_QUAD.as_bytes()).unwrap();
+        assert_eq!(mesh_data.vertices.len(), 4);
+        assert_eq!(mesh_data.indices, [0, 1, 2, 0, 2, 3]);
+        assert_abs_diff_eq!(mesh_data.vertices[2].position, vec3(1.0, 1.0, 0.0));
+        // Normals are calculated from the faces
+        for vertex in &mesh_data.vertices {
+            assert_abs_diff_eq!(vertex.normal, vec3(0.0, 0.0, 1.0), epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn ascii_colors_are_normalized() {
+        let mesh_data = parse_ply(ASCII_QUAD.as_bytes()).unwrap();
+        assert_eq!(mesh_data.colors.len(), 4);
+        assert_eq!(mesh_data.colors[0], vec4(1.0, 0.0, 0.0, 1.0));
+        assert_eq!(mesh_data.colors[2], vec4(0.0, 0.0, 1.0, 1.0));
+        assert_eq!(mesh_data.colors[3], vec4(1.0, 1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn binary_big_endian_is_parsed() {
+        let mesh_data = parse_ply(&binary_triangle()).unwrap();
+        assert_eq!(mesh_data.indices, [0, 1, 2]);
+        assert_eq!(mesh_data.vertices[1].position, vec3(2.0, 0.0, 0.0));
+        assert_eq!(mesh_data.vertices[2].position, vec3(0.0, 0.0, -2.0));
+        // Normals of the file are kept
+        assert_eq!(mesh_data.vertices[0].normal, vec3(0.0, 1.0, 0.0));
+        assert!(mesh_data.colors.is_empty());
+    }
+
+    #[test]
+    fn binary_little_endian_colors_with_alpha_are_parsed() {
+        let mut data = b"ply
+format binary_little_endian 1.0
+element vertex 3
+property double x
+property double y
+property double z
+property ushort red
+property ushort green
+property ushort blue
+property ushort alpha
+element face 1
+property list uchar ushort vertex_indices
+end_header
+"
+        .to_vec();
+        for position in [[0.0f64, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] {
+            for value in position {
+                data.extend(value.to_le_bytes());
+            }
+            for component in [u16::MAX, 0, u16::MAX, 0] {
+                data.extend(component.to_le_bytes());
+            }
+        }
+        data.push(3);
+        for index in [0u16, 1, 2] {
+            data.extend(index.to_le_bytes());
+        }
+        let mesh_data = parse_ply(&data).unwrap();
+        assert_eq!(mesh_data.indices, [0, 1, 2]);
+        assert_eq!(mesh_data.vertices[2].position, vec3(0.0, 1.0, 0.0));
+        assert_eq!(mesh_data.colors[1], vec4(1.0, 0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn point_cloud_without_faces_is_rejected() {
+        let data = "ply
+format ascii 1.0
+element vertex 2
+property float x
+property float y
+property float z
+end_header
+0 0 0
+1 0 0
+";
+        assert_eq!(
+            parse_ply(data.as_bytes()).err().as_deref(),
+            Some("mesh has no faces")
+        );
+    }
+
+    #[test]
+    fn invalid_data_is_rejected() {
+        assert_eq!(
+            parse_ply(b"solid cube\nend_header\n").err().as_deref(),
+            Some("not a PLY file")
+        );
+        assert_eq!(
+            parse_ply(&binary_triangle()[..300]).err().as_deref(),
+            Some("unexpected end of data")
+        );
+        let missing_vertex = ASCII_QUAD.replace("4 0 1 2 3", "3 0 1 7");
+        assert_eq!(
+            parse_ply(missing_vertex.as_bytes()).err().as_deref(),
+            Some("face refers to missing vertex 7")
+        );
+    }
+}
@@ -0,0 +1,112 @@
+/// Stylistic effect applied to the rendered scene before exposure and gamma adjustments.
+///
+/// Discriminant values are passed to the post effect shader and match option indices of HTML
+/// select elements.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PostEffect {
+    /// Darkening towards the corners, like light falling off in a camera lens.
+    Vignette = 0,
+    /// Animated noise imitating the grain of photographic film.
+    FilmGrain = 1,
+    /// Red and blue channels displaced outwards from the center, like a lens focusing colors
+    /// differently.
+    ChromaticAberration = 2,
+}
+
+impl PostEffect {
+    pub const ALL: [PostEffect; 3] = [
+        PostEffect::Vignette,
+        PostEffect::FilmGrain,
+        PostEffect::ChromaticAberration,
+    ];
+
+    /// Message identifier of the translated label.
+    pub(crate) fn l10n_id(self) -> &'static str {
+        match self {
+            PostEffect::Vignette => "post-effect-vignette",
+            PostEffect::FilmGrain => "post-effect-film-grain",
+            PostEffect::ChromaticAberration => "post-effect-chromatic-aberration",
+        }
+    }
+}
+
+/// Stack of post effects, each drawn as a fullscreen pass reading the output of the previous one.
+///
+/// Motion blur comes first when enabled, followed by the stylistic effects in their order.
+#[derive(Clone, Copy, PartialEq)]
+pub struct PostEffectSettings {
+    /// Blur along the movement of pixels on screen since the previous frame, caused by the
+    /// camera. Stereo output is never blurred.
+    pub motion_blur_enabled: bool,
+    /// Part of the frame time the virtual shutter is open, in degrees of a rotary shutter from 0
+    /// to 360. Films are usually shot with 180°.
+    pub shutter_angle: f32,
+    /// Effects in the order they are applied, each of them once.
+    pub order: [PostEffect; 3],
+    pub vignette_enabled: bool,
+    /// Darkening of the corners from 0 to 1.
+    pub vignette_strength: f32,
+    pub film_grain_enabled: bool,
+    /// Largest relative change of brightness by noise.
+    pub film_grain_intensity: f32,
+    pub chromatic_aberration_enabled: bool,
+    /// Displacement of red and blue channels in the corners, in pixels.
+    pub chromatic_aberration_strength: f32,
+}
+
+impl Default for PostEffectSettings {
+    fn default() -> Self {
+        Self {
+            motion_blur_enabled: false,
+            shutter_angle: 180.0,
+            order: PostEffect::ALL,
+            vignette_enabled: false,
+            vignette_strength: 0.5,
+            film_grain_enabled: false,
+            film_grain_intensity: 0.1,
+            chromatic_aberration_enabled: false,
+            chromatic_aberration_strength: 3.0,
+        }
+    }
+}
+
+impl PostEffectSettings {
+    pub fn motion_blur_active(&self) -> bool {
+        self.motion_blur_enabled && 0.0 < self.shutter_angle
+    }
+
+    pub fn enabled(&self, effect: PostEffect) -> bool {
+        match effect {
+            PostEffect::Vignette => self.vignette_enabled,
+            PostEffect::FilmGrain => self.film_grain_enabled,
+            PostEffect::ChromaticAberration => self.chromatic_aberration_enabled,
+        }
+    }
+
+    /// Enabled effects in the order they are applied.
+    pub fn active_effects(&self) -> impl Iterator<Item = PostEffect> + '_ {
+        self.order
+            .into_iter()
+            .filter(|&effect| self.enabled(effect))
+    }
+
+    /// Move the effect at the position one step earlier or later in the order. Moves past either
+    /// end are ignored.
+    pub fn move_effect(&mut self, position: usize, earlier: bool) {
+        let other = if earlier {
+            position.checked_sub(1)
+        } else {
+            Some(position + 1).filter(|&other| other < self.order.len())
+        };
+        if let Some(other) = other {
+            self.order.swap(position, other);
+        }
+    }
+
+    /// Put the effect at the position of the order, swapping places with the effect there.
+    pub fn place_effect(&mut self, position: usize, effect: PostEffect) {
+        if let Some(previous) = self.order.iter().position(|&e| e == effect) {
+            self.order.swap(previous, position);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Procedurally generated meshes for testing shading without external model files, e.g. as ground
+//! planes of scenes built in code.
+//!
+//! Shapes are centered at the origin with Y as the up axis, closed shapes are wound
+//! counter-clockwise seen from outside. Texture coordinates are generated for every shape, tangents
+//! can be added with `MeshData::calculate_tangents()`. Display them with `Model::from_mesh_data()`
+//! or `AppBuilder::with_mesh_data()`.
+
+use std::f32::consts::{PI, TAU};
+
+use cgmath::{vec2, vec3, ElementWise, Vector3};
+
+use crate::{MeshData, Vertex};
+
+/// UV sphere divided into `segments` slices around the Y axis and `rings` stacks from pole to pole.
+pub fn sphere(radius: f32, segments: u32, rings: u32) -> MeshData {
+    let segments = segments.max(3);
+    let rings = rings.max(2);
+    let mut mesh_data = MeshData::default();
+    for ring in 0..=rings {
+        let polar = PI * ring as f32 / rings as f32;
+        for segment in 0..=segments {
+            // Vertices at the same place need identical positions for the mesh to be detected as
+            // closed for back-face culling, so poles are exact and the seam angle is wrapped
+            let azimuth = TAU * (segment % segments) as f32 / segments as f32;
+            let normal = if ring == 0 || ring == rings {
+                vec3(0.0, polar.cos(), 0.0)
+            } else {
+                vec3(
+                    polar.sin() * azimuth.cos(),
+                    polar.cos(),
+                    polar.sin() * azimuth.sin(),
+                )
+            };
+            mesh_data.vertices.push(Vertex {
+                position: normal * radius,
+                normal,
+            });
+            mesh_data.uvs.push(vec2(
+                segment as f32 / segments as f32,
+                1.0 - ring as f32 / rings as f32,
+            ));
+        }
+    }
+
+    let row = segments + 1;
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let a = ring * row + segment;
+            let b = a + row;
+            // Triangles collapsing into the poles are skipped
+            if ring != 0 {
+                mesh_data.indices.extend_from_slice(&[a, a + 1, b]);
+            }
+            if ring != rings - 1 {
+                mesh_data.indices.extend_from_slice(&[a + 1, b + 1, b]);
+            }
+        }
+    }
+    mesh_data
+}
+
+/// Horizontal plane facing upwards, divided into a grid of `subdivisions` × `subdivisions` cells.
+pub fn plane(width: f32, depth: f32, subdivisions: u32) -> MeshData {
+    let subdivisions = subdivisions.max(1);
+    let mut mesh_data = MeshData::default();
+    for z in 0..=subdivisions {
+        for x in 0..=subdivisions {
+            mesh_data.vertices.push(Vertex {
+                position: vec3(
+                    width * (x as f32 / subdivisions as f32 - 0.5),
+                    0.0,
+                    depth * (z as f32 / subdivisions as f32 - 0.5),
+                ),
+                normal: Vector3::unit_y(),
+            });
+            mesh_data.uvs.push(vec2(
+                x as f32 / subdivisions as f32,
+                1.0 - z as f32 / subdivisions as f32,
+            ));
+        }
+    }
+
+    let row = subdivisions + 1;
+    for z in 0..subdivisions {
+        for x in 0..subdivisions {
+            let a = z * row + x;
+            let b = a + 1;
+            let c = a + row;
+            let d = c + 1;
+            mesh_data.indices.extend_from_slice(&[a, c, b, b, c, d]);
+        }
+    }
+    mesh_data
+}
+
+/// Cylinder standing on the Y axis, closed with flat caps.
+pub fn cylinder(radius: f32, height: f32, segments: u32) -> MeshData {
+    let segments = segments.max(3);
+    let half_height = 0.5 * height;
+    let direction = |segment: u32| {
+        // Wrap the seam to get identical positions at both ends
+        let angle = TAU * (segment % segments) as f32 / segments as f32;
+        vec3(angle.cos(), 0.0, angle.sin())
+    };
+    let mut mesh_data = MeshData::default();
+
+    // Side with normals pointing away from the axis, bottom and top vertex of each segment
+    for segment in 0..=segments {
+        let normal = direction(segment);
+        for (y, v) in [(-half_height, 0.0), (half_height, 1.0)] {
+            mesh_data.vertices.push(Vertex {
+                position: normal * radius + vec3(0.0, y, 0.0),
+                normal,
+            });
+            mesh_data
+                .uvs
+                .push(vec2(segment as f32 / segments as f32, v));
+        }
+    }
+    for segment in 0..segments {
+        let bottom = 2 * segment;
+        let top = bottom + 1;
+        mesh_data
+            .indices
+            .extend_from_slice(&[bottom, top, bottom + 2, bottom + 2, top, top + 2]);
+    }
+
+    // Caps with a vertex in the center
+    for (y, normal) in [
+        (half_height, Vector3::unit_y()),
+        (-half_height, -Vector3::unit_y()),
+    ] {
+        let center = mesh_data.vertices.len() as u32;
+        mesh_data.vertices.push(Vertex {
+            position: vec3(0.0, y, 0.0),
+            normal,
+        });
+        mesh_data.uvs.push(vec2(0.5, 0.5));
+        for segment in 0..segments {
+            let direction = direction(segment);
+            mesh_data.vertices.push(Vertex {
+                position: direction * radius + vec3(0.0, y, 0.0),
+                normal,
+            });
+            // Disc mapped into the texture, seen from outside
+            mesh_data.uvs.push(vec2(
+                0.5 + 0.5 * direction.x,
+                0.5 + 0.5 * direction.z * normal.y,
+            ));
+        }
+        for segment in 0..segments {
+            let current = center + 1 + segment;
+            let next = center + 1 + (segment + 1) % segments;
+            if normal.y > 0.0 {
+                mesh_data
+                    .indices
+                    .extend_from_slice(&[center, next, current]);
+            } else {
+                mesh_data
+                    .indices
+                    .extend_from_slice(&[center, current, next]);
+            }
+        }
+    }
+    mesh_data
+}
+
+/// Ring lying in the horizontal plane. `major_radius` is the distance of the tube center from the
+/// origin, `minor_radius` is the radius of the tube.
+pub fn torus(
+    major_radius: f32,
+    minor_radius: f32,
+    major_segments: u32,
+    minor_segments: u32,
+) -> MeshData {
+    let major_segments = major_segments.max(3);
+    let minor_segments = minor_segments.max(3);
+    let mut mesh_data = MeshData::default();
+    for major in 0..=major_segments {
+        // Seams are wrapped to get identical positions at both ends
+        let major_angle = TAU * (major % major_segments) as f32 / major_segments as f32;
+        let tube_direction = vec3(major_angle.cos(), 0.0, major_angle.sin());
+        for minor in 0..=minor_segments {
+            let minor_angle = TAU * (minor % minor_segments) as f32 / minor_segments as f32;
+            let normal = tube_direction * minor_angle.cos() + Vector3::unit_y() * minor_angle.sin();
+            mesh_data.vertices.push(Vertex {
+                position: tube_direction * major_radius + normal * minor_radius,
+                normal,
+            });
+            mesh_data.uvs.push(vec2(
+                major as f32 / major_segments as f32,
+                minor as f32 / minor_segments as f32,
+            ));
+        }
+    }
+
+    let row = minor_segments + 1;
+    for major in 0..major_segments {
+        for minor in 0..minor_segments {
+            let a = major * row + minor;
+            let b = a + row;
+            mesh_data
+                .indices
+                .extend_from_slice(&[a, a + 1, b, a + 1, b + 1, b]);
+        }
+    }
+    mesh_data
+}
+
+/// Box with the given edge lengths along the X, Y and Z axes. Each face has its own vertices for
+/// sharp edges.
+pub fn cuboid(width: f32, height: f32, depth: f32) -> MeshData {
+    let half_size = vec3(0.5 * width, 0.5 * height, 0.5 * depth);
+    let (x, y, z) = (Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z());
+    // Normal and two edge directions of each face, normal = u × v
+    let faces = [
+        (x, y, z),
+        (-x, z, y),
+        (y, z, x),
+        (-y, x, z),
+        (z, x, y),
+        (-z, y, x),
+    ];
+    let mut mesh_data = MeshData::default();
+    for (normal, u, v) in faces {
+        let first = mesh_data.vertices.len() as u32;
+        for (u_sign, v_sign) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            let corner = normal + u * u_sign + v * v_sign;
+            mesh_data.vertices.push(Vertex {
+                position: corner.mul_element_wise(half_size),
+                normal,
+            });
+            mesh_data
+                .uvs
+                .push(vec2(0.5 + 0.5 * u_sign, 0.5 + 0.5 * v_sign));
+        }
+        mesh_data.indices.extend_from_slice(&[
+            first,
+            first + 1,
+            first + 2,
+            first,
+            first + 2,
+            first + 3,
+        ]);
+    }
+    mesh_data
+}
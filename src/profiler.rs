@@ -0,0 +1,187 @@
+//! CPU profiling of the frame loop with puffin, enabled by the `profiling` feature. Without the
+//! feature, scopes compile to nothing.
+//!
+//! Measured time is spent on the CPU only. OpenGL calls are asynchronous, so GPU work shows up where
+//! the driver blocks, usually in buffer swapping.
+
+/// Measure time spent from this point until the end of the enclosing block.
+macro_rules! profile_scope {
+    ($name:expr) => {
+        #[cfg(feature = "profiling")]
+        puffin::profile_scope!($name);
+    };
+}
+pub(crate) use profile_scope;
+
+/// Close the profiled frame and start a new one. Called once at the start of every frame.
+pub fn new_frame() {
+    #[cfg(feature = "profiling")]
+    puffin::GlobalProfiler::lock().new_frame();
+}
+
+#[cfg(feature = "profiling")]
+pub use viewer::ProfilerWindow;
+
+#[cfg(feature = "profiling")]
+mod viewer {
+    use std::sync::Arc;
+
+    use puffin::{FrameData, GlobalFrameView, MergeScope, ScopeCollection};
+
+    /// Which of the recorded frames is displayed.
+    #[derive(Clone, Copy, PartialEq)]
+    enum FrameSelection {
+        Latest,
+        // Frame time spikes are hard to catch from the latest frame
+        Slowest,
+    }
+
+    /// Window listing the profiled scopes of a frame as a tree with their durations.
+    pub struct ProfilerWindow {
+        frame_view: GlobalFrameView,
+        frame_selection: FrameSelection,
+        // Frame kept on screen while paused
+        paused_frame: Option<Arc<FrameData>>,
+    }
+
+    impl ProfilerWindow {
+        /// Turns on recording of profiling scopes, which are ignored until a viewer exists.
+        pub fn new() -> Self {
+            puffin::set_scopes_on(true);
+            Self {
+                frame_view: GlobalFrameView::default(),
+                frame_selection: FrameSelection::Latest,
+                paused_frame: None,
+            }
+        }
+
+        pub fn show(&mut self, egui_ctx: &egui::Context, open: &mut bool) {
+            egui::Window::new("Profiler")
+                .open(open)
+                .default_pos([320.0, 440.0])
+                .default_size([420.0, 360.0])
+                .show(egui_ctx, |ui| {
+                    let frame_view = self.frame_view.lock();
+
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(
+                            &mut self.frame_selection,
+                            FrameSelection::Latest,
+                            "Latest frame",
+                        );
+                        ui.selectable_value(
+                            &mut self.frame_selection,
+                            FrameSelection::Slowest,
+                            "Slowest frame",
+                        );
+                        let mut paused = self.paused_frame.is_some();
+                        if ui.checkbox(&mut paused, "Pause").changed() {
+                            self.paused_frame = None;
+                        }
+                        if paused && self.paused_frame.is_none() {
+                            self.paused_frame = select_frame(&frame_view, self.frame_selection);
+                        }
+                    });
+
+                    let frame = self
+                        .paused_frame
+                        .clone()
+                        .or_else(|| select_frame(&frame_view, self.frame_selection));
+                    let Some(frame) = frame else {
+                        ui.label("No frames recorded yet");
+                        return;
+                    };
+                    // Frames are only compressed with the "packing" feature of puffin
+                    let frame = match frame.unpacked() {
+                        Ok(frame) => frame,
+                        Err(never) => match never {},
+                    };
+
+                    let duration_ns = frame.duration_ns();
+                    ui.label(format!(
+                        "Frame #{}: {:.3} ms",
+                        frame.frame_index(),
+                        to_ms(duration_ns)
+                    ));
+                    ui.separator();
+
+                    let frames = [frame.clone()];
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        for thread_info in frame.thread_streams.keys() {
+                            let scopes = match puffin::merge_scopes_for_thread(
+                                frame_view.scope_collection(),
+                                &frames,
+                                thread_info,
+                            ) {
+                                Ok(scopes) => scopes,
+                                Err(e) => {
+                                    ui.label(format!("{}: {e:?}", thread_info.name));
+                                    continue;
+                                }
+                            };
+                            egui::CollapsingHeader::new(format!("Thread {}", thread_info.name))
+                                .default_open(true)
+                                .show(ui, |ui| {
+                                    show_scopes(
+                                        ui,
+                                        frame_view.scope_collection(),
+                                        &scopes,
+                                        duration_ns,
+                                    );
+                                });
+                        }
+                    });
+                });
+        }
+    }
+
+    fn select_frame(
+        frame_view: &puffin::FrameView,
+        frame_selection: FrameSelection,
+    ) -> Option<Arc<FrameData>> {
+        match frame_selection {
+            FrameSelection::Latest => frame_view.latest_frame(),
+            FrameSelection::Slowest => frame_view
+                .slowest_frames_chronological()
+                .max_by_key(|frame| frame_duration_ns(frame))
+                .cloned(),
+        }
+    }
+
+    /// Scopes nested in each other, with the share of frame time shown as a bar.
+    fn show_scopes(
+        ui: &mut egui::Ui,
+        scope_collection: &ScopeCollection,
+        scopes: &[MergeScope<'_>],
+        frame_duration_ns: puffin::NanoSecond,
+    ) {
+        for scope in scopes {
+            let name = scope_collection
+                .fetch_by_id(&scope.id)
+                .map_or("unknown".into(), |details| details.name().clone());
+            let text = format!("{name}: {:.3} ms", to_ms(scope.duration_per_frame_ns));
+            let fraction = scope.duration_per_frame_ns as f32 / frame_duration_ns.max(1) as f32;
+            let bar = egui::ProgressBar::new(fraction).text(text);
+
+            if scope.children.is_empty() {
+                ui.add(bar);
+            } else {
+                egui::CollapsingHeader::new(name.as_ref())
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        ui.add(bar);
+                        show_scopes(ui, scope_collection, &scope.children, frame_duration_ns);
+                    });
+            }
+        }
+    }
+
+    fn frame_duration_ns(frame: &FrameData) -> puffin::NanoSecond {
+        let (start_ns, end_ns) = frame.meta().range_ns;
+        end_ns - start_ns
+    }
+
+    fn to_ms(ns: puffin::NanoSecond) -> f64 {
+        ns as f64 / 1_000_000.0
+    }
+}
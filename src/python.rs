@@ -0,0 +1,170 @@
+use std::sync::Arc;
+
+use cgmath::{Point3, Vector2, Vector3};
+use numpy::{PyArray1, PyArray3, PyArrayMethods};
+use pyo3::{exceptions::PyRuntimeError, prelude::*};
+
+use crate::{gl_state, Camera, DrawProperties, Model, PointLight, Renderer, Scene, Skybox};
+
+/// Python module built as the `renderer_rust` extension, e.g. with `maturin develop --features
+/// python`.
+#[pymodule]
+#[pyo3(name = "renderer_rust")]
+fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<OffscreenRenderer>()
+}
+
+/// Renders scenes into numpy arrays without opening a window, e.g. for generating machine
+/// learning datasets or in notebooks.
+///
+/// Rendering runs in a headless EGL context, so an EGL device is required, e.g. a GPU driver or
+/// the Mesa software rasterizer. Objects are bound to the thread that created them. Each object has
+/// its own context, made current by methods using OpenGL.
+#[pyclass(unsendable, module = "renderer_rust")]
+pub struct OffscreenRenderer {
+    /// Width of rendered images in pixels.
+    #[pyo3(get, set)]
+    width: u32,
+    /// Height of rendered images in pixels.
+    #[pyo3(get, set)]
+    height: u32,
+    gl: Arc<glow::Context>,
+    renderer: Renderer,
+    skybox: Skybox,
+    scene: Scene,
+    draw_props: DrawProperties,
+    // Dropped last, the context must outlive every OpenGL object
+    #[cfg(not(target_os = "macos"))]
+    context: glutin::api::egl::context::PossiblyCurrentContext,
+}
+
+#[pymethods]
+impl OffscreenRenderer {
+    #[new]
+    #[pyo3(signature = (width = 512, height = 512))]
+    fn new(width: u32, height: u32) -> PyResult<Self> {
+        cfg_if::cfg_if! { if #[cfg(target_os = "macos")] {
+            let _ = (width, height);
+            Err(PyRuntimeError::new_err(
+                "offscreen rendering requires EGL, which is not available on macOS",
+            ))
+        } else {
+            let (context, gl) =
+                crate::thumbnail::headless::create_context().map_err(PyRuntimeError::new_err)?;
+            let renderer = Renderer::new(gl.clone()).map_err(PyRuntimeError::new_err)?;
+            let skybox = Skybox::placeholder(gl.clone())
+                .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+            Ok(Self {
+                width,
+                height,
+                gl,
+                renderer,
+                skybox,
+                scene: Scene::new(Camera::new(
+                    Point3::new(0.0, 0.0, 4.0),
+                    Vector2::new(270.0, 0.0),
+                )),
+                draw_props: DrawProperties {
+                    light_gizmo_enabled: false,
+                    skybox_enabled: false,
+                    ..Default::default()
+                },
+                context,
+            })
+        }}
+    }
+
+    /// Load an OBJ or PLY file as a new entry of the model selection and select it, hiding the
+    /// previously selected model. Returns the index of the model.
+    fn load_model(&mut self, path: &str) -> PyResult<usize> {
+        self.make_current()?;
+        let name = std::path::Path::new(path)
+            .file_stem()
+            .map_or(path.to_string(), |stem| stem.to_string_lossy().into_owned());
+        let model = Model::create_from_file(self.gl.clone(), path)
+            .map_err(PyRuntimeError::new_err)?
+            .with_name(&name);
+        self.scene.models.push(model);
+        let model_index = self.scene.models.len() - 1;
+        self.scene.spawn_selectable(model_index);
+        self.draw_props.selected_model_index = model_index;
+        Ok(model_index)
+    }
+
+    /// Place the camera at the position, turned to yaw, pitch and roll in degrees.
+    fn set_camera(&mut self, position: [f32; 3], rotation: [f32; 3]) {
+        self.scene
+            .camera
+            .set_placement(Point3::from(position), Vector3::from(rotation));
+    }
+
+    /// Direction the sunlight shines towards.
+    fn set_light_direction(&mut self, direction: [f32; 3]) {
+        self.draw_props.light_direction = direction;
+    }
+
+    /// Add a point light shining in every direction from the position.
+    #[pyo3(signature = (position, color = [1.0, 1.0, 1.0], intensity = 1.0))]
+    fn add_point_light(&mut self, position: [f32; 3], color: [f32; 3], intensity: f32) {
+        self.scene.spawn_point_light(PointLight {
+            position: Vector3::from(position),
+            color,
+            intensity,
+            ..Default::default()
+        });
+    }
+
+    /// Set a draw property by its field name, e.g. `set_property("exposure", [1.5])`. Vectors take
+    /// 3 values, flags are enabled by non-zero values and modes are selected by index.
+    fn set_property(&mut self, name: &str, values: Vec<f32>) -> PyResult<()> {
+        self.draw_props
+            .set_by_name(name, &values)
+            .map_err(PyRuntimeError::new_err)?;
+        let last_model_index = self.scene.models.len().saturating_sub(1);
+        self.draw_props.selected_model_index =
+            self.draw_props.selected_model_index.min(last_model_index);
+        Ok(())
+    }
+
+    /// Render the scene into an array of RGB pixels with the shape `(height, width, 3)`, top row
+    /// first.
+    fn render<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyArray3<u8>>> {
+        self.make_current()?;
+        self.scene.apply_draw_properties(&self.draw_props);
+        self.renderer
+            .render_to_texture(
+                &self.scene.camera,
+                &self.draw_props,
+                &self.scene,
+                &self.skybox,
+                self.width,
+                self.height,
+            )
+            .map_err(PyRuntimeError::new_err)?;
+        let pixels = self
+            .renderer
+            .read_texture_output()
+            .map_err(PyRuntimeError::new_err)?;
+        PyArray1::from_vec(py, pixels).reshape([self.height as usize, self.width as usize, 3])
+    }
+}
+
+impl OffscreenRenderer {
+    /// Scripts may use several renderers on the same thread. State known by `gl_state` belongs to
+    /// the previously current context.
+    fn make_current(&self) -> PyResult<()> {
+        #[cfg(not(target_os = "macos"))]
+        self.context
+            .make_current_surfaceless()
+            .map_err(|e| PyRuntimeError::new_err(format!("cannot make context current: {e}")))?;
+        gl_state::invalidate();
+        Ok(())
+    }
+}
+
+impl Drop for OffscreenRenderer {
+    fn drop(&mut self) {
+        // OpenGL objects of the fields are deleted with the context current
+        let _ = self.make_current();
+    }
+}
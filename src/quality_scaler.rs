@@ -0,0 +1,76 @@
+use crate::DrawProperties;
+
+/// Frame times are averaged over this many seconds before quality is adjusted.
+const MEASUREMENT_PERIOD: f32 = 1.0;
+/// Quality is lowered when frames take this much longer than the target on average.
+const LOWER_THRESHOLD: f32 = 1.1;
+/// Quality is raised when frames take at most this fraction of the target on average.
+const RAISE_THRESHOLD: f32 = 0.8;
+/// Measurement periods to wait after lowering quality before raising it again, so quality doesn't
+/// alternate between two levels when only the lower one keeps up with the target.
+const SETTLE_PERIODS: u32 = 5;
+
+/// Render scale and whether expensive passes are skipped, from the highest quality to the lowest.
+/// Skipping reflections saves more than a slightly lower resolution, but is more noticeable.
+const QUALITY_LEVELS: [(f32, bool); 5] = [
+    (1.0, false),
+    (0.85, false),
+    (0.85, true),
+    (0.7, true),
+    (0.5, true),
+];
+
+/// Lowers render scale and skips expensive passes when frames take longer than the target frame
+/// rate allows, raising quality again once there is headroom. Useful on weak integrated GPUs,
+/// especially with the web build.
+///
+/// Frames can't be faster than the display refresh rate with vertical sync enabled, so a target
+/// above it only ever lowers quality.
+#[derive(Default)]
+pub struct QualityScaler {
+    // Index into QUALITY_LEVELS
+    level: usize,
+    // Time and frame count of the current measurement period
+    elapsed_time: f32,
+    frame_count: u32,
+    settle_periods: u32,
+}
+
+impl QualityScaler {
+    /// Measure a frame taking `frame_time` seconds and adjust quality settings in `draw_props` at
+    /// the end of each measurement period. Expensive passes are enabled again when automatic
+    /// quality is switched off, render scale is left for the user to adjust.
+    pub fn frame(&mut self, frame_time: f32, draw_props: &mut DrawProperties) {
+        if !draw_props.auto_quality_enabled {
+            if self.level != 0 {
+                draw_props.reduced_effects = false;
+            }
+            *self = Self::default();
+            return;
+        }
+
+        self.elapsed_time += frame_time;
+        self.frame_count += 1;
+        if self.elapsed_time < MEASUREMENT_PERIOD {
+            return;
+        }
+        let ms_per_frame = 1000.0 * self.elapsed_time / self.frame_count as f32;
+        self.elapsed_time = 0.0;
+        self.frame_count = 0;
+        self.settle_periods = self.settle_periods.saturating_sub(1);
+
+        let target_ms_per_frame = 1000.0 / draw_props.target_frame_rate;
+        if target_ms_per_frame * LOWER_THRESHOLD < ms_per_frame
+            && self.level + 1 < QUALITY_LEVELS.len()
+        {
+            self.level += 1;
+            self.settle_periods = SETTLE_PERIODS;
+        } else if ms_per_frame <= target_ms_per_frame * RAISE_THRESHOLD
+            && 0 < self.level
+            && self.settle_periods == 0
+        {
+            self.level -= 1;
+        }
+        (draw_props.render_scale, draw_props.reduced_effects) = QUALITY_LEVELS[self.level];
+    }
+}
@@ -0,0 +1,28 @@
+use serde::Deserialize;
+
+/// Request of a remote controller, carried out by the application between frames.
+///
+/// Deserialized from JSON objects of the remote control server, named by their `command` field in
+/// snake case, e.g. `{"command": "set_property", "name": "exposure", "values": [1.5]}`.
+#[derive(Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub(crate) enum RemoteCommand {
+    /// Set a field of `DrawProperties` by name, see `DrawProperties::set_by_name()`.
+    SetProperty { name: String, values: Vec<f32> },
+    /// Place the camera, keeping the position or rotation left out. Rotation is yaw, pitch and
+    /// roll in degrees.
+    MoveCamera {
+        position: Option<[f32; 3]>,
+        rotation: Option<[f32; 3]>,
+    },
+    /// Fly to the camera bookmark at the index, counted from 0.
+    GoToCameraBookmark { index: usize },
+    /// Level of the modulation source at the index from 0 to 1.
+    SetModulationLevel { index: usize, level: f32 },
+    /// Open a model file as a new entry of the model selection and select it.
+    LoadModel { path: String },
+    /// Save the next rendered frame into the PNG file of the name in the screenshot directory,
+    /// named after the current time if left out. Names with directories are rejected, so clients
+    /// can't write files elsewhere.
+    Screenshot { name: Option<String> },
+}
@@ -0,0 +1,239 @@
+use std::{
+    io::ErrorKind,
+    net::{SocketAddr, TcpListener, TcpStream},
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+use tungstenite::{
+    handshake::{
+        server::{Callback, ErrorResponse, Request, Response, ServerHandshake},
+        HandshakeError, MidHandshake,
+    },
+    http::StatusCode,
+    protocol::WebSocketConfig,
+    Message, WebSocket,
+};
+
+use crate::remote::RemoteCommand;
+
+/// Longest time for a connecting client to complete the WebSocket handshake before it is dropped.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(1);
+/// Most connected clients, including the ones in the middle of the handshake. Further connections
+/// are closed right away.
+const MAX_CLIENTS: usize = 16;
+/// Largest accepted message and frame in bytes. Requests are far smaller.
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+type PendingHandshake = MidHandshake<ServerHandshake<TcpStream, RejectBrowsers>>;
+
+/// Identifier of a connected client, for replying to its requests.
+pub(crate) type ClientId = u64;
+
+/// Request received from a client. The optional `id` of any JSON type is echoed in the reply, so
+/// clients can match replies to pipelined requests.
+#[derive(Deserialize)]
+pub(crate) struct RemoteRequest {
+    pub id: Option<serde_json::Value>,
+    #[serde(flatten)]
+    pub command: RemoteCommand,
+}
+
+struct Client {
+    id: ClientId,
+    socket: WebSocket<TcpStream>,
+    closed: bool,
+}
+
+/// WebSocket server accepting remote control requests as JSON text messages, e.g. from render farm
+/// schedulers or test automation. Every request is answered with `{"ok": true}` or with
+/// `{"ok": false, "error": "..."}` once carried out.
+///
+/// Clients are served on the thread of the frame loop without blocking it, handshakes of new
+/// connections are continued on every poll until complete. Handshakes with an `Origin` header are
+/// rejected, because browsers send one, so web pages visited meanwhile can't control the
+/// application.
+pub struct RemoteServer {
+    listener: TcpListener,
+    // Connections waiting for more of the handshake, with the time they were accepted
+    handshakes: Vec<(PendingHandshake, SocketAddr, Instant)>,
+    clients: Vec<Client>,
+    next_client_id: ClientId,
+}
+
+impl RemoteServer {
+    /// Listen on the TCP address, e.g. on a port of the loopback interface for clients of the same
+    /// machine.
+    pub fn new(address: SocketAddr) -> Result<Self, String> {
+        let listener = TcpListener::bind(address)
+            .map_err(|e| format!("cannot start remote control server on {address}: {e}"))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("cannot start remote control server on {address}: {e}"))?;
+        Ok(Self {
+            listener,
+            handshakes: Vec::new(),
+            clients: Vec::new(),
+            next_client_id: 0,
+        })
+    }
+
+    /// Accept new clients and collect their requests received since the last poll. Invalid
+    /// requests are answered right away, failing connections are reported in the error list and
+    /// dropped.
+    pub fn poll(&mut self) -> (Vec<(ClientId, RemoteRequest)>, Vec<String>) {
+        let mut errors = Vec::new();
+        loop {
+            let (stream, address) = match self.listener.accept() {
+                Ok(connection) => connection,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    errors.push(format!("cannot accept remote client: {e}"));
+                    break;
+                }
+            };
+            if MAX_CLIENTS <= self.clients.len() + self.handshakes.len() {
+                errors.push(format!(
+                    "remote client {address} rejected: at most {MAX_CLIENTS} clients are served"
+                ));
+                continue;
+            }
+            match start_handshake(stream) {
+                Ok(handshake) => self.handshakes.push((handshake, address, Instant::now())),
+                Err(e) => errors.push(format!("remote client {address} rejected: {e}")),
+            }
+        }
+        for (handshake, address, started) in std::mem::take(&mut self.handshakes) {
+            match handshake.handshake() {
+                Ok(socket) => {
+                    self.clients.push(Client {
+                        id: self.next_client_id,
+                        socket,
+                        closed: false,
+                    });
+                    self.next_client_id += 1;
+                }
+                Err(HandshakeError::Interrupted(_)) if HANDSHAKE_TIMEOUT < started.elapsed() => {
+                    errors.push(format!(
+                        "remote client {address} rejected: handshake timed out"
+                    ))
+                }
+                Err(HandshakeError::Interrupted(handshake)) => {
+                    self.handshakes.push((handshake, address, started))
+                }
+                Err(HandshakeError::Failure(e)) => {
+                    errors.push(format!("remote client {address} rejected: {e}"))
+                }
+            }
+        }
+
+        let mut requests = Vec::new();
+        for client in &mut self.clients {
+            // Replies not sent at once because of a full socket buffer are sent before reading
+            if let Err(e) = client.socket.flush() {
+                client.fail(e, &mut errors);
+                if client.closed {
+                    continue;
+                }
+            }
+            loop {
+                match client.socket.read() {
+                    Ok(Message::Text(text)) => match serde_json::from_str(&text) {
+                        Ok(request) => requests.push((client.id, request)),
+                        Err(e) => {
+                            client.reply(None, Err(format!("invalid request: {e}")), &mut errors)
+                        }
+                    },
+                    Ok(Message::Close(_)) => {
+                        client.closed = true;
+                        break;
+                    }
+                    // Pings are answered by the socket on the next read or flush
+                    Ok(_) => (),
+                    Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => break,
+                    Err(e) => {
+                        client.fail(e, &mut errors);
+                        break;
+                    }
+                }
+            }
+        }
+        self.clients.retain(|client| !client.closed);
+        (requests, errors)
+    }
+
+    /// Answer a request with the result of carrying it out. Ignored if the client is gone.
+    pub fn reply(
+        &mut self,
+        client_id: ClientId,
+        request_id: Option<serde_json::Value>,
+        result: Result<(), String>,
+        errors: &mut Vec<String>,
+    ) {
+        if let Some(client) = self
+            .clients
+            .iter_mut()
+            .find(|client| client.id == client_id)
+        {
+            client.reply(request_id, result, errors);
+        }
+    }
+}
+
+impl Client {
+    fn reply(
+        &mut self,
+        request_id: Option<serde_json::Value>,
+        result: Result<(), String>,
+        errors: &mut Vec<String>,
+    ) {
+        let mut reply = match result {
+            Ok(()) => serde_json::json!({ "ok": true }),
+            Err(e) => serde_json::json!({ "ok": false, "error": e }),
+        };
+        if let Some(request_id) = request_id {
+            reply["id"] = request_id;
+        }
+        match self.socket.send(Message::text(reply.to_string())) {
+            Ok(()) => (),
+            // Queued, sent by a later flush
+            Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => (),
+            Err(e) => self.fail(e, errors),
+        }
+    }
+
+    /// Drop the connection, reporting errors other than the client going away.
+    fn fail(&mut self, error: tungstenite::Error, errors: &mut Vec<String>) {
+        match error {
+            tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => (),
+            tungstenite::Error::Io(e) if e.kind() == ErrorKind::WouldBlock => return,
+            e => errors.push(format!("remote client disconnected: {e}")),
+        }
+        self.closed = true;
+    }
+}
+
+/// Switch the stream to non-blocking and start the handshake, continued as the client sends it.
+fn start_handshake(stream: TcpStream) -> Result<PendingHandshake, String> {
+    stream.set_nonblocking(true).map_err(|e| e.to_string())?;
+    let config = WebSocketConfig {
+        max_message_size: Some(MAX_MESSAGE_SIZE),
+        max_frame_size: Some(MAX_MESSAGE_SIZE),
+        ..Default::default()
+    };
+    Ok(ServerHandshake::start(stream, RejectBrowsers, Some(config)))
+}
+
+/// Handshake callback rejecting web pages, recognized by the `Origin` header browsers send.
+struct RejectBrowsers;
+
+impl Callback for RejectBrowsers {
+    fn on_request(self, request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+        if !request.headers().contains_key("Origin") {
+            return Ok(response);
+        }
+        let mut error = ErrorResponse::new(Some("requests of web pages are not accepted".into()));
+        *error.status_mut() = StatusCode::FORBIDDEN;
+        Err(error)
+    }
+}
@@ -0,0 +1,32 @@
+use cgmath::Matrix4;
+
+use crate::Camera;
+
+/// Extension point for injecting custom OpenGL draw code into the rendered frame without modifying
+/// the renderer, e.g. debug overlays of a plugin.
+///
+/// Hooks are only called while rendering the window contents, not for reflections, light view
+/// inset or render-to-texture requests. OpenGL capabilities and framebuffer binding changed by a
+/// hook must be restored before returning.
+pub trait RenderHook {
+    /// Called before anything is drawn into the frame, e.g. for updating buffers or rendering into
+    /// own offscreen framebuffers.
+    fn on_before_scene(
+        &mut self,
+        _gl: &glow::Context,
+        _camera: &Camera,
+        _projection: &Matrix4<f32>,
+    ) {
+    }
+
+    /// Called after the scene is drawn into the offscreen scene framebuffer, which is still bound
+    /// with depth testing enabled. Drawn geometry is occluded by the scene and post-processed
+    /// together with it, so colors are expected in linear color space.
+    fn on_after_scene(
+        &mut self,
+        _gl: &glow::Context,
+        _camera: &Camera,
+        _projection: &Matrix4<f32>,
+    ) {
+    }
+}
@@ -1,19 +1,558 @@
-use std::sync::Arc;
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    rc::Rc,
+    sync::Arc,
+};
 
 use cfg_if::cfg_if;
-use cgmath::{Deg, Euler, Matrix, Matrix3, Matrix4, Quaternion, SquareMatrix, Vector4, Zero};
-use glow::HasContext;
+use cgmath::{
+    Deg, EuclideanSpace, Euler, InnerSpace, Matrix, Matrix3, Matrix4, Point3, Quaternion,
+    SquareMatrix, Transform as _, Vector3, Vector4,
+};
+use glow::{HasContext, VertexArray};
+use hecs::Entity;
 use winit::window::Window;
 
-use crate::{assets, model::Model, shader::Shader, skybox::Skybox, Camera, DrawProperties};
+use crate::{
+    assets::{self, shader::ShaderSources},
+    decal::DECAL_DEPTH_RESOLUTION,
+    framebuffer::{
+        ColorFormat, DepthFramebuffer, Framebuffer, GBuffer, MultisampleFramebuffer,
+        PingPongFramebuffers,
+    },
+    frustum::Frustum,
+    gizmo, gl_resources, gl_state,
+    label::{Label, LabelRenderer},
+    line::{self, LineRenderer},
+    mesh_batch::{BatchedMesh, MeshBatches},
+    mirror,
+    model::{Aabb, Mesh, Model},
+    occlusion::{OcclusionCulling, OcclusionStats},
+    profiler::profile_scope,
+    scene::{PartOverrides, Renderable, Transform, Visible},
+    shader::Shader,
+    shader_variant::{insert_defines, ShaderFeatures, ShaderVariantCache},
+    shadow::{self, PointShadowMaps, ShadowMaps, MAX_SHADOW_CASCADES},
+    skybox::Skybox,
+    taa::{self, TemporalHistory},
+    terrain::TerrainStats,
+    AntiAliasing, Camera, Decal, DrawProperties, GlInfo, Material, MaterialType, RenderHook,
+    RenderPath, Scene, StereoMode, VertexAttribute, VertexColorMode, Winding, MAX_DECALS,
+    MAX_POINT_LIGHTS,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    lightmap::Lightmap,
+    multi_draw::{DrawData, MultiDrawIndirect},
+    pipeline_statistics::{PipelineStatistics, PipelineStats},
+    vertex_displacement::VertexDisplacement,
+};
+
+/// Distance in front of the camera where the eyes of stereo output converge. Objects there appear
+/// at the depth of the screen, matching the distance of the demo models from the initial camera.
+const STEREO_CONVERGENCE_DISTANCE: f32 = 4.0;
+/// Lowest fraction of the window resolution the scene is rendered at.
+pub(crate) const MIN_RENDER_SCALE: f32 = 0.25;
+/// Texture unit of the nearest shadow cascade, the others follow it. Skybox and lightmap are
+/// bound to the units before.
+const FIRST_SHADOW_MAP_TEXTURE_UNIT: u32 = 2;
+/// Texture unit of the shadow map of the first point light, following shadow cascades.
+const FIRST_POINT_SHADOW_MAP_TEXTURE_UNIT: u32 =
+    FIRST_SHADOW_MAP_TEXTURE_UNIT + MAX_SHADOW_CASCADES as u32;
+/// Texture unit of the image of the first decal, following point light shadow maps.
+const FIRST_DECAL_TEXTURE_UNIT: u32 = FIRST_POINT_SHADOW_MAP_TEXTURE_UNIT + MAX_POINT_LIGHTS as u32;
+/// Texture unit of the depth map atlas of decal projectors, following decal images.
+const DECAL_DEPTH_TEXTURE_UNIT: u32 = FIRST_DECAL_TEXTURE_UNIT + MAX_DECALS as u32;
+/// Length of face normal lines in world units.
+#[cfg(not(target_arch = "wasm32"))]
+const FACE_NORMAL_LENGTH: f32 = 0.05;
+#[cfg(not(target_arch = "wasm32"))]
+const FACE_NORMAL_COLOR: [f32; 3] = [1.0, 0.8, 0.0];
 
 /// Separation of graphics API-dependent rendering mechanisms.
 /// Screen update and buffer swap is responsibility of window
 pub struct Renderer {
     gl: Arc<glow::Context>,
+    gl_info: GlInfo,
     projection: Matrix4<f32>,
     skybox_shader: Shader,
-    model_shader: Shader,
+    // Variants of forward model shading for each combination of material features
+    model_shaders: ShaderVariantCache,
+    // Registered at runtime by name, replacing forward model shading of materials referring to them
+    custom_shaders: BTreeMap<String, CustomShader>,
+    // Built-in sources in the GLSL dialect of the context
+    shaders: &'static ShaderSources,
+    postprocess_shader: Shader,
+    gbuffer_shader: Shader,
+    deferred_lighting_shader: Shader,
+    reflective_plane_shader: Shader,
+    terrain_shader: Shader,
+    // Model and terrain geometry shaded with constant cost in overdraw heatmap mode
+    overdraw_shader: Shader,
+    // Model geometry drawn into shadow maps without color output
+    shadow_depth_shader: Shader,
+    area_light_shader: Shader,
+    post_effect_shader: Shader,
+    motion_blur_shader: Shader,
+    taa_resolve_shader: Shader,
+    // Updated before rendering the scene from each viewpoint
+    shadow_maps: ShadowMaps,
+    point_shadow_maps: PointShadowMaps,
+    // Depth seen by each decal projector in a quadrant, present while decals are drawn
+    decal_depth_atlas: Option<DepthFramebuffer>,
+    label_renderer: LabelRenderer,
+    line_renderer: LineRenderer,
+    // Number of samples per pixel of multisampled render targets
+    samples: i32,
+    // Physical size of the window, scene targets are smaller with render scale below 1
+    window_size: (u32, u32),
+    render_scale: f32,
+    // Display scale factor, labels are scaled further by render scale
+    scale_factor: f32,
+    window_targets: RenderTargets,
+    // Created on first render-to-texture request
+    texture_target: Option<TextureTarget>,
+    // Created when light's-eye view inset is first enabled
+    light_view_targets: Option<RenderTargets>,
+    // Created when stereo output is first enabled, one for each eye
+    stereo_targets: Option<[RenderTargets; 2]>,
+    // Created when temporal anti-aliasing is enabled for the window, released when disabled
+    temporal_history: Option<TemporalHistory>,
+    // Compute shaders require OpenGL 4.3
+    #[cfg(not(target_arch = "wasm32"))]
+    vertex_displacement: Option<VertexDisplacement>,
+    // Created by load_multi_draw_indirect(), requires OpenGL 4.3
+    #[cfg(not(target_arch = "wasm32"))]
+    multi_draw_indirect: Option<RefCell<MultiDrawIndirect>>,
+    // Requires ARB_pipeline_statistics_query
+    #[cfg(not(target_arch = "wasm32"))]
+    pipeline_statistics: Option<PipelineStatistics>,
+    // Geometry shaders are written in GLSL 4.30 only
+    #[cfg(not(target_arch = "wasm32"))]
+    geometry_debug_shaders: Option<GeometryDebugShaders>,
+    // Core profile requires a vertex array bound even when vertices are generated in shader.
+    fullscreen_vertex_array: VertexArray,
+    render_hooks: Vec<Box<dyn RenderHook>>,
+    occlusion_culling: OcclusionCulling,
+    mesh_batches: MeshBatches,
+    stats: RenderStats,
+    // Counted while drawing the frame, published in stats when it's finished
+    draw_stats: Cell<DrawStats>,
+    // Frames drawn from the camera, seeds animated post effects
+    frame_count: u32,
+    // Camera-relative projection-view matrix and eye position of the previous frame drawn from the
+    // camera, pixels are reprojected with it for motion blur
+    previous_camera_view: Option<(Matrix4<f32>, Point3<f32>)>,
+}
+
+/// Statistics of the last frame drawn from the camera.
+#[derive(Clone, Default)]
+pub struct RenderStats {
+    pub lod: LodStats,
+    pub occlusion: OcclusionStats,
+    pub terrain: TerrainStats,
+    pub draws: DrawStats,
+    /// Vertices and fragments processed by the GPU, `None` if pipeline statistics queries are
+    /// unsupported or no result has arrived yet.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub pipeline: Option<PipelineStats>,
+}
+
+/// Draw calls issued for the last frame, counted over every pass like mirror reflections and
+/// stereo eyes. Debug lines, labels and occlusion queries are not included.
+#[derive(Clone, Copy, Default)]
+pub struct DrawStats {
+    pub draw_call_count: usize,
+    pub triangle_count: usize,
+}
+
+/// Levels of detail selected for the last frame drawn from the camera.
+#[derive(Clone, Default)]
+pub struct LodStats {
+    /// Number of visible entities drawn with each level of detail, starting from full detail.
+    pub entities_per_level: Vec<usize>,
+    /// Number of triangles of the drawn meshes.
+    pub triangle_count: usize,
+    /// Level of detail of the selected model, `None` when it is hidden.
+    pub selected_model_level: Option<usize>,
+}
+
+/// Viewpoint of one eye of a stereo display.
+#[derive(Clone, Copy)]
+pub struct EyeView {
+    /// World to eye space transformation.
+    pub view: Matrix4<f32>,
+    pub projection: Matrix4<f32>,
+}
+
+/// Shader compiled from GLSL sources supplied at runtime, kept with its sources for editing.
+struct CustomShader {
+    shader: Rc<Shader>,
+    vertex_source: String,
+    fragment_source: String,
+}
+
+/// Mesh of a visible entity drawn with a single draw call.
+// Components of renderable entities queried by render passes
+type EntityComponents<'a> = (
+    &'a Renderable,
+    &'a Transform,
+    &'a Material,
+    Option<&'a PartOverrides>,
+);
+
+struct ModelDraw<'a> {
+    shader_features: ShaderFeatures,
+    // Name of the registered custom shader replacing the shader variant
+    custom_shader: Option<&'a str>,
+    material: &'a Material,
+    model: &'a Model,
+    // Level of detail selected for the viewpoint
+    mesh: &'a Mesh,
+    // First index and index count of the drawn part, `None` to draw the whole mesh
+    part: Option<(usize, usize)>,
+    // Location in shared buffers if the mesh is merged into a batch
+    batched: Option<BatchedMesh>,
+    model_matrix: Matrix4<f32>,
+    // Baked lighting replacing realtime diffuse and ambient lighting, mesh is the lightmap mesh
+    #[cfg(not(target_arch = "wasm32"))]
+    lightmap: Option<glow::Texture>,
+}
+
+impl<'a> ModelDraw<'a> {
+    fn vertex_array(&self) -> VertexArray {
+        self.batched
+            .map_or(self.mesh.vertex_array(), |batched| batched.vertex_array)
+    }
+
+    /// First index and count of indices to draw.
+    fn index_range(&self) -> (usize, usize) {
+        self.part.unwrap_or((0, self.mesh.index_count()))
+    }
+
+    /// Draws with equal keys use the same shader.
+    fn shader_key(&self) -> (Option<&'a str>, ShaderFeatures) {
+        (self.custom_shader, self.shader_features)
+    }
+
+    /// Draws with equal keys use the same shader and material uniform values.
+    fn material_key(&self) -> ((Option<&'a str>, ShaderFeatures), [u32; 4]) {
+        let [r, g, b] = self.material.color;
+        (
+            self.shader_key(),
+            [
+                r.to_bits(),
+                g.to_bits(),
+                b.to_bits(),
+                self.material.refractive_index.to_bits(),
+            ],
+        )
+    }
+}
+
+/// Debug passes processing triangles of models with geometry shaders. Not available in OpenGL ES
+/// 3.0 and WebGL2.
+#[cfg(not(target_arch = "wasm32"))]
+struct GeometryDebugShaders {
+    // Line along the normal of each triangle
+    face_normals: Shader,
+    // Triangles pushed apart along their normals
+    exploded_view: Shader,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl GeometryDebugShaders {
+    fn new(gl: &Arc<glow::Context>) -> Result<Self, String> {
+        let face_normals = Shader::with_geometry_shader(
+            gl.clone(),
+            assets::shader::FACE_NORMALS_VERTEX_SRC,
+            assets::shader::FACE_NORMALS_GEOMETRY_SRC,
+            assets::shader::FACE_NORMALS_FRAGMENT_SRC,
+        )
+        .map_err(|e| format!("face normals shader creation failed: {e}"))?;
+        let exploded_view = Shader::with_geometry_shader(
+            gl.clone(),
+            assets::shader::EXPLODED_VIEW_VERTEX_SRC,
+            assets::shader::EXPLODED_VIEW_GEOMETRY_SRC,
+            assets::shader::EXPLODED_VIEW_FRAGMENT_SRC,
+        )
+        .map_err(|e| format!("exploded view shader creation failed: {e}"))?;
+        Ok(Self {
+            face_normals,
+            exploded_view,
+        })
+    }
+}
+
+/// Offscreen framebuffers used while rendering the scene into a single output.
+///
+/// Scene is rendered offscreen first in linear color space, then drawn into the output with
+/// post-processing applied.
+struct RenderTargets {
+    scene: Framebuffer,
+    // Rendering into an offscreen framebuffer loses the multisampling of the default framebuffer,
+    // keep anti-aliasing by rendering into a multisampled framebuffer and resolving it.
+    multisample: Option<MultisampleFramebuffer>,
+    gbuffer: GBuffer,
+    // Scene rendered from the camera mirrored by the reflective ground plane
+    reflection: Framebuffer,
+    // Outputs of post effect passes
+    post_effects: PingPongFramebuffers,
+}
+
+impl RenderTargets {
+    /// Framebuffers are sized properly on first resize.
+    fn new(gl: &Arc<glow::Context>, samples: i32) -> Result<Self, String> {
+        let scene = Framebuffer::new(gl.clone(), 1, 1, ColorFormat::scene(gl))
+            .map_err(|e| format!("scene framebuffer creation failed: {e}"))?;
+        let multisample = if 1 < samples {
+            Some(
+                MultisampleFramebuffer::new(gl.clone(), 1, 1, ColorFormat::scene(gl), samples)
+                    .map_err(|e| format!("multisample framebuffer creation failed: {e}"))?,
+            )
+        } else {
+            None
+        };
+        let gbuffer =
+            GBuffer::new(gl.clone(), 1, 1).map_err(|e| format!("G-buffer creation failed: {e}"))?;
+        let reflection = Framebuffer::new(gl.clone(), 1, 1, ColorFormat::scene(gl))
+            .map_err(|e| format!("reflection framebuffer creation failed: {e}"))?;
+        let post_effects = PingPongFramebuffers::new(gl.clone(), 1, 1, ColorFormat::scene(gl))
+            .map_err(|e| format!("post effect framebuffer creation failed: {e}"))?;
+
+        Ok(Self {
+            scene,
+            multisample,
+            gbuffer,
+            reflection,
+            post_effects,
+        })
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        self.scene
+            .resize(width, height)
+            .map_err(|e| format!("unable to resize scene framebuffer: {e}"))?;
+        if let Some(multisample) = self.multisample.as_mut() {
+            multisample
+                .resize(width, height)
+                .map_err(|e| format!("unable to resize multisample framebuffer: {e}"))?;
+        }
+        self.gbuffer
+            .resize(width, height)
+            .map_err(|e| format!("unable to resize G-buffer: {e}"))?;
+        self.reflection
+            .resize(width, height)
+            .map_err(|e| format!("unable to resize reflection framebuffer: {e}"))?;
+        self.post_effects
+            .resize(width, height)
+            .map_err(|e| format!("unable to resize post effect framebuffers: {e}"))
+    }
+}
+
+/// Render targets of render-to-texture requests with the post-processed output texture.
+struct TextureTarget {
+    targets: RenderTargets,
+    output: Framebuffer,
+}
+
+/// Viewpoint of a single scene rendering pass.
+struct SceneView {
+    projection: Matrix4<f32>,
+    view: Matrix4<f32>,
+    /// Eye position in world space used for lighting calculations.
+    position: Point3<f32>,
+    /// Fragments on the negative side of this world space plane are discarded.
+    clip_plane: Vector4<f32>,
+    /// Mirroring flips the winding order of triangles.
+    mirrored: bool,
+    /// Skybox cube only surrounds the viewer with perspective projection.
+    skybox_visible: bool,
+    /// Light gizmo would block the view when looking from the light.
+    light_gizmo_visible: bool,
+    /// Skip entities found occluded in the previous frame and test them again.
+    occlusion_culling: bool,
+}
+
+impl SceneView {
+    /// Fraction of the viewport height covered by a world space sphere.
+    fn screen_coverage(&self, center: Point3<f32>, radius: f32) -> f32 {
+        // Cotangent of half field of view for perspective projection
+        let vertical_scale = self.projection.y.y;
+        // Orthographic projection keeps size at any distance
+        if self.projection.w.w == 1.0 {
+            return radius * vertical_scale;
+        }
+        let distance = (center - self.position).magnitude();
+        if distance <= radius {
+            return 1.0;
+        }
+        radius * vertical_scale / distance
+    }
+
+    /// Projection and view of the eye moved to the world origin.
+    ///
+    /// Models are drawn camera-relative: the world is translated by the negated eye position
+    /// before building matrices, see `relative_model_matrix()`. Coordinates around the viewer stay
+    /// small, so large scenes far from the world origin don't jitter from f32 precision loss of
+    /// huge translations cancelling each other out in the model-view-projection matrix.
+    fn relative_projection_view(&self) -> Matrix4<f32> {
+        let mut view = self.view;
+        // Only rotation is kept, eye position is subtracted from model matrices instead
+        view.w = Vector4::unit_w();
+        self.projection * view
+    }
+
+    /// Matrix from normalized device coordinates of this view into clip space of a previous view,
+    /// given by its `relative_projection_view()` and eye position. Returns `None` if this view
+    /// cannot be inverted.
+    fn reprojection_from(
+        &self,
+        (previous_projection_view, previous_position): (Matrix4<f32>, Point3<f32>),
+    ) -> Option<Matrix4<f32>> {
+        // Movement of the eye is small, unlike the positions themselves
+        let eye_offset = Matrix4::from_translation(self.position - previous_position);
+        let inverse_projection_view = self.relative_projection_view().invert()?;
+        Some(previous_projection_view * eye_offset * inverse_projection_view)
+    }
+
+    /// Model matrix translated by the negated eye position.
+    fn relative_model_matrix(&self, model_matrix: &Matrix4<f32>) -> Matrix4<f32> {
+        Matrix4::from_translation(-self.position.to_vec()) * model_matrix
+    }
+
+    /// Clip plane in camera-relative space of `relative_model_matrix()`.
+    fn relative_clip_plane(&self) -> Vector4<f32> {
+        let distance = self.clip_plane.truncate().dot(self.position.to_vec());
+        self.clip_plane + Vector4::new(0.0, 0.0, 0.0, distance)
+    }
+
+    fn from_camera(camera: &Camera, projection: Matrix4<f32>) -> Self {
+        Self {
+            projection,
+            view: camera.calculate_view_matrix(),
+            position: *camera.position(),
+            // Nothing is clipped, every point is on the positive side
+            clip_plane: Vector4::new(0.0, 0.0, 0.0, 1.0),
+            mirrored: false,
+            skybox_visible: true,
+            light_gizmo_visible: true,
+            occlusion_culling: false,
+        }
+    }
+
+    fn from_view(view: Matrix4<f32>, projection: Matrix4<f32>) -> Self {
+        let position = view.invert().map_or(Point3::origin(), |inverse| {
+            inverse.transform_point(Point3::origin())
+        });
+        Self {
+            projection,
+            view,
+            position,
+            clip_plane: Vector4::new(0.0, 0.0, 0.0, 1.0),
+            mirrored: false,
+            skybox_visible: true,
+            light_gizmo_visible: true,
+            occlusion_culling: false,
+        }
+    }
+
+    /// Eye moved sideways from the camera by `offset` in view space, looking in the same direction.
+    /// Projection is shifted horizontally to converge with the other eye at
+    /// `STEREO_CONVERGENCE_DISTANCE`.
+    fn from_stereo_eye(camera: &Camera, projection: Matrix4<f32>, offset: f32) -> Self {
+        let view = Matrix4::from_translation(Vector3::new(-offset, 0.0, 0.0))
+            * camera.calculate_view_matrix();
+        // Point ahead of the camera at convergence distance is moved back to the screen center
+        let shift = offset * projection.x.x / STEREO_CONVERGENCE_DISTANCE;
+        Self::from_view(
+            view,
+            Matrix4::from_translation(Vector3::new(shift, 0.0, 0.0)) * projection,
+        )
+    }
+
+    /// Orthographic view looking at the world origin along the direction of a directional light,
+    /// similar to what a shadow map would capture. Returns `None` for zero-length direction.
+    fn from_light_direction(light_direction: [f32; 3], aspect_ratio: f32) -> Option<Self> {
+        const DISTANCE: f32 = 10.0;
+        const HALF_HEIGHT: f32 = 3.0;
+
+        let direction = Vector3::from(light_direction);
+        if direction.magnitude2() < f32::EPSILON {
+            return None;
+        }
+        let direction = direction.normalize();
+        // Up vector must not be parallel with viewing direction
+        let up = if direction.y.abs() < 0.99 {
+            Vector3::unit_y()
+        } else {
+            Vector3::unit_z()
+        };
+        let position = Point3::origin() - direction * DISTANCE;
+        let half_width = HALF_HEIGHT * aspect_ratio;
+
+        Some(Self {
+            projection: cgmath::ortho(
+                -half_width,
+                half_width,
+                -HALF_HEIGHT,
+                HALF_HEIGHT,
+                0.1,
+                2.0 * DISTANCE,
+            ),
+            view: Matrix4::look_at_rh(position, Point3::origin(), up),
+            position,
+            clip_plane: Vector4::new(0.0, 0.0, 0.0, 1.0),
+            mirrored: false,
+            skybox_visible: false,
+            light_gizmo_visible: false,
+            occlusion_culling: false,
+        })
+    }
+
+    /// Viewpoint of a light drawing its shadow map.
+    fn from_shadow_map(
+        view: Matrix4<f32>,
+        projection: Matrix4<f32>,
+        position: Point3<f32>,
+    ) -> Self {
+        Self {
+            projection,
+            view,
+            position,
+            clip_plane: Vector4::new(0.0, 0.0, 0.0, 1.0),
+            mirrored: false,
+            skybox_visible: false,
+            light_gizmo_visible: false,
+            occlusion_culling: false,
+        }
+    }
+
+    /// View mirrored by horizontal plane at given height, seeing what the plane reflects.
+    fn mirrored_by_plane(&self, plane_height: f32) -> Self {
+        let reflection = Matrix4::from_translation(Vector3::new(0.0, plane_height, 0.0))
+            * Matrix4::from_nonuniform_scale(1.0, -1.0, 1.0)
+            * Matrix4::from_translation(Vector3::new(0.0, -plane_height, 0.0));
+        // Exclude everything below the plane from reflection
+        self.reflected(reflection, Vector4::new(0.0, 1.0, 0.0, -plane_height))
+    }
+
+    /// View seeing the world transformed by a reflection matrix. Fragments on the negative side of
+    /// the clip plane are discarded.
+    fn reflected(&self, reflection: Matrix4<f32>, clip_plane: Vector4<f32>) -> Self {
+        Self {
+            projection: self.projection,
+            view: self.view * reflection,
+            position: reflection.transform_point(self.position),
+            clip_plane,
+            mirrored: !self.mirrored,
+            skybox_visible: self.skybox_visible,
+            light_gizmo_visible: self.light_gizmo_visible,
+            // Occlusion query results are only valid for the original view
+            occlusion_culling: false,
+        }
+    }
 }
 
 impl Renderer {
@@ -21,188 +560,2344 @@ impl Renderer {
     /// capabilities.
     pub fn new(gl: Arc<glow::Context>) -> Result<Self, String> {
         unsafe {
-            println!("Running on {}", gl.get_parameter_string(glow::RENDERER));
-            println!("OpenGL version {}", gl.get_parameter_string(glow::VERSION));
+            let gl_info = GlInfo::query(&gl);
+            println!("Running on {}", gl_info.renderer);
+            println!("OpenGL version {}", gl_info.version);
+
+            cfg_if! {
+                if #[cfg(not(target_arch = "wasm32"))] {
+                    let shaders = if gl_info.supports_gl43() {
+                        &assets::shader::GL4
+                    } else {
+                        println!(
+                            "OpenGL 4.3 is unavailable, compute shaders are disabled"
+                        );
+                        &assets::shader::GLES3
+                    };
+                } else {
+                    let shaders = &assets::shader::GLES3;
+                }
+            }
+
+            // Load shaders. Variant of default material is compiled upfront to report errors of
+            // model shader sources on startup.
+            let model_shaders =
+                ShaderVariantCache::new(gl.clone(), shaders.model_vertex, shaders.model_fragment);
+            model_shaders
+                .get(material_shader_features(&Material::default()))
+                .map_err(|e| format!("model shader creation failed: {:?}", e))?;
+
+            let skybox_shader =
+                Shader::new(gl.clone(), shaders.skybox_vertex, shaders.skybox_fragment)
+                    .map_err(|e| format!("skybox shader creation failed: {:?}", e))?;
+
+            let postprocess_shader = Shader::new(
+                gl.clone(),
+                shaders.postprocess_vertex,
+                shaders.postprocess_fragment,
+            )
+            .map_err(|e| format!("post-processing shader creation failed: {:?}", e))?;
+
+            // Deferred rendering shares vertex shader with forward model drawing and fullscreen
+            // triangle with post-processing. Use of vertex colors is selected by uniform instead
+            // of shader variants.
+            let gbuffer_shader = Shader::new(
+                gl.clone(),
+                &insert_defines(shaders.model_vertex, "#define VERTEX_COLOR\n"),
+                shaders.gbuffer_fragment,
+            )
+            .map_err(|e| format!("G-buffer shader creation failed: {:?}", e))?;
+            let deferred_lighting_shader = Shader::new(
+                gl.clone(),
+                shaders.postprocess_vertex,
+                shaders.deferred_lighting_fragment,
+            )
+            .map_err(|e| format!("deferred lighting shader creation failed: {:?}", e))?;
 
-            // Load shaders
-            let model_shader = Shader::new(
+            let reflective_plane_shader = Shader::new(
                 gl.clone(),
-                &assets::shader::MODEL_VERTEX_SRC,
-                &assets::shader::MODEL_FRAGMENT_SRC,
+                shaders.reflective_plane_vertex,
+                shaders.reflective_plane_fragment,
+            )
+            .map_err(|e| format!("reflective plane shader creation failed: {:?}", e))?;
+
+            let terrain_shader =
+                Shader::new(gl.clone(), shaders.terrain_vertex, shaders.terrain_fragment)
+                    .map_err(|e| format!("terrain shader creation failed: {:?}", e))?;
+            let overdraw_shader =
+                Shader::new(gl.clone(), shaders.model_vertex, shaders.overdraw_fragment)
+                    .map_err(|e| format!("overdraw shader creation failed: {:?}", e))?;
+            let shadow_depth_shader = Shader::new(
+                gl.clone(),
+                shaders.model_vertex,
+                shaders.shadow_depth_fragment,
+            )
+            .map_err(|e| format!("shadow depth shader creation failed: {:?}", e))?;
+            let area_light_shader = Shader::new(
+                gl.clone(),
+                shaders.reflective_plane_vertex,
+                shaders.area_light_fragment,
+            )
+            .map_err(|e| format!("area light shader creation failed: {:?}", e))?;
+            let post_effect_shader = Shader::new(
+                gl.clone(),
+                shaders.postprocess_vertex,
+                shaders.post_effect_fragment,
+            )
+            .map_err(|e| format!("post effect shader creation failed: {:?}", e))?;
+            let motion_blur_shader = Shader::new(
+                gl.clone(),
+                shaders.postprocess_vertex,
+                shaders.motion_blur_fragment,
+            )
+            .map_err(|e| format!("motion blur shader creation failed: {:?}", e))?;
+            let taa_resolve_shader = Shader::new(
+                gl.clone(),
+                shaders.postprocess_vertex,
+                shaders.taa_resolve_fragment,
+            )
+            .map_err(|e| format!("TAA resolve shader creation failed: {:?}", e))?;
+
+            let label_renderer = LabelRenderer::new(gl.clone(), shaders)?;
+            let line_renderer = LineRenderer::new(gl.clone(), shaders)?;
+            let occlusion_culling = OcclusionCulling::new(gl.clone(), shaders)?;
+
+            #[cfg(not(target_arch = "wasm32"))]
+            let vertex_displacement = if gl_info.supports_gl43() {
+                Some(VertexDisplacement::new(gl.clone())?)
+            } else {
+                None
+            };
+            #[cfg(not(target_arch = "wasm32"))]
+            let pipeline_statistics = if gl_info.supports_pipeline_statistics() {
+                Some(PipelineStatistics::new(gl.clone())?)
+            } else {
+                None
+            };
+            #[cfg(not(target_arch = "wasm32"))]
+            let geometry_debug_shaders = if gl_info.supports_gl43() {
+                Some(GeometryDebugShaders::new(&gl)?)
+            } else {
+                None
+            };
+
+            let samples = gl.get_parameter_i32(glow::SAMPLES);
+            let window_targets = RenderTargets::new(&gl, samples)?;
+
+            let fullscreen_vertex_array = gl_resources::create_vertex_array(&gl)
+                .map_err(|e| format!("cannot create fullscreen vertex array: {e}"))?;
+            let mesh_batches = MeshBatches::new(gl.clone());
+            let shadow_maps = ShadowMaps::new(gl.clone());
+            let point_shadow_maps = PointShadowMaps::new(gl.clone());
+
+            // Customize OpenGL capabilities
+            gl_state::enable(&gl, glow::BLEND);
+            gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+            // Back-face culling is a per-model setting enabled during model draw, because the
+            // display of open meshes like the Utah Teapot where you can look into the inside
+            // would be bugged.
+            gl_state::disable(&gl, glow::CULL_FACE);
+            gl.cull_face(glow::BACK);
+
+            Ok(Self {
+                gl,
+                gl_info,
+                projection: Matrix4::identity(),
+                skybox_shader,
+                model_shaders,
+                custom_shaders: BTreeMap::new(),
+                shaders,
+                postprocess_shader,
+                gbuffer_shader,
+                deferred_lighting_shader,
+                reflective_plane_shader,
+                terrain_shader,
+                overdraw_shader,
+                shadow_depth_shader,
+                area_light_shader,
+                post_effect_shader,
+                motion_blur_shader,
+                taa_resolve_shader,
+                shadow_maps,
+                point_shadow_maps,
+                decal_depth_atlas: None,
+                label_renderer,
+                line_renderer,
+                samples,
+                window_size: (1, 1),
+                render_scale: 1.0,
+                scale_factor: 1.0,
+                window_targets,
+                texture_target: None,
+                light_view_targets: None,
+                stereo_targets: None,
+                temporal_history: None,
+                #[cfg(not(target_arch = "wasm32"))]
+                vertex_displacement,
+                #[cfg(not(target_arch = "wasm32"))]
+                multi_draw_indirect: None,
+                #[cfg(not(target_arch = "wasm32"))]
+                pipeline_statistics,
+                #[cfg(not(target_arch = "wasm32"))]
+                geometry_debug_shaders,
+                fullscreen_vertex_array,
+                render_hooks: Vec::new(),
+                occlusion_culling,
+                mesh_batches,
+                stats: RenderStats::default(),
+                draw_stats: Cell::new(DrawStats::default()),
+                frame_count: 0,
+                previous_camera_view: None,
+            })
+        }
+    }
+
+    /// Setup viewport, clear screen and draw visible entities of the scene seen from its camera
+    pub fn draw(
+        &mut self,
+        window: &Window,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+    ) {
+        let framebuffer_size = window.inner_size();
+        self.draw_to_size(
+            framebuffer_size.width,
+            framebuffer_size.height,
+            draw_props,
+            scene,
+            skybox,
+        );
+    }
+
+    /// Same as `draw()` into a default framebuffer of the given size, e.g. of a window not created
+    /// by winit when embedded into another application.
+    pub fn draw_to_size(
+        &mut self,
+        width: u32,
+        height: u32,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+    ) {
+        profile_scope!("Renderer::draw");
+        // Embedding applications change state between frames behind the cache
+        gl_state::invalidate();
+        // Placed between logic updates, matching the entities
+        let camera = &scene.interpolated_camera();
+        self.frame_count = self.frame_count.wrapping_add(1);
+
+        self.render_scale = draw_props.render_scale.clamp(MIN_RENDER_SCALE, 1.0);
+        // Labels keep their size on screen when the scene is rendered at lower resolution
+        self.label_renderer
+            .set_scale_factor(self.scale_factor * self.render_scale);
+        // Update viewport because of Field of View change
+        self.resize(width, height, draw_props.field_of_view);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(vertex_displacement) = &mut self.vertex_displacement {
+            if draw_props.vertex_displacement_enabled {
+                vertex_displacement.apply(
+                    &scene.models,
+                    draw_props.selected_model_index,
+                    draw_props.vertex_displacement_amplitude,
+                );
+            } else {
+                vertex_displacement.restore(&scene.models);
+            }
+        }
+        self.update_mesh_batches(draw_props, scene);
+        self.draw_stats.set(DrawStats::default());
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(pipeline_statistics) = &mut self.pipeline_statistics {
+            pipeline_statistics.begin_frame();
+        }
+
+        // Hooks are moved out during the frame, because scene rendering borrows the renderer
+        let mut render_hooks = std::mem::take(&mut self.render_hooks);
+        for render_hook in &mut render_hooks {
+            render_hook.on_before_scene(&self.gl, camera, &self.projection);
+        }
+        // Hooks may change any state behind the cache
+        gl_state::invalidate();
+
+        let mut camera_view = SceneView::from_camera(camera, self.projection);
+        // Query results of a single viewpoint don't apply to both eyes
+        if draw_props.occlusion_culling_enabled && draw_props.stereo_mode == StereoMode::Off {
+            camera_view.occlusion_culling = true;
+            self.occlusion_culling.update_results();
+        } else {
+            self.occlusion_culling.clear();
+        }
+        self.stats = self.collect_stats(&camera_view, draw_props, scene);
+        self.update_shadow_maps(&camera_view, draw_props, scene);
+        // Reprojection between frames ignores jitter, history is stored without it
+        let reprojection = self
+            .previous_camera_view
+            .and_then(|previous| camera_view.reprojection_from(previous));
+        let unjittered_camera_view = (camera_view.relative_projection_view(), camera_view.position);
+        self.update_temporal_history(draw_props);
+        if self.temporal_history.is_some() {
+            camera_view.projection = taa::jittered_projection(
+                camera_view.projection,
+                self.frame_count,
+                self.window_targets.scene.width(),
+                self.window_targets.scene.height(),
+            );
+        }
+        match draw_props.stereo_mode {
+            StereoMode::Off => {
+                self.render_scene(
+                    &self.window_targets,
+                    &camera_view,
+                    draw_props,
+                    scene,
+                    skybox,
+                    || {
+                        for render_hook in &mut render_hooks {
+                            render_hook.on_after_scene(&self.gl, camera, &self.projection);
+                        }
+                        gl_state::invalidate();
+                    },
+                );
+                let scene_output = match &self.temporal_history {
+                    Some(history) => {
+                        self.resolve_temporal_anti_aliasing(
+                            history,
+                            &self.window_targets.scene,
+                            draw_props,
+                            reprojection,
+                        );
+                        history.current()
+                    }
+                    None => &self.window_targets.scene,
+                };
+                let output = self.apply_post_effects(
+                    &self.window_targets,
+                    scene_output,
+                    draw_props,
+                    reprojection,
+                );
+                unsafe {
+                    self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+                    self.gl
+                        .viewport(0, 0, self.window_size.0 as i32, self.window_size.1 as i32);
+                }
+                // Scene texture is stretched over the window when rendered at lower resolution
+                self.draw_postprocess(output, draw_props);
+            }
+            stereo_mode => {
+                self.draw_stereo(stereo_mode, draw_props, scene, skybox, &mut render_hooks);
+            }
+        }
+        // Each eye would need its own history, stereo output is not blurred or temporally
+        // anti-aliased
+        self.previous_camera_view =
+            (draw_props.stereo_mode == StereoMode::Off).then_some(unjittered_camera_view);
+        if let Some(history) = &mut self.temporal_history {
+            history.advance();
+        }
+        self.render_hooks = render_hooks;
+
+        if draw_props.light_view_enabled {
+            self.draw_light_view_inset(draw_props, scene, skybox);
+        }
+        self.label_renderer.set_scale_factor(self.scale_factor);
+        self.stats.draws = self.draw_stats.get();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(pipeline_statistics) = &mut self.pipeline_statistics {
+            pipeline_statistics.end_frame();
+            self.stats.pipeline = pipeline_statistics.latest();
+        }
+    }
+
+    /// Render scene seen from camera into a texture of given size instead of the window, e.g. for
+    /// displaying a second viewpoint inside a GUI widget or for further processing.
+    ///
+    /// Output is post-processed the same way as window contents and stored with sRGB encoding
+    /// (`SRGB8_ALPHA8`), sampling it returns linear color values.
+    ///
+    /// Returned texture is owned by the renderer. It stays valid until the renderer is dropped, but
+    /// its contents are overwritten by the next call. Reallocating storage on size change doesn't
+    /// change texture handle.
+    pub fn render_to_texture(
+        &mut self,
+        camera: &Camera,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+        width: u32,
+        height: u32,
+    ) -> Result<glow::Texture, String> {
+        let projection =
+            calculate_projection_matrix(draw_props.field_of_view, width.max(1), height.max(1));
+        self.render_view_to_texture(
+            &SceneView::from_camera(camera, projection),
+            draw_props,
+            scene,
+            skybox,
+            width,
+            height,
+        )
+    }
+
+    /// Render scene seen from an eye of a stereo display into a texture, with view and projection
+    /// matrices provided by the display, e.g. by a VR runtime. Otherwise the same as
+    /// `render_to_texture()`.
+    pub fn render_eye_to_texture(
+        &mut self,
+        eye: &EyeView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+        width: u32,
+        height: u32,
+    ) -> Result<glow::Texture, String> {
+        self.render_view_to_texture(
+            &SceneView::from_view(eye.view, eye.projection),
+            draw_props,
+            scene,
+            skybox,
+            width,
+            height,
+        )
+    }
+
+    /// Copy texture contents drawn by the last `render_to_texture()` or `render_eye_to_texture()`
+    /// call into another framebuffer at the given offset, e.g. into one eye's half of a VR
+    /// swapchain image.
+    pub fn blit_texture_output(&self, target: glow::Framebuffer, x: i32, y: i32) {
+        if let Some(texture_target) = &self.texture_target {
+            texture_target.output.blit_into(target, x, y);
+        }
+    }
+
+    /// Draw the scene with other draw properties over the part of the window right of the split
+    /// position, given relative to the window width, after `draw()`, e.g. for comparing shading
+    /// modes side by side. Overwrites output of `render_to_texture()`.
+    pub fn draw_comparison(
+        &mut self,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+        split: f32,
+    ) -> Result<(), String> {
+        let (width, height) = self.window_size;
+        self.render_to_texture(
+            &scene.interpolated_camera(),
+            draw_props,
+            scene,
+            skybox,
+            width,
+            height,
+        )?;
+        let x = (split.clamp(0.0, 1.0) * width as f32).round() as i32;
+        self.texture_target
+            .as_ref()
+            .unwrap()
+            .output
+            .blit_right_of_into_default(x);
+        Ok(())
+    }
+
+    fn render_view_to_texture(
+        &mut self,
+        scene_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+        width: u32,
+        height: u32,
+    ) -> Result<glow::Texture, String> {
+        // GUI painter of the host application has the context between calls, e.g. with widgets
+        gl_state::invalidate();
+        self.prepare_texture_target(width, height)?;
+        self.update_mesh_batches(draw_props, scene);
+        self.update_shadow_maps(scene_view, draw_props, scene);
+        let texture_target = self.texture_target.as_ref().unwrap();
+        self.render_scene(
+            &texture_target.targets,
+            scene_view,
+            draw_props,
+            scene,
+            skybox,
+            || (),
+        );
+        let output = self.apply_post_effects(
+            &texture_target.targets,
+            &texture_target.targets.scene,
+            draw_props,
+            None,
+        );
+        texture_target.output.bind();
+        self.draw_postprocess(output, draw_props);
+        unsafe {
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+
+        Ok(texture_target.output.color_texture())
+    }
+
+    /// Render scene seen from an eye of a WebXR headset into its viewport of the framebuffer
+    /// provided by the WebGL layer of the session.
+    #[cfg(target_arch = "wasm32")]
+    pub fn draw_eye_into(
+        &mut self,
+        eye: &EyeView,
+        framebuffer: &web_sys::WebGlFramebuffer,
+        viewport: [i32; 4],
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+    ) -> Result<(), String> {
+        let [x, y, width, height] = viewport;
+        self.prepare_texture_target(width as u32, height as u32)?;
+        self.update_mesh_batches(draw_props, scene);
+        let eye_view = SceneView::from_view(eye.view, eye.projection);
+        self.update_shadow_maps(&eye_view, draw_props, scene);
+
+        let texture_target = self.texture_target.as_ref().unwrap();
+        self.render_scene(
+            &texture_target.targets,
+            &eye_view,
+            draw_props,
+            scene,
+            skybox,
+            || (),
+        );
+        let output = self.apply_post_effects(
+            &texture_target.targets,
+            &texture_target.targets.scene,
+            draw_props,
+            None,
+        );
+        // Postprocess directly into the layer instead of blitting the output texture, blits from
+        // sRGB textures decode colors back to linear
+        unsafe {
+            self.gl
+                .bind_external_framebuffer(glow::FRAMEBUFFER, framebuffer);
+            self.gl.viewport(x, y, width, height);
+            gl_state::disable(&self.gl, glow::DEPTH_TEST);
+            self.postprocess_shader.r#use();
+            // WebXR layer framebuffers are not sRGB-capable, same as the default framebuffer
+            self.postprocess_shader.set_uniform("u_encodeSrgb", &true);
+        }
+        self.draw_postprocess_triangle(output, draw_props);
+        unsafe {
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+        Ok(())
+    }
+
+    /// Create, resize or release the history of temporal anti-aliasing of the window, depending on
+    /// whether it is in use.
+    fn update_temporal_history(&mut self, draw_props: &DrawProperties) {
+        // Heatmap colors encode fragment counts
+        let enabled = draw_props.anti_aliasing == AntiAliasing::Temporal
+            && draw_props.stereo_mode == StereoMode::Off
+            && !draw_props.overdraw_heatmap_enabled;
+        if !enabled {
+            self.temporal_history = None;
+            return;
+        }
+        let (width, height) = (
+            self.window_targets.scene.width(),
+            self.window_targets.scene.height(),
+        );
+        let result = match &mut self.temporal_history {
+            Some(history) => history.resize(width, height),
+            None => TemporalHistory::new(self.gl.clone(), width, height)
+                .map(|history| self.temporal_history = Some(history)),
+        };
+        if let Err(e) = result {
+            eprintln!("unable to prepare temporal anti-aliasing history: {e}");
+        }
+    }
+
+    /// Merge small meshes of the scene, except the one modified by vertex displacement.
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    fn update_mesh_batches(&mut self, draw_props: &DrawProperties, scene: &Scene) {
+        cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
+            let displaced_mesh = scene
+                .models
+                .get(draw_props.selected_model_index)
+                .filter(|_| {
+                    self.vertex_displacement.is_some() && draw_props.vertex_displacement_enabled
+                })
+                .map(Model::mesh);
+        } else {
+            let displaced_mesh = None;
+        }}
+        self.mesh_batches.update(&scene.models, displaced_mesh);
+    }
+
+    /// Fit shadow cascades of the directional light around the viewpoint and draw models into
+    /// shadow maps of every light as seen from the light, and into depth maps of decal projectors.
+    /// Shadow maps are released while shadows are disabled, depth maps while there are no decals.
+    fn update_shadow_maps(
+        &mut self,
+        scene_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+    ) {
+        profile_scope!("Renderer::update_shadow_maps");
+        if draw_props.overdraw_heatmap_enabled {
+            self.shadow_maps.clear();
+            self.point_shadow_maps.clear();
+            self.decal_depth_atlas = None;
+            return;
+        }
+        let settings = &draw_props.shadow_settings;
+        if !settings.enabled {
+            self.shadow_maps.clear();
+        } else if let Err(e) = self.shadow_maps.update(
+            settings,
+            &scene_view.view,
+            &scene_view.projection,
+            scene.light_direction(),
+            scene.visible_bounds(),
+        ) {
+            eprintln!("Failed to update shadow maps: {e}");
+            self.shadow_maps.clear();
+        }
+        if let Err(e) = self.point_shadow_maps.update(&scene.point_lights()) {
+            eprintln!("Failed to update point light shadow maps: {e}");
+            self.point_shadow_maps.clear();
+        }
+        let decals = scene.drawn_decals();
+        if decals.is_empty() {
+            self.decal_depth_atlas = None;
+        } else if self.decal_depth_atlas.is_none() {
+            match DepthFramebuffer::new(self.gl.clone(), 2 * DECAL_DEPTH_RESOLUTION) {
+                Ok(atlas) => self.decal_depth_atlas = Some(atlas),
+                Err(e) => eprintln!("Failed to create decal depth maps: {e}"),
+            }
+        }
+
+        self.shadow_depth_shader.r#use();
+        unsafe {
+            gl_state::enable(&self.gl, glow::DEPTH_TEST);
+            // Both sides of open meshes cast shadows, depth is pushed away from the light to keep
+            // lit surfaces from shadowing themselves
+            gl_state::disable(&self.gl, glow::CULL_FACE);
+            gl_state::enable(&self.gl, glow::POLYGON_OFFSET_FILL);
+            self.gl.polygon_offset(2.0, 4.0);
+
+            for (cascade, target) in self.shadow_maps.cascades() {
+                target.bind();
+                self.gl.clear(glow::DEPTH_BUFFER_BIT);
+                self.draw_shadow_casters(
+                    &SceneView::from_shadow_map(cascade.view, cascade.projection, cascade.position),
+                    draw_props,
+                    scene,
+                );
+            }
+            for (point_light, target) in self.point_shadow_maps.lights() {
+                let projection = shadow::cube_face_projection(point_light.range);
+                let position = Point3::from_vec(point_light.position);
+                for (face, view) in shadow::cube_face_views(position).into_iter().enumerate() {
+                    target.bind_face(face as u32);
+                    self.gl.clear(glow::DEPTH_BUFFER_BIT);
+                    self.draw_shadow_casters(
+                        &SceneView::from_shadow_map(view, projection, position),
+                        draw_props,
+                        scene,
+                    );
+                }
+            }
+            if let Some(atlas) = &self.decal_depth_atlas {
+                atlas.bind();
+                self.gl.clear(glow::DEPTH_BUFFER_BIT);
+                let resolution = DECAL_DEPTH_RESOLUTION as i32;
+                for (index, decal) in decals.iter().enumerate() {
+                    // Quadrants of the atlas from the bottom left, row by row
+                    let (column, row) = (index as i32 % 2, index as i32 / 2);
+                    self.gl.viewport(
+                        column * resolution,
+                        row * resolution,
+                        resolution,
+                        resolution,
+                    );
+                    self.draw_shadow_casters(
+                        &SceneView::from_shadow_map(
+                            decal.view_matrix(),
+                            decal.projection_matrix(),
+                            Point3::from_vec(decal.position),
+                        ),
+                        draw_props,
+                        scene,
+                    );
+                }
+            }
+
+            // Reset state
+            gl_state::disable(&self.gl, glow::POLYGON_OFFSET_FILL);
+            gl_state::bind_vertex_array(&self.gl, None);
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+    }
+
+    /// Draw depth of every visible renderable entity into the currently bound shadow map with the
+    /// shadow depth shader in use.
+    unsafe fn draw_shadow_casters(
+        &self,
+        light_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+    ) {
+        let mut query = scene.world.query::<EntityComponents>().with::<&Visible>();
+        let draws = self.sorted_model_draws(light_view, draw_props, scene, query.iter());
+        for draw in &draws {
+            gl_state::bind_vertex_array(&self.gl, Some(draw.vertex_array()));
+            let relative_model_matrix = light_view.relative_model_matrix(&draw.model_matrix);
+            let mvp = light_view.relative_projection_view() * relative_model_matrix;
+            self.shadow_depth_shader.set_uniform("u_mvp", &mvp);
+            self.draw_model_mesh(draw);
+        }
+    }
+
+    /// Create offscreen targets of texture rendering on first use and resize them.
+    fn prepare_texture_target(&mut self, width: u32, height: u32) -> Result<(), String> {
+        if self.texture_target.is_none() {
+            let targets = RenderTargets::new(&self.gl, self.samples)?;
+            let output = Framebuffer::new(self.gl.clone(), 1, 1, ColorFormat::Srgb8Alpha8)
+                .map_err(|e| format!("texture output framebuffer creation failed: {e}"))?;
+            self.texture_target = Some(TextureTarget { targets, output });
+        }
+        let texture_target = self.texture_target.as_mut().unwrap();
+        texture_target.targets.resize(width, height)?;
+        texture_target
+            .output
+            .resize(width, height)
+            .map_err(|e| format!("unable to resize texture output framebuffer: {e}"))
+    }
+
+    /// Save window contents drawn by the last `draw()` call into an image file, with format selected
+    /// by file extension. GUI is not part of the image if it's painted after this call.
+    ///
+    /// Must be called before buffers are swapped, contents of the back buffer are undefined after
+    /// that.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_screenshot(&self, path: &str) -> Result<(), String> {
+        unsafe {
+            self.gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        }
+        self.save_read_framebuffer(path, self.window_size.0, self.window_size.1)
+    }
+
+    /// Encode window contents drawn by the last `draw()` call into PNG file contents, e.g. for
+    /// answering HTTP requests. Same restrictions apply as to `save_screenshot()`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn screenshot_png(&self) -> Result<Vec<u8>, String> {
+        unsafe {
+            self.gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        }
+        let (width, height) = self.window_size;
+        let pixels = self.read_framebuffer(width, height);
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::write_buffer_with_format(
+            &mut png,
+            &pixels,
+            width,
+            height,
+            image::ColorType::Rgb8,
+            image::ImageFormat::Png,
+        )
+        .map_err(|e| format!("cannot encode screenshot: {e}"))?;
+        Ok(png.into_inner())
+    }
+
+    /// Save texture contents drawn by the last `render_to_texture()` call into an image file, with
+    /// format selected by file extension.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_texture_output(&self, path: &str) -> Result<(), String> {
+        let output = &self
+            .texture_target
+            .as_ref()
+            .ok_or("nothing has been rendered to texture yet")?
+            .output;
+        output.bind_read();
+        let result = self.save_read_framebuffer(path, output.width(), output.height());
+        unsafe {
+            self.gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        }
+        result
+    }
+
+    /// RGB pixels of texture contents drawn by the last `render_to_texture()` call, top row first,
+    /// e.g. for handing over to image processing libraries.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_texture_output(&self) -> Result<Vec<u8>, String> {
+        let output = &self
+            .texture_target
+            .as_ref()
+            .ok_or("nothing has been rendered to texture yet")?
+            .output;
+        output.bind_read();
+        let pixels = self.read_framebuffer(output.width(), output.height());
+        unsafe {
+            self.gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        }
+        Ok(pixels)
+    }
+
+    /// Read pixels of the framebuffer bound for reading and save them into an image file.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_read_framebuffer(&self, path: &str, width: u32, height: u32) -> Result<(), String> {
+        let pixels = self.read_framebuffer(width, height);
+        image::save_buffer(path, &pixels, width, height, image::ColorType::Rgb8)
+            .map_err(|e| format!("cannot save image {path}: {e}"))
+    }
+
+    /// RGB pixels of the framebuffer bound for reading, top row first like in image files.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_framebuffer(&self, width: u32, height: u32) -> Vec<u8> {
+        let row_size = width as usize * 3;
+        let mut pixels = vec![0; row_size * height as usize];
+        unsafe {
+            // Rows of RGB pixels are not aligned to 4 bytes
+            self.gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+            self.gl.read_pixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                glow::RGB,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut pixels),
+            );
+            self.gl.pixel_store_i32(glow::PACK_ALIGNMENT, 4);
+        }
+
+        // OpenGL stores the bottom row first, image files the top row
+        pixels
+            .chunks_exact(row_size)
+            .rev()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// Projection matrix of the window viewport, e.g. for mapping cursor position into the scene.
+    pub fn projection(&self) -> Matrix4<f32> {
+        self.projection
+    }
+
+    /// Register custom draw code called on every rendered frame in order of registration.
+    /// Draw models merged into shared buffers with `glMultiDrawElementsIndirect()` when
+    /// `DrawProperties::multi_draw_indirect_enabled` is set. The entry point isn't loaded by glow,
+    /// it's looked up with the loader function of the context. Requires OpenGL 4.3.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_multi_draw_indirect(
+        &mut self,
+        get_proc_address: impl Fn(&std::ffi::CStr) -> *const std::ffi::c_void,
+    ) -> Result<(), String> {
+        if !self.gl_info.supports_gl43() {
+            return Err("multi-draw indirect requires OpenGL 4.3".to_string());
+        }
+        let multi_draw_indirect = MultiDrawIndirect::new(self.gl.clone(), get_proc_address)?;
+        self.multi_draw_indirect = Some(RefCell::new(multi_draw_indirect));
+        Ok(())
+    }
+
+    pub fn add_render_hook(&mut self, render_hook: Box<dyn RenderHook>) {
+        self.render_hooks.push(render_hook);
+    }
+
+    /// Compile a shader from GLSL sources and draw entities with it whose material refers to
+    /// `name`, replacing an earlier shader of the same name. The earlier shader is kept when
+    /// compilation fails.
+    ///
+    /// Custom shaders receive the same vertex attributes and uniforms as built-in model shading,
+    /// see `custom_shader_template()`. They are used with forward rendering only, models are drawn
+    /// with built-in shading by the deferred render path.
+    pub fn register_shader(
+        &mut self,
+        name: &str,
+        vertex_source: &str,
+        fragment_source: &str,
+    ) -> Result<(), String> {
+        if name.is_empty() {
+            return Err("custom shader name is empty".to_string());
+        }
+        let shader = Shader::new(self.gl.clone(), vertex_source, fragment_source)
+            .map_err(|e| format!("custom shader '{name}' creation failed: {e}"))?;
+        self.custom_shaders.insert(
+            name.to_owned(),
+            CustomShader {
+                shader: Rc::new(shader),
+                vertex_source: vertex_source.to_owned(),
+                fragment_source: fragment_source.to_owned(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Names of registered custom shaders in alphabetical order.
+    pub fn custom_shader_names(&self) -> impl Iterator<Item = &str> {
+        self.custom_shaders.keys().map(String::as_str)
+    }
+
+    /// Vertex and fragment source of the registered custom shader.
+    pub fn custom_shader_sources(&self, name: &str) -> Option<(&str, &str)> {
+        self.custom_shaders.get(name).map(|custom_shader| {
+            (
+                custom_shader.vertex_source.as_str(),
+                custom_shader.fragment_source.as_str(),
             )
-            .map_err(|e| format!("model shader creation failed: {:?}", e))?;
+        })
+    }
+
+    /// Vertex and fragment source to start writing custom shaders from, in the GLSL dialect of the
+    /// context.
+    pub fn custom_shader_template(&self) -> (&'static str, &'static str) {
+        (self.shaders.custom_vertex, self.shaders.custom_fragment)
+    }
+
+    /// Replace the fragment shader source of forward model shading, from which shader variants of
+    /// materials are compiled. Variants used so far are compiled upfront, the current source is
+    /// kept when any of them fails.
+    ///
+    /// Line numbers of compiler messages match the lines of the source.
+    pub fn set_model_fragment_source(&mut self, source: &str) -> Result<(), String> {
+        let model_shaders =
+            ShaderVariantCache::new(self.gl.clone(), self.shaders.model_vertex, source);
+        let mut features = self.model_shaders.requested_features();
+        features.push(material_shader_features(&Material::default()));
+        for features in features {
+            model_shaders.get(features)?;
+        }
+        self.model_shaders = model_shaders;
+        Ok(())
+    }
+
+    /// Fragment shader source of forward model shading currently in use.
+    pub fn model_fragment_source(&self) -> &str {
+        self.model_shaders.fragment_shader_src()
+    }
+
+    /// Fragment shader source of forward model shading the renderer was created with.
+    pub fn builtin_model_fragment_source(&self) -> &'static str {
+        self.shaders.model_fragment
+    }
+
+    /// Levels of detail and occlusion culling results of the last frame.
+    pub fn stats(&self) -> &RenderStats {
+        &self.stats
+    }
+
+    /// Capabilities of the OpenGL implementation queried on creation.
+    pub fn gl_info(&self) -> &GlInfo {
+        &self.gl_info
+    }
+
+    /// Screen-space overlays like labels are sized in logical pixels, scaled by the scale factor of
+    /// the display to physical pixels.
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+        self.label_renderer.set_scale_factor(scale_factor);
+    }
+
+    pub fn resize(&mut self, physical_width: u32, physical_height: u32, field_of_view: f32) {
+        // Always query framebuffer size even if the window is not resizable. You'll
+        // never know how framebuffer size might differ from window size, especially
+        // on high-DPI displays. Not doing so can lead to display bugs like clipping
+        // top part of the view.
+        //
+        // Physical screen size means the actual count of pixels taking DPI into account.
+        self.window_size = (physical_width, physical_height);
+        if let Err(e) = self.window_targets.resize(
+            self.scaled_size(physical_width),
+            self.scaled_size(physical_height),
+        ) {
+            eprintln!("{e}");
+        }
+
+        unsafe {
+            self.gl
+                .viewport(0, 0, physical_width as i32, physical_height as i32);
+        }
+        self.projection =
+            calculate_projection_matrix(field_of_view, physical_width, physical_height);
+    }
+
+    /// Size in pixels of scene targets covering `size` pixels of the window.
+    fn scaled_size(&self, size: u32) -> u32 {
+        ((size as f32 * self.render_scale).round() as u32).max(1)
+    }
+
+    /// Draw scene seen from the viewpoint into scene framebuffer of render targets.
+    ///
+    /// `after_scene` is called when every entity is drawn, while the framebuffer holding the scene
+    /// is still bound.
+    fn render_scene(
+        &self,
+        targets: &RenderTargets,
+        camera_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+        after_scene: impl FnOnce(),
+    ) {
+        profile_scope!("Renderer::render_scene");
+        if draw_props.overdraw_heatmap_enabled {
+            // Debug lines, labels and reflections would only add noise to the heatmap
+            targets.scene.bind();
+            self.draw_overdraw(camera_view, draw_props, scene);
+            after_scene();
+            return;
+        }
+        let light_direction = scene.light_direction();
+
+        // Mirror plane is only visible from above
+        let reflective_plane_visible = draw_props.reflective_plane_enabled
+            && !draw_props.reduced_effects
+            && draw_props.reflective_plane_height < camera_view.position.y;
+        if reflective_plane_visible {
+            targets.reflection.bind();
+            let reflection_view = camera_view.mirrored_by_plane(draw_props.reflective_plane_height);
+            self.draw_scene(&reflection_view, draw_props, scene, skybox);
+        }
+
+        // Render scene offscreen
+        let multisample = match draw_props.render_path {
+            RenderPath::Forward => {
+                let multisample = targets
+                    .multisample
+                    .as_ref()
+                    .filter(|_| draw_props.anti_aliasing == AntiAliasing::Multisample);
+                match multisample {
+                    Some(multisample) => multisample.bind(),
+                    None => targets.scene.bind(),
+                }
+                self.draw_scene(camera_view, draw_props, scene, skybox);
+                multisample
+            }
+            RenderPath::Deferred => {
+                self.draw_scene_deferred(targets, camera_view, draw_props, scene, skybox);
+                None
+            }
+        };
+        if reflective_plane_visible {
+            self.draw_reflective_plane(&targets.reflection, camera_view, draw_props);
+        }
+        if draw_props.mirror_enabled
+            && !draw_props.reduced_effects
+            && mirror::is_facing(camera_view.position)
+        {
+            self.draw_stencil_mirror(camera_view, draw_props, scene, skybox);
+        }
+        if camera_view.occlusion_culling {
+            self.issue_occlusion_queries(camera_view, scene);
+        }
+        self.draw_area_lights(camera_view, scene);
+        if draw_props.light_gizmo_enabled && camera_view.light_gizmo_visible {
+            self.line_renderer.draw(
+                &gizmo::light_gizmo_lines(light_direction),
+                &(camera_view.projection * camera_view.view),
+            );
+            for (_, point_light) in scene.point_lights() {
+                self.line_renderer.draw(
+                    &gizmo::point_light_gizmo_lines(&point_light),
+                    &(camera_view.projection * camera_view.view),
+                );
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(shaders) = self
+            .geometry_debug_shaders
+            .as_ref()
+            .filter(|_| draw_props.face_normals_enabled)
+        {
+            self.draw_face_normals(&shaders.face_normals, camera_view, draw_props, scene);
+        }
+        if draw_props.bounding_box_enabled || draw_props.bounding_sphere_enabled {
+            self.draw_selection_bounds(camera_view, draw_props, scene);
+        }
+        if draw_props.decal_volumes_enabled && camera_view.light_gizmo_visible {
+            let vertices: Vec<_> = scene
+                .drawn_decals()
+                .iter()
+                .flat_map(Decal::volume_lines)
+                .collect();
+            self.line_renderer
+                .draw(&vertices, &(camera_view.projection * camera_view.view));
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if draw_props.mesh_problems_enabled {
+            self.draw_mesh_problems(camera_view, draw_props, scene);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(inspected) = scene.inspector.hovered() {
+            self.line_renderer.draw(
+                &inspected.lines(),
+                &(camera_view.projection * camera_view.view),
+            );
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if scene.measurement.enabled {
+            let projection_view = camera_view.projection * camera_view.view;
+            self.line_renderer.draw(
+                &scene.measurement.lines(camera_view.position),
+                &projection_view,
+            );
+            self.label_renderer.draw(
+                &scene.measurement.labels(),
+                &projection_view,
+                targets.scene.width(),
+                targets.scene.height(),
+            );
+        }
+        if draw_props.labels_enabled {
+            self.label_renderer.draw(
+                &scene_labels(
+                    light_direction,
+                    &scene.models[draw_props.selected_model_index].name,
+                ),
+                &(camera_view.projection * camera_view.view),
+                targets.scene.width(),
+                targets.scene.height(),
+            );
+        }
+        after_scene();
+
+        if let Some(multisample) = multisample {
+            multisample.resolve(&targets.scene);
+        }
+    }
+
+    /// Draw the scene reflected by the demo mirror into the currently bound scene framebuffer,
+    /// masked by the stencil buffer to the visible part of the mirror surface.
+    ///
+    /// 1. Mark pixels of the mirror surface passing the depth test with 1 in the stencil buffer.
+    /// 2. Reset depth of marked pixels to the far plane, removing what is behind the mirror.
+    /// 3. Draw entities and skybox seen in the mirror into marked pixels only.
+    /// 4. Write depth of the mirror surface into marked pixels and tint them like glass.
+    fn draw_stencil_mirror(
+        &self,
+        camera_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+    ) {
+        let projection_view = camera_view.projection * camera_view.view;
+        let surface = mirror::surface_vertices();
+
+        unsafe {
+            gl_state::enable(&self.gl, glow::STENCIL_TEST);
+            self.gl.stencil_mask(0xFF);
+            self.gl.clear_stencil(0);
+            self.gl.clear(glow::STENCIL_BUFFER_BIT);
+
+            // 1. Mark visible mirror surface
+            self.gl.stencil_func(glow::ALWAYS, 1, 0xFF);
+            self.gl.stencil_op(glow::KEEP, glow::KEEP, glow::REPLACE);
+            self.gl.color_mask(false, false, false, false);
+            self.gl.depth_mask(false);
+            self.line_renderer
+                .draw_triangles(&surface, &projection_view);
+
+            // 2. Depth range squeezes every fragment onto the far plane
+            self.gl.stencil_func(glow::EQUAL, 1, 0xFF);
+            self.gl.stencil_op(glow::KEEP, glow::KEEP, glow::KEEP);
+            self.gl.depth_mask(true);
+            self.gl.depth_func(glow::ALWAYS);
+            self.gl.depth_range_f32(1.0, 1.0);
+            self.line_renderer
+                .draw_triangles(&surface, &projection_view);
+            self.gl.depth_range_f32(0.0, 1.0);
+            self.gl.depth_func(glow::LESS);
+            self.gl.color_mask(true, true, true, true);
+        }
+
+        // 3. Reflected scene
+        let mirror_view = camera_view.reflected(mirror::reflection_matrix(), mirror::CLIP_PLANE);
+        self.draw_models(&mirror_view, draw_props, scene, skybox);
+        if draw_props.skybox_enabled {
+            self.draw_skybox(&mirror_view, skybox);
+        }
+
+        unsafe {
+            // 4. Mirror surface
+            self.gl.depth_func(glow::ALWAYS);
+            gl_state::enable(&self.gl, glow::BLEND);
+            self.gl
+                .blend_func(glow::CONSTANT_ALPHA, glow::ONE_MINUS_CONSTANT_ALPHA);
+            self.gl.blend_color(0.0, 0.0, 0.0, mirror::TINT_ALPHA);
+            self.line_renderer
+                .draw_triangles(&surface, &projection_view);
+
+            // Reset state
+            gl_state::disable(&self.gl, glow::BLEND);
+            self.gl.depth_func(glow::LESS);
+            gl_state::disable(&self.gl, glow::STENCIL_TEST);
+        }
+    }
+
+    /// Levels of detail and occlusion culling results of entities drawn from the viewpoint.
+    fn collect_stats(
+        &self,
+        scene_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+    ) -> RenderStats {
+        let mut stats = RenderStats::default();
+        if scene_view.occlusion_culling {
+            stats.occlusion.queried_count = self.occlusion_culling.queried_count();
+        }
+        let selected_entity = scene.selected_entity(draw_props.selected_model_index);
+        let mut query = scene
+            .world
+            .query::<(&Renderable, &Transform)>()
+            .with::<&Visible>();
+        for (entity, (renderable, transform)) in query.iter() {
+            let model = &scene.models[renderable.model_index];
+            let model_matrix = scene.interpolated_model_matrix(entity, transform);
+            let level = select_lod(
+                scene_view,
+                draw_props,
+                renderable.model_index,
+                model,
+                &model_matrix,
+            );
+            let triangle_count = model.lod(level).index_count() / 3;
+            if scene_view.occlusion_culling && self.occlusion_culling.is_occluded(entity) {
+                stats.occlusion.occluded_count += 1;
+                stats.occlusion.culled_triangle_count += triangle_count;
+                continue;
+            }
+
+            let lod = &mut stats.lod;
+            if lod.entities_per_level.len() <= level {
+                lod.entities_per_level.resize(level + 1, 0);
+            }
+            lod.entities_per_level[level] += 1;
+            lod.triangle_count += triangle_count;
+            if selected_entity == Some(entity) {
+                lod.selected_model_level = Some(level);
+            }
+        }
+        if let Some(terrain) = scene
+            .terrain
+            .as_ref()
+            .filter(|_| draw_props.terrain_enabled)
+        {
+            let frustum = Frustum::from_matrix(&(scene_view.projection * scene_view.view));
+            stats.terrain = TerrainStats {
+                tile_count: terrain.tile_count(),
+                drawn_tile_count: terrain.visible_tiles(&frustum).count(),
+            };
+        }
+        stats
+    }
+
+    /// Test bounding boxes of visible entities against the depth buffer of the currently bound
+    /// scene framebuffer. Results decide which entities are drawn in the next frame.
+    fn issue_occlusion_queries(&self, scene_view: &SceneView, scene: &Scene) {
+        let mut query = scene
+            .world
+            .query::<(&Renderable, &Transform)>()
+            .with::<&Visible>();
+        let bounding_boxes: Vec<(Entity, Aabb)> = query
+            .iter()
+            .map(|(entity, (renderable, transform))| {
+                let bounds = scene.models[renderable.model_index].bounds();
+                (
+                    entity,
+                    bounds.transformed(&scene.interpolated_model_matrix(entity, transform)),
+                )
+            })
+            .collect();
+        self.occlusion_culling.issue_queries(
+            &bounding_boxes,
+            &(scene_view.projection * scene_view.view),
+            scene_view.position,
+        );
+    }
+
+    /// Draw bounding box and bounding sphere of the selected entity in world space.
+    fn draw_selection_bounds(
+        &self,
+        camera_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+    ) {
+        const BOUNDING_BOX_COLOR: [f32; 3] = [0.2, 1.0, 0.4];
+        const BOUNDING_SPHERE_COLOR: [f32; 3] = [0.3, 0.7, 1.0];
+
+        let Some(entity) = scene.selected_entity(draw_props.selected_model_index) else {
+            return;
+        };
+        let Ok(transform) = scene.world.get::<&Transform>(entity) else {
+            return;
+        };
+        let model_matrix = scene.interpolated_model_matrix(entity, &transform);
+        let bounds = scene.models[draw_props.selected_model_index].bounds();
+
+        let mut vertices = Vec::new();
+        if draw_props.bounding_box_enabled {
+            vertices.extend(line::box_lines(
+                &bounds.transformed(&model_matrix),
+                BOUNDING_BOX_COLOR,
+            ));
+        }
+        if draw_props.bounding_sphere_enabled {
+            // Rotation keeps the radius, model matrix has no scaling
+            vertices.extend(line::sphere_lines(
+                model_matrix.transform_point(bounds.center()),
+                bounds.radius(),
+                BOUNDING_SPHERE_COLOR,
+            ));
+        }
+        self.line_renderer
+            .draw(&vertices, &(camera_view.projection * camera_view.view));
+    }
+
+    /// Highlight problems of the selected model found by mesh validation, once it is validated.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn draw_mesh_problems(
+        &self,
+        camera_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+    ) {
+        let Some(validation) = scene.models[draw_props.selected_model_index]
+            .lod(0)
+            .validation()
+        else {
+            return;
+        };
+        let Some(entity) = scene.selected_entity(draw_props.selected_model_index) else {
+            return;
+        };
+        let Ok(transform) = scene.world.get::<&Transform>(entity) else {
+            return;
+        };
+        let model_matrix = scene.interpolated_model_matrix(entity, &transform);
+        self.line_renderer.draw(
+            &validation.lines(&model_matrix),
+            &(camera_view.projection * camera_view.view),
+        );
+    }
+
+    /// Draw scene seen from the directional light into bottom right corner of the window for
+    /// debugging light direction.
+    fn draw_light_view_inset(
+        &mut self,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+    ) {
+        // Fraction of window size covered by inset
+        const INSET_SCALE: u32 = 4;
+        const MARGIN: i32 = 10;
+
+        let (window_width, window_height) = self.window_size;
+        let width = (window_width / INSET_SCALE).max(1);
+        let height = (window_height / INSET_SCALE).max(1);
+
+        let Some(light_view) =
+            SceneView::from_light_direction(scene.light_direction(), width as f32 / height as f32)
+        else {
+            return;
+        };
+
+        if self.light_view_targets.is_none() {
+            match RenderTargets::new(&self.gl, self.samples) {
+                Ok(targets) => self.light_view_targets = Some(targets),
+                Err(e) => {
+                    eprintln!("light view render target creation failed: {e}");
+                    return;
+                }
+            }
+        }
+        let (scaled_width, scaled_height) = (self.scaled_size(width), self.scaled_size(height));
+        let targets = self.light_view_targets.as_mut().unwrap();
+        if let Err(e) = targets.resize(scaled_width, scaled_height) {
+            eprintln!("{e}");
+            return;
+        }
+
+        let targets = self.light_view_targets.as_ref().unwrap();
+        self.render_scene(targets, &light_view, draw_props, scene, skybox, || ());
+        unsafe {
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            self.gl.viewport(
+                window_width as i32 - width as i32 - MARGIN,
+                MARGIN,
+                width as i32,
+                height as i32,
+            );
+        }
+        self.draw_postprocess(&targets.scene, draw_props);
+        unsafe {
+            self.gl
+                .viewport(0, 0, window_width as i32, window_height as i32);
+        }
+    }
+
+    /// Render scene from two eyes beside the camera and combine them in the window.
+    ///
+    /// Render hooks are called in both eyes with the camera itself, their drawings appear at the
+    /// depth of the screen.
+    fn draw_stereo(
+        &mut self,
+        stereo_mode: StereoMode,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+        render_hooks: &mut [Box<dyn RenderHook>],
+    ) {
+        let (window_width, window_height) = self.window_size;
+        let eye_width = match stereo_mode {
+            StereoMode::SideBySide => (window_width / 2).max(1),
+            _ => window_width,
+        };
+
+        if self.stereo_targets.is_none() {
+            let targets = RenderTargets::new(&self.gl, self.samples).and_then(|left| {
+                RenderTargets::new(&self.gl, self.samples).map(|right| [left, right])
+            });
+            match targets {
+                Ok(targets) => self.stereo_targets = Some(targets),
+                Err(e) => {
+                    eprintln!("stereo render target creation failed: {e}");
+                    return;
+                }
+            }
+        }
+        let (scaled_width, scaled_height) =
+            (self.scaled_size(eye_width), self.scaled_size(window_height));
+        for targets in self.stereo_targets.as_mut().unwrap() {
+            if let Err(e) = targets.resize(scaled_width, scaled_height) {
+                eprintln!("{e}");
+                return;
+            }
+        }
+
+        // Placed between logic updates, matching the entities
+        let camera = &scene.interpolated_camera();
+        let projection =
+            calculate_projection_matrix(draw_props.field_of_view, eye_width, window_height);
+        let targets = self.stereo_targets.as_ref().unwrap();
+        for (eye_targets, side) in targets.iter().zip([-1.0, 1.0]) {
+            let eye_offset = side * draw_props.eye_separation / 2.0;
+            let eye_view = SceneView::from_stereo_eye(camera, projection, eye_offset);
+            self.render_scene(eye_targets, &eye_view, draw_props, scene, skybox, || {
+                for render_hook in render_hooks.iter_mut() {
+                    render_hook.on_after_scene(&self.gl, camera, &projection);
+                }
+                gl_state::invalidate();
+            });
+        }
+        let outputs = targets.each_ref().map(|eye_targets| {
+            self.apply_post_effects(eye_targets, &eye_targets.scene, draw_props, None)
+        });
+
+        unsafe {
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+        for (eye, output) in outputs.into_iter().enumerate() {
+            let left = eye == 0;
+            unsafe {
+                match stereo_mode {
+                    StereoMode::SideBySide => self.gl.viewport(
+                        eye as i32 * eye_width as i32,
+                        0,
+                        eye_width as i32,
+                        window_height as i32,
+                    ),
+                    _ => self.gl.color_mask(left, !left, !left, true),
+                }
+            }
+            self.draw_postprocess(output, draw_props);
+        }
+        unsafe {
+            self.gl.color_mask(true, true, true, true);
+            self.gl
+                .viewport(0, 0, window_width as i32, window_height as i32);
+        }
+    }
+
+    /// Clear currently bound framebuffer and draw entities seen from the given viewpoint.
+    fn draw_scene(
+        &self,
+        scene_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+    ) {
+        unsafe {
+            // Restore depth testing (egui disables it)
+            gl_state::enable(&self.gl, glow::DEPTH_TEST);
+
+            // Clear screen. Lighting is calculated in linear color space, conversion to sRGB
+            // happens in post-processing.
+            self.gl.clear_color(
+                draw_props.background_color[0],
+                draw_props.background_color[1],
+                draw_props.background_color[2],
+                1.0,
+            );
+            self.gl
+                .clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+        }
+
+        // Draw entities
+        cfg_if! {
+            if #[cfg(not(target_arch = "wasm32"))] {
+                match &self.geometry_debug_shaders {
+                    Some(shaders) if 0.0 < draw_props.explode_distance => self
+                        .draw_models_exploded(&shaders.exploded_view, scene_view, draw_props, scene),
+                    _ => self.draw_models(scene_view, draw_props, scene, skybox),
+                }
+            } else {
+                self.draw_models(scene_view, draw_props, scene, skybox);
+            }
+        }
+        self.draw_terrain(scene_view, draw_props, scene);
+        if draw_props.skybox_enabled && scene_view.skybox_visible {
+            self.draw_skybox(scene_view, skybox);
+        }
+    }
+
+    /// Draw entities seen from the given viewpoint into scene framebuffer using deferred
+    /// rendering.
+    ///
+    /// Skybox is drawn with forward rendering after the lighting pass.
+    fn draw_scene_deferred(
+        &self,
+        targets: &RenderTargets,
+        scene_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+    ) {
+        // Geometry pass
+        targets.gbuffer.bind();
+        unsafe {
+            gl_state::enable(&self.gl, glow::DEPTH_TEST);
+            self.gl.clear_color(0.0, 0.0, 0.0, 0.0);
+            self.gl
+                .clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+        }
+        self.draw_models_geometry(scene_view, draw_props, scene);
+
+        // Lighting pass
+        targets.scene.bind();
+        unsafe {
+            self.gl.clear_color(
+                draw_props.background_color[0],
+                draw_props.background_color[1],
+                draw_props.background_color[2],
+                1.0,
+            );
+            self.gl
+                .clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+            gl_state::disable(&self.gl, glow::DEPTH_TEST);
+        }
+        self.draw_deferred_lighting(&targets.gbuffer, scene_view, scene, skybox);
+
+        // Forward rendered entities are occluded by deferred geometry
+        targets.gbuffer.blit_depth(&targets.scene);
+        targets.scene.bind();
+        unsafe {
+            gl_state::enable(&self.gl, glow::DEPTH_TEST);
+        }
+        self.draw_terrain(scene_view, draw_props, scene);
+        if draw_props.skybox_enabled && scene_view.skybox_visible {
+            self.draw_skybox(scene_view, skybox);
+        }
+    }
+
+    /// Draw every visible renderable entity with forward shading.
+    fn draw_models(
+        &self,
+        scene_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+    ) {
+        unsafe {
+            for (index, texture) in self.shadow_maps.textures().enumerate() {
+                gl_state::active_texture(
+                    &self.gl,
+                    glow::TEXTURE0 + FIRST_SHADOW_MAP_TEXTURE_UNIT + index as u32,
+                );
+                gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, Some(texture));
+            }
+            for index in 0..MAX_POINT_LIGHTS {
+                if let Some(texture) = self.point_shadow_maps.texture(index) {
+                    gl_state::active_texture(
+                        &self.gl,
+                        glow::TEXTURE0 + FIRST_POINT_SHADOW_MAP_TEXTURE_UNIT + index as u32,
+                    );
+                    gl_state::bind_texture(&self.gl, glow::TEXTURE_CUBE_MAP, Some(texture));
+                }
+            }
+            self.bind_decal_textures(scene, true);
+            // Skybox is the environment sampled by reflective and refractive materials
+            gl_state::active_texture(&self.gl, glow::TEXTURE0);
+            gl_state::bind_texture(&self.gl, glow::TEXTURE_CUBE_MAP, Some(skybox.texture));
+
+            #[cfg(not(target_arch = "wasm32"))]
+            self.set_polygon_mode(draw_props.wireframe_mode_enabled);
+
+            let mut query = scene.world.query::<EntityComponents>().with::<&Visible>();
+            let draws = self.sorted_model_draws(scene_view, draw_props, scene, query.iter());
+            #[cfg(not(target_arch = "wasm32"))]
+            let draws = match &self.multi_draw_indirect {
+                Some(multi_draw_indirect) if draw_props.multi_draw_indirect_enabled => self
+                    .draw_models_indirect(
+                        &mut multi_draw_indirect.borrow_mut(),
+                        scene_view,
+                        scene,
+                        draws,
+                    ),
+                _ => draws,
+            };
+            let mut shader_key = None;
+            let mut model_shader = None;
+            let mut material_key = None;
+            for draw in &draws {
+                // Set custom shader or model draw shader variant of the material. Uniforms shared
+                // by every entity are set once for each shader.
+                if shader_key != Some(draw.shader_key()) {
+                    shader_key = Some(draw.shader_key());
+                    model_shader = match draw.custom_shader {
+                        Some(name) => Some(self.custom_shaders[name].shader.clone()),
+                        None => self.model_shaders.get(draw.shader_features).ok(),
+                    };
+                    if let Some(model_shader) = &model_shader {
+                        model_shader.r#use();
+                        self.set_shared_model_uniforms(model_shader, scene_view, scene);
+                    }
+                }
+                let Some(model_shader) = &model_shader else {
+                    continue;
+                };
+                if material_key != Some(draw.material_key()) {
+                    material_key = Some(draw.material_key());
+                    model_shader.set_uniform("u_color", &draw.material.color);
+                    model_shader.set_uniform("u_refractiveIndex", &draw.material.refractive_index);
+                }
+
+                // Set vertex input
+                gl_state::bind_vertex_array(&self.gl, Some(draw.vertex_array()));
+                self.set_face_culling(draw.model, scene_view.mirrored);
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(lightmap) = draw.lightmap {
+                    gl_state::active_texture(&self.gl, glow::TEXTURE1);
+                    gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, Some(lightmap));
+                    gl_state::active_texture(&self.gl, glow::TEXTURE0);
+                }
+
+                let relative_model_matrix = scene_view.relative_model_matrix(&draw.model_matrix);
+                let mvp = scene_view.relative_projection_view() * relative_model_matrix;
+                let normal_matrix = calculate_normal_matrix(&draw.model_matrix);
+                model_shader.set_uniform("u_model", &relative_model_matrix);
+                model_shader.set_uniform("u_mvp", &mvp);
+                model_shader.set_uniform("u_normalMatrix", &normal_matrix);
+
+                self.draw_model_mesh(draw);
+            }
+
+            // Reset state
+            #[cfg(not(target_arch = "wasm32"))]
+            self.set_polygon_mode(false);
+            gl_state::disable(&self.gl, glow::CULL_FACE);
+            self.gl.front_face(glow::CCW);
+            gl_state::bind_texture(&self.gl, glow::TEXTURE_CUBE_MAP, None);
+            #[cfg(not(target_arch = "wasm32"))]
+            if draws.iter().any(|draw| draw.lightmap.is_some()) {
+                gl_state::active_texture(&self.gl, glow::TEXTURE1);
+                gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, None);
+                gl_state::active_texture(&self.gl, glow::TEXTURE0);
+            }
+            for index in 0..self.shadow_maps.cascade_count() {
+                gl_state::active_texture(
+                    &self.gl,
+                    glow::TEXTURE0 + FIRST_SHADOW_MAP_TEXTURE_UNIT + index as u32,
+                );
+                gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, None);
+            }
+            for index in 0..MAX_POINT_LIGHTS {
+                if self.point_shadow_maps.texture(index).is_some() {
+                    gl_state::active_texture(
+                        &self.gl,
+                        glow::TEXTURE0 + FIRST_POINT_SHADOW_MAP_TEXTURE_UNIT + index as u32,
+                    );
+                    gl_state::bind_texture(&self.gl, glow::TEXTURE_CUBE_MAP, None);
+                }
+            }
+            self.bind_decal_textures(scene, false);
+            gl_state::active_texture(&self.gl, glow::TEXTURE0);
+            gl_state::bind_vertex_array(&self.gl, None);
+        }
+    }
 
-            let skybox_shader = Shader::new(
-                gl.clone(),
-                &assets::shader::SKYBOX_VERTEX_SRC,
-                &assets::shader::SKYBOX_FRAGMENT_SRC,
+    /// Bind images and the depth map atlas of drawn decals to their texture units, or unbind them.
+    /// Leaves another texture unit active.
+    unsafe fn bind_decal_textures(&self, scene: &Scene, bound: bool) {
+        for (index, decal) in scene.drawn_decals().iter().enumerate() {
+            gl_state::active_texture(
+                &self.gl,
+                glow::TEXTURE0 + FIRST_DECAL_TEXTURE_UNIT + index as u32,
+            );
+            gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, bound.then(|| decal.texture()));
+        }
+        if let Some(atlas) = &self.decal_depth_atlas {
+            gl_state::active_texture(&self.gl, glow::TEXTURE0 + DECAL_DEPTH_TEXTURE_UNIT);
+            gl_state::bind_texture(
+                &self.gl,
+                glow::TEXTURE_2D,
+                bound.then(|| atlas.depth_texture()),
+            );
+        }
+    }
+
+    /// Draw models merged into shared buffers with a single multi-draw indirect call for each
+    /// shader variant, vertex array and face culling mode. Returns the rest to draw one by one.
+    #[cfg(not(target_arch = "wasm32"))]
+    unsafe fn draw_models_indirect<'a>(
+        &self,
+        multi_draw_indirect: &mut MultiDrawIndirect,
+        scene_view: &SceneView,
+        scene: &Scene,
+        draws: Vec<ModelDraw<'a>>,
+    ) -> Vec<ModelDraw<'a>> {
+        // Custom shaders don't read transformations and material from the storage buffer. Parts
+        // of exploded models are rare enough to draw one by one.
+        let (mut batched, unbatched): (Vec<_>, Vec<_>) = draws.into_iter().partition(|draw| {
+            draw.batched.is_some() && draw.custom_shader.is_none() && draw.part.is_none()
+        });
+        // Uniforms and face culling can't change within a multi-draw call
+        let group_key = |draw: &ModelDraw| {
+            (
+                draw.shader_features,
+                draw.vertex_array(),
+                draw.mesh.index_type(),
+                draw.model.face_culling.enabled,
+                draw.model.face_culling.front_face == Winding::CounterClockwise,
             )
-            .map_err(|e| format!("skybox shader creation failed: {:?}", e))?;
+        };
+        batched.sort_by_key(group_key);
 
-            // Customize OpenGL capabilities
-            gl.enable(glow::BLEND);
-            gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
-            // Although in theory back-face culling would make sense from a performance point of
-            // view, the display of the Utah Teapot where you can look into the inside would be
-            // bugged.
-            gl.disable(glow::CULL_FACE);
+        for group in batched.chunk_by(|a, b| group_key(a) == group_key(b)) {
+            let first = &group[0];
+            let mut shader_features = first.shader_features;
+            shader_features.set(ShaderFeatures::MULTI_DRAW_INDIRECT, true);
+            let Ok(model_shader) = self.model_shaders.get(shader_features) else {
+                continue;
+            };
+            model_shader.r#use();
+            self.set_shared_model_uniforms(&model_shader, scene_view, scene);
+            self.set_face_culling(first.model, scene_view.mirrored);
 
-            Ok(Self {
-                gl,
-                projection: Matrix4::zero(),
-                skybox_shader,
-                model_shader,
-            })
+            let mut triangle_count = 0;
+            for draw in group {
+                let batched = draw.batched.unwrap();
+                let relative_model_matrix = scene_view.relative_model_matrix(&draw.model_matrix);
+                let mvp = scene_view.relative_projection_view() * relative_model_matrix;
+                let normal_matrix = Matrix4::from(calculate_normal_matrix(&draw.model_matrix));
+                let [r, g, b] = draw.material.color;
+                multi_draw_indirect.push(
+                    draw.mesh.index_count(),
+                    batched.index_offset as usize / draw.mesh.index_size(),
+                    batched.base_vertex,
+                    DrawData {
+                        model: relative_model_matrix.into(),
+                        mvp: mvp.into(),
+                        normal_matrix: normal_matrix.into(),
+                        color_and_refractive_index: [r, g, b, draw.material.refractive_index],
+                    },
+                );
+                triangle_count += draw.mesh.index_count() / 3;
+            }
+            multi_draw_indirect.submit(first.vertex_array(), first.mesh.index_type());
+            self.count_draw(triangle_count);
         }
+        unbatched
     }
 
-    /// Setup viewport, clear screen and draw entities
-    pub fn draw(
-        &mut self,
-        window: &Window,
-        camera: &Camera,
-        draw_props: &DrawProperties,
-        models: &Vec<Model>,
-        skybox: &Skybox,
+    /// Set uniforms of a forward model shader variant that are the same for every entity.
+    /// Lighting is calculated in camera-relative space with the eye at the origin.
+    unsafe fn set_shared_model_uniforms(
+        &self,
+        model_shader: &Shader,
+        scene_view: &SceneView,
+        scene: &Scene,
     ) {
-        unsafe {
-            // Update viewport because of Field of View change
-            let framebuffer_size = window.inner_size();
-            self.resize(
-                framebuffer_size.width,
-                framebuffer_size.height,
-                draw_props.field_of_view,
-            );
+        // Skybox is bound to the first texture unit, lightmap of baked lighting to the second
+        let texture_unit = 0;
+        let lightmap_texture_unit = 1;
+        model_shader.set_uniform("u_light.direction", &scene.light_direction());
+        model_shader.set_uniform("u_viewPos", &Point3::<f32>::origin());
+        model_shader.set_uniform("u_clipPlane", &scene_view.relative_clip_plane());
+        model_shader.set_uniform("u_skyboxTexture", &texture_unit);
+        model_shader.set_uniform("u_lightmap", &lightmap_texture_unit);
 
-            // Restore depth testing (egui disables it)
-            self.gl.enable(glow::DEPTH_TEST);
+        // Samplers of different types must not share texture units, even when unused
+        for index in 0..MAX_SHADOW_CASCADES {
+            let shadow_map_texture_unit = (FIRST_SHADOW_MAP_TEXTURE_UNIT + index as u32) as i32;
+            model_shader.set_uniform(&format!("u_shadowMap{index}"), &shadow_map_texture_unit);
+        }
+        for index in 0..MAX_POINT_LIGHTS {
+            let shadow_map_texture_unit =
+                (FIRST_POINT_SHADOW_MAP_TEXTURE_UNIT + index as u32) as i32;
+            model_shader.set_uniform(
+                &format!("u_pointShadowMap{index}"),
+                &shadow_map_texture_unit,
+            );
+        }
 
-            // Clear screen
-            self.gl.clear_color(
-                draw_props.background_color[0],
-                draw_props.background_color[1],
-                draw_props.background_color[2],
-                1.0,
+        let point_lights = scene.point_lights();
+        model_shader.set_uniform("u_pointLightCount", &(point_lights.len() as i32));
+        for (index, (_, point_light)) in point_lights.iter().enumerate() {
+            let uniform = |field: &str| format!("u_pointLights[{index}].{field}");
+            model_shader.set_uniform(
+                &uniform("position"),
+                &(Point3::from_vec(point_light.position) - scene_view.position),
             );
-            self.gl
-                .clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+            let color = point_light.color.map(|c| c * point_light.intensity);
+            model_shader.set_uniform(&uniform("color"), &color);
+            model_shader.set_uniform(&uniform("range"), &point_light.range);
+            model_shader.set_uniform(
+                &uniform("shadowed"),
+                &self.point_shadow_maps.texture(index).is_some(),
+            );
+            model_shader.set_uniform(&uniform("shadowBias"), &point_light.shadow_bias);
+        }
 
-            // Draw entities
-            self.draw_model(&camera, &draw_props, &models);
-            if draw_props.skybox_enabled {
-                self.draw_skybox(&camera, &skybox);
+        let area_lights = scene.area_lights();
+        model_shader.set_uniform("u_areaLightCount", &(area_lights.len() as i32));
+        for (index, (_, area_light)) in area_lights.iter().enumerate() {
+            for (corner_index, corner) in area_light.corners().into_iter().enumerate() {
+                model_shader.set_uniform(
+                    &format!("u_areaLightCorners[{}]", index * 4 + corner_index),
+                    &(corner - scene_view.position),
+                );
             }
+            let color = area_light.color.map(|c| c * area_light.intensity);
+            model_shader.set_uniform(&format!("u_areaLightColors[{index}]"), &color);
         }
-    }
+        set_decal_uniforms(model_shader, scene_view, scene);
 
-    pub fn resize(&mut self, physical_width: u32, physical_height: u32, field_of_view: f32) {
-        // Always query framebuffer size even if the window is not resizable. You'll
-        // never know how framebuffer size might differ from window size, especially
-        // on high-DPI displays. Not doing so can lead to display bugs like clipping
-        // top part of the view.
-        //
-        // Physical screen size means the actual count of pixels taking DPI into account.
-        unsafe {
-            self.gl
-                .viewport(0, 0, physical_width as i32, physical_height as i32);
-            self.projection = cgmath::perspective(
-                cgmath::Deg(field_of_view),
-                physical_width as f32 / physical_height as f32,
-                0.1,
-                100.0,
+        let shadow_maps = &self.shadow_maps;
+        model_shader.set_uniform(
+            "u_shadowCascadeCount",
+            &(shadow_maps.cascade_count() as i32),
+        );
+        if shadow_maps.cascade_count() == 0 {
+            return;
+        }
+        for (index, (cascade, _)) in shadow_maps.cascades().enumerate() {
+            // Camera-relative positions are moved back to world space first
+            let light_space_matrix = cascade.projection
+                * cascade.view
+                * Matrix4::from_translation(scene_view.position.to_vec());
+            model_shader.set_uniform(
+                &format!("u_lightSpaceMatrices[{index}]"),
+                &light_space_matrix,
             );
+            model_shader.set_uniform(&format!("u_cascadeEnds[{index}]"), &cascade.far_distance);
+            model_shader.set_uniform(&format!("u_shadowTexelSizes[{index}]"), &cascade.texel_size);
         }
+        model_shader.set_uniform(
+            "u_shadowViewOrigin",
+            &(shadow_maps.view_position() - scene_view.position),
+        );
+        model_shader.set_uniform("u_shadowViewDirection", &shadow_maps.view_direction());
+        model_shader.set_uniform("u_pcfRadius", &(shadow_maps.settings().pcf_radius as i32));
+        model_shader.set_uniform(
+            "u_cascadeVisualization",
+            &shadow_maps.settings().cascade_visualization,
+        );
     }
 
-    fn draw_model(&mut self, camera: &Camera, draw_props: &DrawProperties, models: &Vec<Model>) {
-        assert_eq!(models.len(), 3);
-        let model = &models[draw_props.selected_model_index];
-
-        // Set model draw shader
-        self.model_shader.r#use();
+    /// Write surface attributes of every visible renderable entity into G-buffer.
+    fn draw_models_geometry(
+        &self,
+        scene_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+    ) {
+        self.gbuffer_shader.r#use();
 
         unsafe {
-            // Set vertex input
-            self.gl.bind_vertex_array(Some(model.vertex_array));
+            self.gbuffer_shader
+                .set_uniform("u_clipPlane", &scene_view.relative_clip_plane());
+            set_decal_uniforms(&self.gbuffer_shader, scene_view, scene);
+            self.bind_decal_textures(scene, true);
+            gl_state::active_texture(&self.gl, glow::TEXTURE0);
 
-            // Concat matrix transformations on CPU to avoid unnecessary multiplications
-            // in GLSL. Results would be the same for all vertices.
-            let model_matrix = calculate_model_matrix(&draw_props.model_rotation);
-            let view = camera.calculate_view_matrix();
-            let mvp = self.projection * view * model_matrix;
-            let normal_matrix = calculate_normal_matrix(&model_matrix);
+            #[cfg(not(target_arch = "wasm32"))]
+            self.set_polygon_mode(draw_props.wireframe_mode_enabled);
 
-            // Transfer uniforms
-            self.model_shader.set_uniform("u_model", &model_matrix);
-            self.model_shader.set_uniform("u_mvp", &mvp);
-            self.model_shader
-                .set_uniform("u_normalMatrix", &normal_matrix);
-            self.model_shader
-                .set_uniform("u_color", &draw_props.model_color);
-            self.model_shader
-                .set_uniform("u_light.direction", &draw_props.light_direction);
-            self.model_shader
-                .set_uniform("u_viewPos", camera.position());
+            let mut query = scene.world.query::<EntityComponents>().with::<&Visible>();
+            let draws = self.sorted_model_draws(scene_view, draw_props, scene, query.iter());
+            let mut material_key = None;
+            for draw in &draws {
+                if material_key != Some(draw.material_key()) {
+                    material_key = Some(draw.material_key());
+                    let material = draw.material;
+                    self.gbuffer_shader.set_uniform("u_color", &material.color);
+                    self.gbuffer_shader
+                        .set_uniform("u_materialType", &(material.material_type as i32));
+                    self.gbuffer_shader
+                        .set_uniform("u_refractiveIndex", &material.refractive_index);
+                    self.gbuffer_shader
+                        .set_uniform("u_diffuseEnabled", &material.diffuse_enabled);
+                    self.gbuffer_shader
+                        .set_uniform("u_specularEnabled", &material.specular_enabled);
+                }
 
-            cfg_if! {
-                // Native OpenGL 4 features
-                if #[cfg(not(target_arch = "wasm32"))] {
-                    // Set OpenGL 4.x subroutines
-                    let diffuse_subroutine = if draw_props.diffuse_enabled {
-                        "DiffuseEnabled"
-                    } else {
-                        "Disabled"
-                    };
-                    let specular_subroutine = if draw_props.specular_enabled {
-                        "SpecularEnabled"
+                gl_state::bind_vertex_array(&self.gl, Some(draw.vertex_array()));
+                self.set_face_culling(draw.model, scene_view.mirrored);
+                // Feature is only set for meshes that have vertex colors
+                let vertex_color_mode =
+                    if draw.shader_features.contains(ShaderFeatures::VERTEX_COLOR) {
+                        draw.material.vertex_colors
                     } else {
-                        "Disabled"
+                        VertexColorMode::Ignore
                     };
-                    self.model_shader.update_subroutines(
-                        glow::FRAGMENT_SHADER,
-                        &[diffuse_subroutine, specular_subroutine],
-                    );
+                self.gbuffer_shader
+                    .set_uniform("u_vertexColorMode", &(vertex_color_mode as i32));
 
-                    // Display in either normal- or wireframe mode
-                    self.gl.polygon_mode(
-                        glow::FRONT_AND_BACK,
-                        if draw_props.wireframe_mode_enabled {
-                            glow::LINE
-                        } else {
-                            glow::FILL
-                        },
-                    );
-                }
-                // WebGL features
-                else {
-                   self.model_shader
-                    .set_uniform("u_adsProps.diffuseEnabled", &draw_props.diffuse_enabled);
-                    self.model_shader
-                    .set_uniform("u_adsProps.specularEnabled", &draw_props.specular_enabled);
+                let relative_model_matrix = scene_view.relative_model_matrix(&draw.model_matrix);
+                let mvp = scene_view.relative_projection_view() * relative_model_matrix;
+                let normal_matrix = calculate_normal_matrix(&draw.model_matrix);
+                self.gbuffer_shader
+                    .set_uniform("u_model", &relative_model_matrix);
+                self.gbuffer_shader.set_uniform("u_mvp", &mvp);
+                self.gbuffer_shader
+                    .set_uniform("u_normalMatrix", &normal_matrix);
+
+                self.draw_model_mesh(draw);
+            }
+
+            // Reset state
+            #[cfg(not(target_arch = "wasm32"))]
+            self.set_polygon_mode(false);
+            gl_state::disable(&self.gl, glow::CULL_FACE);
+            self.gl.front_face(glow::CCW);
+            self.bind_decal_textures(scene, false);
+            gl_state::active_texture(&self.gl, glow::TEXTURE0);
+            gl_state::bind_vertex_array(&self.gl, None);
+        }
+    }
+
+    /// Meshes of visible entities seen from the viewpoint, sorted by shader, material and mesh, so
+    /// consecutive draws change as little state as possible.
+    fn sorted_model_draws<'a>(
+        &self,
+        scene_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &'a Scene,
+        entities: impl Iterator<Item = (Entity, EntityComponents<'a>)>,
+    ) -> Vec<ModelDraw<'a>> {
+        let mut draws: Vec<ModelDraw> = Vec::new();
+        for (entity, (renderable, transform, material, part_overrides)) in entities {
+            if scene_view.occlusion_culling && self.occlusion_culling.is_occluded(entity) {
+                continue;
+            }
+            let model = &scene.models[renderable.model_index];
+            // Concat matrix transformations on CPU to avoid unnecessary multiplications
+            // in GLSL. Results would be the same for all vertices.
+            let model_matrix = scene.interpolated_model_matrix(entity, transform);
+            #[cfg(not(target_arch = "wasm32"))]
+            let lightmap = scene
+                .lightmap(entity)
+                .filter(|_| draw_props.baked_lighting_enabled);
+            let draw = |mesh: &'a Mesh, material: &'a Material, part, model_matrix| ModelDraw {
+                shader_features: mesh_shader_features(material, mesh),
+                custom_shader: material
+                    .shader
+                    .as_deref()
+                    .filter(|name| self.custom_shaders.contains_key(*name)),
+                material,
+                model,
+                mesh,
+                part,
+                batched: self.mesh_batches.get(mesh),
+                model_matrix,
+                #[cfg(not(target_arch = "wasm32"))]
+                lightmap: lightmap.map(Lightmap::texture),
+            };
+            // Lightmap meshes keep the triangle order of the full detail mesh
+            let full_detail_mesh = model.lod(0);
+            #[cfg(not(target_arch = "wasm32"))]
+            let full_detail_mesh = lightmap.map_or(full_detail_mesh, Lightmap::mesh);
+
+            let part_overrides = part_overrides
+                .filter(|part_overrides| 1 < model.parts().len() && part_overrides.is_customized());
+            if (0.0 < draw_props.part_explode_factor && 1 < model.parts().len())
+                || part_overrides.is_some()
+            {
+                // Parts refer to indices of the full detail mesh
+                let model_center = model.bounds().center();
+                for (part_index, part) in model.parts().iter().enumerate() {
+                    let part_override =
+                        part_overrides.and_then(|part_overrides| part_overrides.0.get(part_index));
+                    if part_override.is_some_and(|part_override| !part_override.visible) {
+                        continue;
+                    }
+                    let part_material = part_override
+                        .and_then(|part_override| part_override.material.as_ref())
+                        .unwrap_or(material);
+                    let offset = draw_props.part_explode_factor * (part.center - model_center);
+                    draws.push(draw(
+                        full_detail_mesh,
+                        part_material,
+                        Some((part.first_index, part.index_count)),
+                        model_matrix * Matrix4::from_translation(offset),
+                    ));
                 }
+            } else {
+                let mesh = model.lod(select_lod(
+                    scene_view,
+                    draw_props,
+                    renderable.model_index,
+                    model,
+                    &model_matrix,
+                ));
+                // Baked lighting is only stored for full detail
+                #[cfg(not(target_arch = "wasm32"))]
+                let mesh = if lightmap.is_some() {
+                    full_detail_mesh
+                } else {
+                    mesh
+                };
+                draws.push(draw(mesh, material, None, model_matrix));
+            }
+        }
+        draws.sort_by_key(|draw| (draw.material_key(), draw.vertex_array()));
+        draws
+    }
+
+    /// Draw every visible renderable entity with its triangles pushed apart along their face
+    /// normals, flat shaded with the material color.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn draw_models_exploded(
+        &self,
+        exploded_view_shader: &Shader,
+        scene_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+    ) {
+        exploded_view_shader.r#use();
+
+        unsafe {
+            exploded_view_shader
+                .set_uniform("u_projectionView", &scene_view.relative_projection_view());
+            exploded_view_shader.set_uniform("u_distance", &draw_props.explode_distance);
+            exploded_view_shader.set_uniform("u_light.direction", &scene.light_direction());
+            exploded_view_shader.set_uniform("u_clipPlane", &scene_view.relative_clip_plane());
+            self.set_polygon_mode(draw_props.wireframe_mode_enabled);
+
+            let mut query = scene.world.query::<EntityComponents>().with::<&Visible>();
+            let draws = self.sorted_model_draws(scene_view, draw_props, scene, query.iter());
+            for draw in &draws {
+                gl_state::bind_vertex_array(&self.gl, Some(draw.vertex_array()));
+                self.set_face_culling(draw.model, scene_view.mirrored);
+                exploded_view_shader.set_uniform("u_color", &draw.material.color);
+                exploded_view_shader.set_uniform(
+                    "u_model",
+                    &scene_view.relative_model_matrix(&draw.model_matrix),
+                );
+                self.draw_model_mesh(draw);
             }
 
-            // Issue draw call
-            self.gl.draw_elements(
+            // Reset state
+            self.set_polygon_mode(false);
+            gl_state::disable(&self.gl, glow::CULL_FACE);
+            self.gl.front_face(glow::CCW);
+            gl_state::bind_vertex_array(&self.gl, None);
+        }
+    }
+
+    /// Draw a line from the center of every triangle of visible renderable entities along its face
+    /// normal. Lines are debug overlay, not counted in draw statistics.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn draw_face_normals(
+        &self,
+        face_normals_shader: &Shader,
+        camera_view: &SceneView,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+    ) {
+        // Triangles are only pushed apart by forward rendering
+        let explode_distance = match draw_props.render_path {
+            RenderPath::Forward => draw_props.explode_distance,
+            RenderPath::Deferred => 0.0,
+        };
+        face_normals_shader.r#use();
+
+        unsafe {
+            face_normals_shader
+                .set_uniform("u_projectionView", &camera_view.relative_projection_view());
+            face_normals_shader.set_uniform("u_length", &FACE_NORMAL_LENGTH);
+            face_normals_shader.set_uniform("u_distance", &explode_distance);
+            face_normals_shader.set_uniform("u_color", &FACE_NORMAL_COLOR);
+
+            let mut query = scene.world.query::<EntityComponents>().with::<&Visible>();
+            let draws = self.sorted_model_draws(camera_view, draw_props, scene, query.iter());
+            for draw in &draws {
+                gl_state::bind_vertex_array(&self.gl, Some(draw.vertex_array()));
+                face_normals_shader.set_uniform(
+                    "u_model",
+                    &camera_view.relative_model_matrix(&draw.model_matrix),
+                );
+                self.issue_model_mesh_draw(draw);
+            }
+            gl_state::bind_vertex_array(&self.gl, None);
+        }
+    }
+
+    /// Issue draw call of the model mesh and count it in draw statistics.
+    unsafe fn draw_model_mesh(&self, draw: &ModelDraw) {
+        self.issue_model_mesh_draw(draw);
+        self.count_draw(draw.index_range().1 / 3);
+    }
+
+    /// Issue draw call of the model mesh or its part, from shared buffers if it is merged into a
+    /// batch.
+    unsafe fn issue_model_mesh_draw(&self, draw: &ModelDraw) {
+        let mesh = draw.mesh;
+        let (first_index, index_count) = draw.index_range();
+        let index_offset = (first_index * mesh.index_size()) as i32;
+        match draw.batched {
+            Some(batched) => self.gl.draw_elements_base_vertex(
                 glow::TRIANGLES,
-                model.indices.len() as i32,
-                glow::UNSIGNED_INT,
-                0,
-            );
+                index_count as i32,
+                mesh.index_type(),
+                batched.index_offset + index_offset,
+                batched.base_vertex,
+            ),
+            None => self.gl.draw_elements(
+                glow::TRIANGLES,
+                index_count as i32,
+                mesh.index_type(),
+                index_offset,
+            ),
+        }
+    }
+
+    fn count_draw(&self, triangle_count: usize) {
+        let mut draw_stats = self.draw_stats.get();
+        draw_stats.draw_call_count += 1;
+        draw_stats.triangle_count += triangle_count;
+        self.draw_stats.set(draw_stats);
+    }
+
+    /// Calculate lighting of every pixel covered by G-buffer geometry in a single fullscreen pass.
+    fn draw_deferred_lighting(
+        &self,
+        gbuffer: &GBuffer,
+        scene_view: &SceneView,
+        scene: &Scene,
+        skybox: &Skybox,
+    ) {
+        unsafe {
+            self.deferred_lighting_shader.r#use();
+            gl_state::bind_vertex_array(&self.gl, Some(self.fullscreen_vertex_array));
+
+            let textures = [
+                ("u_albedoTexture", gbuffer.albedo_texture()),
+                ("u_normalTexture", gbuffer.normal_texture()),
+                ("u_materialTexture", gbuffer.material_texture()),
+                ("u_depthTexture", gbuffer.depth_texture()),
+            ];
+            for (texture_unit, (name, texture)) in textures.into_iter().enumerate() {
+                gl_state::active_texture(&self.gl, glow::TEXTURE0 + texture_unit as u32);
+                gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, Some(texture));
+                self.deferred_lighting_shader
+                    .set_uniform(name, &(texture_unit as i32));
+            }
+            let skybox_texture_unit = textures.len() as i32;
+            gl_state::active_texture(&self.gl, glow::TEXTURE0 + skybox_texture_unit as u32);
+            gl_state::bind_texture(&self.gl, glow::TEXTURE_CUBE_MAP, Some(skybox.texture));
+            self.deferred_lighting_shader
+                .set_uniform("u_skyboxTexture", &skybox_texture_unit);
+
+            // Positions are reconstructed in camera-relative space of the G-buffer pass
+            let inverse_projection_view = scene_view
+                .relative_projection_view()
+                .invert()
+                .unwrap_or_else(Matrix4::identity);
+            self.deferred_lighting_shader
+                .set_uniform("u_inverseProjectionView", &inverse_projection_view);
+            self.deferred_lighting_shader
+                .set_uniform("u_light.direction", &scene.light_direction());
+            self.deferred_lighting_shader
+                .set_uniform("u_viewPos", &Point3::<f32>::origin());
+
+            self.gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            self.count_draw(1);
+
+            // Reset state
+            gl_state::bind_texture(&self.gl, glow::TEXTURE_CUBE_MAP, None);
+            for texture_unit in 0..textures.len() as u32 {
+                gl_state::active_texture(&self.gl, glow::TEXTURE0 + texture_unit);
+                gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, None);
+            }
+            gl_state::active_texture(&self.gl, glow::TEXTURE0);
+            gl_state::bind_vertex_array(&self.gl, None);
+        }
+    }
+
+    /// Draw tiles of the terrain inside the view frustum with forward shading, colored by height
+    /// and slope.
+    fn draw_terrain(&self, scene_view: &SceneView, draw_props: &DrawProperties, scene: &Scene) {
+        let Some(terrain) = scene
+            .terrain
+            .as_ref()
+            .filter(|_| draw_props.terrain_enabled)
+        else {
+            return;
+        };
+        let frustum = Frustum::from_matrix(&(scene_view.projection * scene_view.view));
+        let bounds = terrain.bounds();
+        // Vertices are already in world space
+        let relative_model_matrix = scene_view.relative_model_matrix(&Matrix4::identity());
+        let mvp = scene_view.relative_projection_view() * relative_model_matrix;
+
+        self.terrain_shader.r#use();
+        unsafe {
+            #[cfg(not(target_arch = "wasm32"))]
+            self.set_polygon_mode(draw_props.wireframe_mode_enabled);
+
+            self.terrain_shader
+                .set_uniform("u_model", &relative_model_matrix);
+            self.terrain_shader.set_uniform("u_mvp", &mvp);
+            self.terrain_shader
+                .set_uniform("u_light.direction", &scene.light_direction());
+            self.terrain_shader
+                .set_uniform("u_heightRange", &[bounds.min.y, bounds.max.y]);
+            self.terrain_shader
+                .set_uniform("u_clipPlane", &scene_view.relative_clip_plane());
+
+            // Underside of the terrain is never seen from above
+            gl_state::enable(&self.gl, glow::CULL_FACE);
+            self.gl.front_face(if scene_view.mirrored {
+                glow::CW
+            } else {
+                glow::CCW
+            });
+            for mesh in terrain.visible_tiles(&frustum) {
+                gl_state::bind_vertex_array(&self.gl, Some(mesh.vertex_array()));
+                self.gl.draw_elements(
+                    glow::TRIANGLES,
+                    mesh.index_count() as i32,
+                    mesh.index_type(),
+                    0,
+                );
+                self.count_draw(mesh.index_count() / 3);
+            }
 
             // Reset state
+            gl_state::disable(&self.gl, glow::CULL_FACE);
+            self.gl.front_face(glow::CCW);
             #[cfg(not(target_arch = "wasm32"))]
+            self.set_polygon_mode(false);
+            gl_state::bind_vertex_array(&self.gl, None);
+        }
+    }
+
+    /// Draw models and terrain into the currently bound framebuffer, adding the same cost for
+    /// every fragment passing the depth test. Post-processing displays the sum as a heatmap, hot
+    /// spots are where many fragments of the same pixel are shaded.
+    fn draw_overdraw(&self, scene_view: &SceneView, draw_props: &DrawProperties, scene: &Scene) {
+        self.overdraw_shader.r#use();
+        unsafe {
+            gl_state::enable(&self.gl, glow::DEPTH_TEST);
+            self.gl.clear_color(0.0, 0.0, 0.0, 1.0);
+            self.gl
+                .clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+            self.gl.blend_func(glow::ONE, glow::ONE);
+
+            let mut query = scene.world.query::<EntityComponents>().with::<&Visible>();
+            let draws = self.sorted_model_draws(scene_view, draw_props, scene, query.iter());
+            for draw in &draws {
+                gl_state::bind_vertex_array(&self.gl, Some(draw.vertex_array()));
+                self.set_face_culling(draw.model, scene_view.mirrored);
+
+                let relative_model_matrix = scene_view.relative_model_matrix(&draw.model_matrix);
+                let mvp = scene_view.relative_projection_view() * relative_model_matrix;
+                self.overdraw_shader.set_uniform("u_mvp", &mvp);
+
+                self.draw_model_mesh(draw);
+            }
+
+            if let Some(terrain) = scene
+                .terrain
+                .as_ref()
+                .filter(|_| draw_props.terrain_enabled)
             {
-                self.gl.polygon_mode(glow::FRONT_AND_BACK, glow::FILL);
+                let frustum = Frustum::from_matrix(&(scene_view.projection * scene_view.view));
+                // Vertices are already in world space
+                let mvp = scene_view.relative_projection_view()
+                    * scene_view.relative_model_matrix(&Matrix4::identity());
+                self.overdraw_shader.set_uniform("u_mvp", &mvp);
+                gl_state::enable(&self.gl, glow::CULL_FACE);
+                self.gl.front_face(if scene_view.mirrored {
+                    glow::CW
+                } else {
+                    glow::CCW
+                });
+                for mesh in terrain.visible_tiles(&frustum) {
+                    gl_state::bind_vertex_array(&self.gl, Some(mesh.vertex_array()));
+                    self.gl.draw_elements(
+                        glow::TRIANGLES,
+                        mesh.index_count() as i32,
+                        mesh.index_type(),
+                        0,
+                    );
+                    self.count_draw(mesh.index_count() / 3);
+                }
             }
-            self.gl.bind_vertex_array(None);
+
+            // Reset state
+            self.gl
+                .blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+            gl_state::disable(&self.gl, glow::CULL_FACE);
+            self.gl.front_face(glow::CCW);
+            gl_state::bind_vertex_array(&self.gl, None);
+        }
+    }
+
+    /// Display in either normal- or wireframe mode. Polygon mode is not available in OpenGL ES.
+    #[cfg(not(target_arch = "wasm32"))]
+    unsafe fn set_polygon_mode(&self, wireframe: bool) {
+        if self.gl_info.embedded {
+            return;
+        }
+        self.gl.polygon_mode(
+            glow::FRONT_AND_BACK,
+            if wireframe { glow::LINE } else { glow::FILL },
+        );
+    }
+
+    /// Enable back-face culling if model requires it. Mirrored views flip the winding order.
+    unsafe fn set_face_culling(&self, model: &Model, mirrored: bool) {
+        if model.face_culling.enabled {
+            gl_state::enable(&self.gl, glow::CULL_FACE);
+            let counter_clockwise =
+                (model.face_culling.front_face == Winding::CounterClockwise) != mirrored;
+            self.gl.front_face(if counter_clockwise {
+                glow::CCW
+            } else {
+                glow::CW
+            });
+        } else {
+            gl_state::disable(&self.gl, glow::CULL_FACE);
         }
     }
 
-    fn draw_skybox(&self, camera: &Camera, skybox: &Skybox) {
+    fn draw_skybox(&self, scene_view: &SceneView, skybox: &Skybox) {
         unsafe {
             // Disable face culling for skybox
-            self.gl.disable(glow::CULL_FACE);
+            gl_state::disable(&self.gl, glow::CULL_FACE);
 
             // Skybox needs to be drawn at the end of the rendering pipeline for
             // efficiency, not the other way around before objects (like in Painter's
@@ -215,21 +2910,20 @@ impl Renderer {
             self.gl.depth_func(glow::LEQUAL);
             // Set skybox shader
             self.skybox_shader.r#use();
-            self.gl.bind_vertex_array(Some(skybox.vertex_array));
+            gl_state::bind_vertex_array(&self.gl, Some(skybox.vertex_array));
 
             // Set skybox texture
-            self.gl.active_texture(glow::TEXTURE0);
-            self.gl
-                .bind_texture(glow::TEXTURE_CUBE_MAP, Some(skybox.texture));
+            gl_state::active_texture(&self.gl, glow::TEXTURE0);
+            gl_state::bind_texture(&self.gl, glow::TEXTURE_CUBE_MAP, Some(skybox.texture));
 
-            let mut normalized_view = camera.calculate_view_matrix();
+            let mut normalized_view = scene_view.view;
             // Remove camera position transformations by nullifying column 4, but keep rotation in the
             // view matrix. If you don't do this,
             // skybox will be shown as a shrinked down cube around model.
             normalized_view.w = Vector4::new(0.0, 0.0, 0.0, 0.0);
             // Concat matrix transformations on CPU to avoid unnecessary
             // multiplications in GLSL. Results would be the same for all vertices.
-            let projection_view = self.projection * normalized_view;
+            let projection_view = scene_view.projection * normalized_view;
 
             // Transfer uniforms
             self.skybox_shader
@@ -241,27 +2935,391 @@ impl Renderer {
             // Issue draw call
             self.gl
                 .draw_elements(glow::TRIANGLES, 36, glow::UNSIGNED_INT, 0);
+            self.count_draw(12);
 
             // Reset state
-            self.gl.bind_vertex_array(None);
+            gl_state::bind_vertex_array(&self.gl, None);
             self.gl.depth_func(glow::LESS); // Reset depth testing to default
-            self.gl.enable(glow::CULL_FACE);
+        }
+    }
+
+    /// Draw emitting rectangles of area lights, so they are visible in the scene and its
+    /// reflections.
+    fn draw_area_lights(&self, scene_view: &SceneView, scene: &Scene) {
+        let area_lights = scene.area_lights();
+        if area_lights.is_empty() {
+            return;
+        }
+        unsafe {
+            self.area_light_shader.r#use();
+            gl_state::bind_vertex_array(&self.gl, Some(self.fullscreen_vertex_array));
+            gl_state::disable(&self.gl, glow::CULL_FACE);
+            for (_, area_light) in &area_lights {
+                let relative_model_matrix =
+                    scene_view.relative_model_matrix(&area_light.model_matrix());
+                let mvp = scene_view.relative_projection_view() * relative_model_matrix;
+                let color = area_light.color.map(|c| c * area_light.intensity);
+                self.area_light_shader
+                    .set_uniform("u_model", &relative_model_matrix);
+                self.area_light_shader.set_uniform("u_mvp", &mvp);
+                self.area_light_shader.set_uniform("u_color", &color);
+                self.gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+                self.count_draw(2);
+            }
+            gl_state::bind_vertex_array(&self.gl, None);
+        }
+    }
+
+    /// Draw ground plane reflecting the scene rendered into reflection framebuffer.
+    fn draw_reflective_plane(
+        &self,
+        reflection: &Framebuffer,
+        scene_view: &SceneView,
+        draw_props: &DrawProperties,
+    ) {
+        // Large enough to reach the horizon at default camera distance
+        const PLANE_HALF_SIZE: f32 = 10.0;
+
+        unsafe {
+            self.reflective_plane_shader.r#use();
+            gl_state::bind_vertex_array(&self.gl, Some(self.fullscreen_vertex_array));
+            gl_state::active_texture(&self.gl, glow::TEXTURE0);
+            gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, Some(reflection.color_texture()));
+
+            let model_matrix = Matrix4::from_translation(Vector3::new(
+                0.0,
+                draw_props.reflective_plane_height,
+                0.0,
+            )) * Matrix4::from_scale(PLANE_HALF_SIZE);
+            let relative_model_matrix = scene_view.relative_model_matrix(&model_matrix);
+            let mvp = scene_view.relative_projection_view() * relative_model_matrix;
+
+            self.reflective_plane_shader
+                .set_uniform("u_model", &relative_model_matrix);
+            self.reflective_plane_shader.set_uniform("u_mvp", &mvp);
+            self.reflective_plane_shader
+                .set_uniform("u_viewPos", &Point3::<f32>::origin());
+            let texture_unit = 0;
+            self.reflective_plane_shader
+                .set_uniform("u_reflectionTexture", &texture_unit);
+            self.reflective_plane_shader
+                .set_uniform("u_reflectivity", &draw_props.reflectivity);
+            self.reflective_plane_shader
+                .set_uniform("u_blurRadius", &draw_props.reflection_blur);
+
+            // Drawn after skybox, plane edges fade out by blending with what is already drawn.
+            gl_state::enable(&self.gl, glow::BLEND);
+            self.gl
+                .blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+            self.gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+            self.count_draw(2);
+
+            // Reset state
+            gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, None);
+            gl_state::bind_vertex_array(&self.gl, None);
+        }
+    }
+
+    /// Draw motion blur and enabled post effects of the scene one after the other into the
+    /// ping-pong framebuffers of the targets. `source` holds the scene colors, either the scene
+    /// framebuffer of the targets or the result of anti-aliasing it, depth is always read from the
+    /// targets. Motion blur needs the `reprojection` of pixels into the previous frame.
+    ///
+    /// Returns the framebuffer with the output of the last pass, or `source` without any. Leaves a
+    /// framebuffer of the targets bound.
+    fn apply_post_effects<'t>(
+        &self,
+        targets: &'t RenderTargets,
+        source: &'t Framebuffer,
+        draw_props: &DrawProperties,
+        reprojection: Option<Matrix4<f32>>,
+    ) -> &'t Framebuffer {
+        let settings = &draw_props.post_effect_settings;
+        let reprojection = reprojection.filter(|_| settings.motion_blur_active());
+        // Heatmap colors encode fragment counts
+        if draw_props.overdraw_heatmap_enabled
+            || (reprojection.is_none() && settings.active_effects().next().is_none())
+        {
+            return source;
+        }
+        profile_scope!("Renderer::apply_post_effects");
+        let mut source = source;
+        let mut pass = 0;
+        unsafe {
+            gl_state::disable(&self.gl, glow::DEPTH_TEST);
+            gl_state::disable(&self.gl, glow::BLEND);
+            gl_state::bind_vertex_array(&self.gl, Some(self.fullscreen_vertex_array));
+
+            // Blurred before stylistic effects, grain and vignette stay sharp
+            if let Some(reprojection) = reprojection {
+                let target = targets.post_effects.target(pass);
+                target.bind();
+                self.motion_blur_shader.r#use();
+                let textures = [
+                    ("u_sceneTexture", source.color_texture()),
+                    ("u_depthTexture", targets.scene.depth_texture()),
+                ];
+                for (texture_unit, (name, texture)) in textures.into_iter().enumerate() {
+                    gl_state::active_texture(&self.gl, glow::TEXTURE0 + texture_unit as u32);
+                    gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, Some(texture));
+                    self.motion_blur_shader
+                        .set_uniform(name, &(texture_unit as i32));
+                }
+                self.motion_blur_shader
+                    .set_uniform("u_reprojection", &reprojection);
+                self.motion_blur_shader
+                    .set_uniform("u_shutterFraction", &(settings.shutter_angle / 360.0));
+                self.gl.draw_arrays(glow::TRIANGLES, 0, 3);
+                self.count_draw(1);
+
+                for texture_unit in (0..textures.len() as u32).rev() {
+                    gl_state::active_texture(&self.gl, glow::TEXTURE0 + texture_unit);
+                    gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, None);
+                }
+                source = target;
+                pass += 1;
+            }
+
+            self.post_effect_shader.r#use();
+            gl_state::active_texture(&self.gl, glow::TEXTURE0);
+            let texture_unit = 0;
+            self.post_effect_shader
+                .set_uniform("u_sourceTexture", &texture_unit);
+            self.post_effect_shader
+                .set_uniform("u_vignetteStrength", &settings.vignette_strength);
+            self.post_effect_shader
+                .set_uniform("u_filmGrainIntensity", &settings.film_grain_intensity);
+            // Kept small, large values lose precision in the noise function
+            let film_grain_seed = (self.frame_count % 1024) as f32;
+            self.post_effect_shader
+                .set_uniform("u_filmGrainSeed", &film_grain_seed);
+            self.post_effect_shader.set_uniform(
+                "u_chromaticAberrationStrength",
+                &settings.chromatic_aberration_strength,
+            );
+
+            for effect in settings.active_effects() {
+                let target = targets.post_effects.target(pass);
+                target.bind();
+                gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, Some(source.color_texture()));
+                self.post_effect_shader
+                    .set_uniform("u_effect", &(effect as i32));
+                self.gl.draw_arrays(glow::TRIANGLES, 0, 3);
+                self.count_draw(1);
+                source = target;
+                pass += 1;
+            }
+
+            gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, None);
+            gl_state::bind_vertex_array(&self.gl, None);
+            gl_state::enable(&self.gl, glow::BLEND);
+        }
+        source
+    }
+
+    /// Blend the scene rendered with jittered projection into the resolved output of previous
+    /// frames, reprojected by `reprojection`, drawing into the current framebuffer of the history.
+    fn resolve_temporal_anti_aliasing(
+        &self,
+        history: &TemporalHistory,
+        scene: &Framebuffer,
+        draw_props: &DrawProperties,
+        reprojection: Option<Matrix4<f32>>,
+    ) {
+        profile_scope!("Renderer::resolve_temporal_anti_aliasing");
+        let previous = history.previous().filter(|_| reprojection.is_some());
+        unsafe {
+            history.current().bind();
+            gl_state::disable(&self.gl, glow::DEPTH_TEST);
+            gl_state::disable(&self.gl, glow::BLEND);
+            self.taa_resolve_shader.r#use();
+            gl_state::bind_vertex_array(&self.gl, Some(self.fullscreen_vertex_array));
+
+            // Scene stands in for missing history, the shader ignores it then
+            let history_texture = previous.unwrap_or(scene).color_texture();
+            let textures = [
+                ("u_sceneTexture", scene.color_texture()),
+                ("u_historyTexture", history_texture),
+                ("u_depthTexture", scene.depth_texture()),
+            ];
+            for (texture_unit, (name, texture)) in textures.into_iter().enumerate() {
+                gl_state::active_texture(&self.gl, glow::TEXTURE0 + texture_unit as u32);
+                gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, Some(texture));
+                self.taa_resolve_shader
+                    .set_uniform(name, &(texture_unit as i32));
+            }
+            self.taa_resolve_shader.set_uniform(
+                "u_reprojection",
+                &reprojection.unwrap_or_else(Matrix4::identity),
+            );
+            self.taa_resolve_shader
+                .set_uniform("u_historyValid", &previous.is_some());
+            self.taa_resolve_shader
+                .set_uniform("u_sharpness", &draw_props.taa_sharpness);
+            self.gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            self.count_draw(1);
+
+            for texture_unit in (0..textures.len() as u32).rev() {
+                gl_state::active_texture(&self.gl, glow::TEXTURE0 + texture_unit);
+                gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, None);
+            }
+            gl_state::bind_vertex_array(&self.gl, None);
+            gl_state::enable(&self.gl, glow::BLEND);
+        }
+    }
+
+    /// Draw offscreen scene texture into currently bound framebuffer as a fullscreen triangle
+    /// while applying exposure and gamma adjustments.
+    ///
+    /// Bound framebuffer is expected to have sRGB encoding, either the default framebuffer or one
+    /// with `SRGB8_ALPHA8` color format.
+    fn draw_postprocess(&self, scene: &Framebuffer, draw_props: &DrawProperties) {
+        profile_scope!("Renderer::draw_postprocess");
+        unsafe {
+            gl_state::disable(&self.gl, glow::DEPTH_TEST);
+
+            self.postprocess_shader.r#use();
+
+            // Native: Let the driver convert linear values written into sRGB-capable framebuffers.
+            // Restore it, because egui disables it.
+            // Web: WebGL2 default framebuffer is never sRGB-capable, shader converts output
+            // colors manually. Writes into sRGB textures are always converted.
+            cfg_if! {
+                if #[cfg(not(target_arch = "wasm32"))] {
+                    // OpenGL ES always converts writes into sRGB-capable framebuffers
+                    if !self.gl_info.embedded {
+                        gl_state::enable(&self.gl, glow::FRAMEBUFFER_SRGB);
+                    }
+                } else {
+                    let default_framebuffer_bound =
+                        self.gl.get_parameter_framebuffer(glow::FRAMEBUFFER_BINDING).is_none();
+                    self.postprocess_shader
+                        .set_uniform("u_encodeSrgb", &default_framebuffer_bound);
+                }
+            }
+        }
+        self.draw_postprocess_triangle(scene, draw_props);
+    }
+
+    /// Draw fullscreen triangle sampling the scene texture with the postprocess shader, which is
+    /// expected to be in use already.
+    fn draw_postprocess_triangle(&self, scene: &Framebuffer, draw_props: &DrawProperties) {
+        unsafe {
+            gl_state::bind_vertex_array(&self.gl, Some(self.fullscreen_vertex_array));
+            gl_state::active_texture(&self.gl, glow::TEXTURE0);
+            gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, Some(scene.color_texture()));
+
+            let texture_unit = 0;
+            self.postprocess_shader
+                .set_uniform("u_sceneTexture", &texture_unit);
+            self.postprocess_shader
+                .set_uniform("u_exposure", &draw_props.exposure);
+            self.postprocess_shader
+                .set_uniform("u_gamma", &draw_props.gamma);
+            self.postprocess_shader
+                .set_uniform("u_overdrawHeatmap", &draw_props.overdraw_heatmap_enabled);
+
+            self.gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            self.count_draw(1);
+
+            // Reset state
+            gl_state::bind_texture(&self.gl, glow::TEXTURE_2D, None);
+            gl_state::bind_vertex_array(&self.gl, None);
         }
     }
 }
 
-fn calculate_model_matrix(rotation: &[f32; 3]) -> Matrix4<f32> {
-    // Avoid Gimbal-lock by converting Euler angles to quaternions
-    let q = Quaternion::from(Euler {
-        x: Deg(rotation[0]),
-        y: Deg(rotation[1]),
-        z: Deg(rotation[2]),
-    });
+impl Drop for Renderer {
+    fn drop(&mut self) {
+        unsafe {
+            gl_resources::delete_vertex_array(&self.gl, self.fullscreen_vertex_array);
+        }
+    }
+}
+
+/// Lighting components and surface of the material are selected by forward model shader variant.
+fn material_shader_features(material: &Material) -> ShaderFeatures {
+    let mut features = ShaderFeatures::default();
+    features.set(ShaderFeatures::DIFFUSE, material.diffuse_enabled);
+    features.set(ShaderFeatures::SPECULAR, material.specular_enabled);
+    match material.material_type {
+        MaterialType::Phong => (),
+        MaterialType::Reflective => features.set(ShaderFeatures::REFLECTIVE_SURFACE, true),
+        MaterialType::Refractive => features.set(ShaderFeatures::REFRACTIVE_SURFACE, true),
+    }
+    features
+}
+
+/// Shader features of drawing the mesh with the material. Vertex colors are only read from meshes
+/// that have them. Only lightmap meshes of baked lighting have lightmap texture coordinates.
+fn mesh_shader_features(material: &Material, mesh: &Mesh) -> ShaderFeatures {
+    let mut features = material_shader_features(material);
+    features.set(
+        ShaderFeatures::LIGHTMAP,
+        mesh.vertex_layout().contains(VertexAttribute::LightmapUv),
+    );
+    if mesh.vertex_layout().contains(VertexAttribute::Color) {
+        match material.vertex_colors {
+            VertexColorMode::Ignore => (),
+            VertexColorMode::Modulate => features.set(ShaderFeatures::VERTEX_COLOR, true),
+            VertexColorMode::Replace => {
+                features.set(ShaderFeatures::VERTEX_COLOR, true);
+                features.set(ShaderFeatures::VERTEX_COLOR_REPLACE, true);
+            }
+        }
+    }
+    features
+}
+
+/// Set uniforms of decals projected by model and G-buffer shaders. Projectors are transformed
+/// from the camera-relative space of fragment positions.
+fn set_decal_uniforms(shader: &Shader, scene_view: &SceneView, scene: &Scene) {
+    let decals = scene.drawn_decals();
+    shader.set_uniform("u_decalCount", &(decals.len() as i32));
+    for index in 0..MAX_DECALS {
+        let texture_unit = (FIRST_DECAL_TEXTURE_UNIT + index as u32) as i32;
+        shader.set_uniform(&format!("u_decalTexture{index}"), &texture_unit);
+    }
+    shader.set_uniform("u_decalDepthAtlas", &(DECAL_DEPTH_TEXTURE_UNIT as i32));
+    for (index, decal) in decals.iter().enumerate() {
+        // Projects world positions into the clip space of the projector
+        let decal_matrix = decal.projection_matrix()
+            * decal.view_matrix()
+            * Matrix4::from_translation(scene_view.position.to_vec());
+        shader.set_uniform(&format!("u_decalMatrices[{index}]"), &decal_matrix);
+        shader.set_uniform(
+            &format!("u_decalPositions[{index}]"),
+            &(decal.position - scene_view.position.to_vec()),
+        );
+        shader.set_uniform(&format!("u_decalOpacities[{index}]"), &decal.opacity);
+    }
+}
+
+pub fn calculate_model_matrix(transform: &Transform) -> Matrix4<f32> {
+    Matrix4::from_translation(transform.position) * Matrix4::from(rotation_quaternion(transform))
+}
 
-    Matrix4::from(q)
+/// Model matrix of the placement between `previous` (`alpha` 0) and `current` (`alpha` 1).
+pub fn calculate_interpolated_model_matrix(
+    previous: &Transform,
+    current: &Transform,
+    alpha: f32,
+) -> Matrix4<f32> {
+    let position = previous.position + (current.position - previous.position) * alpha;
+    let rotation = rotation_quaternion(previous).slerp(rotation_quaternion(current), alpha);
+    Matrix4::from_translation(position) * Matrix4::from(rotation)
 }
 
-fn calculate_normal_matrix(m: &Matrix4<f32>) -> Matrix3<f32> {
+fn rotation_quaternion(transform: &Transform) -> Quaternion<f32> {
+    // Avoid Gimbal-lock by converting Euler angles to quaternions
+    Quaternion::from(Euler {
+        x: Deg(transform.rotation[0]),
+        y: Deg(transform.rotation[1]),
+        z: Deg(transform.rotation[2]),
+    })
+}
+
+pub fn calculate_normal_matrix(m: &Matrix4<f32>) -> Matrix3<f32> {
     let inverse_transpose = m.invert().unwrap().transpose();
     Matrix3::new(
         inverse_transpose.x.x,
@@ -275,3 +3333,75 @@ fn calculate_normal_matrix(m: &Matrix4<f32>) -> Matrix3<f32> {
         inverse_transpose.z.z,
     )
 }
+
+/// Level of detail of a model seen from the viewpoint, chosen by the screen coverage of its
+/// bounding sphere.
+#[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+fn select_lod(
+    scene_view: &SceneView,
+    draw_props: &DrawProperties,
+    model_index: usize,
+    model: &Model,
+    model_matrix: &Matrix4<f32>,
+) -> usize {
+    if !draw_props.lod_enabled {
+        return 0;
+    }
+    // Compute shader only displaces vertices of the full detail mesh
+    #[cfg(not(target_arch = "wasm32"))]
+    if draw_props.vertex_displacement_enabled && model_index == draw_props.selected_model_index {
+        return 0;
+    }
+    let bounds = model.bounds();
+    let center = model_matrix.transform_point(bounds.center());
+    model.lod_for_coverage(scene_view.screen_coverage(center, bounds.radius()))
+}
+
+fn calculate_projection_matrix(field_of_view: f32, width: u32, height: u32) -> Matrix4<f32> {
+    cgmath::perspective(
+        cgmath::Deg(field_of_view),
+        width as f32 / height.max(1) as f32,
+        0.1,
+        100.0,
+    )
+}
+
+/// Built-in labels naming the world axes, the light and the displayed model.
+fn scene_labels(light_direction: [f32; 3], model_name: &str) -> Vec<Label> {
+    const AXIS_LENGTH: f32 = 1.5;
+    // Avoid overlapping label with light gizmo
+    const LIGHT_LABEL_OFFSET: f32 = 0.3;
+
+    let mut labels = vec![
+        Label {
+            text: "X".to_owned(),
+            position: Point3::new(AXIS_LENGTH, 0.0, 0.0),
+            color: [1.0, 0.2, 0.2],
+        },
+        Label {
+            text: "Y".to_owned(),
+            position: Point3::new(0.0, AXIS_LENGTH, 0.0),
+            color: [0.2, 1.0, 0.2],
+        },
+        Label {
+            text: "Z".to_owned(),
+            position: Point3::new(0.0, 0.0, AXIS_LENGTH),
+            color: [0.2, 0.4, 1.0],
+        },
+        Label {
+            text: model_name.to_owned(),
+            position: Point3::new(0.0, -AXIS_LENGTH, 0.0),
+            color: [1.0, 1.0, 1.0],
+        },
+    ];
+
+    if let Some(light_position) = gizmo::light_gizmo_position(light_direction) {
+        labels.push(Label {
+            text: "Light".to_owned(),
+            position: light_position + Vector3::unit_y() * LIGHT_LABEL_OFFSET,
+            color: [1.0, 0.9, 0.3],
+        });
+    }
+
+    labels
+}
@@ -0,0 +1,618 @@
+use std::collections::HashMap;
+
+use cgmath::{Matrix4, Point3, Transform as _, Vector3};
+use hecs::{Entity, World};
+
+use crate::{
+    renderer::{calculate_interpolated_model_matrix, calculate_model_matrix},
+    Aabb, Camera, CameraBookmark, CameraTransition, Comparison, Decal, DrawProperties, Material,
+    Model, Modulation, Terrain, Timeline, MAX_DECALS,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{InspectedTriangle, Inspector, Lightmap, Measurement, Ray, RayHit};
+
+/// Placement of a renderable entity in world space.
+#[derive(Clone, Copy)]
+pub struct Transform {
+    pub position: Vector3<f32>,
+    /// Euler angles in degrees around X, Y and Z axes.
+    pub rotation: [f32; 3],
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            position: Vector3::new(0.0, 0.0, 0.0),
+            rotation: [0.0, 0.0, 0.0],
+        }
+    }
+}
+
+/// Entity drawn with a model of the scene.
+///
+/// Models own OpenGL objects, which can't be shared between threads on web, so they are referred
+/// by index instead of being stored as components.
+#[derive(Clone, Copy)]
+pub struct Renderable {
+    pub model_index: usize,
+}
+
+/// Marker of renderable entities drawn by every render pass.
+pub struct Visible;
+
+/// Marker of renderable entities that are part of the GUI model selection. Selecting a model shows
+/// its entity and hides the other selectable ones. Transform of the selected entity follows the GUI
+/// settings, its material is edited directly.
+pub struct Selectable;
+
+/// Display name of an entity in the scene hierarchy. Entities without a name are listed by the name
+/// of their model.
+#[derive(Clone)]
+pub struct Name(pub String);
+
+/// Visibility and material slot of each part of the model of an entity, indexed like
+/// `Model::parts`. Entities without the component draw every part with their own material.
+#[derive(Clone)]
+pub struct PartOverrides(pub Vec<PartOverride>);
+
+impl PartOverrides {
+    /// Every part visible with the material of the entity.
+    pub fn new(part_count: usize) -> Self {
+        Self(vec![PartOverride::default(); part_count])
+    }
+
+    /// Whether any part is hidden or has its own material, so parts have to be drawn separately.
+    pub fn is_customized(&self) -> bool {
+        self.0
+            .iter()
+            .any(|part| !part.visible || part.material.is_some())
+    }
+}
+
+#[derive(Clone)]
+pub struct PartOverride {
+    pub visible: bool,
+    /// Replaces the material of the entity for this part.
+    pub material: Option<Material>,
+}
+
+impl Default for PartOverride {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            material: None,
+        }
+    }
+}
+
+/// Light illuminating the whole scene from a single direction, like the sun.
+#[derive(Clone, Copy)]
+pub struct DirectionalLight {
+    pub direction: [f32; 3],
+}
+
+/// Most point lights taken into account by shaders.
+pub const MAX_POINT_LIGHTS: usize = 4;
+
+/// Light radiating in every direction from a point, fading out with distance. Lights diffuse and
+/// specular surfaces with forward rendering.
+#[derive(Clone, Copy)]
+pub struct PointLight {
+    pub position: Vector3<f32>,
+    /// Linear RGB color, multiplied by intensity.
+    pub color: [f32; 3],
+    pub intensity: f32,
+    /// Distance where light fades out completely, also the far plane of its shadow map.
+    pub range: f32,
+    /// Render depth of models around the light into a cube map, so they block its light in every
+    /// direction.
+    pub shadows_enabled: bool,
+    /// Distance in world units surfaces are moved towards the light when compared with the shadow
+    /// map, against shadowing themselves.
+    pub shadow_bias: f32,
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        Self {
+            position: Vector3::new(0.0, 2.0, 2.0),
+            color: [1.0, 1.0, 1.0],
+            intensity: 1.0,
+            range: 10.0,
+            shadows_enabled: true,
+            shadow_bias: 0.05,
+        }
+    }
+}
+
+/// Most area lights taken into account by shaders.
+pub const MAX_AREA_LIGHTS: usize = 4;
+
+/// Rectangle emitting light from its front side, e.g. a softbox of a studio setup. Lights diffuse
+/// and specular surfaces with forward rendering and is drawn as an emissive quad.
+#[derive(Clone, Copy)]
+pub struct AreaLight {
+    /// Center of the rectangle.
+    pub position: Vector3<f32>,
+    /// Euler angles in degrees around X, Y and Z axes. Without rotation the rectangle is
+    /// horizontal and emits downwards.
+    pub rotation: [f32; 3],
+    pub width: f32,
+    pub height: f32,
+    /// Linear RGB color, multiplied by intensity.
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+impl Default for AreaLight {
+    fn default() -> Self {
+        Self {
+            position: Vector3::new(0.0, 3.0, 0.0),
+            rotation: [0.0, 0.0, 0.0],
+            width: 2.0,
+            height: 1.0,
+            color: [1.0, 1.0, 1.0],
+            intensity: 2.0,
+        }
+    }
+}
+
+impl AreaLight {
+    /// Transforms the square between -1 and 1 on the XZ plane onto the rectangle.
+    pub fn model_matrix(&self) -> Matrix4<f32> {
+        let transform = Transform {
+            position: self.position,
+            rotation: self.rotation,
+        };
+        calculate_model_matrix(&transform)
+            * Matrix4::from_nonuniform_scale(0.5 * self.width, 1.0, 0.5 * self.height)
+    }
+
+    /// Corners of the rectangle in world space, counter-clockwise seen from the front side.
+    pub fn corners(&self) -> [Point3<f32>; 4] {
+        let model_matrix = self.model_matrix();
+        [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .map(|(x, z)| model_matrix.transform_point(Point3::new(x, 0.0, z)))
+    }
+}
+
+/// Entities of the displayed scene stored in an ECS world, together with the models and camera
+/// they are viewed with.
+///
+/// Render passes and other systems iterate components instead of relying on a single model, so
+/// additional entities can be spawned into the world, e.g. multiple instances of the same model.
+pub struct Scene {
+    pub world: World,
+    /// Models referred by `Renderable` components.
+    pub models: Vec<Model>,
+    pub camera: Camera,
+    /// Placements saved by the user, in the order they were added.
+    pub camera_bookmarks: Vec<CameraBookmark>,
+    /// Flight to a bookmark in progress, moving the camera on each logic update.
+    pub camera_transition: Option<CameraTransition>,
+    /// Keyframe animation of GUI settings and entity placements.
+    pub timeline: Timeline,
+    /// Parameters driven by levels of external sources, e.g. audio input.
+    pub modulation: Modulation,
+    /// Landscape drawn in addition to the entities.
+    pub terrain: Option<Terrain>,
+    /// Settings drawn right of a split line for comparison, if enabled.
+    pub comparison: Option<Comparison>,
+    /// Images projected onto models, only the first `MAX_DECALS` are drawn.
+    pub decals: Vec<Decal>,
+    /// Distance and angle between points picked on models.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub measurement: Measurement,
+    /// Triangle under the cursor in inspector mode.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub inspector: Inspector,
+    // Light controlled from GUI
+    light: Entity,
+    // Model selection that visibility of selectable entities was last updated for. Visibility
+    // toggled from the hierarchy is kept until the selection changes.
+    applied_selection: Option<usize>,
+    // Placements of entities before the last logic update
+    previous_transforms: HashMap<Entity, Transform>,
+    // Fraction of the next logic update elapsed when drawing, 1 draws the current state
+    interpolation: f32,
+    // Baked lighting of entities, drawn instead of realtime lighting when enabled
+    #[cfg(not(target_arch = "wasm32"))]
+    lightmaps: HashMap<Entity, Lightmap>,
+}
+
+impl Scene {
+    pub fn new(camera: Camera) -> Self {
+        let mut world = World::new();
+        let light = world.spawn((DirectionalLight {
+            direction: [-0.5, -1.0, 0.0],
+        },));
+
+        Self {
+            world,
+            models: Vec::new(),
+            camera,
+            camera_bookmarks: Vec::new(),
+            camera_transition: None,
+            timeline: Timeline::default(),
+            modulation: Modulation::default(),
+            terrain: None,
+            comparison: None,
+            decals: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            measurement: Measurement::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            inspector: Inspector::default(),
+            light,
+            applied_selection: None,
+            previous_transforms: HashMap::new(),
+            interpolation: 1.0,
+            #[cfg(not(target_arch = "wasm32"))]
+            lightmaps: HashMap::new(),
+        }
+    }
+
+    /// Remember placements of the camera and the entities before a logic update changes them.
+    /// Frames drawn until the next update blend them with the updated placements, so movement
+    /// looks smooth when the display refreshes faster than logic is updated.
+    pub fn store_previous_state(&mut self) {
+        self.camera.store_previous_placement();
+        self.previous_transforms.clear();
+        self.previous_transforms.extend(
+            self.world
+                .query::<&Transform>()
+                .iter()
+                .map(|(entity, transform)| (entity, *transform)),
+        );
+    }
+
+    /// Advance the camera transition by `delta_time` seconds, if there is one. Returns the field of
+    /// view it sets.
+    pub fn update_camera_transition(&mut self, delta_time: f32) -> Option<f32> {
+        let transition = self.camera_transition.as_mut()?;
+        let field_of_view = transition.update(&mut self.camera, delta_time);
+        if transition.is_finished() {
+            self.camera_transition = None;
+        }
+        Some(field_of_view)
+    }
+
+    /// Advance timeline playback by `delta_time` seconds, writing animated values into GUI
+    /// settings and entities.
+    pub fn update_timeline(&mut self, delta_time: f32, draw_props: &mut DrawProperties) {
+        self.timeline.retain_existing(&self.world);
+        self.timeline
+            .update(delta_time, draw_props, &mut self.world);
+    }
+
+    /// Jump the timeline to a point of time, writing the values there.
+    pub fn seek_timeline(&mut self, time: f32, draw_props: &mut DrawProperties) {
+        self.timeline.retain_existing(&self.world);
+        self.timeline.seek(time, draw_props, &mut self.world);
+    }
+
+    /// Drive modulated parameters of GUI settings and lights for a logic update of `delta_time`
+    /// seconds.
+    pub fn update_modulation(&mut self, delta_time: f32, draw_props: &mut DrawProperties) {
+        self.modulation
+            .update(delta_time, draw_props, &mut self.world);
+    }
+
+    /// Set how far the drawn frame is between the last logic update (0) and the next one (1).
+    pub fn set_interpolation(&mut self, alpha: f32) {
+        self.interpolation = alpha.clamp(0.0, 1.0);
+    }
+
+    /// Camera placed between the last two logic updates, seen from when drawing.
+    pub fn interpolated_camera(&self) -> Camera {
+        self.camera.interpolated(self.interpolation)
+    }
+
+    /// Model matrix of the entity placed between the last two logic updates. Entities spawned
+    /// since the last update are drawn where they are.
+    pub fn interpolated_model_matrix(&self, entity: Entity, transform: &Transform) -> Matrix4<f32> {
+        match self.previous_transforms.get(&entity) {
+            Some(previous)
+                if self.interpolation < 1.0
+                    && (previous.position != transform.position
+                        || previous.rotation != transform.rotation) =>
+            {
+                calculate_interpolated_model_matrix(previous, transform, self.interpolation)
+            }
+            _ => calculate_model_matrix(transform),
+        }
+    }
+
+    /// Spawn an entity of the model at the given index that can be selected from GUI. The entity is
+    /// named after the model.
+    pub fn spawn_selectable(&mut self, model_index: usize) -> Entity {
+        let name = self
+            .models
+            .get(model_index)
+            .map_or(String::new(), |model| model.name.clone());
+        self.world.spawn((
+            Renderable { model_index },
+            Transform::default(),
+            Material::default(),
+            Name(name),
+            Selectable,
+        ))
+    }
+
+    /// Spawn a visible copy of a renderable entity, placed next to the original. The copy is not
+    /// part of the model selection.
+    pub fn duplicate(&mut self, entity: Entity) -> Option<Entity> {
+        let renderable = *self.world.get::<&Renderable>(entity).ok()?;
+        let mut transform = self
+            .world
+            .get::<&Transform>(entity)
+            .map_or(Transform::default(), |transform| *transform);
+        // Side by side instead of overlapping, so the copy can be told apart
+        transform.position.x += 1.5;
+        let material = self
+            .world
+            .get::<&Material>(entity)
+            .map_or(Material::default(), |material| material.clone());
+        let name = format!("{} copy", self.entity_name(entity));
+        let part_overrides = self
+            .world
+            .get::<&PartOverrides>(entity)
+            .ok()
+            .map(|part_overrides| (*part_overrides).clone());
+
+        let copy = self
+            .world
+            .spawn((renderable, transform, material, Name(name), Visible));
+        if let Some(part_overrides) = part_overrides {
+            self.world.insert_one(copy, part_overrides).unwrap();
+        }
+        Some(copy)
+    }
+
+    /// Spawn another visible instance of a loaded model at the origin. The instance shares the
+    /// mesh of the model, but has its own transform and material.
+    pub fn spawn_instance(&mut self, model_index: usize) -> Entity {
+        let instance_count = self
+            .world
+            .query::<&Renderable>()
+            .iter()
+            .filter(|(_, renderable)| renderable.model_index == model_index)
+            .count();
+        let name = format!("{} {}", self.models[model_index].name, instance_count + 1);
+        self.world.spawn((
+            Renderable { model_index },
+            Transform::default(),
+            Material::default(),
+            Name(name),
+            Visible,
+        ))
+    }
+
+    /// Name of the entity, falling back to the name of its model.
+    pub fn entity_name(&self, entity: Entity) -> String {
+        if let Ok(name) = self.world.get::<&Name>(entity) {
+            return name.0.clone();
+        }
+        self.world
+            .get::<&Renderable>(entity)
+            .ok()
+            .and_then(|renderable| self.models.get(renderable.model_index))
+            .map_or(String::new(), |model| model.name.clone())
+    }
+
+    /// Despawn every renderable entity and remove models, e.g. before loading them again into a
+    /// new graphics context.
+    pub fn clear_models(&mut self) {
+        let entities: Vec<Entity> = self
+            .world
+            .query::<&Renderable>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .collect();
+        for entity in entities {
+            let _ = self.world.despawn(entity);
+        }
+        self.models.clear();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.lightmaps.clear();
+        self.applied_selection = None;
+    }
+
+    /// Replace baked lighting of every entity, e.g. with the result of `bake_lightmaps()`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_lightmaps(&mut self, lightmaps: Vec<(Entity, Lightmap)>) {
+        self.lightmaps = lightmaps.into_iter().collect();
+    }
+
+    /// Baked lighting of the entity, `None` if it wasn't visible when lighting was last baked.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn lightmap(&self, entity: Entity) -> Option<&Lightmap> {
+        self.lightmaps.get(&entity)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn lightmaps(&self) -> impl Iterator<Item = (Entity, &Lightmap)> {
+        self.lightmaps
+            .iter()
+            .map(|(&entity, lightmap)| (entity, lightmap))
+    }
+
+    /// Direction of the directional light controlled from GUI. Lights from straight above if the
+    /// light entity was despawned.
+    pub fn light_direction(&self) -> [f32; 3] {
+        self.world
+            .get::<&DirectionalLight>(self.light)
+            .map_or([0.0, -1.0, 0.0], |light| light.direction)
+    }
+
+    pub fn spawn_point_light(&mut self, point_light: PointLight) -> Entity {
+        self.world.spawn((point_light,))
+    }
+
+    /// Point lights taken into account by shaders, at most `MAX_POINT_LIGHTS` in a stable order.
+    pub fn point_lights(&self) -> Vec<(Entity, PointLight)> {
+        let mut point_lights: Vec<(Entity, PointLight)> = self
+            .world
+            .query::<&PointLight>()
+            .iter()
+            .map(|(entity, point_light)| (entity, *point_light))
+            .collect();
+        // Query order changes with structural changes
+        point_lights.sort_by_key(|(entity, _)| entity.id());
+        point_lights.truncate(MAX_POINT_LIGHTS);
+        point_lights
+    }
+
+    pub fn spawn_area_light(&mut self, area_light: AreaLight) -> Entity {
+        self.world.spawn((area_light,))
+    }
+
+    /// Area lights taken into account by shaders, at most `MAX_AREA_LIGHTS` in a stable order.
+    pub fn area_lights(&self) -> Vec<(Entity, AreaLight)> {
+        let mut area_lights: Vec<(Entity, AreaLight)> = self
+            .world
+            .query::<&AreaLight>()
+            .iter()
+            .map(|(entity, area_light)| (entity, *area_light))
+            .collect();
+        // Query order changes with structural changes
+        area_lights.sort_by_key(|(entity, _)| entity.id());
+        area_lights.truncate(MAX_AREA_LIGHTS);
+        area_lights
+    }
+
+    /// Decals taken into account by shaders, at most `MAX_DECALS`.
+    pub fn drawn_decals(&self) -> &[Decal] {
+        &self.decals[..self.decals.len().min(MAX_DECALS)]
+    }
+
+    /// Axis-aligned box enclosing every visible entity in world space, `None` if nothing is visible.
+    pub fn visible_bounds(&self) -> Option<Aabb> {
+        let mut query = self
+            .world
+            .query::<(&Renderable, &Transform)>()
+            .with::<&Visible>();
+        let corners: Vec<_> = query
+            .iter()
+            .flat_map(|(_, (renderable, transform))| {
+                self.models[renderable.model_index]
+                    .bounds()
+                    .transformed(&calculate_model_matrix(transform))
+                    .corners()
+            })
+            .collect();
+        (!corners.is_empty()).then(|| Aabb::from_points(corners))
+    }
+
+    /// Visible entity whose model is hit first by the ray, with the hit in model space. Models
+    /// missed by their bounding box are skipped, bounding volume hierarchies of the others are
+    /// built on first use. Tested against the full detail mesh as uploaded.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pick(&self, ray: &Ray) -> Result<Option<(Entity, RayHit)>, String> {
+        use cgmath::SquareMatrix;
+
+        let mut closest: Option<(Entity, RayHit)> = None;
+        let mut query = self
+            .world
+            .query::<(&Renderable, &Transform)>()
+            .with::<&Visible>();
+        for (entity, (renderable, transform)) in query.iter() {
+            let model = &self.models[renderable.model_index];
+            let Some(inverse_model_matrix) =
+                self.interpolated_model_matrix(entity, transform).invert()
+            else {
+                continue;
+            };
+            // Distances along the transformed ray are the same as in world space
+            let model_ray = ray.transformed(&inverse_model_matrix);
+            let max_distance = closest.map_or(f32::INFINITY, |(_, hit)| hit.distance);
+            if model_ray
+                .intersect_aabb(&model.bounds())
+                .is_none_or(|distance| max_distance <= distance)
+            {
+                continue;
+            }
+            if let Some(hit) = model.lod(0).bvh()?.intersect(&model_ray, max_distance) {
+                closest = Some((entity, hit));
+            }
+        }
+        Ok(closest)
+    }
+
+    /// Inspect the triangle hit by a ray returned by `pick()`, or nothing on a miss. Vertices are
+    /// read back from GPU memory only when another triangle is hit.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn inspect(&mut self, hit: Option<(Entity, RayHit)>) -> Result<(), String> {
+        let Some((entity, hit)) = hit else {
+            self.inspector.set_hovered(None);
+            return Ok(());
+        };
+        let entity_missing = |_| "inspected entity no longer exists".to_string();
+        let model_index = self
+            .world
+            .get::<&Renderable>(entity)
+            .map_err(entity_missing)?
+            .model_index;
+        let transform = *self
+            .world
+            .get::<&Transform>(entity)
+            .map_err(entity_missing)?;
+        let model_matrix = self.interpolated_model_matrix(entity, &transform);
+        let triangle = match self.inspector.hovered() {
+            Some(hovered) if hovered.entity == entity && hovered.triangle.index == hit.triangle => {
+                hovered.triangle
+            }
+            _ => self.models[model_index]
+                .lod(0)
+                .read_triangle(hit.triangle)?,
+        };
+        self.inspector.set_hovered(Some(InspectedTriangle {
+            entity,
+            triangle,
+            model_matrix,
+        }));
+        Ok(())
+    }
+
+    /// Entity of the model selection displaying the model at the given index.
+    pub fn selected_entity(&self, selected_model_index: usize) -> Option<Entity> {
+        self.world
+            .query::<&Renderable>()
+            .with::<&Selectable>()
+            .iter()
+            .find(|(_, renderable)| renderable.model_index == selected_model_index)
+            .map(|(entity, _)| entity)
+    }
+
+    /// System applying GUI settings to the light and to the entities of model selection.
+    pub fn apply_draw_properties(&mut self, draw_props: &DrawProperties) {
+        let selection_changed = self.applied_selection != Some(draw_props.selected_model_index);
+        self.applied_selection = Some(draw_props.selected_model_index);
+        let mut selection = Vec::new();
+        for (entity, (renderable, transform)) in self
+            .world
+            .query_mut::<(&Renderable, &mut Transform)>()
+            .with::<&Selectable>()
+        {
+            let selected = renderable.model_index == draw_props.selected_model_index;
+            if selected {
+                transform.rotation = draw_props.model_rotation;
+            }
+            selection.push((entity, selected));
+        }
+
+        // Structural changes are not allowed while iterating query
+        if selection_changed {
+            for (entity, selected) in selection {
+                if selected {
+                    let _ = self.world.insert_one(entity, Visible);
+                } else {
+                    let _ = self.world.remove_one::<Visible>(entity);
+                }
+            }
+        }
+
+        if let Ok(mut light) = self.world.get::<&mut DirectionalLight>(self.light) {
+            light.direction = draw_props.light_direction;
+        }
+    }
+}
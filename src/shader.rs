@@ -1,17 +1,15 @@
 use std::sync::Arc;
 
-use cfg_if::cfg_if;
-use cgmath::{Matrix, Matrix3, Matrix4, Point3, Vector3};
+use cgmath::{Matrix, Matrix3, Matrix4, Point3, Vector3, Vector4};
 use glow::*;
 
+use crate::gl_state;
+
 /// Wrapper around shader with helper operations
 /// for loading, compiling, binding and uniform value update.
 pub struct Shader {
     gl: Arc<glow::Context>,
     shader_program: glow::Program,
-
-    #[cfg(not(target_arch = "wasm32"))]
-    subroutine_indices: Vec<u32>,
 }
 
 impl Shader {
@@ -21,44 +19,111 @@ impl Shader {
         fragment_shader_src: &str,
     ) -> Result<Self, String> {
         unsafe {
-            let vertex_shader = compile(&gl, vertex_shader_src, glow::VERTEX_SHADER)
-                .map_err(|e| format!("failed to compile vertex shader: {e}"))?;
-            let fragment_shader = compile(&gl, fragment_shader_src, glow::FRAGMENT_SHADER)
-                .map_err(|e| format!("failed to compile fragment shader: {e}"))?;
-
-            let shader_program = gl
-                .create_program()
-                .map_err(|e| format!("cannot create shader program: {e}"))?;
-            gl.attach_shader(shader_program, vertex_shader);
-            gl.attach_shader(shader_program, fragment_shader);
-            gl.link_program(shader_program);
-            if !gl.get_program_link_status(shader_program) {
-                return Err(format!(
-                    "failed to link shader program: {}",
-                    gl.get_program_info_log(shader_program)
-                ));
-            }
+            Self::build(
+                gl,
+                &[
+                    (vertex_shader_src, glow::VERTEX_SHADER, "vertex"),
+                    (fragment_shader_src, glow::FRAGMENT_SHADER, "fragment"),
+                ],
+            )
+        }
+    }
+
+    /// Program with a geometry shader processing assembled primitives between the vertex and the
+    /// fragment shader. Geometry shaders are not available in OpenGL ES 3.0 and WebGL2.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_geometry_shader(
+        gl: Arc<glow::Context>,
+        vertex_shader_src: &str,
+        geometry_shader_src: &str,
+        fragment_shader_src: &str,
+    ) -> Result<Self, String> {
+        unsafe {
+            Self::build(
+                gl,
+                &[
+                    (vertex_shader_src, glow::VERTEX_SHADER, "vertex"),
+                    (geometry_shader_src, glow::GEOMETRY_SHADER, "geometry"),
+                    (fragment_shader_src, glow::FRAGMENT_SHADER, "fragment"),
+                ],
+            )
+        }
+    }
 
-            cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
-                Ok(Self {
-                    gl,
-                    shader_program,
-                    subroutine_indices: Vec::new(),
-                })
-            } else {
-                Ok(Self {
-                    gl,
-                    shader_program,
-                })
-
-            }}
+    /// Compile the sources of the stages, each with its shader type and name for error messages,
+    /// and link them. Stages compiled before a failing one are deleted.
+    unsafe fn build(gl: Arc<glow::Context>, stages: &[(&str, u32, &str)]) -> Result<Self, String> {
+        let mut shaders = Vec::with_capacity(stages.len());
+        for &(shader_src, shader_type, name) in stages {
+            match compile(&gl, shader_src, shader_type) {
+                Ok(shader) => shaders.push(shader),
+                Err(e) => {
+                    for shader in shaders {
+                        gl.delete_shader(shader);
+                    }
+                    return Err(format!("failed to compile {name} shader: {e}"));
+                }
+            }
         }
+        let shader_program =
+            link(&gl, &shaders).map_err(|e| format!("failed to link shader program: {e}"))?;
+        Ok(Self { gl, shader_program })
     }
 
     /// Bind shader to graphics pipeline to use for draw calls.
     pub fn r#use(&self) {
         unsafe {
-            self.gl.use_program(Some(self.shader_program));
+            gl_state::use_program(&self.gl, Some(self.shader_program));
+        }
+    }
+
+    /// Uniforms unused by shader code, e.g. in disabled code paths of a shader variant, are removed
+    /// by the shader compiler and ignored.
+    pub fn set_uniform<T: Uniform>(&self, name: &str, v: &T) {
+        unsafe {
+            if let Some(uniform_location) = self.gl.get_uniform_location(self.shader_program, name)
+            {
+                v.set_uniform(&self.gl, uniform_location);
+            }
+        }
+    }
+}
+
+impl Drop for Shader {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_program(self.shader_program);
+            gl_state::forget_program(self.shader_program);
+        }
+    }
+}
+
+/// Wrapper around compute shader program for running general purpose calculations on GPU outside
+/// of the graphics pipeline.
+///
+/// Compute shaders are only supported from OpenGL 4.3+ and are not available in WebGL2.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct ComputeShader {
+    gl: Arc<glow::Context>,
+    shader_program: glow::Program,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ComputeShader {
+    pub fn new(gl: Arc<glow::Context>, compute_shader_src: &str) -> Result<Self, String> {
+        unsafe {
+            let compute_shader = compile(&gl, compute_shader_src, glow::COMPUTE_SHADER)
+                .map_err(|e| format!("failed to compile compute shader: {e}"))?;
+            let shader_program = link(&gl, &[compute_shader])
+                .map_err(|e| format!("failed to link compute shader program: {e}"))?;
+            Ok(Self { gl, shader_program })
+        }
+    }
+
+    /// Bind shader program for subsequent uniform updates and dispatches.
+    pub fn r#use(&self) {
+        unsafe {
+            gl_state::use_program(&self.gl, Some(self.shader_program));
         }
     }
 
@@ -69,37 +134,38 @@ impl Shader {
         }
     }
 
-    /// Change subroutines to use in shader based on list of subroutine names.
-    ///
-    /// Subroutines are analogous to C function pointers and is an efficient way
-    /// to customize parts of the shader program to execute.
-    ///
-    /// Shader subroutines are only supported from OpenGL 4.0+ and are not
-    /// available in OpenGL ES 3.0.
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn update_subroutines(&mut self, shader_type: u32, names: &[&str]) {
-        // TODO: Clearing subroutine indices on every frame update is slow
-        self.subroutine_indices.clear();
-
-        for &name in names {
-            let index = unsafe {
-                self.gl
-                    .get_subroutine_index(self.shader_program, shader_type, name)
-            };
-            self.subroutine_indices.push(index);
+    /// Attach buffer to shader storage block declared with `layout (binding = index)`.
+    pub fn bind_storage_buffer(&self, index: u32, buffer: glow::Buffer) {
+        unsafe {
+            self.gl
+                .bind_buffer_base(glow::SHADER_STORAGE_BUFFER, index, Some(buffer));
         }
+    }
 
+    /// Launch work groups in three dimensions. Number of invocations within a work group is
+    /// declared by `local_size` layout qualifier in shader code.
+    pub fn dispatch(&self, groups_x: u32, groups_y: u32, groups_z: u32) {
         unsafe {
-            self.gl
-                .uniform_subroutines_u32_slice(shader_type, &self.subroutine_indices);
+            self.gl.dispatch_compute(groups_x, groups_y, groups_z);
+        }
+    }
+
+    /// Make writes of previous dispatches visible to subsequent GPU operations. Barrier bits
+    /// describe how the written data is going to be used, e.g. `glow::VERTEX_ATTRIB_ARRAY_BARRIER_BIT`
+    /// for vertex buffers read by draw calls.
+    pub fn memory_barrier(&self, barriers: u32) {
+        unsafe {
+            self.gl.memory_barrier(barriers);
         }
     }
 }
 
-impl Drop for Shader {
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for ComputeShader {
     fn drop(&mut self) {
         unsafe {
             self.gl.delete_program(self.shader_program);
+            gl_state::forget_program(self.shader_program);
         }
     }
 }
@@ -109,21 +175,58 @@ unsafe fn compile(
     shader_src: &str,
     shader_type: u32,
 ) -> Result<glow::Shader, String> {
+    // GLSL ES 3.00 variants are also used on desktop OpenGL contexts older than 4.3, where GLSL
+    // 3.30 accepts the same code except for the version directive.
+    #[cfg(not(target_arch = "wasm32"))]
+    let shader_src = if gl.version().is_embedded {
+        shader_src.to_string()
+    } else {
+        shader_src.replacen("#version 300 es", "#version 330 core", 1)
+    };
+
     let shader = gl
         .create_shader(shader_type)
         .map_err(|e| format!("cannot create shader: {e}"))?;
     gl.shader_source(shader, &shader_src);
     gl.compile_shader(shader);
     if !gl.get_shader_compile_status(shader) {
-        return Err(format!(
-            "failed to compile GLSL code: {}",
-            gl.get_shader_info_log(shader)
-        ));
+        let info_log = gl.get_shader_info_log(shader);
+        gl.delete_shader(shader);
+        return Err(format!("failed to compile GLSL code: {info_log}"));
     }
 
     Ok(shader)
 }
 
+/// Link the compiled shaders into a program. Shaders are detached and deleted afterwards, and the
+/// program is deleted if linking fails.
+unsafe fn link(gl: &glow::Context, shaders: &[glow::Shader]) -> Result<glow::Program, String> {
+    let shader_program = match gl.create_program() {
+        Ok(shader_program) => shader_program,
+        Err(e) => {
+            for shader in shaders {
+                gl.delete_shader(*shader);
+            }
+            return Err(format!("cannot create shader program: {e}"));
+        }
+    };
+    for shader in shaders {
+        gl.attach_shader(shader_program, *shader);
+    }
+    gl.link_program(shader_program);
+    for shader in shaders {
+        gl.detach_shader(shader_program, *shader);
+        gl.delete_shader(*shader);
+    }
+    if !gl.get_program_link_status(shader_program) {
+        let info_log = gl.get_program_info_log(shader_program);
+        gl.delete_program(shader_program);
+        return Err(info_log);
+    }
+
+    Ok(shader_program)
+}
+
 pub trait Uniform {
     unsafe fn set_uniform(&self, gl: &glow::Context, uniform_location: UniformLocation);
 }
@@ -140,6 +243,24 @@ impl Uniform for i32 {
     }
 }
 
+impl Uniform for u32 {
+    unsafe fn set_uniform(&self, gl: &glow::Context, uniform_location: UniformLocation) {
+        gl.uniform_1_u32(Some(&uniform_location), *self);
+    }
+}
+
+impl Uniform for f32 {
+    unsafe fn set_uniform(&self, gl: &glow::Context, uniform_location: UniformLocation) {
+        gl.uniform_1_f32(Some(&uniform_location), *self);
+    }
+}
+
+impl Uniform for [f32; 2] {
+    unsafe fn set_uniform(&self, gl: &glow::Context, uniform_location: UniformLocation) {
+        gl.uniform_2_f32(Some(&uniform_location), self[0], self[1]);
+    }
+}
+
 impl Uniform for [f32; 3] {
     unsafe fn set_uniform(&self, gl: &glow::Context, uniform_location: UniformLocation) {
         gl.uniform_3_f32(Some(&uniform_location), self[0], self[1], self[2]);
@@ -158,6 +279,12 @@ impl Uniform for Vector3<f32> {
     }
 }
 
+impl Uniform for Vector4<f32> {
+    unsafe fn set_uniform(&self, gl: &glow::Context, uniform_location: UniformLocation) {
+        gl.uniform_4_f32(Some(&uniform_location), self.x, self.y, self.z, self.w);
+    }
+}
+
 impl Uniform for Matrix3<f32> {
     unsafe fn set_uniform(&self, gl: &glow::Context, uniform_location: UniformLocation) {
         let slice = std::slice::from_raw_parts(self.as_ptr(), 9);
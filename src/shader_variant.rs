@@ -0,0 +1,216 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::Arc};
+
+use crate::shader::Shader;
+
+/// Set of compile-time shader features. Each enabled feature is passed to GLSL code as a `#define`,
+/// so disabled code paths are removed by the shader compiler instead of being branched on at
+/// runtime.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShaderFeatures(u32);
+
+impl ShaderFeatures {
+    pub const DIFFUSE: Self = Self(1 << 0);
+    pub const SPECULAR: Self = Self(1 << 1);
+    pub const REFLECTIVE_SURFACE: Self = Self(1 << 2);
+    pub const REFRACTIVE_SURFACE: Self = Self(1 << 3);
+    /// Per-draw data read from a storage buffer, GLSL 4.30 only.
+    pub const MULTI_DRAW_INDIRECT: Self = Self(1 << 4);
+    /// Material color multiplied by the color vertex attribute.
+    pub const VERTEX_COLOR: Self = Self(1 << 5);
+    /// Color vertex attribute replacing the material color, together with `VERTEX_COLOR`.
+    pub const VERTEX_COLOR_REPLACE: Self = Self(1 << 6);
+    /// Diffuse and ambient lighting read from a baked lightmap texture. Lightmaps are only baked
+    /// in native builds.
+    pub const LIGHTMAP: Self = Self(1 << 7);
+
+    const DEFINES: [(Self, &'static str); 8] = [
+        (Self::DIFFUSE, "DIFFUSE_ENABLED"),
+        (Self::SPECULAR, "SPECULAR_ENABLED"),
+        (Self::REFLECTIVE_SURFACE, "REFLECTIVE_SURFACE"),
+        (Self::REFRACTIVE_SURFACE, "REFRACTIVE_SURFACE"),
+        (Self::MULTI_DRAW_INDIRECT, "MULTI_DRAW_INDIRECT"),
+        (Self::VERTEX_COLOR, "VERTEX_COLOR"),
+        (Self::VERTEX_COLOR_REPLACE, "VERTEX_COLOR_REPLACE"),
+        (Self::LIGHTMAP, "LIGHTMAP"),
+    ];
+
+    pub fn contains(self, features: Self) -> bool {
+        self.0 & features.0 == features.0
+    }
+
+    pub fn set(&mut self, features: Self, enabled: bool) {
+        if enabled {
+            self.0 |= features.0;
+        } else {
+            self.0 &= !features.0;
+        }
+    }
+
+    fn defines(self) -> String {
+        Self::DEFINES
+            .iter()
+            .filter(|(feature, _)| self.contains(*feature))
+            .map(|(_, name)| format!("#define {name}\n"))
+            .collect()
+    }
+}
+
+/// Shader programs compiled from the same sources with different feature sets. Works the same with
+/// GLSL 4.30 and GLSL ES 3.00 sources.
+///
+/// Variants are compiled on first use and kept for the lifetime of the cache.
+pub struct ShaderVariantCache {
+    gl: Arc<glow::Context>,
+    vertex_shader_src: String,
+    fragment_shader_src: String,
+    // Failed compilations are also remembered to report them only once
+    variants: RefCell<HashMap<ShaderFeatures, Result<Rc<Shader>, String>>>,
+}
+
+impl ShaderVariantCache {
+    pub fn new(gl: Arc<glow::Context>, vertex_shader_src: &str, fragment_shader_src: &str) -> Self {
+        Self {
+            gl,
+            vertex_shader_src: vertex_shader_src.to_owned(),
+            fragment_shader_src: fragment_shader_src.to_owned(),
+            variants: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Shader program of the feature set, compiled if it wasn't requested before.
+    pub fn get(&self, features: ShaderFeatures) -> Result<Rc<Shader>, String> {
+        self.variants
+            .borrow_mut()
+            .entry(features)
+            .or_insert_with(|| {
+                let defines = features.defines();
+                let shader = Shader::new(
+                    self.gl.clone(),
+                    &insert_defines(&self.vertex_shader_src, &defines),
+                    &insert_defines(&self.fragment_shader_src, &defines),
+                )
+                .map(Rc::new);
+                if let Err(e) = &shader {
+                    eprintln!("shader variant compilation failed: {e}");
+                }
+                shader
+            })
+            .clone()
+    }
+
+    pub fn fragment_shader_src(&self) -> &str {
+        &self.fragment_shader_src
+    }
+
+    /// Feature sets requested so far, whether their compilation succeeded or not.
+    pub fn requested_features(&self) -> Vec<ShaderFeatures> {
+        self.variants.borrow().keys().copied().collect()
+    }
+}
+
+/// Defines are placed after the version directive, which must be the first line of GLSL code. Line
+/// numbers of compiler messages are reset to match the lines of the original source.
+pub(crate) fn insert_defines(shader_src: &str, defines: &str) -> String {
+    match shader_src.split_once('\n') {
+        Some((version, rest)) if version.starts_with("#version") => {
+            format!("{version}\n{defines}#line 2\n{rest}")
+        }
+        _ => format!("{defines}#line 1\n{shader_src}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::Write,
+        process::{Command, Stdio},
+    };
+
+    use super::*;
+
+    /// Reference GLSL compiler of Khronos, e.g. from the glslang-tools package. Required by tests,
+    /// because the build script can't catch compile errors.
+    const VALIDATOR: &str = "glslangValidator";
+    const SHADER_DIR: &str = "assets/shaders";
+
+    /// Compile the source for OpenGL, the profile is taken from its version directive.
+    fn validate(source: &str, stage: &str) -> Result<(), String> {
+        let mut validator = Command::new(VALIDATOR)
+            .args(["--stdin", "-S", stage])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("cannot run {VALIDATOR}: {e}"))?;
+        validator
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(source.as_bytes())
+            .map_err(|e| format!("cannot write source to {VALIDATOR}: {e}"))?;
+        let output = validator
+            .wait_with_output()
+            .map_err(|e| format!("cannot run {VALIDATOR}: {e}"))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+    }
+
+    /// Feature sets model shaders are compiled with. Vertex color replacement needs vertex colors
+    /// and materials are either reflective or refractive.
+    fn model_feature_sets(gles: bool) -> Vec<ShaderFeatures> {
+        (0..1 << ShaderFeatures::DEFINES.len())
+            .map(ShaderFeatures)
+            .filter(|features| {
+                let unused = (features.contains(ShaderFeatures::VERTEX_COLOR_REPLACE)
+                    && !features.contains(ShaderFeatures::VERTEX_COLOR))
+                    || (features.contains(ShaderFeatures::REFLECTIVE_SURFACE)
+                        && features.contains(ShaderFeatures::REFRACTIVE_SURFACE))
+                    || (gles && features.contains(ShaderFeatures::MULTI_DRAW_INDIRECT));
+                !unused
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shader_sources_compile() {
+        if let Err(e) = Command::new(VALIDATOR).arg("--version").output() {
+            panic!("cannot run {VALIDATOR}, install glslang, e.g. the glslang-tools package: {e}");
+        }
+        let mut file_names: Vec<String> = fs::read_dir(SHADER_DIR)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        file_names.sort();
+
+        let mut errors = Vec::new();
+        for file_name in &file_names {
+            let source = fs::read_to_string(format!("{SHADER_DIR}/{file_name}")).unwrap();
+            let stage = file_name.split('.').nth(1).unwrap();
+            let gles = file_name.contains("_gles3.");
+            let feature_sets = if file_name.starts_with("model_") {
+                model_feature_sets(gles)
+            } else {
+                vec![ShaderFeatures::default()]
+            };
+            for features in feature_sets {
+                let source = insert_defines(&source, &features.defines());
+                // GLSL ES 3.00 sources are also compiled as GLSL 3.30 on older desktop contexts
+                let mut sources = vec![source.clone()];
+                if gles {
+                    sources.push(source.replacen("#version 300 es", "#version 330 core", 1));
+                }
+                for source in sources {
+                    if let Err(e) = validate(&source, stage) {
+                        let version = source.lines().next().unwrap();
+                        let defines = features.defines().lines().collect::<Vec<_>>().join(", ");
+                        errors.push(format!("{file_name} ({version}, {defines}):\n{e}"));
+                    }
+                }
+            }
+        }
+        assert!(errors.is_empty(), "{}", errors.join("\n"));
+    }
+}
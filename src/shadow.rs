@@ -0,0 +1,491 @@
+use std::sync::Arc;
+
+use cgmath::{
+    Deg, EuclideanSpace, InnerSpace, Matrix4, Point3, SquareMatrix, Transform as _, Vector3,
+};
+use glow::Texture;
+use hecs::Entity;
+
+use crate::{
+    framebuffer::{DepthCubeFramebuffer, DepthFramebuffer},
+    Aabb, PointLight,
+};
+
+/// Most cascades the model shaders can sample.
+pub const MAX_SHADOW_CASCADES: usize = 4;
+/// Weight of logarithmic split distances against uniform ones. Logarithmic splits give nearby
+/// cascades more resolution, uniform splits keep distant cascades from growing too large.
+const SPLIT_LAMBDA: f32 = 0.75;
+/// Cascade bounds are rounded up to this step, so their size doesn't change while the camera
+/// rotates due to floating point inaccuracy.
+const RADIUS_STEP: f32 = 1.0 / 16.0;
+/// Width and height of each face of point light shadow maps in texels.
+const POINT_SHADOW_RESOLUTION: u32 = 512;
+/// Near plane of point light shadow map projections. Model shaders convert distances to depth
+/// with the same value.
+const POINT_SHADOW_NEAR: f32 = 0.05;
+
+/// Shadows of the directional light, cast and received by models with forward rendering.
+///
+/// The view frustum is split into cascades along the view direction up to the shadow distance.
+/// Every cascade has its own shadow map covering a larger part of the scene than the previous.
+#[derive(Clone, Copy, PartialEq)]
+pub struct ShadowSettings {
+    pub enabled: bool,
+    /// Number of cascades from 1 to `MAX_SHADOW_CASCADES`.
+    pub cascade_count: usize,
+    /// Width and height of the shadow map of each cascade in texels, nearest cascade first.
+    pub resolutions: [u32; MAX_SHADOW_CASCADES],
+    /// Shadow map texels sampled in each direction by percentage-closer filtering. 0 gives hard
+    /// shadows, otherwise (2 × radius + 1)² texels are averaged.
+    pub pcf_radius: u32,
+    /// Distance along the view direction up to which shadows are drawn, in world units.
+    pub distance: f32,
+    /// Tint surfaces by the cascade their shadow is sampled from.
+    pub cascade_visualization: bool,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cascade_count: 3,
+            resolutions: [2048, 1024, 1024, 512],
+            pcf_radius: 1,
+            distance: 30.0,
+            cascade_visualization: false,
+        }
+    }
+}
+
+/// Orthographic light view enclosing a slice of the view frustum.
+pub(crate) struct ShadowCascade {
+    pub view: Matrix4<f32>,
+    pub projection: Matrix4<f32>,
+    /// Eye position of the light view in world space.
+    pub position: Point3<f32>,
+    /// Distance along the view direction where the cascade ends.
+    pub far_distance: f32,
+    /// World size of a shadow map texel.
+    pub texel_size: f32,
+}
+
+/// Shadow maps of the directional light, fitted around the view of the last rendered frame.
+pub(crate) struct ShadowMaps {
+    gl: Arc<glow::Context>,
+    targets: Vec<DepthFramebuffer>,
+    cascades: Vec<ShadowCascade>,
+    /// Eye position and view direction the cascades are fitted to.
+    view_position: Point3<f32>,
+    view_direction: Vector3<f32>,
+    settings: ShadowSettings,
+}
+
+impl ShadowMaps {
+    pub fn new(gl: Arc<glow::Context>) -> Self {
+        Self {
+            gl,
+            targets: Vec::new(),
+            cascades: Vec::new(),
+            view_position: Point3::origin(),
+            view_direction: -Vector3::unit_z(),
+            settings: ShadowSettings::default(),
+        }
+    }
+
+    /// Fit cascades around the view and resize their shadow maps. Contents have to be drawn
+    /// afterwards.
+    pub fn update(
+        &mut self,
+        settings: &ShadowSettings,
+        view: &Matrix4<f32>,
+        projection: &Matrix4<f32>,
+        light_direction: [f32; 3],
+        scene_bounds: Option<Aabb>,
+    ) -> Result<(), String> {
+        self.settings = *settings;
+        let inverse_view = view.invert().ok_or("view matrix is not invertible")?;
+        self.view_position = inverse_view.transform_point(Point3::origin());
+        self.view_direction = inverse_view
+            .transform_vector(-Vector3::unit_z())
+            .normalize();
+        self.cascades = fit_cascades(
+            &inverse_view,
+            projection,
+            light_direction,
+            scene_bounds,
+            settings,
+        );
+
+        self.targets.truncate(self.cascades.len());
+        for (index, &resolution) in settings.resolutions[..self.cascades.len()]
+            .iter()
+            .enumerate()
+        {
+            match self.targets.get_mut(index) {
+                Some(target) => target.resize(resolution)?,
+                None => self
+                    .targets
+                    .push(DepthFramebuffer::new(self.gl.clone(), resolution)?),
+            }
+        }
+        Ok(())
+    }
+
+    /// Disable shadows and release shadow maps.
+    pub fn clear(&mut self) {
+        self.targets.clear();
+        self.cascades.clear();
+    }
+
+    pub fn cascades(&self) -> impl Iterator<Item = (&ShadowCascade, &DepthFramebuffer)> {
+        self.cascades.iter().zip(&self.targets)
+    }
+
+    pub fn cascade_count(&self) -> usize {
+        self.cascades.len()
+    }
+
+    pub fn textures(&self) -> impl Iterator<Item = Texture> + '_ {
+        self.targets.iter().map(DepthFramebuffer::depth_texture)
+    }
+
+    pub fn view_position(&self) -> Point3<f32> {
+        self.view_position
+    }
+
+    pub fn view_direction(&self) -> Vector3<f32> {
+        self.view_direction
+    }
+
+    pub fn settings(&self) -> &ShadowSettings {
+        &self.settings
+    }
+}
+
+/// Cube shadow maps of point lights with shadows enabled, indexed like `Scene::point_lights()`.
+pub(crate) struct PointShadowMaps {
+    gl: Arc<glow::Context>,
+    lights: Vec<(PointLight, Option<DepthCubeFramebuffer>)>,
+}
+
+impl PointShadowMaps {
+    pub fn new(gl: Arc<glow::Context>) -> Self {
+        Self {
+            gl,
+            lights: Vec::new(),
+        }
+    }
+
+    /// Create shadow maps of lights with shadows enabled and release the others. Contents have to
+    /// be drawn afterwards.
+    pub fn update(&mut self, point_lights: &[(Entity, PointLight)]) -> Result<(), String> {
+        self.lights.truncate(point_lights.len());
+        for (index, (_, point_light)) in point_lights.iter().enumerate() {
+            if self.lights.len() <= index {
+                self.lights.push((*point_light, None));
+            }
+            let (light, target) = &mut self.lights[index];
+            *light = *point_light;
+            if !point_light.shadows_enabled {
+                *target = None;
+            } else if target.is_none() {
+                *target = Some(DepthCubeFramebuffer::new(
+                    self.gl.clone(),
+                    POINT_SHADOW_RESOLUTION,
+                )?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Release every shadow map.
+    pub fn clear(&mut self) {
+        self.lights.clear();
+    }
+
+    /// Lights with a shadow map.
+    pub fn lights(&self) -> impl Iterator<Item = (&PointLight, &DepthCubeFramebuffer)> {
+        self.lights
+            .iter()
+            .filter_map(|(light, target)| target.as_ref().map(|target| (light, target)))
+    }
+
+    /// Shadow map texture of the light at the index of `Scene::point_lights()`.
+    pub fn texture(&self, index: usize) -> Option<Texture> {
+        self.lights
+            .get(index)
+            .and_then(|(_, target)| target.as_ref())
+            .map(DepthCubeFramebuffer::depth_texture)
+    }
+}
+
+/// Perspective projection of each face of a point light shadow map, reaching as far as the light.
+pub(crate) fn cube_face_projection(range: f32) -> Matrix4<f32> {
+    cgmath::perspective(
+        Deg(90.0),
+        1.0,
+        POINT_SHADOW_NEAR,
+        range.max(2.0 * POINT_SHADOW_NEAR),
+    )
+}
+
+/// Views from a point through each face of a cube map, in the order of
+/// `TEXTURE_CUBE_MAP_POSITIVE_X` and following targets. Up vectors point down on the side faces,
+/// because cube map faces are stored upside down compared to 2D textures.
+pub(crate) fn cube_face_views(position: Point3<f32>) -> [Matrix4<f32>; 6] {
+    [
+        (Vector3::unit_x(), -Vector3::unit_y()),
+        (-Vector3::unit_x(), -Vector3::unit_y()),
+        (Vector3::unit_y(), Vector3::unit_z()),
+        (-Vector3::unit_y(), -Vector3::unit_z()),
+        (Vector3::unit_z(), -Vector3::unit_y()),
+        (-Vector3::unit_z(), -Vector3::unit_y()),
+    ]
+    .map(|(direction, up)| Matrix4::look_at_rh(position, position + direction, up))
+}
+
+/// Split the view frustum between the near plane and the shadow distance, then fit an
+/// orthographic light view around each slice. Returns no cascades for orthographic projection or
+/// zero-length light direction.
+///
+/// Each light view encloses the bounding sphere of its slice, so its size doesn't change while the
+/// camera rotates, and it moves in whole texels, so shadow edges don't shimmer when the camera
+/// moves. Light views are pulled back to include casters of the whole scene in front of them, and
+/// reach as far behind, so no part of the scene is clipped by their depth range.
+fn fit_cascades(
+    inverse_view: &Matrix4<f32>,
+    projection: &Matrix4<f32>,
+    light_direction: [f32; 3],
+    scene_bounds: Option<Aabb>,
+    settings: &ShadowSettings,
+) -> Vec<ShadowCascade> {
+    let direction = Vector3::from(light_direction);
+    if projection.w.w == 1.0 || direction.magnitude2() < f32::EPSILON {
+        return Vec::new();
+    }
+    let direction = direction.normalize();
+    // Up vector must not be parallel with viewing direction
+    let up = if direction.y.abs() < 0.99 {
+        Vector3::unit_y()
+    } else {
+        Vector3::unit_z()
+    };
+
+    // Clip planes of the perspective projection
+    let near = projection.w.z / (projection.z.z - 1.0);
+    let far = projection.w.z / (projection.z.z + 1.0);
+    let distance = settings.distance.min(far).max(near);
+    let cascade_count = settings.cascade_count.clamp(1, MAX_SHADOW_CASCADES);
+
+    let mut cascades = Vec::with_capacity(cascade_count);
+    let mut slice_near = near;
+    for index in 0..cascade_count {
+        let fraction = (index + 1) as f32 / cascade_count as f32;
+        let uniform_split = near + (distance - near) * fraction;
+        let logarithmic_split = near * (distance / near).powf(fraction);
+        let slice_far = SPLIT_LAMBDA * logarithmic_split + (1.0 - SPLIT_LAMBDA) * uniform_split;
+
+        // Corners of the slice in view space, scaled by the tangent of half field of view
+        let corners: Vec<Point3<f32>> = [slice_near, slice_far]
+            .into_iter()
+            .flat_map(|depth| {
+                let half_width = depth / projection.x.x;
+                let half_height = depth / projection.y.y;
+                [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)].map(|(x, y)| {
+                    inverse_view.transform_point(Point3::new(
+                        x * half_width,
+                        y * half_height,
+                        -depth,
+                    ))
+                })
+            })
+            .collect();
+        let center = Point3::centroid(&corners);
+        let radius = corners
+            .iter()
+            .map(|corner| (corner - center).magnitude())
+            .fold(0.0, f32::max);
+        let radius = (radius / RADIUS_STEP).ceil() * RADIUS_STEP;
+
+        // Snap center to shadow map texels in light space
+        let resolution = settings.resolutions[index].max(1) as f32;
+        let texel_size = 2.0 * radius / resolution;
+        let light_rotation = Matrix4::look_at_rh(Point3::origin(), Point3::from_vec(direction), up);
+        let mut light_center = light_rotation.transform_point(center);
+        light_center.x = (light_center.x / texel_size).floor() * texel_size;
+        light_center.y = (light_center.y / texel_size).floor() * texel_size;
+        let center = light_rotation
+            .invert()
+            .unwrap()
+            .transform_point(light_center);
+
+        let pull_back = scene_bounds.map_or(radius, |bounds| {
+            radius.max((bounds.center() - center).magnitude() + bounds.radius())
+        });
+        let position = center - direction * pull_back;
+        cascades.push(ShadowCascade {
+            view: Matrix4::look_at_rh(position, center, up),
+            projection: cgmath::ortho(-radius, radius, -radius, radius, 0.0, 2.0 * pull_back),
+            position,
+            far_distance: slice_far,
+            texel_size,
+        });
+        slice_near = slice_far;
+    }
+    cascades
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Deg, Vector4};
+
+    use super::*;
+
+    /// Tolerance of clip space coordinates for rounding errors.
+    const EPSILON: f32 = 1e-4;
+
+    fn test_view() -> (Matrix4<f32>, Matrix4<f32>) {
+        let view = Matrix4::look_at_rh(
+            Point3::new(3.0, 2.0, 5.0),
+            Point3::new(0.0, 0.5, 0.0),
+            Vector3::unit_y(),
+        );
+        let projection = cgmath::perspective(Deg(60.0), 16.0 / 9.0, 0.1, 100.0);
+        (view, projection)
+    }
+
+    fn test_settings() -> ShadowSettings {
+        ShadowSettings {
+            enabled: true,
+            cascade_count: 3,
+            distance: 40.0,
+            ..Default::default()
+        }
+    }
+
+    fn clip_position(cascade: &ShadowCascade, point: Point3<f32>) -> Vector4<f32> {
+        let clip = cascade.projection * cascade.view * point.to_homogeneous();
+        clip / clip.w
+    }
+
+    /// Corners of the view frustum between the distances along the view direction.
+    fn slice_corners(
+        view: &Matrix4<f32>,
+        projection: &Matrix4<f32>,
+        near: f32,
+        far: f32,
+    ) -> Vec<Point3<f32>> {
+        let inverse_view = view.invert().unwrap();
+        [near, far]
+            .into_iter()
+            .flat_map(|depth| {
+                [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)].map(|(x, y)| {
+                    inverse_view.transform_point(Point3::new(
+                        x * depth / projection.x.x,
+                        y * depth / projection.y.y,
+                        -depth,
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    fn assert_in_clip_space(clip: Vector4<f32>) {
+        for component in [clip.x, clip.y, clip.z] {
+            assert!(
+                (-1.0 - EPSILON..=1.0 + EPSILON).contains(&component),
+                "{clip:?} is outside of clip space"
+            );
+        }
+    }
+
+    #[test]
+    fn cascade_slices_project_inside_clip_space() {
+        let (view, projection) = test_view();
+        let settings = test_settings();
+        let cascades = fit_cascades(
+            &view.invert().unwrap(),
+            &projection,
+            [-0.3, -1.0, -0.5],
+            None,
+            &settings,
+        );
+        assert_eq!(cascades.len(), settings.cascade_count);
+
+        let mut slice_near = 0.1;
+        for cascade in &cascades {
+            assert!(slice_near < cascade.far_distance);
+            for corner in slice_corners(&view, &projection, slice_near, cascade.far_distance) {
+                assert_in_clip_space(clip_position(cascade, corner));
+            }
+            slice_near = cascade.far_distance;
+        }
+        assert!((slice_near - settings.distance).abs() < EPSILON);
+    }
+
+    #[test]
+    fn cascades_grow_with_distance() {
+        let (view, projection) = test_view();
+        let settings = test_settings();
+        let cascades = fit_cascades(
+            &view.invert().unwrap(),
+            &projection,
+            [0.0, -1.0, 0.0],
+            None,
+            &settings,
+        );
+        // Width of the light view is the shadow map resolution in texels
+        let widths: Vec<f32> = cascades
+            .iter()
+            .zip(settings.resolutions)
+            .map(|(cascade, resolution)| cascade.texel_size * resolution as f32)
+            .collect();
+        assert!(widths.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn scene_bounds_stay_within_depth_range() {
+        let (view, projection) = test_view();
+        let scene_bounds = Aabb {
+            min: Point3::new(-20.0, -1.0, -20.0),
+            max: Point3::new(20.0, 15.0, 20.0),
+        };
+        let cascades = fit_cascades(
+            &view.invert().unwrap(),
+            &projection,
+            [-0.3, -1.0, -0.5],
+            Some(scene_bounds),
+            &test_settings(),
+        );
+        let first = &cascades[0];
+        for x in [scene_bounds.min.x, scene_bounds.max.x] {
+            for y in [scene_bounds.min.y, scene_bounds.max.y] {
+                for z in [scene_bounds.min.z, scene_bounds.max.z] {
+                    let depth = clip_position(first, Point3::new(x, y, z)).z;
+                    assert!(
+                        (-1.0 - EPSILON..=1.0 + EPSILON).contains(&depth),
+                        "scene corner at depth {depth} is clipped"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn no_cascades_for_orthographic_projection_or_missing_direction() {
+        let (view, projection) = test_view();
+        let orthographic = cgmath::ortho(-1.0, 1.0, -1.0, 1.0, 0.1, 100.0);
+        let inverse_view = view.invert().unwrap();
+        let settings = test_settings();
+        assert!(fit_cascades(
+            &inverse_view,
+            &orthographic,
+            [0.0, -1.0, 0.0],
+            None,
+            &settings
+        )
+        .is_empty());
+        assert!(fit_cascades(&inverse_view, &projection, [0.0; 3], None, &settings).is_empty());
+    }
+}
@@ -2,7 +2,9 @@ use std::sync::Arc;
 
 use cfg_if::cfg_if;
 use glow::{Buffer, HasContext, Texture, VertexArray};
-use image::{DynamicImage, EncodableLayout};
+use image::DynamicImage;
+
+use crate::{gl_resources, gl_state, texture::TextureImage, RendererError};
 
 /// Skybox containing cube-mapped texture and vertex positions for skybox
 /// cube.
@@ -21,21 +23,76 @@ pub struct Skybox {
     pub vertex_array: VertexArray,
     vertex_buffer: Buffer,
     index_buffer: Buffer,
+    gpu_memory_size: usize,
+}
+
+impl Skybox {
+    /// Black cube-map standing in for a skybox that failed to load.
+    pub fn placeholder(gl: Arc<glow::Context>) -> Result<Skybox, RendererError> {
+        let images = CubemapFace::ALL.map(|_| TextureImage::Decoded(DynamicImage::new_rgb8(1, 1)));
+        create_skybox(gl, &images)
+    }
+
+    /// Estimated size of cube-map texture and cube vertices in GPU memory in bytes.
+    pub fn gpu_memory_size(&self) -> usize {
+        self.gpu_memory_size
+    }
 }
 
 impl Drop for Skybox {
     fn drop(&mut self) {
         unsafe {
-            self.gl.delete_buffer(self.index_buffer);
-            self.gl.delete_buffer(self.vertex_buffer);
-            self.gl.delete_vertex_array(self.vertex_array);
-            self.gl.delete_texture(self.texture);
+            gl_resources::delete_buffer(&self.gl, self.index_buffer);
+            gl_resources::delete_buffer(&self.gl, self.vertex_buffer);
+            gl_resources::delete_vertex_array(&self.gl, self.vertex_array);
+            gl_resources::delete_texture(&self.gl, self.texture);
         }
     }
 }
 
+/// Faces of a cube-map in the order of OpenGL cube-map texture targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubemapFace {
+    Right,
+    Left,
+    Top,
+    Bottom,
+    Front,
+    Back,
+}
+
+impl CubemapFace {
+    pub const ALL: [CubemapFace; 6] = [
+        CubemapFace::Right,
+        CubemapFace::Left,
+        CubemapFace::Top,
+        CubemapFace::Bottom,
+        CubemapFace::Front,
+        CubemapFace::Back,
+    ];
+
+    /// Texture target of face, e.g. `TEXTURE_CUBE_MAP_POSITIVE_X` for right face.
+    fn texture_target(self) -> u32 {
+        glow::TEXTURE_CUBE_MAP_POSITIVE_X + self as u32
+    }
+}
+
+impl std::fmt::Display for CubemapFace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            CubemapFace::Right => "right (+X)",
+            CubemapFace::Left => "left (-X)",
+            CubemapFace::Top => "top (+Y)",
+            CubemapFace::Bottom => "bottom (-Y)",
+            CubemapFace::Front => "front (+Z)",
+            CubemapFace::Back => "back (-Z)",
+        };
+        f.write_str(name)
+    }
+}
+
 cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
-    #[derive(Default)]
+    #[derive(Default, Clone)]
     pub struct SkyboxFileBuilder {
         right_face_path: String,
         left_face_path: String,
@@ -80,38 +137,38 @@ cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
             self
         }
 
-        pub fn build(self, gl: Arc<glow::Context>) -> Result<Skybox, String> {
-            unsafe {
-                let texture = gl.create_texture().unwrap();
-                gl.bind_texture(glow::TEXTURE_CUBE_MAP, Some(texture));
-                self.read_images_from_files(&gl).map_err(|e| {
-                    format!("unable to create skybox texture: {:?}", e)
-                })?;
-                Ok(setup_shader_plumbing(gl, texture))
-            }
-        }
-
-        fn read_images_from_files(&self, gl: &glow::Context) -> Result<(), String> {
-            let texture_face_paths: [&str; 6] = [
+        /// File paths of faces in the order of `CubemapFace::ALL`.
+        pub fn face_paths(&self) -> [&str; 6] {
+            [
                 &self.right_face_path,
                 &self.left_face_path,
                 &self.top_face_path,
                 &self.bottom_face_path,
                 &self.front_face_path,
                 &self.back_face_path,
-            ];
-            for (i, face_path) in texture_face_paths.iter().enumerate() {
-                let img = image::open(face_path).map_err(|e| {
-                    format!("unable to load skybox texture from {face_path}: {:?}", e)
-                })?;
-                create_texture(&gl, i, &img);
-            }
-
-            Ok(())
+            ]
+        }
+
+        pub fn build(self, gl: Arc<glow::Context>) -> Result<Skybox, RendererError> {
+            let images = self.read_images_from_files()?;
+            create_skybox(gl, &images)
+        }
+
+        /// Image format of each face is selected by file extension, see
+        /// `texture::load_texture_image()`.
+        fn read_images_from_files(&self) -> Result<Vec<TextureImage>, RendererError> {
+            CubemapFace::ALL
+                .iter()
+                .zip(self.face_paths())
+                .map(|(&face, face_path)| {
+                    crate::texture::load_texture_image(face_path)
+                        .map_err(|reason| RendererError::CubemapFaceLoad { face, reason })
+                })
+                .collect()
         }
     }
 } else {
-    #[derive(Default)]
+    #[derive(Default, Clone)]
     pub struct SkyboxBufferBuilder {
         right_face_data: &'static [u8],
         left_face_data: &'static [u8],
@@ -156,62 +213,130 @@ cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
             self
         }
 
-        pub fn build(self, gl: Arc<glow::Context>) -> Result<Skybox, String> {
-            unsafe {
-                let texture = gl.create_texture().unwrap();
-                gl.bind_texture(glow::TEXTURE_CUBE_MAP, Some(texture));
-                self.read_images_from_buffers(&gl).map_err(|e| {
-                    format!("unable to create skybox texture: {:?}", e)
-                })?;
-                Ok(setup_shader_plumbing(gl, texture))
-            }
+        /// Encoded image data of faces in the order of `CubemapFace::ALL`.
+        pub fn face_data(&self) -> [&'static [u8]; 6] {
+            [
+                self.right_face_data,
+                self.left_face_data,
+                self.top_face_data,
+                self.bottom_face_data,
+                self.front_face_data,
+                self.back_face_data,
+            ]
+        }
+
+        pub fn build(self, gl: Arc<glow::Context>) -> Result<Skybox, RendererError> {
+            let images = self.read_images_from_buffers()?;
+            create_skybox(gl, &images)
         }
 
-        fn read_images_from_buffers(&self, gl: &glow::Context) -> Result<(), String> {
-            let texture_face_paths: [&'static [u8]; 6] = [
-                &self.right_face_data,
-                &self.left_face_data,
-                &self.top_face_data,
-                &self.bottom_face_data,
-                &self.front_face_data,
-                &self.back_face_data,
-            ];
-            for (i, face_path) in texture_face_paths.iter().enumerate() {
-                use image::ImageReader;
-                let img = ImageReader::new(std::io::Cursor::new(face_path))
-                    .with_guessed_format()
-                    .map_err(|e| format!("failed to guess format for face {}: {:?}", i, e))?
-                    .decode()
-                    .map_err(|e| format!("failed to convert image for face {}: {:?}", i, e))?;
-                create_texture(&gl, i, &img);
-            }
-
-            Ok(())
+        fn read_images_from_buffers(&self) -> Result<Vec<TextureImage>, RendererError> {
+            use image::ImageReader;
+
+            CubemapFace::ALL
+                .iter()
+                .zip(self.face_data())
+                .map(|(&face, face_data)| {
+                    ImageReader::new(std::io::Cursor::new(face_data))
+                        .with_guessed_format()
+                        .map_err(|e| RendererError::CubemapFaceLoad {
+                            face,
+                            reason: format!("failed to guess format: {e}"),
+                        })?
+                        .decode()
+                        .map_err(|e| RendererError::CubemapFaceLoad {
+                            face,
+                            reason: format!("failed to decode image: {e}"),
+                        })
+                        .map(TextureImage::Decoded)
+                })
+                .collect()
         }
     }
 }}
 
-fn create_texture(gl: &glow::Context, i: usize, img: &DynamicImage) {
+/// Upload validated face images into a new cube-map texture. Images are in the order of
+/// `CubemapFace::ALL`.
+fn create_skybox(gl: Arc<glow::Context>, images: &[TextureImage]) -> Result<Skybox, RendererError> {
+    validate_faces(images)?;
     unsafe {
-        gl.tex_image_2d(
-            glow::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32,
-            0,
-            glow::RGB as i32,
-            img.width() as i32,
-            img.height() as i32,
-            0,
-            glow::RGB,
-            glow::UNSIGNED_BYTE,
-            Some(img.to_rgb8().as_bytes()),
-        );
+        let texture = gl_resources::create_texture(&gl).map_err(RendererError::ResourceCreation)?;
+        gl_state::bind_texture(&gl, glow::TEXTURE_CUBE_MAP, Some(texture));
+        for (&face, img) in CubemapFace::ALL.iter().zip(images) {
+            img.upload(&gl, face.texture_target());
+        }
+        // Cube-map is incomplete if any face misses a sampled mip level
+        let mip_level_count = images
+            .iter()
+            .map(TextureImage::mip_level_count)
+            .min()
+            .unwrap_or(1);
+        let texture_size = images.iter().map(TextureImage::gpu_memory_size).sum();
+        gl_resources::set_texture_size(texture, texture_size);
+        Ok(setup_shader_plumbing(
+            gl,
+            texture,
+            mip_level_count,
+            texture_size,
+        ))
+    }
+}
+
+/// Mismatching faces would produce a corrupted cube-map, because OpenGL requires the faces to be
+/// square and the same size. Same format is required to avoid faces with visibly different
+/// precision or color channels.
+fn validate_faces(images: &[TextureImage]) -> Result<(), RendererError> {
+    let Some(first) = images.first() else {
+        return Ok(());
+    };
+    for (&face, img) in CubemapFace::ALL.iter().zip(images) {
+        if img.width() != img.height() {
+            return Err(RendererError::CubemapFaceNotSquare {
+                face,
+                width: img.width(),
+                height: img.height(),
+            });
+        }
+        if img.width() != first.width() {
+            return Err(RendererError::CubemapFaceSizeMismatch {
+                face,
+                size: img.width(),
+                expected_size: first.width(),
+            });
+        }
+        if img.format_name() != first.format_name() {
+            return Err(RendererError::CubemapFaceFormatMismatch {
+                face,
+                format: img.format_name(),
+                expected_format: first.format_name(),
+            });
+        }
     }
+
+    Ok(())
 }
 
-unsafe fn setup_shader_plumbing(gl: Arc<glow::Context>, texture: Texture) -> Skybox {
+unsafe fn setup_shader_plumbing(
+    gl: Arc<glow::Context>,
+    texture: Texture,
+    mip_level_count: u32,
+    texture_size: usize,
+) -> Skybox {
+    // Pre-generated mip levels of DDS files are used for minification
+    let min_filter = if 1 < mip_level_count {
+        glow::LINEAR_MIPMAP_LINEAR
+    } else {
+        glow::LINEAR
+    };
     gl.tex_parameter_i32(
         glow::TEXTURE_CUBE_MAP,
         glow::TEXTURE_MIN_FILTER,
-        glow::LINEAR as i32,
+        min_filter as i32,
+    );
+    gl.tex_parameter_i32(
+        glow::TEXTURE_CUBE_MAP,
+        glow::TEXTURE_MAX_LEVEL,
+        mip_level_count as i32 - 1,
     );
     gl.tex_parameter_i32(
         glow::TEXTURE_CUBE_MAP,
@@ -271,20 +396,32 @@ unsafe fn setup_shader_plumbing(gl: Arc<glow::Context>, texture: Texture) -> Sky
     ];
 
     // Create vertex array
-    let vertex_array = gl.create_vertex_array().unwrap();
-    gl.bind_vertex_array(Some(vertex_array));
+    let vertex_array = gl_resources::create_vertex_array(&gl).unwrap();
+    gl_state::bind_vertex_array(&gl, Some(vertex_array));
 
     // Create vertex buffer
-    let vertex_buffer = gl.create_buffer().unwrap();
+    let vertex_buffer = gl_resources::create_buffer(&gl).unwrap();
     gl.bind_buffer(glow::ARRAY_BUFFER, Some(vertex_buffer));
     let (_, vertices_bytes, _) = skybox_vertices.align_to::<u8>();
-    gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, vertices_bytes, glow::STATIC_DRAW);
+    gl_resources::buffer_data_u8_slice(
+        &gl,
+        vertex_buffer,
+        glow::ARRAY_BUFFER,
+        vertices_bytes,
+        glow::STATIC_DRAW,
+    );
 
     // Create index buffer
-    let index_buffer = gl.create_buffer().unwrap();
+    let index_buffer = gl_resources::create_buffer(&gl).unwrap();
     gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(index_buffer));
     let (_, indices_bytes, _) = skybox_indices.align_to::<u8>();
-    gl.buffer_data_u8_slice(glow::ELEMENT_ARRAY_BUFFER, indices_bytes, glow::STATIC_DRAW);
+    gl_resources::buffer_data_u8_slice(
+        &gl,
+        index_buffer,
+        glow::ELEMENT_ARRAY_BUFFER,
+        indices_bytes,
+        glow::STATIC_DRAW,
+    );
 
     // Setup vertex array layout (just vertex positions)
     let position_vertex_attribute = 0;
@@ -298,5 +435,8 @@ unsafe fn setup_shader_plumbing(gl: Arc<glow::Context>, texture: Texture) -> Sky
         vertex_array,
         vertex_buffer,
         index_buffer,
+        gpu_memory_size: texture_size
+            + size_of_val(&skybox_vertices)
+            + size_of_val(&skybox_indices),
     }
 }
@@ -0,0 +1,239 @@
+use std::{
+    io::Read,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread,
+};
+
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::remote::RemoteCommand;
+
+/// Largest accepted request body in bytes. Command lists of scene requests are far smaller.
+const MAX_BODY_SIZE: usize = 64 * 1024;
+/// Threads receiving requests and reading their bodies, so this many slow clients are served at
+/// the same time before others have to wait.
+const REQUEST_THREAD_COUNT: usize = 4;
+
+/// HTTP request waiting for the next rendered frame, after the commands it posted are carried out.
+pub(crate) struct SnapshotRequest {
+    request: Request,
+    pub commands: Vec<RemoteCommand>,
+}
+
+/// HTTP server answering requests with PNG images of rendered frames, e.g. for thumbnails or
+/// previews of web services.
+///
+/// Endpoints:
+/// - `GET /screenshot.png`: next frame of the current scene
+/// - `POST /scene`: next frame after carrying out the JSON array of remote commands in the body,
+///   which stay in effect for later requests
+///
+/// Scene requests have to be sent as `application/json`, so browsers ask for permission of the
+/// server first, which is never given, before web pages visited meanwhile can send them. Commands
+/// accessing files are rejected, bodies larger than 64 KiB too.
+///
+/// Requests are received and their bodies are read on background threads, and frames are sent
+/// from threads of their own, so slow clients don't hold up rendering. Commands are carried out on
+/// the thread of the frame loop, and frames are taken once drawn, before the GUI is painted on top.
+pub struct SnapshotServer {
+    server: Arc<Server>,
+    stopped: Arc<AtomicBool>,
+    // Requests with their bodies read, sent by request threads
+    received: Receiver<SnapshotRequest>,
+    // Answered after the next frame is drawn
+    waiting: Vec<Request>,
+}
+
+impl SnapshotServer {
+    /// Listen on the TCP address, e.g. on a port of the loopback interface for clients of the same
+    /// machine.
+    pub fn new(address: SocketAddr) -> Result<Self, String> {
+        let server = Arc::new(
+            Server::http(address)
+                .map_err(|e| format!("cannot start HTTP server on {address}: {e}"))?,
+        );
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (sender, received) = mpsc::channel();
+        for _ in 0..REQUEST_THREAD_COUNT {
+            let (server, stopped, sender) = (server.clone(), stopped.clone(), sender.clone());
+            thread::Builder::new()
+                .name("snapshot-server".to_owned())
+                .spawn(move || receive_requests(&server, &stopped, &sender))
+                .map_err(|e| format!("cannot start HTTP server thread: {e}"))?;
+        }
+        Ok(Self {
+            server,
+            stopped,
+            received,
+            waiting: Vec::new(),
+        })
+    }
+
+    /// Requests received since the last poll with their bodies read. Requests of unknown
+    /// endpoints or with invalid bodies are answered right away by request threads.
+    pub fn poll(&mut self) -> Vec<SnapshotRequest> {
+        self.received.try_iter().collect()
+    }
+
+    /// Answer the request with the next rendered frame, or with the error of carrying out its
+    /// commands.
+    pub fn wait_for_frame(&mut self, request: SnapshotRequest, result: Result<(), String>) {
+        match result {
+            Ok(()) => self.waiting.push(request.request),
+            Err(e) => respond_in_background(
+                request.request,
+                Response::from_string(e).with_status_code(400),
+            ),
+        }
+    }
+
+    /// Whether requests wait for a frame, to skip reading back frames nobody asked for.
+    pub fn is_waiting(&self) -> bool {
+        !self.waiting.is_empty()
+    }
+
+    /// Answer waiting requests with the PNG image of the drawn frame, or with the error of reading
+    /// it back.
+    pub fn send_frame(&mut self, png: Result<Vec<u8>, String>) {
+        for request in self.waiting.drain(..) {
+            match &png {
+                Ok(png) => {
+                    let content_type = Header::from_bytes("Content-Type", "image/png").unwrap();
+                    respond_in_background(
+                        request,
+                        Response::from_data(png.clone()).with_header(content_type),
+                    );
+                }
+                Err(e) => respond_in_background(
+                    request,
+                    Response::from_string(e.clone()).with_status_code(500),
+                ),
+            }
+        }
+    }
+}
+
+impl Drop for SnapshotServer {
+    fn drop(&mut self) {
+        // Ends request threads, one for each unblock
+        self.stopped.store(true, Ordering::Release);
+        for _ in 0..REQUEST_THREAD_COUNT {
+            self.server.unblock();
+        }
+    }
+}
+
+/// Receive requests until the server is stopped, passing the ones waiting for a frame to the
+/// frame loop and answering the others.
+///
+/// Bodies are read here in full, because requests with unread bodies keep reading them when
+/// dropped, which would block the frame loop on clients sending slowly.
+fn receive_requests(server: &Server, stopped: &AtomicBool, sender: &Sender<SnapshotRequest>) {
+    loop {
+        let mut request = match server.recv() {
+            Ok(request) => request,
+            Err(_) if stopped.load(Ordering::Acquire) => break,
+            Err(e) => {
+                eprintln!("cannot receive HTTP request: {e}");
+                continue;
+            }
+        };
+        // Query is ignored, e.g. parameters against caching
+        let path = request.url().split('?').next().unwrap().to_string();
+        let commands = match (request.method(), path.as_str()) {
+            (Method::Get, "/screenshot.png") => read_body(&mut request).map(|_| Vec::new()),
+            (Method::Post, "/scene") if !is_json(&request) => Err((415, String::new())),
+            (Method::Post, "/scene") => {
+                read_body(&mut request).and_then(|body| parse_commands(&body).map_err(|e| (400, e)))
+            }
+            (_, "/screenshot.png" | "/scene") => Err((405, String::new())),
+            _ => Err((404, String::new())),
+        };
+        match commands {
+            Ok(commands) => {
+                let _ = sender.send(SnapshotRequest { request, commands });
+            }
+            Err((status_code, e)) => respond(
+                request,
+                Response::from_string(e).with_status_code(status_code),
+            ),
+        }
+    }
+}
+
+/// Whether the body of the request is declared as JSON. Browsers only send other types without
+/// asking the server first.
+fn is_json(request: &Request) -> bool {
+    request.headers().iter().any(|header| {
+        header.field.equiv("Content-Type")
+            && header
+                .value
+                .as_str()
+                .split(';')
+                .next()
+                .unwrap()
+                .trim()
+                .eq_ignore_ascii_case("application/json")
+    })
+}
+
+/// Read the body of the request up to `MAX_BODY_SIZE` bytes, or the status code and message of
+/// the error. Declared lengths are checked before reading, bodies without one, e.g. chunked ones,
+/// while reading.
+fn read_body(request: &mut Request) -> Result<String, (u16, String)> {
+    let too_large = || (413, format!("request body exceeds {MAX_BODY_SIZE} bytes"));
+    if request
+        .body_length()
+        .is_some_and(|length| MAX_BODY_SIZE < length)
+    {
+        return Err(too_large());
+    }
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_SIZE as u64 + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| (400, format!("cannot read request body: {e}")))?;
+    if MAX_BODY_SIZE < body.len() {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+/// Parse the body as a JSON array of remote commands, rejecting commands accessing files.
+fn parse_commands(body: &str) -> Result<Vec<RemoteCommand>, String> {
+    let commands: Vec<RemoteCommand> =
+        serde_json::from_str(body).map_err(|e| format!("invalid scene commands: {e}"))?;
+    if commands.iter().any(|command| {
+        matches!(
+            command,
+            RemoteCommand::LoadModel { .. } | RemoteCommand::Screenshot { .. }
+        )
+    }) {
+        return Err("loading models and saving screenshots are not accepted".to_owned());
+    }
+    Ok(commands)
+}
+
+/// Send the response, reporting clients that went away meanwhile.
+fn respond<R: Read>(request: Request, response: Response<R>) {
+    if let Err(e) = request.respond(response) {
+        eprintln!("cannot answer HTTP request: {e}");
+    }
+}
+
+/// Send the response from a thread of its own, so clients reading slowly don't hold up the frame
+/// loop.
+fn respond_in_background<R: Read + Send + 'static>(request: Request, response: Response<R>) {
+    let sender = thread::Builder::new()
+        .name("snapshot-response".to_owned())
+        .spawn(move || respond(request, response));
+    if let Err(e) = sender {
+        eprintln!("cannot answer HTTP request: {e}");
+    }
+}
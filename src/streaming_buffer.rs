@@ -0,0 +1,228 @@
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{collections::VecDeque, ops::Range};
+
+use cfg_if::cfg_if;
+use glow::{Buffer, HasContext};
+
+use crate::gl_resources;
+
+/// Capacity of a new buffer in bytes, grown on demand.
+const INITIAL_CAPACITY: usize = 64 * 1024;
+/// Maximum time in nanoseconds to wait for the GPU in one call before checking again.
+#[cfg(not(target_arch = "wasm32"))]
+const FENCE_WAIT_TIMEOUT: i32 = 1_000_000;
+
+/// Buffer for data rewritten on every frame, like vertices of lines and labels.
+///
+/// Writes are appended after the previous ones in a ring instead of reallocating the buffer on
+/// every upload, which stalls until the GPU finishes drawing from the old contents. With OpenGL
+/// 4.4 the buffer is mapped persistently and written directly, fences guard ranges still read by
+/// the GPU. Otherwise (OpenGL ES, WebGL) the storage is orphaned whenever the ring wraps around.
+///
+/// The buffer object is replaced when it grows, so vertex attributes should be pointed at the
+/// buffer bound by each write.
+pub struct StreamingBuffer {
+    gl: Arc<glow::Context>,
+    target: u32,
+    // Writes start at multiples of the element size, so they can be addressed by element index
+    element_size: usize,
+    buffer: Buffer,
+    capacity: usize,
+    // Start of the next write in bytes
+    head: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    mapping: Option<PersistentMapping>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct PersistentMapping {
+    pointer: *mut u8,
+    // Range of the last write, fenced when the next write starts after its draw calls are issued
+    last_write: Option<Range<usize>>,
+    // Written ranges possibly still read by the GPU, from the oldest to the newest
+    fences: VecDeque<(Range<usize>, glow::Fence)>,
+}
+
+impl StreamingBuffer {
+    /// Buffer bound to `target` (e.g. `GL_ARRAY_BUFFER`) holding elements of `element_size`
+    /// bytes.
+    pub fn new(gl: Arc<glow::Context>, target: u32, element_size: usize) -> Result<Self, String> {
+        let buffer = unsafe { gl_resources::create_buffer(&gl)? };
+        let mut streaming_buffer = Self {
+            gl,
+            target,
+            element_size,
+            buffer,
+            capacity: 0,
+            head: 0,
+            #[cfg(not(target_arch = "wasm32"))]
+            mapping: None,
+        };
+        unsafe {
+            streaming_buffer.gl.bind_buffer(target, Some(buffer));
+            let result = streaming_buffer.allocate(INITIAL_CAPACITY);
+            streaming_buffer.gl.bind_buffer(target, None);
+            result?;
+        }
+        Ok(streaming_buffer)
+    }
+
+    /// Copy elements into the buffer and return the index of the first one, e.g. to pass as
+    /// first vertex of `glDrawArrays()`. Buffer is left bound to its target.
+    pub fn write<T: Copy>(&mut self, elements: &[T]) -> Result<usize, String> {
+        debug_assert_eq!(size_of::<T>(), self.element_size);
+        let (_, bytes, _) = unsafe { elements.align_to::<u8>() };
+        unsafe {
+            self.gl.bind_buffer(self.target, Some(self.buffer));
+            if bytes.len() > self.capacity {
+                self.allocate(bytes.len().next_power_of_two())?;
+            }
+
+            let mut start = self.head.next_multiple_of(self.element_size);
+            let wraps = start + bytes.len() > self.capacity;
+            if wraps {
+                start = 0;
+            }
+            let range = start..start + bytes.len();
+
+            cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
+                if let Some(mapping) = &mut self.mapping {
+                    mapping.wait_for_range(&self.gl, &range);
+                    std::ptr::copy_nonoverlapping(
+                        bytes.as_ptr(),
+                        mapping.pointer.add(start),
+                        bytes.len(),
+                    );
+                    mapping.last_write = Some(range.clone());
+                } else {
+                    self.write_orphaning(wraps, start, bytes);
+                }
+            } else {
+                self.write_orphaning(wraps, start, bytes);
+            }}
+
+            self.head = range.end;
+            Ok(start / self.element_size)
+        }
+    }
+
+    /// Data store is replaced when the ring wraps, draw calls issued before keep reading the old
+    /// one. Appended ranges are not used by the GPU yet, updating them doesn't wait.
+    unsafe fn write_orphaning(&self, wraps: bool, start: usize, bytes: &[u8]) {
+        if wraps {
+            self.gl
+                .buffer_data_size(self.target, self.capacity as i32, glow::STREAM_DRAW);
+        }
+        self.gl
+            .buffer_sub_data_u8_slice(self.target, start as i32, bytes);
+    }
+
+    /// Replace storage with an empty one of at least `capacity` bytes. Buffer must be bound.
+    unsafe fn allocate(&mut self, capacity: usize) -> Result<(), String> {
+        self.head = 0;
+        self.capacity = capacity;
+
+        cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
+            let version = self.gl.version();
+            let supports_buffer_storage = (!version.is_embedded
+                && (version.major, version.minor) >= (4, 4))
+                || self
+                    .gl
+                    .supported_extensions()
+                    .contains("GL_ARB_buffer_storage");
+            if supports_buffer_storage {
+                // Immutable storage can't be resized, it's replaced with a new buffer object.
+                // Pending draw calls keep the old storage alive until they finish.
+                if let Some(mapping) = self.mapping.take() {
+                    mapping.delete_fences(&self.gl);
+                    gl_resources::delete_buffer(&self.gl, self.buffer);
+                    self.buffer = gl_resources::create_buffer(&self.gl)?;
+                    self.gl.bind_buffer(self.target, Some(self.buffer));
+                }
+                let flags = glow::MAP_WRITE_BIT | glow::MAP_PERSISTENT_BIT | glow::MAP_COHERENT_BIT;
+                gl_resources::buffer_storage(
+                    &self.gl,
+                    self.buffer,
+                    self.target,
+                    capacity as i32,
+                    flags,
+                );
+                let pointer = self
+                    .gl
+                    .map_buffer_range(self.target, 0, capacity as i32, flags);
+                if pointer.is_null() {
+                    return Err(format!(
+                        "cannot map streaming buffer persistently: error {}",
+                        self.gl.get_error()
+                    ));
+                }
+                self.mapping = Some(PersistentMapping {
+                    pointer,
+                    last_write: None,
+                    fences: VecDeque::new(),
+                });
+                return Ok(());
+            }
+        }}
+
+        gl_resources::buffer_data_size(
+            &self.gl,
+            self.buffer,
+            self.target,
+            capacity as i32,
+            glow::STREAM_DRAW,
+        );
+        Ok(())
+    }
+}
+
+impl Drop for StreamingBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(mapping) = self.mapping.take() {
+                mapping.delete_fences(&self.gl);
+            }
+            // Deleting the buffer unmaps it
+            gl_resources::delete_buffer(&self.gl, self.buffer);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PersistentMapping {
+    /// Block until the GPU finished reading every earlier write overlapping the range.
+    unsafe fn wait_for_range(&mut self, gl: &glow::Context, range: &Range<usize>) {
+        // Draw calls reading the last write were issued since, fence them
+        if let Some(last_write) = self.last_write.take() {
+            if let Ok(fence) = gl.fence_sync(glow::SYNC_GPU_COMMANDS_COMPLETE, 0) {
+                self.fences.push_back((last_write, fence));
+            }
+        }
+
+        // Fences signal in order, waiting for the newest overlapping one covers the older ones
+        let overlaps = |fenced: &Range<usize>| fenced.start < range.end && range.start < fenced.end;
+        let Some(newest) = self.fences.iter().rposition(|(fenced, _)| overlaps(fenced)) else {
+            return;
+        };
+        let (_, fence) = self.fences[newest];
+        loop {
+            let status =
+                gl.client_wait_sync(fence, glow::SYNC_FLUSH_COMMANDS_BIT, FENCE_WAIT_TIMEOUT);
+            if status != glow::TIMEOUT_EXPIRED {
+                // Signaled, or failed with context loss where waiting longer wouldn't help
+                break;
+            }
+        }
+        for (_, fence) in self.fences.drain(..=newest) {
+            gl.delete_sync(fence);
+        }
+    }
+
+    unsafe fn delete_fences(self, gl: &glow::Context) {
+        for (_, fence) in self.fences {
+            gl.delete_sync(fence);
+        }
+    }
+}
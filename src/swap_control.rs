@@ -0,0 +1,130 @@
+#[cfg(any(
+    windows,
+    all(unix, not(target_os = "macos"), not(target_os = "android"))
+))]
+use std::ffi::{c_char, c_void, CStr};
+
+use cfg_if::cfg_if;
+use glutin::{
+    display::Display,
+    surface::{Surface, WindowSurface},
+};
+
+/// Negative swap interval of `EXT_swap_control_tear`, swapping late frames immediately.
+#[cfg(any(
+    windows,
+    all(unix, not(target_os = "macos"), not(target_os = "android"))
+))]
+const ADAPTIVE_SWAP_INTERVAL: i32 = -1;
+
+/// Enable adaptive vertical sync: wait for vertical blank, but don't wait for the next one when a
+/// frame missed it. Late frames tear instead of stuttering.
+///
+/// glutin only accepts positive swap intervals, so the negative interval of
+/// `GLX_EXT_swap_control_tear` and `WGL_EXT_swap_control_tear` is set through entry points looked
+/// up with the loader function of the display. EGL has no equivalent.
+pub fn set_adaptive_vsync(
+    display: &Display,
+    surface: &Surface<WindowSurface>,
+) -> Result<(), String> {
+    cfg_if! {
+        if #[cfg(windows)] {
+            let _ = surface;
+            unsafe { set_adaptive_vsync_wgl(display) }
+        } else if #[cfg(all(unix, not(target_os = "macos"), not(target_os = "android")))] {
+            unsafe { set_adaptive_vsync_glx(display, surface) }
+        } else {
+            let _ = (display, surface);
+            Err("adaptive vertical sync is not supported on this platform".to_string())
+        }
+    }
+}
+
+#[cfg(windows)]
+unsafe fn set_adaptive_vsync_wgl(display: &Display) -> Result<(), String> {
+    use glutin::display::{AsRawDisplay, RawDisplay};
+
+    type GetExtensionsStringFn = unsafe extern "system" fn() -> *const c_char;
+    // Applies to the window of the current context
+    type SwapIntervalFn = unsafe extern "system" fn(interval: i32) -> i32;
+
+    if !matches!(display.raw_display(), RawDisplay::Wgl) {
+        return Err("adaptive vertical sync requires WGL".to_string());
+    }
+    let get_extensions_string: GetExtensionsStringFn = load(display, c"wglGetExtensionsStringEXT")?;
+    if !has_extension(get_extensions_string(), "WGL_EXT_swap_control_tear") {
+        return Err("WGL_EXT_swap_control_tear is not supported".to_string());
+    }
+    let swap_interval: SwapIntervalFn = load(display, c"wglSwapIntervalEXT")?;
+    if swap_interval(ADAPTIVE_SWAP_INTERVAL) == 0 {
+        return Err("wglSwapIntervalEXT failed".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos"), not(target_os = "android")))]
+unsafe fn set_adaptive_vsync_glx(
+    display: &Display,
+    surface: &Surface<WindowSurface>,
+) -> Result<(), String> {
+    use std::ffi::c_ulong;
+
+    use glutin::{
+        display::{AsRawDisplay, RawDisplay},
+        surface::{AsRawSurface, RawSurface},
+    };
+
+    /// Name of the client extension string queried by `glXGetClientString()`.
+    const GLX_EXTENSIONS: i32 = 3;
+
+    type GetClientStringFn =
+        unsafe extern "C" fn(display: *const c_void, name: i32) -> *const c_char;
+    type SwapIntervalFn =
+        unsafe extern "C" fn(display: *const c_void, drawable: c_ulong, interval: i32);
+
+    // Wayland and X11 without GLX go through EGL
+    let (RawDisplay::Glx(raw_display), RawSurface::Glx(drawable)) =
+        (display.raw_display(), surface.raw_surface())
+    else {
+        return Err("adaptive vertical sync requires GLX".to_string());
+    };
+    let get_client_string: GetClientStringFn = load(display, c"glXGetClientString")?;
+    if !has_extension(
+        get_client_string(raw_display, GLX_EXTENSIONS),
+        "GLX_EXT_swap_control_tear",
+    ) {
+        return Err("GLX_EXT_swap_control_tear is not supported".to_string());
+    }
+    let swap_interval: SwapIntervalFn = load(display, c"glXSwapIntervalEXT")?;
+    swap_interval(raw_display, drawable as c_ulong, ADAPTIVE_SWAP_INTERVAL);
+    Ok(())
+}
+
+/// Look up an entry point of the platform API. `F` must be the function pointer type matching
+/// its signature.
+#[cfg(any(
+    windows,
+    all(unix, not(target_os = "macos"), not(target_os = "android"))
+))]
+unsafe fn load<F: Copy>(display: &Display, symbol: &CStr) -> Result<F, String> {
+    use glutin::display::GlDisplay;
+
+    let pointer = display.get_proc_address(symbol);
+    if pointer.is_null() {
+        return Err(format!("{} is not available", symbol.to_string_lossy()));
+    }
+    Ok(std::mem::transmute_copy::<*const c_void, F>(&pointer))
+}
+
+/// Space-separated extension list contains the extension.
+#[cfg(any(
+    windows,
+    all(unix, not(target_os = "macos"), not(target_os = "android"))
+))]
+unsafe fn has_extension(extensions: *const c_char, extension: &str) -> bool {
+    !extensions.is_null()
+        && CStr::from_ptr(extensions)
+            .to_string_lossy()
+            .split_whitespace()
+            .any(|supported| supported == extension)
+}
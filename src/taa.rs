@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use cgmath::{Matrix4, Vector3};
+
+use crate::framebuffer::{ColorFormat, Framebuffer, PingPongFramebuffers};
+
+/// Number of distinct subpixel offsets the projection cycles through.
+const JITTER_SEQUENCE_LENGTH: u32 = 8;
+
+/// Resolved output of previous frames for temporal anti-aliasing (TAA), accumulated from the
+/// scene rendered with a different subpixel offset every frame.
+///
+/// Each frame blends the current scene into the history of the previous frame, writing the result
+/// into the other framebuffer of the pair.
+pub(crate) struct TemporalHistory {
+    framebuffers: PingPongFramebuffers,
+    frame: usize,
+    /// Previous output holds a resolved frame, false after creation or resize.
+    valid: bool,
+}
+
+impl TemporalHistory {
+    pub fn new(gl: Arc<glow::Context>, width: u32, height: u32) -> Result<Self, String> {
+        let color_format = ColorFormat::scene(&gl);
+        Ok(Self {
+            framebuffers: PingPongFramebuffers::new(gl, width, height, color_format)?,
+            frame: 0,
+            valid: false,
+        })
+    }
+
+    /// Reallocate framebuffers if size differs, discarding the history.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        let current = self.current();
+        if current.width() != width.max(1) || current.height() != height.max(1) {
+            self.valid = false;
+        }
+        self.framebuffers.resize(width, height)
+    }
+
+    /// Framebuffer the resolve pass of this frame draws into.
+    pub fn current(&self) -> &Framebuffer {
+        self.framebuffers.target(self.frame)
+    }
+
+    /// Output of the previous frame, unless there is none yet.
+    pub fn previous(&self) -> Option<&Framebuffer> {
+        self.valid.then(|| self.framebuffers.target(self.frame + 1))
+    }
+
+    /// Keep the output of this frame as history of the next one.
+    pub fn advance(&mut self) {
+        self.frame = (self.frame + 1) % 2;
+        self.valid = true;
+    }
+}
+
+/// Projection moved by a subpixel offset that differs every frame, so accumulated frames sample
+/// each pixel at several positions. Offsets follow the Halton sequence in bases 2 and 3, covering
+/// the pixel evenly.
+pub(crate) fn jittered_projection(
+    projection: Matrix4<f32>,
+    frame: u32,
+    width: u32,
+    height: u32,
+) -> Matrix4<f32> {
+    // Sequence starts from 1, index 0 would give the same offset in both directions
+    let index = frame % JITTER_SEQUENCE_LENGTH + 1;
+    // Offset in pixels between -0.5 and 0.5 converted to normalized device coordinates
+    let x = (halton(index, 2) - 0.5) * 2.0 / width.max(1) as f32;
+    let y = (halton(index, 3) - 0.5) * 2.0 / height.max(1) as f32;
+    Matrix4::from_translation(Vector3::new(x, y, 0.0)) * projection
+}
+
+/// Element of a low-discrepancy sequence between 0 and 1, the digits of the index in the base
+/// mirrored around the decimal point.
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+    while 0 < index {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
@@ -0,0 +1,264 @@
+use std::sync::Arc;
+
+use cgmath::{vec3, InnerSpace, Point3};
+
+use crate::{frustum::Frustum, Aabb, Mesh, MeshData, Vertex};
+
+/// Number of grid cells along each side of a tile.
+const TILE_SIZE: usize = 64;
+
+/// Landscape generated from a heightmap, e.g. exported from GIS or terrain generator applications.
+///
+/// Grid of height samples is split into square tiles of separate meshes, tiles outside the view
+/// frustum are skipped when drawing. Terrain is centered horizontally at the world origin. Heights
+/// are also kept in CPU memory for sampling the ground height, e.g. by the walking camera.
+pub struct Terrain {
+    tiles: Vec<TerrainTile>,
+    // World heights of samples, row by row along the X axis
+    heights: Vec<f32>,
+    width: usize,
+    depth: usize,
+    spacing: f32,
+    // World position of the first sample
+    origin: Point3<f32>,
+    bounds: Aabb,
+}
+
+struct TerrainTile {
+    mesh: Mesh,
+    bounds: Aabb,
+}
+
+/// Dimensions of terrain generated from heightmap values.
+#[derive(Clone, Copy)]
+pub struct TerrainSettings {
+    /// Horizontal distance between neighbouring samples in world units.
+    pub spacing: f32,
+    /// World height of heightmap value 1. Pixels of PNG images are mapped to values from 0 to 1,
+    /// floating point EXR images keep their values.
+    pub height_scale: f32,
+    /// World height of heightmap value 0.
+    pub base_height: f32,
+}
+
+impl Default for TerrainSettings {
+    fn default() -> Self {
+        Self {
+            spacing: 0.1,
+            height_scale: 3.0,
+            base_height: -1.0,
+        }
+    }
+}
+
+/// Tiles drawn in the last frame drawn from the camera.
+#[derive(Clone, Copy, Default)]
+pub struct TerrainStats {
+    pub tile_count: usize,
+    /// Number of tiles inside the view frustum.
+    pub drawn_tile_count: usize,
+}
+
+impl Terrain {
+    /// Load grayscale heightmap from a PNG or EXR image. Color images are converted to luminance.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn create_from_file(
+        gl: Arc<glow::Context>,
+        path: &str,
+        settings: &TerrainSettings,
+    ) -> Result<Terrain, String> {
+        let image = image::open(path)
+            .map_err(|e| format!("failed to load heightmap from {path}: {e}"))?
+            .to_luma32f();
+        let (width, depth) = image.dimensions();
+        Self::from_heights(
+            gl,
+            width as usize,
+            depth as usize,
+            &image.into_raw(),
+            settings,
+        )
+    }
+
+    /// Generate terrain from a grid of `width` × `depth` heightmap values, row by row along the X
+    /// axis.
+    pub fn from_heights(
+        gl: Arc<glow::Context>,
+        width: usize,
+        depth: usize,
+        values: &[f32],
+        settings: &TerrainSettings,
+    ) -> Result<Terrain, String> {
+        if width < 2 || depth < 2 {
+            return Err(format!(
+                "heightmap of {width}x{depth} samples is too small, at least 2x2 is required"
+            ));
+        }
+        if values.len() != width * depth {
+            return Err(format!(
+                "heightmap has {} samples instead of {width}x{depth}",
+                values.len()
+            ));
+        }
+
+        let heights: Vec<f32> = values
+            .iter()
+            .map(|value| settings.base_height + value * settings.height_scale)
+            .collect();
+        let origin = Point3::new(
+            -0.5 * (width - 1) as f32 * settings.spacing,
+            0.0,
+            -0.5 * (depth - 1) as f32 * settings.spacing,
+        );
+        let mut terrain = Self {
+            tiles: Vec::new(),
+            heights,
+            width,
+            depth,
+            spacing: settings.spacing,
+            origin,
+            bounds: Aabb::from_points([]),
+        };
+        let vertices = terrain.generate_vertices();
+
+        for tile_z in (0..depth - 1).step_by(TILE_SIZE) {
+            for tile_x in (0..width - 1).step_by(TILE_SIZE) {
+                let x_range = tile_x..=(tile_x + TILE_SIZE).min(width - 1);
+                let z_range = tile_z..=(tile_z + TILE_SIZE).min(depth - 1);
+                terrain.tiles.push(TerrainTile::new(
+                    gl.clone(),
+                    &vertices,
+                    width,
+                    x_range,
+                    z_range,
+                ));
+            }
+        }
+        terrain.bounds = Aabb::from_points(
+            terrain
+                .tiles
+                .iter()
+                .flat_map(|tile| [tile.bounds.min, tile.bounds.max]),
+        );
+        Ok(terrain)
+    }
+
+    /// Bounding box in world space.
+    pub fn bounds(&self) -> Aabb {
+        self.bounds
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Height of the terrain surface at the horizontal world position, `None` outside of the
+    /// terrain. Interpolated on the same triangles as the ones drawn.
+    pub fn height_at(&self, x: f32, z: f32) -> Option<f32> {
+        let grid_x = (x - self.origin.x) / self.spacing;
+        let grid_z = (z - self.origin.z) / self.spacing;
+        let max_x = (self.width - 1) as f32;
+        let max_z = (self.depth - 1) as f32;
+        if !(0.0..=max_x).contains(&grid_x) || !(0.0..=max_z).contains(&grid_z) {
+            return None;
+        }
+
+        // Cell containing the position, last row and column belong to the cells before them
+        let cell_x = (grid_x.floor() as usize).min(self.width - 2);
+        let cell_z = (grid_z.floor() as usize).min(self.depth - 2);
+        let fx = grid_x - cell_x as f32;
+        let fz = grid_z - cell_z as f32;
+        let a = self.height(cell_x, cell_z);
+        let b = self.height(cell_x + 1, cell_z);
+        let c = self.height(cell_x, cell_z + 1);
+        let d = self.height(cell_x + 1, cell_z + 1);
+        // Cells are split into two triangles along the diagonal from B to C
+        Some(if fx + fz <= 1.0 {
+            a + (b - a) * fx + (c - a) * fz
+        } else {
+            d + (c - d) * (1.0 - fx) + (b - d) * (1.0 - fz)
+        })
+    }
+
+    /// Meshes of tiles intersecting the frustum.
+    pub(crate) fn visible_tiles<'a>(
+        &'a self,
+        frustum: &'a Frustum,
+    ) -> impl Iterator<Item = &'a Mesh> {
+        self.tiles
+            .iter()
+            .filter(|tile| frustum.intersects(&tile.bounds))
+            .map(|tile| &tile.mesh)
+    }
+
+    fn height(&self, x: usize, z: usize) -> f32 {
+        self.heights[z * self.width + x]
+    }
+
+    /// Vertex of every sample with normal from central differences of neighbouring heights.
+    fn generate_vertices(&self) -> Vec<Vertex> {
+        let mut vertices = Vec::with_capacity(self.width * self.depth);
+        for z in 0..self.depth {
+            for x in 0..self.width {
+                // One-sided differences on the edges
+                let (left, right) = (x.saturating_sub(1), (x + 1).min(self.width - 1));
+                let (back, front) = (z.saturating_sub(1), (z + 1).min(self.depth - 1));
+                let slope_x = (self.height(right, z) - self.height(left, z))
+                    / ((right - left) as f32 * self.spacing);
+                let slope_z = (self.height(x, front) - self.height(x, back))
+                    / ((front - back) as f32 * self.spacing);
+
+                vertices.push(Vertex {
+                    position: vec3(
+                        self.origin.x + x as f32 * self.spacing,
+                        self.height(x, z),
+                        self.origin.z + z as f32 * self.spacing,
+                    ),
+                    normal: vec3(-slope_x, 1.0, -slope_z).normalize(),
+                });
+            }
+        }
+        vertices
+    }
+}
+
+impl TerrainTile {
+    /// Upload samples of the inclusive ranges. Samples on the borders are duplicated in the
+    /// neighbouring tiles.
+    fn new(
+        gl: Arc<glow::Context>,
+        vertices: &[Vertex],
+        width: usize,
+        x_range: std::ops::RangeInclusive<usize>,
+        z_range: std::ops::RangeInclusive<usize>,
+    ) -> Self {
+        let tile_width = x_range.end() - x_range.start() + 1;
+        let tile_vertices: Vec<Vertex> = z_range
+            .clone()
+            .flat_map(|z| x_range.clone().map(move |x| vertices[z * width + x]))
+            .collect();
+
+        let mut indices = Vec::new();
+        for z in 0..z_range.end() - z_range.start() {
+            for x in 0..tile_width - 1 {
+                let a = (z * tile_width + x) as u32;
+                let b = a + 1;
+                let c = a + tile_width as u32;
+                let d = c + 1;
+                // Counter-clockwise seen from above
+                indices.extend_from_slice(&[a, c, b, b, c, d]);
+            }
+        }
+
+        let mesh = Mesh::from_mesh_data(
+            gl,
+            &MeshData {
+                vertices: tile_vertices,
+                indices,
+                ..Default::default()
+            },
+        );
+        let bounds = mesh.bounds();
+        Self { mesh, bounds }
+    }
+}
@@ -0,0 +1,256 @@
+use cfg_if::cfg_if;
+use glow::HasContext;
+use image::{ColorType, DynamicImage, EncodableLayout};
+
+cfg_if! { if #[cfg(not(target_arch = "wasm32"))] {
+    use std::path::Path;
+
+    use ddsfile::{D3DFormat, Dds, DxgiFormat};
+
+    // sRGB variants of S3TC formats from EXT_texture_sRGB. Not part of core OpenGL, but supported
+    // by every desktop driver implementing OpenGL 4.3.
+    const COMPRESSED_SRGB_S3TC_DXT1_EXT: u32 = 0x8C4C;
+    const COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT: u32 = 0x8C4E;
+    const COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT: u32 = 0x8C4F;
+}}
+
+/// Pixel data of a single texture image ready for upload to GPU memory.
+pub enum TextureImage {
+    /// Pixels decoded by `image` crate. 8-bit images are treated as sRGB color, floating point
+    /// images (e.g. EXR) as linear HDR color.
+    Decoded(DynamicImage),
+    /// Block-compressed pixels uploaded as-is, including pre-generated mip levels.
+    #[cfg(not(target_arch = "wasm32"))]
+    Compressed(CompressedImage),
+}
+
+/// Block-compressed (BCn) image loaded from DDS file.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct CompressedImage {
+    width: u32,
+    height: u32,
+    format: CompressedFormat,
+    /// Mip levels from largest to smallest.
+    levels: Vec<Vec<u8>>,
+}
+
+/// Supported block compression formats of DDS files.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompressedFormat {
+    Bc1,
+    Bc2,
+    Bc3,
+    Bc6hUnsigned,
+    Bc6hSigned,
+    Bc7,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CompressedFormat {
+    fn from_dds(dds: &Dds) -> Option<Self> {
+        // Color textures are authored in sRGB, so UNorm variants are treated the same as UNorm_sRGB
+        // like uncompressed 8-bit images
+        if let Some(format) = dds.get_dxgi_format() {
+            return match format {
+                DxgiFormat::BC1_UNorm | DxgiFormat::BC1_UNorm_sRGB => Some(Self::Bc1),
+                DxgiFormat::BC2_UNorm | DxgiFormat::BC2_UNorm_sRGB => Some(Self::Bc2),
+                DxgiFormat::BC3_UNorm | DxgiFormat::BC3_UNorm_sRGB => Some(Self::Bc3),
+                DxgiFormat::BC6H_UF16 => Some(Self::Bc6hUnsigned),
+                DxgiFormat::BC6H_SF16 => Some(Self::Bc6hSigned),
+                DxgiFormat::BC7_UNorm | DxgiFormat::BC7_UNorm_sRGB => Some(Self::Bc7),
+                _ => None,
+            };
+        }
+        match dds.get_d3d_format()? {
+            D3DFormat::DXT1 => Some(Self::Bc1),
+            D3DFormat::DXT3 => Some(Self::Bc2),
+            D3DFormat::DXT5 => Some(Self::Bc3),
+            _ => None,
+        }
+    }
+
+    fn internal_format(self) -> u32 {
+        match self {
+            Self::Bc1 => COMPRESSED_SRGB_S3TC_DXT1_EXT,
+            Self::Bc2 => COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT,
+            Self::Bc3 => COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT,
+            Self::Bc6hUnsigned => glow::COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT,
+            Self::Bc6hSigned => glow::COMPRESSED_RGB_BPTC_SIGNED_FLOAT,
+            Self::Bc7 => glow::COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+        }
+    }
+
+    /// Each 4x4 pixel block is compressed into this many bytes.
+    fn block_size(self) -> usize {
+        match self {
+            Self::Bc1 => 8,
+            _ => 16,
+        }
+    }
+}
+
+impl TextureImage {
+    pub fn width(&self) -> u32 {
+        match self {
+            TextureImage::Decoded(img) => img.width(),
+            #[cfg(not(target_arch = "wasm32"))]
+            TextureImage::Compressed(img) => img.width,
+        }
+    }
+
+    pub fn height(&self) -> u32 {
+        match self {
+            TextureImage::Decoded(img) => img.height(),
+            #[cfg(not(target_arch = "wasm32"))]
+            TextureImage::Compressed(img) => img.height,
+        }
+    }
+
+    /// Human-readable pixel format for error messages.
+    pub fn format_name(&self) -> String {
+        match self {
+            TextureImage::Decoded(img) => format!("{:?}", img.color()),
+            #[cfg(not(target_arch = "wasm32"))]
+            TextureImage::Compressed(img) => format!("{:?}", img.format),
+        }
+    }
+
+    /// Number of mip levels stored in the image, 1 when only the base image is present.
+    pub fn mip_level_count(&self) -> u32 {
+        match self {
+            TextureImage::Decoded(_) => 1,
+            #[cfg(not(target_arch = "wasm32"))]
+            TextureImage::Compressed(img) => img.levels.len() as u32,
+        }
+    }
+
+    /// Estimated size of uploaded texture in GPU memory in bytes, including mip levels.
+    pub fn gpu_memory_size(&self) -> usize {
+        match self {
+            TextureImage::Decoded(img) => {
+                let bytes_per_pixel = match img.color() {
+                    // RGB16F
+                    ColorType::Rgb32F | ColorType::Rgba32F => 6,
+                    // SRGB8
+                    _ => 3,
+                };
+                img.width() as usize * img.height() as usize * bytes_per_pixel
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            TextureImage::Compressed(img) => img.levels.iter().map(Vec::len).sum(),
+        }
+    }
+
+    /// Upload all mip levels into texture target of currently bound texture, e.g.
+    /// `TEXTURE_2D` or a face of `TEXTURE_CUBE_MAP`.
+    pub fn upload(&self, gl: &glow::Context, target: u32) {
+        match self {
+            TextureImage::Decoded(img) => upload_decoded(gl, target, img),
+            #[cfg(not(target_arch = "wasm32"))]
+            TextureImage::Compressed(img) => upload_compressed(gl, target, img),
+        }
+    }
+}
+
+/// Load texture image from file. Format is selected by file extension: DDS files are loaded with
+/// their block compression and mip levels intact, other formats (including EXR) are decoded by
+/// `image` crate.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_texture_image(path: &str) -> Result<TextureImage, String> {
+    let is_dds = Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("dds"));
+    if is_dds {
+        return load_dds(path).map(TextureImage::Compressed);
+    }
+
+    let img = image::open(path).map_err(|e| format!("{path}: {e}"))?;
+    Ok(TextureImage::Decoded(img))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_dds(path: &str) -> Result<CompressedImage, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("{path}: {e}"))?;
+    let dds = Dds::read(std::io::BufReader::new(file))
+        .map_err(|e| format!("{path}: invalid DDS file: {e}"))?;
+    let format = CompressedFormat::from_dds(&dds).ok_or_else(|| {
+        format!("{path}: unsupported DDS pixel format, expected BC1-BC3, BC6H or BC7")
+    })?;
+    let data = dds
+        .get_data(0)
+        .map_err(|e| format!("{path}: missing DDS image data: {e}"))?;
+
+    // Mip levels are stored one after the other, each half the size of the previous one
+    let (width, height) = (dds.get_width(), dds.get_height());
+    let mut levels = Vec::new();
+    let mut offset = 0;
+    for level in 0..dds.get_num_mipmap_levels() {
+        let level_width = (width >> level).max(1) as usize;
+        let level_height = (height >> level).max(1) as usize;
+        let size = level_width.div_ceil(4) * level_height.div_ceil(4) * format.block_size();
+        let level_data = data
+            .get(offset..offset + size)
+            .ok_or_else(|| format!("{path}: DDS mip level {level} is truncated"))?;
+        levels.push(level_data.to_vec());
+        offset += size;
+    }
+
+    Ok(CompressedImage {
+        width,
+        height,
+        format,
+        levels,
+    })
+}
+
+fn upload_decoded(gl: &glow::Context, target: u32, img: &DynamicImage) {
+    unsafe {
+        match img.color() {
+            // HDR images keep their values above 1.0, exposure is applied in post-processing.
+            // Half precision is plenty for color and halves memory usage.
+            ColorType::Rgb32F | ColorType::Rgba32F => gl.tex_image_2d(
+                target,
+                0,
+                glow::RGB16F as i32,
+                img.width() as i32,
+                img.height() as i32,
+                0,
+                glow::RGB,
+                glow::FLOAT,
+                Some(img.to_rgb32f().as_bytes()),
+            ),
+            // Image files are authored in sRGB color space. Let the GPU convert texels to linear
+            // color space on sampling to use them correctly in lighting calculations.
+            _ => gl.tex_image_2d(
+                target,
+                0,
+                glow::SRGB8 as i32,
+                img.width() as i32,
+                img.height() as i32,
+                0,
+                glow::RGB,
+                glow::UNSIGNED_BYTE,
+                Some(img.to_rgb8().as_bytes()),
+            ),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn upload_compressed(gl: &glow::Context, target: u32, img: &CompressedImage) {
+    for (level, data) in img.levels.iter().enumerate() {
+        unsafe {
+            gl.compressed_tex_image_2d(
+                target,
+                level as i32,
+                img.format.internal_format() as i32,
+                (img.width >> level).max(1) as i32,
+                (img.height >> level).max(1) as i32,
+                0,
+                data.len() as i32,
+                data,
+            );
+        }
+    }
+}
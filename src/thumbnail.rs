@@ -0,0 +1,171 @@
+use cgmath::{EuclideanSpace, Point3, Vector2};
+
+use crate::{Camera, DrawProperties};
+
+const DEFAULT_OUTPUT_PATH: &str = "thumbnail.png";
+const DEFAULT_SIZE: u32 = 512;
+/// Viewing angle of the framed model, looking slightly down at it.
+const CAMERA_ROTATION: Vector2<f32> = Vector2::new(270.0, -20.0);
+/// Empty space left around the bounding sphere of the model, relative to its radius.
+const FRAMING_MARGIN: f32 = 1.1;
+/// Far clipping plane of the renderer's projection.
+const MAX_VIEW_DISTANCE: f32 = 100.0;
+
+/// Settings of the `thumbnail` command line mode.
+pub struct ThumbnailOptions {
+    /// OBJ file of the model.
+    pub model_path: String,
+    /// Image file written, with format selected by file extension.
+    pub output_path: String,
+    /// Width and height of the square image in pixels.
+    pub size: u32,
+}
+
+impl ThumbnailOptions {
+    /// Parse arguments following the `thumbnail` subcommand, in the form of
+    /// `<model> [--out <path>] [--size <pixels>]`.
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut model_path = None;
+        let mut output_path = DEFAULT_OUTPUT_PATH.to_string();
+        let mut size = DEFAULT_SIZE;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--out" => {
+                    output_path = args.next().ok_or("--out requires a file path")?.clone();
+                }
+                "--size" => {
+                    size = args
+                        .next()
+                        .ok_or("--size requires a number of pixels")?
+                        .parse()
+                        .ok()
+                        .filter(|&size| 0 < size)
+                        .ok_or("--size must be a positive number of pixels")?;
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+                _ if model_path.is_none() => model_path = Some(arg.clone()),
+                _ => return Err(format!("unexpected argument {arg}")),
+            }
+        }
+
+        Ok(Self {
+            model_path: model_path.ok_or(
+                "usage: 3d-renderer-rust thumbnail <model> [--out <path>] [--size <pixels>]",
+            )?,
+            output_path,
+            size,
+        })
+    }
+}
+
+/// Render model into an image file without opening a window, e.g. for generating thumbnails of
+/// assets in build pipelines.
+///
+/// Camera is placed to fit the bounding sphere of the model into the image. Rendering runs in a
+/// headless EGL context, so an EGL device is required, e.g. a GPU driver or the Mesa software
+/// rasterizer.
+pub fn render_thumbnail(options: &ThumbnailOptions) -> Result<(), String> {
+    cfg_if::cfg_if! { if #[cfg(target_os = "macos")] {
+        let _ = options;
+        Err("thumbnail rendering requires EGL, which is not available on macOS".to_string())
+    } else {
+        use crate::{Model, Renderer, Scene, Skybox};
+
+        let (_context, gl) = headless::create_context()?;
+        let mut renderer = Renderer::new(gl.clone())?;
+        let skybox = Skybox::placeholder(gl.clone()).map_err(|e| e.to_string())?;
+        let model = Model::create_from_file(gl, &options.model_path)?;
+
+        let draw_props = DrawProperties {
+            selected_model_index: 0,
+            light_gizmo_enabled: false,
+            skybox_enabled: false,
+            ..Default::default()
+        };
+        let camera = framing_camera(model.bounds().center(), model.bounds().radius(), &draw_props)?;
+        let mut scene = Scene::new(camera);
+        scene.models.push(model);
+        scene.spawn_selectable(0);
+        scene.apply_draw_properties(&draw_props);
+
+        renderer.render_to_texture(
+            &scene.camera,
+            &draw_props,
+            &scene,
+            &skybox,
+            options.size,
+            options.size,
+        )?;
+        renderer.save_texture_output(&options.output_path)?;
+        println!("saved thumbnail to {}", options.output_path);
+        Ok(())
+    }}
+}
+
+/// Camera looking at the bounding sphere from the distance where it fills the field of view.
+fn framing_camera(
+    center: Point3<f32>,
+    radius: f32,
+    draw_props: &DrawProperties,
+) -> Result<Camera, String> {
+    let half_field_of_view = (draw_props.field_of_view / 2.0).to_radians();
+    let distance = radius * FRAMING_MARGIN / half_field_of_view.sin();
+    if MAX_VIEW_DISTANCE < distance + radius {
+        return Err(format!(
+            "model with bounding sphere radius {radius} is too large to fit into the view"
+        ));
+    }
+
+    let direction = *Camera::new(Point3::origin(), CAMERA_ROTATION).direction();
+    Ok(Camera::new(center - direction * distance, CAMERA_ROTATION))
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) mod headless {
+    use std::sync::Arc;
+
+    use glutin::{
+        api::egl::{context::PossiblyCurrentContext, device::Device, display::Display},
+        config::{ConfigSurfaceTypes, ConfigTemplateBuilder},
+        context::{ContextApi, ContextAttributesBuilder, GlProfile, Version},
+        prelude::*,
+    };
+
+    /// OpenGL context made current without any surface, preferring OpenGL 4.3 and falling back to
+    /// 3.3. Context must be kept alive while the returned glow context is used.
+    pub fn create_context() -> Result<(PossiblyCurrentContext, Arc<glow::Context>), String> {
+        let device = Device::query_devices()
+            .map_err(|e| format!("cannot query EGL devices: {e}"))?
+            .next()
+            .ok_or("no EGL device available")?;
+        let display = unsafe { Display::with_device(&device, None) }
+            .map_err(|e| format!("cannot create EGL display: {e}"))?;
+        // Offscreen rendering doesn't need any surface support
+        let template = ConfigTemplateBuilder::new()
+            .with_surface_type(ConfigSurfaceTypes::empty())
+            .build();
+        let config = unsafe { display.find_configs(template) }
+            .map_err(|e| format!("cannot query EGL configs: {e}"))?
+            .next()
+            .ok_or("no EGL config available")?;
+
+        let create = |version| {
+            let context_attributes = ContextAttributesBuilder::new()
+                .with_profile(GlProfile::Core)
+                .with_context_api(ContextApi::OpenGl(Some(version)))
+                .build(None);
+            unsafe { display.create_context(&config, &context_attributes) }
+        };
+        let context = create(Version::new(4, 3))
+            .or_else(|_| create(Version::new(3, 3)))
+            .map_err(|e| format!("cannot create OpenGL context: {e}"))?
+            .make_current_surfaceless()
+            .map_err(|e| format!("cannot make context current: {e}"))?;
+
+        let gl = unsafe {
+            glow::Context::from_loader_function_cstr(|symbol| display.get_proc_address(symbol))
+        };
+        Ok((context, Arc::new(gl)))
+    }
+}
@@ -0,0 +1,265 @@
+use hecs::{Entity, World};
+
+use crate::{DrawProperties, Easing, Interpolate, Transform};
+
+/// Property of the scene whose value is keyframed by a track of the timeline. Single values are
+/// stored in the first component of keyframe values.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AnimatedProperty {
+    LightDirection,
+    BackgroundColor,
+    ModelRotation,
+    FieldOfView,
+    Exposure,
+    ReflectivePlaneHeight,
+    /// Position of an entity with a `Transform`.
+    EntityPosition(Entity),
+    /// Euler angles of an entity with a `Transform`.
+    EntityRotation(Entity),
+}
+
+impl AnimatedProperty {
+    /// Properties of `DrawProperties`, without the ones of entities.
+    pub const DRAW_PROPERTIES: [AnimatedProperty; 6] = [
+        AnimatedProperty::LightDirection,
+        AnimatedProperty::BackgroundColor,
+        AnimatedProperty::ModelRotation,
+        AnimatedProperty::FieldOfView,
+        AnimatedProperty::Exposure,
+        AnimatedProperty::ReflectivePlaneHeight,
+    ];
+
+    /// Message identifier of the translated label.
+    pub(crate) fn l10n_id(self) -> &'static str {
+        match self {
+            AnimatedProperty::LightDirection => "animated-light-direction",
+            AnimatedProperty::BackgroundColor => "animated-background-color",
+            AnimatedProperty::ModelRotation => "animated-model-rotation",
+            AnimatedProperty::FieldOfView => "animated-field-of-view",
+            AnimatedProperty::Exposure => "animated-exposure",
+            AnimatedProperty::ReflectivePlaneHeight => "animated-reflective-plane-height",
+            AnimatedProperty::EntityPosition(_) => "animated-entity-position",
+            AnimatedProperty::EntityRotation(_) => "animated-entity-rotation",
+        }
+    }
+
+    /// Entity the property belongs to, `None` for properties of `DrawProperties`.
+    pub fn entity(self) -> Option<Entity> {
+        match self {
+            AnimatedProperty::EntityPosition(entity) | AnimatedProperty::EntityRotation(entity) => {
+                Some(entity)
+            }
+            _ => None,
+        }
+    }
+
+    /// Current value, `None` if the entity was despawned or has no transform.
+    pub fn read(self, draw_props: &DrawProperties, world: &World) -> Option<[f32; 3]> {
+        let value = match self {
+            AnimatedProperty::LightDirection => draw_props.light_direction,
+            AnimatedProperty::BackgroundColor => draw_props.background_color,
+            AnimatedProperty::ModelRotation => draw_props.model_rotation,
+            AnimatedProperty::FieldOfView => [draw_props.field_of_view, 0.0, 0.0],
+            AnimatedProperty::Exposure => [draw_props.exposure, 0.0, 0.0],
+            AnimatedProperty::ReflectivePlaneHeight => {
+                [draw_props.reflective_plane_height, 0.0, 0.0]
+            }
+            AnimatedProperty::EntityPosition(entity) => {
+                world.get::<&Transform>(entity).ok()?.position.into()
+            }
+            AnimatedProperty::EntityRotation(entity) => {
+                world.get::<&Transform>(entity).ok()?.rotation
+            }
+        };
+        Some(value)
+    }
+
+    /// Replace the current value. Entities without a transform are left alone.
+    pub fn write(self, value: [f32; 3], draw_props: &mut DrawProperties, world: &mut World) {
+        match self {
+            AnimatedProperty::LightDirection => draw_props.light_direction = value,
+            AnimatedProperty::BackgroundColor => draw_props.background_color = value,
+            AnimatedProperty::ModelRotation => draw_props.model_rotation = value,
+            AnimatedProperty::FieldOfView => draw_props.field_of_view = value[0],
+            AnimatedProperty::Exposure => draw_props.exposure = value[0],
+            AnimatedProperty::ReflectivePlaneHeight => {
+                draw_props.reflective_plane_height = value[0]
+            }
+            AnimatedProperty::EntityPosition(entity) => {
+                if let Ok(mut transform) = world.get::<&mut Transform>(entity) {
+                    transform.position = value.into();
+                }
+            }
+            AnimatedProperty::EntityRotation(entity) => {
+                if let Ok(mut transform) = world.get::<&mut Transform>(entity) {
+                    transform.rotation = value;
+                }
+            }
+        }
+    }
+}
+
+/// Value of a property at a point of time.
+#[derive(Clone, Copy)]
+pub struct Keyframe {
+    /// Seconds from the start of the timeline.
+    pub time: f32,
+    pub value: [f32; 3],
+    /// Progress of the change from the previous keyframe towards this one.
+    pub easing: Easing,
+}
+
+/// Keyframes of a single property, ordered by time.
+pub struct Track {
+    pub property: AnimatedProperty,
+    keyframes: Vec<Keyframe>,
+}
+
+impl Track {
+    pub fn new(property: AnimatedProperty) -> Self {
+        Self {
+            property,
+            keyframes: Vec::new(),
+        }
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    /// Insert keyframe in time order, replacing the one at the same time.
+    pub fn set_keyframe(&mut self, keyframe: Keyframe) {
+        match self
+            .keyframes
+            .binary_search_by(|other| other.time.total_cmp(&keyframe.time))
+        {
+            Ok(index) => self.keyframes[index] = keyframe,
+            Err(index) => self.keyframes.insert(index, keyframe),
+        }
+    }
+
+    pub fn remove_keyframe(&mut self, index: usize) {
+        self.keyframes.remove(index);
+    }
+
+    /// Value blended between the keyframes around the time. Values before the first and after the
+    /// last keyframe are held. `None` without keyframes.
+    pub fn value_at(&self, time: f32) -> Option<[f32; 3]> {
+        let next = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time);
+        match (next.checked_sub(1), self.keyframes.get(next)) {
+            (Some(previous), Some(next)) => {
+                let previous = &self.keyframes[previous];
+                let t = (time - previous.time) / (next.time - previous.time);
+                Some(previous.value.interpolate(next.value, next.easing.apply(t)))
+            }
+            (Some(previous), None) => Some(self.keyframes[previous].value),
+            (None, next) => next.map(|next| next.value),
+        }
+    }
+}
+
+/// Keyframe animation of scene properties, played back by logic updates.
+///
+/// Properties are only written while playing or when the time is changed with `seek()`, so they
+/// can be edited freely in between to set up the next keyframe.
+pub struct Timeline {
+    pub tracks: Vec<Track>,
+    /// Seconds from the start, up to the duration.
+    time: f32,
+    /// Length of playback in seconds.
+    pub duration: f32,
+    pub playing: bool,
+    /// Start again from the beginning when the end is reached, otherwise stop there.
+    pub looping: bool,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self {
+            tracks: Vec::new(),
+            time: 0.0,
+            duration: 5.0,
+            playing: false,
+            looping: true,
+        }
+    }
+}
+
+impl Timeline {
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Jump to a point of time and write the values of every track there.
+    pub fn seek(&mut self, time: f32, draw_props: &mut DrawProperties, world: &mut World) {
+        self.time = time.clamp(0.0, self.duration.max(0.0));
+        self.apply(draw_props, world);
+    }
+
+    /// Advance playback by `delta_time` seconds and write the values of every track, if playing.
+    pub fn update(&mut self, delta_time: f32, draw_props: &mut DrawProperties, world: &mut World) {
+        if !self.playing {
+            return;
+        }
+        self.time += delta_time;
+        if self.duration <= self.time {
+            if self.looping && 0.0 < self.duration {
+                self.time %= self.duration;
+            } else {
+                self.time = self.duration.max(0.0);
+                self.playing = false;
+            }
+        }
+        self.apply(draw_props, world);
+    }
+
+    /// Keyframe the current value of the property at the current time, adding a track for it if
+    /// there is none yet. Ignored if the value can't be read.
+    pub fn add_keyframe(
+        &mut self,
+        property: AnimatedProperty,
+        easing: Easing,
+        draw_props: &DrawProperties,
+        world: &World,
+    ) {
+        let Some(value) = property.read(draw_props, world) else {
+            return;
+        };
+        let index = match self
+            .tracks
+            .iter()
+            .position(|track| track.property == property)
+        {
+            Some(index) => index,
+            None => {
+                self.tracks.push(Track::new(property));
+                self.tracks.len() - 1
+            }
+        };
+        self.tracks[index].set_keyframe(Keyframe {
+            time: self.time,
+            value,
+            easing,
+        });
+    }
+
+    /// Drop tracks of despawned entities.
+    pub fn retain_existing(&mut self, world: &World) {
+        self.tracks.retain(|track| {
+            track
+                .property
+                .entity()
+                .is_none_or(|entity| world.contains(entity))
+        });
+    }
+
+    fn apply(&self, draw_props: &mut DrawProperties, world: &mut World) {
+        for track in &self.tracks {
+            if let Some(value) = track.value_at(self.time) {
+                track.property.write(value, draw_props, world);
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use cgmath::{Point3, Vector3};
+
+/// Progress of an animation over its duration.
+///
+/// Discriminant values match option indices of HTML select elements.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Easing {
+    /// Constant speed, starting and stopping abruptly.
+    Linear = 0,
+    /// Speeding up and slowing down gently.
+    Smoothstep = 1,
+    /// Speeding up and slowing down more strongly than smoothstep, fastest in the middle.
+    Cubic = 2,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 3] = [Easing::Linear, Easing::Smoothstep, Easing::Cubic];
+
+    /// Message identifier of the translated label.
+    pub(crate) fn l10n_id(self) -> &'static str {
+        match self {
+            Easing::Linear => "easing-linear",
+            Easing::Smoothstep => "easing-smoothstep",
+            Easing::Cubic => "easing-cubic",
+        }
+    }
+
+    /// Eased progress of linear progress `t` from 0 to 1. Both ends are kept in place.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::Smoothstep => t * t * (3.0 - 2.0 * t),
+            Easing::Cubic => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (2.0 - 2.0 * t).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// Value that can be blended with another one of the same type.
+pub trait Interpolate: Copy {
+    /// Value between `self` (`t` 0) and `other` (`t` 1).
+    fn interpolate(self, other: Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for [f32; 3] {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        std::array::from_fn(|i| self[i].interpolate(other[i], t))
+    }
+}
+
+impl Interpolate for Vector3<f32> {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Point3<f32> {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+/// Animation of a value from a start to an end over a duration, advanced by the elapsed time.
+#[derive(Clone, Copy)]
+pub struct Tween<T> {
+    from: T,
+    to: T,
+    /// Seconds from start to end.
+    duration: f32,
+    elapsed: f32,
+    easing: Easing,
+}
+
+impl<T: Interpolate> Tween<T> {
+    /// Zero or negative duration jumps to the end on the first advance.
+    pub fn new(from: T, to: T, duration: f32, easing: Easing) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            elapsed: 0.0,
+            easing,
+        }
+    }
+
+    /// Pass `delta_time` seconds and return the value at the new time.
+    pub fn advance(&mut self, delta_time: f32) -> T {
+        self.elapsed = (self.elapsed + delta_time).min(self.duration.max(0.0));
+        self.value()
+    }
+
+    /// Value at the current time.
+    pub fn value(&self) -> T {
+        let t = if 0.0 < self.duration {
+            self.elapsed / self.duration
+        } else {
+            1.0
+        };
+        self.from.interpolate(self.to, self.easing.apply(t))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.duration <= self.elapsed
+    }
+}
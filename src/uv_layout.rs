@@ -0,0 +1,250 @@
+use cgmath::{InnerSpace, Vector2};
+
+/// Overlap smaller than this in texture space is ignored, so triangles sharing an edge or corner
+/// don't count as overlapping.
+const OVERLAP_TOLERANCE: f32 = 1e-5;
+/// Most cells of the grid along each axis used for finding overlap candidates.
+const MAX_GRID_SIZE: usize = 256;
+
+/// Triangles of a mesh laid out in texture space, with triangles covering the same texels marked,
+/// e.g. for checking texture coordinates of a model before assigning textures. Overlaps are
+/// usually mistakes, apart from intentionally mirrored or stacked shells.
+pub struct UvLayout {
+    pub triangle_count: usize,
+    /// Triangles overlapping at least one other triangle.
+    pub overlapping_triangles: usize,
+    // Shared edges are stored once
+    edges: Vec<[Vector2<f32>; 2]>,
+    overlapping: Vec<[Vector2<f32>; 3]>,
+    min: Vector2<f32>,
+    max: Vector2<f32>,
+}
+
+impl UvLayout {
+    pub fn analyze(uvs: &[Vector2<f32>], indices: &[u32]) -> Self {
+        let triangles: Vec<[Vector2<f32>; 3]> = indices
+            .chunks_exact(3)
+            .map(|triangle| [0, 1, 2].map(|i| uvs[triangle[i] as usize]))
+            .collect();
+        let mut edge_indices = std::collections::HashSet::new();
+        for triangle in indices.chunks_exact(3) {
+            for i in 0..3 {
+                let (start, end) = (triangle[i], triangle[(i + 1) % 3]);
+                edge_indices.insert((start.min(end), start.max(end)));
+            }
+        }
+        let edges = edge_indices
+            .into_iter()
+            .map(|(start, end)| [uvs[start as usize], uvs[end as usize]])
+            .collect();
+
+        let (min, max) = triangles.iter().flatten().fold(
+            (Vector2::new(0.0, 0.0), Vector2::new(1.0, 1.0)),
+            extend_bounds,
+        );
+        let overlapping_flags = find_overlaps(&triangles, min, max);
+        let overlapping: Vec<_> = triangles
+            .iter()
+            .zip(&overlapping_flags)
+            .filter(|(_, &overlapping)| overlapping)
+            .map(|(triangle, _)| *triangle)
+            .collect();
+        Self {
+            triangle_count: triangles.len(),
+            overlapping_triangles: overlapping.len(),
+            edges,
+            overlapping,
+            min,
+            max,
+        }
+    }
+
+    pub fn edges(&self) -> &[[Vector2<f32>; 2]] {
+        &self.edges
+    }
+
+    pub fn overlapping(&self) -> &[[Vector2<f32>; 3]] {
+        &self.overlapping
+    }
+
+    /// Corners of the rectangle containing every texture coordinate and the 0-1 texture range.
+    pub fn bounds(&self) -> (Vector2<f32>, Vector2<f32>) {
+        (self.min, self.max)
+    }
+}
+
+fn extend_bounds(
+    (min, max): (Vector2<f32>, Vector2<f32>),
+    uv: &Vector2<f32>,
+) -> (Vector2<f32>, Vector2<f32>) {
+    (
+        Vector2::new(min.x.min(uv.x), min.y.min(uv.y)),
+        Vector2::new(max.x.max(uv.x), max.y.max(uv.y)),
+    )
+}
+
+/// Whether each triangle overlaps another one. Candidates are triangles with bounding boxes in
+/// the same cells of a grid over the bounds.
+fn find_overlaps(
+    triangles: &[[Vector2<f32>; 3]],
+    min: Vector2<f32>,
+    max: Vector2<f32>,
+) -> Vec<bool> {
+    let grid_size = ((triangles.len() as f32).sqrt().ceil() as usize).clamp(1, MAX_GRID_SIZE);
+    let size = max - min;
+    let cell = |uv: Vector2<f32>| {
+        let relative = (uv - min).zip(size, |offset, size| offset / size);
+        relative.map(|r| ((r * grid_size as f32) as usize).min(grid_size - 1))
+    };
+    let cell_ranges: Vec<_> = triangles
+        .iter()
+        .map(|triangle| {
+            let (low, high) = triangle
+                .iter()
+                .fold((triangle[0], triangle[0]), extend_bounds);
+            (cell(low), cell(high))
+        })
+        .collect();
+    let mut grid = vec![Vec::new(); grid_size * grid_size];
+    for (triangle_index, (low, high)) in cell_ranges.iter().enumerate() {
+        for y in low.y..=high.y {
+            for x in low.x..=high.x {
+                grid[y * grid_size + x].push(triangle_index);
+            }
+        }
+    }
+
+    let mut overlapping = vec![false; triangles.len()];
+    // Triangle last tested against each triangle, so pairs sharing several cells are tested once
+    let mut tested_with = vec![usize::MAX; triangles.len()];
+    for (triangle_index, (low, high)) in cell_ranges.iter().enumerate() {
+        for y in low.y..=high.y {
+            for x in low.x..=high.x {
+                for &other_index in &grid[y * grid_size + x] {
+                    if other_index <= triangle_index || tested_with[other_index] == triangle_index {
+                        continue;
+                    }
+                    tested_with[other_index] = triangle_index;
+                    if triangles_overlap(&triangles[triangle_index], &triangles[other_index]) {
+                        overlapping[triangle_index] = true;
+                        overlapping[other_index] = true;
+                    }
+                }
+            }
+        }
+    }
+    overlapping
+}
+
+/// Separating axis test of the triangles, with edge normals as candidate axes. Triangles without
+/// area never overlap.
+fn triangles_overlap(a: &[Vector2<f32>; 3], b: &[Vector2<f32>; 3]) -> bool {
+    let axes = |triangle: [Vector2<f32>; 3]| {
+        (0..3).map(move |i| {
+            let edge = triangle[(i + 1) % 3] - triangle[i];
+            Vector2::new(-edge.y, edge.x)
+        })
+    };
+    let project = |triangle: &[Vector2<f32>; 3], axis: Vector2<f32>| {
+        triangle
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(low, high), uv| {
+                let distance = uv.dot(axis);
+                (low.min(distance), high.max(distance))
+            })
+    };
+    for axis in axes(*a).chain(axes(*b)) {
+        if axis.magnitude2() == 0.0 {
+            return false;
+        }
+        let axis = axis.normalize();
+        let ((a_low, a_high), (b_low, b_high)) = (project(a, axis), project(b, axis));
+        if a_high.min(b_high) - a_low.max(b_low) <= OVERLAP_TOLERANCE {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::vec2;
+
+    use super::*;
+    use crate::primitives;
+
+    const TRIANGLE: [Vector2<f32>; 3] = [
+        Vector2::new(0.0, 0.0),
+        Vector2::new(1.0, 0.0),
+        Vector2::new(0.0, 1.0),
+    ];
+
+    fn offset(triangle: [Vector2<f32>; 3], x: f32, y: f32) -> [Vector2<f32>; 3] {
+        triangle.map(|uv| uv + vec2(x, y))
+    }
+
+    #[test]
+    fn triangles_overlap_when_covering_same_area() {
+        assert!(triangles_overlap(&TRIANGLE, &TRIANGLE));
+        assert!(triangles_overlap(&TRIANGLE, &offset(TRIANGLE, 0.25, 0.25)));
+    }
+
+    #[test]
+    fn triangles_sharing_edge_or_corner_do_not_overlap() {
+        let across_edge = [
+            Vector2::new(1.0, 0.0),
+            Vector2::new(1.0, 1.0),
+            Vector2::new(0.0, 1.0),
+        ];
+        assert!(!triangles_overlap(&TRIANGLE, &across_edge));
+        assert!(!triangles_overlap(&TRIANGLE, &offset(TRIANGLE, 1.0, 0.0)));
+    }
+
+    #[test]
+    fn separated_or_degenerate_triangles_do_not_overlap() {
+        assert!(!triangles_overlap(&TRIANGLE, &offset(TRIANGLE, 2.0, 2.0)));
+        let degenerate = [
+            Vector2::new(0.1, 0.1),
+            Vector2::new(0.2, 0.2),
+            Vector2::new(0.3, 0.3),
+        ];
+        assert!(!triangles_overlap(&TRIANGLE, &degenerate));
+    }
+
+    #[test]
+    fn find_overlaps_marks_both_triangles_of_pair() {
+        let triangles = [
+            TRIANGLE,
+            offset(TRIANGLE, 5.0, 5.0),
+            offset(TRIANGLE, 0.25, 0.25),
+        ];
+        let overlapping = find_overlaps(&triangles, vec2(0.0, 0.0), vec2(6.0, 6.0));
+        assert_eq!(overlapping, [true, false, true]);
+    }
+
+    #[test]
+    fn find_overlaps_handles_triangles_spanning_cells() {
+        // Large triangle overlapping small ones in different grid cells
+        let mut triangles = vec![offset(TRIANGLE, 0.0, 0.0).map(|uv| uv * 4.0)];
+        triangles.extend((0..8).map(|i| offset(TRIANGLE, 6.0 + i as f32 * 1.5, 0.0)));
+        triangles.push(offset(TRIANGLE, 2.5, 0.5));
+        let overlapping = find_overlaps(&triangles, vec2(0.0, 0.0), vec2(20.0, 2.0));
+        assert!(overlapping[0] && overlapping[9]);
+        assert!(overlapping[1..9].iter().all(|&overlapping| !overlapping));
+    }
+
+    #[test]
+    fn analyze_counts_overlapping_triangles() {
+        let plane = primitives::plane(1.0, 1.0, 4);
+        let layout = UvLayout::analyze(&plane.uvs, &plane.indices);
+        assert_eq!(layout.triangle_count, 32);
+        assert_eq!(layout.overlapping_triangles, 0);
+        assert_eq!(layout.bounds(), (vec2(0.0, 0.0), vec2(1.0, 1.0)));
+
+        // Every face of the cuboid covers the whole texture
+        let cuboid = primitives::cuboid(1.0, 1.0, 1.0);
+        let layout = UvLayout::analyze(&cuboid.uvs, &cuboid.indices);
+        assert_eq!(layout.overlapping_triangles, 12);
+        assert_eq!(layout.overlapping().len(), 12);
+    }
+}
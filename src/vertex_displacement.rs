@@ -0,0 +1,148 @@
+use std::{sync::Arc, time::Instant};
+
+use glow::{Buffer, HasContext};
+
+use crate::{assets, gl_resources, gl_state, shader::ComputeShader, Model};
+
+/// Number of invocations in a single work group, must match `local_size_x` of compute shader.
+const WORK_GROUP_SIZE: u32 = 64;
+
+/// Demonstration of compute shaders animating waves on the surface of a model by displacing
+/// vertices along their normals.
+///
+/// Compute shader writes into the vertex buffer of the model directly, no copy to CPU memory is
+/// involved. Original vertices are kept in a separate buffer and written back when displacement
+/// is turned off or a different model is displaced.
+pub struct VertexDisplacement {
+    gl: Arc<glow::Context>,
+    compute_shader: ComputeShader,
+    // Undisplaced copy of vertex buffer of displaced model
+    source_buffer: Buffer,
+    // Index of the model whose vertex buffer is currently modified, and the buffer itself. Buffer
+    // differs if the mesh of the model was replaced, e.g. reloaded from file.
+    displaced_model: Option<(usize, Buffer)>,
+    start_time: Instant,
+}
+
+impl VertexDisplacement {
+    pub fn new(gl: Arc<glow::Context>) -> Result<Self, String> {
+        let compute_shader =
+            ComputeShader::new(gl.clone(), assets::shader::VERTEX_DISPLACEMENT_COMPUTE_SRC)
+                .map_err(|e| format!("vertex displacement shader creation failed: {e}"))?;
+        let source_buffer = unsafe {
+            gl_resources::create_buffer(&gl)
+                .map_err(|e| format!("cannot create vertex displacement source buffer: {e}"))?
+        };
+
+        Ok(Self {
+            gl,
+            compute_shader,
+            source_buffer,
+            displaced_model: None,
+            start_time: Instant::now(),
+        })
+    }
+
+    /// Animate vertices of model for the current frame.
+    pub fn apply(&mut self, models: &[Model], model_index: usize, amplitude: f32) {
+        let model = &models[model_index];
+        if self.displaced_model != Some((model_index, model.vertex_buffer())) {
+            self.restore(models);
+            self.save_source(model);
+            self.displaced_model = Some((model_index, model.vertex_buffer()));
+        }
+
+        self.compute_shader.r#use();
+        self.compute_shader
+            .bind_storage_buffer(0, self.source_buffer);
+        self.compute_shader
+            .bind_storage_buffer(1, model.vertex_buffer());
+        self.compute_shader
+            .set_uniform("u_vertexCount", &(model.vertex_count() as u32));
+        self.compute_shader.set_uniform(
+            "u_floatsPerVertex",
+            &(model.mesh().vertex_layout().floats_per_vertex() as u32),
+        );
+        self.compute_shader
+            .set_uniform("u_time", &self.start_time.elapsed().as_secs_f32());
+        self.compute_shader.set_uniform("u_amplitude", &amplitude);
+
+        let work_group_count = (model.vertex_count() as u32).div_ceil(WORK_GROUP_SIZE);
+        self.compute_shader.dispatch(work_group_count, 1, 1);
+        // Vertex buffer is read as vertex attributes by the following draw calls, and mapped when
+        // the mesh is exported
+        self.compute_shader.memory_barrier(
+            glow::VERTEX_ATTRIB_ARRAY_BARRIER_BIT | glow::BUFFER_UPDATE_BARRIER_BIT,
+        );
+
+        unsafe {
+            gl_state::use_program(&self.gl, None);
+        }
+    }
+
+    /// Write original vertices back into vertex buffer of displaced model.
+    pub fn restore(&mut self, models: &[Model]) {
+        let Some((model_index, vertex_buffer)) = self.displaced_model.take() else {
+            return;
+        };
+        // Replaced mesh has its original vertices already
+        let model = &models[model_index];
+        if model.vertex_buffer() != vertex_buffer {
+            return;
+        }
+
+        unsafe {
+            self.gl
+                .bind_buffer(glow::COPY_READ_BUFFER, Some(self.source_buffer));
+            self.gl
+                .bind_buffer(glow::COPY_WRITE_BUFFER, Some(model.vertex_buffer()));
+            self.gl.copy_buffer_sub_data(
+                glow::COPY_READ_BUFFER,
+                glow::COPY_WRITE_BUFFER,
+                0,
+                0,
+                vertex_buffer_size(model),
+            );
+            self.gl.bind_buffer(glow::COPY_READ_BUFFER, None);
+            self.gl.bind_buffer(glow::COPY_WRITE_BUFFER, None);
+        }
+    }
+
+    fn save_source(&self, model: &Model) {
+        let size = vertex_buffer_size(model);
+        unsafe {
+            self.gl
+                .bind_buffer(glow::COPY_WRITE_BUFFER, Some(self.source_buffer));
+            gl_resources::buffer_data_size(
+                &self.gl,
+                self.source_buffer,
+                glow::COPY_WRITE_BUFFER,
+                size,
+                glow::STATIC_COPY,
+            );
+            self.gl
+                .bind_buffer(glow::COPY_READ_BUFFER, Some(model.vertex_buffer()));
+            self.gl.copy_buffer_sub_data(
+                glow::COPY_READ_BUFFER,
+                glow::COPY_WRITE_BUFFER,
+                0,
+                0,
+                size,
+            );
+            self.gl.bind_buffer(glow::COPY_READ_BUFFER, None);
+            self.gl.bind_buffer(glow::COPY_WRITE_BUFFER, None);
+        }
+    }
+}
+
+impl Drop for VertexDisplacement {
+    fn drop(&mut self) {
+        unsafe {
+            gl_resources::delete_buffer(&self.gl, self.source_buffer);
+        }
+    }
+}
+
+fn vertex_buffer_size(model: &Model) -> i32 {
+    (model.vertex_count() * model.mesh().vertex_layout().stride()) as i32
+}
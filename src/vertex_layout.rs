@@ -0,0 +1,96 @@
+/// Vertex attribute stored in vertex buffers. Bound to the shader input of the same location, e.g.
+/// `layout (location = 2) in vec2 a_uv;`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VertexAttribute {
+    Position = 0,
+    Normal = 1,
+    /// Texture coordinates.
+    Uv = 2,
+    /// Tangent direction in XYZ and handedness of the bitangent in W, for normal mapping.
+    Tangent = 3,
+    /// Linear RGBA color.
+    Color = 4,
+    /// Texture coordinates in the lightmap of baked lighting, unique for each triangle. Location 5
+    /// is taken by the draw index of multi-draw indirect rendering.
+    LightmapUv = 6,
+}
+
+impl VertexAttribute {
+    pub fn location(self) -> u32 {
+        self as u32
+    }
+
+    /// Number of floats the attribute takes.
+    pub fn component_count(self) -> usize {
+        match self {
+            Self::Position | Self::Normal => 3,
+            Self::Uv | Self::LightmapUv => 2,
+            Self::Tangent | Self::Color => 4,
+        }
+    }
+}
+
+/// Attributes interleaved in each vertex of a vertex buffer, replacing a fixed vertex stride.
+///
+/// Position and normal are always present and come first, so code reading them directly from
+/// vertex buffers (e.g. vertex displacement compute shader) works with any layout. Optional
+/// attributes follow in the order of their locations.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VertexLayout {
+    attributes: Vec<VertexAttribute>,
+}
+
+impl Default for VertexLayout {
+    fn default() -> Self {
+        Self {
+            attributes: vec![VertexAttribute::Position, VertexAttribute::Normal],
+        }
+    }
+}
+
+impl VertexLayout {
+    /// Layout with position and normal only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_attribute(mut self, attribute: VertexAttribute) -> Self {
+        if !self.contains(attribute) {
+            self.attributes.push(attribute);
+            self.attributes
+                .sort_by_key(|attribute| attribute.location());
+        }
+        self
+    }
+
+    pub fn attributes(&self) -> &[VertexAttribute] {
+        &self.attributes
+    }
+
+    pub fn contains(&self, attribute: VertexAttribute) -> bool {
+        self.attributes.contains(&attribute)
+    }
+
+    pub fn floats_per_vertex(&self) -> usize {
+        self.attributes
+            .iter()
+            .map(|attribute| attribute.component_count())
+            .sum()
+    }
+
+    /// Size of a vertex in bytes.
+    pub fn stride(&self) -> usize {
+        self.floats_per_vertex() * size_of::<f32>()
+    }
+
+    /// Offset of the attribute from the start of the vertex in floats, `None` if not present.
+    pub fn float_offset(&self, attribute: VertexAttribute) -> Option<usize> {
+        let index = self.attributes.iter().position(|&a| a == attribute)?;
+        Some(
+            self.attributes[..index]
+                .iter()
+                .map(|attribute| attribute.component_count())
+                .sum(),
+        )
+    }
+}
@@ -0,0 +1,133 @@
+use cgmath::{EuclideanSpace, InnerSpace, Point3, Vector2, Vector3};
+
+use crate::{Camera, DrawProperties, Renderer, Scene, Skybox};
+
+/// Degrees turned per point of dragging.
+const ORBIT_SENSITIVITY: f32 = 0.4;
+/// Relative change of distance per point of scrolling.
+const ZOOM_SENSITIVITY: f32 = 0.002;
+const MIN_DISTANCE: f32 = 0.1;
+/// Stays inside the far clipping plane of the renderer's projection.
+const MAX_DISTANCE: f32 = 90.0;
+
+/// egui widget displaying the scene rendered into a texture, with orbit controls around a target
+/// point, e.g. for adding a 3D preview to egui_glow or eframe applications.
+///
+/// Dragging with the primary button orbits, with the secondary or middle button pans and scrolling
+/// zooms. The camera of the widget is independent of the camera of the scene.
+pub struct ViewportWidget {
+    target: Point3<f32>,
+    distance: f32,
+    yaw: f32,
+    pitch: f32,
+    // Output texture of the renderer registered with the GUI painter
+    texture: Option<(glow::Texture, egui::TextureId)>,
+}
+
+impl Default for ViewportWidget {
+    fn default() -> Self {
+        Self {
+            target: Point3::origin(),
+            distance: 4.0,
+            yaw: 270.0,
+            pitch: -15.0,
+            texture: None,
+        }
+    }
+}
+
+impl ViewportWidget {
+    /// Orbit around the target point from the distance.
+    pub fn with_orbit(mut self, target: Point3<f32>, distance: f32) -> Self {
+        self.target = target;
+        self.distance = distance.clamp(MIN_DISTANCE, MAX_DISTANCE);
+        self
+    }
+
+    /// Camera the scene is rendered with by the next `show()` call.
+    pub fn camera(&self) -> Camera {
+        let rotation = Vector2::new(self.yaw, self.pitch);
+        let direction = *Camera::new(Point3::origin(), rotation).direction();
+        Camera::new(self.target - direction * self.distance, rotation)
+    }
+
+    /// Render the scene into the available space of the UI and display it, after applying orbit
+    /// input of the previous frame. Rendering failures are displayed inside the widget.
+    ///
+    /// `register_texture` is called when the output texture of the renderer needs to be made known
+    /// to the painter of the GUI, e.g. with `egui_glow::Painter::register_native_texture()` or
+    /// `eframe::Frame::register_native_glow_texture()`. Other output of `Renderer` rendered into
+    /// texture is overwritten, so the widget is shown before rendering any.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        renderer: &mut Renderer,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+        register_texture: impl FnOnce(glow::Texture) -> egui::TextureId,
+    ) -> egui::Response {
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), egui::Sense::drag());
+        self.handle_input(ui, &response);
+
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let width = (rect.width() * pixels_per_point).round().max(1.0) as u32;
+        let height = (rect.height() * pixels_per_point).round().max(1.0) as u32;
+        let texture = match renderer.render_to_texture(
+            &self.camera(),
+            draw_props,
+            scene,
+            skybox,
+            width,
+            height,
+        ) {
+            Ok(texture) => texture,
+            Err(e) => {
+                ui.painter().text(
+                    rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    e,
+                    egui::FontId::default(),
+                    ui.visuals().error_fg_color,
+                );
+                return response;
+            }
+        };
+        let texture_id = match self.texture {
+            Some((registered, texture_id)) if registered == texture => texture_id,
+            _ => {
+                let texture_id = register_texture(texture);
+                self.texture = Some((texture, texture_id));
+                texture_id
+            }
+        };
+
+        // OpenGL textures store the bottom row first
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 1.0), egui::pos2(1.0, 0.0));
+        ui.painter()
+            .image(texture_id, rect, uv, egui::Color32::WHITE);
+        response
+    }
+
+    fn handle_input(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        let delta = response.drag_delta();
+        if response.dragged_by(egui::PointerButton::Primary) {
+            self.yaw = (self.yaw + delta.x * ORBIT_SENSITIVITY).rem_euclid(360.0);
+            self.pitch = (self.pitch - delta.y * ORBIT_SENSITIVITY).clamp(-89.0, 89.0);
+        } else if response.dragged_by(egui::PointerButton::Secondary)
+            || response.dragged_by(egui::PointerButton::Middle)
+        {
+            // Target follows the pointer at the same speed on screen regardless of distance
+            let camera = self.camera();
+            let right = camera.direction().cross(Vector3::unit_y()).normalize();
+            let up = right.cross(*camera.direction());
+            let scale = self.distance / response.rect.height().max(1.0);
+            self.target += (up * delta.y - right * delta.x) * scale;
+        }
+        if response.hovered() {
+            let scroll = ui.input(|input| input.smooth_scroll_delta.y);
+            self.distance = (self.distance * (-scroll * ZOOM_SENSITIVITY).exp())
+                .clamp(MIN_DISTANCE, MAX_DISTANCE);
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use std::{cell::RefCell, rc::Rc};
+
+use cgmath::{Matrix4, SquareMatrix};
+use js_sys::{Array, Promise};
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    HtmlCanvasElement, WebGl2RenderingContext, XrFrame, XrReferenceSpace, XrReferenceSpaceType,
+    XrRenderStateInit, XrSession, XrSessionMode, XrSystem, XrView, XrWebGlLayer,
+};
+
+use crate::{Camera, DrawProperties, EyeView, Renderer, Scene, Skybox};
+
+/// Immersive session modes in order of preference.
+const SESSION_MODES: [XrSessionMode; 2] = [XrSessionMode::ImmersiveVr, XrSessionMode::ImmersiveAr];
+// Same clipping planes as the projection of the canvas
+const NEAR_PLANE: f64 = 0.1;
+const FAR_PLANE: f64 = 100.0;
+
+/// Immersive WebXR session displaying the scene in a VR or AR headset, e.g. in the browser of a
+/// Meta Quest.
+///
+/// Each eye is rendered into its viewport of the WebGL layer of the session, using the WebGL2
+/// context of the canvas. The fly camera is the origin of the play space, moving the camera moves
+/// the viewer in the scene.
+pub struct WebXrSession {
+    session: XrSession,
+    layer: XrWebGlLayer,
+    reference_space: XrReferenceSpace,
+}
+
+impl WebXrSession {
+    /// Schedule `callback` for the next frame of the headset. Frames of the headset replace
+    /// animation frames of the page, which are paused by browsers during immersive sessions.
+    pub fn request_animation_frame(&self, callback: &Closure<dyn FnMut(f64, XrFrame)>) {
+        self.session
+            .request_animation_frame(callback.as_ref().unchecked_ref());
+    }
+
+    /// Call `on_end` when the session is ended by the user or by the browser.
+    pub fn on_end(&self, on_end: impl FnOnce() + 'static) {
+        let f = Closure::once(move |_: web_sys::Event| on_end());
+        self.session.set_onend(Some(f.as_ref().unchecked_ref()));
+        f.forget();
+    }
+
+    pub fn end(&self) {
+        let _ = self.session.end();
+    }
+
+    /// Render the scene from each eye of the viewer pose of the frame.
+    pub fn render(
+        &self,
+        frame: &XrFrame,
+        renderer: &mut Renderer,
+        camera: &Camera,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+    ) -> Result<(), String> {
+        // Pose is missing while tracking is lost, the headset keeps showing the last frame
+        let Some(pose) = frame.get_viewer_pose(&self.reference_space) else {
+            return Ok(());
+        };
+        let framebuffer = self
+            .layer
+            .framebuffer()
+            .ok_or("WebXR layer has no framebuffer")?;
+
+        let origin = camera.play_space_transform();
+        for view in pose.views().iter() {
+            let view: XrView = view.unchecked_into();
+            let Some(viewport) = self.layer.get_viewport(&view) else {
+                continue;
+            };
+            let eye = EyeView {
+                view: (origin * matrix_from_slice(&view.transform().matrix()))
+                    .invert()
+                    .unwrap(),
+                projection: matrix_from_slice(&view.projection_matrix()),
+            };
+            renderer.draw_eye_into(
+                &eye,
+                &framebuffer,
+                [
+                    viewport.x(),
+                    viewport.y(),
+                    viewport.width(),
+                    viewport.height(),
+                ],
+                draw_props,
+                scene,
+                skybox,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Call `on_supported` with the first immersive session mode supported by the browser and the
+/// connected devices. Nothing is called without WebXR support, e.g. on pages not served over
+/// HTTPS.
+pub fn query_session_mode(on_supported: impl FnOnce(XrSessionMode) + 'static) {
+    let Some(xr) = xr_system() else {
+        return;
+    };
+    let queries: Array = SESSION_MODES
+        .iter()
+        .map(|&mode| xr.is_session_supported(mode))
+        .collect();
+    on_settled(&Promise::all(&queries), move |supported| {
+        let Ok(supported) = supported else {
+            return;
+        };
+        let supported = Array::from(&supported);
+        if let Some(mode) = SESSION_MODES
+            .into_iter()
+            .zip(supported.iter())
+            .find_map(|(mode, supported)| supported.is_truthy().then_some(mode))
+        {
+            on_supported(mode);
+        }
+    });
+}
+
+/// Start an immersive session rendered from the WebGL2 context of the canvas. Browsers only grant
+/// sessions requested from user gesture handlers, e.g. of button clicks.
+///
+/// WebGL2 context must be created with the `xrCompatible` context attribute.
+pub fn request_session(
+    mode: XrSessionMode,
+    canvas: &HtmlCanvasElement,
+    on_result: impl FnOnce(Result<WebXrSession, String>) + 'static,
+) {
+    let Some(xr) = xr_system() else {
+        on_result(Err("WebXR is not supported by the browser".to_string()));
+        return;
+    };
+    let canvas = canvas.clone();
+    on_settled(&xr.request_session(mode), move |session| {
+        let result = session
+            .map_err(|e| format!("unable to start WebXR session: {:?}", e))
+            .and_then(|session| {
+                let session: XrSession = session.unchecked_into();
+                let layer = create_layer(&session, &canvas)?;
+                Ok((session, layer))
+            });
+        let (session, layer) = match result {
+            Ok(v) => v,
+            Err(e) => {
+                on_result(Err(e));
+                return;
+            }
+        };
+
+        let reference_space = session.request_reference_space(XrReferenceSpaceType::Local);
+        on_settled(&reference_space, move |reference_space| {
+            let result = reference_space
+                .map(|reference_space| WebXrSession {
+                    session: session.clone(),
+                    layer,
+                    reference_space: reference_space.unchecked_into(),
+                })
+                .map_err(|e| {
+                    let _ = session.end();
+                    format!("unable to create WebXR reference space: {:?}", e)
+                });
+            on_result(result);
+        });
+    });
+}
+
+fn xr_system() -> Option<XrSystem> {
+    let navigator = web_sys::window()?.navigator();
+    // Only exposed in secure contexts of browsers supporting WebXR
+    js_sys::Reflect::get(&navigator, &"xr".into())
+        .ok()?
+        .dyn_into()
+        .ok()
+}
+
+/// WebGL layer of the session drawn into from the context of the canvas.
+fn create_layer(session: &XrSession, canvas: &HtmlCanvasElement) -> Result<XrWebGlLayer, String> {
+    let context: WebGl2RenderingContext = canvas
+        .get_context("webgl2")
+        .map_err(|e| format!("failed to get WebGL2 context: {:?}", e))?
+        .ok_or("'webgl2' context is not available")?
+        .unchecked_into();
+    let layer = XrWebGlLayer::new_with_web_gl2_rendering_context(session, &context)
+        .map_err(|e| format!("unable to create WebXR layer: {:?}", e))?;
+
+    let render_state = XrRenderStateInit::new();
+    render_state.set_base_layer(Some(&layer));
+    render_state.set_depth_near(NEAR_PLANE);
+    render_state.set_depth_far(FAR_PLANE);
+    session.update_render_state_with_state(&render_state);
+    Ok(layer)
+}
+
+/// Call `callback` once the promise is either resolved or rejected.
+fn on_settled(promise: &Promise, callback: impl FnOnce(Result<JsValue, JsValue>) + 'static) {
+    let callback = Rc::new(RefCell::new(Some(callback)));
+    let callback_clone = callback.clone();
+    let on_resolved = Closure::once(move |value: JsValue| {
+        let callback = callback_clone.borrow_mut().take();
+        if let Some(callback) = callback {
+            callback(Ok(value));
+        }
+    });
+    let on_rejected = Closure::once(move |error: JsValue| {
+        let callback = callback.borrow_mut().take();
+        if let Some(callback) = callback {
+            callback(Err(error));
+        }
+    });
+    let _ = promise.then2(&on_resolved, &on_rejected);
+    // Browser calls only one of them, at most once
+    on_resolved.forget();
+    on_rejected.forget();
+}
+
+/// Column-major matrix of WebXR.
+fn matrix_from_slice(values: &[f32]) -> Matrix4<f32> {
+    let mut matrix = Matrix4::identity();
+    AsMut::<[f32; 16]>::as_mut(&mut matrix).copy_from_slice(values);
+    matrix
+}
@@ -0,0 +1,412 @@
+use std::{num::NonZeroU32, sync::Arc};
+
+use cfg_if::cfg_if;
+use cgmath::{Matrix4, Quaternion, SquareMatrix, Vector3};
+use glow::HasContext;
+use glutin::{
+    context::PossiblyCurrentContext,
+    surface::{Surface, WindowSurface},
+};
+use openxr as xr;
+
+use crate::{gl_resources, Camera, DrawProperties, EyeView, Renderer, Scene, Skybox};
+
+const VIEW_CONFIGURATION: xr::ViewConfigurationType = xr::ViewConfigurationType::PRIMARY_STEREO;
+// Same clipping planes as the projection of the window
+const NEAR_PLANE: f32 = 0.1;
+const FAR_PLANE: f32 = 100.0;
+
+/// Stereo rendering of the scene into a VR headset through an OpenXR runtime, next to the window.
+///
+/// Both eyes are rendered with view and projection reported by the runtime into the two halves of
+/// a single swapchain image. The fly camera is the origin of the play space, moving the camera
+/// moves the viewer in the scene.
+pub struct XrSession {
+    gl: Arc<glow::Context>,
+    // Runtime events are polled from the instance
+    instance: xr::Instance,
+    session: xr::Session<xr::OpenGL>,
+    frame_waiter: xr::FrameWaiter,
+    frame_stream: xr::FrameStream<xr::OpenGL>,
+    space: xr::Space,
+    swapchain: xr::Swapchain<xr::OpenGL>,
+    // Framebuffer attached to each image of the swapchain
+    framebuffers: Vec<glow::Framebuffer>,
+    eye_width: u32,
+    eye_height: u32,
+    blend_mode: xr::EnvironmentBlendMode,
+    event_buffer: xr::EventDataBuffer,
+    // Frames are only submitted between session begin and end requested by the runtime
+    running: bool,
+}
+
+impl XrSession {
+    /// Connect to the OpenXR runtime and create a session sharing the current OpenGL context.
+    pub fn new(
+        gl: Arc<glow::Context>,
+        context: &PossiblyCurrentContext,
+        surface: &Surface<WindowSurface>,
+    ) -> Result<Self, String> {
+        let entry = unsafe { xr::Entry::load() }
+            .map_err(|e| format!("cannot load OpenXR loader library: {e}"))?;
+        let available_extensions = entry
+            .enumerate_extensions()
+            .map_err(|e| format!("cannot query OpenXR extensions: {e}"))?;
+        if !available_extensions.khr_opengl_enable {
+            return Err("OpenXR runtime doesn't support OpenGL".to_string());
+        }
+        let mut extensions = xr::ExtensionSet::default();
+        extensions.khr_opengl_enable = true;
+        let instance = entry
+            .create_instance(
+                &xr::ApplicationInfo {
+                    application_name: "3D Renderer in Rust",
+                    application_version: 0,
+                    engine_name: "renderer-rust",
+                    engine_version: 0,
+                },
+                &extensions,
+                &[],
+            )
+            .map_err(|e| format!("cannot create OpenXR instance: {e}"))?;
+        let system = instance
+            .system(xr::FormFactor::HEAD_MOUNTED_DISPLAY)
+            .map_err(|e| format!("no VR headset available: {e}"))?;
+
+        // Runtime requires querying graphics requirements before creating the session
+        let requirements = instance
+            .graphics_requirements::<xr::OpenGL>(system)
+            .map_err(|e| format!("cannot query OpenXR graphics requirements: {e}"))?;
+        println!(
+            "OpenXR runtime requires OpenGL {}.{}",
+            requirements.min_api_version_supported.major(),
+            requirements.min_api_version_supported.minor()
+        );
+        let blend_mode = *instance
+            .enumerate_environment_blend_modes(system, VIEW_CONFIGURATION)
+            .map_err(|e| format!("cannot query OpenXR blend modes: {e}"))?
+            .first()
+            .ok_or("OpenXR runtime has no environment blend mode")?;
+        let view_configurations = instance
+            .enumerate_view_configuration_views(system, VIEW_CONFIGURATION)
+            .map_err(|e| format!("cannot query OpenXR views: {e}"))?;
+        let eye = view_configurations
+            .first()
+            .ok_or("OpenXR runtime has no stereo views")?;
+        let (eye_width, eye_height) = (
+            eye.recommended_image_rect_width,
+            eye.recommended_image_rect_height,
+        );
+
+        let session_create_info = unsafe { session_create_info(context, surface)? };
+        let (session, frame_waiter, frame_stream) =
+            unsafe { instance.create_session::<xr::OpenGL>(system, &session_create_info) }
+                .map_err(|e| format!("cannot create OpenXR session: {e}"))?;
+        let space = session
+            .create_reference_space(xr::ReferenceSpaceType::LOCAL, xr::Posef::IDENTITY)
+            .map_err(|e| format!("cannot create OpenXR reference space: {e}"))?;
+
+        // Renderer output is sRGB encoded, blitting decodes it for linear formats
+        let formats = session
+            .enumerate_swapchain_formats()
+            .map_err(|e| format!("cannot query OpenXR swapchain formats: {e}"))?;
+        let format = [glow::SRGB8_ALPHA8, glow::RGBA8]
+            .into_iter()
+            .find(|format| formats.contains(format))
+            .ok_or("OpenXR runtime doesn't support any RGBA8 swapchain format")?;
+        let swapchain = session
+            .create_swapchain(&xr::SwapchainCreateInfo {
+                create_flags: xr::SwapchainCreateFlags::EMPTY,
+                usage_flags: xr::SwapchainUsageFlags::COLOR_ATTACHMENT
+                    | xr::SwapchainUsageFlags::TRANSFER_DST,
+                format,
+                sample_count: 1,
+                width: eye_width * 2,
+                height: eye_height,
+                face_count: 1,
+                array_size: 1,
+                mip_count: 1,
+            })
+            .map_err(|e| format!("cannot create OpenXR swapchain: {e}"))?;
+        let images = swapchain
+            .enumerate_images()
+            .map_err(|e| format!("cannot query OpenXR swapchain images: {e}"))?;
+        let framebuffers = images
+            .into_iter()
+            .map(|image| swapchain_framebuffer(&gl, image))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            gl,
+            instance,
+            session,
+            frame_waiter,
+            frame_stream,
+            space,
+            swapchain,
+            framebuffers,
+            eye_width,
+            eye_height,
+            blend_mode,
+            event_buffer: xr::EventDataBuffer::new(),
+            running: false,
+        })
+    }
+
+    /// Begin and end the session as requested by the runtime. Returns `false` when the session is
+    /// over, e.g. the headset was disconnected.
+    pub fn poll_events(&mut self) -> Result<bool, String> {
+        while let Some(event) = self
+            .instance
+            .poll_event(&mut self.event_buffer)
+            .map_err(|e| format!("cannot poll OpenXR events: {e}"))?
+        {
+            match event {
+                xr::Event::SessionStateChanged(state_change) => match state_change.state() {
+                    xr::SessionState::READY => {
+                        self.session
+                            .begin(VIEW_CONFIGURATION)
+                            .map_err(|e| format!("cannot begin OpenXR session: {e}"))?;
+                        self.running = true;
+                    }
+                    xr::SessionState::STOPPING => {
+                        self.session
+                            .end()
+                            .map_err(|e| format!("cannot end OpenXR session: {e}"))?;
+                        self.running = false;
+                    }
+                    xr::SessionState::EXITING | xr::SessionState::LOSS_PENDING => {
+                        return Ok(false);
+                    }
+                    _ => (),
+                },
+                xr::Event::InstanceLossPending(_) => return Ok(false),
+                _ => (),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Render and submit a frame to the headset. Waits for the runtime to request the frame, which
+    /// paces the whole application to the refresh rate of the headset.
+    pub fn render(
+        &mut self,
+        renderer: &mut Renderer,
+        camera: &Camera,
+        draw_props: &DrawProperties,
+        scene: &Scene,
+        skybox: &Skybox,
+    ) -> Result<(), String> {
+        if !self.running {
+            return Ok(());
+        }
+
+        let frame_state = self
+            .frame_waiter
+            .wait()
+            .map_err(|e| format!("cannot wait for OpenXR frame: {e}"))?;
+        self.frame_stream
+            .begin()
+            .map_err(|e| format!("cannot begin OpenXR frame: {e}"))?;
+        if !frame_state.should_render {
+            return self
+                .frame_stream
+                .end(frame_state.predicted_display_time, self.blend_mode, &[])
+                .map_err(|e| format!("cannot end OpenXR frame: {e}"));
+        }
+
+        let (_, views) = self
+            .session
+            .locate_views(
+                VIEW_CONFIGURATION,
+                frame_state.predicted_display_time,
+                &self.space,
+            )
+            .map_err(|e| format!("cannot locate OpenXR views: {e}"))?;
+        let image_index = self
+            .swapchain
+            .acquire_image()
+            .map_err(|e| format!("cannot acquire OpenXR swapchain image: {e}"))?;
+        self.swapchain
+            .wait_image(xr::Duration::INFINITE)
+            .map_err(|e| format!("cannot wait for OpenXR swapchain image: {e}"))?;
+
+        let origin = camera.play_space_transform();
+        let framebuffer = self.framebuffers[image_index as usize];
+        for (eye, view) in views.iter().enumerate() {
+            let eye_view = EyeView {
+                view: (origin * pose_matrix(&view.pose)).invert().unwrap(),
+                projection: fov_projection(&view.fov),
+            };
+            renderer.render_eye_to_texture(
+                &eye_view,
+                draw_props,
+                scene,
+                skybox,
+                self.eye_width,
+                self.eye_height,
+            )?;
+            renderer.blit_texture_output(framebuffer, eye as i32 * self.eye_width as i32, 0);
+        }
+
+        self.swapchain
+            .release_image()
+            .map_err(|e| format!("cannot release OpenXR swapchain image: {e}"))?;
+        let eye_rect = |eye: i32| xr::Rect2Di {
+            offset: xr::Offset2Di {
+                x: eye * self.eye_width as i32,
+                y: 0,
+            },
+            extent: xr::Extent2Di {
+                width: self.eye_width as i32,
+                height: self.eye_height as i32,
+            },
+        };
+        let projection_views = [0, 1].map(|eye| {
+            xr::CompositionLayerProjectionView::new()
+                .pose(views[eye].pose)
+                .fov(views[eye].fov)
+                .sub_image(
+                    xr::SwapchainSubImage::new()
+                        .swapchain(&self.swapchain)
+                        .image_array_index(0)
+                        .image_rect(eye_rect(eye as i32)),
+                )
+        });
+        self.frame_stream
+            .end(
+                frame_state.predicted_display_time,
+                self.blend_mode,
+                &[&xr::CompositionLayerProjection::new()
+                    .space(&self.space)
+                    .views(&projection_views)],
+            )
+            .map_err(|e| format!("cannot end OpenXR frame: {e}"))
+    }
+}
+
+impl Drop for XrSession {
+    fn drop(&mut self) {
+        unsafe {
+            for framebuffer in &self.framebuffers {
+                gl_resources::delete_framebuffer(&self.gl, *framebuffer);
+            }
+        }
+    }
+}
+
+/// Handles of the current OpenGL context shared with the OpenXR runtime.
+///
+/// # Safety
+///
+/// Context and surface must outlive the session.
+unsafe fn session_create_info(
+    context: &PossiblyCurrentContext,
+    surface: &Surface<WindowSurface>,
+) -> Result<xr::opengl::SessionCreateInfo, String> {
+    cfg_if! { if #[cfg(target_os = "linux")] {
+        use glutin::{
+            config::{AsRawConfig, GetGlConfig, RawConfig},
+            context::{AsRawContext, RawContext},
+            display::{AsRawDisplay, GetGlDisplay, RawDisplay},
+            platform::x11::X11GlConfigExt,
+            surface::{AsRawSurface, RawSurface},
+        };
+
+        let config = context.config();
+        match (
+            context.display().raw_display(),
+            config.raw_config(),
+            context.raw_context(),
+            surface.raw_surface(),
+        ) {
+            (
+                RawDisplay::Glx(x_display),
+                RawConfig::Glx(glx_fb_config),
+                RawContext::Glx(glx_context),
+                RawSurface::Glx(glx_drawable),
+            ) => Ok(xr::opengl::SessionCreateInfo::Xlib {
+                x_display: x_display as _,
+                visualid: config
+                    .x11_visual()
+                    .map_or(0, |visual| visual.visual_id() as u32),
+                glx_fb_config: glx_fb_config as _,
+                glx_drawable: glx_drawable as _,
+                glx_context: glx_context as _,
+            }),
+            _ => Err("OpenXR requires a GLX context on Linux, EGL is not supported".to_string()),
+        }
+    } else if #[cfg(target_os = "windows")] {
+        use std::ffi::c_void;
+
+        use glutin::{
+            context::{AsRawContext, RawContext},
+            display::{GetGlDisplay, GlDisplay},
+        };
+
+        let _ = surface;
+        let RawContext::Wgl(h_glrc) = context.raw_context() else {
+            return Err("OpenXR requires a WGL context on Windows".to_string());
+        };
+        // Device context of the window is not exposed by glutin, but it's current with the context
+        let get_current_dc = context.display().get_proc_address(c"wglGetCurrentDC");
+        if get_current_dc.is_null() {
+            return Err("cannot look up wglGetCurrentDC()".to_string());
+        }
+        let get_current_dc: unsafe extern "system" fn() -> *mut c_void =
+            std::mem::transmute(get_current_dc);
+        Ok(xr::opengl::SessionCreateInfo::Windows {
+            h_dc: get_current_dc() as _,
+            h_glrc: h_glrc as _,
+        })
+    } else {
+        let _ = (context, surface);
+        Err("OpenXR with OpenGL is only supported on Linux and Windows".to_string())
+    }}
+}
+
+fn swapchain_framebuffer(gl: &glow::Context, image: u32) -> Result<glow::Framebuffer, String> {
+    let texture = NonZeroU32::new(image)
+        .map(glow::NativeTexture)
+        .ok_or("OpenXR swapchain image is not a texture")?;
+    unsafe {
+        let framebuffer = gl_resources::create_framebuffer(gl)
+            .map_err(|e| format!("cannot create OpenXR swapchain framebuffer: {e}"))?;
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+        gl.framebuffer_texture_2d(
+            glow::FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::TEXTURE_2D,
+            Some(texture),
+            0,
+        );
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        Ok(framebuffer)
+    }
+}
+
+fn pose_matrix(pose: &xr::Posef) -> Matrix4<f32> {
+    let orientation = Quaternion::new(
+        pose.orientation.w,
+        pose.orientation.x,
+        pose.orientation.y,
+        pose.orientation.z,
+    );
+    Matrix4::from_translation(Vector3::new(
+        pose.position.x,
+        pose.position.y,
+        pose.position.z,
+    )) * Matrix4::from(orientation)
+}
+
+/// Asymmetric perspective projection of an eye, from angles of the view frustum sides.
+fn fov_projection(fov: &xr::Fovf) -> Matrix4<f32> {
+    cgmath::frustum(
+        fov.angle_left.tan() * NEAR_PLANE,
+        fov.angle_right.tan() * NEAR_PLANE,
+        fov.angle_down.tan() * NEAR_PLANE,
+        fov.angle_up.tan() * NEAR_PLANE,
+        NEAR_PLANE,
+        FAR_PLANE,
+    )
+}